@@ -0,0 +1,133 @@
+//! Benchmarks for the hot paths of the puzzle and renderer.
+//!
+//! The puzzle itself comes from `hyperspeedcube-core`, but the preferences
+//! are only in the Hyperspeedcube binary, so those modules are included
+//! directly from `src/`. Run with `cargo bench`.
+
+#![allow(
+    dead_code,
+    unused_imports,
+    clippy::collapsible_match,
+    clippy::match_like_matches_macro,
+    clippy::single_match,
+    clippy::useless_format
+)]
+
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate strum;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+#[path = "../src/commands.rs"]
+mod commands;
+#[path = "../src/preferences/mod.rs"]
+mod preferences;
+#[path = "../src/serde_impl/mod.rs"]
+mod serde_impl;
+
+use hyperspeedcube_core::{puzzle, util};
+use preferences::{ScrambleOptions, DEFAULT_PREFS};
+use puzzle::*;
+
+/// Number of random twists used by benchmarks that twist the puzzle.
+const TWIST_COUNT: usize = 100;
+
+fn puzzle_types() -> Vec<PuzzleTypeEnum> {
+    let rubiks_3d = [3, 6, 9].map(|layer_count| PuzzleTypeEnum::Rubiks3D { layer_count });
+    let rubiks_4d = [3, 5, 7].map(|layer_count| PuzzleTypeEnum::Rubiks4D { layer_count });
+    rubiks_3d.into_iter().chain(rubiks_4d).collect()
+}
+
+fn random_twists(ty: PuzzleTypeEnum) -> Vec<Twist> {
+    (0..TWIST_COUNT).map(|_| Twist::from_rng(ty)).collect()
+}
+
+fn bench_sticker_geometry(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sticker_geometry");
+    group.sample_size(20);
+    for ty in puzzle_types() {
+        group.bench_function(BenchmarkId::from_parameter(ty), |b| {
+            b.iter_batched(
+                || PuzzleController::new(ty),
+                |mut puzzle| puzzle.geometry(&DEFAULT_PREFS),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_z_sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("z_sort");
+    group.sample_size(20);
+    for ty in puzzle_types() {
+        let mut sorted = (*PuzzleController::new(ty).geometry(&DEFAULT_PREFS)).clone();
+        // Start from the order least like the sorted one.
+        sorted.reverse();
+        group.bench_function(BenchmarkId::from_parameter(ty), |b| {
+            b.iter_batched(
+                || sorted.clone(),
+                |mut geometry| geometry::sort_by_depth(&mut geometry),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_twist(c: &mut Criterion) {
+    let mut group = c.benchmark_group("twist");
+    for ty in puzzle_types() {
+        let twists = random_twists(ty);
+        let solved = Puzzle::new(ty);
+        group.bench_function(BenchmarkId::from_parameter(ty), |b| {
+            b.iter_batched(
+                || solved.clone(),
+                |mut puzzle| {
+                    for &twist in &twists {
+                        puzzle.twist(twist).unwrap();
+                    }
+                    puzzle
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_scramble(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scramble_full");
+    group.sample_size(10);
+    let options = ScrambleOptions::default();
+    for ty in puzzle_types() {
+        ty.load_description();
+        group.bench_function(BenchmarkId::from_parameter(ty), |b| {
+            b.iter(|| PrecomputedScramble::generate(ty, None, &options, |_| ()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_notation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_twists");
+    for ty in puzzle_types() {
+        let string = ty.twists_to_string(&random_twists(ty));
+        group.bench_function(BenchmarkId::from_parameter(ty), |b| {
+            b.iter(|| ty.parse_twists(&string).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_sticker_geometry,
+    bench_z_sort,
+    bench_twist,
+    bench_scramble,
+    bench_notation,
+);
+criterion_main!(benches);
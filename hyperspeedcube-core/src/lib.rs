@@ -0,0 +1,26 @@
+//! Puzzle model, controller, notation, and log file formats of Hyperspeedcube,
+//! without any windowing or graphics dependencies.
+
+#![warn(clippy::if_then_some_else_none, missing_docs)]
+#![allow(
+    clippy::collapsible_match,
+    clippy::match_like_matches_macro,
+    clippy::single_match,
+    clippy::useless_format,
+    missing_docs, // TODO: remove
+)]
+
+#[macro_use]
+extern crate ambassador;
+#[macro_use]
+extern crate enum_dispatch;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate strum;
+
+pub mod logfile;
+pub mod preferences;
+pub mod puzzle;
+pub mod serde_impl;
+pub mod util;
@@ -187,6 +187,7 @@ impl Mc4dLogFile {
                 log::warn!("Error executing twist {e:?} from MC4D log file")
             }
         }
+        ret.clear_twist_times();
         ret.skip_twist_animations();
         ret.mark_saved();
 
@@ -223,10 +224,10 @@ mod tests {
 
     #[test]
     fn test_mc4d_compat() {
-        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 5 };
+        let desc = PuzzleTypeEnum::Rubiks4D { layer_count: 5 }.desc();
 
-        for axis in (0..ty.twist_axes().len() as _).map(TwistAxis) {
-            for direction in (0..ty.twist_directions().len() as _).map(TwistDirection) {
+        for axis in (0..desc.twist_axes().len() as _).map(TwistAxis) {
+            for direction in (0..desc.twist_directions().len() as _).map(TwistDirection) {
                 let twist = Twist {
                     axis,
                     direction,
@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Context, Result};
 use bitvec::vec::BitVec;
+use instant::Duration;
 use num_enum::FromPrimitive;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -11,6 +12,9 @@ use std::str::FromStr;
 use strum::IntoEnumIterator;
 
 mod mc4d_compat;
+mod verify;
+
+pub use verify::{verify, SolveVerification};
 
 use crate::puzzle::*;
 
@@ -29,12 +33,12 @@ pub fn deserialize(log_file_contents: &str) -> anyhow::Result<(PuzzleController,
 }
 
 /// Saves the puzzle state to a log file string.
-pub(crate) fn serialize(
-    puzzle: &PuzzleController,
-    format: LogFileFormat,
-) -> anyhow::Result<String> {
+pub fn serialize(puzzle: &PuzzleController, format: LogFileFormat) -> anyhow::Result<String> {
     match format {
         LogFileFormat::Hsc => Ok(LogFile::new(puzzle).to_string()),
+        LogFileFormat::Mc4d if puzzle.has_custom_state() => Err(anyhow!(
+            "custom puzzle states cannot be saved in MC4D format"
+        )),
         LogFileFormat::Mc4d => Ok(mc4d_compat::Mc4dLogFile::from_puzzle(puzzle)?.to_string()),
     }
 }
@@ -84,6 +88,10 @@ struct LogFile {
     puzzle: Option<PuzzleTypeEnum>,
     #[serde(default)]
     state: u8,
+    /// State code of a custom state that the puzzle started from instead of
+    /// being scrambled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    custom_state: Option<String>,
     #[serde(
         default,
         skip_serializing_if = "Option::is_none",
@@ -98,10 +106,16 @@ struct LogFile {
     scramble_length: usize,
     #[serde(default, skip_deserializing)]
     twist_count: BTreeMap<TwistMetric, usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    splits: Vec<LogPhaseSplit>,
     #[serde(default, skip_serializing)] // manually serialized
     scramble: String,
     #[serde(default, skip_serializing)] // manually serialized
     twists: String,
+    /// Time of each twist in milliseconds since the start of the solve, or
+    /// `-` if unknown.
+    #[serde(default, skip_serializing)] // manually serialized
+    times: String,
 }
 impl fmt::Display for LogFile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -123,6 +137,12 @@ impl fmt::Display for LogFile {
                 writeln!(f, "  {line}")?;
             }
         }
+        if !self.times.is_empty() {
+            writeln!(f, "times: >")?;
+            for line in self.times.lines() {
+                writeln!(f, "  {line}")?;
+            }
+        }
         Ok(())
     }
 }
@@ -137,6 +157,7 @@ impl LogFile {
             version: Self::VERSION,
             puzzle: Some(puzzle.ty()),
             state: puzzle.scramble_state() as u8,
+            custom_state: puzzle.custom_state().map(encode_state),
             visible_pieces: puzzle
                 .is_any_piece_hidden()
                 .then(|| puzzle.visible_pieces().to_bitvec()),
@@ -144,6 +165,15 @@ impl LogFile {
             twist_count: TwistMetric::iter()
                 .map(|metric| (metric, puzzle.twist_count(metric)))
                 .collect(),
+            splits: puzzle
+                .phase_splits()
+                .iter()
+                .map(|split| LogPhaseSplit {
+                    name: split.name.clone(),
+                    twists: split.twist_count,
+                    time: split.time.map(|t| t.as_millis() as u64),
+                })
+                .collect(),
             scramble: crate::util::wrap_words(
                 puzzle.scramble().iter().map(|twist| twist.to_string()),
             ),
@@ -153,6 +183,14 @@ impl LogFile {
                     .iter()
                     .map(|&entry| entry.to_string(notation)),
             ),
+            times: if puzzle.twist_times().iter().any(Option::is_some) {
+                crate::util::wrap_words(puzzle.twist_times().iter().map(|t| match t {
+                    Some(t) => t.as_millis().to_string(),
+                    None => "-".to_string(),
+                }))
+            } else {
+                String::new()
+            },
         }
     }
 
@@ -193,6 +231,22 @@ impl LogFile {
         (ret_twists, ret_errors)
     }
 
+    fn times(&self) -> Result<Vec<Option<Duration>>, TwistParseError<'_>> {
+        self.times
+            .split_whitespace()
+            .map(|time_str| match time_str {
+                "-" => Ok(None),
+                _ => match time_str.parse() {
+                    Ok(ms) => Ok(Some(Duration::from_millis(ms))),
+                    Err(_) => Err(TwistParseError {
+                        twist_str: time_str,
+                        error_msg: "invalid twist time".to_string(),
+                    }),
+                },
+            })
+            .collect()
+    }
+
     fn to_puzzle(&self) -> Result<(PuzzleController, Vec<String>)> {
         self.validate()?;
 
@@ -213,6 +267,11 @@ impl LogFile {
 
         let scramble_state = ScrambleState::from_primitive(self.state);
 
+        if let Some(code) = &self.custom_state {
+            let state = decode_state(code).map_err(|e| anyhow!(e))?;
+            ret.set_custom_state(state).map_err(|e| anyhow!(e))?;
+        }
+
         if let Some(visible_pieces) = &self.visible_pieces {
             ret.set_visible_pieces(visible_pieces);
         }
@@ -226,13 +285,36 @@ impl LogFile {
         }
         ret.add_scramble_marker(scramble_state);
 
-        let (twists, parse_errors) = self.twists(&puzzle_type);
+        let (twists, parse_errors) = self.twists(&*puzzle_type.desc());
         warnings.extend(parse_errors.iter().map(|e| e.to_string()));
         for twist in twists {
             if let Err(e) = ret.twist_no_collapse(twist) {
                 warnings.push(e.to_string());
             }
         }
+        ret.clear_twist_times();
+        if !self.times.is_empty() {
+            match self.times() {
+                Ok(times) => {
+                    if let Err(e) = ret.set_twist_times(times) {
+                        warnings.push(e.to_string());
+                    }
+                }
+                Err(e) => warnings.push(e.to_string()),
+            }
+        }
+        let splits = self
+            .splits
+            .iter()
+            .map(|split| PhaseSplit {
+                name: split.name.clone(),
+                twist_count: split.twists,
+                time: split.time.map(Duration::from_millis),
+            })
+            .collect();
+        if let Err(e) = ret.set_phase_splits(splits) {
+            warnings.push(e.to_string());
+        }
         ret.skip_twist_animations();
         ret.mark_saved();
 
@@ -240,6 +322,16 @@ impl LogFile {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct LogPhaseSplit {
+    name: String,
+    /// Number of twists in the solve when the phase was completed.
+    twists: usize,
+    /// Time in milliseconds since the start of the solve.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time: Option<u64>,
+}
+
 #[derive(Debug)]
 struct TwistParseError<'a> {
     twist_str: &'a str,
@@ -255,3 +347,108 @@ impl fmt::Display for TwistParseError<'_> {
     }
 }
 impl Error for TwistParseError<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    use super::*;
+
+    /// Returns a valid log file for a scrambled puzzle with a few twists.
+    fn valid_log_file() -> String {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle
+            .scramble_with(&ty.parse_twists("R U F").unwrap(), ScrambleState::Partial)
+            .unwrap();
+        for twist in ty.parse_twists("F' U' R'").unwrap() {
+            puzzle.twist(twist).unwrap();
+        }
+        serialize(&puzzle, LogFileFormat::Hsc).unwrap()
+    }
+
+    #[test]
+    fn test_custom_state_log_file() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+        let mut scrambled = PuzzleController::new(ty);
+        let scramble = (0..5).map(|_| Twist::from_rng(ty)).collect::<Vec<_>>();
+        scrambled
+            .scramble_with(&scramble, ScrambleState::Partial)
+            .unwrap();
+        let custom_state = scrambled.latest().clone();
+
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle.set_custom_state(custom_state.clone()).unwrap();
+        puzzle.twist(Twist::from_rng(ty)).unwrap();
+        assert!(serialize(&puzzle, LogFileFormat::Mc4d).is_err());
+
+        let (loaded, warnings) =
+            deserialize(&serialize(&puzzle, LogFileFormat::Hsc).unwrap()).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(loaded.custom_state(), Some(&custom_state));
+        assert_eq!(loaded.latest(), puzzle.latest());
+        assert_eq!(loaded.undo_buffer().len(), 1);
+    }
+
+    #[test]
+    fn test_malformed_log_file_corpus() {
+        let valid = valid_log_file();
+        let (puzzle, warnings) = deserialize(&valid).unwrap();
+        assert!(puzzle.is_solved());
+        assert!(warnings.is_empty());
+
+        let unscrambled = serialize(
+            &PuzzleController::new(PuzzleTypeEnum::default()),
+            LogFileFormat::Hsc,
+        )
+        .unwrap();
+
+        let corpus = [
+            String::new(),
+            "\n".to_string(),
+            "version: x".to_string(),
+            "[1, 2".to_string(),
+            valid.replace("layer_count: 3", "layer_count: 0"),
+            valid.replace("layer_count: 3", "layer_count: 200"),
+            valid.replace("Rubiks3D", "Rubiks9D"),
+            format!("{unscrambled}scramble: 255,255,255 0,0,0 0,0,99999999999\n"),
+            format!("{unscrambled}twists: R U Q 0,0,0\ntimes: 1 2 x\n"),
+            format!("{unscrambled}twists: R U\ntimes: 1 2 3 4 5\n"),
+            format!("{unscrambled}splits:\n- name: x\n  twists: 99\n"),
+            "MagicCube4D".to_string(),
+            "MagicCube4D 3 1 0 {4,3,3} 0".to_string(),
+            "MagicCube4D 3 1 0 {4,3,3} 3\n1 0 0 0\n0 1 0\n".to_string(),
+            "MagicCube4D 3 1 0 {4,3,3} 3\n\
+             1 0 0 0\n0 1 0 0\n0 0 1 0\n0 0 0 1\n*\n\
+             9999,1,1 12,7,1 12,1,999 m| 12,1,1.\n"
+                .to_string(),
+        ];
+        for log_file in corpus {
+            // Any result is fine, as long as it doesn't panic.
+            let _ = deserialize(&log_file);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_log_file() {
+        let valid = valid_log_file();
+        let mut rng = rand::thread_rng();
+        for _ in 0..500 {
+            let mut chars: Vec<char> = valid.chars().collect();
+            for _ in 0..rng.gen_range(1..=4) {
+                let i = rng.gen_range(0..chars.len());
+                match rng.gen_range(0..3) {
+                    0 => chars[i] = *b"0123456789-,: \nRUF'!".choose(&mut rng).unwrap() as char,
+                    1 => {
+                        chars.remove(i);
+                    }
+                    _ => chars.insert(i, chars[i]),
+                }
+            }
+            let log_file: String = chars.into_iter().collect();
+            // Any result is fine, as long as it doesn't panic.
+            let _ = deserialize(&log_file);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use strum::IntoEnumIterator;
+
+use crate::preferences::ScrambleOptions;
+use crate::puzzle::*;
+
+/// Result of checking whether a log file contains a valid solve.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SolveVerification {
+    /// Whether the log file contains a complete solve from a full-length
+    /// scramble. Unless `scramble_verified` is also `true`, the scramble may
+    /// have been chosen by the solver.
+    pub valid: bool,
+    /// Reasons that the solve is not valid.
+    pub errors: Vec<String>,
+
+    /// Name of the puzzle type, such as "3x3x3x3".
+    pub puzzle: Option<String>,
+    /// Number of twists in the scramble.
+    pub scramble_length: usize,
+    /// Whether the scramble was checked against the one generated from its
+    /// seed. A scramble cannot be verified without its seed.
+    pub scramble_verified: bool,
+    /// Number of twists in the solve, in each twist metric.
+    pub twist_counts: BTreeMap<TwistMetric, usize>,
+    /// Duration of the solve in milliseconds, if the log file records twist
+    /// times.
+    pub duration_ms: Option<u64>,
+}
+
+/// Loads a log file string and checks that it contains a complete solve from
+/// a full scramble.
+///
+/// The twists are replayed from the scramble, so the result does not depend
+/// on the scramble state recorded in the log file. If `scramble_seed` is
+/// given, the scramble must be the one generated from that seed; otherwise
+/// the scramble is reported as unverified, because any sequence of twists
+/// with the right length passes the other checks.
+pub fn verify(log_file_contents: &str, scramble_seed: Option<u64>) -> SolveVerification {
+    let (puzzle, warnings) = match super::deserialize(log_file_contents) {
+        Ok(ok) => ok,
+        Err(e) => {
+            return SolveVerification {
+                errors: vec![format!("Error loading log file: {e}")],
+                ..Default::default()
+            }
+        }
+    };
+    let ty = puzzle.ty();
+
+    let mut errors = warnings;
+    if puzzle.has_custom_state() {
+        errors.push("Puzzle started from a custom state".to_string());
+    }
+    let scramble_length = puzzle.scramble().len();
+    if scramble_length < ty.scramble_moves_count() {
+        errors.push(format!(
+            "Scramble has {scramble_length} twists but a full scramble has {}",
+            ty.scramble_moves_count(),
+        ));
+    }
+    let mut scrambled = PuzzleController::new(ty);
+    match scrambled.scramble_with(puzzle.scramble(), ScrambleState::Full) {
+        Ok(()) if scrambled.is_solved() => errors.push("Scramble is solved".to_string()),
+        Ok(()) => (),
+        Err(e) => errors.push(format!("Invalid scramble: {e}")),
+    }
+    let mut scramble_verified = false;
+    if let Some(seed) = scramble_seed {
+        let n = ty.scramble_moves_count();
+        match scrambled.generate_seeded_scramble(n, &ScrambleOptions::default(), seed) {
+            Ok(expected) if expected == puzzle.scramble() => scramble_verified = true,
+            Ok(_) => errors.push("Scramble does not match its seed".to_string()),
+            Err(e) => errors.push(format!("Unable to generate scramble from seed: {e}")),
+        }
+    }
+    if !puzzle.is_solved() {
+        errors.push("Puzzle is not solved".to_string());
+    }
+
+    SolveVerification {
+        valid: errors.is_empty(),
+        errors,
+
+        puzzle: Some(ty.name().to_string()),
+        scramble_length,
+        scramble_verified,
+        twist_counts: TwistMetric::iter()
+            .map(|metric| (metric, puzzle.twist_count(metric)))
+            .collect(),
+        duration_ms: puzzle
+            .twist_times()
+            .iter()
+            .rev()
+            .find_map(|&t| t)
+            .map(|t| t.as_millis() as u64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{serialize, LogFileFormat};
+    use super::*;
+
+    /// Solves a puzzle by undoing the scramble.
+    fn solve(puzzle: &mut PuzzleController) {
+        let ty = puzzle.ty();
+        for &twist in puzzle.scramble().to_vec().iter().rev() {
+            puzzle.twist(ty.reverse_twist(twist)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_verify_solve() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle.scramble_full(&ScrambleOptions::default()).unwrap();
+        let scramble_length = puzzle.scramble().len();
+
+        let unsolved = verify(&serialize(&puzzle, LogFileFormat::Hsc).unwrap(), None);
+        assert!(!unsolved.valid);
+        assert_eq!(unsolved.errors, ["Puzzle is not solved"]);
+
+        solve(&mut puzzle);
+        let solved = verify(&serialize(&puzzle, LogFileFormat::Hsc).unwrap(), None);
+        assert!(solved.valid, "{:?}", solved.errors);
+        assert_eq!(solved.puzzle.as_deref(), Some("3x3x3x3"));
+        assert_eq!(solved.scramble_length, scramble_length);
+        assert!(!solved.scramble_verified);
+        assert_eq!(solved.twist_counts[&TwistMetric::Etm], scramble_length);
+    }
+
+    #[test]
+    fn test_verify_partial_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle.scramble_n(3, &ScrambleOptions::default()).unwrap();
+        solve(&mut puzzle);
+        let result = verify(&serialize(&puzzle, LogFileFormat::Hsc).unwrap(), None);
+        assert!(!result.valid);
+        assert_eq!(
+            result.errors,
+            [format!(
+                "Scramble has 3 twists but a full scramble has {}",
+                ty.scramble_moves_count(),
+            )],
+        );
+
+        // The recorded scramble state is ignored; only the twists matter.
+        let mut puzzle = PuzzleController::new(ty);
+        let n = ty.scramble_moves_count();
+        let scramble = puzzle
+            .generate_scramble(n, &ScrambleOptions::default())
+            .unwrap();
+        puzzle
+            .scramble_with(&scramble, ScrambleState::Partial)
+            .unwrap();
+        solve(&mut puzzle);
+        let result = verify(&serialize(&puzzle, LogFileFormat::Hsc).unwrap(), None);
+        assert!(result.valid, "{:?}", result.errors);
+
+        let result = verify("not a log file", None);
+        assert!(!result.valid);
+        assert_eq!(result.puzzle, None);
+    }
+
+    #[test]
+    fn test_verify_seeded_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        let n = ty.scramble_moves_count();
+        let scramble = puzzle
+            .generate_seeded_scramble(n, &ScrambleOptions::default(), 42)
+            .unwrap();
+        puzzle
+            .scramble_with(&scramble, ScrambleState::Full)
+            .unwrap();
+        solve(&mut puzzle);
+        let log = serialize(&puzzle, LogFileFormat::Hsc).unwrap();
+
+        let result = verify(&log, Some(42));
+        assert!(result.valid, "{:?}", result.errors);
+        assert!(result.scramble_verified);
+
+        // A scramble of the right length that was not generated from the
+        // seed is rejected.
+        let result = verify(&log, Some(43));
+        assert!(!result.valid);
+        assert!(!result.scramble_verified);
+        assert_eq!(result.errors, ["Scramble does not match its seed"]);
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct InteractionPreferences {
+    pub confirm_discard_only_when_scrambled: bool,
+    /// Whether to save the puzzle and open windows on exit and restore them on
+    /// the next launch.
+    pub restore_session: bool,
+    /// Whether switching puzzles keeps the previous puzzle in the background
+    /// instead of discarding it.
+    pub keep_background_puzzle: bool,
+    /// Whether switching to another puzzle type restores where that puzzle
+    /// was left off.
+    pub remember_puzzle_states: bool,
+
+    /// Whether to pause the solve clock when there has been no input for
+    /// `auto_pause_delay` seconds.
+    pub auto_pause: bool,
+    /// Number of seconds without input before the solve clock is paused.
+    pub auto_pause_delay: f32,
+    /// Whether to pause the solve clock when the window loses focus.
+    pub pause_on_focus_loss: bool,
+    /// What to do with twist animations when the window loses focus.
+    pub focus_loss_animation: FocusLossAnimation,
+
+    pub drag_sensitivity: f32,
+    pub realign_on_release: bool,
+    pub realign_on_keypress: bool,
+    pub smart_realign: bool,
+
+    /// Speed of view rotation using the keyboard, in degrees per second.
+    pub view_rotation_speed: f32,
+    /// Whether keyboard view rotation accelerates and decelerates smoothly.
+    pub smooth_view_rotation: bool,
+
+    /// What to do when a twist key is held down.
+    pub key_repeat: KeyRepeatMode,
+    /// Delay before a held twist key starts repeating, in seconds.
+    pub key_repeat_delay: f32,
+    /// Number of twists per second while a held twist key repeats.
+    pub key_repeat_rate: f32,
+
+    pub dynamic_twist_speed: bool,
+    /// How much faster each queued twist is animated when
+    /// `dynamic_twist_speed` is enabled. Higher values catch up with the queue
+    /// exponentially faster.
+    pub twist_speedup_factor: f32,
+    /// Twists shorter than this many seconds are completed instantly instead
+    /// of being animated.
+    pub min_twist_duration: f32,
+    pub twist_duration: f32,
+    /// Fraction of `twist_duration` added for each layer beyond the first.
+    pub extra_layer_twist_duration: f32,
+    /// Multiplier of `twist_duration` for whole-puzzle rotations.
+    pub rotation_duration_scale: f32,
+    pub other_anim_duration: f32,
+}
+
+/// Named settings for how quickly queued twists catch up.
+#[derive(Debug, Display, EnumIter, Copy, Clone, PartialEq, Eq)]
+pub enum TwistQueuePreset {
+    /// Queued twists speed up gradually, so every twist stays visible.
+    Smooth,
+    /// Queued twists speed up quickly.
+    Snappy,
+    /// Queued twists are completed almost immediately.
+    Instant,
+}
+impl TwistQueuePreset {
+    /// Returns the speed-up factor and minimum twist duration of the preset.
+    fn values(self) -> (f32, f32) {
+        match self {
+            TwistQueuePreset::Smooth => (0.25, 0.02),
+            TwistQueuePreset::Snappy => (0.5, 0.05),
+            TwistQueuePreset::Instant => (3.0, 0.1),
+        }
+    }
+    /// Applies the preset to interaction preferences.
+    pub fn apply(self, prefs: &mut InteractionPreferences) {
+        (prefs.twist_speedup_factor, prefs.min_twist_duration) = self.values();
+    }
+    /// Returns the preset that matches interaction preferences, if any.
+    pub fn from_prefs(prefs: &InteractionPreferences) -> Option<Self> {
+        Self::iter().find(|preset| {
+            preset.values() == (prefs.twist_speedup_factor, prefs.min_twist_duration)
+        })
+    }
+}
+
+/// Behavior of twist animations when the window loses focus.
+#[derive(Serialize, Deserialize, Debug, Display, EnumIter, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusLossAnimation {
+    /// Keep animating twists in the background.
+    #[default]
+    #[strum(serialize = "Continue")]
+    Continue,
+    /// Complete all queued twists instantly.
+    #[strum(serialize = "Catch up instantly")]
+    CatchUp,
+    /// Pause twist animations until the window is focused again.
+    #[strum(serialize = "Pause")]
+    Pause,
+}
+
+/// Behavior of twist keys that are held down.
+#[derive(Serialize, Deserialize, Debug, Display, EnumIter, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRepeatMode {
+    /// Twist once per keypress, ignoring the OS key repeat.
+    #[default]
+    #[strum(serialize = "Ignore repeats")]
+    Ignore,
+    /// Repeat the twist after a delay at a fixed rate, ignoring the OS key
+    /// repeat.
+    #[strum(serialize = "Repeat after delay")]
+    Delayed,
+    /// Twist again on every key repeat event from the OS.
+    #[strum(serialize = "Use OS key repeat")]
+    Os,
+}
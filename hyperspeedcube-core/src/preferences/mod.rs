@@ -0,0 +1,30 @@
+//! Preferences that affect the puzzle controller and sticker geometry.
+//!
+//! The rest of the preferences belong to the frontend, which exposes these to
+//! the controller by implementing [`PuzzlePreferences`].
+
+use std::collections::BTreeMap;
+
+use crate::puzzle::PuzzleTypeEnum;
+
+mod interaction;
+mod opacity;
+mod scramble;
+mod view;
+
+pub use interaction::*;
+pub use opacity::*;
+pub use scramble::*;
+pub use view::*;
+
+/// Preferences read by the puzzle controller.
+pub trait PuzzlePreferences {
+    /// Returns the view settings for a puzzle type.
+    fn view(&self, ty: PuzzleTypeEnum) -> &ViewPreferences;
+    /// Returns the spacing overrides for a puzzle type, keyed by face symbol.
+    fn spacing_overrides(&self, ty: PuzzleTypeEnum) -> &BTreeMap<String, SpacingOverride>;
+    /// Returns the interaction preferences.
+    fn interaction(&self) -> &InteractionPreferences;
+    /// Returns the opacity preferences.
+    fn opacity(&self) -> &OpacityPreferences;
+}
@@ -10,5 +10,8 @@ pub struct OpacityPreferences {
 
     pub unhide_grip: bool,
 
+    pub show_ghost: bool,
+    pub ghost: f32,
+
     pub save_opacity_in_piece_filter_preset: bool,
 }
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Constraints on the random twists used to scramble a puzzle, for practicing
+/// a subset of the puzzle.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ScrambleOptions {
+    /// Only twist the outermost layer of each axis.
+    pub outer_layers_only: bool,
+    /// Names of twist axes that are never twisted.
+    pub excluded_axes: Vec<String>,
+    /// Only scramble corners, leaving every other piece solved.
+    pub corners_only: bool,
+}
+impl ScrambleOptions {
+    /// Returns whether any constraints are enabled. Constrained scrambles never
+    /// count as full scrambles.
+    pub fn is_restricted(&self) -> bool {
+        *self != Self::default()
+    }
+}
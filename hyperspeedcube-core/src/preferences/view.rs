@@ -0,0 +1,351 @@
+use cgmath::{Deg, InnerSpace, Matrix4, Quaternion, Rotation3};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+/// Minimum global puzzle scale.
+pub const MIN_SCALE: f32 = 0.1;
+/// Maximum global puzzle scale.
+pub const MAX_SCALE: f32 = 5.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ViewPreferences {
+    /// Puzzle angle around X axis, in degrees.
+    pub pitch: f32,
+    /// Puzzle angle around Y axis, in degrees.
+    pub yaw: f32,
+    /// Puzzle angle around Z axis, in degrees.
+    pub roll: f32,
+
+    /// 4D puzzle angle in the XW plane, in degrees.
+    pub angle_xw: f32,
+    /// 4D puzzle angle in the YW plane, in degrees.
+    pub angle_yw: f32,
+    /// 4D puzzle angle in the ZW plane, in degrees.
+    pub angle_zw: f32,
+
+    /// Global puzzle scale.
+    pub scale: f32,
+    /// 3D FOV, in degrees (may be negative).
+    pub fov_3d: f32,
+    /// 4D FOV, in degrees.
+    pub fov_4d: f32,
+    /// Scale of the part of the puzzle nearest the 4D camera (usually the
+    /// outer cell).
+    pub near_scale_4d: f32,
+    /// Scale of the part of the puzzle farthest from the 4D camera (usually
+    /// the inner cell).
+    pub far_scale_4d: f32,
+    /// Distance to move the 4D camera back along the W axis, relative to the
+    /// size of the puzzle.
+    pub w_offset_4d: f32,
+    /// Minimum 4D perspective divisor of geometry that is not clipped when
+    /// `clip_4d` is enabled. Lower values show more of the geometry near the
+    /// 4D camera.
+    pub clip_threshold_4d: f32,
+    /// How much to fade geometry farther from the 4D camera, from 0.0 (no
+    /// fading) to 1.0 (farthest geometry is fully transparent).
+    pub w_fade_4d: f32,
+
+    /// Horizontal alignment, from -1.0 to +1.0.
+    pub align_h: f32,
+    /// Vertical alignment, from -1.0 to +1.0.
+    pub align_v: f32,
+
+    pub show_frontfaces: bool,
+    pub show_backfaces: bool,
+    /// Whether to hide 4D stickers that are turned inside-out by the 4D
+    /// projection, which are facing away from the 4D camera.
+    pub cull_4d: bool,
+    pub clip_4d: bool,
+    /// Whether to place the 3D camera at the center of the puzzle, looking
+    /// outward.
+    pub interior_view: bool,
+
+    pub face_spacing: f32,
+    pub sticker_spacing: f32,
+
+    pub outline_thickness: f32,
+
+    pub light_ambient: f32,
+    pub light_directional: f32,
+    pub light_pitch: f32,
+    pub light_yaw: f32,
+}
+impl Default for ViewPreferences {
+    fn default() -> Self {
+        Self {
+            pitch: 0_f32,
+            yaw: 0_f32,
+            roll: 0_f32,
+
+            angle_xw: 0_f32,
+            angle_yw: 0_f32,
+            angle_zw: 0_f32,
+
+            scale: 1.0,
+            fov_3d: 30_f32,
+            fov_4d: 30_f32,
+            near_scale_4d: 1.0,
+            far_scale_4d: 1.0,
+            w_offset_4d: 0.0,
+            clip_threshold_4d: 0.1,
+            w_fade_4d: 0.0,
+
+            align_h: 0.0,
+            align_v: 0.0,
+
+            face_spacing: 0.0,
+            sticker_spacing: 0.0,
+
+            show_frontfaces: true,
+            show_backfaces: true,
+            cull_4d: true,
+            clip_4d: true,
+            interior_view: false,
+
+            outline_thickness: 1.0,
+
+            light_ambient: 1.0,
+            light_directional: 0.0,
+            light_pitch: 0.0,
+            light_yaw: 0.0,
+        }
+    }
+}
+
+impl ViewPreferences {
+    pub fn view_angle(&self) -> Quaternion<f32> {
+        Quaternion::from_angle_z(Deg(self.roll))
+            * Quaternion::from_angle_x(Deg(self.pitch))
+            * Quaternion::from_angle_y(Deg(self.yaw))
+    }
+    /// Returns the 4D rotation applied to the puzzle before 4D projection.
+    pub fn view_angle_4d(&self) -> Matrix4<f32> {
+        use crate::util::rotation_in_plane;
+
+        const X: usize = 0;
+        const Y: usize = 1;
+        const Z: usize = 2;
+        const W: usize = 3;
+        rotation_in_plane([Z, W], self.angle_zw)
+            * rotation_in_plane([Y, W], self.angle_yw)
+            * rotation_in_plane([X, W], self.angle_xw)
+    }
+
+    /// Returns the view settings with the pitch, yaw, and roll changed to the
+    /// nearest orientation in which the puzzle axes are aligned with the
+    /// screen, changing each angle as little as possible.
+    #[must_use]
+    pub fn snapped_to_axes(&self) -> Self {
+        let current = self.view_angle();
+        // Consider multiples of 90 degrees within 180 degrees of each angle.
+        let candidates = |angle: f32| {
+            let nearest = (angle / 90.0).round() as i32;
+            (nearest - 2..=nearest + 2).map(|i| i as f32 * 90.0)
+        };
+        let (_, _, pitch, yaw, roll) = candidates(self.pitch)
+            .cartesian_product(candidates(self.yaw).collect_vec())
+            .cartesian_product(candidates(self.roll).collect_vec())
+            .map(|((pitch, yaw), roll)| {
+                let candidate = Self {
+                    pitch,
+                    yaw,
+                    roll,
+                    ..self.clone()
+                };
+                // `q` and `-q` represent the same rotation. Round so that
+                // equivalent orientations compare equal.
+                let similarity = (current.dot(candidate.view_angle()).abs() * 1000.0).round();
+                let distance =
+                    (pitch - self.pitch).abs() + (yaw - self.yaw).abs() + (roll - self.roll).abs();
+                (similarity, -distance, pitch, yaw, roll)
+            })
+            .max_by(|a, b| (a.0, a.1).partial_cmp(&(b.0, b.1)).unwrap())
+            .expect("no candidate orientations");
+        Self {
+            pitch,
+            yaw,
+            roll,
+            ..self.clone()
+        }
+    }
+
+    // TODO: make a proc macro crate to generate a trait impl like this
+    pub fn interpolate(&self, rhs: &Self, t: f32) -> Self {
+        Self {
+            // I know, I know, I should use quaternions for interpolation. But
+            // cgmath uses XYZ order by default instead of YXZ so doing this
+            // properly isn't trivial.
+            pitch: crate::util::mix(self.pitch, rhs.pitch, t),
+            yaw: crate::util::mix(self.yaw, rhs.yaw, t),
+            roll: crate::util::mix(self.roll, rhs.roll, t),
+
+            angle_xw: crate::util::mix(self.angle_xw, rhs.angle_xw, t),
+            angle_yw: crate::util::mix(self.angle_yw, rhs.angle_yw, t),
+            angle_zw: crate::util::mix(self.angle_zw, rhs.angle_zw, t),
+
+            scale: crate::util::mix(self.scale, rhs.scale, t),
+            fov_3d: crate::util::mix(self.fov_3d, rhs.fov_3d, t),
+            fov_4d: crate::util::mix(self.fov_4d, rhs.fov_4d, t),
+            near_scale_4d: crate::util::mix(self.near_scale_4d, rhs.near_scale_4d, t),
+            far_scale_4d: crate::util::mix(self.far_scale_4d, rhs.far_scale_4d, t),
+            w_offset_4d: crate::util::mix(self.w_offset_4d, rhs.w_offset_4d, t),
+            clip_threshold_4d: crate::util::mix(self.clip_threshold_4d, rhs.clip_threshold_4d, t),
+            w_fade_4d: crate::util::mix(self.w_fade_4d, rhs.w_fade_4d, t),
+            align_h: crate::util::mix(self.align_h, rhs.align_h, t),
+            align_v: crate::util::mix(self.align_v, rhs.align_v, t),
+            show_frontfaces: if t < 0.5 {
+                self.show_frontfaces
+            } else {
+                rhs.show_frontfaces
+            },
+            show_backfaces: if t < 0.5 {
+                self.show_backfaces
+            } else {
+                rhs.show_backfaces
+            },
+            cull_4d: if t < 0.5 { self.cull_4d } else { rhs.cull_4d },
+            clip_4d: if t < 0.5 { self.clip_4d } else { rhs.clip_4d },
+            interior_view: if t < 0.5 {
+                self.interior_view
+            } else {
+                rhs.interior_view
+            },
+            face_spacing: crate::util::mix(self.face_spacing, rhs.face_spacing, t),
+            sticker_spacing: crate::util::mix(self.sticker_spacing, rhs.sticker_spacing, t),
+            outline_thickness: crate::util::mix(self.outline_thickness, rhs.outline_thickness, t),
+            light_ambient: crate::util::mix(self.light_ambient, rhs.light_ambient, t),
+            light_directional: crate::util::mix(self.light_directional, rhs.light_directional, t),
+            light_pitch: crate::util::mix(self.light_pitch, rhs.light_pitch, t),
+            light_yaw: crate::util::mix(self.light_yaw, rhs.light_yaw, t),
+        }
+    }
+}
+
+/// Standard orientation of a 4D puzzle, named after the part of the puzzle
+/// that faces the 4D camera.
+#[derive(Debug, Display, EnumIter, Copy, Clone, PartialEq, Eq)]
+pub enum Projection4D {
+    #[strum(serialize = "Cell-first")]
+    CellFirst,
+    #[strum(serialize = "Face-first")]
+    FaceFirst,
+    #[strum(serialize = "Edge-first")]
+    EdgeFirst,
+    #[strum(serialize = "Vertex-first")]
+    VertexFirst,
+}
+impl Projection4D {
+    /// Returns the 4D angles in the XW, YW, and ZW planes, in degrees.
+    pub fn angles(self) -> [f32; 3] {
+        // Each step rotates the W axis to point halfway between the current
+        // part and an adjacent cell, so that the camera faces the center of
+        // a face, then an edge, then a vertex.
+        let edge_angle = (1.0 / 2.0_f32.sqrt()).atan().to_degrees();
+        let vertex_angle = (1.0 / 3.0_f32.sqrt()).atan().to_degrees();
+        match self {
+            Projection4D::CellFirst => [0.0, 0.0, 0.0],
+            Projection4D::FaceFirst => [45.0, 0.0, 0.0],
+            Projection4D::EdgeFirst => [45.0, edge_angle, 0.0],
+            Projection4D::VertexFirst => [45.0, edge_angle, vertex_angle],
+        }
+    }
+    /// Returns the 4D FOV, in degrees.
+    pub fn fov_4d(self) -> f32 {
+        match self {
+            Projection4D::CellFirst => 30.0,
+            Projection4D::FaceFirst => 40.0,
+            Projection4D::EdgeFirst => 45.0,
+            Projection4D::VertexFirst => 50.0,
+        }
+    }
+
+    /// Applies the 4D angles and FOV to view settings.
+    pub fn apply(self, view_prefs: &mut ViewPreferences) {
+        [
+            view_prefs.angle_xw,
+            view_prefs.angle_yw,
+            view_prefs.angle_zw,
+        ] = self.angles();
+        view_prefs.fov_4d = self.fov_4d();
+    }
+    /// Returns the projection matching the 4D angles of view settings, if
+    /// there is one.
+    pub fn from_view_prefs(view_prefs: &ViewPreferences) -> Option<Self> {
+        let angles = [
+            view_prefs.angle_xw,
+            view_prefs.angle_yw,
+            view_prefs.angle_zw,
+        ];
+        Self::iter().find(|p| {
+            let expected = p.angles();
+            (0..3).all(|i| (angles[i] - expected[i]).abs() < 0.01)
+        })
+    }
+}
+
+/// Preset for the 4D perspective, which controls the relative sizes of the
+/// cells nearest and farthest from the 4D camera.
+#[derive(Debug, Display, EnumIter, Copy, Clone, PartialEq)]
+pub enum Perspective4D {
+    #[strum(serialize = "Default")]
+    Default,
+    /// Makes the inner cell larger so that it is easier to see.
+    #[strum(serialize = "Large inner cell")]
+    LargeInnerCell,
+    /// Moves the outer cell toward the camera so that the cells around it are
+    /// unfolded further.
+    #[strum(serialize = "Unfolded outer cell")]
+    UnfoldedOuterCell,
+    /// Moves the 4D camera far away so that the cells are closer to the same
+    /// size.
+    #[strum(serialize = "Flat")]
+    Flat,
+}
+impl Perspective4D {
+    /// Returns the near scale, far scale, W offset, and clipping threshold.
+    /// The 4D FOV is left to the 4D projection.
+    fn values(self) -> [f32; 4] {
+        match self {
+            Perspective4D::Default => [1.0, 1.0, 0.0, 0.1],
+            Perspective4D::LargeInnerCell => [0.8, 1.6, 0.0, 0.1],
+            Perspective4D::UnfoldedOuterCell => [1.5, 1.0, -0.25, 0.02],
+            Perspective4D::Flat => [1.0, 1.0, 4.0, 0.1],
+        }
+    }
+
+    /// Applies the 4D perspective settings to view settings.
+    pub fn apply(self, view_prefs: &mut ViewPreferences) {
+        [
+            view_prefs.near_scale_4d,
+            view_prefs.far_scale_4d,
+            view_prefs.w_offset_4d,
+            view_prefs.clip_threshold_4d,
+        ] = self.values();
+    }
+    /// Returns the preset matching the 4D perspective settings of view
+    /// settings, if there is one.
+    pub fn from_view_prefs(view_prefs: &ViewPreferences) -> Option<Self> {
+        let values = [
+            view_prefs.near_scale_4d,
+            view_prefs.far_scale_4d,
+            view_prefs.w_offset_4d,
+            view_prefs.clip_threshold_4d,
+        ];
+        Self::iter().find(|p| {
+            let expected = p.values();
+            (0..4).all(|i| (values[i] - expected[i]).abs() < 0.001)
+        })
+    }
+}
+
+/// Face spacing and sticker spacing for a single face, overriding the view
+/// settings.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub struct SpacingOverride {
+    pub face_spacing: f32,
+    pub sticker_spacing: f32,
+}
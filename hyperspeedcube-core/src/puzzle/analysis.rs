@@ -0,0 +1,222 @@
+//! Analysis of the effect of a twist sequence on a puzzle.
+
+use std::cmp::Reverse;
+use std::fmt;
+
+use super::*;
+
+/// Effect of a twist sequence on the solved puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceAnalysis {
+    /// Cycles of pieces that are moved or reoriented, longest first.
+    pub cycles: Vec<PieceCycle>,
+    /// Number of times the sequence must be repeated to return the puzzle to
+    /// its original state, or `None` if it is too large to compute.
+    pub order: Option<u64>,
+}
+impl SequenceAnalysis {
+    /// Applies a twist sequence to a solved puzzle and analyzes the result.
+    pub fn new(ty: PuzzleTypeEnum, twists: &[Twist]) -> Result<Self, &'static str> {
+        let mut puzzle = Puzzle::new(ty);
+        for &twist in twists {
+            puzzle.twist(twist)?;
+        }
+        Ok(Self::from_sticker_permutation(
+            ty,
+            puzzle.permutation().as_slice(),
+        ))
+    }
+
+    fn from_sticker_permutation(ty: PuzzleTypeEnum, perm: &[Sticker]) -> Self {
+        let desc = ty.desc();
+        let mut cycles = vec![];
+        let mut order = Some(1);
+        let mut seen = vec![false; desc.pieces().len()];
+
+        for start in (0..desc.pieces().len() as _).map(Piece) {
+            if seen[start.0 as usize] {
+                continue;
+            }
+
+            // Follow the piece until it returns to its original location.
+            let mut pieces = vec![];
+            let mut piece = start;
+            loop {
+                seen[piece.0 as usize] = true;
+                pieces.push(piece);
+                piece = desc
+                    .info(perm[desc.info(piece).stickers[0].0 as usize])
+                    .piece;
+                if piece == start {
+                    break;
+                }
+            }
+
+            // After going around the cycle, the stickers of the piece may be
+            // permuted among themselves. The order of that permutation is the
+            // number of times the cycle must be repeated to fix the piece's
+            // orientation.
+            let stickers = &desc.info(start).stickers;
+            let orientation_order = stickers
+                .iter()
+                .map(|&sticker| {
+                    let advance = |mut s: Sticker| {
+                        for _ in 0..pieces.len() {
+                            s = perm[s.0 as usize];
+                        }
+                        s
+                    };
+                    let mut s = advance(sticker);
+                    let mut n = 1;
+                    while s != sticker {
+                        s = advance(s);
+                        n += 1;
+                    }
+                    n
+                })
+                .fold(1, lcm);
+
+            let cycle_order = pieces.len() as u64 * orientation_order;
+            order = order.and_then(|o| checked_lcm(o, cycle_order));
+
+            if pieces.len() > 1 || orientation_order > 1 {
+                cycles.push(PieceCycle {
+                    piece_type: desc.info(start).piece_type,
+                    pieces,
+                    twisted: orientation_order > 1,
+                });
+            }
+        }
+
+        // Sort so that similar cycles are adjacent.
+        cycles.sort_by_key(|cycle| {
+            (
+                Reverse(cycle.pieces.len()),
+                cycle.piece_type.0,
+                cycle.twisted,
+            )
+        });
+
+        Self { cycles, order }
+    }
+
+    /// Returns the number of pieces that are moved or reoriented.
+    pub fn affected_piece_count(&self) -> usize {
+        self.cycles.iter().map(|cycle| cycle.pieces.len()).sum()
+    }
+
+    /// Returns a human-readable summary of each kind of cycle, such as
+    /// "2× 3-cycle of edge pieces" or "twists 2 corner pieces".
+    pub fn summary(&self, ty: PuzzleTypeEnum) -> Vec<String> {
+        let desc = ty.desc();
+        let mut groups: Vec<(usize, (usize, PieceType, bool))> = vec![];
+        for cycle in &self.cycles {
+            let key = (cycle.pieces.len(), cycle.piece_type, cycle.twisted);
+            match groups.last_mut() {
+                Some((count, k)) if *k == key => *count += 1,
+                _ => groups.push((1, key)),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(count, (len, piece_type, twisted))| {
+                let piece_type_name = &desc.info(piece_type).name;
+                if len == 1 {
+                    match count {
+                        1 => format!("twists 1 {piece_type_name} piece"),
+                        n => format!("twists {n} {piece_type_name} pieces"),
+                    }
+                } else {
+                    let mut s = match count {
+                        1 => format!("{len}-cycle of {piece_type_name} pieces"),
+                        n => format!("{n}× {len}-cycle of {piece_type_name} pieces"),
+                    };
+                    if twisted {
+                        s += " (twisted)";
+                    }
+                    s
+                }
+            })
+            .collect()
+    }
+}
+impl fmt::Display for SequenceAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.order {
+            Some(order) => write!(f, "order {order}")?,
+            None => write!(f, "order too large to compute")?,
+        }
+        write!(f, ", {} pieces affected", self.affected_piece_count())
+    }
+}
+
+/// Cycle of pieces that each move to the location of the next one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceCycle {
+    pub piece_type: PieceType,
+    pub pieces: Vec<Piece>,
+    /// Whether the pieces are reoriented after going around the cycle.
+    pub twisted: bool,
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
+fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze(ty: PuzzleTypeEnum, twists: &str) -> SequenceAnalysis {
+        SequenceAnalysis::new(ty, &ty.parse_twists(twists).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_rubiks_3d_sequence_analysis() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+
+        let identity = analyze(ty, "");
+        assert_eq!(identity.order, Some(1));
+        assert!(identity.cycles.is_empty());
+
+        let r = analyze(ty, "R");
+        assert_eq!(r.order, Some(4));
+        assert_eq!(r.affected_piece_count(), 8);
+        assert_eq!(r.cycles.len(), 2);
+
+        let sexy = analyze(ty, "R U R' U'");
+        assert_eq!(sexy.order, Some(6));
+        assert_eq!(sexy.affected_piece_count(), 7);
+
+        // Inverse sequences have the same order.
+        let sune = analyze(ty, "R U R' U R U2 R'");
+        let antisune = analyze(ty, "R U2 R' U' R U' R'");
+        assert_eq!(sune.order, antisune.order);
+        assert_eq!(sune.affected_piece_count(), antisune.affected_piece_count());
+    }
+
+    #[test]
+    fn test_rubiks_4d_sequence_analysis() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+
+        assert_eq!(analyze(ty, "").order, Some(1));
+
+        for _ in 0..10 {
+            let twist = Twist::from_rng(ty);
+            let a = SequenceAnalysis::new(ty, &[twist]).unwrap();
+            let inverse = SequenceAnalysis::new(ty, &[ty.reverse_twist(twist)]).unwrap();
+            assert_eq!(a.order, inverse.order);
+            assert_eq!(a.affected_piece_count(), inverse.affected_piece_count());
+        }
+    }
+}
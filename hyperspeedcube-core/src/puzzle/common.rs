@@ -8,6 +8,7 @@ use smallvec::SmallVec;
 use std::fmt;
 use std::ops::*;
 use std::str::FromStr;
+use std::sync::Arc;
 use strum::{Display, EnumIter, EnumMessage};
 
 use super::*;
@@ -35,6 +36,11 @@ pub trait PuzzleType {
     fn twist_directions(&self) -> &[TwistDirectionInfo];
     fn piece_types(&self) -> &[PieceTypeInfo];
 
+    /// Returns the position of a sticker in the grid of stickers on its face
+    /// when the puzzle is solved. This is `[row, column]` for 3D puzzles and
+    /// `[layer, row, column]` for 4D puzzles.
+    fn sticker_grid_position(&self, sticker: Sticker) -> smallvec::SmallVec<[u8; 3]>;
+
     fn twist_axis_from_name(&self, name: &str) -> Option<TwistAxis> {
         (0..self.twist_axes().len() as u8)
             .map(TwistAxis)
@@ -46,6 +52,21 @@ pub trait PuzzleType {
             .find(|&twist_direction| self.info(twist_direction).name == name)
     }
     fn opposite_twist_axis(&self, twist_axis: TwistAxis) -> Option<TwistAxis>;
+    /// Returns the twist axes needed to reach every state of the puzzle up to
+    /// rotation. With two layers or fewer, twisting an axis is the same as
+    /// twisting the opposite axis and rotating the whole puzzle, so only one
+    /// axis from each opposite pair is needed.
+    fn essential_twist_axes(&self) -> Vec<TwistAxis> {
+        (0..self.twist_axes().len() as u8)
+            .map(TwistAxis)
+            .filter(|&axis| {
+                self.layer_count() > 2
+                    || self
+                        .opposite_twist_axis(axis)
+                        .map_or(true, |opposite| opposite.0 > axis.0)
+            })
+            .collect()
+    }
     fn count_quarter_turns(&self, twist: Twist) -> usize;
 
     fn check_layers(&self, layers: LayerMask) -> Result<(), &'static str> {
@@ -67,6 +88,16 @@ pub trait PuzzleType {
     }
 
     fn make_recenter_twist(&self, axis: TwistAxis) -> Result<Twist, String>;
+    /// Returns the oriented plane of the 90-degree whole-puzzle rotation that
+    /// moves the face of the axis `from` to the location of the face of the
+    /// axis `to`, or `None` if they are on the same axis. The plane `[a, b]` is
+    /// a pair of coordinate axis indices, and rotating in it moves the positive
+    /// end of `a` toward the positive end of `b`, as in
+    /// [`crate::util::rotation_in_plane()`].
+    fn rotation_plane(&self, from: TwistAxis, to: TwistAxis) -> Option<[usize; 2]>;
+    /// Returns the 90-degree whole-puzzle rotation in the plane returned by
+    /// `rotation_plane()`.
+    fn make_rotation_twist(&self, from: TwistAxis, to: TwistAxis) -> Option<Twist>;
 
     fn reverse_twist(&self, twist: Twist) -> Twist {
         Twist {
@@ -76,6 +107,9 @@ pub trait PuzzleType {
         }
     }
     fn canonicalize_twist(&self, twist: Twist) -> Twist;
+    /// Mirrors a twist across the hyperplane that separates `axis` from its
+    /// opposite twist axis.
+    fn mirror_twist(&self, twist: Twist, axis: TwistAxis) -> Twist;
 
     fn reverse_twist_direction(&self, direction: TwistDirection) -> TwistDirection;
     fn chain_twist_directions(&self, dirs: &[TwistDirection]) -> Option<TwistDirection>;
@@ -99,6 +133,21 @@ pub trait PuzzleType {
 
         TWIST_REGEX.find_iter(string)
     }
+    /// Parses a sequence of twists written in the puzzle's notation.
+    fn parse_twists(&self, string: &str) -> Result<Vec<Twist>, String> {
+        self.split_twists_string(string)
+            .map(|m| {
+                self.notation_scheme()
+                    .parse_twist(m.as_str())
+                    .map_err(|e| format!("{e} {:?}", m.as_str()))
+            })
+            .collect()
+    }
+    /// Formats a sequence of twists using the puzzle's notation.
+    fn twists_to_string(&self, twists: &[Twist]) -> String {
+        let notation = self.notation_scheme();
+        crate::util::wrap_words(twists.iter().map(|&twist| notation.twist_to_string(twist)))
+    }
 
     fn twist_command_short_description(
         &self,
@@ -127,7 +176,7 @@ trait PuzzleTypeRefExt {
 }
 #[delegate_to_methods]
 #[delegate(PuzzleType, target_ref = "deref_internal")]
-impl<'a, P: PuzzleType> PuzzleTypeRefExt for &'a P {
+impl<'a, P: PuzzleType + ?Sized> PuzzleTypeRefExt for &'a P {
     fn deref_internal(&self) -> &'a P {
         self
     }
@@ -177,7 +226,31 @@ pub trait PuzzleState: PuzzleType {
         p: StickerGeometryParams,
     ) -> Option<StickerGeometry>;
 
+    /// Returns whether every face is a single color, in any orientation of the
+    /// whole puzzle.
     fn is_solved(&self) -> bool;
+    /// Returns whether the given pieces are solved relative to each other,
+    /// ignoring all other pieces.
+    fn are_pieces_solved(&self, pieces: &[Piece]) -> bool;
+    /// Returns whether the piece is in its solved position and orientation.
+    fn is_piece_solved(&self, piece: Piece) -> bool;
+
+    /// Rearranges the pieces so that each sticker position shows the given
+    /// color, where `colors` is indexed by the sticker currently at that
+    /// position. Returns an error and leaves the puzzle unchanged if the colors
+    /// do not describe a valid arrangement of pieces.
+    fn repaint(&mut self, colors: &[Face]) -> Result<(), String>;
+    /// Returns an error naming the invariant that is violated if the state
+    /// cannot be reached from the solved state by twisting.
+    fn check_solvable(&self) -> Result<(), String>;
+
+    /// Returns where each sticker is, as the sticker that occupies the same
+    /// position when the puzzle is solved. This is computed from scratch; use
+    /// `permutation()` for the permutation maintained after each twist.
+    fn sticker_permutation(&self) -> Vec<Sticker>;
+    /// Returns the permutation of stickers, which is kept up to date as the
+    /// puzzle is twisted.
+    fn permutation(&self) -> &StickerPermutation;
 
     #[cfg(debug_assertions)]
     fn sticker_debug_info(&self, _s: &mut String, _sticker: Sticker) {}
@@ -197,15 +270,82 @@ pub enum PuzzleTypeEnum {
         layer_count: u8,
     },
 }
-#[delegate_to_methods]
-#[delegate(PuzzleType, target_ref = "as_dyn_type")]
+/// Forwards methods of [`PuzzleType`] that do not borrow from the puzzle
+/// description, so that they can be called on a puzzle type directly.
+macro_rules! forward_to_description {
+    ($(fn $method:ident(self $(, $arg:ident: $arg_ty:ty)* $(,)?) -> $ret:ty;)*) => {
+        $(
+            pub fn $method(self $(, $arg: $arg_ty)*) -> $ret {
+                self.desc().$method($($arg),*)
+            }
+        )*
+    };
+}
+
 impl PuzzleTypeEnum {
-    fn as_dyn_type(&self) -> &dyn PuzzleType {
-        match *self {
+    /// Returns the description of the puzzle type, building it if it is not
+    /// cached. The description is freed once nothing holds it and it has been
+    /// evicted from the cache.
+    pub fn desc(self) -> Arc<dyn PuzzleType + Send + Sync> {
+        match self {
             PuzzleTypeEnum::Rubiks3D { layer_count } => rubiks_3d::puzzle_type(layer_count),
             PuzzleTypeEnum::Rubiks4D { layer_count } => rubiks_4d::puzzle_type(layer_count),
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PuzzleTypeEnum::Rubiks3D { layer_count } => rubiks_3d::puzzle_name(layer_count),
+            PuzzleTypeEnum::Rubiks4D { layer_count } => rubiks_4d::puzzle_name(layer_count),
+        }
+    }
+    pub fn layer_count(self) -> u8 {
+        match self {
+            PuzzleTypeEnum::Rubiks3D { layer_count } => layer_count,
+            PuzzleTypeEnum::Rubiks4D { layer_count } => layer_count,
+        }
+    }
+    pub fn projection_type(self) -> ProjectionType {
+        match self {
+            PuzzleTypeEnum::Rubiks3D { .. } => ProjectionType::_3D,
+            PuzzleTypeEnum::Rubiks4D { .. } => ProjectionType::_4D,
+        }
+    }
+
+    forward_to_description! {
+        fn family_display_name(self) -> &'static str;
+        fn family_internal_name(self) -> &'static str;
+        fn family_max_layer_count(self) -> u8;
+        fn projection_radius_3d(self, p: StickerGeometryParams) -> f32;
+        fn scramble_moves_count(self) -> usize;
+        fn sticker_grid_position(self, sticker: Sticker) -> SmallVec<[u8; 3]>;
+        fn twist_axis_from_name(self, name: &str) -> Option<TwistAxis>;
+        fn twist_direction_from_name(self, name: &str) -> Option<TwistDirection>;
+        fn opposite_twist_axis(self, twist_axis: TwistAxis) -> Option<TwistAxis>;
+        fn essential_twist_axes(self) -> Vec<TwistAxis>;
+        fn count_quarter_turns(self, twist: Twist) -> usize;
+        fn check_layers(self, layers: LayerMask) -> Result<(), &'static str>;
+        fn all_layers(self) -> LayerMask;
+        fn slice_layers(self) -> Option<LayerMask>;
+        fn reverse_layers(self, layers: LayerMask) -> LayerMask;
+        fn make_recenter_twist(self, axis: TwistAxis) -> Result<Twist, String>;
+        fn rotation_plane(self, from: TwistAxis, to: TwistAxis) -> Option<[usize; 2]>;
+        fn make_rotation_twist(self, from: TwistAxis, to: TwistAxis) -> Option<Twist>;
+        fn reverse_twist(self, twist: Twist) -> Twist;
+        fn canonicalize_twist(self, twist: Twist) -> Twist;
+        fn mirror_twist(self, twist: Twist, axis: TwistAxis) -> Twist;
+        fn reverse_twist_direction(self, direction: TwistDirection) -> TwistDirection;
+        fn chain_twist_directions(self, dirs: &[TwistDirection]) -> Option<TwistDirection>;
+        fn parse_twists(self, string: &str) -> Result<Vec<Twist>, String>;
+        fn twists_to_string(self, twists: &[Twist]) -> String;
+        fn twist_command_short_description(
+            self,
+            axis_name: Option<TwistAxis>,
+            direction: TwistDirection,
+            layers: LayerMask,
+        ) -> String;
+    }
+
     pub fn validate(self) -> Result<(), String> {
         match self {
             PuzzleTypeEnum::Rubiks3D { layer_count } => {
@@ -225,6 +365,28 @@ impl PuzzleTypeEnum {
         }
     }
 
+    /// Returns every puzzle type, in the order they appear in the puzzle menu.
+    pub fn iter_all() -> impl Iterator<Item = PuzzleTypeEnum> {
+        let rubiks_3d = rubiks_3d::LAYER_COUNT_RANGE
+            .map(|layer_count| PuzzleTypeEnum::Rubiks3D { layer_count });
+        let rubiks_4d = rubiks_4d::LAYER_COUNT_RANGE
+            .map(|layer_count| PuzzleTypeEnum::Rubiks4D { layer_count });
+        rubiks_3d.chain(rubiks_4d)
+    }
+    /// Returns the puzzle type `offset` places after this one in the puzzle
+    /// menu, wrapping around at either end.
+    pub fn cycle(self, offset: isize) -> Self {
+        let all = Self::iter_all().collect_vec();
+        let index = all.iter().position(|&ty| ty == self).unwrap_or(0) as isize;
+        all[(index + offset).rem_euclid(all.len() as isize) as usize]
+    }
+    /// Builds the description of the puzzle type if it has not been built
+    /// already. This can take a while for large puzzles, so it may be called
+    /// on another thread ahead of time.
+    pub fn load_description(self) {
+        self.desc();
+    }
+
     pub fn supports_mc4d_compat(&self) -> bool {
         match *self {
             PuzzleTypeEnum::Rubiks3D { .. } => false,
@@ -283,14 +445,18 @@ impl FromStr for Twist {
 }
 impl Twist {
     pub fn from_rng(ty: PuzzleTypeEnum) -> Self {
-        let mut rng = rand::thread_rng();
+        Self::random(ty, &mut rand::thread_rng())
+    }
+    /// Returns a random twist using a specific random number generator.
+    pub fn random(ty: PuzzleTypeEnum, rng: &mut impl Rng) -> Self {
+        let desc = ty.desc();
         Self {
-            axis: TwistAxis(rng.gen_range(0..ty.twist_axes().len()) as _),
-            direction: TwistDirection(rng.gen_range(0..ty.twist_directions().len()) as _),
-            layers: if ty.layer_count() > 1 {
-                LayerMask(rng.gen_range(1..ty.all_layers().0))
+            axis: TwistAxis(rng.gen_range(0..desc.twist_axes().len()) as _),
+            direction: TwistDirection(rng.gen_range(0..desc.twist_directions().len()) as _),
+            layers: if desc.layer_count() > 1 {
+                LayerMask(rng.gen_range(1..desc.all_layers().0))
             } else {
-                ty.all_layers()
+                desc.all_layers()
             },
         }
     }
@@ -298,7 +464,7 @@ impl Twist {
 
 /// Puzzle of any type.
 #[enum_dispatch(PuzzleType, PuzzleState)]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Puzzle {
     /// 3D Rubik's cube.
     Rubiks3D(Rubiks3D),
@@ -657,6 +823,28 @@ impl Sign {
     }
 }
 
+/// Returns the `[row, column]` of a sticker on a face of a cube, as seen from
+/// outside the face with U at the top (or B at the top of U and F at the top
+/// of D). `axis` is the index of the X, Y, or Z axis perpendicular to the face
+/// and `pos` is the location of the piece, from 0 on the negative side to
+/// `layer_count - 1` on the positive side.
+pub(super) fn cube_face_grid_position(
+    axis: usize,
+    sign: Sign,
+    [x, y, z]: [u8; 3],
+    layer_count: u8,
+) -> [u8; 2] {
+    let max = layer_count - 1;
+    match (axis, sign) {
+        (0, Sign::Pos) => [max - y, max - z], // R
+        (0, Sign::Neg) => [max - y, z],       // L
+        (1, Sign::Pos) => [z, x],             // U
+        (1, Sign::Neg) => [max - z, x],       // D
+        (2, Sign::Pos) => [max - y, x],       // F
+        _ => [max - y, max - x],              // B
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ProjectionType {
     _3D,
@@ -783,6 +971,11 @@ impl FromStr for LayerMask {
                         None => Some(Self(1 << (s.trim().parse::<u8>().ok()? - 1))),
                     })
                     .try_fold(Self(0), |a, b| Some(a | b?))
+            } else if let Some((lo, hi)) = s.trim().split_once('-') {
+                // Range notation without braces; e.g., "3-5"
+                let lo = lo.trim().parse::<u8>().ok()?.checked_sub(1)?;
+                let hi = hi.trim().parse::<u8>().ok()?.checked_sub(1)?;
+                Some(Self::from(lo..=hi))
             } else {
                 Some(Self(1 << (s.trim().parse::<u8>().ok()? - 1)))
             }
@@ -791,17 +984,22 @@ impl FromStr for LayerMask {
     }
 }
 impl LayerMask {
-    pub(crate) fn slice_layers(total_layer_count: u8) -> Option<Self> {
+    pub fn slice_layers(total_layer_count: u8) -> Option<Self> {
         (total_layer_count >= 3).then(|| Self((Self::all_layers(total_layer_count).0 >> 1) & !1))
     }
-    pub(crate) fn all_layers(total_layer_count: u8) -> Self {
+    /// Returns the outer layer on each side of the puzzle, which are twisted
+    /// together in a slice move such as R and L' at once.
+    pub fn outer_layers(total_layer_count: u8) -> Option<Self> {
+        (total_layer_count >= 3).then(|| Self(1 | 1 << (total_layer_count - 1)))
+    }
+    pub fn all_layers(total_layer_count: u8) -> Self {
         Self((1 << total_layer_count as u32) - 1)
     }
 
-    pub(crate) fn is_default(self) -> bool {
+    pub fn is_default(self) -> bool {
         self == Self::default()
     }
-    pub(crate) fn long_description(self) -> String {
+    pub fn long_description(self) -> String {
         match self.count() {
             0 => "no layers".to_owned(),
             1 => format!("layer {}", self.0.trailing_zeros() + 1),
@@ -811,10 +1009,10 @@ impl LayerMask {
             ),
         }
     }
-    pub(crate) fn count(self) -> u32 {
+    pub fn count(self) -> u32 {
         self.0.count_ones()
     }
-    pub(crate) fn count_contiguous_slices(self) -> u32 {
+    pub fn count_contiguous_slices(self) -> u32 {
         let mut n = self.0;
         let mut ret = 0;
         while n != 0 {
@@ -824,7 +1022,7 @@ impl LayerMask {
         }
         ret
     }
-    pub(crate) fn count_outer_slices(self, layer_count: u8) -> u32 {
+    pub fn count_outer_slices(self, layer_count: u8) -> u32 {
         let mut n = self.0;
         let mut ret = 0;
         while n != 0 {
@@ -840,10 +1038,13 @@ impl LayerMask {
         }
         ret
     }
-    pub(crate) fn is_contiguous_from_outermost(self) -> bool {
+    pub fn is_contiguous_from_outermost(self) -> bool {
         self.0 != 0 && self.0.count_ones() == self.0.trailing_ones()
     }
-    pub(crate) fn get_single_layer(self) -> Option<u32> {
+    pub fn is_contiguous(self) -> bool {
+        self.count_contiguous_slices() == 1
+    }
+    pub fn get_single_layer(self) -> Option<u32> {
         (self.count() == 1).then(|| self.0.trailing_zeros())
     }
 }
@@ -0,0 +1,2992 @@
+//! Puzzle wrapper that adds animation and undo history functionality.
+
+use anyhow::Result;
+use bitvec::bitvec;
+use bitvec::slice::BitSlice;
+use bitvec::vec::BitVec;
+use cgmath::{
+    Deg, EuclideanSpace, InnerSpace, Matrix3, One, Point2, Point3, Quaternion, Rotation, Rotation3,
+};
+use instant::{Duration, Instant};
+use num_enum::FromPrimitive;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+use std::ops::{BitOr, BitOrAssign, Range};
+use std::sync::Arc;
+
+/// Fraction of a twist to animate each time the animation is stepped manually.
+const TWIST_ANIMATION_STEP: f32 = 0.1;
+
+/// Maximum number of past states to remember for drawing piece trails.
+pub const MAX_TRAIL_LENGTH: usize = 50;
+
+/// Higher number means slower exponential decay of view angle offset.
+const VIEW_ANGLE_OFFSET_DECAY_RATE: f32 = 0.02_f32;
+
+/// Maximum number of moves in a scramble.
+pub const MAX_SCRAMBLE_LEN: usize = 10_000;
+/// Minimum number of moves for a partial scramble.
+pub const PARTIAL_SCRAMBLE_MOVE_COUNT_MIN: usize = 1;
+/// Maximum number of moves for a partial scramble.
+pub const PARTIAL_SCRAMBLE_MOVE_COUNT_MAX: usize = 20;
+
+/// Interpolation functions.
+pub mod interpolate {
+    use std::f32::consts::PI;
+
+    /// Function that maps a float from the range 0.0 to 1.0 to another float
+    /// from 0.0 to 1.0.
+    pub type InterpolateFn = fn(f32) -> f32;
+
+    /// Interpolate using cosine from 0.0 to PI.
+    pub const COSINE: InterpolateFn = |x| (1.0 - (x * PI).cos()) / 2.0;
+    /// Interpolate using cosine from 0.0 to PI/2.0.
+    pub const COSINE_ACCEL: InterpolateFn = |x| 1.0 - (x * PI / 2.0).cos();
+    /// Interpolate using cosine from PI/2.0 to 0.0.
+    pub const COSINE_DECEL: InterpolateFn = |x| ((1.0 - x) * PI / 2.0).cos();
+}
+
+use super::events::Subscriptions;
+use super::*;
+use crate::preferences::{
+    InteractionPreferences, OpacityPreferences, PuzzlePreferences, ScrambleOptions, ViewPreferences,
+};
+use crate::util::{self, IterCyclicPairsExt};
+use interpolate::InterpolateFn;
+
+const TWIST_INTERPOLATION_FN: InterpolateFn = interpolate::COSINE;
+
+/// Maximum number of twists in each part of a corners-only scramble.
+const MAX_CORNERS_ONLY_BLOCK_LEN: usize = 60;
+/// Number of random sequences to try for each part of a corners-only scramble
+/// before giving up.
+const CORNERS_ONLY_ATTEMPTS: usize = 100;
+
+/// Generates a random sequence of `n` twists. Adjacent twists never cancel
+/// each other.
+///
+/// Corners-only scrambles are made of blocks that each leave every other
+/// piece solved, so they can't be cut at an arbitrary twist. For these, `n` is
+/// the number of blocks instead.
+fn generate_scramble(
+    ty: PuzzleTypeEnum,
+    n: usize,
+    options: &ScrambleOptions,
+    rng: &mut impl Rng,
+) -> Result<Vec<Twist>, &'static str> {
+    let desc = ty.desc();
+    let axes = scramble_axes(ty, options)?;
+    loop {
+        let mut ret: Vec<Twist> = vec![];
+        if options.corners_only {
+            let mut block_count = 0;
+            while block_count < n && ret.len() < MAX_SCRAMBLE_LEN {
+                let block = corners_only_block(ty, &axes, options, rng)?;
+                if ret.last() != Some(&desc.reverse_twist(block[0])) {
+                    ret.extend(block);
+                    block_count += 1;
+                }
+            }
+        } else {
+            while ret.len() < n.min(MAX_SCRAMBLE_LEN) {
+                let twist = desc.canonicalize_twist(random_scramble_twist(ty, &axes, options, rng));
+                if ret.last() != Some(&desc.reverse_twist(twist)) {
+                    ret.push(twist);
+                }
+            }
+        }
+
+        // A puzzle with one layer has so few states that a scramble often
+        // leaves it in its original orientation, so try again.
+        if ty.layer_count() == 1 && n > 0 {
+            let mut state = Puzzle::new(ty);
+            for &twist in &ret {
+                state.twist(twist)?;
+            }
+            if state.is_piece_solved(Piece(0)) {
+                continue;
+            }
+        }
+        return Ok(ret);
+    }
+}
+/// Returns the twist axes that scrambles may use.
+fn scramble_axes(
+    ty: PuzzleTypeEnum,
+    options: &ScrambleOptions,
+) -> Result<Vec<TwistAxis>, &'static str> {
+    let desc = ty.desc();
+    let axes = desc
+        .essential_twist_axes()
+        .into_iter()
+        .filter(|&axis| {
+            let name = desc.info(axis).name;
+            !options.excluded_axes.iter().any(|a| a == name)
+        })
+        .collect::<Vec<_>>();
+    // With only one axis, twists would keep merging with each other.
+    if axes.len() < 2 {
+        return Err("Scrambles must use at least two twist axes");
+    }
+    Ok(axes)
+}
+/// Returns a random twist on one of `axes` that satisfies the scramble
+/// options.
+fn random_scramble_twist(
+    ty: PuzzleTypeEnum,
+    axes: &[TwistAxis],
+    options: &ScrambleOptions,
+    rng: &mut impl Rng,
+) -> Twist {
+    let mut twist = Twist::random(ty, rng);
+    twist.axis = axes[rng.gen_range(0..axes.len())];
+    // Twisting only the outer layers of a puzzle with two layers keeps the
+    // piece opposite every essential axis fixed.
+    if options.outer_layers_only || ty.layer_count() <= 2 {
+        twist.layers = LayerMask(1);
+    }
+    twist
+}
+/// Returns a random sequence of twists that scrambles some corners and leaves
+/// every other piece solved. This is a short random sequence repeated until
+/// all the pieces that are not corners return to where they started.
+fn corners_only_block(
+    ty: PuzzleTypeEnum,
+    axes: &[TwistAxis],
+    options: &ScrambleOptions,
+    rng: &mut impl Rng,
+) -> Result<Vec<Twist>, &'static str> {
+    let desc = ty.desc();
+    let other_pieces: Vec<Piece> = (0..desc.pieces().len() as _)
+        .map(Piece)
+        .filter(|&piece| desc.info(desc.info(piece).piece_type).name != "corner")
+        .collect();
+    if other_pieces.len() == desc.pieces().len() {
+        return Err("Puzzle has no corners to scramble");
+    }
+
+    for _ in 0..CORNERS_ONLY_ATTEMPTS {
+        let len = rng.gen_range(2..=4);
+        let mut sequence: Vec<Twist> = vec![];
+        while sequence.len() < len {
+            let twist = desc.canonicalize_twist(random_scramble_twist(ty, axes, options, rng));
+            if sequence.last() != Some(&desc.reverse_twist(twist)) {
+                sequence.push(twist);
+            }
+        }
+        // The end of each repetition must not cancel the start of the next.
+        if sequence[0] == desc.reverse_twist(sequence[len - 1]) {
+            continue;
+        }
+
+        let mut state = Puzzle::new(ty);
+        let mut block = vec![];
+        while block.len() + len <= MAX_CORNERS_ONLY_BLOCK_LEN {
+            for &twist in &sequence {
+                state.twist(twist)?;
+            }
+            block.extend_from_slice(&sequence);
+            let others_solved = other_pieces.iter().all(|&p| state.is_piece_solved(p));
+            if others_solved {
+                if !state.is_solved() {
+                    return Ok(block);
+                }
+                // The corners are solved too, so try another sequence.
+                break;
+            }
+        }
+    }
+    Err("Unable to generate a corners-only scramble")
+}
+
+/// Scramble that has been generated and applied to a puzzle state ahead of
+/// time, so that it can be done without a `PuzzleController`.
+#[derive(Debug, Clone)]
+pub struct PrecomputedScramble {
+    /// Scramble twists.
+    pub twists: Vec<Twist>,
+    /// Puzzle state after the scramble.
+    pub state: Puzzle,
+    /// Scramble state to mark the puzzle with.
+    pub scramble_state: ScrambleState,
+}
+impl PrecomputedScramble {
+    /// Generates a random scramble of `n` twists, or a full scramble if `n` is
+    /// `None`, and applies it to a solved puzzle. `progress` is called with
+    /// the number of twists applied so far.
+    pub fn generate(
+        ty: PuzzleTypeEnum,
+        n: Option<usize>,
+        options: &ScrambleOptions,
+        mut progress: impl FnMut(usize),
+    ) -> Result<Self, &'static str> {
+        let len = n.unwrap_or(ty.scramble_moves_count());
+        if len > MAX_SCRAMBLE_LEN {
+            return Err("Cannot scramble more than 10,000 moves");
+        }
+        let twists = generate_scramble(ty, len, options, &mut rand::thread_rng())?;
+
+        let mut state = Puzzle::new(ty);
+        for (i, &twist) in twists.iter().enumerate() {
+            state.twist(twist)?;
+            progress(i + 1);
+        }
+
+        let scramble_state = if n.is_none() && !options.is_restricted() {
+            ScrambleState::Full
+        } else {
+            ScrambleState::Partial
+        };
+        Ok(Self {
+            twists,
+            state,
+            scramble_state,
+        })
+    }
+}
+
+/// Puzzle wrapper that adds animation and undo history functionality.
+#[derive(Delegate, Debug)]
+#[delegate(PuzzleType, target = "puzzle")]
+pub struct PuzzleController {
+    /// Latest puzzle state, not including any transient rotation.
+    puzzle: Puzzle,
+    /// Set of pieces that are in their solved position and orientation,
+    /// updated incrementally after each twist.
+    solved_pieces: BitVec,
+    /// Number of pieces in `solved_pieces`.
+    solved_piece_count: usize,
+    /// Twist animation state.
+    twist_anim: TwistAnimationState,
+    /// Whether twist animations only advance when stepped manually.
+    step_mode: bool,
+    /// View settings animation state.
+    view_settings_anim: ViewSettingsAnimState,
+    /// View angle animation state.
+    view_angle: ViewAngleAnimState,
+
+    /// Whether the puzzle has been modified since the last time the log file
+    /// was saved.
+    is_unsaved: bool,
+    /// Whether the puzzle has been modified since the last time the log file
+    /// was exported via clipboard.
+    is_unsaved_via_clipboard: bool,
+    /// Whether the puzzle has been modified since the last time the log file
+    /// was saved in local storage (always `true` on desktop).
+    is_unsaved_in_local_storage: bool,
+
+    /// Whether the puzzle has been scrambled.
+    scramble_state: ScrambleState,
+    /// Scramble twists.
+    scramble: Vec<Twist>,
+    /// Arbitrary state that the puzzle was set to instead of being scrambled.
+    custom_state: Option<Puzzle>,
+    /// Undo history.
+    undo_buffer: Vec<HistoryEntry>,
+    /// Redo history.
+    redo_buffer: Vec<HistoryEntry>,
+    /// Ranges of entries in the undo history that are undone together, such as
+    /// the twists of a pattern, in order.
+    undo_groups: Vec<Range<usize>>,
+    /// Ranges of entries in the redo history that are redone together, in
+    /// order.
+    redo_groups: Vec<Range<usize>>,
+    /// Scramble that was undone and can be redone, along with its scramble
+    /// state.
+    undone_scramble: Option<(Vec<Twist>, ScrambleState)>,
+    /// Time at which each twist in the undo history was made, relative to
+    /// the start of the solve, or `None` if unknown.
+    twist_times: Vec<Option<Duration>>,
+    /// Time at which the solve clock was last started.
+    solve_start: Option<Instant>,
+    /// Time on the solve clock when it was last started.
+    solve_time_offset: Duration,
+    /// Phases of the solve that have been completed, in order.
+    phase_splits: Vec<PhaseSplit>,
+
+    /// Sticker that the user is hovering over.
+    hovered_sticker: Option<Sticker>,
+    /// Twists from the hovered sticker.
+    hovered_twists: Option<ClickTwists>,
+
+    /// Grip, which controls which pieces will be twisted.
+    grip: Grip,
+    /// Set of selected stickers.
+    selection: HashSet<Sticker>,
+    /// Last used filter.
+    last_filter: String,
+    /// Set of non-hidden pieces.
+    visible_pieces: BitVec,
+    /// Set of non-hidden pieces to preview when hovering over a piece filter
+    /// button.
+    visible_pieces_preview: Option<BitVec>,
+    /// Opacity of hidden pieces preview when hovering over a piece filter
+    /// buton.
+    hidden_pieces_preview_opacity: Option<f32>,
+    /// Set of faces (cells, on 4D puzzles) whose stickers are not drawn,
+    /// regardless of which pieces are hidden.
+    hidden_cells: BitVec,
+
+    /// Piece states, such as whether a piece is hidden. All values are
+    /// represented as `f32` for animation.
+    visual_piece_states: Vec<VisualPieceState>,
+
+    /// Cached sticker geometry.
+    cached_geometry: Option<Arc<Vec<ProjectedStickerGeometry>>>,
+    cached_geometry_params: Option<StickerGeometryParams>,
+
+    /// Sticker colors being edited in the state editor.
+    paint: Option<PaintState>,
+
+    /// State to display as a ghost overlay, or `None` for the solved state.
+    ghost_target: Option<Puzzle>,
+    /// Cached sticker geometry for the ghost overlay.
+    cached_ghost_geometry: Option<Arc<Vec<ProjectedStickerGeometry>>>,
+    cached_ghost_geometry_params: Option<StickerGeometryParams>,
+
+    /// Pieces whose movement is shown by a trail.
+    tracked_pieces: Vec<Piece>,
+    /// State before each recent twist, oldest first, used to draw trails for
+    /// tracked pieces.
+    trail_states: VecDeque<Puzzle>,
+
+    /// Callbacks subscribed to puzzle events.
+    subscriptions: Subscriptions,
+}
+impl Default for PuzzleController {
+    fn default() -> Self {
+        Self::new(PuzzleTypeEnum::default())
+    }
+}
+impl Eq for PuzzleController {}
+impl PartialEq for PuzzleController {
+    fn eq(&self, other: &Self) -> bool {
+        self.puzzle == other.puzzle
+    }
+}
+impl PartialEq<Puzzle> for PuzzleController {
+    fn eq(&self, other: &Puzzle) -> bool {
+        self.puzzle == *other
+    }
+}
+impl PuzzleController {
+    /// Constructs a new PuzzleController with a solved puzzle.
+    pub fn new(ty: PuzzleTypeEnum) -> Self {
+        let desc = ty.desc();
+        Self {
+            puzzle: Puzzle::new(ty),
+            solved_pieces: bitvec![1; desc.pieces().len()],
+            solved_piece_count: desc.pieces().len(),
+            twist_anim: TwistAnimationState::default(),
+            step_mode: false,
+            view_settings_anim: ViewSettingsAnimState::default(),
+            view_angle: ViewAngleAnimState::default(),
+
+            is_unsaved: false,
+            is_unsaved_via_clipboard: true,
+            is_unsaved_in_local_storage: true,
+
+            scramble_state: ScrambleState::None,
+            scramble: vec![],
+            custom_state: None,
+            undo_buffer: vec![],
+            redo_buffer: vec![],
+            undo_groups: vec![],
+            redo_groups: vec![],
+            undone_scramble: None,
+            twist_times: vec![],
+            solve_start: None,
+            solve_time_offset: Duration::ZERO,
+            phase_splits: vec![],
+
+            hovered_sticker: None,
+            hovered_twists: None,
+
+            grip: Grip::default(),
+            selection: HashSet::new(),
+            last_filter: "".to_string(),
+            visible_pieces: bitvec![1; desc.pieces().len()],
+            visible_pieces_preview: None,
+            hidden_pieces_preview_opacity: None,
+            hidden_cells: bitvec![0; desc.faces().len()],
+
+            visual_piece_states: vec![VisualPieceState::default(); desc.pieces().len()],
+
+            cached_geometry: None,
+            cached_geometry_params: None,
+
+            paint: None,
+
+            ghost_target: None,
+            cached_ghost_geometry: None,
+            cached_ghost_geometry_params: None,
+
+            tracked_pieces: vec![],
+            trail_states: VecDeque::new(),
+
+            subscriptions: Subscriptions::default(),
+        }
+    }
+    /// Resets the puzzle. The ghost target and event subscriptions are kept.
+    pub fn reset(&mut self) {
+        let ghost_target = self.ghost_target.take();
+        let tracked_pieces = std::mem::take(&mut self.tracked_pieces);
+        let subscriptions = std::mem::take(&mut self.subscriptions);
+        *self = Self::new(self.ty());
+        self.ghost_target = ghost_target;
+        self.tracked_pieces = tracked_pieces;
+        self.subscriptions = subscriptions;
+    }
+    /// Replaces the puzzle with another one, such as one loaded from a log
+    /// file, and returns the old puzzle. Event subscriptions are kept.
+    pub fn replace(&mut self, new: PuzzleController) -> PuzzleController {
+        let subscriptions = std::mem::take(&mut self.subscriptions);
+        let old = std::mem::replace(self, new);
+        self.subscriptions = subscriptions;
+        old
+    }
+
+    /// Subscribes to every puzzle event. Returns a handle that can be passed to
+    /// `unsubscribe()`.
+    pub fn subscribe(&mut self, callback: impl FnMut(&PuzzleEvent) + 'static) -> SubscriptionId {
+        self.subscriptions.add(Box::new(callback))
+    }
+    /// Subscribes to new twists, not including scramble twists or redo.
+    pub fn on_twist(&mut self, mut callback: impl FnMut(Twist) + 'static) -> SubscriptionId {
+        self.subscribe(move |event| {
+            if let PuzzleEvent::Twist(twist) = event {
+                callback(*twist);
+            }
+        })
+    }
+    /// Subscribes to scrambles.
+    pub fn on_scramble(
+        &mut self,
+        mut callback: impl FnMut(ScrambleState) + 'static,
+    ) -> SubscriptionId {
+        self.subscribe(move |event| {
+            if let PuzzleEvent::Scramble(scramble_state) = event {
+                callback(*scramble_state);
+            }
+        })
+    }
+    /// Subscribes to the puzzle being solved after it was scrambled. This is
+    /// detected by `check_just_solved()`.
+    pub fn on_solved(&mut self, mut callback: impl FnMut() + 'static) -> SubscriptionId {
+        self.subscribe(move |event| {
+            if let PuzzleEvent::Solved = event {
+                callback();
+            }
+        })
+    }
+    /// Subscribes to undo.
+    pub fn on_undo(&mut self, mut callback: impl FnMut(HistoryEntry) + 'static) -> SubscriptionId {
+        self.subscribe(move |event| {
+            if let PuzzleEvent::Undo(entry) = event {
+                callback(*entry);
+            }
+        })
+    }
+    /// Subscribes to redo.
+    pub fn on_redo(&mut self, mut callback: impl FnMut(HistoryEntry) + 'static) -> SubscriptionId {
+        self.subscribe(move |event| {
+            if let PuzzleEvent::Redo(entry) = event {
+                callback(*entry);
+            }
+        })
+    }
+    /// Removes a subscription. Returns `false` if it did not exist.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.subscriptions.remove(id)
+    }
+
+    /// Returns whether the puzzle has been scrambled, solved, etc..
+    pub fn scramble_state(&self) -> ScrambleState {
+        self.scramble_state
+    }
+    /// Reset and then scramble some number of moves. For corners-only
+    /// scrambles, `n` is the number of corners-only blocks instead.
+    pub fn scramble_n(&mut self, n: usize, options: &ScrambleOptions) -> Result<(), &'static str> {
+        self.reset();
+
+        // Set a reasonable limit on the number of moves.
+        if n > MAX_SCRAMBLE_LEN {
+            return Err("Cannot scramble more than 10,000 moves");
+        }
+
+        if options.corners_only {
+            for twist in self.generate_scramble(n, options)? {
+                self._twist(twist, false)?;
+            }
+            self.add_scramble_marker(ScrambleState::Partial);
+            return Ok(());
+        }
+
+        let axes = scramble_axes(self.ty(), options)?;
+        let mut rng = rand::thread_rng();
+
+        loop {
+            // Use a `while` loop instead of a `for` loop because moves may
+            // cancel.
+            while self.undo_buffer.len() < n {
+                let twist = random_scramble_twist(self.ty(), &axes, options, &mut rng);
+                self._twist(twist, true)?;
+            }
+
+            // A puzzle with one layer has so few states that a scramble
+            // often leaves it in its original orientation, so try again.
+            if self.layer_count() > 1 || n == 0 || !self.puzzle.is_piece_solved(Piece(0)) {
+                break;
+            }
+            self.reset();
+        }
+        self.add_scramble_marker(ScrambleState::Partial);
+        Ok(())
+    }
+    /// Reset and then scramble the puzzle completely.
+    pub fn scramble_full(&mut self, options: &ScrambleOptions) -> Result<(), &'static str> {
+        self.reset();
+        self.scramble_n(self.scramble_moves_count(), options)?;
+        if !options.is_restricted() {
+            self.scramble_state = ScrambleState::Full;
+        }
+        Ok(())
+    }
+    /// Reset and then scramble using a specific sequence of twists.
+    pub fn scramble_with(
+        &mut self,
+        twists: &[Twist],
+        new_scramble_state: ScrambleState,
+    ) -> Result<(), &'static str> {
+        self.reset();
+
+        if twists.len() > MAX_SCRAMBLE_LEN {
+            return Err("Cannot scramble more than 10,000 moves");
+        }
+
+        for &twist in twists {
+            self._twist(twist, false)?;
+        }
+        self.add_scramble_marker(new_scramble_state);
+        Ok(())
+    }
+    /// Discards all twists since the scramble, returning the puzzle to its
+    /// scrambled state.
+    pub fn reset_to_scramble(&mut self) -> Result<(), &'static str> {
+        if self.custom_state.is_some() {
+            return Err("Custom puzzle states have no scramble");
+        }
+        if self.scramble_state == ScrambleState::None {
+            return Err("Puzzle is not scrambled");
+        }
+        let scramble = std::mem::take(&mut self.scramble);
+        self.scramble_with(&scramble, self.scramble_state)
+    }
+    /// Generates a random sequence of `n` twists without applying it. Adjacent
+    /// twists never cancel each other. For corners-only scrambles, `n` is the
+    /// number of corners-only blocks instead.
+    pub fn generate_scramble(
+        &self,
+        n: usize,
+        options: &ScrambleOptions,
+    ) -> Result<Vec<Twist>, &'static str> {
+        generate_scramble(self.ty(), n, options, &mut rand::thread_rng())
+    }
+    /// Generates a sequence of twists from a seed without applying it. The
+    /// same seed always gives the same scramble in the same version of
+    /// Hyperspeedcube, so a scramble can be shared or checked using only its
+    /// seed.
+    pub fn generate_seeded_scramble(
+        &self,
+        n: usize,
+        options: &ScrambleOptions,
+        seed: u64,
+    ) -> Result<Vec<Twist>, &'static str> {
+        generate_scramble(self.ty(), n, options, &mut StdRng::seed_from_u64(seed))
+    }
+    /// Resets the puzzle and applies a scramble that was generated ahead of
+    /// time, such as on a worker thread.
+    pub fn apply_precomputed_scramble(
+        &mut self,
+        scramble: PrecomputedScramble,
+    ) -> Result<(), &'static str> {
+        if scramble.state.ty() != self.ty() {
+            return Err("Puzzle type does not match");
+        }
+        self.reset();
+        self.puzzle = scramble.state;
+        self.recompute_solved_pieces();
+        self.scramble = scramble.twists;
+        self.mark_unsaved();
+        self.add_scramble_marker(scramble.scramble_state);
+        Ok(())
+    }
+    /// Marks the puzzle as scrambled.
+    pub fn add_scramble_marker(&mut self, new_scramble_state: ScrambleState) {
+        self.skip_twist_animations();
+        self.scramble
+            .extend(self.undo_buffer.drain(..).filter_map(HistoryEntry::twist));
+        self.twist_times.clear();
+        self.undo_groups.clear();
+        self.solve_start = None;
+        self.solve_time_offset = Duration::ZERO;
+        self.phase_splits.clear();
+        self.trail_states.clear();
+        if new_scramble_state == ScrambleState::None {
+            // This is technically invalid? But I've seen some older MC4D log files that do this, so just assume it's a full scramble.
+            self.scramble_state = ScrambleState::Full;
+        } else {
+            self.scramble_state = new_scramble_state;
+        }
+        self.subscriptions
+            .emit(PuzzleEvent::Scramble(self.scramble_state));
+    }
+    /// Resets the puzzle and replaces its state with an arbitrary one, such as
+    /// one made in the state editor. The new state has no scramble, so log
+    /// files store the state itself instead.
+    pub fn set_custom_state(&mut self, state: Puzzle) -> Result<(), &'static str> {
+        if state.ty() != self.ty() {
+            return Err("Puzzle type does not match");
+        }
+        self.reset();
+        self.puzzle = state.clone();
+        self.recompute_solved_pieces();
+        if !self.is_solved() {
+            self.scramble_state = ScrambleState::Partial;
+        }
+        self.custom_state = Some(state);
+        self.mark_unsaved();
+        Ok(())
+    }
+    /// Returns whether the puzzle was set to an arbitrary state using
+    /// `set_custom_state()`.
+    pub fn has_custom_state(&self) -> bool {
+        self.custom_state.is_some()
+    }
+    /// Returns the state that the puzzle was set to using
+    /// `set_custom_state()`, before any twists since.
+    pub fn custom_state(&self) -> Option<&Puzzle> {
+        self.custom_state.as_ref()
+    }
+
+    /// Returns whether stickers are being painted in the state editor.
+    pub fn is_painting(&self) -> bool {
+        self.paint.is_some()
+    }
+    /// Starts painting stickers, beginning from their current colors. Does
+    /// nothing if already painting.
+    pub fn start_painting(&mut self) {
+        if self.paint.is_none() {
+            self.paint = Some(PaintState {
+                colors: self.stickers().iter().map(|s| s.color).collect(),
+                brush: Face(0),
+            });
+        }
+    }
+    /// Stops painting stickers and discards the painted colors.
+    pub fn stop_painting(&mut self) {
+        self.paint = None;
+    }
+    /// Resets all painted stickers to their current colors.
+    pub fn reset_paint(&mut self) {
+        let colors = self.stickers().iter().map(|s| s.color).collect();
+        if let Some(paint) = &mut self.paint {
+            paint.colors = colors;
+        }
+    }
+    /// Returns the color used to paint stickers, if painting.
+    pub fn paint_brush(&self) -> Option<Face> {
+        Some(self.paint.as_ref()?.brush)
+    }
+    /// Sets the color used to paint stickers.
+    pub fn set_paint_brush(&mut self, color: Face) {
+        if let Some(paint) = &mut self.paint {
+            paint.brush = color;
+        }
+    }
+    /// Paints a sticker using the current brush color.
+    pub fn paint_sticker(&mut self, sticker: Sticker) {
+        if let Some(paint) = &mut self.paint {
+            paint.colors[sticker.0 as usize] = paint.brush;
+        }
+    }
+    /// Returns the color to display for a sticker, which may have been
+    /// painted.
+    pub fn sticker_color(&self, sticker: Sticker) -> Face {
+        match &self.paint {
+            Some(paint) => paint.colors[sticker.0 as usize],
+            None => self.info(sticker).color,
+        }
+    }
+    /// Returns the state described by the painted sticker colors, or an error
+    /// if they do not describe a valid and solvable arrangement of pieces.
+    /// Returns `None` if not painting.
+    pub fn painted_state(&self) -> Option<Result<Puzzle, String>> {
+        let paint = self.paint.as_ref()?;
+        let mut state = self.puzzle.clone();
+        Some(
+            state
+                .repaint(&paint.colors)
+                .and_then(|()| state.check_solvable())
+                .map(|()| state),
+        )
+    }
+    /// Returns a code that can be shared to reproduce the current state of
+    /// the puzzle, independent of its twist history.
+    pub fn state_code(&self) -> String {
+        encode_state(&self.puzzle)
+    }
+
+    /// Adds a twist to the back of the twist queue.
+    pub fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+        let twist = self._twist(twist, true)?;
+        self.subscriptions.emit(PuzzleEvent::Twist(twist));
+        Ok(())
+    }
+    /// Adds a twist to the back of the twist queue. Does not cancel adjacent
+    /// twists.
+    pub fn twist_no_collapse(&mut self, twist: Twist) -> Result<(), &'static str> {
+        let twist = self._twist(twist, false)?;
+        self.subscriptions.emit(PuzzleEvent::Twist(twist));
+        Ok(())
+    }
+    /// Adds a sequence of twists to the back of the twist queue as a single
+    /// undo unit. Does not cancel adjacent twists.
+    pub fn twist_group(&mut self, twists: &[Twist]) -> Result<(), &'static str> {
+        let start = self.undo_buffer.len();
+        for &twist in twists {
+            self.twist_no_collapse(twist)?;
+        }
+        let end = self.undo_buffer.len();
+        if end - start > 1 {
+            self.undo_groups.push(start..end);
+        }
+        Ok(())
+    }
+    /// Adds a pair of twists on opposite axes to the back of the twist queue
+    /// as a single undo unit, animating both at the same time.
+    pub fn twist_simultaneous(&mut self, twists: [Twist; 2]) -> Result<(), &'static str> {
+        let [a, b] = twists;
+        if self.opposite_twist_axis(a.axis) != Some(b.axis) {
+            return Err("twists are not on opposite axes");
+        }
+        if a.layers & self.reverse_layers(b.layers) & self.all_layers() != LayerMask(0) {
+            return Err("twists overlap");
+        }
+        self.twist_group(&twists)?;
+
+        // Merge the two animations into one.
+        let second = self.twist_anim.queue.pop_back();
+        if let (Some(first), Some(second)) = (self.twist_anim.queue.back_mut(), second) {
+            first.simultaneous_twist = Some(second.twist);
+            if !self.tracked_pieces.is_empty() {
+                self.trail_states.pop_back();
+            }
+        }
+        Ok(())
+    }
+    /// Adds a twist to the back of the twist queue without emitting an event,
+    /// and returns the canonicalized twist.
+    fn _twist(&mut self, mut twist: Twist, collapse: bool) -> Result<Twist, &'static str> {
+        twist.layers &= self.all_layers(); // Restrict layer mask.
+        if twist.layers == LayerMask(0) {
+            return Err("invalid layer mask");
+        }
+        if twist.axis.0 as usize >= self.twist_axes().len() {
+            return Err("invalid twist axis");
+        }
+        if twist.direction.0 as usize >= self.twist_directions().len() {
+            return Err("invalid twist direction");
+        }
+
+        self.mark_unsaved();
+        self.redo_buffer.clear();
+        self.redo_groups.clear();
+        self.undone_scramble = None;
+        // Canonicalize twist.
+        twist = self.canonicalize_twist(twist);
+        if collapse && self.undo_buffer.last() == Some(&self.reverse_twist(twist).into()) {
+            // This twist is the reverse of the last one, so just undo the last
+            // one.
+            self._undo()?;
+        } else {
+            self.animate_twist(twist, HistoryChange::Twist)?;
+            self.undo_buffer.push(twist.into());
+            self.record_twist_time();
+        }
+        Ok(twist)
+    }
+    /// Applies the transient rotation to the puzzle.
+    pub fn apply_transient_rotation(&mut self) {
+        if let Some((twists, rot)) = self.view_angle.transient_rotation.take() {
+            // Remove a rotation from `current` and add it onto `queued_delta`.
+            for twist in twists {
+                self.mark_unsaved();
+
+                if self.undo_buffer.last() == Some(&self.reverse_twist(twist).into()) {
+                    // This twist is the reverse of the last one, so just undo the last one.
+                    self.redo_buffer.extend(self.undo_buffer.pop());
+                    self.twist_times.pop();
+                    self.forget_undone_phase_splits();
+                } else {
+                    self.redo_buffer.clear();
+                    self.undone_scramble = None;
+                    self.undo_buffer.push(twist.into());
+                    self.record_twist_time();
+                }
+                if self.puzzle.twist(twist).is_err() {
+                    log::error!("error applying transient rotation twist {:?}", twist);
+                }
+                self.update_solved_pieces(self.puzzle.pieces_affected_by_twist(twist));
+            }
+            self.forget_broken_undo_groups();
+            // Remove this rotation from `current`.
+            self.view_angle.current = self.view_angle.current * rot.invert();
+            if let Some(t) = self.twist_anim.queue.back_mut() {
+                // Actually, instead of just removing the rotation from
+                // `current`, transfer it from `current` to `queued_delta`.
+                // Resolve this rotation after the currently-last twist on the
+                // queue is popped.
+                self.view_angle.queued_delta = rot * self.view_angle.queued_delta;
+                t.view_angle_offset_delta = rot * t.view_angle_offset_delta;
+            }
+
+            // Invalidate the cache.
+            self.cached_geometry = None;
+        }
+    }
+    /// Applies a twist to the puzzle and queues it for animation. Does _not_
+    /// handle undo/redo stack or `is_unsaved`.
+    fn animate_twist(
+        &mut self,
+        twist: Twist,
+        history_change: HistoryChange,
+    ) -> Result<(), &'static str> {
+        let old_state = self.puzzle.clone();
+        self.puzzle.twist(twist)?;
+        self.update_solved_pieces(self.puzzle.pieces_affected_by_twist(twist));
+        if !self.tracked_pieces.is_empty() {
+            if self.trail_states.len() >= MAX_TRAIL_LENGTH {
+                self.trail_states.pop_front();
+            }
+            self.trail_states.push_back(old_state.clone());
+        }
+        self.twist_anim.queue.push_back(TwistAnimation {
+            state: old_state,
+            twist,
+            history_change,
+            view_angle_offset_delta: Quaternion::one(),
+            simultaneous_twist: None,
+            undone_twist_time: None,
+        });
+
+        // Invalidate the cache.
+        self.cached_geometry = None;
+
+        Ok(())
+    }
+    /// Returns the twist currently being animated, along with a float between
+    /// 0.0 and 1.0 indicating the progress on that animation.
+    pub fn current_twist(&self) -> Option<(Twist, f32)> {
+        self.twist_anim
+            .queue
+            .get(0)
+            .map(|anim| (anim.twist, TWIST_INTERPOLATION_FN(self.twist_anim.progress)))
+    }
+    /// Returns the twist being animated at the same time as the current twist,
+    /// if there is one.
+    pub fn current_simultaneous_twist(&self) -> Option<Twist> {
+        self.twist_anim.queue.get(0)?.simultaneous_twist
+    }
+
+    /// Returns the state of the cube that should be displayed, not including
+    /// the twist currently being animated (if there is one).
+    pub fn displayed(&self) -> &Puzzle {
+        match self.twist_anim.queue.get(0) {
+            Some(anim) => &anim.state,
+            None => &self.puzzle,
+        }
+    }
+    /// Returns the state of the cube that should be displayed after the twist
+    /// currently being animated (if there is one).
+    pub fn next_displayed(&self) -> &Puzzle {
+        match self.twist_anim.queue.get(1) {
+            Some(anim) => &anim.state,
+            None => &self.puzzle,
+        }
+    }
+    /// Returns the state of the cube after all queued twists have been applied.
+    pub fn latest(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    /// Returns the puzzle type.
+    pub fn ty(&self) -> PuzzleTypeEnum {
+        self.puzzle.ty()
+    }
+
+    /// Returns the puzzle grip.
+    pub fn grip(&self) -> &Grip {
+        &self.grip
+    }
+    /// Sets the puzzle grip.
+    pub fn set_grip(&mut self, grip: Grip, prefs: &InteractionPreferences) {
+        if grip != self.grip && !grip.axes.is_empty() && prefs.realign_on_keypress {
+            self.unfreeze_view_angle_offset();
+        } else {
+            self.apply_transient_rotation();
+        }
+        self.grip = grip;
+    }
+
+    /// Sets the view angle offset. Consider calling
+    /// `freeze_view_angle_offset()` as well.
+    pub fn add_view_angle_offset(&mut self, offset: [f32; 2], view_prefs: &ViewPreferences) {
+        let prefs_view_angle = view_prefs.view_angle();
+        let offset =
+            Quaternion::from_angle_x(Deg(offset[1])) * Quaternion::from_angle_y(Deg(offset[0]));
+        self.view_angle.current =
+            prefs_view_angle.invert() * offset * prefs_view_angle * self.view_angle.current;
+    }
+    /// Freezes the view angle offset, so that it will not animate back to zero
+    /// automatically. It can still be changed with `set_view_angle_offset()`.
+    pub fn freeze_view_angle_offset(&mut self) {
+        self.view_angle.is_frozen = true;
+    }
+    /// Unfreezes the view angle offset and begins animating it to the nearest
+    /// compatible orientation.
+    pub fn unfreeze_view_angle_offset(&mut self) {
+        self.apply_transient_rotation();
+        self.view_angle.is_frozen = false;
+    }
+    fn update_transient_rotation(&mut self, interaction_prefs: &InteractionPreferences) {
+        if interaction_prefs.smart_realign {
+            let nearest_twists = self.puzzle.nearest_rotation(self.view_angle.current);
+            self.view_angle.transient_rotation =
+                (!nearest_twists.0.is_empty()).then_some(nearest_twists);
+        } else {
+            self.view_angle.transient_rotation = None;
+        }
+    }
+
+    /// Adds an animation to the view settings animation queue.
+    pub fn animate_from_view_settings(&mut self, view_prefs: ViewPreferences) {
+        self.view_settings_anim.queue.push_back(view_prefs);
+    }
+
+    /// Returns whether this sticker can be hovered.
+    fn is_sticker_hoverable(&self, sticker: Sticker) -> bool {
+        let less_than_halfway = TWIST_INTERPOLATION_FN(self.twist_anim.progress) < 0.5;
+        let puzzle_state = if less_than_halfway {
+            self.displayed() // puzzle state before the twist
+        } else {
+            self.next_displayed() // puzzle state after the twist
+        };
+        let piece = self.info(sticker).piece;
+        self.grip
+            .has_piece(puzzle_state, piece)
+            .unwrap_or_else(|| self.is_visible(piece))
+    }
+
+    /// Sets the hovered stickers, in order from front to back.
+    pub fn update_hovered_sticker(
+        &mut self,
+        stickers_under_cursor: impl IntoIterator<Item = (Sticker, ClickTwists)>,
+    ) {
+        let hovered = stickers_under_cursor
+            .into_iter()
+            .find(|&(sticker, _twists)| self.is_sticker_hoverable(sticker));
+
+        self.hovered_sticker = hovered.map(|(sticker, _twists)| sticker);
+        self.hovered_twists = hovered.map(|(_sticker, twists)| twists);
+    }
+    pub fn hovered_sticker(&self) -> Option<Sticker> {
+        self.hovered_sticker
+    }
+    pub fn hovered_twists(&self) -> Option<ClickTwists> {
+        self.hovered_twists
+    }
+    /// Returns a name for a piece, such as "UFR corner", listing the faces of
+    /// its stickers' colors.
+    pub fn piece_name(&self, piece: Piece) -> String {
+        let colors: String = self
+            .info(piece)
+            .stickers
+            .iter()
+            .map(|&sticker| self.info(self.info(sticker).color).symbol)
+            .collect();
+        let piece_type = &self.info(self.info(piece).piece_type).name;
+        format!("{colors} {piece_type}")
+    }
+    /// Returns the faces that a piece's stickers are currently on, in the same
+    /// order as in `piece_name()`.
+    pub fn piece_location_name(&self, piece: Piece) -> String {
+        let permutation = self.puzzle.permutation().as_slice();
+        self.info(piece)
+            .stickers
+            .iter()
+            .map(|&sticker| {
+                let position = permutation[sticker.0 as usize];
+                self.info(self.info(position).color).symbol
+            })
+            .collect()
+    }
+    /// Returns the center of the hovered sticker after projection, using the
+    /// geometry from the last call to `geometry()`.
+    pub fn hovered_sticker_center(&self) -> Option<Point2<f32>> {
+        let sticker = self.hovered_sticker?;
+        let geometry = self.cached_geometry.as_ref()?;
+        let sticker_geom = geometry.iter().find(|geom| geom.sticker == sticker)?;
+        let verts = &sticker_geom.verts;
+        let sum = verts.iter().fold(cgmath::vec2(0.0, 0.0), |sum, v| {
+            sum + cgmath::vec2(v.x, v.y)
+        });
+        Some(Point2::from_vec(sum / verts.len() as f32))
+    }
+
+    /// Returns the current animated view settings, given the static settings
+    /// stored in the preferences file.
+    pub fn view_prefs<'a>(
+        &mut self,
+        prefs: &'a impl PuzzlePreferences,
+    ) -> Cow<'a, ViewPreferences> {
+        // Use animated view settings.
+        let old_view_prefs = prefs.view(self.ty());
+        while self.view_settings_anim.queue.back() == Some(old_view_prefs) {
+            // No need to animate this one! It's the same as what we're
+            // currently displaying;
+            self.view_settings_anim.queue.pop_back();
+        }
+        if let Some(old) = self.view_settings_anim.queue.get(0) {
+            let new = self
+                .view_settings_anim
+                .queue
+                .get(1)
+                .unwrap_or(old_view_prefs);
+            let t = self.view_settings_anim.progress;
+            Cow::Owned(ViewPreferences::interpolate(old, new, t))
+        } else {
+            Cow::Borrowed(old_view_prefs)
+        }
+    }
+    pub fn geometry(
+        &mut self,
+        prefs: &impl PuzzlePreferences,
+    ) -> Arc<Vec<ProjectedStickerGeometry>> {
+        let view_prefs = self.view_prefs(prefs);
+
+        self.update_transient_rotation(prefs.interaction());
+
+        let mut params = StickerGeometryParams::new(
+            &view_prefs,
+            prefs.spacing_overrides(self.ty()),
+            self.ty(),
+            self.current_twist(),
+            self.view_angle.current * self.view_angle.queued_delta,
+        );
+        params.simultaneous_twist = self.current_simultaneous_twist();
+
+        if self.cached_geometry_params != Some(params) {
+            // Invalidate the cache.
+            self.cached_geometry = None;
+        }
+
+        self.cached_geometry_params = Some(params);
+
+        let ret = self.cached_geometry.take().unwrap_or_else(|| {
+            log::trace!("Regenerating puzzle geometry");
+
+            let state = self.displayed();
+            Arc::new(project_stickers(state, params, |sticker| {
+                let piece = self.info(sticker).piece;
+                let vis_piece = self.visual_piece_state(piece);
+                self.is_sticker_in_hidden_cell(state, sticker)
+                    || !self.is_sticker_hoverable(sticker)
+                        && vis_piece.opacity(prefs.opacity()) == 0.0
+            }))
+        });
+
+        self.cached_geometry = Some(Arc::clone(&ret));
+        ret
+    }
+    /// Returns the geometry for the ghost overlay, or `None` if it is
+    /// disabled. This uses the parameters from the last call to `geometry()`,
+    /// so it must be called after that.
+    pub fn ghost_geometry(
+        &mut self,
+        prefs: &impl PuzzlePreferences,
+    ) -> Option<Arc<Vec<ProjectedStickerGeometry>>> {
+        if !prefs.opacity().show_ghost {
+            return None;
+        }
+
+        // The ghost is never animated.
+        let params = StickerGeometryParams {
+            twist_animation: None,
+            simultaneous_twist: None,
+            ..self.cached_geometry_params?
+        };
+
+        if self.cached_ghost_geometry_params != Some(params) {
+            // Invalidate the cache.
+            self.cached_ghost_geometry = None;
+        }
+
+        self.cached_ghost_geometry_params = Some(params);
+
+        let ret = self.cached_ghost_geometry.take().unwrap_or_else(|| {
+            log::trace!("Regenerating ghost geometry");
+
+            let solved;
+            let state = match &self.ghost_target {
+                Some(target) => target,
+                None => {
+                    solved = Puzzle::new(self.ty());
+                    &solved
+                }
+            };
+            Arc::new(project_stickers(state, params, |_| false))
+        });
+
+        self.cached_ghost_geometry = Some(Arc::clone(&ret));
+        Some(ret)
+    }
+    /// Returns the geometry of an arbitrary state of this puzzle, viewed the
+    /// same way as the puzzle itself but without animation. This uses the
+    /// parameters from the last call to `geometry()`, so it returns `None` if
+    /// that has never been called.
+    pub fn preview_geometry(&self, state: &Puzzle) -> Option<Vec<ProjectedStickerGeometry>> {
+        let params = StickerGeometryParams {
+            twist_animation: None,
+            simultaneous_twist: None,
+            ..self.cached_geometry_params?
+        };
+        Some(project_stickers(state, params, |_| false))
+    }
+    /// Returns the state displayed by the ghost overlay, or `None` if it shows
+    /// the solved state.
+    pub fn ghost_target(&self) -> Option<&Puzzle> {
+        self.ghost_target.as_ref()
+    }
+    /// Sets the state displayed by the ghost overlay. `None` shows the solved
+    /// state.
+    pub fn set_ghost_target(&mut self, target: Option<Puzzle>) {
+        self.ghost_target = target;
+        self.cached_ghost_geometry = None;
+    }
+
+    /// Returns the pieces whose movement is shown by a trail.
+    pub fn tracked_pieces(&self) -> &[Piece] {
+        &self.tracked_pieces
+    }
+    /// Sets the pieces whose movement is shown by a trail, and clears the
+    /// existing trails.
+    pub fn set_tracked_pieces(&mut self, pieces: Vec<Piece>) {
+        self.tracked_pieces = pieces;
+        self.clear_trails();
+    }
+    /// Clears the trails of tracked pieces, so that they start from their
+    /// current locations.
+    pub fn clear_trails(&mut self) {
+        self.trail_states.clear();
+    }
+    /// Returns the trail of each tracked piece as a list of projected points,
+    /// covering at most `length` twists. The last point is the current
+    /// (possibly animated) location of the piece. This uses the parameters
+    /// from the last call to `geometry()`, so it must be called after that.
+    pub fn piece_trails(&self, length: usize) -> Vec<Vec<Point2<f32>>> {
+        let params = match self.cached_geometry_params {
+            Some(p) => p,
+            None => return vec![],
+        };
+        let static_params = StickerGeometryParams {
+            twist_animation: None,
+            simultaneous_twist: None,
+            ..params
+        };
+
+        // Don't show states that are still queued for animation.
+        let queued = self.twist_anim.queue.len().saturating_sub(1);
+        let end = self.trail_states.len().saturating_sub(queued);
+        let start = end.saturating_sub(length);
+
+        self.tracked_pieces
+            .iter()
+            .map(|&piece| {
+                self.trail_states
+                    .range(start..end)
+                    .filter_map(|state| projected_piece_center(state, piece, static_params))
+                    .chain(projected_piece_center(self.displayed(), piece, params))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the rotation (and scale) applied to the puzzle by the view
+    /// angle, using the parameters from the last call to `geometry()`.
+    pub fn view_transform(&self) -> Option<Matrix3<f32>> {
+        Some(self.cached_geometry_params?.view_transform)
+    }
+
+    /// Returns the projected center of each face of the solved puzzle, along
+    /// with its depth in the Z coordinate. Faces with no visible stickers are
+    /// skipped. This uses the parameters from the last call to `geometry()`,
+    /// so it must be called after that.
+    pub fn face_label_positions(&self) -> Vec<(Face, Point3<f32>)> {
+        let Some(params) = self.cached_geometry_params else {
+            return vec![];
+        };
+        let params = StickerGeometryParams {
+            twist_animation: None,
+            simultaneous_twist: None,
+            ..params
+        };
+
+        let solved = Puzzle::new(self.ty());
+        let mut sums = vec![(cgmath::vec3(0.0, 0.0, 0.0), 0); self.faces().len()];
+        for sticker in (0..self.stickers().len() as _).map(Sticker) {
+            let Some(sticker_geom) = solved.sticker_geometry(sticker, params) else {
+                continue;
+            };
+            let (sum, count) = &mut sums[self.info(sticker).color.0 as usize];
+            for &v in &sticker_geom.verts {
+                if let Some(p) = params.project_3d(v) {
+                    *sum += p.to_vec();
+                    *count += 1;
+                }
+            }
+        }
+
+        sums.into_iter()
+            .enumerate()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(i, (sum, count))| (Face(i as _), Point3::from_vec(sum / count as f32)))
+            .collect()
+    }
+    /// Returns the projected center of the largest front-facing polygon of
+    /// each visible sticker, along with the square root of its area, from back
+    /// to front. This uses the geometry from the last call to `geometry()`.
+    pub fn sticker_label_positions(&self) -> Vec<(Sticker, Point3<f32>, f32)> {
+        let Some(geometry) = &self.cached_geometry else {
+            return vec![];
+        };
+
+        geometry
+            .iter()
+            .filter_map(|sticker_geom| {
+                let (polygon, area) = sticker_geom
+                    .front_polygons
+                    .iter()
+                    .map(|polygon| {
+                        let twice_area: f32 = polygon
+                            .verts
+                            .iter()
+                            .cyclic_pairs()
+                            .map(|(a, b)| a.x * b.y - b.x * a.y)
+                            .sum();
+                        (polygon, twice_area.abs() / 2.0)
+                    })
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+                let sum = polygon
+                    .verts
+                    .iter()
+                    .fold(cgmath::vec3(0.0, 0.0, 0.0), |sum, v| sum + v.to_vec());
+                let center = Point3::from_vec(sum / polygon.verts.len() as f32);
+                Some((sticker_geom.sticker, center, area.sqrt()))
+            })
+            .collect()
+    }
+
+    /// Advances the puzzle geometry and internal state to the next frame, using
+    /// the given time delta between this frame and the last.
+    pub fn update_geometry(&mut self, delta: Duration, prefs: &InteractionPreferences) {
+        let delta = delta.as_secs_f32();
+        // `twist_duration` is in seconds (per one twist); `base_speed` is
+        // fraction of twist per frame.
+        let base_speed = delta / prefs.twist_duration;
+
+        // Animate view settings.
+        self.view_settings_anim.proceed(base_speed);
+
+        // Animate view angle offset.
+        if !self.view_angle.is_frozen {
+            let offset = &mut self.view_angle.current;
+
+            let decay_multiplier = VIEW_ANGLE_OFFSET_DECAY_RATE.powf(delta);
+            let new_offset = Quaternion::one().slerp(*offset, decay_multiplier);
+            if offset.s == new_offset.s {
+                // Stop the animation once we're not making any more progress.
+                *offset = Quaternion::one();
+            } else {
+                *offset = new_offset;
+            }
+        }
+
+        // Animate twist.
+        let anim = &mut self.twist_anim;
+        if anim.queue.is_empty() {
+            anim.queue_max = 0;
+            self.view_angle.queued_delta = Quaternion::one();
+        } else if !self.step_mode {
+            // Update queue_max.
+            anim.queue_max = std::cmp::max(anim.queue_max, anim.queue.len());
+
+            // Time left over after finishing one twist carries over to the
+            // next, so that the same twists are animated in the same amount of
+            // time regardless of framerate.
+            let mut remaining_time = delta;
+            while let Some(front) = self.twist_anim.queue.front() {
+                // Twist exponentially faster if there are/were more twists in
+                // the queue.
+                let queued = (self.twist_anim.queue.len() - 1) as f32;
+                let speed_mod = match prefs.dynamic_twist_speed {
+                    true => (queued * prefs.twist_speedup_factor).exp(),
+                    false => 1.0,
+                };
+                let duration_scale = twist_duration_scale(&front.state, front.twist, prefs);
+                let duration = prefs.twist_duration * duration_scale / speed_mod;
+
+                let progress_left = 1.0 - self.twist_anim.progress;
+                // Instantly complete twists that are too fast to see, which
+                // reduces unnecessary flashing, and also handle the case where
+                // something went wrong with the calculation (e.g., division by
+                // zero).
+                if duration >= prefs.min_twist_duration {
+                    let twist_delta = remaining_time / duration;
+                    if twist_delta < progress_left {
+                        self.twist_anim.progress += twist_delta;
+                        break;
+                    }
+                    remaining_time = (remaining_time - progress_left * duration).max(0.0);
+                }
+                // Complete the twist.
+                if let Some(q) = self.twist_anim.proceed(1.0) {
+                    self.view_angle.queued_delta = self.view_angle.queued_delta * q;
+                }
+            }
+        }
+    }
+    /// Advances the puzzle decorations (outlines and sticker opacities) to the
+    /// next frame, using the given time delta between this frame and the last.
+    /// Returns whether the decorations changed, in which case a redraw is
+    /// needed.
+    pub fn update_decorations(&mut self, delta: Duration, prefs: &impl PuzzlePreferences) -> bool {
+        let mut changed = false;
+
+        let delta = delta.as_secs_f32() / prefs.interaction().other_anim_duration;
+
+        for piece in (0..self.pieces().len() as _).map(Piece) {
+            let logical_state = self.logical_piece_state(piece);
+
+            let gripped = self.grip.has_piece(&self.puzzle, piece);
+            let hidden = logical_state.preview_hidden.unwrap_or(logical_state.hidden);
+            let stickers = &self.info(piece).stickers;
+            let target = VisualPieceState {
+                gripped: (gripped == Some(true)) as u8 as f32,
+                ungripped: (gripped == Some(false)) as u8 as f32,
+                hidden: hidden as u8 as f32,
+                selected: stickers.iter().any(|s| self.selection.contains(s)) as u8 as f32,
+                hovered: stickers.iter().any(|&s| Some(s) == self.hovered_sticker) as u8 as f32,
+
+                hidden_opacity_override: self.hidden_pieces_preview_opacity,
+            };
+
+            /// Adds or subtracts up to `delta` to reach `target`. Returns
+            /// `true` if `current` changed.
+            fn approach_target(current: &mut f32, target: f32, delta: f32) -> bool {
+                if *current == target {
+                    false
+                } else {
+                    if !delta.is_finite() {
+                        *current = target; // recovery from invalid state
+                    } else if *current + delta < target {
+                        *current += delta;
+                    } else if *current - delta > target {
+                        *current -= delta;
+                    } else {
+                        *current = target;
+                    }
+                    true
+                }
+            }
+
+            let current = &mut self.visual_piece_states[piece.0 as usize];
+            let was_visible = current.opacity(prefs.opacity()) != 0.0;
+            changed |= approach_target(&mut current.gripped, target.gripped, delta);
+            changed |= approach_target(&mut current.ungripped, target.ungripped, delta);
+            changed |= approach_target(&mut current.hidden, target.hidden, delta);
+            changed |= approach_target(&mut current.selected, target.selected, delta);
+            changed |= approach_target(&mut current.hovered, target.hovered, delta);
+            if current.hovered < target.hovered {
+                // Highlight hovered sticker instantly for better responsiveness.
+                changed |= approach_target(&mut current.hovered, target.hovered, f32::INFINITY);
+            }
+            if current.hidden_opacity_override != target.hidden_opacity_override {
+                // I don't know how to animate this easily, so don't bother trying.
+                current.hidden_opacity_override = target.hidden_opacity_override;
+                changed = true;
+            }
+            let is_visible = current.opacity(prefs.opacity()) != 0.0;
+            if was_visible != is_visible {
+                // If a piece changes from invisible to visible, then it might need to be
+                // re-added to the geometry, so invalidate the cache.
+                self.cached_geometry = None;
+            }
+        }
+
+        changed
+    }
+    /// Returns the logical state for a piece.
+    pub fn logical_piece_state(&self, piece: Piece) -> LogicalPieceState {
+        LogicalPieceState {
+            hidden: !self.visible_pieces[piece.0 as usize],
+            preview_hidden: self
+                .visible_pieces_preview
+                .as_ref()
+                .map(|bits| !bits[piece.0 as usize]),
+        }
+    }
+    /// Returns the visual state for a piece.
+    pub fn visual_piece_state(&self, piece: Piece) -> VisualPieceState {
+        self.visual_piece_states[piece.0 as usize]
+    }
+
+    pub fn last_filter(&self) -> &str {
+        &self.last_filter
+    }
+    pub fn set_last_filter(&mut self, filter_name: String) {
+        self.last_filter = filter_name
+    }
+
+    /// Returns the set of non-hidden pieces.
+    pub fn visible_pieces(&self) -> &BitSlice {
+        &self.visible_pieces
+    }
+    /// Returns a mutable reference to the set of non-hidden pieces.
+    pub fn visible_pieces_mut(&mut self) -> &mut BitSlice {
+        &mut self.visible_pieces
+    }
+    /// Sets the set of non-hidden pieces.
+    pub fn set_visible_pieces(&mut self, visible_pieces: &BitSlice) {
+        self.visible_pieces = visible_pieces.to_bitvec();
+        self.visible_pieces.resize(self.pieces().len(), false);
+    }
+    /// Sets the set of non-hidden pieces.
+    pub fn set_visible_pieces_preview(
+        &mut self,
+        visible_pieces: Option<&BitSlice>,
+        hidden_opacity: Option<f32>,
+    ) {
+        self.visible_pieces_preview = visible_pieces.map(|bits| {
+            let mut bv = bits.to_bitvec();
+            bv.resize(self.pieces().len(), false);
+            bv
+        });
+        self.hidden_pieces_preview_opacity = hidden_opacity;
+    }
+    /// Returns whether a piece is hidden.
+    pub fn is_visible(&self, piece: Piece) -> bool {
+        self.visible_pieces[piece.0 as usize]
+    }
+    /// Returns whether any piece is hidden.
+    pub fn is_any_piece_hidden(&self) -> bool {
+        !self.visible_pieces.all()
+    }
+
+    /// Returns the set of faces (cells, on 4D puzzles) whose stickers are not
+    /// drawn.
+    pub fn hidden_cells(&self) -> &BitSlice {
+        &self.hidden_cells
+    }
+    /// Sets whether the stickers on a face are drawn.
+    pub fn set_cell_hidden(&mut self, face: Face, hidden: bool) {
+        self.hidden_cells.set(face.0 as usize, hidden);
+        self.cached_geometry = None;
+    }
+    /// Hides every face except the given ones.
+    pub fn show_only_cells(&mut self, faces: &[Face]) {
+        self.hidden_cells.fill(true);
+        for face in faces {
+            self.hidden_cells.set(face.0 as usize, false);
+        }
+        self.cached_geometry = None;
+    }
+    /// Shows every face.
+    pub fn show_all_cells(&mut self) {
+        self.hidden_cells.fill(false);
+        self.cached_geometry = None;
+    }
+    /// Returns whether a sticker is currently on a hidden face of `state`.
+    fn is_sticker_in_hidden_cell(&self, state: &Puzzle, sticker: Sticker) -> bool {
+        if self.hidden_cells.not_any() {
+            return false;
+        }
+        let position = state.permutation().as_slice()[sticker.0 as usize];
+        self.hidden_cells[self.info(position).color.0 as usize]
+    }
+
+    /// Returns the set of selected stickers
+    pub fn selection(&self) -> &HashSet<Sticker> {
+        &self.selection
+    }
+    /// Toggles whether a sticker is selected.
+    pub fn toggle_select(&mut self, sticker: Sticker) {
+        if self.selection.contains(&sticker) {
+            self.deselect(sticker)
+        } else {
+            self.select(sticker)
+        }
+    }
+    /// Selects a sticker.
+    pub fn select(&mut self, sticker: Sticker) {
+        self.selection.insert(sticker);
+    }
+    /// Deselects a sticker.
+    pub fn deselect(&mut self, sticker: Sticker) {
+        self.selection.remove(&sticker);
+    }
+    /// Deselects all stickers.
+    pub fn deselect_all(&mut self) {
+        self.selection = HashSet::new();
+    }
+
+    /// Skips the animations for all twists in the queue.
+    pub fn skip_twist_animations(&mut self) {
+        self.twist_anim.queue.clear();
+    }
+    /// Returns whether twist animations only advance when stepped manually
+    /// using `step_twist_animation()`.
+    pub fn is_step_mode(&self) -> bool {
+        self.step_mode
+    }
+    /// Sets whether twist animations only advance when stepped manually.
+    pub fn set_step_mode(&mut self, step_mode: bool) {
+        self.step_mode = step_mode;
+    }
+    /// Advances the current twist animation by a fixed fraction of a twist,
+    /// regardless of how much time has passed.
+    pub fn step_twist_animation(&mut self) {
+        let remaining = 1.0 - self.twist_anim.progress;
+        // Avoid leaving a tiny sliver of the twist due to rounding error.
+        let delta = if remaining - TWIST_ANIMATION_STEP < 0.001 {
+            1.0 // Complete the twist.
+        } else {
+            TWIST_ANIMATION_STEP
+        };
+        if let Some(q) = self.twist_anim.proceed(delta) {
+            self.view_angle.queued_delta = self.view_angle.queued_delta * q;
+        }
+    }
+    /// Returns the number of twists waiting for the current twist animation to
+    /// finish.
+    pub fn queued_twist_count(&self) -> usize {
+        self.twist_anim.queue.len().saturating_sub(1)
+    }
+    /// Drops twists that are queued but have not started animating, reverting
+    /// the puzzle state and undo history accordingly. Returns the number of
+    /// twists canceled.
+    pub fn cancel_queued_twists(&mut self) -> usize {
+        let mut canceled = 0;
+        while self.twist_anim.queue.len() > 1 {
+            let Some(anim) = self.twist_anim.queue.back() else {
+                break;
+            };
+            let twist = anim.twist;
+            let twists: Vec<Twist> = std::iter::once(twist)
+                .chain(anim.simultaneous_twist)
+                .collect();
+            let undone_twist_time = anim.undone_twist_time;
+            // Rotations applied after this twist are not in the queue, so they
+            // can't be reverted.
+            if anim.view_angle_offset_delta != Quaternion::one() {
+                break;
+            }
+            // Reverting a twist or redo undoes it, and reverting an undo redoes
+            // it.
+            let events: Vec<PuzzleEvent> = match anim.history_change {
+                HistoryChange::Twist => {
+                    let entries: Vec<HistoryEntry> = twists.iter().map(|&t| t.into()).collect();
+                    if !self.undo_buffer.ends_with(&entries) {
+                        break;
+                    }
+                    self.undo_buffer
+                        .truncate(self.undo_buffer.len() - entries.len());
+                    self.twist_times
+                        .truncate(self.twist_times.len() - entries.len());
+                    entries.into_iter().rev().map(PuzzleEvent::Undo).collect()
+                }
+                HistoryChange::Undo => {
+                    let undone = self.redo_buffer.last().and_then(|entry| entry.twist());
+                    if undone.map(|t| self.reverse_twist(t)) != Some(twist) {
+                        break;
+                    }
+                    let Some(entry) = self.redo_buffer.pop() else {
+                        break;
+                    };
+                    self.undo_buffer.push(entry);
+                    self.twist_times.push(undone_twist_time);
+                    vec![PuzzleEvent::Redo(entry)]
+                }
+                HistoryChange::Redo => {
+                    if self.undo_buffer.last() != Some(&twist.into()) {
+                        break;
+                    }
+                    let Some(entry) = self.undo_buffer.pop() else {
+                        break;
+                    };
+                    self.redo_buffer.push(entry);
+                    self.twist_times.pop();
+                    vec![PuzzleEvent::Undo(entry)]
+                }
+            };
+            if let Some(anim) = self.twist_anim.queue.pop_back() {
+                self.puzzle = anim.state;
+            }
+            for &t in &twists {
+                self.update_solved_pieces(self.puzzle.pieces_affected_by_twist(t));
+            }
+            self.trail_states.pop_back();
+            canceled += twists.len();
+            for event in events {
+                self.subscriptions.emit(event);
+            }
+        }
+
+        if canceled > 0 {
+            self.mark_unsaved();
+            self.forget_undone_phase_splits();
+            self.forget_broken_undo_groups();
+            // Invalidate the cache.
+            self.cached_geometry = None;
+        }
+        canceled
+    }
+    /// Returns whether any twist or view settings animation is in progress.
+    pub fn is_animating(&self) -> bool {
+        !self.twist_anim.queue.is_empty() || !self.view_settings_anim.queue.is_empty()
+    }
+
+    /// Returns whether there is a twist or scramble to undo.
+    pub fn has_undo(&self) -> bool {
+        !self.undo_buffer.is_empty() || !self.scramble.is_empty()
+    }
+    /// Returns whether there is a twist or scramble to redo.
+    pub fn has_redo(&self) -> bool {
+        !self.redo_buffer.is_empty() || self.undone_scramble.is_some()
+    }
+
+    /// Undoes one twist, or a group of twists that were applied together. If
+    /// there are no twists to undo, undoes the scramble instead. Returns an
+    /// error if there was nothing to undo or the twist could not be applied to
+    /// the puzzle.
+    pub fn undo(&mut self) -> Result<(), &'static str> {
+        if self.undo_buffer.is_empty() && !self.scramble.is_empty() {
+            self.undo_scramble();
+            return Ok(());
+        }
+
+        let len = self.undo_buffer.len();
+        let count = match self.undo_groups.last() {
+            Some(group) if group.end == len => {
+                let count = group.len();
+                self.undo_groups.pop();
+                count
+            }
+            _ => 1,
+        };
+        for _ in 0..count {
+            let entry = self._undo()?;
+            self.subscriptions.emit(PuzzleEvent::Undo(entry));
+        }
+        if count > 1 {
+            let len = self.redo_buffer.len();
+            self.redo_groups.push(len - count..len);
+        }
+        Ok(())
+    }
+    /// Resets the puzzle to the solved state, keeping the scramble so that it
+    /// can be redone.
+    fn undo_scramble(&mut self) {
+        let scramble = std::mem::take(&mut self.scramble);
+        let scramble_state = self.scramble_state;
+        self.reset();
+        self.undone_scramble = Some((scramble, scramble_state));
+        self.mark_unsaved();
+    }
+    /// Undoes one twist without emitting an event, and returns the entry that
+    /// was undone.
+    fn _undo(&mut self) -> Result<HistoryEntry, &'static str> {
+        if let Some(entry) = self.undo_buffer.pop() {
+            self.mark_unsaved();
+            match entry {
+                HistoryEntry::Twist(twist) => {
+                    let rev = self.reverse_twist(twist);
+                    self.animate_twist(rev, HistoryChange::Undo)?;
+                }
+            }
+            self.redo_buffer.push(entry);
+            let twist_time = self.twist_times.pop().flatten();
+            if let Some(anim) = self.twist_anim.queue.back_mut() {
+                anim.undone_twist_time = twist_time;
+            }
+            self.forget_undone_phase_splits();
+            self.forget_broken_undo_groups();
+            Ok(entry)
+        } else {
+            Err("Nothing to undo")
+        }
+    }
+    /// Redoes one twist, or a group of twists that were undone together. If
+    /// there are no twists to redo, redoes the scramble that was undone
+    /// instead. Returns an error if there was nothing to redo or the twist
+    /// could not be applied to the puzzle.
+    pub fn redo(&mut self) -> Result<(), &'static str> {
+        if self.redo_buffer.is_empty() {
+            if let Some((scramble, scramble_state)) = self.undone_scramble.take() {
+                return self.scramble_with(&scramble, scramble_state);
+            }
+        }
+
+        let len = self.redo_buffer.len();
+        let count = match self.redo_groups.last() {
+            Some(group) if group.end == len => {
+                let count = group.len();
+                self.redo_groups.pop();
+                count
+            }
+            _ => 1,
+        };
+        for _ in 0..count {
+            let entry = self._redo()?;
+            self.subscriptions.emit(PuzzleEvent::Redo(entry));
+        }
+        if count > 1 {
+            let len = self.undo_buffer.len();
+            self.undo_groups.push(len - count..len);
+        }
+        Ok(())
+    }
+    /// Redoes one twist without emitting an event, and returns the entry that
+    /// was redone.
+    fn _redo(&mut self) -> Result<HistoryEntry, &'static str> {
+        if let Some(entry) = self.redo_buffer.pop() {
+            self.mark_unsaved();
+            match entry {
+                HistoryEntry::Twist(twist) => self.animate_twist(twist, HistoryChange::Redo)?,
+            }
+            self.undo_buffer.push(entry);
+            self.record_twist_time();
+            self.forget_broken_undo_groups();
+            Ok(entry)
+        } else {
+            Err("Nothing to redo")
+        }
+    }
+    /// Undoes or redoes twists until the undo history has exactly `len`
+    /// entries, ignoring undo groups. Returns an error if the redo history
+    /// does not reach that far.
+    pub fn seek_history(&mut self, len: usize) -> Result<(), &'static str> {
+        while self.undo_buffer.len() > len {
+            let entry = self._undo()?;
+            self.subscriptions.emit(PuzzleEvent::Undo(entry));
+        }
+        while self.undo_buffer.len() < len {
+            let entry = self._redo()?;
+            self.subscriptions.emit(PuzzleEvent::Redo(entry));
+        }
+        Ok(())
+    }
+
+    /// Marks the puzzle as saved
+    pub fn mark_saved(&mut self) {
+        self.is_unsaved = false;
+    }
+    /// Marks the puzzle as saved and copied to the clipboard.
+    pub fn mark_copied(&mut self) {
+        self.mark_saved();
+        self.is_unsaved_via_clipboard = false;
+    }
+    /// Marks the puzzle as saved in local storage.
+    pub fn mark_saved_in_local_storage(&mut self) {
+        self.is_unsaved_in_local_storage = false;
+    }
+    /// Marks the puzzle as unsaved.
+    pub fn mark_unsaved(&mut self) {
+        self.is_unsaved = true;
+        self.is_unsaved_via_clipboard = true;
+        self.is_unsaved_in_local_storage = true;
+    }
+    /// Returns whether the puzzle has been modified since the last time it was
+    /// marked as saved or copied to the clipboard.
+    pub fn is_unsaved(&self) -> bool {
+        self.is_unsaved && self.is_unsaved_via_clipboard
+    }
+    /// Returns whether the puzzle has been modified since the last time it was
+    /// saved in local storage.
+    pub fn is_unsaved_in_local_storage(&self) -> bool {
+        self.is_unsaved_in_local_storage
+    }
+    /// Returns whether the puzzle has been fully scrambled, even if it has been solved.
+    pub fn has_been_fully_scrambled(&self) -> bool {
+        match self.scramble_state {
+            ScrambleState::None => false,
+            ScrambleState::Partial => false,
+            ScrambleState::Full => true,
+            ScrambleState::Solved => {
+                self.scramble.len() >= self.scramble_moves_count()
+                    || self.scramble.len() > PARTIAL_SCRAMBLE_MOVE_COUNT_MAX
+            }
+        }
+    }
+    /// Returns whether the puzzle has been solved at some point.
+    pub fn has_been_solved(&self) -> bool {
+        self.scramble_state == ScrambleState::Solved
+    }
+    /// Returns whether the puzzle is currently in a solved configuration.
+    pub fn is_solved(&self) -> bool {
+        self.puzzle.is_solved()
+    }
+    /// Returns whether the puzzle is solved but not in its original
+    /// orientation, so that only a whole-puzzle rotation is left.
+    pub fn is_solved_up_to_rotation(&self) -> bool {
+        self.is_solved() && self.solved_piece_count < self.pieces().len()
+    }
+    /// Returns whole-puzzle rotations that return the puzzle to its original
+    /// orientation, or `None` if it is not solved up to rotation.
+    pub fn realignment_rotation(&self) -> Option<Vec<Twist>> {
+        if !self.is_solved_up_to_rotation() {
+            return None;
+        }
+        let target = color_faces(&self.puzzle)?;
+
+        // Search the orientations of a puzzle with one layer, which only has
+        // one piece, for the current orientation of the puzzle.
+        let one_layer_ty = match self.ty() {
+            PuzzleTypeEnum::Rubiks3D { .. } => PuzzleTypeEnum::Rubiks3D { layer_count: 1 },
+            PuzzleTypeEnum::Rubiks4D { .. } => PuzzleTypeEnum::Rubiks4D { layer_count: 1 },
+        };
+        let rotations: Vec<Twist> = (0..self.twist_axes().len() as _)
+            .flat_map(|axis| {
+                (0..self.twist_directions().len() as _).map(move |direction| Twist {
+                    axis: TwistAxis(axis),
+                    direction: TwistDirection(direction),
+                    layers: LayerMask(1),
+                })
+            })
+            .collect();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([(Puzzle::new(one_layer_ty), vec![])]);
+        while let Some((state, twists)) = queue.pop_front() {
+            let faces = color_faces(&state)?;
+            if faces == target {
+                // Undo the rotations that lead from the original orientation
+                // to the current one.
+                return Some(
+                    twists
+                        .iter()
+                        .rev()
+                        .map(|&twist| Twist {
+                            layers: self.all_layers(),
+                            ..self.reverse_twist(twist)
+                        })
+                        .collect(),
+                );
+            }
+            if !seen.insert(faces) {
+                continue;
+            }
+            for &twist in &rotations {
+                let mut next = state.clone();
+                if next.twist(twist).is_ok() {
+                    let mut next_twists = twists.clone();
+                    next_twists.push(twist);
+                    queue.push_back((next, next_twists));
+                }
+            }
+        }
+        None
+    }
+    /// Returns the fraction of pieces that are in their solved position and
+    /// orientation.
+    pub fn solved_fraction(&self) -> f32 {
+        self.solved_piece_count as f32 / self.pieces().len() as f32
+    }
+    /// Rechecks whether each of `pieces` is solved.
+    fn update_solved_pieces(&mut self, pieces: Vec<Piece>) {
+        for piece in pieces {
+            let solved = self.puzzle.is_piece_solved(piece);
+            if self.solved_pieces[piece.0 as usize] != solved {
+                self.solved_pieces.set(piece.0 as usize, solved);
+                if solved {
+                    self.solved_piece_count += 1;
+                } else {
+                    self.solved_piece_count -= 1;
+                }
+            }
+        }
+    }
+    /// Rechecks whether every piece is solved.
+    fn recompute_solved_pieces(&mut self) {
+        self.update_solved_pieces((0..self.pieces().len() as _).map(Piece).collect());
+    }
+    /// Checks whether the puzzle was scrambled and is now solved. If so,
+    /// updates the scramble state, and returns `true`.
+    pub fn check_just_solved(&mut self) -> bool {
+        let has_been_scrambled = matches!(
+            self.scramble_state,
+            ScrambleState::Partial | ScrambleState::Full,
+        );
+        if has_been_scrambled && self.is_solved() {
+            self.scramble_state = ScrambleState::Solved;
+            self.subscriptions.emit(PuzzleEvent::Solved);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Records a split for each phase whose pieces have just been solved, and
+    /// returns the new splits. `phases` pairs the name of each phase with the
+    /// set of pieces that must be solved to complete it. Each phase is only
+    /// recorded once per solve.
+    pub fn check_phase_splits<'a>(
+        &mut self,
+        phases: impl IntoIterator<Item = (&'a str, &'a BitSlice)>,
+    ) -> Vec<PhaseSplit> {
+        if !matches!(
+            self.scramble_state,
+            ScrambleState::Partial | ScrambleState::Full,
+        ) {
+            return vec![];
+        }
+
+        let mut new_splits = vec![];
+        for (name, piece_set) in phases {
+            if self.phase_splits.iter().any(|split| split.name == name) {
+                continue;
+            }
+            let pieces = piece_set
+                .iter_ones()
+                .map(|i| Piece(i as _))
+                .collect::<Vec<_>>();
+            if !pieces.is_empty() && self.puzzle.are_pieces_solved(&pieces) {
+                new_splits.push(PhaseSplit {
+                    name: name.to_owned(),
+                    twist_count: self.undo_buffer.len(),
+                    time: self.twist_times.last().copied().flatten(),
+                });
+            }
+        }
+        self.phase_splits.extend(new_splits.iter().cloned());
+        new_splits
+    }
+    /// Returns the phases of the solve that have been completed, in order.
+    pub fn phase_splits(&self) -> &[PhaseSplit] {
+        &self.phase_splits
+    }
+    /// Sets the phases of the solve that have been completed, such as when
+    /// loading a log file.
+    pub fn set_phase_splits(&mut self, splits: Vec<PhaseSplit>) -> Result<(), &'static str> {
+        if splits
+            .iter()
+            .any(|split| split.twist_count > self.undo_buffer.len())
+        {
+            return Err("Phase split is after the last twist");
+        }
+        self.phase_splits = splits;
+        Ok(())
+    }
+    /// Forgets phase splits that were completed by twists that have since been
+    /// undone, so that they can be recorded again.
+    fn forget_undone_phase_splits(&mut self) {
+        let len = self.undo_buffer.len();
+        self.phase_splits.retain(|split| split.twist_count <= len);
+    }
+    /// Forgets undo groups that are no longer entirely in the undo or redo
+    /// history.
+    fn forget_broken_undo_groups(&mut self) {
+        let undo_len = self.undo_buffer.len();
+        self.undo_groups.retain(|group| group.end <= undo_len);
+        let redo_len = self.redo_buffer.len();
+        self.redo_groups.retain(|group| group.end <= redo_len);
+    }
+
+    /// Returns the number of twists applied to the puzzle, not including the scramble.
+    pub fn twist_count(&self, metric: TwistMetric) -> usize {
+        metric.count_twists(
+            self,
+            self.undo_buffer
+                .iter()
+                .copied()
+                .filter_map(HistoryEntry::twist),
+        )
+    }
+    /// Returns the moves used to scramble the puzzle.
+    pub fn scramble(&self) -> &[Twist] {
+        &self.scramble
+    }
+    /// Returns the twists and other actions applied to the puzzle, not
+    /// including the scramble.
+    pub fn undo_buffer(&self) -> &[HistoryEntry] {
+        &self.undo_buffer
+    }
+    /// Returns the time at which each entry in the undo buffer was made,
+    /// relative to the start of the solve, or `None` if unknown.
+    pub fn twist_times(&self) -> &[Option<Duration>] {
+        &self.twist_times
+    }
+    /// Sets the time at which each entry in the undo buffer was made, such as
+    /// when loading a log file. The solve clock resumes from the last time.
+    pub fn set_twist_times(&mut self, times: Vec<Option<Duration>>) -> Result<(), &'static str> {
+        if times.len() != self.undo_buffer.len() {
+            return Err("Number of twist times does not match number of twists");
+        }
+        self.solve_time_offset = times.iter().rev().find_map(|&t| t).unwrap_or_default();
+        self.solve_start = None;
+        self.twist_times = times;
+        Ok(())
+    }
+    /// Marks the time of every entry in the undo buffer as unknown and resets
+    /// the solve clock.
+    pub fn clear_twist_times(&mut self) {
+        self.twist_times = vec![None; self.undo_buffer.len()];
+        self.solve_start = None;
+        self.solve_time_offset = Duration::ZERO;
+    }
+    /// Returns the current time on the solve clock, which starts on the first
+    /// twist after scrambling.
+    pub fn solve_clock(&self) -> Duration {
+        self.solve_time_offset
+            + self
+                .solve_start
+                .map_or(Duration::ZERO, |start| start.elapsed())
+    }
+    /// Returns whether the solve clock is running.
+    pub fn is_solve_clock_running(&self) -> bool {
+        self.solve_start.is_some()
+    }
+    /// Stops the solve clock at the given time. It starts again on the next
+    /// twist or when it is resumed.
+    pub fn pause_solve_clock(&mut self, at: Instant) {
+        if let Some(start) = self.solve_start.take() {
+            if at > start {
+                self.solve_time_offset += at - start;
+            }
+        }
+    }
+    /// Starts the solve clock again after it was paused. Does nothing if the
+    /// clock has not been started since scrambling.
+    pub fn resume_solve_clock(&mut self) {
+        if self.solve_start.is_none() && !self.solve_time_offset.is_zero() {
+            self.solve_start = Some(Instant::now());
+        }
+    }
+    /// Records the current time on the solve clock for the latest entry in the
+    /// undo buffer, starting the clock if it isn't already running.
+    fn record_twist_time(&mut self) {
+        let start = *self.solve_start.get_or_insert_with(Instant::now);
+        let time = self.solve_time_offset + start.elapsed();
+        self.twist_times.push(Some(time));
+    }
+    /// Returns the twists and other actions in the redo buffer.
+    pub fn redo_buffer(&self) -> &[HistoryEntry] {
+        &self.redo_buffer
+    }
+}
+
+/// Returns the average of the projected vertices of a piece's visible stickers.
+fn projected_piece_center(
+    state: &Puzzle,
+    piece: Piece,
+    params: StickerGeometryParams,
+) -> Option<Point2<f32>> {
+    let mut sum = cgmath::vec2(0.0, 0.0);
+    let mut count = 0;
+    for &sticker in &state.info(piece).stickers {
+        let Some(sticker_geom) = state.sticker_geometry(sticker, params) else {
+            continue;
+        };
+        for &v in &sticker_geom.verts {
+            if let Some(p) = params.project_3d(v) {
+                sum += cgmath::vec2(p.x, p.y);
+                count += 1;
+            }
+        }
+    }
+    (count > 0).then(|| Point2::new(sum.x / count as f32, sum.y / count as f32))
+}
+
+/// Projects the stickers of a puzzle state and sorts them by depth, skipping
+/// any sticker for which `skip` returns `true`.
+fn project_stickers(
+    state: &Puzzle,
+    params: StickerGeometryParams,
+    mut skip: impl FnMut(Sticker) -> bool,
+) -> Vec<ProjectedStickerGeometry> {
+    // Project stickers.
+    let mut sticker_geometries: Vec<ProjectedStickerGeometry> = vec![];
+    for sticker in (0..state.stickers().len() as _).map(Sticker) {
+        if skip(sticker) {
+            continue;
+        }
+
+        // Compute geometry, including vertex positions before 3D
+        // perspective projection.
+        let sticker_geom = match state.sticker_geometry(sticker, params) {
+            Some(s) => s,
+            None => continue, // invisible; skip this sticker
+        };
+
+        // Compute vertex positions after 3D perspective projection.
+        let projected_verts = match sticker_geom
+            .verts
+            .iter()
+            .map(|&v| params.project_3d(v))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(s) => s,
+            None => continue, // behind camera; skip this sticker
+        };
+
+        let mut projected_front_polygons = vec![];
+        let mut projected_back_polygons = vec![];
+
+        for (indices, twists) in sticker_geom
+            .polygon_indices
+            .iter()
+            .zip(sticker_geom.polygon_twists)
+        {
+            let projected_normal = geometry::polygon_normal_from_indices(&projected_verts, indices);
+            if projected_normal.z > 0.0 {
+                // This polygon is front-facing.
+                let lighting_normal =
+                    geometry::polygon_normal_from_indices(&sticker_geom.verts, indices).normalize();
+                let illumination = params.ambient_light + lighting_normal.dot(params.light_vector);
+                projected_front_polygons.push(geometry::polygon_from_indices(
+                    &projected_verts,
+                    &sticker_geom.vert_alphas,
+                    indices,
+                    illumination,
+                    twists,
+                ));
+            } else {
+                // This polygon is back-facing.
+                let illumination = 0.0; // don't care
+                projected_back_polygons.push(geometry::polygon_from_indices(
+                    &projected_verts,
+                    &sticker_geom.vert_alphas,
+                    indices,
+                    illumination,
+                    ClickTwists::default(), // don't care
+                ));
+            }
+        }
+
+        let (min_bound, max_bound) = util::min_and_max_bound(&projected_verts);
+
+        sticker_geometries.push(ProjectedStickerGeometry {
+            sticker,
+
+            verts: projected_verts.into_boxed_slice(),
+            min_bound,
+            max_bound,
+
+            front_polygons: projected_front_polygons.into_boxed_slice(),
+            back_polygons: projected_back_polygons.into_boxed_slice(),
+        });
+    }
+
+    // Sort stickers by depth.
+    geometry::sort_by_depth(&mut sticker_geometries);
+
+    sticker_geometries
+}
+
+/// Sticker colors being edited in the state editor.
+#[derive(Debug, Clone)]
+struct PaintState {
+    /// Color of each sticker.
+    colors: Vec<Face>,
+    /// Color to paint with.
+    brush: Face,
+}
+
+/// Returns the face that each color is on, or `None` if some color is on more
+/// than one face.
+fn color_faces(puzzle: &Puzzle) -> Option<Vec<Face>> {
+    let mut ret = vec![None; puzzle.faces().len()];
+    for (i, &position) in puzzle.permutation().as_slice().iter().enumerate() {
+        let color = puzzle.info(Sticker(i as _)).color;
+        let face = puzzle.info(position).color;
+        match ret[color.0 as usize] {
+            None => ret[color.0 as usize] = Some(face),
+            Some(f) if f == face => (),
+            Some(_) => return None,
+        }
+    }
+    ret.into_iter().collect()
+}
+
+#[derive(Debug, Default, Clone)]
+struct TwistAnimationState {
+    /// Queue of twist animations to be displayed.
+    queue: VecDeque<TwistAnimation>,
+    /// Maximum number of animations in the queue (reset when queue is empty).
+    queue_max: usize,
+    /// Progress of the animation in the current twist, from 0.0 to 1.0.
+    progress: f32,
+}
+impl TwistAnimationState {
+    #[must_use]
+    fn proceed(&mut self, delta_t: f32) -> Option<Quaternion<f32>> {
+        self.progress += delta_t;
+        if self.progress >= 1.0 {
+            self.progress = 0.0;
+            self.queue
+                .pop_front()
+                .map(|anim| anim.view_angle_offset_delta)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TwistAnimation {
+    /// Puzzle state before twist.
+    state: Puzzle,
+    /// Twist to animate.
+    twist: Twist,
+    /// Change to the undo history that queued the twist.
+    history_change: HistoryChange,
+    /// Delta to apply to the view angle before animating.
+    view_angle_offset_delta: Quaternion<f32>,
+    /// Twist on the opposite axis to animate at the same time.
+    simultaneous_twist: Option<Twist>,
+    /// Time of the twist that was undone, if `history_change` is
+    /// `HistoryChange::Undo`, so that it can be restored if the undo is
+    /// canceled.
+    undone_twist_time: Option<Duration>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum HistoryChange {
+    Twist,
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ViewSettingsAnimState {
+    /// Queue of view settings animations to be displayed. Each element is a
+    /// pair of the view settings before the animation. Once there is only one
+    /// element in the queue, the animation will proceed to the view settings
+    /// stored in the application preferences and then stop there.
+    queue: VecDeque<ViewPreferences>,
+    /// Progress of the current animation, from 0.0 to 1.0.
+    progress: f32,
+}
+impl ViewSettingsAnimState {
+    /// Removes intermediate animations.
+    ///
+    /// For example, if the user switches from preset A to preset B, then we
+    /// want to animate from A to B. If during the animation from A to B, the
+    /// user selects preset C, we should finish the animation from A to B, then
+    /// animate from B to C. But if the user also selects preset D during that
+    /// animation, then we shouldn't animate from A to B to C to D; we can skip
+    /// C. In that example, this method would replace the animations from B to C
+    /// and C to D with a single animation from B to D.
+    fn remove_intermediate(&mut self) {
+        // In the example above, preset D is stored in the current settings, and
+        // presets A and B (what we're currently animating between) are at the
+        // front of the queue, so just delete everything in the queue after
+        // index 2.
+        self.queue.truncate(2);
+    }
+    fn proceed(&mut self, delta_t: f32) {
+        if self.queue.is_empty() {
+            self.progress = 0.0;
+        } else {
+            self.remove_intermediate();
+            self.progress += delta_t;
+            if self.progress >= 1.0 {
+                self.queue.pop_front();
+                self.progress = 0.0;
+            }
+        }
+    }
+}
+
+/// The following rotations are applied to the whole puzzle in order before
+/// rendering:
+///
+/// 1. `queued_delta`
+/// 2. `current`
+/// 3. `view_prefs.view_angle` (from `ViewPreferences`)
+#[derive(Debug, Clone)]
+pub struct ViewAngleAnimState {
+    /// Cumulative view angle offset delta from all the twists in the twist
+    /// queue.
+    queued_delta: Quaternion<f32>,
+    /// View angle offset compared to the latest puzzle state.
+    current: Quaternion<f32>,
+
+    /// Full-puzzle rotations to silently apply next time the user grips or
+    /// twists the puzzle.
+    transient_rotation: Option<(Vec<Twist>, Quaternion<f32>)>,
+    /// Whether to freeze the view angle offset, versus animating it back to
+    /// zero.
+    is_frozen: bool,
+}
+impl Default for ViewAngleAnimState {
+    fn default() -> Self {
+        Self {
+            queued_delta: Quaternion::one(),
+            current: Quaternion::one(),
+
+            transient_rotation: None,
+            is_frozen: false,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HistoryEntry {
+    Twist(Twist),
+}
+impl From<Twist> for HistoryEntry {
+    fn from(twist: Twist) -> Self {
+        Self::Twist(twist)
+    }
+}
+impl HistoryEntry {
+    pub fn twist(self) -> Option<Twist> {
+        match self {
+            HistoryEntry::Twist(twist) => Some(twist),
+        }
+    }
+    pub fn to_string(self, notation: &NotationScheme) -> String {
+        match self {
+            HistoryEntry::Twist(twist) => notation.twist_to_string(twist),
+        }
+    }
+}
+
+/// Returns the factor by which to scale the duration of a twist animation,
+/// based on how many layers it moves.
+fn twist_duration_scale(puzzle: &Puzzle, twist: Twist, prefs: &InteractionPreferences) -> f32 {
+    if twist.layers == puzzle.all_layers() {
+        prefs.rotation_duration_scale
+    } else {
+        let extra_layers = twist.layers.count().saturating_sub(1) as f32;
+        1.0 + extra_layers * prefs.extra_layer_twist_duration
+    }
+}
+
+/// Record of when a phase of a solve was completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseSplit {
+    /// Name of the phase.
+    pub name: String,
+    /// Number of entries in the undo history when the phase was completed.
+    pub twist_count: usize,
+    /// Time on the solve clock when the phase was completed, or `None` if
+    /// unknown.
+    pub time: Option<Duration>,
+}
+
+/// Whether the puzzle has been scrambled.
+#[derive(FromPrimitive, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ScrambleState {
+    /// Unscrambled.
+    #[default]
+    None = 0,
+    /// Some small number of scramble twists.
+    Partial = 1,
+    /// Fully scrambled.
+    Full = 2,
+    /// Was solved by user even if not currently solved.
+    Solved = 3,
+}
+
+/// Which parts of the puzzle to twist.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Grip {
+    pub axes: HashSet<TwistAxis>,
+    pub layers: Option<LayerMask>,
+}
+impl BitOr<&Grip> for Grip {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: &Grip) -> Self::Output {
+        self |= rhs;
+        self
+    }
+}
+impl BitOrAssign<&Grip> for Grip {
+    fn bitor_assign(&mut self, rhs: &Self) {
+        self.axes.extend(&rhs.axes);
+        self.layers = match (self.layers, rhs.layers) {
+            (None, None) => None,
+            (None, Some(l)) | (Some(l), None) => Some(l),
+            (Some(l1), Some(l2)) => Some(l1 | l2),
+        }
+    }
+}
+impl Grip {
+    pub fn with_axis(axis: TwistAxis) -> Self {
+        Self {
+            axes: HashSet::from_iter([axis]),
+            ..Self::default()
+        }
+    }
+    pub fn with_layers(layers: LayerMask) -> Self {
+        Self {
+            layers: Some(layers),
+            ..Self::default()
+        }
+    }
+
+    pub fn toggle_axis(&mut self, axis: TwistAxis, exclusive: bool) {
+        if self.axes.contains(&axis) {
+            if exclusive {
+                self.axes = HashSet::new();
+            } else {
+                self.axes.remove(&axis);
+            }
+        } else if exclusive {
+            self.axes = HashSet::from_iter([axis]);
+        } else {
+            self.axes.insert(axis);
+        }
+    }
+    pub fn toggle_layer(&mut self, layer: u8, exclusive: bool) {
+        let l = self.layers.get_or_insert(LayerMask::default());
+        *l ^= LayerMask(1 << layer);
+        if exclusive {
+            *l &= LayerMask(1 << layer);
+        }
+        if *l == LayerMask::default() {
+            self.layers = None;
+        }
+    }
+
+    /// Returns whether the twist selection includes a particular piece.
+    pub fn has_piece(&self, puzzle: &dyn PuzzleState, piece: Piece) -> Option<bool> {
+        if self.axes.is_empty() {
+            None
+        } else {
+            let layer_mask = self.layers.unwrap_or_default();
+            Some(
+                self.axes
+                    .iter()
+                    .map(|&twist_axis| puzzle.layer_from_twist_axis(twist_axis, piece))
+                    .all(|layer| layer_mask[layer]),
+            )
+        }
+    }
+}
+
+/// Boolean piece state, such as whether a piece is hidden.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LogicalPieceState {
+    pub hidden: bool,
+    pub preview_hidden: Option<bool>,
+}
+
+/// Floating-point piece state, such as whether a piece is hidden.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct VisualPieceState {
+    pub gripped: f32,
+    pub ungripped: f32,
+    pub hidden: f32,
+    pub selected: f32,
+    pub hovered: f32,
+
+    hidden_opacity_override: Option<f32>,
+}
+impl VisualPieceState {
+    pub fn opacity(self, pr: &OpacityPreferences) -> f32 {
+        let full_opacity = f32::max(
+            self.hovered,
+            self.gripped
+                * if pr.unhide_grip {
+                    1.0
+                } else {
+                    1.0 - self.hidden
+                },
+        );
+        let hidden_opacity = self.hidden_opacity_override.unwrap_or(pr.hidden);
+
+        let mut ret = 1.0;
+        // In order from lowest to highest priority:
+        ret = util::mix(ret, hidden_opacity, self.hidden);
+        ret *= pr.base;
+        ret = util::mix(ret, pr.selected, self.selected);
+        ret = util::mix(ret, 1.0, full_opacity);
+        if pr.base * pr.ungripped < ret {
+            ret = util::mix(ret, pr.base * pr.ungripped, self.ungripped);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_twist_animation_framerate_independent() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let twists = ty.parse_twists("R U F' L").unwrap();
+        let prefs = InteractionPreferences {
+            twist_duration: 0.2,
+            ..Default::default()
+        };
+
+        let animate_at_fps = |fps: u32| {
+            let mut puzzle = PuzzleController::new(ty);
+            for &twist in &twists {
+                puzzle.twist(twist).unwrap();
+            }
+            // Animate for a quarter of a second.
+            let frame_time = Duration::from_secs_f32(1.0 / fps as f32);
+            for _ in 0..fps / 4 {
+                puzzle.update_geometry(frame_time, &prefs);
+            }
+            (puzzle.twist_anim.queue.len(), puzzle.twist_anim.progress)
+        };
+
+        let (queue_len_60, progress_60) = animate_at_fps(60);
+        let (queue_len_240, progress_240) = animate_at_fps(240);
+        assert!(queue_len_60 < twists.len());
+        assert_eq!(queue_len_60, queue_len_240);
+        assert!((progress_60 - progress_240).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_pause_solve_clock() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+
+        // The clock can't be resumed before it has started.
+        puzzle.resume_solve_clock();
+        assert!(!puzzle.is_solve_clock_running());
+
+        puzzle.twist(ty.parse_twists("R").unwrap()[0]).unwrap();
+        assert!(puzzle.is_solve_clock_running());
+        puzzle.pause_solve_clock(Instant::now() + Duration::from_millis(10));
+        assert!(!puzzle.is_solve_clock_running());
+        let paused_time = puzzle.solve_clock();
+        assert_eq!(puzzle.solve_clock(), paused_time);
+
+        puzzle.resume_solve_clock();
+        assert!(puzzle.is_solve_clock_running());
+        assert!(puzzle.solve_clock() >= paused_time);
+    }
+
+    #[test]
+    fn test_undo_groups() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let twists = ty.parse_twists("R U R' U'").unwrap();
+        let mut puzzle = PuzzleController::new(ty);
+
+        puzzle.twist(twists[0]).unwrap();
+        puzzle.twist_group(&twists).unwrap();
+        assert_eq!(puzzle.undo_buffer().len(), 5);
+
+        // The group is undone and redone in one step.
+        puzzle.undo().unwrap();
+        assert_eq!(puzzle.undo_buffer().len(), 1);
+        puzzle.redo().unwrap();
+        assert_eq!(puzzle.undo_buffer().len(), 5);
+        puzzle.undo().unwrap();
+        puzzle.undo().unwrap();
+        assert!(puzzle.undo_buffer().is_empty());
+        puzzle.redo().unwrap();
+        assert_eq!(puzzle.undo_buffer().len(), 1);
+
+        // Seeking into the middle of a group breaks it up.
+        puzzle.redo().unwrap();
+        puzzle.seek_history(3).unwrap();
+        puzzle.undo().unwrap();
+        assert_eq!(puzzle.undo_buffer().len(), 2);
+    }
+
+    #[test]
+    fn test_simultaneous_opposite_twists() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let a = Twist {
+                axis: TwistAxis(0),
+                direction: TwistDirection(0),
+                layers: LayerMask(1),
+            };
+            let b = Twist {
+                axis: ty.opposite_twist_axis(a.axis).unwrap(),
+                ..a
+            };
+            let mut expected = Puzzle::new(ty);
+            expected.twist(a).unwrap();
+            expected.twist(b).unwrap();
+
+            // Both twists are applied and animated together.
+            let mut puzzle = PuzzleController::new(ty);
+            puzzle.twist_simultaneous([a, b]).unwrap();
+            assert_eq!(puzzle.latest(), &expected);
+            assert_eq!(puzzle.twist_anim.queue.len(), 1);
+            assert_eq!(
+                puzzle.current_simultaneous_twist(),
+                puzzle.undo_buffer()[1].twist(),
+            );
+
+            // They are undone in one step.
+            assert_eq!(puzzle.undo_buffer().len(), 2);
+            puzzle.undo().unwrap();
+            assert!(puzzle.undo_buffer().is_empty());
+            assert_eq!(puzzle.latest(), &Puzzle::new(ty));
+
+            // Canceling the queued pair reverts both twists.
+            puzzle.skip_twist_animations();
+            puzzle.twist(a).unwrap();
+            puzzle.twist_simultaneous([a, b]).unwrap();
+            assert_eq!(puzzle.cancel_queued_twists(), 2);
+            assert_eq!(puzzle.undo_buffer().len(), 1);
+
+            // Twists that are not on opposite axes or that share layers are
+            // rejected.
+            assert!(puzzle.twist_simultaneous([a, a]).is_err());
+            let whole_puzzle = Twist {
+                layers: ty.all_layers(),
+                ..b
+            };
+            assert!(puzzle.twist_simultaneous([a, whole_puzzle]).is_err());
+            assert_eq!(puzzle.undo_buffer().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_reset_to_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let twists = ty.parse_twists("R U F").unwrap();
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle.reset_to_scramble().unwrap_err();
+
+        puzzle
+            .scramble_with(&twists, ScrambleState::Partial)
+            .unwrap();
+        let scrambled = puzzle.latest().clone();
+        puzzle.twist(twists[0]).unwrap();
+        puzzle.twist(twists[1]).unwrap();
+
+        puzzle.reset_to_scramble().unwrap();
+        assert_eq!(puzzle, scrambled);
+        assert_eq!(puzzle.scramble(), twists);
+        assert_eq!(puzzle.scramble_state(), ScrambleState::Partial);
+        assert!(puzzle.undo_buffer().is_empty());
+    }
+
+    #[test]
+    fn test_undo_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let twists = ty.parse_twists("R U F").unwrap();
+        let mut puzzle = PuzzleController::new(ty);
+
+        puzzle.scramble_with(&twists, ScrambleState::Full).unwrap();
+        assert!(puzzle.has_undo());
+        puzzle.undo().unwrap();
+        assert!(puzzle.is_solved());
+        assert_eq!(puzzle.scramble_state(), ScrambleState::None);
+        assert!(!puzzle.has_undo());
+
+        puzzle.redo().unwrap();
+        assert_eq!(puzzle.scramble(), twists);
+        assert_eq!(puzzle.scramble_state(), ScrambleState::Full);
+        assert!(!puzzle.has_redo());
+
+        // Twisting after undoing the scramble discards it.
+        puzzle.undo().unwrap();
+        puzzle.twist(twists[0]).unwrap();
+        assert!(!puzzle.has_redo());
+    }
+
+    #[test]
+    fn test_seeded_scramble() {
+        let puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks4D { layer_count: 3 });
+        let options = ScrambleOptions::default();
+        let scramble = puzzle.generate_seeded_scramble(100, &options, 42).unwrap();
+        assert_eq!(scramble.len(), 100);
+        assert_eq!(
+            puzzle.generate_seeded_scramble(100, &options, 42).unwrap(),
+            scramble,
+        );
+        assert_ne!(
+            puzzle.generate_seeded_scramble(100, &options, 43).unwrap(),
+            scramble,
+        );
+    }
+
+    #[test]
+    fn test_scramble_twist_count() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let puzzle = PuzzleController::new(ty);
+        for n in [0, 1, 20, 100] {
+            let scramble = puzzle
+                .generate_scramble(n, &ScrambleOptions::default())
+                .unwrap();
+            assert_eq!(scramble.len(), n);
+        }
+
+        // Corners-only scrambles count blocks instead of twists.
+        let options = ScrambleOptions {
+            corners_only: true,
+            ..ScrambleOptions::default()
+        };
+        assert!(puzzle.generate_scramble(0, &options).unwrap().is_empty());
+        let scramble = puzzle.generate_scramble(1, &options).unwrap();
+        assert!((2..=MAX_CORNERS_ONLY_BLOCK_LEN).contains(&scramble.len()));
+    }
+
+    #[test]
+    fn test_corners_only_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let options = ScrambleOptions {
+            corners_only: true,
+            ..ScrambleOptions::default()
+        };
+        let desc = ty.desc();
+        let mut puzzle = PuzzleController::new(ty);
+        for _ in 0..10 {
+            // Each of the 3 blocks is kept whole.
+            let scramble = puzzle.generate_scramble(3, &options).unwrap();
+            assert!(scramble.len() >= 3 * 2);
+            assert!(scramble.len() <= 3 * MAX_CORNERS_ONLY_BLOCK_LEN);
+            let mut state = Puzzle::new(ty);
+            for twist in scramble {
+                state.twist(twist).unwrap();
+            }
+            assert!(!state.is_solved());
+            for piece in (0..desc.pieces().len() as _).map(Piece) {
+                if desc.info(desc.info(piece).piece_type).name != "corner" {
+                    assert!(state.is_piece_solved(piece));
+                }
+            }
+
+            puzzle.scramble_full(&options).unwrap();
+            assert!(!puzzle.is_solved());
+            assert_eq!(puzzle.scramble_state(), ScrambleState::Partial);
+        }
+
+        // A puzzle with one layer has no corners.
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 1 };
+        assert!(PuzzleController::new(ty)
+            .generate_scramble(20, &options)
+            .is_err());
+    }
+
+    #[test]
+    fn test_one_layer_puzzles() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 1 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 1 },
+        ] {
+            // Every twist rotates the whole puzzle, so it is always solved,
+            // and solved up to rotation whenever it is rotated.
+            let mut puzzle = PuzzleController::new(ty);
+            let twist = Twist::from_rng(ty);
+            puzzle.twist(twist).unwrap();
+            assert!(puzzle.is_solved());
+            assert!(puzzle.is_solved_up_to_rotation());
+            for rotation in puzzle.realignment_rotation().unwrap() {
+                puzzle.twist(rotation).unwrap();
+            }
+            assert!(puzzle.is_solved());
+            assert!(!puzzle.is_solved_up_to_rotation());
+
+            // Scrambles never leave the puzzle in its original orientation.
+            for _ in 0..50 {
+                puzzle.scramble_full(&ScrambleOptions::default()).unwrap();
+                assert!(puzzle.is_solved_up_to_rotation());
+                let options = ScrambleOptions::default();
+                let scramble = puzzle.generate_scramble(3, &options).unwrap();
+                let mut state = Puzzle::new(ty);
+                for twist in scramble {
+                    state.twist(twist).unwrap();
+                }
+                assert!(!state.is_piece_solved(Piece(0)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_puzzle_scrambles() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 2 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 2 },
+        ] {
+            let axes = ty.essential_twist_axes();
+            assert_eq!(axes.len(), ty.desc().twist_axes().len() / 2);
+            for &axis in &axes {
+                assert!(!axes.contains(&ty.opposite_twist_axis(axis).unwrap()));
+            }
+
+            // Scrambles keep the piece opposite every essential axis fixed.
+            let puzzle = PuzzleController::new(ty);
+            let scramble = puzzle
+                .generate_scramble(50, &ScrambleOptions::default())
+                .unwrap();
+            let mut state = Puzzle::new(ty);
+            for twist in scramble {
+                assert!(axes.contains(&twist.axis));
+                assert_eq!(twist.layers, LayerMask(1));
+                state.twist(twist).unwrap();
+            }
+            let fixed = (0..ty.desc().pieces().len() as _)
+                .map(Piece)
+                .filter(|&piece| state.is_piece_solved(piece))
+                .count();
+            assert!(fixed >= 1);
+        }
+
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        assert_eq!(ty.essential_twist_axes().len(), 6);
+    }
+
+    #[test]
+    fn test_two_layer_rotation() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let mut puzzle = PuzzleController::new(ty);
+        // Rotations do not unsolve the puzzle.
+        for twist in ty.parse_twists("Rw y").unwrap() {
+            assert_eq!(twist.layers, ty.all_layers());
+            puzzle.twist(twist).unwrap();
+            assert!(puzzle.is_solved());
+        }
+        puzzle.twist(ty.parse_twists("R").unwrap()[0]).unwrap();
+        assert!(!puzzle.is_solved());
+    }
+
+    #[test]
+    fn test_solved_fraction() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        assert_eq!(puzzle.solved_fraction(), 1.0);
+
+        for twist in ty.parse_twists("R U R' U'").unwrap() {
+            puzzle.twist(twist).unwrap();
+            let expected = (0..puzzle.pieces().len() as _)
+                .map(Piece)
+                .filter(|&piece| puzzle.latest().is_piece_solved(piece))
+                .count() as f32
+                / puzzle.pieces().len() as f32;
+            assert_eq!(puzzle.solved_fraction(), expected);
+            assert!(puzzle.solved_fraction() < 1.0);
+        }
+
+        puzzle.undo().unwrap();
+        puzzle.undo().unwrap();
+        puzzle.undo().unwrap();
+        puzzle.undo().unwrap();
+        assert_eq!(puzzle.solved_fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_realignment_rotation() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 2 },
+        ] {
+            let mut puzzle = PuzzleController::new(ty);
+            assert!(!puzzle.is_solved_up_to_rotation());
+            assert_eq!(puzzle.realignment_rotation(), None);
+
+            // Rotate the whole puzzle around two different axes, like `x y`
+            // on a 3D puzzle.
+            for axis in [0, 2] {
+                puzzle
+                    .twist(Twist {
+                        axis: TwistAxis(axis),
+                        direction: TwistDirection(0),
+                        layers: ty.all_layers(),
+                    })
+                    .unwrap();
+            }
+            assert!(puzzle.is_solved_up_to_rotation());
+
+            for twist in puzzle.realignment_rotation().unwrap() {
+                puzzle.twist(twist).unwrap();
+            }
+            assert!(puzzle.is_solved());
+            assert_eq!(puzzle.solved_fraction(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_hidden_cells() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let desc = ty.desc();
+        let mut puzzle = PuzzleController::new(ty);
+        let face = |symbol| {
+            Face(
+                desc.faces()
+                    .iter()
+                    .position(|f| f.symbol == symbol)
+                    .unwrap() as _,
+            )
+        };
+        let shown_stickers = |puzzle: &PuzzleController| {
+            (0..desc.stickers().len() as _)
+                .map(Sticker)
+                .filter(|&s| !puzzle.is_sticker_in_hidden_cell(puzzle.latest(), s))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shown_stickers(&puzzle).len(), desc.stickers().len());
+
+        puzzle.show_only_cells(&[face("R"), face("L")]);
+        let before = shown_stickers(&puzzle);
+        assert_eq!(before.len(), 18);
+
+        // Hidden cells are positions, so different stickers are shown after
+        // a twist.
+        puzzle.twist(ty.parse_twists("U").unwrap()[0]).unwrap();
+        let after = shown_stickers(&puzzle);
+        assert_eq!(after.len(), 18);
+        assert_ne!(before, after);
+
+        puzzle.set_cell_hidden(face("L"), true);
+        assert_eq!(shown_stickers(&puzzle).len(), 9);
+        puzzle.show_all_cells();
+        assert!(puzzle.hidden_cells().not_any());
+    }
+
+    #[test]
+    fn test_piece_names() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        let corners: Vec<Piece> = (0..puzzle.pieces().len() as _)
+            .map(Piece)
+            .filter(|&piece| puzzle.info(piece).stickers.len() == 3)
+            .collect();
+        for &piece in &corners {
+            let name = puzzle.piece_name(piece);
+            assert!(name.ends_with(" corner"), "{name}");
+            assert!(name.starts_with(&puzzle.piece_location_name(piece)));
+        }
+
+        puzzle.twist(ty.parse_twists("U").unwrap()[0]).unwrap();
+        let moved = corners
+            .iter()
+            .filter(|&&piece| {
+                !puzzle
+                    .piece_name(piece)
+                    .starts_with(&puzzle.piece_location_name(piece))
+            })
+            .count();
+        assert_eq!(moved, 4);
+    }
+}
@@ -0,0 +1,159 @@
+//! Textual description of a puzzle state, for screen readers and other
+//! non-visual ways of solving.
+
+use itertools::Itertools;
+use std::fmt::Write;
+
+use super::*;
+
+/// Returns a description of the colors on each face of a puzzle, listing each
+/// face (or each layer of each cell, for 4D puzzles) as rows of color symbols.
+///
+/// Each color is written as the symbol of the face it belongs to when solved.
+pub fn describe_state(puzzle: &Puzzle) -> String {
+    let is_4d = puzzle.projection_type() == ProjectionType::_4D;
+    let face_word = if is_4d { "cell" } else { "face" };
+
+    let mut ret = String::new();
+    for (face_info, grid) in puzzle.faces().iter().zip(face_grids(puzzle)) {
+        let _ = writeln!(ret, "{} {face_word}", face_info.symbol);
+        for (layer_index, layer) in grid.iter().enumerate() {
+            if is_4d {
+                let _ = writeln!(ret, "  Layer {}", layer_index + 1);
+            }
+            for (row_index, row) in layer.iter().enumerate() {
+                // Every position should be filled, but write a dash
+                // instead of panicking if one isn't.
+                let colors = row
+                    .iter()
+                    .map(|color| color.map_or("-", |c| puzzle.info(c).symbol))
+                    .join(" ");
+                let indent = if is_4d { "    " } else { "  " };
+                let _ = writeln!(ret, "{indent}Row {}: {colors}", row_index + 1);
+            }
+        }
+    }
+    ret
+}
+
+/// Returns a monospaced diagram of the colors on a puzzle: a cross-shaped net
+/// of faces for 3D puzzles, or the layers of each cell side by side for 4D
+/// puzzles.
+///
+/// `sticker_text` returns the text for a sticker of a given color, which
+/// should be two columns wide, such as a face symbol followed by a space.
+pub fn state_diagram(puzzle: &Puzzle, sticker_text: impl Fn(Face) -> String) -> String {
+    const BLANK: &str = "  ";
+
+    let layer_count = puzzle.layer_count() as usize;
+    let grids = face_grids(puzzle);
+    let row_text = |row: &[Option<Face>]| -> String {
+        row.iter()
+            .map(|color| color.map_or_else(|| BLANK.to_string(), &sticker_text))
+            .collect()
+    };
+
+    let mut ret = String::new();
+    if puzzle.projection_type() == ProjectionType::_3D {
+        // Standard net, with U above F and D below it.
+        const NET: &[&[&str]] = &[&["", "U"], &["L", "F", "R", "B"], &["", "D"]];
+        for net_row in NET {
+            for row in 0..layer_count {
+                let mut line = String::new();
+                for &symbol in *net_row {
+                    match puzzle.faces().iter().position(|f| f.symbol == symbol) {
+                        Some(i) => line += &row_text(&grids[i][0][row]),
+                        None => line += &BLANK.repeat(layer_count),
+                    }
+                    line += BLANK;
+                }
+                let _ = writeln!(ret, "{}", line.trim_end());
+            }
+        }
+    } else {
+        for (face_info, grid) in puzzle.faces().iter().zip(&grids) {
+            let _ = writeln!(ret, "{}", face_info.symbol);
+            for row in 0..layer_count {
+                let line = grid.iter().map(|layer| row_text(&layer[row])).join(BLANK);
+                let _ = writeln!(ret, "{}", line.trim_end());
+            }
+        }
+    }
+    ret
+}
+
+/// Returns the color at each sticker position, indexed by face (or cell),
+/// then layer, row, and column. 3D puzzles have only one layer per face.
+fn face_grids(puzzle: &Puzzle) -> Vec<Vec<Vec<Vec<Option<Face>>>>> {
+    let layer_count = puzzle.layer_count() as usize;
+    let is_4d = puzzle.projection_type() == ProjectionType::_4D;
+
+    // Find the sticker currently at each position.
+    let mut occupants = vec![Sticker(0); puzzle.stickers().len()];
+    for (i, &position) in puzzle.permutation().as_slice().iter().enumerate() {
+        occupants[position.0 as usize] = Sticker(i as _);
+    }
+
+    let layers = if is_4d { layer_count } else { 1 };
+    let mut grids =
+        vec![vec![vec![vec![None; layer_count]; layer_count]; layers]; puzzle.faces().len()];
+    for position in (0..puzzle.stickers().len() as _).map(Sticker) {
+        let face = puzzle.info(position).color;
+        let grid_pos = puzzle.sticker_grid_position(position);
+        let [layer, row, col] = match grid_pos.as_slice() {
+            &[row, col] => [0, row, col],
+            &[layer, row, col] => [layer, row, col],
+            _ => continue,
+        };
+        let color = puzzle.info(occupants[position.0 as usize]).color;
+        grids[face.0 as usize][layer as usize][row as usize][col as usize] = Some(color);
+    }
+    grids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_state() {
+        let mut puzzle = Puzzle::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let solved = describe_state(&puzzle);
+        assert!(solved.contains("U face\n  Row 1: U U U\n  Row 2: U U U\n  Row 3: U U U\n"));
+
+        // After R, the right column of the U face has the colors of F.
+        let r = puzzle.parse_twists("R").unwrap()[0];
+        puzzle.twist(r).unwrap();
+        let description = describe_state(&puzzle);
+        assert!(description.contains("U face\n  Row 1: U U F\n  Row 2: U U F\n  Row 3: U U F\n"));
+
+        // Every sticker of a 4D puzzle is described.
+        let puzzle = Puzzle::new(PuzzleTypeEnum::Rubiks4D { layer_count: 3 });
+        let description = describe_state(&puzzle);
+        let sticker_count = description
+            .lines()
+            .filter_map(|line| line.split_once(": "))
+            .flat_map(|(_, colors)| colors.split(' '))
+            .filter(|&color| color != "-")
+            .count();
+        assert_eq!(sticker_count, puzzle.stickers().len());
+    }
+
+    #[test]
+    fn test_state_diagram() {
+        let puzzle = Puzzle::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let symbol = |face: Face| format!("{} ", puzzle.info(face).symbol);
+        let diagram = state_diagram(&puzzle, symbol);
+        let lines: Vec<&str> = diagram.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[0], "        U U U");
+        assert_eq!(lines[3], "L L L   F F F   R R R   B B B");
+        assert_eq!(lines[8], "        D D D");
+
+        let puzzle = Puzzle::new(PuzzleTypeEnum::Rubiks4D { layer_count: 2 });
+        let symbol = |face: Face| format!("{} ", puzzle.info(face).symbol);
+        let diagram = state_diagram(&puzzle, symbol);
+        assert_eq!(diagram.lines().count(), puzzle.faces().len() * 3);
+        assert!(diagram.contains("I\nI I   I I\nI I   I I\n"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Events emitted by a puzzle controller, so that other systems can react to
+//! changes in the puzzle without being called directly by the controller.
+
+use std::fmt;
+
+use super::*;
+
+/// Event emitted by a [`PuzzleController`] to its subscribers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PuzzleEvent {
+    /// A new twist was made. This does not include scramble twists or twists
+    /// that are redone.
+    Twist(Twist),
+    /// The puzzle was scrambled.
+    Scramble(ScrambleState),
+    /// The puzzle was solved after being scrambled.
+    Solved,
+    /// An entry in the undo history was undone.
+    Undo(HistoryEntry),
+    /// An entry that was undone was redone.
+    Redo(HistoryEntry),
+}
+
+/// Handle returned when subscribing to puzzle events, which can be used to
+/// unsubscribe.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Set of callbacks subscribed to puzzle events.
+#[derive(Default)]
+pub(super) struct Subscriptions {
+    next_id: u64,
+    callbacks: Vec<(SubscriptionId, Box<dyn FnMut(&PuzzleEvent)>)>,
+}
+impl fmt::Debug for Subscriptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscriptions")
+            .field("count", &self.callbacks.len())
+            .finish()
+    }
+}
+impl Subscriptions {
+    pub(super) fn add(&mut self, callback: Box<dyn FnMut(&PuzzleEvent)>) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.callbacks.push((id, callback));
+        id
+    }
+    pub(super) fn remove(&mut self, id: SubscriptionId) -> bool {
+        let len_before = self.callbacks.len();
+        self.callbacks.retain(|(i, _)| *i != id);
+        self.callbacks.len() < len_before
+    }
+    pub(super) fn emit(&mut self, event: PuzzleEvent) {
+        for (_, callback) in &mut self.callbacks {
+            callback(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::preferences::ScrambleOptions;
+
+    #[test]
+    fn test_puzzle_events() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let twists = ty.parse_twists("R U").unwrap();
+
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut puzzle = PuzzleController::new(ty);
+        let id = puzzle.subscribe({
+            let events = Rc::clone(&events);
+            move |event| events.borrow_mut().push(*event)
+        });
+        let solved_count = Rc::new(RefCell::new(0));
+        puzzle.on_solved({
+            let solved_count = Rc::clone(&solved_count);
+            move || *solved_count.borrow_mut() += 1
+        });
+
+        // Scramble twists are not reported individually.
+        puzzle.scramble_n(5, &ScrambleOptions::default()).unwrap();
+        assert_eq!(
+            events.borrow().as_slice(),
+            [PuzzleEvent::Scramble(ScrambleState::Partial)],
+        );
+        events.borrow_mut().clear();
+
+        puzzle.twist(twists[0]).unwrap();
+        puzzle.twist(twists[1]).unwrap();
+        puzzle.undo().unwrap();
+        // Twisting the inverse of the last twist is still a twist.
+        puzzle.twist(ty.reverse_twist(twists[0])).unwrap();
+        assert_eq!(
+            events.borrow().as_slice(),
+            [
+                PuzzleEvent::Twist(twists[0]),
+                PuzzleEvent::Twist(twists[1]),
+                PuzzleEvent::Undo(twists[1].into()),
+                PuzzleEvent::Twist(ty.reverse_twist(twists[0])),
+            ],
+        );
+
+        // Subscriptions are kept when the puzzle is reset.
+        puzzle.reset();
+        puzzle
+            .scramble_with(&twists, ScrambleState::Partial)
+            .unwrap();
+        // The scramble can be undone and redone.
+        puzzle.undo().unwrap();
+        puzzle.redo().unwrap();
+        for &twist in twists.iter().rev() {
+            puzzle.twist(ty.reverse_twist(twist)).unwrap();
+        }
+        assert!(puzzle.check_just_solved());
+        assert_eq!(events.borrow().last(), Some(&PuzzleEvent::Solved));
+        assert_eq!(*solved_count.borrow(), 1);
+
+        assert!(puzzle.unsubscribe(id));
+        assert!(!puzzle.unsubscribe(id));
+        events.borrow_mut().clear();
+        puzzle.twist(twists[0]).unwrap();
+        assert!(events.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_cancel_queued_twist_events() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let [r, u, f]: [Twist; 3] = ty.parse_twists("R U F").unwrap().try_into().unwrap();
+
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle.subscribe({
+            let events = Rc::clone(&events);
+            move |event| events.borrow_mut().push(*event)
+        });
+
+        // Canceling queued twists undoes them.
+        puzzle.twist(r).unwrap();
+        puzzle.twist(u).unwrap();
+        puzzle.twist(f).unwrap();
+        assert_eq!(puzzle.cancel_queued_twists(), 2);
+        assert_eq!(
+            events.borrow().as_slice(),
+            [
+                PuzzleEvent::Twist(r),
+                PuzzleEvent::Twist(u),
+                PuzzleEvent::Twist(f),
+                PuzzleEvent::Undo(f.into()),
+                PuzzleEvent::Undo(u.into()),
+            ],
+        );
+        events.borrow_mut().clear();
+
+        // Canceling a queued undo redoes the twist and keeps its time.
+        puzzle.twist(u).unwrap();
+        puzzle.skip_twist_animations();
+        let twist_times = puzzle.twist_times().to_vec();
+        puzzle.undo().unwrap();
+        puzzle.undo().unwrap();
+        assert_eq!(puzzle.cancel_queued_twists(), 1);
+        assert_eq!(puzzle.twist_times(), &twist_times[..1]);
+        assert!(puzzle.twist_times()[0].is_some());
+        assert_eq!(
+            events.borrow().as_slice(),
+            [
+                PuzzleEvent::Twist(u),
+                PuzzleEvent::Undo(u.into()),
+                PuzzleEvent::Undo(r.into()),
+                PuzzleEvent::Redo(r.into()),
+            ],
+        );
+        events.borrow_mut().clear();
+
+        // Canceling a queued redo undoes the twist again.
+        puzzle.skip_twist_animations();
+        puzzle.undo().unwrap();
+        puzzle.skip_twist_animations();
+        puzzle.redo().unwrap();
+        puzzle.redo().unwrap();
+        assert_eq!(puzzle.cancel_queued_twists(), 1);
+        assert_eq!(puzzle.undo_buffer(), [HistoryEntry::Twist(r)]);
+        assert_eq!(puzzle.twist_times().len(), 1);
+        assert_eq!(
+            events.borrow().as_slice(),
+            [
+                PuzzleEvent::Undo(r.into()),
+                PuzzleEvent::Redo(r.into()),
+                PuzzleEvent::Redo(u.into()),
+                PuzzleEvent::Undo(u.into()),
+            ],
+        );
+    }
+}
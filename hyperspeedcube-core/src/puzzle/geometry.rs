@@ -4,13 +4,19 @@
 use cgmath::*;
 use smallvec::{smallvec, SmallVec};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 
-use super::{ClickTwists, PuzzleType, PuzzleTypeEnum, Sticker, Twist};
-use crate::preferences::ViewPreferences;
+use super::{ClickTwists, Face, PuzzleTypeEnum, Sticker, Twist};
+use crate::preferences::{SpacingOverride, ViewPreferences};
 use crate::util::{self, IterCyclicPairsExt};
 
-const W_NEAR_CLIPPING_DIVISOR: f32 = 0.1;
 const Z_NEAR_CLIPPING_DIVISOR: f32 = 0.0;
+/// Minimum perspective divisor of geometry that is not clipped in the interior
+/// view. This must be positive, since the camera is inside the puzzle.
+const INTERIOR_NEAR_CLIPPING_DIVISOR: f32 = 0.01;
+
+/// Maximum number of faces that can have spacing overrides.
+const MAX_FACES: usize = 8;
 
 const EPSILON: f32 = 0.000001;
 
@@ -32,8 +38,28 @@ pub struct StickerGeometryParams {
     /// `(sticker width) / (puzzle diameter)`. Ranges from 0.0 to 1.0.
     pub sticker_scale: f32,
 
+    /// `sticker_grid_scale` and `sticker_scale` for each face that overrides
+    /// the face spacing or sticker spacing, indexed by face.
+    pub face_scale_overrides: [Option<(f32, f32)>; MAX_FACES],
+
+    /// 4D rotation applied before 4D projection.
+    pub view_transform_4d: Matrix4<f32>,
+    /// W coordinate of the 4D camera, after `view_transform_4d`.
+    pub camera_w_4d: f32,
+    /// W coordinate of the part of the puzzle farthest from the 4D camera,
+    /// after `view_transform_4d`.
+    pub far_w_4d: f32,
+
     /// 4D FOV, in degrees.
     pub fov_4d: f32,
+    /// Scale of geometry nearest the 4D camera.
+    pub near_scale_4d: f32,
+    /// Scale of geometry farthest from the 4D camera.
+    pub far_scale_4d: f32,
+    /// Minimum 4D perspective divisor of geometry that is not clipped.
+    pub clip_threshold_4d: f32,
+    /// Opacity lost by geometry farthest from the 4D camera.
+    pub w_fade_4d: f32,
     /// 3D FOV, in degrees.
     pub fov_3d: f32,
 
@@ -46,6 +72,8 @@ pub struct StickerGeometryParams {
 
     /// Animated twist and animation progress.
     pub twist_animation: Option<(Twist, f32)>,
+    /// Twist on the opposite axis animated alongside `twist_animation`.
+    pub simultaneous_twist: Option<Twist>,
     /// View transformation matrix for the whole puzzle, after 4D projection.
     pub view_transform: Matrix3<f32>,
 
@@ -58,13 +86,18 @@ pub struct StickerGeometryParams {
     pub show_frontfaces: bool,
     /// Whether to show backfaces.
     pub show_backfaces: bool,
+    /// Whether to hide 4D stickers that are inside-out after 4D projection.
+    pub cull_4d: bool,
     /// Whether to clip points behind the 4D camera.
     pub clip_4d: bool,
+    /// Whether the 3D camera is at the center of the puzzle.
+    pub interior_view: bool,
 }
 impl StickerGeometryParams {
     /// Constructs sticker geometry parameters for a set of view preferences.
     pub fn new(
         view_prefs: &ViewPreferences,
+        spacing_overrides: &BTreeMap<String, SpacingOverride>,
         puzzle_type: PuzzleTypeEnum,
         twist_animation: Option<(Twist, f32)>,
         view_angle_offset: Quaternion<f32>,
@@ -91,10 +124,34 @@ impl StickerGeometryParams {
             0.0
         };
 
-        let sticker_grid_scale =
-            (1.0 - face_spacing) / (puzzle_type.layer_count() as f32 - sticker_spacing);
-        let face_scale = sticker_grid_scale * (puzzle_type.layer_count() as f32);
-        let sticker_scale = sticker_grid_scale * (1.0 - sticker_spacing);
+        let layer_count = puzzle_type.layer_count();
+        let (sticker_grid_scale, sticker_scale) =
+            sticker_scales(layer_count, face_spacing, sticker_spacing);
+        let face_scale = sticker_grid_scale * (layer_count as f32);
+
+        let mut face_scale_overrides = [None; MAX_FACES];
+        let desc = puzzle_type.desc();
+        for (i, face) in desc.faces().iter().enumerate().take(MAX_FACES) {
+            if let Some(o) = spacing_overrides.get(face.symbol) {
+                let sticker_spacing = if layer_count > 1 {
+                    o.sticker_spacing
+                } else {
+                    0.0
+                };
+                face_scale_overrides[i] =
+                    Some(sticker_scales(layer_count, o.face_spacing, sticker_spacing));
+            }
+        }
+
+        // Move the 4D camera back so that it is the same distance from the
+        // nearest part of the puzzle regardless of the 4D rotation.
+        let view_transform_4d = view_prefs.view_angle_4d();
+        let w_row = view_transform_4d.row(3);
+        let w_extent = w_row.x.abs() + w_row.y.abs() + w_row.z.abs() + w_row.w.abs();
+        // A positive W offset moves the camera further back, and a negative
+        // one moves it into the puzzle.
+        let camera_w_4d = face_scale * (w_extent + view_prefs.w_offset_4d).max(0.01);
+        let far_w_4d = -face_scale * w_extent;
 
         let mut ret = Self {
             face_spacing,
@@ -104,12 +161,23 @@ impl StickerGeometryParams {
             face_scale,
             sticker_scale,
 
+            face_scale_overrides,
+
+            view_transform_4d,
+            camera_w_4d,
+            far_w_4d,
+
             fov_4d: view_prefs.fov_4d,
+            near_scale_4d: view_prefs.near_scale_4d,
+            far_scale_4d: view_prefs.far_scale_4d,
+            clip_threshold_4d: view_prefs.clip_threshold_4d,
+            w_fade_4d: view_prefs.w_fade_4d,
             fov_3d: view_prefs.fov_3d,
             w_factor_4d: (view_prefs.fov_4d.to_radians() / 2.0).tan(),
             w_factor_3d: (view_prefs.fov_3d.to_radians() / 2.0).tan(),
 
             twist_animation,
+            simultaneous_twist: None,
             view_transform,
 
             ambient_light,
@@ -117,7 +185,9 @@ impl StickerGeometryParams {
 
             show_frontfaces: view_prefs.show_frontfaces,
             show_backfaces: view_prefs.show_backfaces,
+            cull_4d: view_prefs.cull_4d,
             clip_4d: view_prefs.clip_4d,
+            interior_view: view_prefs.interior_view,
         };
 
         ret.view_transform /= puzzle_type.projection_radius_3d(ret);
@@ -125,9 +195,28 @@ impl StickerGeometryParams {
         ret
     }
 
+    /// Returns the parameters for stickers on a face, taking into account any
+    /// spacing override for that face.
+    pub fn for_face(self, face: Face) -> Self {
+        match self
+            .face_scale_overrides
+            .get(face.0 as usize)
+            .copied()
+            .flatten()
+        {
+            Some((sticker_grid_scale, sticker_scale)) => Self {
+                sticker_grid_scale,
+                sticker_scale,
+                ..self
+            },
+            None => self,
+        }
+    }
+
     /// Projects a 4D point down to 3D.
     pub fn project_4d(self, point: Vector4<f32>) -> Option<Point3<f32>> {
-        let camera_w = self.face_scale;
+        let point = self.view_transform_4d * point;
+        let camera_w = self.camera_w_4d;
 
         // See `project_3d()` for an explanation of this formula. The only
         // differences here are that we assume the 4D FOV is positive and we
@@ -135,15 +224,37 @@ impl StickerGeometryParams {
         let divisor = 1.0 + (1.0 - point.w / camera_w) * self.w_factor_4d;
 
         // Clip geometry that is behind the 4D camera.
-        if self.clip_4d && divisor < W_NEAR_CLIPPING_DIVISOR {
+        if self.clip_4d && divisor < self.clip_threshold_4d {
             return None;
         }
 
-        Some(Point3::from_vec(point.truncate()) / divisor)
+        // Scale geometry depending on how far it is from the camera, so that
+        // the near and far cells can be resized independently.
+        let t = self.depth_4d(point);
+        let scale = util::mix(self.near_scale_4d, self.far_scale_4d, t);
+
+        Some(Point3::from_vec(point.truncate()) / divisor * scale)
+    }
+    /// Returns the opacity factor of a 4D point, which fades with distance from
+    /// the 4D camera.
+    pub fn alpha_4d(self, point: Vector4<f32>) -> f32 {
+        let point = self.view_transform_4d * point;
+        1.0 - self.w_fade_4d * self.depth_4d(point)
+    }
+    /// Returns how far a 4D point (after `view_transform_4d`) is from the 4D
+    /// camera, from 0.0 at the camera to 1.0 at the farthest part of the
+    /// puzzle.
+    fn depth_4d(self, point: Vector4<f32>) -> f32 {
+        let camera_w = self.camera_w_4d;
+        ((point.w - camera_w) / (self.far_w_4d - camera_w)).clamp(0.0, 1.0)
     }
 
     /// Projects a 3D point according to the perspective projection.
     pub fn project_3d(self, point: Point3<f32>) -> Option<Point3<f32>> {
+        if self.interior_view {
+            return self.project_3d_interior(point);
+        }
+
         // This formula gives us a divisor (which we would store in the W
         // coordinate, if we were doing this using the normal computer graphics
         // methods) that applies the desired FOV but keeps Z=1 fixed for
@@ -168,12 +279,35 @@ impl StickerGeometryParams {
 
         Some(point / divisor)
     }
+    /// Projects a 3D point with the camera at the origin, looking toward -Z.
+    fn project_3d_interior(self, point: Point3<f32>) -> Option<Point3<f32>> {
+        let divisor = -point.z * self.w_factor_3d.abs();
+
+        // Clip geometry that is behind the camera or very close to it.
+        if divisor < INTERIOR_NEAR_CLIPPING_DIVISOR {
+            return None;
+        }
+
+        // Use the reciprocal of the divisor as the Z coordinate, which keeps
+        // polygons planar and is larger for geometry nearer to the camera.
+        Some(Point3::new(point.x, point.y, 1.0) / divisor)
+    }
+}
+
+/// Returns `sticker_grid_scale` and `sticker_scale` for a face with the given
+/// spacing.
+fn sticker_scales(layer_count: u8, face_spacing: f32, sticker_spacing: f32) -> (f32, f32) {
+    let sticker_grid_scale = (1.0 - face_spacing) / (layer_count as f32 - sticker_spacing);
+    let sticker_scale = sticker_grid_scale * (1.0 - sticker_spacing);
+    (sticker_grid_scale, sticker_scale)
 }
 
 /// Vertices for a sticker in 3D space.
 pub struct StickerGeometry {
     /// Vertex positions, after 4D projection but before 3D projection.
     pub verts: Vec<Point3<f32>>,
+    /// Opacity factor of each vertex.
+    pub vert_alphas: Vec<f32>,
     /// Indices for polygons.
     pub polygon_indices: Vec<Box<[u16]>>,
     /// Twists on left/right/middle mouse click per polygon.
@@ -188,6 +322,7 @@ impl StickerGeometry {
     ) -> Self {
         let mut ret = Self {
             verts: verts.to_vec(),
+            vert_alphas: vec![1.0; 4],
             polygon_indices: vec![Box::new([0, 2, 3, 1]), Box::new([2, 0, 1, 3])],
             polygon_twists: vec![twists, twists.rev()],
         };
@@ -201,18 +336,24 @@ impl StickerGeometry {
         }
         ret
     }
-    pub(super) fn new_cube(verts: [Point3<f32>; 8], twists: [ClickTwists; 6]) -> Option<Self> {
-        // Only show this sticker if the 3D volume is positive. (Cull it if its
-        // 3D volume is negative.)
-        Matrix3::from_cols(
+    pub(super) fn new_cube(
+        verts: [Point3<f32>; 8],
+        vert_alphas: [f32; 8],
+        twists: [ClickTwists; 6],
+        cull: bool,
+    ) -> Option<Self> {
+        // Only show this sticker if the 3D volume is positive, unless culling
+        // is disabled. (Cull it if its 3D volume is negative.)
+        let is_inside_out = Matrix3::from_cols(
             verts[4] - verts[0],
             verts[2] - verts[0],
             verts[1] - verts[0],
         )
         .determinant()
-        .is_sign_positive()
-        .then(|| Self {
+        .is_sign_negative();
+        (!(cull && is_inside_out)).then(|| Self {
             verts: verts.to_vec(),
+            vert_alphas: vert_alphas.to_vec(),
             polygon_indices: vec![
                 Box::new([0, 2, 3, 1]),
                 Box::new([4, 5, 7, 6]),
@@ -226,8 +367,8 @@ impl StickerGeometry {
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct ProjectedStickerGeometry {
+#[derive(Debug, Clone)]
+pub struct ProjectedStickerGeometry {
     pub sticker: Sticker,
 
     pub verts: Box<[Point3<f32>]>,
@@ -238,7 +379,7 @@ pub(crate) struct ProjectedStickerGeometry {
     pub back_polygons: Box<[Polygon]>,
 }
 impl ProjectedStickerGeometry {
-    pub(crate) fn twists_for_point(&self, point: Point2<f32>) -> Option<ClickTwists> {
+    pub fn twists_for_point(&self, point: Point2<f32>) -> Option<ClickTwists> {
         self.front_polygons
             .iter()
             .find(|polygon| polygon.contains_point(point))
@@ -247,11 +388,13 @@ impl ProjectedStickerGeometry {
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct Polygon {
+pub struct Polygon {
     pub verts: SmallVec<[Point3<f32>; 4]>,
     pub min_bound: Point3<f32>,
     pub max_bound: Point3<f32>,
     pub normal: Vector3<f32>,
+    /// Opacity factor of each vertex.
+    pub alphas: SmallVec<[f32; 4]>,
 
     pub illumination: f32,
 
@@ -261,7 +404,12 @@ impl Polygon {
     /// Constructs a convex polygon from a list of coplanar vertices in
     /// counterclockwise order. The polygon must not be degenerate, and no three
     /// vertices may be colinear.
-    pub fn new(verts: SmallVec<[Point3<f32>; 4]>, illumination: f32, twists: ClickTwists) -> Self {
+    pub fn new(
+        verts: SmallVec<[Point3<f32>; 4]>,
+        alphas: SmallVec<[f32; 4]>,
+        illumination: f32,
+        twists: ClickTwists,
+    ) -> Self {
         let mut min_bound = verts[0];
         let mut max_bound = verts[0];
         for v in &verts[1..] {
@@ -293,6 +441,7 @@ impl Polygon {
             min_bound,
             max_bound,
             normal,
+            alphas,
 
             illumination,
 
@@ -314,13 +463,15 @@ impl Polygon {
     }
 }
 
-pub(crate) fn polygon_from_indices(
+pub fn polygon_from_indices(
     verts: &[Point3<f32>],
+    vert_alphas: &[f32],
     indices: &[u16],
     illumination: f32,
     twists: ClickTwists,
 ) -> Polygon {
     let verts: SmallVec<_> = indices.iter().map(|&i| verts[i as usize]).collect();
+    let alphas = indices.iter().map(|&i| vert_alphas[i as usize]).collect();
     let normal = polygon_normal_from_indices(&verts, &[0, 1, 2]);
     let (min_bound, max_bound) = util::min_and_max_bound(&verts);
 
@@ -329,6 +480,7 @@ pub(crate) fn polygon_from_indices(
         min_bound,
         max_bound,
         normal,
+        alphas,
 
         illumination,
 
@@ -336,7 +488,7 @@ pub(crate) fn polygon_from_indices(
     }
 }
 
-pub(crate) fn polygon_normal_from_indices(verts: &[Point3<f32>], indices: &[u16]) -> Vector3<f32> {
+pub fn polygon_normal_from_indices(verts: &[Point3<f32>], indices: &[u16]) -> Vector3<f32> {
     let a = verts[indices[0] as usize];
     let b = verts[indices[1] as usize];
     let c = verts[indices[2] as usize];
@@ -354,7 +506,7 @@ trait NewellObj: Sized {
 }
 
 /// Sort stickers by depth using to Newell's algorithm. Stickers are not split.
-pub(crate) fn sort_by_depth(objs: &mut [ProjectedStickerGeometry]) {
+pub fn sort_by_depth(objs: &mut [ProjectedStickerGeometry]) {
     // First, approximate the correct order.
     objs.sort_by(NewellObj::approx_depth_cmp);
 
@@ -486,7 +638,10 @@ impl Polygon {
             verts = new_verts;
         }
 
-        (verts.len() >= 3).then(|| Polygon::new(verts, self.illumination, self.twists))
+        // The intersection is only used for depth sorting, so its opacity
+        // doesn't matter.
+        let alphas = smallvec![1.0; verts.len()];
+        (verts.len() >= 3).then(|| Polygon::new(verts, alphas, self.illumination, self.twists))
     }
 
     fn edges(&self) -> impl '_ + Iterator<Item = (Point3<f32>, Point3<f32>)> {
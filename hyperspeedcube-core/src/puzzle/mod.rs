@@ -0,0 +1,348 @@
+//! Common types and traits used for any puzzle.
+
+#[macro_use]
+mod common;
+
+pub mod analysis;
+pub mod controller;
+pub mod description;
+pub mod events;
+pub mod geometry;
+pub mod notation;
+pub mod permutation;
+#[macro_use]
+mod piece_states;
+#[cfg(test)]
+mod proptests;
+mod registry;
+pub mod rubiks_3d;
+pub mod rubiks_4d;
+pub mod state_code;
+pub mod state_space;
+
+pub use analysis::*;
+pub use common::*;
+pub use controller::*;
+pub use description::{describe_state, state_diagram};
+pub use events::*;
+pub use geometry::*;
+pub use notation::*;
+pub use permutation::StickerPermutation;
+pub use rubiks_3d::Rubiks3D;
+pub use rubiks_4d::Rubiks4D;
+pub use state_code::{decode_state, encode_state};
+pub use state_space::{gods_number, log10_state_count};
+
+pub mod traits {
+    pub use super::{PuzzleInfo, PuzzleState, PuzzleType};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Test that every twist is equivalent to its canonicalization.
+    pub(super) fn test_twist_canonicalization(
+        p: &impl PuzzleType,
+        mut twists_are_eq: impl FnMut(Twist, Twist) -> bool,
+    ) {
+        eprintln!("Testing twist canonicalization for {}", p.name());
+
+        for twist in iter_all_twists(p) {
+            let canonicalized = p.canonicalize_twist(twist);
+
+            assert!(
+                twists_are_eq(twist, canonicalized),
+                "Twist for {} does not match its canonicalization. \n\n\
+                 Twist:\n{:?}\n\n\
+                 Canonicalization:\n{:?}",
+                p.name(),
+                twist,
+                canonicalized,
+            );
+        }
+    }
+
+    /// Test that mirroring every twist twice across the same hyperplane is
+    /// equivalent to the original twist.
+    pub(super) fn test_twist_mirroring(
+        p: &impl PuzzleType,
+        mut twists_are_eq: impl FnMut(Twist, Twist) -> bool,
+    ) {
+        eprintln!("Testing twist mirroring for {}", p.name());
+
+        for twist in iter_all_twists(p) {
+            for axis in (0..p.twist_axes().len() as _).map(TwistAxis) {
+                let mirrored_twice = p.mirror_twist(p.mirror_twist(twist, axis), axis);
+
+                assert!(
+                    twists_are_eq(twist, mirrored_twice),
+                    "Twist for {} does not match itself mirrored twice. \n\n\
+                     Twist:\n{:?}\n\n\
+                     Mirrored twice:\n{:?}",
+                    p.name(),
+                    twist,
+                    mirrored_twice,
+                );
+            }
+        }
+    }
+
+    /// Test that repainting a scrambled puzzle with its own colors produces a
+    /// state that looks the same, and that invalid colors are rejected.
+    pub(super) fn test_repaint<P: PuzzleState + Clone>(solved: &P) {
+        eprintln!("Testing repainting for {}", solved.name());
+
+        let mut p = solved.clone();
+        let twists = (0..50).map(|_| Twist::from_rng(p.ty())).collect::<Vec<_>>();
+        for &twist in &twists {
+            p.twist(twist).unwrap();
+        }
+        let mut colors = p.stickers().iter().map(|s| s.color).collect::<Vec<_>>();
+        p.repaint(&colors).unwrap();
+        assert_eq!(
+            p.permutation().as_slice(),
+            p.sticker_permutation(),
+            "Repainted permutation does not match piece states for {}",
+            p.name(),
+        );
+        for &twist in twists.iter().rev() {
+            p.twist(p.reverse_twist(twist)).unwrap();
+        }
+        assert!(
+            p.is_solved(),
+            "Repainted state does not match for {}",
+            p.name()
+        );
+
+        // Give a piece two stickers of the same color.
+        if let Some(piece) = p.pieces().iter().find(|piece| piece.stickers.len() >= 2) {
+            let [a, b] = [piece.stickers[0], piece.stickers[1]];
+            colors[b.0 as usize] = colors[a.0 as usize];
+            let before = p.clone();
+            assert!(p.repaint(&colors).is_err());
+            assert!(p == before, "Failed repaint modified {}", p.name());
+        }
+    }
+
+    /// Test that the maintained permutation matches the one computed from the
+    /// piece states after every twist.
+    pub(super) fn test_permutation<P: PuzzleState + Clone>(solved: &P) {
+        eprintln!("Testing permutation for {}", solved.name());
+
+        let mut p = solved.clone();
+        assert!(p.permutation().is_identity());
+        for _ in 0..50 {
+            let twist = Twist::from_rng(p.ty());
+            let before = p.permutation().clone();
+            p.twist(twist).unwrap();
+            assert_eq!(
+                p.permutation().as_slice(),
+                p.sticker_permutation(),
+                "Permutation does not match piece states for {} after {:?}",
+                p.name(),
+                twist,
+            );
+
+            // Undoing the twist applies the inverse permutation.
+            let mut undone = p.clone();
+            undone.twist(p.reverse_twist(twist)).unwrap();
+            assert_eq!(undone.permutation(), &before);
+        }
+    }
+
+    /// Test that scrambled states are solvable and that swapping two pieces is
+    /// detected.
+    pub(super) fn test_solvability<P: PuzzleState + Clone>(solved: &P) {
+        eprintln!("Testing solvability for {}", solved.name());
+
+        assert!(solved.check_solvable().is_ok());
+
+        let mut p = solved.clone();
+        for _ in 0..50 {
+            p.twist(Twist::from_rng(p.ty())).unwrap();
+        }
+        assert!(
+            p.check_solvable().is_ok(),
+            "Scrambled state is not solvable for {}",
+            p.name(),
+        );
+
+        // Only 3-layer puzzles are guaranteed to have pieces with two stickers
+        // that are all distinguishable.
+        if solved.layer_count() != 3 {
+            return;
+        }
+
+        // Swap the colors of two pieces with two stickers that share a color,
+        // which swaps the pieces.
+        let mut colors = solved
+            .stickers()
+            .iter()
+            .map(|s| s.color)
+            .collect::<Vec<_>>();
+        let two_sticker_pieces = solved
+            .pieces()
+            .iter()
+            .filter(|piece| piece.stickers.len() == 2)
+            .map(|piece| [piece.stickers[0].0 as usize, piece.stickers[1].0 as usize])
+            .collect::<Vec<_>>();
+        let a = two_sticker_pieces[0];
+        let (a_other, b_other) = two_sticker_pieces[1..]
+            .iter()
+            .find_map(|&b| {
+                itertools::iproduct!(0..2, 0..2)
+                    .find_map(|(i, j)| (colors[a[i]] == colors[b[j]]).then(|| (a[1 - i], b[1 - j])))
+            })
+            .expect("no pieces with two stickers share a color");
+        colors.swap(a_other, b_other);
+
+        let mut p = solved.clone();
+        p.repaint(&colors).unwrap();
+        let e = p.check_solvable().unwrap_err();
+        assert!(e.contains("parity"), "Wrong error for {}: {e}", p.name());
+    }
+
+    /// Test that pieces untouched by a twist are still solved relative to each
+    /// other, while the whole puzzle is not.
+    pub(super) fn test_pieces_solved<P: PuzzleState + Clone>(solved: &P) {
+        eprintln!("Testing piece subsets for {}", solved.name());
+
+        let all_pieces = (0..solved.pieces().len() as _)
+            .map(Piece)
+            .collect::<Vec<_>>();
+        assert!(solved.are_pieces_solved(&all_pieces));
+
+        let mut p = solved.clone();
+        let twist = Twist::from_rng(p.ty());
+        p.twist(twist).unwrap();
+        let unaffected = all_pieces
+            .iter()
+            .copied()
+            .filter(|&piece| !p.is_piece_affected_by_twist(twist, piece))
+            .collect::<Vec<_>>();
+        assert!(
+            p.are_pieces_solved(&unaffected),
+            "Unaffected pieces are not solved after {twist:?} on {}",
+            p.name(),
+        );
+        assert_eq!(p.are_pieces_solved(&all_pieces), p.is_solved());
+    }
+
+    /// Test that every canonical twist can be losslessly serialized/deserialized.
+    pub(super) fn test_twist_serialization(p: &impl PuzzleType) {
+        let mut seen = HashSet::new();
+        test_twist_serialization_for_each(
+            p,
+            iter_all_twists(p)
+                .map(|t| p.canonicalize_twist(t))
+                .filter(|&t| seen.insert(t)),
+        );
+    }
+
+    /// Test that one canonical twist with each possible layer mask can be
+    /// losslessly serialized/deserialized.
+    pub(super) fn test_layered_twist_serialization(p: &impl PuzzleType) {
+        test_twist_serialization_for_each(
+            p,
+            iter_all_layer_masks(p)
+                .map(|layers| Twist {
+                    layers,
+                    ..Default::default()
+                })
+                .map(|t| p.canonicalize_twist(t)),
+        );
+    }
+
+    fn test_twist_serialization_for_each(
+        p: &impl PuzzleType,
+        twists: impl IntoIterator<Item = Twist>,
+    ) {
+        let notation = p.notation_scheme();
+
+        for twist in twists {
+            let serialized_twist = notation.twist_to_string(twist);
+            let deserialized_twist = notation.parse_twist(&serialized_twist);
+            assert_eq!(
+                Ok(twist),
+                deserialized_twist,
+                "Error deserializing {:?} for {}",
+                serialized_twist,
+                p.name(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_puzzle_type_cycle() {
+        let first = PuzzleTypeEnum::Rubiks3D {
+            layer_count: rubiks_3d::MIN_LAYER_COUNT,
+        };
+        let last = PuzzleTypeEnum::Rubiks4D {
+            layer_count: rubiks_4d::MAX_LAYER_COUNT,
+        };
+        assert_eq!(last.cycle(1), first);
+        assert_eq!(first.cycle(-1), last);
+        for ty in PuzzleTypeEnum::iter_all() {
+            assert_eq!(ty.cycle(1).cycle(-1), ty);
+        }
+        assert_eq!(
+            PuzzleTypeEnum::Rubiks3D {
+                layer_count: rubiks_3d::MAX_LAYER_COUNT,
+            }
+            .cycle(1),
+            PuzzleTypeEnum::Rubiks4D {
+                layer_count: rubiks_4d::MIN_LAYER_COUNT,
+            },
+        );
+    }
+
+    #[test]
+    fn test_precomputed_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let options = crate::preferences::ScrambleOptions::default();
+
+        let mut progress = 0;
+        let scramble =
+            PrecomputedScramble::generate(ty, Some(20), &options, |i| progress = i).unwrap();
+        assert_eq!(progress, 20);
+        assert_eq!(scramble.scramble_state, ScrambleState::Partial);
+
+        let mut expected = Puzzle::new(ty);
+        for &twist in &scramble.twists {
+            expected.twist(twist).unwrap();
+        }
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle.apply_precomputed_scramble(scramble).unwrap();
+        assert_eq!(puzzle.latest(), &expected);
+        assert_eq!(puzzle.scramble_state(), ScrambleState::Partial);
+        assert!(puzzle.is_unsaved());
+
+        let full = PrecomputedScramble::generate(ty, None, &options, |_| ()).unwrap();
+        assert_eq!(full.twists.len(), ty.scramble_moves_count());
+        assert_eq!(full.scramble_state, ScrambleState::Full);
+
+        let other_ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+        let other = PrecomputedScramble::generate(other_ty, Some(5), &options, |_| ()).unwrap();
+        assert!(puzzle.apply_precomputed_scramble(other).is_err());
+    }
+
+    fn iter_all_twists(p: &impl PuzzleType) -> impl Iterator<Item = Twist> {
+        itertools::iproduct!(
+            (0..p.twist_axes().len() as _).map(TwistAxis),
+            (0..p.twist_directions().len() as _).map(TwistDirection),
+            iter_all_layer_masks(p)
+        )
+        .map(|(axis, direction, layers)| Twist {
+            axis,
+            direction,
+            layers,
+        })
+    }
+
+    fn iter_all_layer_masks(p: &impl PuzzleType) -> impl Clone + Iterator<Item = LayerMask> {
+        (1..(1 << p.layer_count())).map(LayerMask)
+    }
+}
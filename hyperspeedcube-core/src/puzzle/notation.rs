@@ -1,9 +1,13 @@
 use itertools::Itertools;
 use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::fmt;
 
 use super::*;
 
+const PATTERNS_STR: &str = include_str!("patterns.yaml");
+
 #[derive(Debug, Clone)]
 pub struct NotationScheme {
     pub(super) axis_names: Vec<String>,
@@ -79,7 +83,7 @@ impl NotationScheme {
         self.format_layers(f, twist.layers)?;
         self.format_axis(f, twist.axis)?;
         if let Some(block_suffix) = &self.block_suffix {
-            if twist.layers.is_contiguous_from_outermost() && twist.layers.count() > 1 {
+            if twist.layers.is_contiguous() && twist.layers.count() > 1 {
                 write!(f, "{block_suffix}")?;
             }
         }
@@ -96,6 +100,11 @@ impl NotationScheme {
             } else {
                 write!(f, "{}", layers.count())
             }
+        } else if self.block_suffix.is_some() && layers.is_contiguous() && layers.count() > 1 {
+            // Range of layers, such as "3-5Rw".
+            let lo = layers.0.trailing_zeros() + 1;
+            let hi = lo + layers.count() - 1;
+            write!(f, "{lo}-{hi}")
         } else {
             write!(f, "{}", layers)
         }
@@ -152,8 +161,13 @@ impl NotationScheme {
             if let Some(block_suffix) = &self.block_suffix {
                 if let Some(after_block_suffix) = remaining.strip_prefix(block_suffix) {
                     remaining = after_block_suffix;
-                    let leading_zeros = prefix_layers.unwrap_or(LayerMask(3)).0.leading_zeros();
-                    layers = LayerMask(u32::MAX >> leading_zeros);
+                    // A range such as "3-5Rw" already gives all the layers,
+                    // but a single layer such as "3Rw" means every layer up
+                    // to that one.
+                    if layers.count() <= 1 {
+                        let leading_zeros = prefix_layers.unwrap_or(LayerMask(3)).0.leading_zeros();
+                        layers = LayerMask(u32::MAX >> leading_zeros);
+                    }
                 }
             }
             // Parse twist direction.
@@ -186,9 +200,9 @@ impl NotationScheme {
         &self,
         string: &'a str,
     ) -> Result<(Option<LayerMask>, &'a str), String> {
-        const LAYER_PREFIX_PATTERN: &str = r"^(\{[\d\s,-]*\}|\d+)(.*)$";
-        // match the whole string            ^                       $
-        // capture                            (                 )
+        const LAYER_PREFIX_PATTERN: &str = r"^(\{[\d\s,-]*\}|\d+-\d+|\d+)(.*)$";
+        // match the whole string            ^                               $
+        // capture                            (                         )
         //   match a pair of `{}`              \{         \}
         //     any number of                     [      ]*
         //       digits,                          \d
@@ -196,8 +210,10 @@ impl NotationScheme {
         //       commas,                              ,
         //       and hyphens                           -
         //   or                                             |
-        //     a sequence of one or more digits              \d+
-        // then capture the rest                                 (.*)
+        //     a range of layers, such as `3-5`              \d+-\d+
+        //   or                                                     |
+        //     a sequence of one or more digits                      \d+
+        // then capture the rest                                         (.*)
 
         lazy_static! {
             static ref LAYER_PREFIX_REGEX: Regex = Regex::new(LAYER_PREFIX_PATTERN).unwrap();
@@ -221,3 +237,131 @@ fn strip_any_prefix<'a, 'b, T>(
         .into_iter()
         .find_map(|(value, prefix)| Some((value, s.strip_prefix(prefix.as_ref())?)))
 }
+
+/// Named pattern that can be made by twisting a solved puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    pub name: String,
+    pub twists: Vec<Twist>,
+}
+
+#[derive(Deserialize)]
+struct PatternGroupData {
+    puzzle: PuzzleTypeEnum,
+    patterns: Vec<PatternData>,
+}
+#[derive(Deserialize)]
+struct PatternData {
+    name: String,
+    twists: String,
+}
+
+/// Returns the built-in patterns for a puzzle type.
+pub fn patterns(ty: PuzzleTypeEnum) -> &'static [Pattern] {
+    lazy_static! {
+        static ref PATTERNS: HashMap<PuzzleTypeEnum, Vec<Pattern>> = parse_patterns(PATTERNS_STR)
+            .unwrap_or_else(|e| {
+                log::error!("Error loading patterns: {e}");
+                HashMap::new()
+            });
+    }
+
+    PATTERNS.get(&ty).map(|v| v.as_slice()).unwrap_or_default()
+}
+
+fn parse_patterns(s: &str) -> Result<HashMap<PuzzleTypeEnum, Vec<Pattern>>, String> {
+    let groups: Vec<PatternGroupData> = serde_yaml::from_str(s).map_err(|e| e.to_string())?;
+
+    let mut ret = HashMap::new();
+    for group in groups {
+        let ty = group.puzzle;
+        ty.validate()?;
+        let patterns: &mut Vec<Pattern> = ret.entry(ty).or_default();
+        for pattern in group.patterns {
+            let twists = ty
+                .parse_twists(&pattern.twists)
+                .map_err(|e| format!("{} on {}: {e}", pattern.name, ty.name()))?;
+            patterns.push(Pattern {
+                name: pattern.name,
+                twists,
+            });
+        }
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_big_cube_notation() {
+        let p = PuzzleTypeEnum::Rubiks3D { layer_count: 7 }.desc();
+        let parse = |s: &str| p.notation_scheme().parse_twist(s).unwrap();
+
+        assert_eq!(parse("3Rw").layers, LayerMask(0b111));
+        assert_eq!(parse("3-5Rw").layers, LayerMask(0b11100));
+        assert_eq!(parse("3-5R").layers, LayerMask(0b11100));
+        assert_eq!(parse("{3-5}R").layers, LayerMask(0b11100));
+        assert_eq!(parse("3-5Rw"), parse("{3-5}R"));
+
+        let twist = parse("3-5Rw");
+        assert_eq!(p.notation_scheme().twist_to_string(twist), "3-5Rw");
+        assert_eq!(
+            p.twist_command_short_description(Some(twist.axis), twist.direction, twist.layers),
+            p.notation_scheme()
+                .twist_to_string(p.canonicalize_twist(twist)),
+        );
+    }
+
+    #[test]
+    fn test_slice_move_notation() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let p = ty.desc();
+        let parse = |s: &str| p.notation_scheme().parse_twist(s).unwrap();
+
+        let twist = parse("Rs");
+        assert_eq!(twist.layers, LayerMask(0b101));
+        assert_eq!(twist, parse("{1,3}R"));
+        assert_eq!(p.notation_scheme().twist_to_string(twist), "Rs");
+
+        // A slice move is R and L' at once.
+        let mut expected = Puzzle::new(ty);
+        for twist in p.parse_twists("R L'").unwrap() {
+            expected.twist(twist).unwrap();
+        }
+        let mut actual = Puzzle::new(ty);
+        actual.twist(twist).unwrap();
+        assert_eq!(actual, expected);
+
+        assert_eq!(TwistMetric::Stm.count_twists(&*p, [twist]), 1);
+        assert_eq!(TwistMetric::Btm.count_twists(&*p, [twist]), 2);
+
+        let p = PuzzleTypeEnum::Rubiks4D { layer_count: 3 }.desc();
+        let twist = p.notation_scheme().parse_twist("IU").unwrap();
+        let slice_move = p.notation_scheme().parse_twist("IsU").unwrap();
+        assert_eq!(slice_move.axis, twist.axis);
+        assert_eq!(slice_move.direction, twist.direction);
+        assert_eq!(slice_move.layers, LayerMask(0b101));
+    }
+
+    #[test]
+    fn test_builtin_patterns() {
+        let patterns = parse_patterns(PATTERNS_STR).unwrap();
+        assert!(!patterns.is_empty());
+        for (&ty, patterns) in &patterns {
+            for pattern in patterns {
+                let mut puzzle = Puzzle::new(ty);
+                for &twist in &pattern.twists {
+                    puzzle.twist(twist).unwrap();
+                }
+                assert!(
+                    !puzzle.is_solved(),
+                    "{} on {} is solved",
+                    pattern.name,
+                    ty.name(),
+                );
+            }
+        }
+    }
+}
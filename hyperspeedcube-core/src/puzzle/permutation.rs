@@ -0,0 +1,121 @@
+//! Permutation representation of puzzle states.
+
+use std::ops::Index;
+
+use super::*;
+
+/// Permutation of sticker positions. For each sticker, records where it is as
+/// the sticker that occupies the same position when the puzzle is solved.
+///
+/// Unlike the per-piece representation of each puzzle type, permutations can
+/// be composed, inverted, and compared across puzzle types.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StickerPermutation(Box<[Sticker]>);
+impl StickerPermutation {
+    /// Returns the permutation of a solved puzzle with `len` stickers.
+    pub fn identity(len: usize) -> Self {
+        Self((0..len as _).map(Sticker).collect())
+    }
+
+    /// Returns the number of stickers.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Returns whether there are no stickers.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Returns whether every sticker is in its solved position.
+    pub fn is_identity(&self) -> bool {
+        self.0.iter().enumerate().all(|(i, s)| s.0 as usize == i)
+    }
+    /// Returns where each sticker is, indexed by sticker.
+    pub fn as_slice(&self) -> &[Sticker] {
+        &self.0
+    }
+
+    /// Sets the position of a sticker.
+    pub(super) fn set(&mut self, sticker: Sticker, position: Sticker) {
+        self.0[sticker.0 as usize] = position;
+    }
+
+    /// Returns the permutation that applies `self` and then `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the permutations have different lengths.
+    pub fn then(&self, other: &Self) -> Self {
+        assert_eq!(self.len(), other.len(), "permutation length mismatch");
+        Self(self.0.iter().map(|&s| other[s]).collect())
+    }
+    /// Returns the permutation that undoes `self`.
+    pub fn inverse(&self) -> Self {
+        let mut ret = self.clone();
+        for (i, &s) in self.0.iter().enumerate() {
+            ret.set(s, Sticker(i as _));
+        }
+        ret
+    }
+
+    /// Returns each cycle of stickers that are moved, starting from the
+    /// lowest-numbered sticker in each cycle. Stickers that are not moved are
+    /// omitted.
+    pub fn cycles(&self) -> Vec<Vec<Sticker>> {
+        let mut seen = vec![false; self.len()];
+        let mut cycles = vec![];
+        for start in (0..self.len() as _).map(Sticker) {
+            if seen[start.0 as usize] || self[start] == start {
+                continue;
+            }
+            let mut cycle = vec![];
+            let mut s = start;
+            while !seen[s.0 as usize] {
+                seen[s.0 as usize] = true;
+                cycle.push(s);
+                s = self[s];
+            }
+            cycles.push(cycle);
+        }
+        cycles
+    }
+}
+impl Index<Sticker> for StickerPermutation {
+    type Output = Sticker;
+
+    fn index(&self, sticker: Sticker) -> &Self::Output {
+        &self.0[sticker.0 as usize]
+    }
+}
+impl From<Vec<Sticker>> for StickerPermutation {
+    fn from(stickers: Vec<Sticker>) -> Self {
+        Self(stickers.into_boxed_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn twist_permutation(ty: PuzzleTypeEnum, twists: &str) -> StickerPermutation {
+        let mut puzzle = Puzzle::new(ty);
+        for twist in ty.parse_twists(twists).unwrap() {
+            puzzle.twist(twist).unwrap();
+        }
+        puzzle.permutation().clone()
+    }
+
+    #[test]
+    fn test_permutation_composition() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let r = twist_permutation(ty, "R");
+        let u = twist_permutation(ty, "U");
+        assert_eq!(r.then(&u), twist_permutation(ty, "R U"));
+        assert_eq!(r.inverse(), twist_permutation(ty, "R'"));
+        assert!(r.then(&r.inverse()).is_identity());
+
+        // A quarter turn of a face moves 20 stickers in 4-cycles.
+        let cycles = r.cycles();
+        assert_eq!(cycles.len(), 5);
+        assert!(cycles.iter().all(|cycle| cycle.len() == 4));
+    }
+}
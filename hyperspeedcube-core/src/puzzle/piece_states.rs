@@ -0,0 +1,155 @@
+//! Implementation shared between puzzles whose state is an orientation for
+//! each piece, such as Rubik's cubes.
+
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use super::*;
+
+/// Puzzle state made of an orientation for each piece, which determines the
+/// location of the piece and the direction that each of its stickers faces.
+pub(super) trait PieceStates: PuzzleType + Sized {
+    /// Orientation of a single piece.
+    type PieceState: Copy + Default + Eq + Hash;
+    /// Location of a piece, given by its coordinates.
+    type Location: Copy + Eq + Hash;
+    /// Direction that a sticker faces.
+    type Facing: Copy + Eq + Hash;
+
+    /// Returns a solved puzzle of the same type.
+    fn solved(&self) -> Self;
+    /// Returns the orientations that a piece can be twisted into from `state`
+    /// using a single twist.
+    fn twisted_piece_states(state: Self::PieceState) -> Vec<Self::PieceState>;
+    /// Returns the orientation of every piece.
+    fn piece_states_mut(&mut self) -> &mut [Self::PieceState];
+    /// Returns the permutation of stickers, which must be kept up to date
+    /// with the orientation of every piece.
+    fn permutation_mut(&mut self) -> &mut StickerPermutation;
+    /// Returns the sticker at each location and facing when the puzzle is
+    /// solved.
+    fn home_stickers(&self) -> &HashMap<(Self::Location, Self::Facing), Sticker>;
+
+    /// Returns the current location of a piece.
+    fn piece_location(&self, piece: Piece) -> Self::Location;
+    /// Returns the direction that a sticker currently faces.
+    fn sticker_face(&self, sticker: Sticker) -> Self::Facing;
+
+    /// Returns the sticker that occupies the current position of `sticker`
+    /// when the puzzle is solved.
+    fn home_sticker(&self, sticker: Sticker) -> Sticker {
+        let pos = self.piece_location(self.info(sticker).piece);
+        self.home_stickers()[&(pos, self.sticker_face(sticker))]
+    }
+    /// Updates the permutation for the stickers of pieces that have moved.
+    fn update_permutation(&mut self, pieces: impl IntoIterator<Item = Piece>) {
+        for piece in pieces {
+            for i in 0..self.info(piece).stickers.len() {
+                let sticker = self.info(piece).stickers[i];
+                let home = self.home_sticker(sticker);
+                self.permutation_mut().set(sticker, home);
+            }
+        }
+    }
+
+    /// Returns every orientation that a piece can be twisted into.
+    fn all_piece_states() -> Vec<Self::PieceState> {
+        let mut seen = HashSet::new();
+        let mut ret = vec![];
+        let mut queue = vec![Self::PieceState::default()];
+        while let Some(state) = queue.pop() {
+            if seen.insert(state) {
+                ret.push(state);
+                queue.extend(Self::twisted_piece_states(state));
+            }
+        }
+        ret
+    }
+
+    /// Moves the pieces so that each sticker has the color at the same index
+    /// in `colors`. This implements [`PuzzleState::repaint()`].
+    fn repaint_pieces(&mut self, colors: &[Face]) -> Result<(), String> {
+        if colors.len() != self.stickers().len() {
+            return Err(format!(
+                "expected {} sticker colors but got {}",
+                self.stickers().len(),
+                colors.len(),
+            ));
+        }
+
+        // Record the desired color at each sticker position.
+        let desired_colors: HashMap<(Self::Location, Self::Facing), Face> = (0..colors.len() as _)
+            .map(Sticker)
+            .zip(colors)
+            .map(|(sticker, &color)| {
+                let pos = self.piece_location(self.info(sticker).piece);
+                ((pos, self.sticker_face(sticker)), color)
+            })
+            .collect();
+
+        // Find a location and orientation for each piece that matches those
+        // colors. Pieces with identical colors are interchangeable, so it's
+        // fine to greedily take the first location that fits.
+        let orientations = Self::all_piece_states();
+        let mut new_state = self.solved();
+        let mut occupied = HashSet::new();
+        for piece in (0..self.pieces().len() as _).map(Piece) {
+            let fits = orientations.iter().any(|&orientation| {
+                new_state.piece_states_mut()[piece.0 as usize] = orientation;
+                let pos = new_state.piece_location(piece);
+                !occupied.contains(&pos)
+                    && self.info(piece).stickers.iter().all(|&sticker| {
+                        let facet = (pos, new_state.sticker_face(sticker));
+                        desired_colors.get(&facet) == Some(&self.info(sticker).color)
+                    })
+            });
+            if !fits {
+                return Err(format!(
+                    "there is nowhere to put the {} piece",
+                    self.info(piece)
+                        .stickers
+                        .iter()
+                        .map(|&sticker| self.info(self.info(sticker).color).symbol)
+                        .join(""),
+                ));
+            }
+            occupied.insert(new_state.piece_location(piece));
+        }
+
+        new_state.update_permutation((0..self.pieces().len() as _).map(Piece));
+        *self = new_state;
+        Ok(())
+    }
+}
+
+/// Implements equality, hashing, and indexing by piece for a puzzle with
+/// `piece_states` and `permutation` fields.
+macro_rules! impl_piece_states_traits {
+    ($puzzle:ty, $piece_state:ty) => {
+        impl Eq for $puzzle {}
+        impl PartialEq for $puzzle {
+            fn eq(&self, other: &Self) -> bool {
+                self.piece_states == other.piece_states
+            }
+        }
+        impl std::hash::Hash for $puzzle {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                // Equal piece states always have equal permutations.
+                std::hash::Hash::hash(&self.permutation, state);
+            }
+        }
+        impl std::ops::Index<Piece> for $puzzle {
+            type Output = $piece_state;
+
+            fn index(&self, piece: Piece) -> &Self::Output {
+                &self.piece_states[piece.0 as usize]
+            }
+        }
+        impl std::ops::IndexMut<Piece> for $puzzle {
+            fn index_mut(&mut self, piece: Piece) -> &mut Self::Output {
+                &mut self.piece_states[piece.0 as usize]
+            }
+        }
+    };
+}
@@ -0,0 +1,139 @@
+//! Property-based tests that check invariants of every puzzle type using
+//! random twist sequences.
+
+use itertools::Itertools;
+use proptest::prelude::*;
+use strum::IntoEnumIterator;
+
+use super::*;
+
+/// Maximum layer count to test, to keep large puzzles from slowing down the
+/// tests.
+const MAX_TESTED_LAYER_COUNT: u8 = 4;
+/// Maximum length of a generated twist sequence.
+const MAX_SEQUENCE_LEN: usize = 30;
+
+fn puzzle_type() -> impl Strategy<Value = PuzzleTypeEnum> {
+    let types = PuzzleTypeEnum::iter_all()
+        .filter(|ty| ty.layer_count() <= MAX_TESTED_LAYER_COUNT)
+        .collect_vec();
+    proptest::sample::select(types)
+}
+
+fn twist(ty: PuzzleTypeEnum) -> impl Strategy<Value = Twist> {
+    let desc = ty.desc();
+    (
+        0..desc.twist_axes().len() as u8,
+        0..desc.twist_directions().len() as u8,
+        1..=desc.all_layers().0,
+    )
+        .prop_map(|(axis, direction, layers)| Twist {
+            axis: TwistAxis(axis),
+            direction: TwistDirection(direction),
+            layers: LayerMask(layers),
+        })
+}
+
+fn puzzle_and_twists() -> impl Strategy<Value = (PuzzleTypeEnum, Vec<Twist>)> {
+    puzzle_type().prop_flat_map(|ty| {
+        (
+            Just(ty),
+            proptest::collection::vec(twist(ty), 0..MAX_SEQUENCE_LEN),
+        )
+    })
+}
+
+fn apply(ty: PuzzleTypeEnum, twists: &[Twist]) -> Puzzle {
+    let mut puzzle = Puzzle::new(ty);
+    for &twist in twists {
+        puzzle.twist(twist).unwrap();
+    }
+    puzzle
+}
+
+fn inverse(ty: PuzzleTypeEnum, twists: &[Twist]) -> Vec<Twist> {
+    twists
+        .iter()
+        .rev()
+        .map(|&twist| ty.reverse_twist(twist))
+        .collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn proptest_sequence_then_inverse_is_solved((ty, twists) in puzzle_and_twists()) {
+        let mut puzzle = apply(ty, &twists);
+        for twist in inverse(ty, &twists) {
+            puzzle.twist(twist).unwrap();
+        }
+        prop_assert!(puzzle.is_solved());
+        prop_assert_eq!(puzzle, Puzzle::new(ty));
+    }
+
+    #[test]
+    fn proptest_undo_redo_symmetry((ty, twists) in puzzle_and_twists()) {
+        let expected = apply(ty, &twists);
+
+        let mut puzzle = PuzzleController::new(ty);
+        for &twist in &twists {
+            puzzle.twist(twist).unwrap();
+        }
+        prop_assert_eq!(puzzle.latest(), &expected);
+
+        while puzzle.has_undo() {
+            puzzle.undo().unwrap();
+        }
+        prop_assert!(puzzle.is_solved());
+        prop_assert_eq!(puzzle.latest(), &Puzzle::new(ty));
+
+        while puzzle.has_redo() {
+            puzzle.redo().unwrap();
+        }
+        prop_assert_eq!(puzzle.latest(), &expected);
+    }
+
+    #[test]
+    fn proptest_metric_consistency((ty, twists) in puzzle_and_twists()) {
+        // Whole-puzzle rotations reset the state of some metrics, which makes
+        // them depend on the order of twists.
+        let twists = twists
+            .into_iter()
+            .filter(|twist| twist.layers != ty.all_layers())
+            .collect_vec();
+        let inverse = inverse(ty, &twists);
+        let desc = ty.desc();
+        let count = |metric: TwistMetric| metric.count_twists(&*desc, twists.iter().copied());
+
+        for metric in TwistMetric::iter() {
+            prop_assert_eq!(
+                count(metric),
+                metric.count_twists(&*desc, inverse.iter().copied()),
+                "{} count differs for the inverse sequence",
+                metric,
+            );
+        }
+
+        prop_assert_eq!(count(TwistMetric::Etm), twists.len());
+        prop_assert!(count(TwistMetric::Atm) <= count(TwistMetric::Stm));
+        prop_assert!(count(TwistMetric::Stm) <= count(TwistMetric::Btm));
+        prop_assert!(count(TwistMetric::Btm) <= count(TwistMetric::Obtm));
+        prop_assert!(count(TwistMetric::Stm) <= count(TwistMetric::Qstm));
+        prop_assert!(count(TwistMetric::Btm) <= count(TwistMetric::Qbtm));
+        prop_assert!(count(TwistMetric::Obtm) <= count(TwistMetric::Qobtm));
+    }
+
+    #[test]
+    fn proptest_notation_round_trip((ty, twists) in puzzle_and_twists()) {
+        let string = ty.twists_to_string(&twists);
+        let parsed = ty.parse_twists(&string);
+        prop_assert!(parsed.is_ok(), "error parsing {:?}: {:?}", string, parsed);
+        prop_assert_eq!(
+            apply(ty, &parsed.unwrap()),
+            apply(ty, &twists),
+            "state differs after round-tripping {:?}",
+            string,
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Cache of puzzle descriptions, shared between puzzle states.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+/// Cache of puzzle descriptions, keyed by the parameters used to generate them
+/// (such as layer count).
+///
+/// Descriptions are shared using [`Arc`]. When the registry holds more than
+/// its capacity, it evicts the least recently used descriptions that are not in
+/// use anywhere else.
+///
+/// Descriptions are built without holding the lock on the whole registry, so
+/// building a large description does not block access to other ones. Threads
+/// that request a description while it is being built wait for it instead of
+/// building it again.
+pub(super) struct Registry<K, V> {
+    capacity: usize,
+    state: Mutex<RegistryState<K, V>>,
+}
+impl<K: Clone + Eq + Hash, V> Registry<K, V> {
+    /// Constructs an empty registry that holds at most `capacity` descriptions
+    /// that are not in use.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(RegistryState {
+                entries: HashMap::new(),
+                clock: 0,
+            }),
+        }
+    }
+
+    /// Returns the description for `key`, calling `build` to generate it if it
+    /// is not already in the registry.
+    pub fn get(&self, key: K, build: impl FnOnce(&K) -> V) -> Arc<V> {
+        let slot = Arc::clone(&self.state.lock().unwrap().entry(&key, self.capacity).slot);
+        let mut value = slot.lock().unwrap();
+        Arc::clone(value.get_or_insert_with(|| Arc::new(build(&key))))
+    }
+
+    /// Returns the number of descriptions in the registry.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+}
+
+struct RegistryState<K, V> {
+    entries: HashMap<K, RegistryEntry<V>>,
+    /// Counter incremented on every access, used to find the least recently
+    /// used entry.
+    clock: u64,
+}
+impl<K: Clone + Eq + Hash, V> RegistryState<K, V> {
+    /// Returns the entry for `key`, adding an empty one if there is none.
+    fn entry(&mut self, key: &K, capacity: usize) -> &mut RegistryEntry<V> {
+        self.clock += 1;
+        if !self.entries.contains_key(key) {
+            self.evict_unused(capacity.saturating_sub(1));
+            self.entries.insert(
+                key.clone(),
+                RegistryEntry {
+                    slot: Arc::new(Mutex::new(None)),
+                    last_used: 0,
+                },
+            );
+        }
+        let entry = self.entries.get_mut(key).unwrap();
+        entry.last_used = self.clock;
+        entry
+    }
+
+    /// Evicts least recently used entries until there are at most `max_len`
+    /// or every remaining entry is in use.
+    fn evict_unused(&mut self, max_len: usize) {
+        while self.entries.len() > max_len {
+            let least_recently_used = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.is_unused())
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            match least_recently_used {
+                Some(key) => {
+                    self.entries.remove(&key);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+struct RegistryEntry<V> {
+    /// Description, or `None` if it has not been built yet.
+    slot: Arc<Mutex<Option<Arc<V>>>>,
+    last_used: u64,
+}
+impl<V> RegistryEntry<V> {
+    /// Returns whether the description is neither being built nor in use
+    /// outside the registry.
+    fn is_unused(&self) -> bool {
+        // The slot is only shared while a description is being fetched or
+        // built, and new references to it can only be made while the whole
+        // registry is locked, so locking it here never blocks.
+        Arc::strong_count(&self.slot) == 1
+            && match &*self.slot.lock().unwrap() {
+                Some(value) => Arc::strong_count(value) == 1,
+                None => true,
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_eviction() {
+        let registry = Registry::new(2);
+        let build = |&n: &u8| n.to_string();
+
+        let one = registry.get(1, build);
+        assert!(Arc::ptr_eq(&one, &registry.get(1, build)));
+
+        // Unused descriptions are evicted to stay within capacity.
+        drop(registry.get(2, build));
+        drop(registry.get(3, build));
+        assert_eq!(registry.len(), 2);
+
+        // Descriptions in use are never evicted.
+        assert!(Arc::ptr_eq(&one, &registry.get(1, build)));
+        let four = registry.get(4, build);
+        drop(registry.get(5, build));
+        drop(registry.get(6, build));
+        assert_eq!(registry.len(), 3);
+        assert!(Arc::ptr_eq(&four, &registry.get(4, build)));
+        assert!(Arc::ptr_eq(&one, &registry.get(1, build)));
+
+        // Descriptions are evicted once they are no longer in use.
+        drop(four);
+        drop(registry.get(7, build));
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn test_registry_nested_build() {
+        let registry = Registry::new(2);
+
+        // Building a description does not lock the registry, so it can depend
+        // on other descriptions.
+        let value = registry.get(2, |&n: &u8| *registry.get(n - 1, |&n| n * 10) + n);
+        assert_eq!(*value, 12);
+        assert_eq!(*registry.get(1, |_| unreachable!()), 10);
+    }
+}
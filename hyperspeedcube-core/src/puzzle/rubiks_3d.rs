@@ -5,11 +5,13 @@ use itertools::Itertools;
 use num_enum::FromPrimitive;
 use serde::{de::Error, Deserialize, Deserializer};
 use smallvec::smallvec;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Index, IndexMut, RangeInclusive};
-use std::sync::Mutex;
+use std::sync::Arc;
 use strum::IntoEnumIterator;
 
+use super::piece_states::PieceStates;
+use super::registry::Registry;
 use super::*;
 
 pub const DEFAULT_LAYER_COUNT: u8 = 3;
@@ -30,156 +32,195 @@ where
     Ok(layer_count)
 }
 
-pub(super) fn puzzle_type(layer_count: u8) -> &'static dyn PuzzleType {
+/// Maximum number of unused puzzle descriptions to keep cached.
+const REGISTRY_CAPACITY: usize = 4;
+
+/// Parameters that determine a puzzle description.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct DescriptionKey {
+    layer_count: u8,
+}
+
+lazy_static! {
+    static ref REGISTRY: Registry<DescriptionKey, Rubiks3DDescription> =
+        Registry::new(REGISTRY_CAPACITY);
+}
+
+/// Names of the puzzles, indexed by layer count minus one.
+const NAMES: [&str; MAX_LAYER_COUNT as usize] = [
+    "1x1x1", "2x2x2", "3x3x3", "4x4x4", "5x5x5", "6x6x6", "7x7x7", "8x8x8", "9x9x9",
+];
+
+pub(super) fn puzzle_name(layer_count: u8) -> &'static str {
+    NAMES[layer_count as usize - 1]
+}
+
+pub(super) fn puzzle_type(layer_count: u8) -> Arc<dyn PuzzleType + Send + Sync> {
     puzzle_description(layer_count)
 }
 
-fn puzzle_description(layer_count: u8) -> &'static Rubiks3DDescription {
-    lazy_static! {
-        static ref CACHE: Mutex<HashMap<u8, &'static Rubiks3DDescription>> =
-            Mutex::new(HashMap::new());
-    }
+fn puzzle_description(layer_count: u8) -> Arc<Rubiks3DDescription> {
+    REGISTRY.get(DescriptionKey { layer_count }, build_description)
+}
 
+fn build_description(key: &DescriptionKey) -> Rubiks3DDescription {
+    let layer_count = key.layer_count;
     assert!(LAYER_COUNT_RANGE.contains(&layer_count));
 
-    CACHE.lock().unwrap().entry(layer_count).or_insert_with(|| {
-        let mut pieces = vec![];
-        let mut stickers = vec![];
+    let mut pieces = vec![];
+    let mut stickers = vec![];
 
-        let full_range = (0..layer_count).collect_vec();
-        let ends = [0, layer_count - 1];
+    let full_range = (0..layer_count).collect_vec();
+    let ends = [0, layer_count - 1];
 
-        let center_coord = (layer_count % 2 == 0) as u8;
-        let mut piece_types = (center_coord..=layer_count / 2)
-            .flat_map(|y| {
-                (center_coord..=y).map(move |x| PieceTypeEnum::from_offset([x, y, layer_count / 2]))
-            })
-            .collect_vec();
-        piece_types.sort();
-
-        let mut piece_locations = vec![];
-        for z in 0..layer_count {
-            let z_min = z == 0;
-            let z_max = z == layer_count - 1;
-
-            for y in 0..layer_count {
-                let y_min = y == 0;
-                let y_max = y == layer_count - 1;
-
-                let x_range = if z_min || z_max || y_min || y_max {
-                    full_range.as_slice()
-                } else {
-                    ends.as_slice()
+    let center_coord = (layer_count % 2 == 0) as u8;
+    let mut piece_types = (center_coord..=layer_count / 2)
+        .flat_map(|y| {
+            (center_coord..=y).map(move |x| PieceTypeEnum::from_offset([x, y, layer_count / 2]))
+        })
+        .collect_vec();
+    piece_types.sort();
+
+    let mut piece_locations = vec![];
+    for z in 0..layer_count {
+        let z_min = z == 0;
+        let z_max = z == layer_count - 1;
+
+        for y in 0..layer_count {
+            let y_min = y == 0;
+            let y_max = y == layer_count - 1;
+
+            let x_range = if z_min || z_max || y_min || y_max {
+                full_range.as_slice()
+            } else {
+                ends.as_slice()
+            };
+            for &x in x_range {
+                let x_min = x == 0;
+                let x_max = x == layer_count - 1;
+
+                let piece = Piece(pieces.len() as _);
+                let mut piece_stickers = smallvec![];
+
+                let mut push_sticker_if = |condition, face| {
+                    if condition {
+                        piece_stickers.push(Sticker(stickers.len() as _));
+                        stickers.push(StickerInfo { piece, color: face });
+                    }
                 };
-                for &x in x_range {
-                    let x_min = x == 0;
-                    let x_max = x == layer_count - 1;
-
-                    let piece = Piece(pieces.len() as _);
-                    let mut piece_stickers = smallvec![];
-
-                    let mut push_sticker_if = |condition, face| {
-                        if condition {
-                            piece_stickers.push(Sticker(stickers.len() as _));
-                            stickers.push(StickerInfo { piece, color: face });
-                        }
-                    };
-                    push_sticker_if(x_max, FaceEnum::R.into());
-                    push_sticker_if(x_min, FaceEnum::L.into());
-                    push_sticker_if(y_max, FaceEnum::U.into());
-                    push_sticker_if(y_min, FaceEnum::D.into());
-                    push_sticker_if(z_max, FaceEnum::F.into());
-                    push_sticker_if(z_min, FaceEnum::B.into());
-
-                    let piece_type = {
-                        // Compute the distance of each coordinate from the
-                        // center. 0 = centered along axis (only exists for odd
-                        // puzzles).
-                        let center = (layer_count - 1) as f32 / 2.0;
-                        let x = (x as f32 - center).abs().ceil() as u8;
-                        let y = (y as f32 - center).abs().ceil() as u8;
-                        let z = (z as f32 - center).abs().ceil() as u8;
-                        PieceType(
-                            piece_types
-                                .iter()
-                                .find_position(|&&p| p == PieceTypeEnum::from_offset([x, y, z]))
-                                .map(|(i, _)| i)
-                                .unwrap_or(0) as _, // shouldn't ever happen
-                        )
-                    };
-
-                    piece_locations.push([x, y, z]);
-                    pieces.push(PieceInfo {
-                        stickers: piece_stickers,
-                        piece_type,
-                    })
-                }
+                push_sticker_if(x_max, FaceEnum::R.into());
+                push_sticker_if(x_min, FaceEnum::L.into());
+                push_sticker_if(y_max, FaceEnum::U.into());
+                push_sticker_if(y_min, FaceEnum::D.into());
+                push_sticker_if(z_max, FaceEnum::F.into());
+                push_sticker_if(z_min, FaceEnum::B.into());
+
+                let piece_type = {
+                    // Compute the distance of each coordinate from the
+                    // center. 0 = centered along axis (only exists for odd
+                    // puzzles).
+                    let center = (layer_count - 1) as f32 / 2.0;
+                    let x = (x as f32 - center).abs().ceil() as u8;
+                    let y = (y as f32 - center).abs().ceil() as u8;
+                    let z = (z as f32 - center).abs().ceil() as u8;
+                    PieceType(
+                        piece_types
+                            .iter()
+                            .find_position(|&&p| p == PieceTypeEnum::from_offset([x, y, z]))
+                            .map(|(i, _)| i)
+                            .unwrap_or(0) as _, // shouldn't ever happen
+                    )
+                };
+
+                piece_locations.push([x, y, z]);
+                pieces.push(PieceInfo {
+                    stickers: piece_stickers,
+                    piece_type,
+                })
             }
         }
+    }
 
-        let mut aliases = vec![];
-        {
-            use FaceEnum::*;
-            let all_layers = LayerMask::all_layers(layer_count);
-            aliases.push(("x".to_string(), Alias::AxisLayers(R.into(), all_layers)));
-            aliases.push(("y".to_string(), Alias::AxisLayers(U.into(), all_layers)));
-            aliases.push(("z".to_string(), Alias::AxisLayers(F.into(), all_layers)));
-
-            if let Some(slice_layers) = LayerMask::slice_layers(layer_count) {
-                aliases.push(("M".to_string(), Alias::AxisLayers(L.into(), slice_layers)));
-                aliases.push(("E".to_string(), Alias::AxisLayers(D.into(), slice_layers)));
-                aliases.push(("S".to_string(), Alias::AxisLayers(F.into(), slice_layers)));
+    let mut aliases = vec![];
+    {
+        use FaceEnum::*;
+        let all_layers = LayerMask::all_layers(layer_count);
+        aliases.push(("x".to_string(), Alias::AxisLayers(R.into(), all_layers)));
+        aliases.push(("y".to_string(), Alias::AxisLayers(U.into(), all_layers)));
+        aliases.push(("z".to_string(), Alias::AxisLayers(F.into(), all_layers)));
+
+        if let Some(slice_layers) = LayerMask::slice_layers(layer_count) {
+            aliases.push(("M".to_string(), Alias::AxisLayers(L.into(), slice_layers)));
+            aliases.push(("E".to_string(), Alias::AxisLayers(D.into(), slice_layers)));
+            aliases.push(("S".to_string(), Alias::AxisLayers(F.into(), slice_layers)));
+        }
+
+        // Add slice move aliases, such as "Rs" for R and L' at once.
+        if let Some(outer_layers) = LayerMask::outer_layers(layer_count) {
+            for f in FaceEnum::iter() {
+                aliases.push((
+                    format!("{}s", f.symbol_upper()),
+                    Alias::AxisLayers(f.into(), outer_layers),
+                ));
             }
+        }
 
-            if layer_count >= 4 {
-                for f in FaceEnum::iter() {
-                    aliases.push((
-                        f.symbol_lower().to_string(),
-                        Alias::AxisLayers(f.into(), LayerMask(2)),
-                    ))
-                }
+        if layer_count >= 4 {
+            for f in FaceEnum::iter() {
+                aliases.push((
+                    f.symbol_lower().to_string(),
+                    Alias::AxisLayers(f.into(), LayerMask(2)),
+                ))
             }
         }
-        // Try to match longer aliases first.
-        aliases.sort_by_key(|(s, _)| -(s.len() as isize));
-
-        let notation = NotationScheme {
-            axis_names: FaceEnum::iter()
-                .map(|f| f.symbol_upper().to_string())
-                .collect(),
-            direction_names: TwistDirectionEnum::iter()
-                .map(|dir| TwistDirectionName::Same(dir.symbol().to_string()))
-                .collect(),
-            block_suffix: Some("w".to_string()),
-            aliases,
-        };
+    }
+    // Try to match longer aliases first.
+    aliases.sort_by_key(|(s, _)| -(s.len() as isize));
+
+    let notation = NotationScheme {
+        axis_names: FaceEnum::iter()
+            .map(|f| f.symbol_upper().to_string())
+            .collect(),
+        direction_names: TwistDirectionEnum::iter()
+            .map(|dir| TwistDirectionName::Same(dir.symbol().to_string()))
+            .collect(),
+        block_suffix: Some("w".to_string()),
+        aliases,
+    };
+
+    let home_stickers = stickers
+        .iter()
+        .enumerate()
+        .map(|(i, info)| {
+            let pos = piece_locations[info.piece.0 as usize];
+            ((pos, info.color.into()), Sticker(i as _))
+        })
+        .collect();
 
-        // It's not like we'll ever clear the cache anyway, so just leak it
-        // and let us have the 'static lifetimes.
-        Box::leak(Box::new(Rubiks3DDescription {
-            name: format!("{0}x{0}x{0}", layer_count),
+    Rubiks3DDescription {
+        name: puzzle_name(layer_count),
 
-            layer_count,
+        layer_count,
 
-            faces: FaceEnum::iter().map(|f| f.info()).collect(),
-            pieces,
-            stickers,
-            twist_axes: FaceEnum::iter().map(|f| f.twist_axis_info()).collect(),
-            twist_directions: TwistDirectionEnum::iter().map(|dir| dir.info()).collect(),
-            piece_types: piece_types
-                .into_iter()
-                .map(|piece_type| PieceTypeInfo::new(piece_type.to_string()))
-                .collect(),
-            notation,
+        faces: FaceEnum::iter().map(|f| f.info()).collect(),
+        pieces,
+        stickers,
+        twist_axes: FaceEnum::iter().map(|f| f.twist_axis_info()).collect(),
+        twist_directions: TwistDirectionEnum::iter().map(|dir| dir.info()).collect(),
+        piece_types: piece_types
+            .into_iter()
+            .map(|piece_type| PieceTypeInfo::new(piece_type.to_string()))
+            .collect(),
+        notation,
 
-            piece_locations,
-        }))
-    })
+        piece_locations,
+        home_stickers,
+    }
 }
 
 #[derive(Debug, Clone)]
 struct Rubiks3DDescription {
-    name: String,
+    name: &'static str,
 
     layer_count: u8,
 
@@ -192,6 +233,9 @@ struct Rubiks3DDescription {
     notation: NotationScheme,
 
     piece_locations: Vec<[u8; 3]>,
+    /// Sticker at each sticker position when solved, keyed by the position of
+    /// the piece and the face that the sticker is on.
+    home_stickers: HashMap<([u8; 3], FaceEnum), Sticker>,
 }
 impl PuzzleType for Rubiks3DDescription {
     fn ty(&self) -> PuzzleTypeEnum {
@@ -200,7 +244,7 @@ impl PuzzleType for Rubiks3DDescription {
         }
     }
     fn name(&self) -> &str {
-        &self.name
+        self.name
     }
     fn family_display_name(&self) -> &'static str {
         "Rubik's 3D"
@@ -244,6 +288,14 @@ impl PuzzleType for Rubiks3DDescription {
         &self.piece_types
     }
 
+    fn sticker_grid_position(&self, sticker: Sticker) -> smallvec::SmallVec<[u8; 3]> {
+        let face = FaceEnum::from(self.info(sticker).color);
+        let pos = self.piece_locations[self.info(sticker).piece.0 as usize];
+        let [row, col] =
+            cube_face_grid_position(face.axis() as usize, face.sign(), pos, self.layer_count);
+        smallvec![row, col]
+    }
+
     fn opposite_twist_axis(&self, twist_axis: TwistAxis) -> Option<TwistAxis> {
         Some(FaceEnum::from(twist_axis).opposite().into())
     }
@@ -272,6 +324,25 @@ impl PuzzleType for Rubiks3DDescription {
             layers: self.all_layers(),
         })
     }
+    fn rotation_plane(&self, from: TwistAxis, to: TwistAxis) -> Option<[usize; 2]> {
+        let plane = FaceEnum::from(from).rotation_plane_to(to.into())?;
+        Some(plane.map(|axis| axis as usize))
+    }
+    fn make_rotation_twist(&self, from: TwistAxis, to: TwistAxis) -> Option<Twist> {
+        let plane = FaceEnum::from(from).rotation_plane_to(to.into())?;
+        // Rotate around the remaining axis.
+        let axis = Axis::iter().find(|ax| !plane.contains(ax))?;
+        let direction = if axis.perpendiculars() == plane {
+            TwistDirectionEnum::CW90
+        } else {
+            TwistDirectionEnum::CCW90
+        };
+        Some(Twist {
+            axis: axis.face(Sign::Pos).into(),
+            direction: direction.into(),
+            layers: self.all_layers(),
+        })
+    }
 
     fn canonicalize_twist(&self, twist: Twist) -> Twist {
         let face: FaceEnum = twist.axis.into();
@@ -296,6 +367,21 @@ impl PuzzleType for Rubiks3DDescription {
         }
     }
 
+    fn mirror_twist(&self, twist: Twist, axis: TwistAxis) -> Twist {
+        let mirror_axis = FaceEnum::from(axis).axis();
+        let mut face: FaceEnum = twist.axis.into();
+        if face.axis() == mirror_axis {
+            face = face.opposite();
+        }
+        let direction: TwistDirectionEnum = twist.direction.into();
+        // Reflection always reverses the handedness of a rotation.
+        self.canonicalize_twist(Twist {
+            axis: face.into(),
+            direction: direction.rev().into(),
+            layers: twist.layers,
+        })
+    }
+
     fn reverse_twist_direction(&self, direction: TwistDirection) -> TwistDirection {
         use TwistDirectionEnum::*;
 
@@ -335,32 +421,19 @@ impl PuzzleType for Rubiks3DDescription {
 
 #[derive(Debug, Clone)]
 pub struct Rubiks3D {
-    desc: &'static Rubiks3DDescription,
+    desc: Arc<Rubiks3DDescription>,
     piece_states: Box<[PieceState]>,
+    /// Permutation of stickers, updated along with `piece_states`.
+    permutation: StickerPermutation,
 }
-impl Eq for Rubiks3D {}
-impl PartialEq for Rubiks3D {
-    fn eq(&self, other: &Self) -> bool {
-        self.piece_states == other.piece_states
-    }
-}
-impl Index<Piece> for Rubiks3D {
-    type Output = PieceState;
-
-    fn index(&self, piece: Piece) -> &Self::Output {
-        &self.piece_states[piece.0 as usize]
-    }
-}
-impl IndexMut<Piece> for Rubiks3D {
-    fn index_mut(&mut self, piece: Piece) -> &mut Self::Output {
-        &mut self.piece_states[piece.0 as usize]
-    }
-}
+impl_piece_states_traits!(Rubiks3D, PieceState);
 impl PuzzleState for Rubiks3D {
     fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
-        for piece in self.pieces_affected_by_twist(twist) {
+        let pieces = self.pieces_affected_by_twist(twist);
+        for &piece in &pieces {
             self[piece] = self[piece].twist(twist.axis.into(), twist.direction.into());
         }
+        self.update_permutation(pieces);
         Ok(())
     }
     fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
@@ -444,9 +517,14 @@ impl PuzzleState for Rubiks3D {
     ) -> Option<StickerGeometry> {
         let piece = self.info(sticker).piece;
         let face = self.sticker_face(sticker);
+        let p = p.for_face(face.into());
 
         let mut transform = p.view_transform;
         if let Some((twist, progress)) = p.twist_animation {
+            let twist = p
+                .simultaneous_twist
+                .filter(|&t| self.is_piece_affected_by_twist(t, piece))
+                .unwrap_or(twist);
             if self.is_piece_affected_by_twist(twist, piece) {
                 let twist_axis: FaceEnum = twist.axis.into();
                 let twist_transform = twist_axis.twist_matrix(twist.direction.into(), progress);
@@ -477,6 +555,14 @@ impl PuzzleState for Rubiks3D {
         let ccw_twist = self.reverse_twist(cw_twist);
         let recenter = self.make_recenter_twist(face.into()).ok();
 
+        // From inside the puzzle, the backfaces are the ones facing the
+        // camera, so flip culling.
+        let (show_frontfaces, show_backfaces) = if p.interior_view {
+            (p.show_backfaces, p.show_frontfaces)
+        } else {
+            (p.show_frontfaces, p.show_backfaces)
+        };
+
         Some(StickerGeometry::new_double_quad(
             [
                 center - u - v,
@@ -489,8 +575,8 @@ impl PuzzleState for Rubiks3D {
                 ccw: Some(ccw_twist),
                 recenter,
             },
-            p.show_frontfaces,
-            p.show_backfaces,
+            show_frontfaces,
+            show_backfaces,
         ))
     }
 
@@ -507,18 +593,108 @@ impl PuzzleState for Rubiks3D {
         }
         true
     }
-}
-#[delegate_to_methods]
-#[delegate(PuzzleType, target_ref = "desc")]
-impl Rubiks3D {
-    pub fn new(layer_count: u8) -> Self {
-        let desc = puzzle_description(layer_count);
-        let piece_states = vec![PieceState::default(); desc.pieces().len()].into_boxed_slice();
-        Self { desc, piece_states }
+
+    fn are_pieces_solved(&self, pieces: &[Piece]) -> bool {
+        let mut color_per_facet = vec![None; self.faces().len()];
+        let mut used_colors = HashSet::new();
+        for &piece in pieces {
+            for &sticker in &self.info(piece).stickers {
+                let color = self.sticker_face(sticker);
+                let facet = self.info(sticker).color.0 as usize;
+                if color_per_facet[facet] == None {
+                    // Two facets cannot be on the same face.
+                    if !used_colors.insert(color) {
+                        return false;
+                    }
+                    color_per_facet[facet] = Some(color);
+                } else if color_per_facet[facet] != Some(color) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+    fn is_piece_solved(&self, piece: Piece) -> bool {
+        self.info(piece)
+            .stickers
+            .iter()
+            .all(|&sticker| self.sticker_face(sticker) == FaceEnum::from(self.info(sticker).color))
     }
 
-    fn desc(&self) -> &Rubiks3DDescription {
-        self.desc
+    fn sticker_permutation(&self) -> Vec<Sticker> {
+        (0..self.stickers().len() as _)
+            .map(Sticker)
+            .map(|sticker| self.home_sticker(sticker))
+            .collect()
+    }
+    fn permutation(&self) -> &StickerPermutation {
+        &self.permutation
+    }
+
+    fn repaint(&mut self, colors: &[Face]) -> Result<(), String> {
+        self.repaint_pieces(colors)
+    }
+
+    fn check_solvable(&self) -> Result<(), String> {
+        if self.layer_count() < 2 {
+            return Ok(());
+        }
+
+        let corners = self.fixed_orbit(3);
+        let corner_twist: usize = corners
+            .iter()
+            .map(|&piece| self.corner_orientation(piece))
+            .sum();
+        if corner_twist % 3 != 0 {
+            return Err("corner orientation: a corner is twisted in place".to_string());
+        }
+
+        if self.layer_count() % 2 == 1 {
+            let edges = self.fixed_orbit(2);
+            let flipped_edges = edges
+                .iter()
+                .filter(|&&piece| self.is_edge_flipped(piece))
+                .count();
+            if flipped_edges % 2 != 0 {
+                return Err("edge orientation: an edge is flipped in place".to_string());
+            }
+
+            // Every quarter turn of an outer or middle layer moves two of
+            // these orbits in a 4-cycle.
+            let centers = self.fixed_orbit(1);
+            let odd_orbits = [corners, edges, centers]
+                .iter()
+                .filter(|orbit| self.is_odd_orbit_permutation(orbit))
+                .count();
+            if odd_orbits % 2 != 0 {
+                return Err("permutation parity: two pieces are swapped".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+impl PieceStates for Rubiks3D {
+    type PieceState = PieceState;
+    type Location = [u8; 3];
+    type Facing = FaceEnum;
+
+    fn solved(&self) -> Self {
+        Self::new(self.layer_count())
+    }
+    fn twisted_piece_states(state: PieceState) -> Vec<PieceState> {
+        itertools::iproduct!(FaceEnum::iter(), TwistDirectionEnum::iter())
+            .map(|(face, direction)| state.twist(face, direction))
+            .collect()
+    }
+    fn piece_states_mut(&mut self) -> &mut [PieceState] {
+        &mut self.piece_states
+    }
+    fn permutation_mut(&mut self) -> &mut StickerPermutation {
+        &mut self.permutation
+    }
+    fn home_stickers(&self) -> &HashMap<([u8; 3], FaceEnum), Sticker> {
+        &self.desc.home_stickers
     }
 
     fn piece_location(&self, piece: Piece) -> [u8; 3] {
@@ -543,6 +719,96 @@ impl Rubiks3D {
             Sign::Neg => current_face.opposite(),
         }
     }
+}
+#[delegate_to_methods]
+#[delegate(PuzzleType, target_ref = "desc")]
+impl Rubiks3D {
+    pub fn new(layer_count: u8) -> Self {
+        let desc = puzzle_description(layer_count);
+        let piece_states = vec![PieceState::default(); desc.pieces().len()].into_boxed_slice();
+        let permutation = StickerPermutation::identity(desc.stickers().len());
+        Self {
+            desc,
+            piece_states,
+            permutation,
+        }
+    }
+
+    fn desc(&self) -> &Rubiks3DDescription {
+        &self.desc
+    }
+
+    /// Returns the pieces with `outer_count` coordinates on the outside of the
+    /// puzzle and all other coordinates in the middle layer. Unlike other
+    /// pieces, these always have distinct colors and can never be moved to a
+    /// position outside the orbit.
+    fn fixed_orbit(&self, outer_count: usize) -> Vec<Piece> {
+        let n = self.layer_count();
+        let is_outer = |x: u8| x == 0 || x == n - 1;
+        let is_middle = |x: u8| 2 * x + 1 == n;
+        (0..self.pieces().len() as _)
+            .map(Piece)
+            .filter(|&piece| {
+                let pos = self.desc.piece_locations[piece.0 as usize];
+                pos.iter().filter(|&&x| is_outer(x)).count() == outer_count
+                    && pos.iter().all(|&x| is_outer(x) || is_middle(x))
+            })
+            .collect()
+    }
+    /// Returns whether the pieces of an orbit returned by `fixed_orbit()` are
+    /// in an odd permutation.
+    fn is_odd_orbit_permutation(&self, orbit: &[Piece]) -> bool {
+        let home_indices: HashMap<[u8; 3], usize> = orbit
+            .iter()
+            .enumerate()
+            .map(|(i, &piece)| (self.desc.piece_locations[piece.0 as usize], i))
+            .collect();
+        let perm = orbit
+            .iter()
+            .map(|&piece| home_indices[&self.piece_location(piece)])
+            .collect::<Vec<_>>();
+        crate::util::is_odd_permutation(&perm)
+    }
+    /// Returns how many thirds of a turn a corner is twisted, using its sticker
+    /// from the U or D face as a reference.
+    fn corner_orientation(&self, piece: Piece) -> usize {
+        let reference = self
+            .info(piece)
+            .stickers
+            .iter()
+            .find(|&&sticker| FaceEnum::from(self.info(sticker).color).axis() == Axis::Y);
+        let Some(&reference) = reference else {
+            return 0;
+        };
+        let steps = (self.sticker_face(reference).axis() as usize + 2) % 3;
+        // Corners with opposite handedness are twisted in opposite directions.
+        let pos = self.piece_location(piece);
+        if pos.iter().filter(|&&x| x == 0).count() % 2 == 0 {
+            steps
+        } else {
+            (3 - steps) % 3
+        }
+    }
+    /// Returns whether an edge is flipped, using its sticker from the U or D
+    /// face (or else the F or B face) as a reference.
+    fn is_edge_flipped(&self, piece: Piece) -> bool {
+        let stickers = &self.info(piece).stickers;
+        let color_axis = |sticker: Sticker| FaceEnum::from(self.info(sticker).color).axis();
+        let reference = stickers
+            .iter()
+            .find(|&&s| color_axis(s) == Axis::Y)
+            .or_else(|| stickers.iter().find(|&&s| color_axis(s) == Axis::Z));
+        let Some(&reference) = reference else {
+            return false;
+        };
+        let y = self.piece_location(piece)[Axis::Y as usize];
+        let reference_axis = if y == 0 || y == self.layer_count() - 1 {
+            Axis::Y
+        } else {
+            Axis::Z
+        };
+        self.sticker_face(reference).axis() != reference_axis
+    }
 
     fn piece_center_3d(&self, piece: Piece, p: StickerGeometryParams) -> Point3<f32> {
         let pos = self.piece_location(piece);
@@ -630,7 +896,7 @@ impl PieceState {
 
 #[derive(EnumIter, FromPrimitive, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(u8)]
-enum FaceEnum {
+pub(super) enum FaceEnum {
     #[default]
     R = 0,
     L = 1,
@@ -742,6 +1008,21 @@ impl FaceEnum {
         } * self.sign().float())
     }
 
+    /// Returns the oriented plane of the 90-degree rotation that moves this
+    /// face to `target`, or `None` if they are on the same axis. Rotating in
+    /// the plane `[a, b]` moves the positive end of `a` toward the positive end
+    /// of `b`.
+    fn rotation_plane_to(self, target: FaceEnum) -> Option<[Axis; 2]> {
+        let (from, to) = (self.axis(), target.axis());
+        if from == to {
+            return None;
+        }
+        Some(match self.sign() * target.sign() {
+            Sign::Pos => [from, to],
+            Sign::Neg => [to, from],
+        })
+    }
+
     /// Returns the axes parallel to this face (all except the perpendicular
     /// axis).
     fn parallel_axes(self) -> [Axis; 2] {
@@ -916,6 +1197,20 @@ impl Axis {
         [Axis::X, Axis::Y, Axis::Z].into_iter()
     }
 
+    /// Returns the face on this axis with the given sign.
+    fn face(self, sign: Sign) -> FaceEnum {
+        use FaceEnum::*;
+
+        match (self, sign) {
+            (Axis::X, Sign::Pos) => R,
+            (Axis::X, Sign::Neg) => L,
+            (Axis::Y, Sign::Pos) => U,
+            (Axis::Y, Sign::Neg) => D,
+            (Axis::Z, Sign::Pos) => F,
+            (Axis::Z, Sign::Neg) => B,
+        }
+    }
+
     /// Returns the unit vector along this axis.
     fn unit_vec3(self) -> Vector3<f32> {
         match self {
@@ -941,6 +1236,144 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rubiks_3d_rotation_planes() {
+        // Each rotation should move one face to the other, both as a twist and
+        // as a view rotation in the same plane.
+        let p = Rubiks3D::new(3);
+        for from in FaceEnum::iter() {
+            for to in FaceEnum::iter() {
+                let (twist, plane) = match (
+                    p.make_rotation_twist(from.into(), to.into()),
+                    p.rotation_plane(from.into(), to.into()),
+                ) {
+                    (Some(twist), Some(plane)) => (twist, plane),
+                    (None, None) => {
+                        assert_eq!(from.axis(), to.axis());
+                        continue;
+                    }
+                    _ => panic!("rotation twist and plane disagree for {from:?} to {to:?}"),
+                };
+                let face = FaceEnum::from(twist.axis);
+                let rotated = face.twist_rotation(twist.direction.into()) * from.vector();
+                assert!((rotated - to.vector()).magnitude() < 0.0001);
+                let rotated =
+                    crate::util::rotation_in_plane(plane, 90.0) * from.vector().extend(0.0);
+                assert!((rotated.truncate() - to.vector()).magnitude() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_small_notation() {
+        let cases = [
+            // Every twist of a puzzle with one layer is a rotation.
+            (1, "R", "x"),
+            (1, "L", "x'"),
+            (1, "U2", "y2"),
+            (1, "B'", "z"),
+            (2, "Rw", "x"),
+            (2, "2R", "L'"),
+        ];
+        for (layer_count, twist, expected) in cases {
+            let p = Rubiks3D::new(layer_count);
+            let twist = p.notation_scheme().parse_twist(twist).unwrap();
+            let twist = p.canonicalize_twist(twist);
+            assert_eq!(p.notation_scheme().twist_to_string(twist), expected);
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_twist_mirroring() {
+        for layer_count in 1..=3 {
+            let p = Rubiks3D::new(layer_count);
+            let are_twists_eq = |twist1, twist2| {
+                twist_comparison_key(&p, twist1) == twist_comparison_key(&p, twist2)
+            };
+            crate::puzzle::tests::test_twist_mirroring(&p, are_twists_eq);
+        }
+
+        let p = Rubiks3D::new(3);
+        let mirror = |twist: &str, axis: FaceEnum| {
+            let twist = p.notation_scheme().parse_twist(twist).unwrap();
+            let mirrored = p.mirror_twist(twist, axis.into());
+            p.notation_scheme().twist_to_string(mirrored)
+        };
+        // Mirroring across the twist's own axis moves it to the opposite face.
+        assert_eq!(mirror("R", FaceEnum::R), "L'");
+        assert_eq!(mirror("U'", FaceEnum::D), "D");
+        // Mirroring across another axis keeps the face.
+        assert_eq!(mirror("R", FaceEnum::U), "R'");
+        assert_eq!(mirror("F'", FaceEnum::R), "F");
+    }
+
+    #[test]
+    fn test_rubiks_3d_permutation() {
+        for layer_count in 1..=4 {
+            crate::puzzle::tests::test_permutation(&Rubiks3D::new(layer_count));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_repaint() {
+        for layer_count in 1..=4 {
+            crate::puzzle::tests::test_repaint(&Rubiks3D::new(layer_count));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_pieces_solved() {
+        for layer_count in 1..=4 {
+            crate::puzzle::tests::test_pieces_solved(&Rubiks3D::new(layer_count));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_solvability() {
+        for layer_count in 1..=5 {
+            crate::puzzle::tests::test_solvability(&Rubiks3D::new(layer_count));
+        }
+
+        let solved = Rubiks3D::new(3);
+        let colors = solved
+            .stickers()
+            .iter()
+            .map(|s| s.color)
+            .collect::<Vec<_>>();
+        let piece_with_stickers = |n| {
+            solved
+                .pieces()
+                .iter()
+                .find(|piece| piece.stickers.len() == n)
+                .unwrap()
+                .stickers
+                .iter()
+                .map(|s| s.0 as usize)
+                .collect::<Vec<_>>()
+        };
+
+        // Twist a corner in place.
+        let corner = piece_with_stickers(3);
+        let mut twisted = colors.clone();
+        twisted[corner[0]] = colors[corner[1]];
+        twisted[corner[1]] = colors[corner[2]];
+        twisted[corner[2]] = colors[corner[0]];
+        let mut p = solved.clone();
+        p.repaint(&twisted).unwrap();
+        assert!(p
+            .check_solvable()
+            .unwrap_err()
+            .contains("corner orientation"));
+
+        // Flip an edge in place.
+        let edge = piece_with_stickers(2);
+        let mut flipped = colors;
+        flipped.swap(edge[0], edge[1]);
+        let mut p = solved;
+        p.repaint(&flipped).unwrap();
+        assert!(p.check_solvable().unwrap_err().contains("edge orientation"));
+    }
+
     #[test]
     fn test_rubiks_3d_twist_serialization() {
         for layer_count in 1..=5 {
@@ -5,11 +5,13 @@ use itertools::Itertools;
 use num_enum::FromPrimitive;
 use serde::{de::Error, Deserialize, Deserializer};
 use smallvec::smallvec;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Index, IndexMut, RangeInclusive};
-use std::sync::Mutex;
+use std::sync::Arc;
 use strum::IntoEnumIterator;
 
+use super::piece_states::PieceStates;
+use super::registry::Registry;
 use super::*;
 
 pub const DEFAULT_LAYER_COUNT: u8 = 3;
@@ -30,179 +32,217 @@ where
     Ok(layer_count)
 }
 
-pub(super) fn puzzle_type(layer_count: u8) -> &'static dyn PuzzleType {
+/// Maximum number of unused puzzle descriptions to keep cached.
+const REGISTRY_CAPACITY: usize = 4;
+
+/// Parameters that determine a puzzle description.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct DescriptionKey {
+    layer_count: u8,
+}
+
+lazy_static! {
+    static ref REGISTRY: Registry<DescriptionKey, Rubiks4DDescription> =
+        Registry::new(REGISTRY_CAPACITY);
+}
+
+/// Names of the puzzles, indexed by layer count minus one.
+const NAMES: [&str; MAX_LAYER_COUNT as usize] = [
+    "1x1x1x1", "2x2x2x2", "3x3x3x3", "4x4x4x4", "5x5x5x5", "6x6x6x6", "7x7x7x7", "8x8x8x8",
+    "9x9x9x9",
+];
+
+pub(super) fn puzzle_name(layer_count: u8) -> &'static str {
+    NAMES[layer_count as usize - 1]
+}
+
+pub(super) fn puzzle_type(layer_count: u8) -> Arc<dyn PuzzleType + Send + Sync> {
     puzzle_description(layer_count)
 }
 
-fn puzzle_description(layer_count: u8) -> &'static Rubiks4DDescription {
-    lazy_static! {
-        static ref CACHE: Mutex<HashMap<u8, &'static Rubiks4DDescription>> =
-            Mutex::new(HashMap::new());
-    }
+fn puzzle_description(layer_count: u8) -> Arc<Rubiks4DDescription> {
+    REGISTRY.get(DescriptionKey { layer_count }, build_description)
+}
 
+fn build_description(key: &DescriptionKey) -> Rubiks4DDescription {
+    let layer_count = key.layer_count;
     assert!(LAYER_COUNT_RANGE.contains(&layer_count));
 
-    CACHE.lock().unwrap().entry(layer_count).or_insert_with(|| {
-        let mut pieces = vec![];
-        let mut stickers = vec![];
+    let mut pieces = vec![];
+    let mut stickers = vec![];
 
-        let full_range = (0..layer_count).collect_vec();
-        let ends = [0, layer_count - 1];
+    let full_range = (0..layer_count).collect_vec();
+    let ends = [0, layer_count - 1];
 
-        let center_coord = (layer_count % 2 == 0) as u8;
-        let mut piece_types = (center_coord..=layer_count / 2)
-            .flat_map(|z| {
-                (center_coord..=z).flat_map(move |y| {
-                    (center_coord..=y)
-                        .map(move |x| PieceTypeEnum::from_offset([x, y, z, layer_count / 2]))
-                })
+    let center_coord = (layer_count % 2 == 0) as u8;
+    let mut piece_types = (center_coord..=layer_count / 2)
+        .flat_map(|z| {
+            (center_coord..=z).flat_map(move |y| {
+                (center_coord..=y)
+                    .map(move |x| PieceTypeEnum::from_offset([x, y, z, layer_count / 2]))
             })
-            .collect_vec();
-        piece_types.sort();
-
-        let mut piece_locations = vec![];
-        for w in 0..layer_count {
-            let w_min = w == 0;
-            let w_max = w == layer_count - 1;
-
-            for z in 0..layer_count {
-                let z_min = z == 0;
-                let z_max = z == layer_count - 1;
-
-                for y in 0..layer_count {
-                    let y_min = y == 0;
-                    let y_max = y == layer_count - 1;
-
-                    let x_range = if w_min || w_max || z_min || z_max || y_min || y_max {
-                        full_range.as_slice()
-                    } else {
-                        ends.as_slice()
-                    };
-                    for &x in x_range {
-                        let x_min = x == 0;
-                        let x_max = x == layer_count - 1;
+        })
+        .collect_vec();
+    piece_types.sort();
+
+    let mut piece_locations = vec![];
+    for w in 0..layer_count {
+        let w_min = w == 0;
+        let w_max = w == layer_count - 1;
+
+        for z in 0..layer_count {
+            let z_min = z == 0;
+            let z_max = z == layer_count - 1;
+
+            for y in 0..layer_count {
+                let y_min = y == 0;
+                let y_max = y == layer_count - 1;
+
+                let x_range = if w_min || w_max || z_min || z_max || y_min || y_max {
+                    full_range.as_slice()
+                } else {
+                    ends.as_slice()
+                };
+                for &x in x_range {
+                    let x_min = x == 0;
+                    let x_max = x == layer_count - 1;
 
-                        let piece = Piece(pieces.len() as _);
-                        let mut piece_stickers = smallvec![];
+                    let piece = Piece(pieces.len() as _);
+                    let mut piece_stickers = smallvec![];
 
-                        let mut push_sticker_if = |condition, face| {
-                            if condition {
-                                piece_stickers.push(Sticker(stickers.len() as _));
-                                stickers.push(StickerInfo { piece, color: face });
-                            }
-                        };
-                        push_sticker_if(x_max, FaceEnum::R.into());
-                        push_sticker_if(x_min, FaceEnum::L.into());
-                        push_sticker_if(y_max, FaceEnum::U.into());
-                        push_sticker_if(y_min, FaceEnum::D.into());
-                        push_sticker_if(z_max, FaceEnum::F.into());
-                        push_sticker_if(z_min, FaceEnum::B.into());
-                        push_sticker_if(w_max, FaceEnum::O.into());
-                        push_sticker_if(w_min, FaceEnum::I.into());
-
-                        let piece_type = {
-                            // Compute the distance of each coordinate from the
-                            // center. 0 = centered along axis (only exists for odd
-                            // puzzles).
-                            let center = (layer_count - 1) as f32 / 2.0;
-                            let x = (x as f32 - center).abs().ceil() as u8;
-                            let y = (y as f32 - center).abs().ceil() as u8;
-                            let z = (z as f32 - center).abs().ceil() as u8;
-                            let w = (w as f32 - center).abs().ceil() as u8;
-                            PieceType(
-                                piece_types
-                                    .iter()
-                                    .find_position(|&&p| {
-                                        p == PieceTypeEnum::from_offset([x, y, z, w])
-                                    })
-                                    .map(|(i, _)| i)
-                                    .unwrap_or(0) as _, // shouldn't ever happen
-                            )
-                        };
-
-                        piece_locations.push([x, y, z, w]);
-                        pieces.push(PieceInfo {
-                            stickers: piece_stickers,
-                            piece_type,
-                        })
-                    }
+                    let mut push_sticker_if = |condition, face| {
+                        if condition {
+                            piece_stickers.push(Sticker(stickers.len() as _));
+                            stickers.push(StickerInfo { piece, color: face });
+                        }
+                    };
+                    push_sticker_if(x_max, FaceEnum::R.into());
+                    push_sticker_if(x_min, FaceEnum::L.into());
+                    push_sticker_if(y_max, FaceEnum::U.into());
+                    push_sticker_if(y_min, FaceEnum::D.into());
+                    push_sticker_if(z_max, FaceEnum::F.into());
+                    push_sticker_if(z_min, FaceEnum::B.into());
+                    push_sticker_if(w_max, FaceEnum::O.into());
+                    push_sticker_if(w_min, FaceEnum::I.into());
+
+                    let piece_type = {
+                        // Compute the distance of each coordinate from the
+                        // center. 0 = centered along axis (only exists for odd
+                        // puzzles).
+                        let center = (layer_count - 1) as f32 / 2.0;
+                        let x = (x as f32 - center).abs().ceil() as u8;
+                        let y = (y as f32 - center).abs().ceil() as u8;
+                        let z = (z as f32 - center).abs().ceil() as u8;
+                        let w = (w as f32 - center).abs().ceil() as u8;
+                        PieceType(
+                            piece_types
+                                .iter()
+                                .find_position(|&&p| p == PieceTypeEnum::from_offset([x, y, z, w]))
+                                .map(|(i, _)| i)
+                                .unwrap_or(0) as _, // shouldn't ever happen
+                        )
+                    };
+
+                    piece_locations.push([x, y, z, w]);
+                    pieces.push(PieceInfo {
+                        stickers: piece_stickers,
+                        piece_type,
+                    })
                 }
             }
         }
+    }
 
-        let mut aliases = vec![];
+    let mut aliases = vec![];
 
-        // Add slice twist aliases.
-        if let Some(slice_layers) = LayerMask::slice_layers(layer_count) {
-            use FaceEnum::*;
+    // Add slice twist aliases.
+    if let Some(slice_layers) = LayerMask::slice_layers(layer_count) {
+        use FaceEnum::*;
+
+        aliases.push(("M".to_string(), Alias::AxisLayers(L.into(), slice_layers)));
+        aliases.push(("E".to_string(), Alias::AxisLayers(D.into(), slice_layers)));
+        aliases.push(("S".to_string(), Alias::AxisLayers(F.into(), slice_layers)));
+        aliases.push(("P".to_string(), Alias::AxisLayers(O.into(), slice_layers)));
+    }
 
-            aliases.push(("M".to_string(), Alias::AxisLayers(L.into(), slice_layers)));
-            aliases.push(("E".to_string(), Alias::AxisLayers(D.into(), slice_layers)));
-            aliases.push(("S".to_string(), Alias::AxisLayers(F.into(), slice_layers)));
-            aliases.push(("P".to_string(), Alias::AxisLayers(O.into(), slice_layers)));
+    // Add slice move aliases, such as "Rs" for R and L' at once.
+    if let Some(outer_layers) = LayerMask::outer_layers(layer_count) {
+        for f in FaceEnum::iter() {
+            aliases.push((
+                format!("{}s", f.symbol_upper()),
+                Alias::AxisLayers(f.into(), outer_layers),
+            ));
         }
+    }
 
-        // Add 90-degree full-puzzle rotation aliases.
-        let all_layers = LayerMask::all_layers(layer_count);
-        for (ax1, ax2) in itertools::iproduct!(Axis::iter(), Axis::iter()) {
-            if let Some((dir, face)) = TwistDirectionEnum::from_face_twist_plane(ax1, ax2) {
-                let alias_string = format!("{}{}", ax1.symbol_lower(), ax2.symbol_lower());
+    // Add 90-degree full-puzzle rotation aliases.
+    let all_layers = LayerMask::all_layers(layer_count);
+    for (ax1, ax2) in itertools::iproduct!(Axis::iter(), Axis::iter()) {
+        if let Some((dir, face)) = TwistDirectionEnum::from_face_twist_plane(ax1, ax2) {
+            let alias_string = format!("{}{}", ax1.symbol_lower(), ax2.symbol_lower());
 
-                let mut twist = Twist {
-                    axis: face.into(),
-                    direction: dir.into(),
-                    layers: all_layers,
-                };
-                aliases.push((alias_string.clone(), Alias::EntireTwist(twist)));
+            let mut twist = Twist {
+                axis: face.into(),
+                direction: dir.into(),
+                layers: all_layers,
+            };
+            aliases.push((alias_string.clone(), Alias::EntireTwist(twist)));
 
-                twist.direction = dir.double().unwrap().into();
-                aliases.push((alias_string + "2", Alias::EntireTwist(twist)));
-            }
+            twist.direction = dir.double().unwrap().into();
+            aliases.push((alias_string + "2", Alias::EntireTwist(twist)));
         }
-        // Try to match longer aliases first.
-        aliases.sort_by_key(|(s, _)| -(s.len() as isize));
-
-        let notation = NotationScheme {
-            axis_names: FaceEnum::iter()
-                .map(|f| f.symbol_upper().to_string())
-                .collect(),
-            direction_names: TwistDirectionEnum::iter()
-                .map(|dir| {
-                    TwistDirectionName::PerAxis(
-                        FaceEnum::iter().map(|f| dir.symbol_on_face(f)).collect(),
-                    )
-                })
-                .collect(),
-            block_suffix: None,
-            aliases,
-        };
+    }
+    // Try to match longer aliases first.
+    aliases.sort_by_key(|(s, _)| -(s.len() as isize));
+
+    let notation = NotationScheme {
+        axis_names: FaceEnum::iter()
+            .map(|f| f.symbol_upper().to_string())
+            .collect(),
+        direction_names: TwistDirectionEnum::iter()
+            .map(|dir| {
+                TwistDirectionName::PerAxis(
+                    FaceEnum::iter().map(|f| dir.symbol_on_face(f)).collect(),
+                )
+            })
+            .collect(),
+        block_suffix: None,
+        aliases,
+    };
+
+    let home_stickers = stickers
+        .iter()
+        .enumerate()
+        .map(|(i, info)| {
+            let pos = piece_locations[info.piece.0 as usize];
+            ((pos, info.color.into()), Sticker(i as _))
+        })
+        .collect();
+
+    Rubiks4DDescription {
+        name: puzzle_name(layer_count),
+
+        layer_count,
 
-        // It's not like we'll ever clear the cache anyway, so just leak it
-        // and let us have the 'static lifetimes.
-        Box::leak(Box::new(Rubiks4DDescription {
-            name: format!("{0}x{0}x{0}x{0}", layer_count),
-
-            layer_count,
-
-            faces: FaceEnum::iter().map(|f| f.info()).collect(),
-            pieces,
-            stickers,
-            twist_axes: FaceEnum::iter().map(|f| f.twist_axis_info()).collect(),
-            twist_directions: TwistDirectionEnum::iter().map(|dir| dir.info()).collect(),
-            piece_types: piece_types
-                .into_iter()
-                .map(|piece_type| PieceTypeInfo::new(piece_type.to_string()))
-                .collect(),
-            notation,
-
-            piece_locations,
-        }))
-    })
+        faces: FaceEnum::iter().map(|f| f.info()).collect(),
+        pieces,
+        stickers,
+        twist_axes: FaceEnum::iter().map(|f| f.twist_axis_info()).collect(),
+        twist_directions: TwistDirectionEnum::iter().map(|dir| dir.info()).collect(),
+        piece_types: piece_types
+            .into_iter()
+            .map(|piece_type| PieceTypeInfo::new(piece_type.to_string()))
+            .collect(),
+        notation,
+
+        piece_locations,
+        home_stickers,
+    }
 }
 
 #[derive(Debug, Clone)]
 struct Rubiks4DDescription {
-    name: String,
+    name: &'static str,
 
     layer_count: u8,
 
@@ -215,6 +255,9 @@ struct Rubiks4DDescription {
     notation: NotationScheme,
 
     piece_locations: Vec<[u8; 4]>,
+    /// Sticker at each sticker position when solved, keyed by the position of
+    /// the piece and the face that the sticker is on.
+    home_stickers: HashMap<([u8; 4], FaceEnum), Sticker>,
 }
 impl PuzzleType for Rubiks4DDescription {
     fn ty(&self) -> PuzzleTypeEnum {
@@ -223,7 +266,7 @@ impl PuzzleType for Rubiks4DDescription {
         }
     }
     fn name(&self) -> &str {
-        &self.name
+        self.name
     }
     fn family_display_name(&self) -> &'static str {
         "Rubik's 4D"
@@ -243,14 +286,32 @@ impl PuzzleType for Rubiks4DDescription {
     }
     fn projection_radius_3d(&self, p: StickerGeometryParams) -> f32 {
         let r = 1.0 - p.face_spacing;
-        let farthest_point = cgmath::vec4(1.0, r, r, r);
-        match p.project_4d(farthest_point) {
-            Some(farthest_point) => p
-                .view_transform
-                .transform_point(farthest_point)
-                .distance(Point3::origin()),
-            None => 3.0_f32.sqrt(), // shouldn't ever happen
-        }
+        // Consider the corners of every cell except the ones nearest the 4D
+        // camera, which take up most of the view when they aren't clipped.
+        // Without any 4D rotation, the farthest point is `(1, r, r, r)`.
+        let max_w = -p.far_w_4d / p.face_scale * r;
+        let corners = (0..4).flat_map(|axis| {
+            (0..16).map(move |signs: u32| {
+                let mut corner = cgmath::vec4(r, r, r, r);
+                corner[axis] = 1.0;
+                for i in 0..4 {
+                    if signs & (1 << i) != 0 {
+                        corner[i] = -corner[i];
+                    }
+                }
+                corner
+            })
+        });
+        corners
+            .filter(|&corner| (p.view_transform_4d * corner).w <= max_w + 0.0001)
+            .filter_map(|corner| p.project_4d(corner))
+            .map(|point| {
+                p.view_transform
+                    .transform_point(point)
+                    .distance(Point3::origin())
+            })
+            .reduce(f32::max)
+            .unwrap_or(3.0_f32.sqrt()) // shouldn't ever happen
     }
     fn scramble_moves_count(&self) -> usize {
         15 * self.layer_count as usize // TODO pulled from thin air; probably insufficient for big cubes
@@ -275,6 +336,28 @@ impl PuzzleType for Rubiks4DDescription {
         &self.piece_types
     }
 
+    fn sticker_grid_position(&self, sticker: Sticker) -> smallvec::SmallVec<[u8; 3]> {
+        let face = FaceEnum::from(self.info(sticker).color);
+        let [x, y, z, w] = self.piece_locations[self.info(sticker).piece.0 as usize];
+        let max = self.layer_count - 1;
+        // Slice each cell into layers from O to I, or from F to B for the O
+        // and I cells, and view each layer like a face of a 3D cube.
+        let (layer, [row, col]) = match face {
+            FaceEnum::O => (max - z, [max - y, x]),
+            FaceEnum::I => (max - z, [max - y, max - x]),
+            _ => (
+                max - w,
+                cube_face_grid_position(
+                    face.axis() as usize,
+                    face.sign(),
+                    [x, y, z],
+                    self.layer_count,
+                ),
+            ),
+        };
+        smallvec![layer, row, col]
+    }
+
     fn opposite_twist_axis(&self, twist_axis: TwistAxis) -> Option<TwistAxis> {
         Some(FaceEnum::from(twist_axis).opposite().into())
     }
@@ -291,21 +374,33 @@ impl PuzzleType for Rubiks4DDescription {
 
     fn make_recenter_twist(&self, axis: TwistAxis) -> Result<Twist, String> {
         use FaceEnum::*;
-        use TwistDirectionEnum as Dir;
-
-        let (axis, direction) = match axis.into() {
-            R => (U, Dir::F),
-            L => (U, Dir::B),
-            U => (R, Dir::B),
-            D => (R, Dir::F),
-            F => (R, Dir::U),
-            B => (R, Dir::D),
-            O => return Err("cannot recenter near face".to_string()),
-            I => return Err("cannot recenter far face".to_string()),
-        };
 
-        Ok(Twist {
-            axis: axis.into(),
+        match axis.into() {
+            I => Err("in face is already centered".to_string()),
+            // The out face is opposite the in face, so no single 90-degree
+            // rotation brings it to the center. Rotating it to the right face
+            // and then to the in face are rotations in the same plane, so
+            // together they make a single 180-degree rotation.
+            O => {
+                let twist = self.make_rotation_twist(R.into(), I.into()).unwrap();
+                let direction = TwistDirectionEnum::from(twist.direction);
+                Ok(Twist {
+                    direction: direction.double().unwrap().into(),
+                    ..twist
+                })
+            }
+            face => Ok(self.make_rotation_twist(face.into(), I.into()).unwrap()),
+        }
+    }
+    fn rotation_plane(&self, from: TwistAxis, to: TwistAxis) -> Option<[usize; 2]> {
+        let plane = FaceEnum::from(from).rotation_plane_to(to.into())?;
+        Some(plane.map(|axis| axis as usize))
+    }
+    fn make_rotation_twist(&self, from: TwistAxis, to: TwistAxis) -> Option<Twist> {
+        let [ax1, ax2] = FaceEnum::from(from).rotation_plane_to(to.into())?;
+        let (direction, face) = TwistDirectionEnum::from_face_twist_plane(ax1, ax2)?;
+        Some(Twist {
+            axis: face.into(),
             direction: direction.into(),
             layers: self.all_layers(),
         })
@@ -354,6 +449,28 @@ impl PuzzleType for Rubiks4DDescription {
         }
     }
 
+    fn mirror_twist(&self, twist: Twist, axis: TwistAxis) -> Twist {
+        let mirror_axis = FaceEnum::from(axis).axis();
+        let mut face: FaceEnum = twist.axis.into();
+
+        // Conjugate the twist's rotation by the reflection: `M * T * M`.
+        let mut state = PieceState::default()
+            .twist(face, twist.direction.into())
+            .mirror(mirror_axis);
+        state[mirror_axis] = state[mirror_axis].opposite();
+
+        if face.axis() == mirror_axis {
+            face = face.opposite();
+        }
+        let direction = TwistDirectionEnum::from_piece_state_on_face(state, face)
+            .expect("mirrored twist must be a valid twist");
+        self.canonicalize_twist(Twist {
+            axis: face.into(),
+            direction: direction.into(),
+            layers: twist.layers,
+        })
+    }
+
     fn reverse_twist_direction(&self, mut direction: TwistDirection) -> TwistDirection {
         direction.0 ^= 1;
         direction
@@ -387,35 +504,21 @@ impl PuzzleType for Rubiks4DDescription {
         &self.notation
     }
 }
-
 #[derive(Debug, Clone)]
 pub struct Rubiks4D {
-    desc: &'static Rubiks4DDescription,
+    desc: Arc<Rubiks4DDescription>,
     piece_states: Box<[PieceState]>,
+    /// Permutation of stickers, updated along with `piece_states`.
+    permutation: StickerPermutation,
 }
-impl Eq for Rubiks4D {}
-impl PartialEq for Rubiks4D {
-    fn eq(&self, other: &Self) -> bool {
-        self.piece_states == other.piece_states
-    }
-}
-impl Index<Piece> for Rubiks4D {
-    type Output = PieceState;
-
-    fn index(&self, piece: Piece) -> &Self::Output {
-        &self.piece_states[piece.0 as usize]
-    }
-}
-impl IndexMut<Piece> for Rubiks4D {
-    fn index_mut(&mut self, piece: Piece) -> &mut Self::Output {
-        &mut self.piece_states[piece.0 as usize]
-    }
-}
+impl_piece_states_traits!(Rubiks4D, PieceState);
 impl PuzzleState for Rubiks4D {
     fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
-        for piece in self.pieces_affected_by_twist(twist) {
+        let pieces = self.pieces_affected_by_twist(twist);
+        for &piece in &pieces {
             self[piece] = self[piece].twist(twist.axis.into(), twist.direction.into());
         }
+        self.update_permutation(pieces);
         Ok(())
     }
     fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
@@ -450,9 +553,14 @@ impl PuzzleState for Rubiks4D {
     ) -> Option<StickerGeometry> {
         let piece = self.info(sticker).piece;
         let face = self.sticker_face(sticker);
+        let p = p.for_face(face.into());
 
         let mut model_transform = Matrix4::identity();
         if let Some((twist, progress)) = p.twist_animation {
+            let twist = p
+                .simultaneous_twist
+                .filter(|&t| self.is_piece_affected_by_twist(t, piece))
+                .unwrap_or(twist);
             if self.is_piece_affected_by_twist(twist, piece) {
                 let twist_axis: FaceEnum = twist.axis.into();
                 model_transform = twist_axis.twist_matrix(twist.direction.into(), progress);
@@ -513,19 +621,23 @@ impl PuzzleState for Rubiks4D {
             }
         }
 
-        StickerGeometry::new_cube(
-            [
-                project(center + -x + -y + -z)?,
-                project(center + -x + -y + z)?,
-                project(center + -x + y + -z)?,
-                project(center + -x + y + z)?,
-                project(center + x + -y + -z)?,
-                project(center + x + -y + z)?,
-                project(center + x + y + -z)?,
-                project(center + x + y + z)?,
-            ],
-            twists,
-        )
+        let corners = [
+            center + -x + -y + -z,
+            center + -x + -y + z,
+            center + -x + y + -z,
+            center + -x + y + z,
+            center + x + -y + -z,
+            center + x + -y + z,
+            center + x + y + -z,
+            center + x + y + z,
+        ];
+        let mut verts = [Point3::origin(); 8];
+        for (vert, &corner) in verts.iter_mut().zip(&corners) {
+            *vert = project(corner)?;
+        }
+        let vert_alphas = corners.map(|corner| p.alpha_4d(corner));
+
+        StickerGeometry::new_cube(verts, vert_alphas, twists, p.cull_4d)
     }
 
     fn is_solved(&self) -> bool {
@@ -541,18 +653,95 @@ impl PuzzleState for Rubiks4D {
         }
         true
     }
-}
-#[delegate_to_methods]
-#[delegate(PuzzleType, target_ref = "desc")]
-impl Rubiks4D {
-    pub fn new(layer_count: u8) -> Self {
-        let desc = puzzle_description(layer_count);
-        let piece_states = vec![PieceState::default(); desc.pieces().len()].into_boxed_slice();
-        Self { desc, piece_states }
+
+    fn are_pieces_solved(&self, pieces: &[Piece]) -> bool {
+        let mut color_per_facet = vec![None; self.faces().len()];
+        let mut used_colors = HashSet::new();
+        for &piece in pieces {
+            for &sticker in &self.info(piece).stickers {
+                let color = self.sticker_face(sticker);
+                let facet = self.info(sticker).color.0 as usize;
+                if color_per_facet[facet] == None {
+                    // Two facets cannot be on the same face.
+                    if !used_colors.insert(color) {
+                        return false;
+                    }
+                    color_per_facet[facet] = Some(color);
+                } else if color_per_facet[facet] != Some(color) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+    fn is_piece_solved(&self, piece: Piece) -> bool {
+        self.info(piece)
+            .stickers
+            .iter()
+            .all(|&sticker| self.sticker_face(sticker) == FaceEnum::from(self.info(sticker).color))
     }
 
-    fn desc(&self) -> &Rubiks4DDescription {
-        self.desc
+    fn sticker_permutation(&self) -> Vec<Sticker> {
+        (0..self.stickers().len() as _)
+            .map(Sticker)
+            .map(|sticker| self.home_sticker(sticker))
+            .collect()
+    }
+    fn permutation(&self) -> &StickerPermutation {
+        &self.permutation
+    }
+
+    fn repaint(&mut self, colors: &[Face]) -> Result<(), String> {
+        self.repaint_pieces(colors)
+    }
+
+    fn check_solvable(&self) -> Result<(), String> {
+        if self.layer_count() < 2 {
+            return Ok(());
+        }
+
+        // Every twist moves corners in an even permutation.
+        if self.is_odd_orbit_permutation(&self.fixed_orbit(4)) {
+            return Err("permutation parity: two corners are swapped".to_string());
+        }
+
+        if self.layer_count() % 2 == 1 {
+            // Every twist of an outer or middle layer moves an even number of
+            // these orbits in an odd permutation.
+            let odd_orbits = (1..=3)
+                .filter(|&outer_count| {
+                    self.is_odd_orbit_permutation(&self.fixed_orbit(outer_count))
+                })
+                .count();
+            if odd_orbits % 2 != 0 {
+                return Err("permutation parity: two pieces are swapped".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+impl PieceStates for Rubiks4D {
+    type PieceState = PieceState;
+    type Location = [u8; 4];
+    type Facing = FaceEnum;
+
+    fn solved(&self) -> Self {
+        Self::new(self.layer_count())
+    }
+    fn twisted_piece_states(state: PieceState) -> Vec<PieceState> {
+        itertools::iproduct!(FaceEnum::iter(), TwistDirectionEnum::iter())
+            .map(|(face, direction)| state.twist(face, direction))
+            .collect()
+    }
+    fn piece_states_mut(&mut self) -> &mut [PieceState] {
+        &mut self.piece_states
+    }
+    fn permutation_mut(&mut self) -> &mut StickerPermutation {
+        &mut self.permutation
+    }
+    fn home_stickers(&self) -> &HashMap<([u8; 4], FaceEnum), Sticker> {
+        &self.desc.home_stickers
     }
 
     fn piece_location(&self, piece: Piece) -> [u8; 4] {
@@ -568,6 +757,65 @@ impl Rubiks4D {
         }
         ret
     }
+    fn sticker_face(&self, sticker: Sticker) -> FaceEnum {
+        let sticker_info = self.info(sticker);
+        let original_face: FaceEnum = sticker_info.color.into();
+        let current_face = self[sticker_info.piece][original_face.axis()];
+        match original_face.sign() {
+            Sign::Pos => current_face,
+            Sign::Neg => current_face.opposite(),
+        }
+    }
+}
+#[delegate_to_methods]
+#[delegate(PuzzleType, target_ref = "desc")]
+impl Rubiks4D {
+    pub fn new(layer_count: u8) -> Self {
+        let desc = puzzle_description(layer_count);
+        let piece_states = vec![PieceState::default(); desc.pieces().len()].into_boxed_slice();
+        let permutation = StickerPermutation::identity(desc.stickers().len());
+        Self {
+            desc,
+            piece_states,
+            permutation,
+        }
+    }
+
+    fn desc(&self) -> &Rubiks4DDescription {
+        &self.desc
+    }
+
+    /// Returns the pieces with `outer_count` coordinates on the outside of the
+    /// puzzle and all other coordinates in the middle layer. Unlike other
+    /// pieces, these always have distinct colors and can never be moved to a
+    /// position outside the orbit.
+    fn fixed_orbit(&self, outer_count: usize) -> Vec<Piece> {
+        let n = self.layer_count();
+        let is_outer = |x: u8| x == 0 || x == n - 1;
+        let is_middle = |x: u8| 2 * x + 1 == n;
+        (0..self.pieces().len() as _)
+            .map(Piece)
+            .filter(|&piece| {
+                let pos = self.desc.piece_locations[piece.0 as usize];
+                pos.iter().filter(|&&x| is_outer(x)).count() == outer_count
+                    && pos.iter().all(|&x| is_outer(x) || is_middle(x))
+            })
+            .collect()
+    }
+    /// Returns whether the pieces of an orbit returned by `fixed_orbit()` are
+    /// in an odd permutation.
+    fn is_odd_orbit_permutation(&self, orbit: &[Piece]) -> bool {
+        let home_indices: HashMap<[u8; 4], usize> = orbit
+            .iter()
+            .enumerate()
+            .map(|(i, &piece)| (self.desc.piece_locations[piece.0 as usize], i))
+            .collect();
+        let perm = orbit
+            .iter()
+            .map(|&piece| home_indices[&self.piece_location(piece)])
+            .collect::<Vec<_>>();
+        crate::util::is_odd_permutation(&perm)
+    }
     fn piece_location_from_center(&self, piece: Piece) -> [i8; 4] {
         let center = (self.layer_count() - 1) as f32 / 2.0;
         self.piece_location(piece)
@@ -608,16 +856,6 @@ impl Rubiks4D {
             piece_loc_signs.dot(basis3.cast().unwrap()),
         )
     }
-    fn sticker_face(&self, sticker: Sticker) -> FaceEnum {
-        let sticker_info = self.info(sticker);
-        let original_face: FaceEnum = sticker_info.color.into();
-        let current_face = self[sticker_info.piece][original_face.axis()];
-        match original_face.sign() {
-            Sign::Pos => current_face,
-            Sign::Neg => current_face.opposite(),
-        }
-    }
-
     fn piece_center_4d(&self, piece: Piece, p: StickerGeometryParams) -> Vector4<f32> {
         let pos = self.piece_location(piece);
         cgmath::vec4(
@@ -806,7 +1044,7 @@ impl PieceState {
 
 #[derive(EnumIter, FromPrimitive, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(u8)]
-enum FaceEnum {
+pub(super) enum FaceEnum {
     #[default]
     R = 0,
     L = 1,
@@ -950,6 +1188,21 @@ impl FaceEnum {
         Matrix4 { x, y, z, w }
     }
 
+    /// Returns the oriented plane of the 90-degree rotation that moves this
+    /// face to `target`, or `None` if they are on the same axis. Rotating in
+    /// the plane `[a, b]` moves the positive end of `a` toward the positive end
+    /// of `b`.
+    fn rotation_plane_to(self, target: FaceEnum) -> Option<[Axis; 2]> {
+        let (from, to) = (self.axis(), target.axis());
+        if from == to {
+            return None;
+        }
+        Some(match self.sign() * target.sign() {
+            Sign::Pos => [from, to],
+            Sign::Neg => [to, from],
+        })
+    }
+
     fn twist_matrix(self, direction: TwistDirectionEnum, progress: f32) -> Matrix4<f32> {
         let mat3: Matrix3<f32> = direction.twist_rotation(progress).into();
         let mut ret = Matrix4::identity();
@@ -1277,13 +1530,10 @@ impl TwistDirectionEnum {
     fn from_face_twist_plane(ax1: Axis, ax2: Axis) -> Option<(Self, FaceEnum)> {
         use TwistDirectionEnum::*;
 
-        let basis_face = if ax1 != Axis::X && ax2 != Axis::X {
-            FaceEnum::R
-        } else if ax1 != Axis::Y && ax2 != Axis::Y {
-            FaceEnum::U
-        } else {
-            FaceEnum::F
-        };
+        // Twist around the lowest axis that is perpendicular to the plane,
+        // which is never W.
+        let [perp1, perp2] = ax1.perpendicular_plane(ax2);
+        let basis_face = std::cmp::min(perp1, perp2).face(Sign::Pos);
 
         let direction = [R, L, U, D, F, B]
             .into_iter()
@@ -1444,6 +1694,48 @@ impl Axis {
         }
     }
 
+    /// Returns the face on this axis with the given sign.
+    fn face(self, sign: Sign) -> FaceEnum {
+        use FaceEnum::*;
+
+        match (self, sign) {
+            (Axis::X, Sign::Pos) => R,
+            (Axis::X, Sign::Neg) => L,
+            (Axis::Y, Sign::Pos) => U,
+            (Axis::Y, Sign::Neg) => D,
+            (Axis::Z, Sign::Pos) => F,
+            (Axis::Z, Sign::Neg) => B,
+            (Axis::W, Sign::Pos) => O,
+            (Axis::W, Sign::Neg) => I,
+        }
+    }
+
+    /// Returns the plane perpendicular to the plane spanned by `self` and
+    /// `other`, oriented so that `[self, other, ..perpendicular_plane]` is an
+    /// even permutation of XYZW.
+    ///
+    /// A rotation in a plane of 4D space fixes every point in the
+    /// perpendicular plane.
+    fn perpendicular_plane(self, other: Axis) -> [Axis; 2] {
+        assert_ne!(self, other, "axes must be distinct");
+
+        let [a, b]: [Axis; 2] = Axis::iter()
+            .filter(|&ax| ax != self && ax != other)
+            .collect_vec()
+            .try_into()
+            .unwrap();
+        let inversions = [self, other, a, b]
+            .into_iter()
+            .tuple_combinations()
+            .filter(|(x, y)| x > y)
+            .count();
+        if inversions % 2 == 0 {
+            [a, b]
+        } else {
+            [b, a]
+        }
+    }
+
     /// Returns the unit vector along this axis.
     fn unit_vec4<S: BaseNum>(self) -> Vector4<S> {
         match self {
@@ -1471,6 +1763,130 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rubiks_4d_twist_mirroring() {
+        for layer_count in 1..=3 {
+            let p = Rubiks4D::new(layer_count);
+            let are_twists_eq = |twist1, twist2| {
+                twist_comparison_key(&p, twist1) == twist_comparison_key(&p, twist2)
+            };
+            crate::puzzle::tests::test_twist_mirroring(&p, are_twists_eq);
+        }
+
+        let p = Rubiks4D::new(3);
+        let mirror = |twist: &str, axis: FaceEnum| {
+            let twist = p.notation_scheme().parse_twist(twist).unwrap();
+            let mirrored = p.mirror_twist(twist, axis.into());
+            p.notation_scheme().twist_to_string(mirrored)
+        };
+        // `RU` rotates in the plane of the Z and W axes. Mirroring across X
+        // moves it to the opposite cell, where the same rotation is `LD`.
+        assert_eq!(mirror("RU", FaceEnum::R), "LD");
+        // Mirroring across an axis in the plane of rotation reverses it.
+        assert_eq!(mirror("RU", FaceEnum::F), "RD");
+        assert_eq!(mirror("RU", FaceEnum::O), "RD");
+        // Mirroring across the other axis leaves it unchanged.
+        assert_eq!(mirror("RU", FaceEnum::U), "RU");
+    }
+
+    #[test]
+    fn test_rubiks_4d_permutation() {
+        for layer_count in 1..=4 {
+            crate::puzzle::tests::test_permutation(&Rubiks4D::new(layer_count));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_4d_repaint() {
+        for layer_count in 1..=4 {
+            crate::puzzle::tests::test_repaint(&Rubiks4D::new(layer_count));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_4d_pieces_solved() {
+        for layer_count in 1..=4 {
+            crate::puzzle::tests::test_pieces_solved(&Rubiks4D::new(layer_count));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_4d_solvability() {
+        for layer_count in 1..=4 {
+            crate::puzzle::tests::test_solvability(&Rubiks4D::new(layer_count));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_4d_rotation_planes() {
+        for ax1 in Axis::iter() {
+            for ax2 in Axis::iter().filter(|&ax| ax != ax1) {
+                let [perp1, perp2] = ax1.perpendicular_plane(ax2);
+                assert_eq!(perp1.perpendicular_plane(perp2), [ax1, ax2]);
+                assert_eq!(ax2.perpendicular_plane(ax1), [perp2, perp1]);
+            }
+        }
+
+        // Each rotation should move one face to the other, both as a twist and
+        // as a view rotation in the same plane.
+        let p = Rubiks4D::new(3);
+        for from in FaceEnum::iter() {
+            for to in FaceEnum::iter() {
+                let (twist, plane) = match (
+                    p.make_rotation_twist(from.into(), to.into()),
+                    p.rotation_plane(from.into(), to.into()),
+                ) {
+                    (Some(twist), Some(plane)) => (twist, plane),
+                    (None, None) => {
+                        assert_eq!(from.axis(), to.axis());
+                        continue;
+                    }
+                    _ => panic!("rotation twist and plane disagree for {from:?} to {to:?}"),
+                };
+                assert_eq!(face_after_twist(from, twist), to);
+                let rotated = crate::util::rotation_in_plane(plane, 90.0) * from.vector();
+                assert!((rotated - to.vector()).magnitude() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rubiks_4d_recenter() {
+        // Every face except the in face should be recentered to the in face.
+        let p = Rubiks4D::new(3);
+        for face in FaceEnum::iter() {
+            let twist = match p.make_recenter_twist(face.into()) {
+                Ok(twist) => twist,
+                Err(_) => {
+                    assert_eq!(face, FaceEnum::I);
+                    continue;
+                }
+            };
+            assert_eq!(
+                face_after_twist(face, twist),
+                FaceEnum::I,
+                "recentering {face:?}"
+            );
+
+            // Only the out face needs a double rotation.
+            let direction = TwistDirectionEnum::from(twist.direction);
+            assert_eq!(
+                direction.is_face_180(),
+                face == FaceEnum::O,
+                "recentering {face:?}"
+            );
+        }
+    }
+
+    /// Returns the face that `face` is moved to by a whole-puzzle twist.
+    fn face_after_twist(face: FaceEnum, twist: Twist) -> FaceEnum {
+        let new_state = PieceState::default().twist(twist.axis.into(), twist.direction.into());
+        match face.sign() {
+            Sign::Pos => new_state[face.axis()],
+            Sign::Neg => new_state[face.axis()].opposite(),
+        }
+    }
+
     #[test]
     fn test_rubiks_4d_twist_serialization() {
         for layer_count in 1..=4 {
@@ -0,0 +1,164 @@
+//! Compact text encoding of a puzzle state, for sharing positions.
+//!
+//! A code records the puzzle type and the color of each sticker position, so
+//! it depends only on the current state and not on how it was reached. Pieces
+//! with identical colors are interchangeable, so decoding a code may not give
+//! back exactly the same internal state, but it always looks the same.
+
+use super::*;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Returns a code that can be passed to `decode_state()` to reproduce the
+/// state of a puzzle.
+pub fn encode_state(puzzle: &Puzzle) -> String {
+    let ty = puzzle.ty();
+    let (family, layer_count) = match ty {
+        PuzzleTypeEnum::Rubiks3D { layer_count } => (0, layer_count),
+        PuzzleTypeEnum::Rubiks4D { layer_count } => (1, layer_count),
+    };
+
+    // Find the color displayed at each sticker's home position.
+    let mut colors = vec![Face(0); puzzle.stickers().len()];
+    for (sticker, &home) in puzzle.permutation().as_slice().iter().enumerate() {
+        colors[home.0 as usize] = puzzle.stickers()[sticker].color;
+    }
+
+    let bits = bits_per_color(puzzle);
+    let mut bytes = vec![family, layer_count];
+    let mut acc = 0_u32;
+    let mut acc_len = 0;
+    for color in colors {
+        acc |= (color.0 as u32) << acc_len;
+        acc_len += bits;
+        while acc_len >= 8 {
+            bytes.push(acc as u8);
+            acc >>= 8;
+            acc_len -= 8;
+        }
+    }
+    if acc_len > 0 {
+        bytes.push(acc as u8);
+    }
+
+    encode_base64(&bytes)
+}
+
+/// Reconstructs a puzzle state from a code returned by `encode_state()`.
+pub fn decode_state(code: &str) -> Result<Puzzle, String> {
+    let bytes = decode_base64(code.trim()).ok_or("invalid characters in code")?;
+    let [family, layer_count, color_bytes @ ..] = bytes.as_slice() else {
+        return Err("code is too short".to_string());
+    };
+    let ty = match family {
+        0 => PuzzleTypeEnum::Rubiks3D {
+            layer_count: *layer_count,
+        },
+        1 => PuzzleTypeEnum::Rubiks4D {
+            layer_count: *layer_count,
+        },
+        _ => return Err("unknown puzzle type".to_string()),
+    };
+    ty.validate()?;
+    let desc = ty.desc();
+
+    let bits = bits_per_color(&*desc);
+    let sticker_count = desc.stickers().len();
+    if color_bytes.len() != (sticker_count * bits as usize + 7) / 8 {
+        return Err(format!("code has wrong length for {}", ty.name()));
+    }
+
+    let mut colors = Vec::with_capacity(sticker_count);
+    let mut acc = 0_u32;
+    let mut acc_len = 0;
+    let mut color_bytes = color_bytes.iter();
+    while colors.len() < sticker_count {
+        while acc_len < bits {
+            acc |= (*color_bytes.next().unwrap() as u32) << acc_len;
+            acc_len += 8;
+        }
+        let color = acc & ((1 << bits) - 1);
+        if color as usize >= desc.faces().len() {
+            return Err(format!("invalid color in code for {}", ty.name()));
+        }
+        colors.push(Face(color as _));
+        acc >>= bits;
+        acc_len -= bits;
+    }
+
+    let mut puzzle = Puzzle::new(ty);
+    puzzle.repaint(&colors)?;
+    puzzle.check_solvable()?;
+    Ok(puzzle)
+}
+
+fn bits_per_color(ty: &dyn PuzzleType) -> u32 {
+    usize::BITS - (ty.faces().len() - 1).leading_zeros()
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut ret = String::with_capacity((bytes.len() * 4 + 2) / 3);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            ret.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    ret
+}
+
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let mut ret = Vec::with_capacity(s.len() * 3 / 4);
+    let mut acc = 0_u32;
+    let mut acc_len = 0;
+    for c in s.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|&a| a == c)?;
+        acc = acc << 6 | value as u32;
+        acc_len += 6;
+        if acc_len >= 8 {
+            acc_len -= 8;
+            ret.push((acc >> acc_len) as u8);
+            acc &= (1 << acc_len) - 1;
+        }
+    }
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        for len in 0..10 {
+            let bytes = (0..len).map(|i| (i * 37 + 11) as u8).collect::<Vec<_>>();
+            assert_eq!(decode_base64(&encode_base64(&bytes)), Some(bytes));
+        }
+    }
+
+    #[test]
+    fn test_state_code_round_trip() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let mut puzzle = Puzzle::new(ty);
+            let solved_code = encode_state(&puzzle);
+            assert_eq!(decode_state(&solved_code).unwrap(), puzzle);
+
+            for _ in 0..20 {
+                puzzle.twist(Twist::from_rng(ty)).unwrap();
+            }
+            let code = encode_state(&puzzle);
+            assert_ne!(code, solved_code);
+            assert_eq!(decode_state(&code).unwrap(), puzzle);
+
+            assert!(decode_state(&code[..code.len() - 4]).is_err());
+            assert!(decode_state("not a code!").is_err());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Size of the state space of a puzzle and related facts about its
+//! difficulty.
+
+use super::*;
+
+/// Returns the base-10 logarithm of the number of reachable states of a
+/// puzzle, not counting whole-puzzle rotations as different states, or `None`
+/// if it is not known.
+///
+/// Centers with the same color are considered indistinguishable.
+pub fn log10_state_count(ty: PuzzleTypeEnum) -> Option<f64> {
+    match ty {
+        PuzzleTypeEnum::Rubiks3D { layer_count } => Some(log10_cube_state_count(layer_count)),
+        PuzzleTypeEnum::Rubiks4D { layer_count } => match layer_count {
+            1 => Some(0.0),
+            // 3,357,894,533,384,932,272,635,904,000
+            2 => Some(3.357_894_533_384_932_f64.log10() + 27.0),
+            // About 1.757 * 10^120
+            3 => Some(1.756_772_880_709_135_8_f64.log10() + 120.0),
+            _ => None,
+        },
+    }
+}
+
+/// Returns the base-10 logarithm of the number of states of an NxNxN cube.
+fn log10_cube_state_count(n: u8) -> f64 {
+    let n = n as i32;
+    let log10_24_factorial = log10_factorial(24);
+    // Each orbit of 24 center pieces has 6 colors with 4 identical pieces
+    // each.
+    let log10_center_orbit = log10_24_factorial - 6.0 * log10_factorial(4);
+    match n {
+        0 | 1 => 0.0,
+        // Fix one corner to account for whole-puzzle rotations. Wings and
+        // centers each come in orbits of 24.
+        _ if n % 2 == 0 => {
+            let wing_orbits = (n - 2) / 2;
+            let center_orbits = (n - 2) * (n - 2) / 4;
+            log10_factorial(7)
+                + 6.0 * 3_f64.log10()
+                + wing_orbits as f64 * log10_24_factorial
+                + center_orbits as f64 * log10_center_orbit
+        }
+        // Fixed centers determine the orientation. Corner and middle edge
+        // permutations must have the same parity, and the orientation of the
+        // last corner and middle edge is determined by the others.
+        _ => {
+            let wing_orbits = (n - 3) / 2;
+            let center_orbits = ((n - 2) * (n - 2) - 1) / 4;
+            log10_factorial(8)
+                + 7.0 * 3_f64.log10()
+                + log10_factorial(12)
+                + 10.0 * 2_f64.log10()
+                + wing_orbits as f64 * log10_24_factorial
+                + center_orbits as f64 * log10_center_orbit
+        }
+    }
+}
+
+fn log10_factorial(n: u32) -> f64 {
+    (2..=n).map(|i| (i as f64).log10()).sum()
+}
+
+/// Returns the maximum number of twists needed to solve any state of a puzzle
+/// in each metric where it is known.
+pub fn gods_number(ty: PuzzleTypeEnum) -> Vec<(TwistMetric, u32)> {
+    match ty {
+        PuzzleTypeEnum::Rubiks3D { layer_count: 2 } => {
+            vec![(TwistMetric::Obtm, 11), (TwistMetric::Qobtm, 14)]
+        }
+        PuzzleTypeEnum::Rubiks3D { layer_count: 3 } => {
+            vec![(TwistMetric::Obtm, 20), (TwistMetric::Qobtm, 26)]
+        }
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cube_state_count() {
+        let count = |n| 10_f64.powf(log10_cube_state_count(n));
+        let assert_close = |a: f64, b: f64| assert!((a / b - 1.0).abs() < 1e-6, "{a} != {b}");
+
+        assert_close(count(2), 3_674_160.0);
+        assert_close(count(3), 43_252_003_274_489_856_000.0);
+        assert_close(count(4), 7.401_196_841_564_901e45);
+        assert_close(count(5), 2.828_709_422_777_418e74);
+    }
+}
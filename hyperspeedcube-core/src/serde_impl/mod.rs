@@ -0,0 +1,3 @@
+//! Manual Serde implementations.
+
+pub mod hex_bitvec;
@@ -1,4 +1,4 @@
-use cgmath::Point3;
+use cgmath::{Matrix4, Point3, SquareMatrix};
 use std::ops::{Add, Mul};
 
 pub const INVALID_STR: &str = "<invalid>";
@@ -47,6 +47,23 @@ where
     }
 }
 
+/// Returns a rotation of `angle` degrees in the plane spanned by two
+/// coordinate axes, which rotates the positive end of `plane[0]` toward the
+/// positive end of `plane[1]`.
+///
+/// This is the same plane representation as
+/// [`crate::puzzle::PuzzleType::rotation_plane()`].
+pub fn rotation_in_plane(plane: [usize; 2], angle: f32) -> Matrix4<f32> {
+    let [axis1, axis2] = plane;
+    let (s, c) = angle.to_radians().sin_cos();
+    let mut ret = Matrix4::identity();
+    ret[axis1][axis1] = c;
+    ret[axis1][axis2] = s;
+    ret[axis2][axis1] = -s;
+    ret[axis2][axis2] = c;
+    ret
+}
+
 pub fn min_and_max_bound(verts: &[Point3<f32>]) -> (Point3<f32>, Point3<f32>) {
     let mut min_bound = verts[0];
     let mut max_bound = verts[0];
@@ -99,6 +116,24 @@ pub fn wrap_words<S: AsRef<str>>(words: impl Iterator<Item = S>) -> String {
     ret
 }
 
+/// Returns whether a permutation, given as the destination of each index, is
+/// odd.
+pub fn is_odd_permutation(perm: &[usize]) -> bool {
+    let mut seen = vec![false; perm.len()];
+    let mut cycle_count = 0;
+    for start in 0..perm.len() {
+        if !seen[start] {
+            cycle_count += 1;
+            let mut i = start;
+            while !seen[i] {
+                seen[i] = true;
+                i = perm[i];
+            }
+        }
+    }
+    (perm.len() - cycle_count) % 2 == 1
+}
+
 pub fn mix<T>(a: T, b: T, t: f32) -> <T::Output as Add>::Output
 where
     T: Mul<f32>,
@@ -0,0 +1,64 @@
+/*
+ * C API for the puzzle core of Hyperspeedcube.
+ *
+ * Puzzles are opaque pointers owned by the caller, which must free them with
+ * `hsc_puzzle_free()`. Strings returned by these functions are owned by the
+ * caller, which must free them with `hsc_string_free()`. Functions that can
+ * fail return a null pointer or a negative number and set an error message
+ * that can be retrieved with `hsc_last_error()`.
+ *
+ * Puzzles are not thread-safe, but different puzzles may be used on
+ * different threads at the same time.
+ */
+
+#ifndef HYPERSPEEDCUBE_H
+#define HYPERSPEEDCUBE_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct HscPuzzle HscPuzzle;
+
+/* Returns the message for the last error on this thread, or null. */
+const char *hsc_last_error(void);
+/* Frees a string returned by this library. */
+void hsc_string_free(char *s);
+
+/* Creates a solved puzzle, such as `hsc_puzzle_new("Rubiks4D", 3)`. */
+HscPuzzle *hsc_puzzle_new(const char *family, uint8_t layer_count);
+/* Creates a puzzle from a state code, with no undo history. */
+HscPuzzle *hsc_puzzle_from_state_code(const char *code);
+/* Frees a puzzle. */
+void hsc_puzzle_free(HscPuzzle *puzzle);
+
+/* Applies twists written in the notation of the puzzle. */
+int hsc_puzzle_twist(HscPuzzle *puzzle, const char *notation);
+/* Undoes the last twist. */
+int hsc_puzzle_undo(HscPuzzle *puzzle);
+/* Redoes the last undone twist. */
+int hsc_puzzle_redo(HscPuzzle *puzzle);
+/* Scrambles the puzzle with `n` random twists, or fully if `n` is negative. */
+int hsc_puzzle_scramble(HscPuzzle *puzzle, int64_t n);
+
+/* Returns whether the puzzle is solved. */
+bool hsc_puzzle_is_solved(const HscPuzzle *puzzle);
+/* Returns the number of twists since the scramble in a metric, such as "STM". */
+int64_t hsc_puzzle_twist_count(const HscPuzzle *puzzle, const char *metric);
+/* Returns the name of the puzzle type, such as "3x3x3x3". */
+char *hsc_puzzle_name(const HscPuzzle *puzzle);
+/* Returns the state code of the puzzle. */
+char *hsc_puzzle_state_code(const HscPuzzle *puzzle);
+/* Returns the twists made since the scramble. */
+char *hsc_puzzle_history(const HscPuzzle *puzzle);
+/* Parses twists without applying them and returns them in canonical notation. */
+char *hsc_puzzle_parse_twists(const HscPuzzle *puzzle, const char *notation);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HYPERSPEEDCUBE_H */
@@ -0,0 +1,362 @@
+//! C ABI for the puzzle core. See `include/hyperspeedcube.h` for the
+//! declarations.
+//!
+//! Puzzles are opaque pointers owned by the caller, which must free them with
+//! [`hsc_puzzle_free()`]. Strings returned by these functions are owned by the
+//! caller, which must free them with [`hsc_string_free()`]. Functions that can
+//! fail return a null pointer or a negative number and set an error message
+//! that can be retrieved with [`hsc_last_error()`].
+
+use hyperspeedcube_core::preferences::ScrambleOptions;
+use hyperspeedcube_core::puzzle::*;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+thread_local! {
+    /// Message for the last error on this thread.
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', "");
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Returns an error code after setting the error message.
+fn fail(message: impl Into<String>) -> c_int {
+    set_last_error(message);
+    -1
+}
+
+/// Converts a C string into a Rust string.
+///
+/// # Safety
+///
+/// `s` must be null or a valid nul-terminated string.
+unsafe fn str_arg<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("string is null".to_string());
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| "string is not valid UTF-8".to_string())
+}
+
+/// Converts a Rust string into a C string owned by the caller.
+fn string_result(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            set_last_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the message for the last error on this thread, or null if there
+/// has not been an error. The string is owned by the library and is valid
+/// until the next call that fails.
+#[no_mangle]
+pub extern "C" fn hsc_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Frees a string returned by this library.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that has not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Creates a solved puzzle with a family name, such as `Rubiks3D` or
+/// `Rubiks4D`, and a layer count. Returns null on error.
+///
+/// # Safety
+///
+/// `family` must be a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_new(
+    family: *const c_char,
+    layer_count: u8,
+) -> *mut PuzzleController {
+    match str_arg(family).and_then(|family| crate::puzzle_type(family, layer_count)) {
+        Ok(ty) => Box::into_raw(Box::new(PuzzleController::new(ty))),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Creates a puzzle from a state code, with no undo history. Returns null on
+/// error.
+///
+/// # Safety
+///
+/// `code` must be a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_from_state_code(code: *const c_char) -> *mut PuzzleController {
+    let result = str_arg(code).and_then(decode_state).and_then(|state| {
+        let mut puzzle = PuzzleController::new(state.ty());
+        puzzle.set_custom_state(state)?;
+        Ok(puzzle)
+    });
+    match result {
+        Ok(puzzle) => Box::into_raw(Box::new(puzzle)),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a puzzle.
+///
+/// # Safety
+///
+/// `puzzle` must be null or a puzzle returned by this library that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_free(puzzle: *mut PuzzleController) {
+    if !puzzle.is_null() {
+        drop(Box::from_raw(puzzle));
+    }
+}
+
+/// Applies twists written in the notation of the puzzle. Returns 0 on
+/// success or -1 on error, in which case no twists are applied.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle and `notation` must be a valid
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_twist(
+    puzzle: *mut PuzzleController,
+    notation: *const c_char,
+) -> c_int {
+    let Some(puzzle) = puzzle.as_mut() else {
+        return fail("puzzle is null");
+    };
+    match str_arg(notation).and_then(|notation| crate::apply_twists(puzzle, notation)) {
+        Ok(()) => 0,
+        Err(e) => fail(e),
+    }
+}
+
+/// Undoes the last twist. Returns 0 on success or -1 if there is nothing to
+/// undo.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_undo(puzzle: *mut PuzzleController) -> c_int {
+    let Some(puzzle) = puzzle.as_mut() else {
+        return fail("puzzle is null");
+    };
+    match crate::without_animation(puzzle, PuzzleController::undo) {
+        Ok(()) => 0,
+        Err(e) => fail(e),
+    }
+}
+
+/// Redoes the last undone twist. Returns 0 on success or -1 if there is
+/// nothing to redo.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_redo(puzzle: *mut PuzzleController) -> c_int {
+    let Some(puzzle) = puzzle.as_mut() else {
+        return fail("puzzle is null");
+    };
+    match crate::without_animation(puzzle, PuzzleController::redo) {
+        Ok(()) => 0,
+        Err(e) => fail(e),
+    }
+}
+
+/// Resets the puzzle and scrambles it with `n` random twists, or fully if `n`
+/// is negative. Returns 0 on success or -1 on error.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_scramble(puzzle: *mut PuzzleController, n: i64) -> c_int {
+    let Some(puzzle) = puzzle.as_mut() else {
+        return fail("puzzle is null");
+    };
+    let options = ScrambleOptions::default();
+    let result = match usize::try_from(n) {
+        Ok(n) => puzzle.scramble_n(n, &options),
+        Err(_) => puzzle.scramble_full(&options),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => fail(e),
+    }
+}
+
+/// Returns whether the puzzle is solved. A null puzzle is not solved.
+///
+/// # Safety
+///
+/// `puzzle` must be null or a valid puzzle.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_is_solved(puzzle: *const PuzzleController) -> bool {
+    puzzle.as_ref().map_or(false, |puzzle| puzzle.is_solved())
+}
+
+/// Returns the number of twists made since the scramble in a twist metric,
+/// such as `STM` or `ETM`, or -1 on error.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle and `metric` must be a valid
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_twist_count(
+    puzzle: *const PuzzleController,
+    metric: *const c_char,
+) -> i64 {
+    let Some(puzzle) = puzzle.as_ref() else {
+        return fail("puzzle is null").into();
+    };
+    match str_arg(metric).and_then(crate::twist_metric) {
+        Ok(metric) => puzzle.twist_count(metric) as i64,
+        Err(e) => fail(e).into(),
+    }
+}
+
+/// Returns the name of the puzzle type, such as `3x3x3` or `3x3x3x3`. Returns
+/// null on error.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_name(puzzle: *const PuzzleController) -> *mut c_char {
+    match puzzle.as_ref() {
+        Some(puzzle) => string_result(puzzle.ty().name().to_string()),
+        None => {
+            set_last_error("puzzle is null");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the state code of the puzzle, which can be loaded with
+/// [`hsc_puzzle_from_state_code()`]. Returns null on error.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_state_code(puzzle: *const PuzzleController) -> *mut c_char {
+    match puzzle.as_ref() {
+        Some(puzzle) => string_result(encode_state(puzzle.latest())),
+        None => {
+            set_last_error("puzzle is null");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the twists made since the scramble, in the notation of the
+/// puzzle. Returns null on error.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_history(puzzle: *const PuzzleController) -> *mut c_char {
+    match puzzle.as_ref() {
+        Some(puzzle) => string_result(crate::history_string(puzzle)),
+        None => {
+            set_last_error("puzzle is null");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Parses twists in the notation of the puzzle without applying them and
+/// returns them in canonical notation, separated by spaces. Returns null if
+/// the notation is invalid.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle and `notation` must be a valid
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hsc_puzzle_parse_twists(
+    puzzle: *const PuzzleController,
+    notation: *const c_char,
+) -> *mut c_char {
+    let Some(puzzle) = puzzle.as_ref() else {
+        set_last_error("puzzle is null");
+        return ptr::null_mut();
+    };
+    match str_arg(notation).and_then(|notation| crate::normalize_twists(puzzle.ty(), notation)) {
+        Ok(twists) => string_result(twists.join(" ")),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let ret = CStr::from_ptr(s).to_str().unwrap().to_string();
+        hsc_string_free(s);
+        ret
+    }
+
+    #[test]
+    fn test_c_api() {
+        unsafe {
+            let puzzle = hsc_puzzle_new(b"Rubiks3D\0".as_ptr().cast(), 3);
+            assert!(!puzzle.is_null());
+            assert_eq!(take_string(hsc_puzzle_name(puzzle)), "3x3x3");
+
+            assert_eq!(hsc_puzzle_twist(puzzle, b"R U\0".as_ptr().cast()), 0);
+            assert!(!hsc_puzzle_is_solved(puzzle));
+            assert_eq!(hsc_puzzle_twist_count(puzzle, b"stm\0".as_ptr().cast()), 2);
+            assert_eq!(take_string(hsc_puzzle_history(puzzle)), "R U");
+
+            assert_eq!(hsc_puzzle_twist(puzzle, b"R U Q\0".as_ptr().cast()), -1);
+            assert!(!hsc_last_error().is_null());
+            assert_eq!(take_string(hsc_puzzle_history(puzzle)), "R U");
+
+            let code = hsc_puzzle_state_code(puzzle);
+            let copy = hsc_puzzle_from_state_code(code);
+            hsc_string_free(code);
+            assert!(!copy.is_null());
+            assert_eq!((*copy).latest(), (*puzzle).latest());
+            hsc_puzzle_free(copy);
+
+            assert_eq!(hsc_puzzle_undo(puzzle), 0);
+            assert_eq!(hsc_puzzle_undo(puzzle), 0);
+            assert!(hsc_puzzle_is_solved(puzzle));
+            hsc_puzzle_free(puzzle);
+
+            assert!(hsc_puzzle_new(b"Rubiks4D\0".as_ptr().cast(), 0).is_null());
+        }
+    }
+}
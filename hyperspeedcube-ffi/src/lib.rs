@@ -0,0 +1,122 @@
+//! C and Python bindings for the puzzle core of Hyperspeedcube, so that
+//! puzzles can be scripted against the same implementation as the app.
+//!
+//! The C API is declared in `include/hyperspeedcube.h`. The Python module is
+//! built with [maturin](https://www.maturin.rs/) using `maturin build`, which
+//! enables the `python` feature.
+
+#![warn(clippy::if_then_some_else_none, missing_docs)]
+
+use hyperspeedcube_core::puzzle::*;
+use strum::IntoEnumIterator;
+
+pub mod c_api;
+#[cfg(feature = "python")]
+mod python;
+
+/// Returns the puzzle type with a family name, such as `Rubiks3D` or
+/// `Rubiks4D`, and a layer count.
+pub fn puzzle_type(family: &str, layer_count: u8) -> Result<PuzzleTypeEnum, String> {
+    let ty = match family {
+        "Rubiks3D" => PuzzleTypeEnum::Rubiks3D { layer_count },
+        "Rubiks4D" => PuzzleTypeEnum::Rubiks4D { layer_count },
+        _ => return Err(format!("unknown puzzle family {family:?}")),
+    };
+    ty.validate()?;
+    Ok(ty)
+}
+
+/// Returns the twist metric with an abbreviation, such as `STM` or `ETM`.
+/// Case is ignored.
+pub fn twist_metric(name: &str) -> Result<TwistMetric, String> {
+    TwistMetric::iter()
+        .find(|metric| metric.to_string().eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("unknown twist metric {name:?}"))
+}
+
+/// Parses twists in the notation of a puzzle and applies them in order. If
+/// the notation is invalid, no twists are applied.
+pub fn apply_twists(puzzle: &mut PuzzleController, notation: &str) -> Result<(), String> {
+    let twists = puzzle.ty().parse_twists(notation)?;
+    without_animation(puzzle, |puzzle| {
+        twists.into_iter().try_for_each(|twist| puzzle.twist(twist))
+    })?;
+    Ok(())
+}
+
+/// Calls `f` to change a puzzle and then skips the resulting twist
+/// animations. Nothing draws the puzzle in the bindings, so animations would
+/// otherwise pile up in the queue forever.
+pub fn without_animation<T>(
+    puzzle: &mut PuzzleController,
+    f: impl FnOnce(&mut PuzzleController) -> T,
+) -> T {
+    let ret = f(puzzle);
+    puzzle.skip_twist_animations();
+    ret
+}
+
+/// Returns the twists in the undo history of a puzzle, not including the
+/// scramble, in the notation of the puzzle.
+pub fn history_string(puzzle: &PuzzleController) -> String {
+    let twists = puzzle
+        .undo_buffer()
+        .iter()
+        .filter_map(|entry| entry.twist())
+        .collect::<Vec<_>>();
+    puzzle.ty().twists_to_string(&twists)
+}
+
+/// Parses twists in the notation of a puzzle type and returns each one in
+/// canonical notation.
+pub fn normalize_twists(ty: PuzzleTypeEnum, notation: &str) -> Result<Vec<String>, String> {
+    let desc = ty.desc();
+    let notation_scheme = desc.notation_scheme();
+    Ok(desc
+        .parse_twists(notation)?
+        .into_iter()
+        .map(|twist| notation_scheme.twist_to_string(twist))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_puzzle_type() {
+        assert_eq!(
+            puzzle_type("Rubiks4D", 3),
+            Ok(PuzzleTypeEnum::Rubiks4D { layer_count: 3 }),
+        );
+        assert!(puzzle_type("Rubiks4D", 0).is_err());
+        assert!(puzzle_type("Megaminx", 3).is_err());
+    }
+
+    #[test]
+    fn test_twist_metric() {
+        assert_eq!(twist_metric("etm"), Ok(TwistMetric::Etm));
+        assert_eq!(twist_metric("QOBTM"), Ok(TwistMetric::Qobtm));
+        assert!(twist_metric("htm").is_err());
+    }
+
+    #[test]
+    fn test_apply_twists() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        apply_twists(&mut puzzle, "R U R' U'").unwrap();
+        assert_eq!(history_string(&puzzle), "R U R' U'");
+        assert!(!puzzle.is_solved());
+
+        assert!(apply_twists(&mut puzzle, "R Q").is_err());
+        assert_eq!(puzzle.undo_buffer().len(), 4);
+
+        apply_twists(&mut puzzle, "U R U' R'").unwrap();
+        assert!(puzzle.is_solved());
+
+        // Twists are never animated.
+        assert_eq!(puzzle.displayed(), puzzle.latest());
+        without_animation(&mut puzzle, PuzzleController::undo).unwrap();
+        assert_eq!(puzzle.displayed(), puzzle.latest());
+    }
+}
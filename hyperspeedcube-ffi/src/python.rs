@@ -0,0 +1,110 @@
+//! Python module `hyperspeedcube`, built with maturin.
+//!
+//! ```python
+//! import hyperspeedcube
+//!
+//! puzzle = hyperspeedcube.Puzzle("Rubiks3D", 3)
+//! puzzle.twist("R U R' U'")
+//! print(puzzle.history, puzzle.twist_count("ETM"), puzzle.is_solved)
+//! ```
+
+use hyperspeedcube_core::preferences::ScrambleOptions;
+use hyperspeedcube_core::puzzle::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+fn value_error(e: impl Into<String>) -> PyErr {
+    PyValueError::new_err(e.into())
+}
+
+/// Puzzle with an undo history.
+#[pyclass(name = "Puzzle", unsendable)]
+struct PyPuzzle(PuzzleController);
+
+#[pymethods]
+impl PyPuzzle {
+    /// Creates a solved puzzle with a family name, such as "Rubiks3D" or
+    /// "Rubiks4D", and a layer count.
+    #[new]
+    fn new(family: &str, layer_count: u8) -> PyResult<Self> {
+        let ty = crate::puzzle_type(family, layer_count).map_err(value_error)?;
+        Ok(Self(PuzzleController::new(ty)))
+    }
+    /// Creates a puzzle from a state code, with no undo history.
+    #[staticmethod]
+    fn from_state_code(code: &str) -> PyResult<Self> {
+        let state = decode_state(code).map_err(value_error)?;
+        let mut puzzle = PuzzleController::new(state.ty());
+        puzzle.set_custom_state(state).map_err(value_error)?;
+        Ok(Self(puzzle))
+    }
+
+    /// Applies twists written in the notation of the puzzle. If the notation
+    /// is invalid, no twists are applied.
+    fn twist(&mut self, notation: &str) -> PyResult<()> {
+        crate::apply_twists(&mut self.0, notation).map_err(value_error)
+    }
+    /// Undoes the last twist.
+    fn undo(&mut self) -> PyResult<()> {
+        crate::without_animation(&mut self.0, PuzzleController::undo).map_err(value_error)
+    }
+    /// Redoes the last undone twist.
+    fn redo(&mut self) -> PyResult<()> {
+        crate::without_animation(&mut self.0, PuzzleController::redo).map_err(value_error)
+    }
+    /// Resets the puzzle and scrambles it with `n` random twists, or fully if
+    /// `n` is `None`.
+    #[args(n = "None")]
+    fn scramble(&mut self, n: Option<usize>) -> PyResult<()> {
+        let options = ScrambleOptions::default();
+        match n {
+            Some(n) => self.0.scramble_n(n, &options),
+            None => self.0.scramble_full(&options),
+        }
+        .map_err(value_error)
+    }
+
+    /// Whether the puzzle is solved.
+    #[getter]
+    fn is_solved(&self) -> bool {
+        self.0.is_solved()
+    }
+    /// Returns the number of twists made since the scramble in a twist
+    /// metric, such as "STM" or "ETM".
+    #[args(metric = "\"STM\"")]
+    fn twist_count(&self, metric: &str) -> PyResult<usize> {
+        let metric = crate::twist_metric(metric).map_err(value_error)?;
+        Ok(self.0.twist_count(metric))
+    }
+    /// Name of the puzzle type, such as "3x3x3x3".
+    #[getter]
+    fn name(&self) -> String {
+        self.0.ty().name().to_string()
+    }
+    /// State code of the puzzle, which can be loaded with
+    /// `Puzzle.from_state_code()`.
+    #[getter]
+    fn state_code(&self) -> String {
+        encode_state(self.0.latest())
+    }
+    /// Twists made since the scramble, in the notation of the puzzle.
+    #[getter]
+    fn history(&self) -> String {
+        crate::history_string(&self.0)
+    }
+    /// Parses twists in the notation of the puzzle without applying them and
+    /// returns each one in canonical notation.
+    fn parse_twists(&self, notation: &str) -> PyResult<Vec<String>> {
+        crate::normalize_twists(self.0.ty(), notation).map_err(value_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Puzzle {} {:?}>", self.0.ty().name(), self.history())
+    }
+}
+
+#[pymodule]
+fn hyperspeedcube(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyPuzzle>()?;
+    Ok(())
+}
@@ -0,0 +1,205 @@
+//! Achievements unlocked by solving puzzles, saved between launches.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::puzzle::*;
+
+/// Number of moves (in STM) that a 3^4 solve must be under to unlock
+/// [`Achievement::FewMoves3x3x3x3`].
+pub(crate) const FEW_MOVES_3X3X3X3: usize = 500;
+/// Number of consecutive days with a solve needed to unlock
+/// [`Achievement::WeekStreak`].
+pub(crate) const STREAK_DAYS: usize = 7;
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Display,
+    EnumIter,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Achievement {
+    #[strum(serialize = "Into the fourth dimension")]
+    First3x3x3x3Solve,
+    #[strum(serialize = "Efficient")]
+    FewMoves3x3x3x3,
+    #[strum(serialize = "Middle management")]
+    MSliceOnly,
+    #[strum(serialize = "Dedicated")]
+    WeekStreak,
+}
+impl Achievement {
+    pub fn description(self) -> String {
+        match self {
+            Achievement::First3x3x3x3Solve => "Solve a fully scrambled 3^4".to_owned(),
+            Achievement::FewMoves3x3x3x3 => {
+                format!("Solve a fully scrambled 3^4 in under {FEW_MOVES_3X3X3X3} moves (STM)")
+            }
+            Achievement::MSliceOnly => "Solve a scrambled 3^3 using only M slice moves".to_owned(),
+            Achievement::WeekStreak => format!("Solve a puzzle on {STREAK_DAYS} days in a row"),
+        }
+    }
+}
+
+/// Achievements that have been unlocked, along with the history needed to
+/// unlock the rest.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub(crate) struct Achievements {
+    /// Date on which each achievement was unlocked, as `YYYY-MM-DD`.
+    pub unlocked: BTreeMap<Achievement, String>,
+    /// Recent days on which a puzzle was solved, as Julian day numbers.
+    pub solve_days: BTreeSet<i32>,
+}
+impl Achievements {
+    /// Loads achievements saved on a previous launch.
+    pub fn load() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let contents = crate::preferences::achievements_file_path()
+                .map_err(anyhow::Error::from)
+                .and_then(|path| Ok(std::fs::read_to_string(path)?));
+            match contents {
+                Ok(contents) => match serde_yaml::from_str(&contents) {
+                    Ok(achievements) => return achievements,
+                    Err(e) => log::warn!("Error loading achievements: {}", e),
+                },
+                Err(e) => log::info!("No achievements loaded: {}", e),
+            }
+        }
+        Self::default()
+    }
+    /// Saves achievements to be loaded on the next launch.
+    pub fn save(&self) -> Result<()> {
+        // Tests must not overwrite the user's achievements.
+        #[cfg(not(any(target_arch = "wasm32", test)))]
+        {
+            let path = crate::preferences::achievements_file_path()?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, serde_yaml::to_string(self)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains_key(&achievement)
+    }
+
+    /// Records that the puzzle was just solved, and returns the achievements
+    /// that were unlocked by it. `scramble_state` is the scramble state of the
+    /// puzzle before it was solved.
+    pub fn record_solve(
+        &mut self,
+        puzzle: &PuzzleController,
+        scramble_state: ScrambleState,
+    ) -> Vec<Achievement> {
+        let today = today();
+        let today_str = format!(
+            "{:04}-{:02}-{:02}",
+            today.year(),
+            today.month() as u8,
+            today.day(),
+        );
+        let today = today.to_julian_day();
+
+        // Only the last few days are needed to compute the streak.
+        self.solve_days.insert(today);
+        self.solve_days = self.solve_days.split_off(&(today - STREAK_DAYS as i32 + 1));
+
+        let mut earned = vec![];
+
+        let ty = puzzle.ty();
+        let twists = || {
+            puzzle
+                .undo_buffer()
+                .iter()
+                .filter_map(|entry| entry.twist())
+        };
+        if ty == (PuzzleTypeEnum::Rubiks4D { layer_count: 3 })
+            && scramble_state == ScrambleState::Full
+        {
+            earned.push(Achievement::First3x3x3x3Solve);
+            if TwistMetric::Stm.count_twists(puzzle, twists()) < FEW_MOVES_3X3X3X3 {
+                earned.push(Achievement::FewMoves3x3x3x3);
+            }
+        }
+        if ty == (PuzzleTypeEnum::Rubiks3D { layer_count: 3 })
+            && twists().next().is_some()
+            && twists().all(|twist| is_m_slice_twist(ty, twist))
+        {
+            earned.push(Achievement::MSliceOnly);
+        }
+        if streak_length(&self.solve_days, today) >= STREAK_DAYS {
+            earned.push(Achievement::WeekStreak);
+        }
+
+        earned.retain(|&achievement| !self.is_unlocked(achievement));
+        for &achievement in &earned {
+            self.unlocked.insert(achievement, today_str.clone());
+        }
+        earned
+    }
+}
+
+/// Returns whether a twist on a 3^3 moves only the M slice.
+fn is_m_slice_twist(ty: PuzzleTypeEnum, twist: Twist) -> bool {
+    let axis_name = ty.desc().info(twist.axis).name;
+    (axis_name == "L" || axis_name == "R") && twist.layers == LayerMask(0b010)
+}
+
+/// Returns the current date in the local time zone.
+#[cfg(not(target_arch = "wasm32"))]
+fn today() -> time::Date {
+    time::OffsetDateTime::now_local()
+        .unwrap_or_else(|_| time::OffsetDateTime::now_utc())
+        .date()
+}
+/// Returns the current date in the local time zone. `time` cannot read the
+/// clock on web, so this asks the browser instead.
+#[cfg(target_arch = "wasm32")]
+fn today() -> time::Date {
+    let now = js_sys::Date::new_0();
+    let month = time::Month::try_from(now.get_month() as u8 + 1).unwrap_or(time::Month::January);
+    time::Date::from_calendar_date(now.get_full_year() as i32, month, now.get_date() as u8)
+        .unwrap_or(time::Date::MIN)
+}
+
+/// Returns the number of consecutive days ending on `today` that are in
+/// `days`.
+fn streak_length(days: &BTreeSet<i32>, today: i32) -> usize {
+    (0..).take_while(|&i| days.contains(&(today - i))).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streak_length() {
+        let days = BTreeSet::from([10, 12, 13, 14]);
+        assert_eq!(streak_length(&days, 14), 3);
+        assert_eq!(streak_length(&days, 10), 1);
+        assert_eq!(streak_length(&days, 15), 0);
+    }
+
+    #[test]
+    fn test_m_slice_twists() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let twists = ty.parse_twists("M M' M2").unwrap();
+        assert!(twists.into_iter().all(|t| is_m_slice_twist(ty, t)));
+        let twists = ty.parse_twists("R L' U").unwrap();
+        assert!(!twists.into_iter().any(|t| is_m_slice_twist(ty, t)));
+    }
+}
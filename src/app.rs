@@ -1,20 +1,64 @@
 use bitvec::bitvec;
 use cgmath::Point2;
+use instant::{Duration, Instant};
 use itertools::Itertools;
 use key_names::KeyMappingCode;
+#[cfg(test)]
+use std::cell::RefCell;
 use std::collections::HashMap;
+#[cfg(test)]
+use std::collections::VecDeque;
 use std::fmt;
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::Path;
 use std::path::PathBuf;
+#[cfg(test)]
+use std::rc::Rc;
 use winit::event::{ElementState, ModifiersState, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop, EventLoopProxy};
 
-use crate::commands::{Command, PuzzleCommand, PuzzleMouseCommand};
+use crate::achievements::{Achievement, Achievements};
+use crate::attempt::{Attempt, Attempts};
+use crate::commands::{Command, PuzzleCommand, PuzzleMouseCommand, ScreenDirection};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::crash::CrashDump;
+use crate::fmc::FmcSession;
+use crate::gui::RecentInputs;
+use crate::keyframes::Keyframes;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::leaderboard::Leaderboard;
+use crate::lifetime_stats::LifetimeStats;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::log_analysis::LogReport;
 use crate::logfile::LogFileFormat;
-use crate::preferences::{Key, Keybind, PieceFilter, Preferences, Preset};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::plugins::{PluginAction, Plugins};
+use crate::preferences::{
+    FocusLossAnimation, GfxPreset, Key, KeyComboMode, KeyRepeatMode, Keybind, PieceFilter,
+    Preferences, Preset, ViewPreferences, DEFAULT_PREFS, MAX_SCALE, MIN_SCALE,
+};
 use crate::puzzle::*;
 use crate::render::{GraphicsState, PuzzleRenderCache};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::session::Session;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::updates::Updates;
+use crate::versus::Versus;
+use crate::worker::{Task, TaskOutput, Worker};
+
+/// Maximum time between pressing and releasing a key for it to count as a
+/// tap.
+const MAX_KEY_TAP_DURATION: Duration = Duration::from_millis(400);
+/// Time constant for smoothing keyboard view rotation, in seconds.
+const VIEW_ROTATION_SMOOTHING_TIME: f32 = 0.1;
+/// Factor by which the puzzle scale changes with each zoom command.
+const ZOOM_STEP: f32 = 1.25;
+/// Distance the puzzle moves with each pan command, as a fraction of the
+/// viewport.
+const PAN_STEP: f32 = 0.1;
+/// Minimum time between updates of the snapshot saved if the app crashes.
+#[cfg(not(target_arch = "wasm32"))]
+const CRASH_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
 
 #[cfg(target_arch = "wasm32")]
 macro_rules! unsupported_on_web {
@@ -32,7 +76,7 @@ macro_rules! unsupported_on_web {
 pub struct App {
     pub(crate) prefs: Preferences,
 
-    events: EventLoopProxy<AppEvent>,
+    events: AppEventSender,
 
     pub(crate) puzzle: PuzzleController,
     pub(crate) render_cache: PuzzleRenderCache,
@@ -42,6 +86,9 @@ pub struct App {
     /// Mouse cursor position relative to the puzzle texture. Each axis ranges
     /// from -1.0 to +1.0.
     pub(crate) cursor_pos: Option<Point2<f32>>,
+    /// Position in egui points to move the mouse cursor to at the end of the
+    /// frame.
+    pub(crate) cursor_warp: Option<egui::Pos2>,
 
     /// Set of pressed keys.
     pressed_keys: Vec<Key>,
@@ -57,39 +104,145 @@ pub struct App {
     /// Grip that is more permanent.
     pub(crate) toggle_grip: Grip,
 
-    status_msg: String,
-}
-impl App {
-    pub(crate) fn new(event_loop: &EventLoop<AppEvent>, initial_file: Option<PathBuf>) -> Self {
-        let mut this = Self {
-            prefs: Preferences::load(None),
+    /// View rotations that are tied to a held key.
+    held_view_rotations: HashMap<Key, ScreenDirection>,
+    /// Twist that is repeated while its key is held, along with the time of
+    /// the next repeat.
+    repeating_twist: Option<(Key, Twist, Instant)>,
+    /// Key that was pressed on its own, along with the time it was pressed.
+    /// Releasing it soon enough triggers keybinds for tapping the key.
+    key_tap: Option<(Option<KeyMappingCode>, Option<VirtualKeyCode>, Instant)>,
+    /// Current speed of keyboard view rotation, in degrees per second.
+    view_rotation_velocity: [f32; 2],
+    /// Time of the last call to `frame()`, used to make keyboard view rotation
+    /// independent of framerate.
+    last_frame_time: Option<Instant>,
+    /// Time of the last keyboard or mouse input, used to pause the solve
+    /// clock when idle.
+    last_input_time: Instant,
+    /// Whether the solve clock was paused because of inactivity or loss of
+    /// focus, in which case it resumes on the next input.
+    pub(crate) solve_clock_paused: bool,
+    /// Whether twist animations were paused because the window lost focus, in
+    /// which case they resume when it is focused again.
+    animations_paused_for_focus: bool,
+
+    /// Error from loading or compiling the custom shader, shown in the
+    /// graphics preferences.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) custom_shader_error: Option<String>,
 
-            events: event_loop.create_proxy(),
+    /// Camera keyframes for presentations.
+    pub(crate) keyframes: Keyframes,
+    /// Fewest-moves session using premoves and NISS, if one is active.
+    pub(crate) fmc: Option<FmcSession>,
 
-            puzzle: PuzzleController::default(),
-            render_cache: PuzzleRenderCache::default(),
-            puzzle_texture_size: (0, 0),
-            force_redraw: true,
+    /// File to export a solve bundle to once the puzzle has been redrawn.
+    #[cfg(not(target_arch = "wasm32"))]
+    pending_bundle_export: Option<PathBuf>,
+    /// Statistics over the last folder of log files that was analyzed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) log_report: Option<LogReport>,
+
+    /// Achievements unlocked so far.
+    pub(crate) achievements: Achievements,
+    /// Achievements that were just unlocked, along with when they were
+    /// unlocked, to be shown as toasts.
+    pub(crate) achievement_toasts: Vec<(Achievement, Instant)>,
+
+    /// Totals of twists, time, and solves for each puzzle type.
+    pub(crate) lifetime_stats: LifetimeStats,
+
+    /// Current timed attempt and results of previous attempts.
+    pub(crate) attempts: Attempts,
+
+    /// Number of times each key has been pressed since launch.
+    pub(crate) key_press_counts: HashMap<KeyMappingCode, usize>,
+    /// Recently pressed keys and the commands they triggered, shown in an
+    /// overlay.
+    pub(crate) recent_inputs: RecentInputs,
+
+    /// Second player's puzzle, if versus mode is active.
+    pub(crate) versus: Option<Versus>,
+    /// Previous puzzle, kept when switching puzzles if enabled in the
+    /// preferences.
+    pub(crate) background_puzzle: Option<PuzzleController>,
+    /// Puzzles of other types that were switched away from, to be restored
+    /// when switching back to them.
+    other_puzzles: HashMap<PuzzleTypeEnum, PuzzleController>,
+
+    /// Time of the last update of the crash snapshot, along with a hash of the
+    /// puzzle state at that time.
+    #[cfg(not(target_arch = "wasm32"))]
+    last_crash_snapshot: Option<(Instant, u64)>,
 
-            cursor_pos: None,
+    /// Worker thread for loading puzzles and generating scrambles.
+    pub(crate) worker: Worker,
 
-            pressed_keys: Vec::new(),
-            toggled_keys: Vec::new(),
-            pressed_modifiers: ModifiersState::default(),
-            toggled_modifiers: ModifiersState::default(),
+    /// Plugins loaded from the plugins folder.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) plugins: Plugins,
 
-            transient_grips: HashMap::default(),
-            toggle_grip: Grip::default(),
+    /// Ranked solve in progress and solves waiting to be submitted to the
+    /// leaderboard.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) leaderboard: Leaderboard,
 
-            status_msg: String::default(),
-        };
+    /// Latest release and the state of downloading it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) updates: Updates,
+
+    /// Time to use instead of the current time, so that replay tests are
+    /// deterministic.
+    #[cfg(test)]
+    pub(crate) replay_time: Option<Instant>,
+
+    status_msg: String,
+}
+impl App {
+    pub(crate) fn new(
+        event_loop: &EventLoop<AppEvent>,
+        ctx: &egui::Context,
+        initial_file: Option<PathBuf>,
+    ) -> Self {
+        let mut this = Self::with_prefs(
+            load_prefs(),
+            AppEventSender::EventLoop(event_loop.create_proxy()),
+            Achievements::load(),
+            LifetimeStats::load(),
+        );
 
         // Always save preferences after opening.
         this.prefs.needs_save = true;
 
+        #[cfg(not(target_arch = "wasm32"))]
+        this.plugins.reload(&this.prefs.plugins.disabled);
+        #[cfg(not(target_arch = "wasm32"))]
+        this.leaderboard.load_queue();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            crate::updates::remove_old_executable();
+            if this.prefs.updates.check_on_startup {
+                this.updates.check(true);
+            }
+        }
+
         if let Some(path) = initial_file {
             this.prefs.log_file = Some(path);
+        } else {
+            // Recover from a crash or restore the last session, unless a file
+            // was given explicitly.
+            #[cfg(not(target_arch = "wasm32"))]
+            if this.try_recover_crash() {
+                return this;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if this.prefs.interaction.restore_session && this.try_restore_session(ctx) {
+                return this;
+            }
         }
+        #[cfg(target_arch = "wasm32")]
+        let _ = ctx;
 
         // Load last open file.
         #[cfg(target_arch = "wasm32")]
@@ -101,6 +254,108 @@ impl App {
 
         this
     }
+    /// Constructs an app without a window, which handles the events it sends
+    /// to itself only when they are taken from the returned queue.
+    #[cfg(test)]
+    pub(crate) fn new_headless(prefs: Preferences) -> (Self, Rc<RefCell<VecDeque<AppEvent>>>) {
+        let queue = Rc::new(RefCell::new(VecDeque::new()));
+        let events = AppEventSender::Queue(Rc::clone(&queue));
+        (
+            Self::with_prefs(
+                prefs,
+                events,
+                Achievements::default(),
+                LifetimeStats::default(),
+            ),
+            queue,
+        )
+    }
+    fn with_prefs(
+        prefs: Preferences,
+        events: AppEventSender,
+        achievements: Achievements,
+        lifetime_stats: LifetimeStats,
+    ) -> Self {
+        let mut puzzle = PuzzleController::default();
+        lifetime_stats.subscribe(&mut puzzle);
+        #[cfg(not(target_arch = "wasm32"))]
+        let plugins = Plugins::new(&mut puzzle);
+
+        Self {
+            prefs,
+
+            events,
+
+            puzzle,
+            render_cache: PuzzleRenderCache::default(),
+            puzzle_texture_size: (0, 0),
+            force_redraw: true,
+
+            cursor_pos: None,
+            cursor_warp: None,
+
+            pressed_keys: Vec::new(),
+            toggled_keys: Vec::new(),
+            pressed_modifiers: ModifiersState::default(),
+            toggled_modifiers: ModifiersState::default(),
+
+            transient_grips: HashMap::default(),
+            toggle_grip: Grip::default(),
+
+            held_view_rotations: HashMap::default(),
+            repeating_twist: None,
+            key_tap: None,
+            view_rotation_velocity: [0.0; 2],
+            last_frame_time: None,
+            last_input_time: Instant::now(),
+            solve_clock_paused: false,
+            animations_paused_for_focus: false,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            custom_shader_error: None,
+
+            keyframes: Keyframes::default(),
+            fmc: None,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            pending_bundle_export: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            log_report: None,
+
+            achievements,
+            achievement_toasts: vec![],
+
+            lifetime_stats,
+
+            attempts: Attempts::default(),
+
+            key_press_counts: HashMap::default(),
+            recent_inputs: RecentInputs::default(),
+
+            versus: None,
+            background_puzzle: None,
+            other_puzzles: HashMap::new(),
+
+            #[cfg(not(target_arch = "wasm32"))]
+            last_crash_snapshot: None,
+
+            worker: Worker::new(),
+
+            #[cfg(not(target_arch = "wasm32"))]
+            plugins,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            leaderboard: Leaderboard::default(),
+
+            #[cfg(not(target_arch = "wasm32"))]
+            updates: Updates::default(),
+
+            #[cfg(test)]
+            replay_time: None,
+
+            status_msg: String::default(),
+        }
+    }
 
     pub(crate) fn request_redraw_puzzle(&mut self) {
         self.force_redraw = true;
@@ -110,11 +365,40 @@ impl App {
         self.force_redraw = false;
         ret
     }
+    /// Draws the second player's puzzle in versus mode. This must be called
+    /// before `draw_puzzle()`.
+    pub(crate) fn draw_versus_puzzle(
+        &mut self,
+        gfx: &mut GraphicsState,
+    ) -> Option<wgpu::TextureView> {
+        let versus = self.versus.as_mut()?;
+        crate::render::draw_puzzle_view(
+            &mut versus.puzzle,
+            &mut versus.render_cache,
+            &self.prefs,
+            versus.texture_size,
+            None,
+            gfx,
+            self.force_redraw,
+        )
+    }
 
     pub(crate) fn event(&self, event: impl Into<AppEvent>) {
-        self.events
-            .send_event(event.into())
-            .expect("tried to send event but event loop doesn't exist")
+        match &self.events {
+            AppEventSender::EventLoop(proxy) => proxy
+                .send_event(event.into())
+                .expect("tried to send event but event loop doesn't exist"),
+            #[cfg(test)]
+            AppEventSender::Queue(queue) => queue.borrow_mut().push_back(event.into()),
+        }
+    }
+    /// Returns the current time, or the time set by a replay test.
+    fn now(&self) -> Instant {
+        #[cfg(test)]
+        if let Some(t) = self.replay_time {
+            return t;
+        }
+        Instant::now()
     }
 
     pub(crate) fn handle_app_event(
@@ -168,11 +452,25 @@ impl App {
                     }
                 }
                 Command::SaveAs => unsupported_on_web! { self; self.try_save_puzzle_as() },
+                Command::ExportSolveBundle => {
+                    unsupported_on_web! {
+                        self;
+                        if let Some(path) = bundle_file_dialog().save_file() {
+                            // The screenshot is taken after the next redraw.
+                            self.pending_bundle_export = Some(path);
+                            self.request_redraw_puzzle();
+                        }
+                    }
+                }
 
                 Command::Exit => {
                     unsupported_on_web! {
                         self;
-                        if self.confirm_discard_changes("exit") {
+                        // The puzzle is saved in the session, so there is
+                        // nothing to discard.
+                        if self.prefs.interaction.restore_session
+                            || self.confirm_discard_changes("exit")
+                        {
                             control_flow.set_exit_with_code(0);
                         }
                     }
@@ -188,34 +486,83 @@ impl App {
                 Command::Redo => {
                     self.puzzle.redo()?;
                 }
+                Command::CancelQueuedTwists => match self.puzzle.cancel_queued_twists() {
+                    0 => return Err("No queued twists to cancel".to_string()),
+                    1 => self.set_status_ok("Canceled 1 queued twist"),
+                    n => self.set_status_ok(format!("Canceled {n} queued twists")),
+                },
+                Command::RealignSolved => match self.puzzle.realignment_rotation() {
+                    Some(twists) => {
+                        self.puzzle.twist_group(&twists)?;
+                        self.set_status_ok("Realigned solved puzzle");
+                    }
+                    None => return Err("Puzzle is not solved up to rotation".to_string()),
+                },
                 Command::Reset => {
                     if self.confirm_discard_changes("reset puzzle") {
                         self.puzzle.reset();
                     }
                 }
+                Command::ResetToScramble => {
+                    if self.confirm_discard_changes("reset to scramble") {
+                        self.puzzle.reset_to_scramble()?;
+                        self.set_status_ok("Reset to scramble");
+                    }
+                }
 
                 Command::ScrambleN(n) => {
                     if self.confirm_discard_changes("scramble") {
-                        self.puzzle.scramble_n(n)?;
-                        self.set_status_ok(format!(
-                            "Scrambled with {} random {}",
-                            n,
-                            if n == 1 { "move" } else { "moves" }
-                        ));
+                        self.scramble_in_background(Some(n));
                     }
                 }
                 Command::ScrambleFull => {
                     if self.confirm_discard_changes("scramble") {
-                        self.puzzle.scramble_full()?;
-                        self.set_status_ok("Scrambled fully");
+                        self.scramble_in_background(None);
+                    }
+                }
+                Command::NewAttempt => {
+                    if matches!(self.attempts.current, Some(Attempt::Pending { .. })) {
+                        self.attempts.confirm(&mut self.puzzle)?;
+                        self.set_status_ok("Inspection started");
+                    } else if self.confirm_discard_changes("start new attempt") {
+                        let options = &self.prefs.scramble[self.puzzle.ty()];
+                        self.attempts.start(&self.puzzle, options)?;
+                        self.set_status_ok("Generated scramble");
                     }
                 }
 
-                Command::NewPuzzle(puzzle_type) => {
-                    if self.confirm_discard_changes("reset puzzle") {
-                        self.puzzle = PuzzleController::new(puzzle_type);
-                        self.set_status_ok(format!("Loaded {}", puzzle_type));
+                Command::NewPuzzle(puzzle_type) => self.switch_puzzle(puzzle_type),
+                Command::NextPuzzle => self.switch_puzzle(self.puzzle.ty().cycle(1)),
+                Command::PreviousPuzzle => self.switch_puzzle(self.puzzle.ty().cycle(-1)),
+                Command::SwapBackgroundPuzzle => match self.background_puzzle.take() {
+                    Some(puzzle) => {
+                        let ty = puzzle.ty();
+                        self.background_puzzle = Some(self.puzzle.replace(puzzle));
+                        self.set_status_ok(format!("Switched to {}", ty));
                     }
+                    None => self.set_status_err("No puzzle in the background"),
+                },
+                Command::DescribeState => {
+                    if self.prefs.colors.blindfold {
+                        return Err("Unable to describe puzzle state while blindfolded".to_string());
+                    }
+                    response.copy_string = Some(describe_state(self.puzzle.latest()));
+                    self.set_status_ok("Copied puzzle state description");
+                }
+                Command::CopyTextDiagram | Command::CopyColorDiagram => {
+                    if self.prefs.colors.blindfold {
+                        return Err("Unable to copy puzzle diagram while blindfolded".to_string());
+                    }
+                    let puzzle = self.puzzle.latest();
+                    let ty = puzzle.ty();
+                    response.copy_string = Some(if c == Command::CopyColorDiagram {
+                        state_diagram(puzzle, |face| {
+                            color_square(self.prefs.colors[(ty, face)]).to_string()
+                        })
+                    } else {
+                        state_diagram(puzzle, |face| format!("{} ", puzzle.info(face).symbol))
+                    });
+                    self.set_status_ok("Copied puzzle diagram");
                 }
 
                 Command::ToggleBlindfold => {
@@ -227,21 +574,130 @@ impl App {
                     self.request_redraw_puzzle();
                 }
 
+                Command::StepTwist => {
+                    if self.puzzle.is_step_mode() {
+                        self.puzzle.step_twist_animation();
+                    }
+                }
+                Command::ToggleStepMode => {
+                    let step_mode = !self.puzzle.is_step_mode();
+                    self.puzzle.set_step_mode(step_mode);
+                    if step_mode {
+                        self.set_status_ok("Step mode enabled; use the step key to advance twists");
+                    } else {
+                        self.set_status_ok("Step mode disabled");
+                    }
+                }
+
+                // View rotation is applied every frame while the key is held.
+                Command::RotateView(_) => (),
+                Command::ZoomIn => self.animate_view_change(|view| {
+                    view.scale = (view.scale * ZOOM_STEP).clamp(MIN_SCALE, MAX_SCALE);
+                }),
+                Command::ZoomOut => self.animate_view_change(|view| {
+                    view.scale = (view.scale / ZOOM_STEP).clamp(MIN_SCALE, MAX_SCALE);
+                }),
+                Command::ResetZoom => {
+                    let default_view = self.default_view();
+                    self.animate_view_change(|view| view.scale = default_view.scale);
+                }
+                Command::Pan(direction) => {
+                    let [dx, dy] = direction.offset();
+                    self.animate_view_change(|view| {
+                        // Alignment spans the viewport from -1.0 to +1.0, with
+                        // +Y pointing up.
+                        view.align_h = (view.align_h + dx * PAN_STEP * 2.0).clamp(-1.0, 1.0);
+                        view.align_v = (view.align_v - dy * PAN_STEP * 2.0).clamp(-1.0, 1.0);
+                    });
+                }
+                Command::ResetPan => {
+                    let default_view = self.default_view();
+                    self.animate_view_change(|view| {
+                        view.align_h = default_view.align_h;
+                        view.align_v = default_view.align_v;
+                    });
+                }
+                Command::ToggleFullscreen => response.toggle_fullscreen = true,
+
+                #[cfg(not(target_arch = "wasm32"))]
+                Command::Plugin { plugin, command } => {
+                    let actions = self.plugins.run_command(&plugin, &command, &self.puzzle)?;
+                    self.apply_plugin_actions(actions);
+                }
+                #[cfg(target_arch = "wasm32")]
+                Command::Plugin { .. } => self.set_status_err("Plugins are not supported on web"),
+
+                Command::ImportMc4dSettings => {
+                    unsupported_on_web! {
+                        self;
+                        if let Some(path) = mc4d_settings_file_dialog().pick_file() {
+                            self.try_import_mc4d_settings(&path);
+                        }
+                    }
+                }
+                Command::SnapView => {
+                    let view_prefs = self.prefs.view_mut(self.puzzle.ty());
+                    let old = view_prefs.clone();
+                    *view_prefs = old.snapped_to_axes();
+                    self.puzzle.animate_from_view_settings(old);
+                    // Let the view angle offset from dragging animate back to
+                    // zero.
+                    self.puzzle.unfreeze_view_angle_offset();
+                    self.prefs.needs_save = true;
+                }
+
+                Command::Sequence(commands) => {
+                    for command in commands {
+                        let r = self
+                            .handle_app_event_internal(AppEvent::Command(command), control_flow)?;
+                        response.copy_string = r.copy_string.or(response.copy_string);
+                        response.request_paste |= r.request_paste;
+                    }
+                }
+
                 Command::None => (),
             },
 
             AppEvent::Twist(twist) => {
                 self.puzzle.twist(twist)?;
             }
+            AppEvent::Scramble(twists, new_scramble_state) => {
+                if self.confirm_discard_changes("scramble") {
+                    let n = twists.len();
+                    self.puzzle.scramble_with(&twists, new_scramble_state)?;
+                    self.set_status_ok(format!(
+                        "Scrambled with {} {}",
+                        n,
+                        if n == 1 { "move" } else { "moves" }
+                    ));
+                }
+            }
 
+            AppEvent::SetCustomState(state) => {
+                if self.confirm_discard_changes("replace the puzzle state") {
+                    self.puzzle.set_custom_state(state)?;
+                    self.set_status_ok("Replaced puzzle state");
+                }
+            }
+            AppEvent::ApplyPattern(pattern) => {
+                if self.confirm_discard_changes("apply pattern") {
+                    self.puzzle.reset();
+                    self.puzzle.twist_group(&pattern.twists)?;
+                    self.set_status_ok(format!("Applied {} pattern", pattern.name));
+                }
+            }
+
+            AppEvent::Click(egui::PointerButton::Primary) if self.puzzle.is_painting() => {
+                if let Some(sticker) = self.puzzle.hovered_sticker() {
+                    self.puzzle.paint_sticker(sticker);
+                    self.request_redraw_puzzle();
+                }
+            }
             AppEvent::Click(mouse_button) => {
-                let modifiers_mask = self.modifiers_mask(None, None);
-                let matching_mousebind = self.prefs.mousebinds.iter().find(|bind| {
-                    egui::PointerButton::from(bind.button) == mouse_button
-                        && bind.mods() & modifiers_mask == self.pressed_modifiers() & modifiers_mask
-                });
-                if let Some(bind) = matching_mousebind {
-                    match bind.command {
+                // Clicking while holding a key means the key isn't tapped.
+                self.key_tap = None;
+                if let Some(command) = self.mousebind_command(mouse_button) {
+                    match command {
                         PuzzleMouseCommand::TwistCw => self.click_twist(|tw| tw.cw)?,
                         PuzzleMouseCommand::TwistCcw => self.click_twist(|tw| tw.ccw)?,
                         PuzzleMouseCommand::Recenter => self.click_twist(|tw| tw.recenter)?,
@@ -252,6 +708,8 @@ impl App {
                                 self.puzzle.deselect_all();
                             }
                         }
+                        // The context menu is opened by the puzzle view.
+                        PuzzleMouseCommand::ContextMenu => (),
                         PuzzleMouseCommand::None => (),
                     }
                 }
@@ -267,6 +725,17 @@ impl App {
                     self.puzzle.unfreeze_view_angle_offset();
                 }
             }
+            AppEvent::Zoom(factor) => {
+                let view_prefs = self.prefs.view_mut(self.puzzle.ty());
+                view_prefs.scale = (view_prefs.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+                self.prefs.needs_save = true;
+            }
+            AppEvent::Pan(delta) => {
+                let view_prefs = self.prefs.view_mut(self.puzzle.ty());
+                view_prefs.align_h = (view_prefs.align_h + delta.x).clamp(-1.0, 1.0);
+                view_prefs.align_v = (view_prefs.align_v + delta.y).clamp(-1.0, 1.0);
+                self.prefs.needs_save = true;
+            }
 
             AppEvent::StatusError(msg) => return Err(msg),
         }
@@ -291,6 +760,26 @@ impl App {
             }
 
             WindowEvent::Focused(false) => {
+                if self.prefs.interaction.pause_on_focus_loss {
+                    self.pause_solve_clock(self.now());
+                }
+
+                match self.prefs.interaction.focus_loss_animation {
+                    FocusLossAnimation::Continue => (),
+                    FocusLossAnimation::CatchUp => {
+                        self.puzzle.skip_twist_animations();
+                        self.request_redraw_puzzle();
+                    }
+                    // Step mode stops twist animations from advancing on their
+                    // own, so reuse it unless the user already enabled it.
+                    FocusLossAnimation::Pause => {
+                        if !self.puzzle.is_step_mode() {
+                            self.puzzle.set_step_mode(true);
+                            self.animations_paused_for_focus = true;
+                        }
+                    }
+                }
+
                 // Release all keys when the window loses focus.
                 for key in std::mem::take(&mut self.pressed_keys) {
                     match key {
@@ -300,6 +789,12 @@ impl App {
                 }
             }
 
+            WindowEvent::Focused(true) => {
+                if std::mem::take(&mut self.animations_paused_for_focus) {
+                    self.puzzle.set_step_mode(false);
+                }
+            }
+
             WindowEvent::ModifiersChanged(mods) => {
                 self.pressed_modifiers = *mods;
                 // Sometimes we miss key events for modifiers when the left and
@@ -318,55 +813,87 @@ impl App {
 
             WindowEvent::KeyboardInput { input, .. } => {
                 let sc = key_names::sc_to_key(input.scancode as u16);
-                let vk = input.virtual_keycode;
-
-                match input.state {
-                    ElementState::Pressed => {
-                        let mut held = false;
-
-                        // Only ever let a modifier key be in the pressed_keys set is it was the last key pressed
-                        // This prevents modifier keys from counting as one key in the resolve_keys function
-                        self.pressed_keys.retain(|&k| !k.is_modifier());
-
-                        // Record the key as being pressed. If the key is
-                        // already pressed, then ignore this event.
-                        if let Some(sc) = sc {
-                            if !self.pressed_keys.contains(&Key::Sc(sc)) {
-                                self.pressed_keys.push(Key::Sc(sc));
-                                held |= false
-                            } else {
-                                held |= true
-                            }
-                        }
-                        if let Some(vk) = vk {
-                            if !self.pressed_keys.contains(&Key::Vk(vk)) {
-                                self.pressed_keys.push(Key::Vk(vk));
-                                held |= false
-                            } else {
-                                held |= true
-                            }
-                        }
-
-                        self.handle_key_press(sc, vk, held);
-                    }
+                self.handle_key_input(sc, input.virtual_keycode, input.state);
+            }
 
-                    ElementState::Released => {
-                        if let Some(sc) = sc {
-                            self.pressed_keys.retain(|&key| key != Key::Sc(sc));
-                        }
-                        if let Some(vk) = vk {
-                            self.pressed_keys.retain(|&key| key != Key::Vk(vk));
-                        }
+            _ => (),
+        }
+    }
 
-                        self.handle_key_release(sc, vk);
+    /// Handles a key being pressed or released.
+    pub(crate) fn handle_key_input(
+        &mut self,
+        sc: Option<KeyMappingCode>,
+        vk: Option<VirtualKeyCode>,
+        state: ElementState,
+    ) {
+        match state {
+            ElementState::Pressed => {
+                let mut held = false;
+
+                // Only ever let a modifier key be in the pressed_keys set is it was the last key pressed
+                // This prevents modifier keys from counting as one key in the resolve_keys function
+                self.pressed_keys.retain(|&k| !k.is_modifier());
+
+                // Record the key as being pressed. If the key is
+                // already pressed, then ignore this event.
+                if let Some(sc) = sc {
+                    if !self.pressed_keys.contains(&Key::Sc(sc)) {
+                        self.pressed_keys.push(Key::Sc(sc));
+                        held |= false
+                    } else {
+                        held |= true
                     }
                 }
+                if let Some(vk) = vk {
+                    if !self.pressed_keys.contains(&Key::Vk(vk)) {
+                        self.pressed_keys.push(Key::Vk(vk));
+                        held |= false
+                    } else {
+                        held |= true
+                    }
+                }
+
+                if held {
+                    self.handle_key_press(sc, vk, KeyPress::Repeat);
+                } else {
+                    // The key may be tapped if no other keys are held.
+                    let is_this_key = |k| Some(k) == sc.map(Key::Sc) || Some(k) == vk.map(Key::Vk);
+                    self.key_tap = (self.pressed_keys.iter().all(|&k| is_this_key(k)))
+                        .then(|| (sc, vk, self.now()));
+                    self.handle_key_press(sc, vk, KeyPress::Down);
+                }
             }
 
-            _ => (),
+            ElementState::Released => {
+                if let Some(sc) = sc {
+                    self.pressed_keys.retain(|&key| key != Key::Sc(sc));
+                }
+                if let Some(vk) = vk {
+                    self.pressed_keys.retain(|&key| key != Key::Vk(vk));
+                }
+
+                self.handle_key_release(sc, vk);
+                self.handle_key_tap(sc, vk);
+            }
         }
     }
 
+    /// Returns the command bound to a mouse button with the current modifiers.
+    pub(crate) fn mousebind_command(
+        &self,
+        mouse_button: egui::PointerButton,
+    ) -> Option<&PuzzleMouseCommand> {
+        let modifiers_mask = self.modifiers_mask(None, None);
+        self.prefs
+            .mousebinds
+            .iter()
+            .find(|bind| {
+                egui::PointerButton::from(bind.button) == mouse_button
+                    && bind.mods() & modifiers_mask == self.pressed_modifiers() & modifiers_mask
+            })
+            .map(|bind| &bind.command)
+    }
     fn click_twist(
         &mut self,
         get_twist: fn(ClickTwists) -> Option<Twist>,
@@ -386,12 +913,32 @@ impl App {
         &mut self,
         sc: Option<KeyMappingCode>,
         vk: Option<VirtualKeyCode>,
-        held: bool,
+        press: KeyPress,
     ) {
+        if press == KeyPress::Down {
+            if let Some(sc) = sc {
+                *self.key_press_counts.entry(sc).or_default() += 1;
+            }
+
+            let input_display = self.prefs.info.input_display;
+            if input_display.show {
+                let key_name = match (sc, vk) {
+                    (Some(sc), _) => key_names::key_name(sc),
+                    (None, Some(vk)) => format!("{vk:?}"),
+                    (None, None) => "?".to_string(),
+                };
+                self.recent_inputs
+                    .push_key(key_name, input_display.max_keys);
+            }
+        }
+        // Key repeats trigger the same commands again, so don't show them
+        // again.
+        let show_commands = press != KeyPress::Repeat && self.prefs.info.input_display.show;
+
         // Only allow one twist command per keypress. Don't use
         // multiple keybinds for macros.
         let mut done_twist_command = false;
-        if held {
+        if press == KeyPress::Repeat && self.prefs.interaction.key_repeat != KeyRepeatMode::Os {
             done_twist_command = true;
         }
 
@@ -403,11 +950,45 @@ impl App {
         let mut success = false;
         let mut grip_error = None;
 
-        let active_puzzle_keybinds =
-            self.prefs.puzzle_keybinds[self.puzzle.ty()].get_active_keybinds();
-        for bind in self.resolve_keypress(active_puzzle_keybinds, sc, vk, &self.pressed_keys) {
+        // A tapped key has already been released, so match it on its own.
+        let tap = press == KeyPress::Tap;
+        let pressed_keys = if tap {
+            sc.map(Key::Sc).into_iter().chain(vk.map(Key::Vk)).collect()
+        } else {
+            self.pressed_keys.clone()
+        };
+
+        if self.versus.is_some() && self.handle_versus_key_press(sc, vk, &pressed_keys, press) {
+            return;
+        }
+
+        // In step mode, keys bound to stepping advance the current twist
+        // animation instead of doing anything else.
+        if !tap && self.puzzle.is_step_mode() && self.puzzle.current_twist().is_some() {
+            let global_keybinds = &self.prefs.global_keybinds;
+            let binds = self.resolve_keypress(global_keybinds, sc, vk, &pressed_keys, tap);
+            if binds.iter().any(|bind| bind.command == Command::StepTwist) {
+                self.puzzle.step_twist_animation();
+                return;
+            }
+        }
+
+        let keybind_sets = &self.prefs.puzzle_keybinds[self.puzzle.ty()];
+        // In versus mode, each player has their own keybind set.
+        let active_set_name = match &self.versus {
+            Some(_) => &self.prefs.versus.player_1_keybinds,
+            None => &keybind_sets.active,
+        };
+        let active_puzzle_keybinds = keybind_sets.get_keybinds(active_set_name);
+        for bind in self.resolve_keypress(active_puzzle_keybinds, sc, vk, &pressed_keys, tap) {
+            if show_commands {
+                let description = bind.command.short_description(self.puzzle.ty());
+                self.recent_inputs.push_command(description);
+            }
             let key = bind.key.keys()[0];
             match &bind.command {
+                // Grips last only as long as the key is held.
+                PuzzleCommand::Grip { .. } if tap => (),
                 PuzzleCommand::Grip { axis, layers } => {
                     let mut new_grip = Grip::default();
 
@@ -439,7 +1020,56 @@ impl App {
                             self.puzzle.apply_transient_rotation();
                         }
                         let layers = layers.to_layer_mask(self.puzzle.layer_count());
-                        match self.do_twist(axis.as_deref(), direction, layers) {
+                        // Twist immediately rather than sending an event so
+                        // that the twist time isn't delayed by rendering.
+                        match self
+                            .keybind_twist(axis.as_deref(), direction, layers)
+                            .and_then(|twist| {
+                                self.puzzle.twist(twist)?;
+                                Ok(twist)
+                            }) {
+                            Ok(twist) => {
+                                if press == KeyPress::Down
+                                    && self.prefs.interaction.key_repeat == KeyRepeatMode::Delayed
+                                {
+                                    let delay = self.prefs.interaction.key_repeat_delay;
+                                    let next_repeat = self.now() + Duration::from_secs_f32(delay);
+                                    self.repeating_twist = Some((key, twist, next_repeat));
+                                }
+                                done_twist_command = true;
+                                success = true;
+                            }
+                            Err(e) => grip_error = Some(e),
+                        }
+                    }
+                }
+                PuzzleCommand::OppositeTwist {
+                    axis,
+                    direction,
+                    opposite_direction,
+                    layers,
+                } => {
+                    if !done_twist_command {
+                        if self.prefs.interaction.realign_on_keypress {
+                            self.puzzle.unfreeze_view_angle_offset();
+                        } else {
+                            self.puzzle.apply_transient_rotation();
+                        }
+                        let layers = layers.to_layer_mask(self.puzzle.layer_count());
+                        let opposite_direction = match opposite_direction.as_str() {
+                            "" => direction,
+                            s => s,
+                        };
+                        match self
+                            .keybind_opposite_twists(
+                                axis.as_deref(),
+                                direction,
+                                opposite_direction,
+                                layers,
+                            )
+                            .and_then(|twists| {
+                                self.puzzle.twist_simultaneous(twists).map_err(String::from)
+                            }) {
                             Ok(()) => {
                                 done_twist_command = true;
                                 success = true;
@@ -455,7 +1085,10 @@ impl App {
                         } else {
                             self.puzzle.apply_transient_rotation();
                         }
-                        match self.do_recenter(axis.as_deref()) {
+                        match self
+                            .keybind_recenter_twist(axis.as_deref())
+                            .and_then(|twist| self.puzzle.twist(twist).map_err(String::from))
+                        {
                             Ok(()) => {
                                 done_twist_command = true;
                                 success = true;
@@ -464,6 +1097,23 @@ impl App {
                         }
                     }
                 }
+                PuzzleCommand::Mirror { axis } => match self.gripped_twist_axis(axis.as_deref()) {
+                    Ok(axis) => {
+                        let ty = self.puzzle.ty();
+                        let mirrored = self
+                            .puzzle
+                            .undo_buffer()
+                            .iter()
+                            .filter_map(|&entry| entry.twist())
+                            .map(|twist| ty.mirror_twist(twist, axis))
+                            .collect_vec();
+                        for twist in mirrored {
+                            self.event(twist);
+                        }
+                        success = true;
+                    }
+                    Err(e) => grip_error = Some(e),
+                },
 
                 PuzzleCommand::Filter { mode, filter_name } => {
                     fn jump_piece_filter<'a>(
@@ -490,7 +1140,7 @@ impl App {
                             p.value.clone()
                         }
                         None if filter_name == "Everything" => PieceFilter {
-                            visible_pieces: bitvec![1; self.puzzle.ty().pieces().len()],
+                            visible_pieces: bitvec![1; self.puzzle.pieces().len()],
                             hidden_opacity: None,
                         },
                         None if filter_name == "Next" => {
@@ -585,10 +1235,24 @@ impl App {
             }
         }
 
-        for bind in self.resolve_keypress(&self.prefs.global_keybinds, sc, vk, &self.pressed_keys) {
+        for bind in self.resolve_keypress(&self.prefs.global_keybinds, sc, vk, &pressed_keys, tap) {
+            if show_commands {
+                self.recent_inputs
+                    .push_command(bind.command.short_description());
+            }
             match &bind.command {
                 Command::None => return, // Do not try to match other keybinds.
 
+                // View rotation lasts only as long as the key is held.
+                Command::RotateView(_) if tap => (),
+
+                Command::RotateView(direction) => {
+                    self.held_view_rotations
+                        .insert(bind.key.keys()[0], *direction);
+
+                    success = true;
+                }
+
                 _ => {
                     self.event(bind.command.clone());
 
@@ -606,8 +1270,22 @@ impl App {
         }
     }
     fn handle_key_release(&mut self, sc: Option<KeyMappingCode>, vk: Option<VirtualKeyCode>) {
-        // Remove grips for this held key.
-        self.remove_held_grips(|k| Some(k) == sc.map(Key::Sc) || Some(k) == vk.map(Key::Vk));
+        // Remove grips and view rotations for this held key.
+        let is_released = |k| Some(k) == sc.map(Key::Sc) || Some(k) == vk.map(Key::Vk);
+        self.remove_held_grips(is_released);
+        self.held_view_rotations.retain(|&k, _| !is_released(k));
+        if matches!(self.repeating_twist, Some((k, _, _)) if is_released(k)) {
+            self.repeating_twist = None;
+        }
+    }
+    /// Handles the release of a key that may have been tapped, meaning that it
+    /// was pressed and released quickly without pressing anything else.
+    fn handle_key_tap(&mut self, sc: Option<KeyMappingCode>, vk: Option<VirtualKeyCode>) {
+        if let Some((tap_sc, tap_vk, start)) = self.key_tap.take() {
+            if (tap_sc, tap_vk) == (sc, vk) && self.now() - start <= MAX_KEY_TAP_DURATION {
+                self.handle_key_press(sc, vk, KeyPress::Tap);
+            }
+        }
     }
 
     pub(crate) fn resolve_keypress<'a, C>(
@@ -616,37 +1294,56 @@ impl App {
         sc: Option<KeyMappingCode>,
         vk: Option<VirtualKeyCode>,
         pressed_keys: &Vec<Key>,
+        tap: bool,
     ) -> Vec<&'a Keybind<C>> {
         let sc = sc.map(Key::Sc);
         let vk = vk.map(Key::Vk);
 
         let modifiers_mask = self.modifiers_mask(sc, vk);
+        let pressed_mods = self.pressed_modifiers() & modifiers_mask;
 
         let pressed_keys_length = pressed_keys.iter().fold(0, |acc, key| match key {
             Key::Sc(_) => acc + 1,
             _ => acc,
         });
 
-        keybinds
+        let mut matching: Vec<&Keybind<C>> = keybinds
             .into_iter()
             .filter(move |bind| {
                 let key_combo = bind.key.clone();
                 let keys = key_combo.keys();
-                let keys_match = keys.len() > 0
-                    && keys
-                        .iter()
-                        .fold(true, |acc, key| acc && pressed_keys.contains(key));
+                let keys_match = key_combo.keys_match(pressed_keys);
+                // Tap keybinds only match when the keys are released.
+                let mode_match = (key_combo.mode() == KeyComboMode::Tap) == tap;
                 // Prevent long keybinds (2+ keys) from being executed if extra keys are being pressed
                 // This is necessary to prevent conflicts between key combos of different lengths that share almost the same keys
                 //
                 // For example, the keybinds `S + E + K` and `S + E + F + K` would conflict, and the shorter keybind
                 // would take priority and never let the longer one execute
                 let extra_keys = pressed_keys_length > keys.len();
-                let mods_match = key_combo.clone().mods() & modifiers_mask
-                    == self.pressed_modifiers() & modifiers_mask;
-                keys_match && mods_match && !(keys.len() > 1 && extra_keys)
+                let required_mods = key_combo.clone().mods() & modifiers_mask;
+                let mods_match = if key_combo.ignore_other_mods() {
+                    pressed_mods.contains(required_mods)
+                } else {
+                    required_mods == pressed_mods
+                };
+                keys_match && mode_match && mods_match && !(keys.len() > 1 && extra_keys)
             })
-            .collect()
+            .collect();
+
+        // Prefer keybinds that match the held modifiers exactly over ones that
+        // ignore other modifiers, and among those prefer ones that require
+        // more modifiers.
+        let specificity = |bind: &Keybind<C>| {
+            let required_mods = bind.key.clone().mods() & modifiers_mask;
+            (
+                !bind.key.ignore_other_mods(),
+                required_mods.bits().count_ones(),
+            )
+        };
+        let max_specificity = matching.iter().map(|bind| specificity(bind)).max();
+        matching.retain(|bind| Some(specificity(bind)) == max_specificity);
+        matching
     }
     fn modifiers_mask(&self, sc: Option<Key>, vk: Option<Key>) -> ModifiersState {
         // Sometimes, we want to ignore certain modifier keys when resolving a
@@ -714,23 +1411,78 @@ impl App {
         }
     }
 
-    pub(crate) fn do_twist(
+    /// Handles a keypress using the second player's keybinds in versus mode.
+    /// Only twists with an explicit axis are supported, since grips are shared
+    /// between the players. Returns whether a keybind matched.
+    fn handle_versus_key_press(
+        &mut self,
+        sc: Option<KeyMappingCode>,
+        vk: Option<VirtualKeyCode>,
+        pressed_keys: &Vec<Key>,
+        press: KeyPress,
+    ) -> bool {
+        let keybinds = self.prefs.puzzle_keybinds[self.puzzle.ty()]
+            .get_keybinds(&self.prefs.versus.player_2_keybinds);
+        let tap = press == KeyPress::Tap;
+        let twist = self
+            .resolve_keypress(keybinds, sc, vk, pressed_keys, tap)
+            .into_iter()
+            .find_map(|bind| match &bind.command {
+                PuzzleCommand::Twist {
+                    axis: Some(axis),
+                    direction,
+                    layers,
+                } => Some(Twist {
+                    axis: self.twist_axis_from_name(Some(axis.as_str())).ok()?,
+                    direction: self.twist_direction_from_name(direction).ok()?,
+                    layers: layers.to_layer_mask(self.puzzle.layer_count()),
+                }),
+                _ => None,
+            });
+
+        let (Some(twist), Some(versus)) = (twist, &mut self.versus) else {
+            return false;
+        };
+        if press != KeyPress::Repeat {
+            if let Err(e) = versus.puzzle.twist(twist) {
+                self.set_status_err(e);
+            }
+        }
+        true
+    }
+    fn keybind_twist(
         &self,
         twist_axis: Option<&str>,
         direction: &str,
         layers: LayerMask,
-    ) -> Result<(), String> {
-        self.event(AppEvent::Twist(Twist {
+    ) -> Result<Twist, String> {
+        Ok(Twist {
             axis: self.gripped_twist_axis(twist_axis)?,
             direction: self.twist_direction_from_name(direction)?,
             layers: self.gripped_layers(layers),
-        }));
-        Ok(())
+        })
+    }
+    fn keybind_opposite_twists(
+        &self,
+        twist_axis: Option<&str>,
+        direction: &str,
+        opposite_direction: &str,
+        layers: LayerMask,
+    ) -> Result<[Twist; 2], String> {
+        let twist = self.keybind_twist(twist_axis, direction, layers)?;
+        let opposite = Twist {
+            axis: self
+                .puzzle
+                .opposite_twist_axis(twist.axis)
+                .ok_or_else(|| "Twist axis has no opposite".to_string())?,
+            direction: self.twist_direction_from_name(opposite_direction)?,
+            layers: twist.layers,
+        };
+        Ok([twist, opposite])
     }
-    pub(crate) fn do_recenter(&self, twist_axis: Option<&str>) -> Result<(), String> {
+    fn keybind_recenter_twist(&self, twist_axis: Option<&str>) -> Result<Twist, String> {
         let axis = self.gripped_twist_axis(twist_axis)?;
-        self.event(AppEvent::Twist(self.puzzle.make_recenter_twist(axis)?));
-        Ok(())
+        self.puzzle.make_recenter_twist(axis)
     }
 
     pub(crate) fn pressed_keys(&self) -> &Vec<Key> {
@@ -770,16 +1522,329 @@ impl App {
                 self.toggled_keys.push(k);
             }
             self.toggled_modifiers |= mods;
-            self.handle_key_press(sc, vk, false);
+            self.handle_key_press(sc, vk, KeyPress::Down);
         }
     }
 
     pub(crate) fn frame(&mut self) {
+        while let Some(output) = self.worker.poll() {
+            self.handle_task_output(output);
+        }
+
         self.puzzle.set_grip(self.grip(), &self.prefs.interaction);
+        self.auto_pause_solve_clock();
+
+        let puzzle_type = self.puzzle.ty();
+        let piece_filters = &self.prefs.piece_filters[puzzle_type];
+        let phases = self.prefs.phases[puzzle_type].iter().filter_map(|name| {
+            let preset = piece_filters.iter().find(|p| p.preset_name == *name)?;
+            Some((name.as_str(), preset.value.visible_pieces.as_bitslice()))
+        });
+        if let Some(split) = self.puzzle.check_phase_splits(phases).pop() {
+            match split.time {
+                Some(t) => self.set_status_ok(format!(
+                    "{} complete at {:.2}s",
+                    split.name,
+                    t.as_secs_f64(),
+                )),
+                None => self.set_status_ok(format!("{} complete", split.name)),
+            }
+        }
 
+        self.lifetime_stats.update(self.puzzle.ty());
+        let scramble_state = self.puzzle.scramble_state();
         if self.puzzle.check_just_solved() {
             self.set_status_ok("Solved!");
+            self.record_solve_achievements(scramble_state);
+            self.record_solve_lifetime_stats();
+            #[cfg(not(target_arch = "wasm32"))]
+            self.auto_save_solve();
+            #[cfg(not(target_arch = "wasm32"))]
+            self.submit_to_leaderboard();
+        }
+        if let Some(result) = self.attempts.update(&self.puzzle) {
+            self.set_status_ok(format!("Solved in {:.2}s", result.time.as_secs_f64()));
+        }
+        if let Some(versus) = &mut self.versus {
+            versus.update(&self.puzzle);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let actions = self.plugins.dispatch_events(&self.puzzle);
+            self.apply_plugin_actions(actions);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.leaderboard.poll(&self.prefs.leaderboard);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.updates.poll(&self.prefs.updates.skipped_version);
+
+        self.update_key_repeat();
+        self.update_view_rotation();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_crash_snapshot();
+
+        if self.keyframes.is_playing() && !self.puzzle.is_animating() {
+            if let Err(e) = self.play_next_keyframe() {
+                self.keyframes.stop();
+                self.set_status_err(e);
+            }
+        }
+        if !self.keyframes.is_playing() {
+            if let Some((_, view)) = self.prefs.view_override.take() {
+                // Return to the current view once playback has finished.
+                self.puzzle.animate_from_view_settings(view);
+                self.request_redraw_puzzle();
+            }
+        }
+    }
+
+    /// Checks for achievements unlocked by solving the puzzle. `scramble_state`
+    /// is the scramble state from before the puzzle was solved.
+    fn record_solve_achievements(&mut self, scramble_state: ScrambleState) {
+        let unlocked = self.achievements.record_solve(&self.puzzle, scramble_state);
+        let now = Instant::now();
+        self.achievement_toasts
+            .extend(unlocked.into_iter().map(|achievement| (achievement, now)));
+        if let Err(e) = self.achievements.save() {
+            log::warn!("Error saving achievements: {}", e);
+        }
+    }
+
+    /// Counts the solve that was just finished in the lifetime statistics.
+    fn record_solve_lifetime_stats(&mut self) {
+        self.lifetime_stats.record_solve(self.puzzle.ty());
+        if let Err(e) = self.lifetime_stats.save() {
+            log::warn!("Error saving lifetime stats: {}", e);
+        }
+    }
+    /// Saves the log file of the solve that was just finished to the solves
+    /// folder, if enabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn auto_save_solve(&mut self) {
+        // Tests must not write to the user's solves folder. This is checked
+        // here rather than with `#[cfg]` so that the saving code is still
+        // compiled in tests.
+        if cfg!(test) || !self.prefs.solves.auto_save {
+            return;
+        }
+        let dir = match &self.prefs.solves.dir {
+            Some(dir) => Ok(dir.clone()),
+            None => crate::preferences::solves_dir_path().map_err(anyhow::Error::from),
+        };
+        match dir.and_then(|dir| crate::solve_archive::save_solve(&self.puzzle, &dir)) {
+            Ok(path) => {
+                log::info!("Saved solve to {}", path.display());
+                self.set_status_ok(format!("Solved! Saved to {}", path.display()));
+            }
+            Err(e) => self.set_status_err(format!("Solve was not saved: {e}")),
+        }
+    }
+    /// Queues the solve for the leaderboard if it was a ranked solve.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn submit_to_leaderboard(&mut self) {
+        match self
+            .leaderboard
+            .record_solve(&self.puzzle, &self.prefs.leaderboard)
+        {
+            Ok(true) => self.set_status_ok("Solved! Submitting to the leaderboard"),
+            Ok(false) => (),
+            Err(e) => self.set_status_err(format!("Solve was not submitted: {e}")),
+        }
+    }
+
+    /// Applies the changes requested by a plugin. Twists and commands are sent
+    /// as events, so they are handled the same way as keybinds.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn apply_plugin_actions(&mut self, actions: Vec<PluginAction>) {
+        for action in actions {
+            match action {
+                PluginAction::Twists(twists) => {
+                    for twist in twists {
+                        self.event(AppEvent::Twist(twist));
+                    }
+                }
+                PluginAction::Command(command) => self.event(AppEvent::Command(command)),
+                PluginAction::Status(msg) => self.set_status_ok(msg),
+            }
+        }
+    }
+
+    /// Changes the view settings and animates the puzzle to the new view.
+    fn animate_view_change(&mut self, f: impl FnOnce(&mut ViewPreferences)) {
+        let view_prefs = self.prefs.view_mut(self.puzzle.ty());
+        let old = view_prefs.clone();
+        f(view_prefs);
+        self.puzzle.animate_from_view_settings(old);
+        self.prefs.needs_save = true;
+    }
+    /// Returns the view settings that the zoom and pan are reset to: the
+    /// active view preset, or the default view if there is none.
+    fn default_view(&mut self) -> ViewPreferences {
+        let ty = self.puzzle.ty();
+        match &self.prefs.view_presets(ty).active_preset {
+            Some(preset) => preset.value.clone(),
+            None => DEFAULT_PREFS.view(ty).clone(),
+        }
+    }
+
+    /// Repeats the twist of a held twist key, if it's time to do so.
+    fn update_key_repeat(&mut self) {
+        let interaction = &self.prefs.interaction;
+        if interaction.key_repeat != KeyRepeatMode::Delayed {
+            self.repeating_twist = None;
+        }
+        let Some((key, twist, mut next_repeat)) = self.repeating_twist else {
+            return;
+        };
+        let interval = Duration::from_secs_f32(1.0 / interaction.key_repeat_rate.max(1.0));
+        let now = self.now();
+        while next_repeat <= now {
+            if let Err(e) = self.puzzle.twist(twist) {
+                self.repeating_twist = None;
+                self.set_status_err(e);
+                return;
+            }
+            next_repeat += interval;
+        }
+        self.repeating_twist = Some((key, twist, next_repeat));
+    }
+
+    /// Returns the title of the window, which shows the log file, the puzzle
+    /// type, the solve clock, and whether there are unsaved changes.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn window_title(&self) -> String {
+        let mut parts = vec![];
+        if let Some(file_name) = self.prefs.log_file.as_ref().and_then(|p| p.file_name()) {
+            parts.push(file_name.to_string_lossy().into_owned());
+        }
+        parts.push(self.puzzle.ty().name().to_string());
+        if self.puzzle.scramble_state() != ScrambleState::None {
+            let secs = self.puzzle.solve_clock().as_secs();
+            parts.push(format!("{}:{:02}", secs / 60, secs % 60));
+        }
+        parts.push(crate::TITLE.to_string());
+
+        let unsaved_marker = if self.puzzle.is_unsaved() { "*" } else { "" };
+        format!("{unsaved_marker}{}", parts.join(" - "))
+    }
+    /// Returns the fraction of pieces solved while a scrambled puzzle is being
+    /// solved, or `None` if the puzzle is not scrambled or is already solved.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn solve_progress(&self) -> Option<f32> {
+        match self.puzzle.scramble_state() {
+            ScrambleState::Partial | ScrambleState::Full => Some(self.puzzle.solved_fraction()),
+            ScrambleState::None | ScrambleState::Solved => None,
+        }
+    }
+
+    /// Records keyboard or mouse input, resuming the solve clock if it was
+    /// paused.
+    pub(crate) fn handle_user_input(&mut self) {
+        self.last_input_time = self.now();
+        if std::mem::take(&mut self.solve_clock_paused) {
+            self.puzzle.resume_solve_clock();
+        }
+    }
+    /// Pauses the solve clock if there has been no input for long enough.
+    fn auto_pause_solve_clock(&mut self) {
+        if !self.prefs.interaction.auto_pause {
+            return;
+        }
+        let delay = Duration::from_secs_f32(self.prefs.interaction.auto_pause_delay.max(0.0));
+        if self.now() - self.last_input_time >= delay {
+            // Don't count the idle time, even if no frame was drawn since.
+            self.pause_solve_clock(self.last_input_time + delay);
+        }
+    }
+    /// Pauses the solve clock at the given time until the next input, unless
+    /// the clock is not running or the solve is being timed competitively.
+    fn pause_solve_clock(&mut self, at: Instant) {
+        if self.solve_clock_paused
+            || !self.puzzle.is_solve_clock_running()
+            || self.puzzle.is_solved()
+            || self.is_timed_solve()
+        {
+            return;
+        }
+        self.puzzle.pause_solve_clock(at);
+        self.solve_clock_paused = true;
+    }
+    /// Returns whether the current solve is a timed attempt, a versus match, or
+    /// a ranked solve, which must not be paused.
+    fn is_timed_solve(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.leaderboard.is_ranked(&self.puzzle) {
+            return true;
+        }
+        let is_attempt = matches!(
+            self.attempts.current,
+            Some(Attempt::Inspecting { .. } | Attempt::Solving { .. }),
+        );
+        is_attempt || self.versus.is_some()
+    }
+
+    /// Rotates the view according to the view rotation keys that are held.
+    fn update_view_rotation(&mut self) {
+        let now = self.now();
+        let dt = match self.last_frame_time.replace(now) {
+            Some(t) => (now - t).as_secs_f32(),
+            None => 0.0,
+        };
+
+        let speed = self.prefs.interaction.view_rotation_speed;
+        let target = self
+            .held_view_rotations
+            .values()
+            .map(|direction| direction.offset())
+            .fold([0.0, 0.0], |[x1, y1], [x2, y2]| [x1 + x2, y1 + y2])
+            .map(|v| v * speed);
+
+        let [vx, vy] = if self.prefs.interaction.smooth_view_rotation {
+            // Exponential smoothing that doesn't depend on framerate.
+            let t = 1.0 - (-dt / VIEW_ROTATION_SMOOTHING_TIME).exp();
+            let [vx, vy] = self.view_rotation_velocity;
+            let [tx, ty] = target;
+            [vx + (tx - vx) * t, vy + (ty - vy) * t]
+        } else {
+            target
+        };
+        // Stop once the rotation is imperceptibly slow.
+        let was_rotating = self.view_rotation_velocity != [0.0; 2];
+        self.view_rotation_velocity = if target == [0.0; 2] && vx.hypot(vy) < 1.0 {
+            [0.0; 2]
+        } else {
+            [vx, vy]
+        };
+
+        if self.view_rotation_velocity != [0.0; 2] {
+            self.puzzle.freeze_view_angle_offset();
+            self.puzzle
+                .add_view_angle_offset([vx * dt, vy * dt], self.prefs.view(self.puzzle.ty()));
+        } else if was_rotating && self.prefs.interaction.realign_on_release {
+            self.puzzle.unfreeze_view_angle_offset();
+        }
+    }
+
+    /// Animates the view and puzzle state toward the next keyframe.
+    fn play_next_keyframe(&mut self) -> Result<(), &'static str> {
+        let Some((i, keyframe)) = self.keyframes.advance() else {
+            return Ok(());
+        };
+        self.puzzle.seek_history(keyframe.history_len)?;
+        // Override the view instead of changing it so that playback does not
+        // touch the saved preferences.
+        let ty = self.puzzle.ty();
+        let old_view = self.prefs.view(ty).clone();
+        self.prefs.view_override = Some((ty.projection_type(), keyframe.view));
+        if i == 0 {
+            // Jump straight to the first keyframe.
+            self.puzzle.skip_twist_animations();
+        } else {
+            self.puzzle.animate_from_view_settings(old_view);
         }
+        self.request_redraw_puzzle();
+        Ok(())
     }
 
     fn confirm_load_puzzle(&self, warnings: &[String]) -> bool {
@@ -795,6 +1860,73 @@ impl App {
                 .show()
     }
 
+    /// Loads a new puzzle, either keeping the current one in the background
+    /// or discarding it depending on the preferences.
+    fn switch_puzzle(&mut self, puzzle_type: PuzzleTypeEnum) {
+        let remembered =
+            self.prefs.interaction.remember_puzzle_states && puzzle_type != self.puzzle.ty();
+        if remembered
+            || self.prefs.interaction.keep_background_puzzle
+            || self.confirm_discard_changes("reset puzzle")
+        {
+            // Building the description of a large puzzle can take a while, so
+            // finish switching once it has been built on the worker thread.
+            self.worker.submit(Task::LoadPuzzle(puzzle_type));
+        }
+    }
+    fn finish_switch_puzzle(&mut self, puzzle_type: PuzzleTypeEnum) {
+        if self.prefs.interaction.remember_puzzle_states && puzzle_type != self.puzzle.ty() {
+            let new = self
+                .other_puzzles
+                .remove(&puzzle_type)
+                .unwrap_or_else(|| PuzzleController::new(puzzle_type));
+            let old = self.puzzle.replace(new);
+            self.other_puzzles.insert(old.ty(), old);
+            self.set_status_ok(format!("Switched to {}", puzzle_type));
+        } else if self.prefs.interaction.keep_background_puzzle {
+            let old = self.puzzle.replace(PuzzleController::new(puzzle_type));
+            self.background_puzzle = Some(old);
+            self.set_status_ok(format!("Loaded {} (previous puzzle kept)", puzzle_type));
+        } else {
+            self.puzzle.replace(PuzzleController::new(puzzle_type));
+            self.set_status_ok(format!("Loaded {}", puzzle_type));
+        }
+    }
+    /// Scrambles the puzzle with `n` random twists, or fully if `n` is
+    /// `None`, on the worker thread.
+    fn scramble_in_background(&mut self, n: Option<usize>) {
+        // The current state has already been discarded, so only changes made
+        // while the scramble is generated need confirmation.
+        self.puzzle.mark_saved();
+        let ty = self.puzzle.ty();
+        let options = self.prefs.scramble[ty].clone();
+        self.worker.submit(Task::Scramble { ty, n, options });
+    }
+    fn handle_task_output(&mut self, output: TaskOutput) {
+        match output {
+            TaskOutput::PuzzleLoaded(ty) => self.finish_switch_puzzle(ty),
+            TaskOutput::Scrambled { ty, n, scramble } => {
+                // Ignore the scramble if the puzzle was switched meanwhile.
+                if ty != self.puzzle.ty() {
+                    return;
+                }
+                if !self.confirm_discard_changes("scramble") {
+                    return;
+                }
+                let result = scramble.and_then(|s| self.puzzle.apply_precomputed_scramble(s));
+                match (result, n) {
+                    (Ok(()), Some(n)) => self.set_status_ok(format!(
+                        "Scrambled with {} random {}",
+                        n,
+                        if n == 1 { "move" } else { "moves" }
+                    )),
+                    (Ok(()), None) => self.set_status_ok("Scrambled fully"),
+                    (Err(e), _) => self.set_status_err(e),
+                }
+            }
+        }
+    }
+
     fn confirm_discard_changes(&mut self, action: &str) -> bool {
         let mut needs_save = self.puzzle.is_unsaved();
 
@@ -817,11 +1949,36 @@ impl App {
         confirm
     }
 
+    /// Scrambles both puzzles in versus mode and starts a new race.
+    pub(crate) fn start_versus_race(&mut self) {
+        if self.versus.is_none() || !self.confirm_discard_changes("start a new race") {
+            return;
+        }
+        let options = &self.prefs.scramble[self.puzzle.ty()];
+        if let Some(versus) = &mut self.versus {
+            match versus.start_race(&mut self.puzzle, options) {
+                Ok(()) => self.set_status_ok("Race started"),
+                Err(e) => self.set_status_err(e),
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn start_ranked_solve(&mut self) {
+        if !self.confirm_discard_changes("start a ranked solve") {
+            return;
+        }
+        match self.leaderboard.start_ranked_solve(&mut self.puzzle) {
+            Ok(()) => self.set_status_ok("Scrambled for a ranked solve"),
+            Err(e) => self.set_status_err(e),
+        }
+    }
+
     fn try_paste_puzzle(&mut self, log_file_contents: &str) {
         match crate::logfile::deserialize(log_file_contents) {
             Ok((puzzle, warnings)) => {
                 if self.confirm_load_puzzle(&warnings) {
-                    self.puzzle = puzzle;
+                    self.puzzle.replace(puzzle);
 
                     self.set_status_ok("Loaded puzzle log file from clipboard");
 
@@ -853,7 +2010,7 @@ impl App {
         match crate::logfile::load_file(&path) {
             Ok((puzzle, warnings)) => {
                 if self.confirm_load_puzzle(&warnings) {
-                    self.puzzle = puzzle;
+                    self.puzzle.replace(puzzle);
 
                     self.set_status_ok(format!("Loaded log file from {}", path.display()));
 
@@ -886,6 +2043,215 @@ impl App {
             self.try_save_puzzle(&path)
         }
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_import_mc4d_settings(&mut self, path: &Path) {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                show_error_dialog(
+                    "Unable to import MC4D settings",
+                    format!("Unable to import MC4D settings:\n\n{e}"),
+                );
+                return;
+            }
+        };
+        let warnings = crate::preferences::import_mc4d_settings(&mut self.prefs, &contents);
+        self.request_redraw_puzzle();
+        self.set_status_ok(format!("Imported MC4D settings from {}", path.display()));
+        if !warnings.is_empty() {
+            show_error_dialog(
+                "Imported MC4D settings with warnings",
+                format!(
+                    "Some MC4D settings could not be imported:\n\n{}",
+                    warnings.iter().map(|w| format!("• {w}")).join("\n"),
+                ),
+            );
+        }
+    }
+    /// Asks the user where to save a move heatmap image and saves it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn try_export_heatmap(&mut self, svg: &str) {
+        if let Some(path) = heatmap_file_dialog().save_file() {
+            match std::fs::write(&path, svg) {
+                Ok(()) => self.set_status_ok(format!("Exported heatmap to {}", path.display())),
+                Err(e) => show_error_dialog(
+                    "Unable to export heatmap",
+                    format!("Unable to export heatmap:\n\n{e}"),
+                ),
+            }
+        }
+    }
+    /// Picks graphics settings suited to the graphics adapter and the size of
+    /// the puzzle.
+    pub(crate) fn detect_gfx_preset(&mut self, gfx: &GraphicsState) {
+        let sticker_count = self.puzzle.stickers().len();
+        let preset = GfxPreset::detect(gfx.adapter_info.device_type, sticker_count);
+        log::info!(
+            "Using {preset} graphics preset for {:?} {:?}",
+            gfx.adapter_info.device_type,
+            gfx.adapter_info.name,
+        );
+        preset.apply(&mut self.prefs.gfx);
+        self.prefs.needs_save = true;
+    }
+    /// Exports a pending solve bundle, if there is one. This must be called
+    /// after drawing the puzzle so that the screenshot is up to date.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn export_pending_bundle(&mut self, gfx: &GraphicsState) {
+        let Some(path) = self.pending_bundle_export.take() else {
+            return;
+        };
+        let screenshot = crate::render::capture_puzzle(self, gfx);
+        if screenshot.is_none() {
+            log::warn!("Unable to capture puzzle for solve bundle");
+        }
+        match crate::bundle::save_file(&path, &self.puzzle, screenshot.as_ref()) {
+            Ok(()) => self.set_status_ok(format!("Exported solve bundle to {}", path.display())),
+            Err(e) => show_error_dialog(
+                "Unable to export solve bundle",
+                format!("Unable to export solve bundle:\n\n{e}"),
+            ),
+        }
+    }
+
+    /// Saves the puzzle and open windows to be restored on the next launch,
+    /// or deletes the saved session if restoring sessions is disabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn save_session(&mut self, ctx: &egui::Context) {
+        let result = if self.prefs.interaction.restore_session {
+            let open_windows = crate::gui::windows::ALL
+                .iter()
+                .filter(|w| w.is_open(ctx))
+                .map(|w| w.name.to_string())
+                .collect();
+            Session::new(
+                &self.puzzle,
+                self.other_puzzles.values(),
+                self.prefs.log_file.clone(),
+                open_windows,
+            )
+            .and_then(|session| session.save())
+        } else {
+            Session::delete()
+        };
+        if let Err(e) = result {
+            log::error!("Error saving session: {}", e);
+        }
+    }
+    /// Restores the session saved on the last exit, returning whether there
+    /// was one.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_restore_session(&mut self, ctx: &egui::Context) -> bool {
+        let session = match Session::load() {
+            Ok(session) => session,
+            Err(e) => {
+                log::info!("No session restored: {}", e);
+                return false;
+            }
+        };
+
+        for window in crate::gui::windows::ALL {
+            if session.open_windows.iter().any(|name| name == window.name) {
+                window.set_open(ctx, true);
+            }
+        }
+
+        match session.puzzle() {
+            Ok(Some((puzzle, _warnings))) => {
+                self.puzzle.replace(puzzle);
+                if session.unsaved {
+                    self.puzzle.mark_unsaved();
+                } else {
+                    self.puzzle.mark_saved();
+                }
+                self.prefs.log_file = session.log_file;
+                if self.prefs.interaction.remember_puzzle_states {
+                    for puzzle in session.other_puzzles() {
+                        self.other_puzzles.insert(puzzle.ty(), puzzle);
+                    }
+                }
+                self.set_status_ok("Restored previous session");
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                log::error!("Error restoring session: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Offers to recover the puzzle from a crash dump that has not been
+    /// offered yet, returning whether it was recovered.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_recover_crash(&mut self) -> bool {
+        let (path, dump) = match CrashDump::load_unrecovered() {
+            Ok(Some(crash)) => crash,
+            Ok(None) => return false,
+            Err(e) => {
+                log::error!("Error loading crash dump: {}", e);
+                return false;
+            }
+        };
+        // Only offer each crash dump once.
+        if let Err(e) = CrashDump::mark_recovered(&path) {
+            log::error!("Error marking crash dump as recovered: {}", e);
+        }
+
+        let recover = rfd::MessageDialog::new()
+            .set_title("Recover puzzle")
+            .set_description(&format!(
+                "{} crashed the last time it was run. Recover the puzzle \
+                 from before the crash?\n\n\
+                 The crash dump is saved at \"{}\"",
+                crate::TITLE,
+                path.display(),
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if !recover {
+            return false;
+        }
+
+        match dump.puzzle() {
+            Ok(Some((puzzle, _warnings))) => {
+                self.puzzle.replace(puzzle);
+                self.puzzle.mark_unsaved();
+                self.prefs.log_file = dump.log_file;
+                self.set_status_ok("Recovered puzzle from crash");
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                show_error_dialog("Unable to recover puzzle", e);
+                false
+            }
+        }
+    }
+    /// Updates the snapshot saved if the app crashes, if the puzzle has
+    /// changed since the last update.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_crash_snapshot(&mut self) {
+        use std::hash::{Hash, Hasher};
+
+        let now = Instant::now();
+        if let Some((last_time, _)) = self.last_crash_snapshot {
+            if now < last_time + CRASH_SNAPSHOT_INTERVAL {
+                return;
+            }
+        }
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.puzzle.latest().hash(&mut hasher);
+        self.puzzle.undo_buffer().len().hash(&mut hasher);
+        self.puzzle.has_redo().hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if self.last_crash_snapshot.map(|(_, h)| h) != Some(hash) {
+            CrashDump::new(&self.puzzle, &self.prefs).set_snapshot();
+        }
+        self.last_crash_snapshot = Some((now, hash));
+    }
 
     #[cfg(target_arch = "wasm32")]
     const LOCAL_STORAGE_KEY: &str = "hyperspeedcube_puzzle_log";
@@ -912,7 +2278,7 @@ impl App {
             return
         };
         if self.confirm_load_puzzle(&warnings) {
-            self.puzzle = p;
+            self.puzzle.replace(p);
             self.puzzle.mark_saved_in_local_storage();
         }
     }
@@ -923,10 +2289,10 @@ impl App {
     fn clear_status(&mut self) {
         self.status_msg = String::new();
     }
-    fn set_status_ok(&mut self, msg: impl fmt::Display) {
+    pub(crate) fn set_status_ok(&mut self, msg: impl fmt::Display) {
         self.status_msg = msg.to_string()
     }
-    fn set_status_err(&mut self, msg: impl fmt::Display) {
+    pub(crate) fn set_status_err(&mut self, msg: impl fmt::Display) {
         self.status_msg = format!("Error: {}", msg)
     }
 
@@ -951,17 +2317,48 @@ impl App {
     }
 }
 
+/// How a keypress was generated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum KeyPress {
+    /// The key was just pressed.
+    Down,
+    /// The key is held and the OS repeated the keypress.
+    Repeat,
+    /// The key was pressed and released on its own.
+    Tap,
+}
+
+/// Destination of events that the app sends to itself.
+enum AppEventSender {
+    /// Event loop of the window.
+    EventLoop(EventLoopProxy<AppEvent>),
+    /// Queue that is handled manually, for tests that run without a window.
+    #[cfg(test)]
+    Queue(Rc<RefCell<VecDeque<AppEvent>>>),
+}
+
 #[derive(Debug)]
 pub(crate) enum AppEvent {
     Command(Command),
 
     Twist(Twist),
+    /// Reset the puzzle and apply a specific scramble.
+    Scramble(Vec<Twist>, ScrambleState),
+    /// Reset the puzzle and replace its state with an arbitrary one.
+    SetCustomState(Puzzle),
+    /// Reset the puzzle and twist it into a named pattern.
+    ApplyPattern(Pattern),
 
     Click(egui::PointerButton),
     /// Drag event with a per-frame delta, sent every frame until the drag ends
     /// (even if the delta is zero).
     Drag(egui::Vec2),
     DragReleased,
+    /// Multiplies the puzzle scale by a factor.
+    Zoom(f32),
+    /// Moves the puzzle within the viewport, in the same units as the view
+    /// alignment.
+    Pan(egui::Vec2),
 
     StatusError(String),
 
@@ -984,6 +2381,32 @@ impl From<Twist> for AppEvent {
 pub(crate) struct AppEventResponse {
     pub(crate) copy_string: Option<String>,
     pub(crate) request_paste: bool,
+    pub(crate) toggle_fullscreen: bool,
+}
+
+/// Returns the colored square emoji closest to `color`, for text diagrams.
+fn color_square(color: egui::Color32) -> &'static str {
+    const SQUARES: [(&str, [u8; 3]); 9] = [
+        ("⬜", [230, 231, 232]),
+        ("🟥", [221, 46, 68]),
+        ("🟧", [244, 144, 12]),
+        ("🟨", [253, 203, 88]),
+        ("🟩", [120, 177, 89]),
+        ("🟦", [85, 172, 238]),
+        ("🟪", [170, 142, 214]),
+        ("🟫", [193, 105, 79]),
+        ("⬛", [49, 55, 61]),
+    ];
+    let distance = |[r, g, b]: [u8; 3]| {
+        let dr = color.r() as i32 - r as i32;
+        let dg = color.g() as i32 - g as i32;
+        let db = color.b() as i32 - b as i32;
+        dr * dr + dg * dg + db * db
+    };
+    SQUARES
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map_or("⬜", |(square, _)| square)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -992,6 +2415,53 @@ fn file_dialog() -> rfd::FileDialog {
         .add_filter("Hyperspeedcube Log Files", &["hsc", "log"])
         .add_filter("All files", &["*"])
 }
+#[cfg(not(target_arch = "wasm32"))]
+fn bundle_file_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new()
+        .add_filter("Zip Files", &["zip"])
+        .set_file_name("solve.zip")
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn heatmap_file_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new()
+        .add_filter("SVG Images", &["svg"])
+        .set_file_name("heatmap.svg")
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn mc4d_settings_file_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new()
+        .add_filter("MC4D Settings Files", &["txt", "prop", "properties"])
+        .add_filter("All files", &["*"])
+}
+
+/// Loads preferences. If the preferences file is invalid, asks whether to load
+/// the defaults instead, and quits if the user declines.
+fn load_prefs() -> Preferences {
+    Preferences::try_load().unwrap_or_else(|e| {
+        log::warn!("Error loading preferences: {}", e);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let load_defaults = rfd::MessageDialog::new()
+                .set_title("Invalid preferences")
+                .set_description(&format!(
+                    "Unable to load preferences:\n\n{e}\n\n\
+                     Load the default preferences instead? Your \
+                     preferences file will be backed up. Choose \
+                     \"No\" to quit and fix the file yourself.",
+                ))
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show();
+            if !load_defaults {
+                std::process::exit(1);
+            }
+        }
+
+        Preferences::backup_user_prefs();
+        Preferences::load_defaults()
+    })
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn show_error_dialog(title: &str, e: impl fmt::Display) {
     rfd::MessageDialog::new()
@@ -0,0 +1,172 @@
+//! Timed speedsolving attempts: a scramble is generated and shown, applied
+//! once the user confirms it, and then inspected and solved against the
+//! clock.
+
+use instant::{Duration, Instant};
+
+use crate::preferences::ScrambleOptions;
+use crate::puzzle::*;
+
+/// Stage of the current attempt.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Attempt {
+    /// The scramble has been generated and is waiting for confirmation.
+    Pending {
+        scramble: Vec<Twist>,
+        scramble_state: ScrambleState,
+    },
+    /// The scramble has been applied and the user is inspecting the puzzle.
+    /// The solve clock starts on the first twist.
+    Inspecting { start: Instant },
+    /// The solve clock is running.
+    Solving { inspection: Duration },
+    /// The puzzle was solved.
+    Done(AttemptResult),
+}
+
+/// Result of a completed attempt.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct AttemptResult {
+    pub puzzle_type: PuzzleTypeEnum,
+    pub inspection: Duration,
+    pub time: Duration,
+    /// Number of twists in the default metric.
+    pub twist_count: usize,
+}
+
+/// Current attempt and results of previous attempts.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Attempts {
+    pub current: Option<Attempt>,
+    /// Results of attempts completed since launch, oldest first.
+    pub results: Vec<AttemptResult>,
+}
+impl Attempts {
+    /// Generates a scramble for a new attempt, without applying it yet.
+    pub fn start(
+        &mut self,
+        puzzle: &PuzzleController,
+        options: &ScrambleOptions,
+    ) -> Result<(), &'static str> {
+        let scramble = puzzle.generate_scramble(puzzle.scramble_moves_count(), options)?;
+        let scramble_state = if options.is_restricted() {
+            ScrambleState::Partial
+        } else {
+            ScrambleState::Full
+        };
+        self.current = Some(Attempt::Pending {
+            scramble,
+            scramble_state,
+        });
+        Ok(())
+    }
+
+    /// Applies the scramble of a pending attempt and starts inspection.
+    pub fn confirm(&mut self, puzzle: &mut PuzzleController) -> Result<(), &'static str> {
+        let Some(Attempt::Pending {
+            scramble,
+            scramble_state,
+        }) = &self.current
+        else {
+            return Err("No scramble to confirm");
+        };
+        puzzle.scramble_with(scramble, *scramble_state)?;
+        self.current = Some(Attempt::Inspecting {
+            start: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Cancels the current attempt.
+    pub fn cancel(&mut self) {
+        self.current = None;
+    }
+
+    /// Advances the current attempt based on the state of the puzzle, and
+    /// returns the result if the attempt was just completed.
+    pub fn update(&mut self, puzzle: &PuzzleController) -> Option<AttemptResult> {
+        match self.current.as_ref()? {
+            Attempt::Pending { .. } | Attempt::Done(_) => (),
+
+            &Attempt::Inspecting { start } => {
+                if puzzle.scramble_state() == ScrambleState::None {
+                    // The puzzle was reset or replaced.
+                    self.cancel();
+                } else if !puzzle.undo_buffer().is_empty() {
+                    // Measure inspection up to the first twist rather than
+                    // up to this update, which may be a frame later.
+                    self.current = Some(Attempt::Solving {
+                        inspection: start.elapsed().saturating_sub(puzzle.solve_clock()),
+                    });
+                }
+            }
+
+            &Attempt::Solving { inspection } => {
+                if puzzle.scramble_state() == ScrambleState::None {
+                    self.cancel();
+                } else if puzzle.has_been_solved() {
+                    let time = puzzle
+                        .twist_times()
+                        .iter()
+                        .rev()
+                        .find_map(|&t| t)
+                        .unwrap_or_else(|| puzzle.solve_clock());
+                    let result = AttemptResult {
+                        puzzle_type: puzzle.ty(),
+                        inspection,
+                        time,
+                        twist_count: puzzle.twist_count(TwistMetric::default()),
+                    };
+                    self.results.push(result);
+                    self.current = Some(Attempt::Done(result));
+                    return Some(result);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attempt_flow() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        let mut attempts = Attempts::default();
+
+        attempts
+            .start(&puzzle, &ScrambleOptions::default())
+            .unwrap();
+        assert!(matches!(attempts.current, Some(Attempt::Pending { .. })));
+        // The scramble is not applied until it is confirmed.
+        assert!(puzzle.scramble().is_empty());
+
+        attempts.confirm(&mut puzzle).unwrap();
+        assert!(!puzzle.scramble().is_empty());
+        attempts.update(&puzzle);
+        assert!(matches!(attempts.current, Some(Attempt::Inspecting { .. })));
+
+        // Solve by reversing the scramble.
+        let solution: Vec<Twist> = puzzle
+            .scramble()
+            .iter()
+            .rev()
+            .map(|&t| ty.reverse_twist(t))
+            .collect();
+        puzzle.twist(solution[0]).unwrap();
+        assert_eq!(attempts.update(&puzzle), None);
+        assert!(matches!(attempts.current, Some(Attempt::Solving { .. })));
+
+        for &twist in &solution[1..] {
+            puzzle.twist(twist).unwrap();
+        }
+        assert!(puzzle.check_just_solved());
+        let result = attempts.update(&puzzle).unwrap();
+        assert_eq!(result.puzzle_type, ty);
+        assert_eq!(attempts.results, [result]);
+        assert_eq!(attempts.current, Some(Attempt::Done(result)));
+    }
+}
@@ -0,0 +1,163 @@
+//! Solve bundles, which package a solve into a single zip file for archiving
+//! or sharing.
+
+use anyhow::Result;
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+use crate::logfile::LogFileFormat;
+use crate::puzzle::*;
+
+const SCREENSHOT_FILE_NAME: &str = "final_state.png";
+const RECONSTRUCTION_FILE_NAME: &str = "reconstruction.txt";
+const STATS_FILE_NAME: &str = "stats.json";
+
+/// Image of the puzzle view.
+#[derive(Debug, Clone)]
+pub(crate) struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    /// Pixel data, with 8-bit RGBA for each pixel and no padding between rows.
+    pub rgba: Vec<u8>,
+}
+impl Screenshot {
+    fn to_png(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.rgba)?;
+        writer.finish()?;
+        Ok(bytes)
+    }
+}
+
+/// Saves a solve bundle containing the log file, a screenshot of the final
+/// state, a reconstruction, and stats.
+pub(crate) fn save_file(
+    path: &Path,
+    puzzle: &PuzzleController,
+    screenshot: Option<&Screenshot>,
+) -> Result<()> {
+    let log = crate::logfile::serialize(puzzle, LogFileFormat::Hsc)?;
+    let reconstruction = reconstruction(puzzle);
+    let stats = serde_json::to_string_pretty(&SolveStats::new(puzzle))?;
+
+    let deflated = zip::write::FileOptions::default();
+    // PNG files are already compressed.
+    let stored = deflated.compression_method(zip::CompressionMethod::Stored);
+
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+    zip.start_file(
+        format!("solve.{}", LogFileFormat::Hsc.extension()),
+        deflated,
+    )?;
+    zip.write_all(log.as_bytes())?;
+    if let Some(screenshot) = screenshot {
+        zip.start_file(SCREENSHOT_FILE_NAME, stored)?;
+        zip.write_all(&screenshot.to_png()?)?;
+    }
+    zip.start_file(RECONSTRUCTION_FILE_NAME, deflated)?;
+    zip.write_all(reconstruction.as_bytes())?;
+    zip.start_file(STATS_FILE_NAME, deflated)?;
+    zip.write_all(stats.as_bytes())?;
+    zip.finish()?;
+
+    Ok(())
+}
+
+/// Returns a human-readable reconstruction of the solve, with one line per
+/// phase.
+fn reconstruction(puzzle: &PuzzleController) -> String {
+    let notation = puzzle.notation_scheme();
+    let twists_to_string = |entries: &[HistoryEntry]| {
+        entries
+            .iter()
+            .map(|entry| entry.to_string(notation))
+            .join(" ")
+    };
+
+    let mut ret = format!("{}\n\n", puzzle.ty().name());
+
+    ret += &format!("Scramble ({} twists):\n", puzzle.scramble().len());
+    ret += &puzzle
+        .scramble()
+        .iter()
+        .map(|&twist| notation.twist_to_string(twist))
+        .join(" ");
+    ret += "\n\n";
+
+    ret += &format!(
+        "Solution ({} {}):\n",
+        puzzle.twist_count(TwistMetric::default()),
+        TwistMetric::default(),
+    );
+    let undo_buffer = puzzle.undo_buffer();
+    let mut start = 0;
+    for split in puzzle.phase_splits() {
+        let end = split.twist_count.min(undo_buffer.len());
+        ret += &format!(
+            "{} // {}\n",
+            twists_to_string(&undo_buffer[start..end]),
+            split.name,
+        );
+        start = end;
+    }
+    if start < undo_buffer.len() {
+        ret += &twists_to_string(&undo_buffer[start..]);
+        ret += "\n";
+    }
+
+    ret
+}
+
+/// Summary of a solve, saved as JSON.
+#[derive(Serialize, Debug, Clone)]
+struct SolveStats {
+    puzzle: String,
+    scrambled: bool,
+    solved: bool,
+    scramble_length: usize,
+    /// Number of twists in each metric.
+    twist_counts: BTreeMap<String, usize>,
+    /// Time on the solve clock at the last twist, in seconds.
+    time: Option<f64>,
+    splits: Vec<SplitStats>,
+}
+impl SolveStats {
+    fn new(puzzle: &PuzzleController) -> Self {
+        Self {
+            puzzle: puzzle.ty().name().to_owned(),
+            scrambled: puzzle.scramble_state() != ScrambleState::None,
+            solved: puzzle.has_been_solved(),
+            scramble_length: puzzle.scramble().len(),
+            twist_counts: TwistMetric::iter()
+                .map(|metric| (metric.to_string(), puzzle.twist_count(metric)))
+                .collect(),
+            time: (puzzle.twist_times().iter().rev())
+                .find_map(|&t| t)
+                .map(|t| t.as_secs_f64()),
+            splits: puzzle
+                .phase_splits()
+                .iter()
+                .map(|split| SplitStats {
+                    name: split.name.clone(),
+                    twist_count: split.twist_count,
+                    time: split.time.map(|t| t.as_secs_f64()),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct SplitStats {
+    name: String,
+    twist_count: usize,
+    time: Option<f64>,
+}
@@ -6,11 +6,7 @@ use std::fmt;
 use std::str::FromStr;
 
 use crate::puzzle::*;
-
-/// Minimum number of moves for a partial scramble.
-pub const PARTIAL_SCRAMBLE_MOVE_COUNT_MIN: usize = 1;
-/// Maximum number of moves for a partial scramble.
-pub const PARTIAL_SCRAMBLE_MOVE_COUNT_MAX: usize = 20;
+pub use crate::puzzle::{PARTIAL_SCRAMBLE_MOVE_COUNT_MAX, PARTIAL_SCRAMBLE_MOVE_COUNT_MIN};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -19,6 +15,7 @@ pub enum Command {
     Open,
     Save,
     SaveAs,
+    ExportSolveBundle,
     Exit,
 
     // File menu (web)
@@ -29,16 +26,57 @@ pub enum Command {
     // Edit menu
     Undo,
     Redo,
+    CancelQueuedTwists,
+    /// Rotates a puzzle that is solved up to rotation back to its original
+    /// orientation.
+    RealignSolved,
     Reset,
+    ResetToScramble,
 
     // Scramble menu
     ScrambleN(usize),
     ScrambleFull,
+    NewAttempt,
 
     // Puzzle menu
     NewPuzzle(PuzzleTypeEnum),
+    NextPuzzle,
+    PreviousPuzzle,
+    /// Swaps the current puzzle with the one kept in the background.
+    SwapBackgroundPuzzle,
+    /// Copies a textual description of the puzzle state, for screen readers.
+    DescribeState,
+    /// Copies a diagram of the puzzle state using face symbols.
+    CopyTextDiagram,
+    /// Copies a diagram of the puzzle state using colored squares.
+    CopyColorDiagram,
 
     ToggleBlindfold,
+    ToggleStepMode,
+    /// Advances the current twist animation in step mode.
+    StepTwist,
+
+    // Settings menu
+    ImportMc4dSettings,
+    SnapView,
+    /// Rotates the view for as long as the key is held.
+    RotateView(ScreenDirection),
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    Pan(ScreenDirection),
+    ResetPan,
+    /// Switches between windowed and fullscreen.
+    ToggleFullscreen,
+
+    /// Runs a custom command from a plugin.
+    Plugin {
+        plugin: String,
+        command: String,
+    },
+
+    /// Runs several commands in order.
+    Sequence(Vec<Command>),
 
     #[default]
     #[serde(other)]
@@ -50,6 +88,7 @@ impl Command {
             Command::Open => "🗁".to_owned(),
             Command::Save => "💾".to_owned(),
             Command::SaveAs => "Save As".to_owned(),
+            Command::ExportSolveBundle => "📦".to_owned(),
             Command::Exit => "Exit".to_owned(),
 
             Command::CopyHscLog => "🗐".to_owned(),
@@ -58,14 +97,43 @@ impl Command {
 
             Command::Undo => "⮪".to_owned(),
             Command::Redo => "⮫".to_owned(),
+            Command::CancelQueuedTwists => "✖".to_owned(),
+            Command::RealignSolved => "🔄".to_owned(),
             Command::Reset => "⟲".to_owned(),
+            Command::ResetToScramble => "⟲🔀".to_owned(),
 
             Command::ScrambleN(n) => format!("🔀 {n}"),
             Command::ScrambleFull => "🔀".to_owned(),
+            Command::NewAttempt => "⏱".to_owned(),
 
             Command::NewPuzzle(ty) => format!("New {}", ty.name()),
+            Command::NextPuzzle => "Puzzle ⏵".to_owned(),
+            Command::PreviousPuzzle => "⏴ Puzzle".to_owned(),
+            Command::SwapBackgroundPuzzle => "⇄ Puzzle".to_owned(),
+            Command::DescribeState => "🗐 State".to_owned(),
+            Command::CopyTextDiagram => "🗐 Diagram".to_owned(),
+            Command::CopyColorDiagram => "🗐 Colors".to_owned(),
 
             Command::ToggleBlindfold => "BLD".to_owned(),
+            Command::ToggleStepMode => "STEP".to_owned(),
+            Command::StepTwist => "⏭".to_owned(),
+
+            Command::ImportMc4dSettings => "Import MC4D".to_owned(),
+            Command::SnapView => "⌖".to_owned(),
+            Command::RotateView(direction) => direction.symbol().to_owned(),
+            Command::ZoomIn => "🔍+".to_owned(),
+            Command::ZoomOut => "🔍-".to_owned(),
+            Command::ResetZoom => "🔍=".to_owned(),
+            Command::Pan(direction) => format!("✋{}", direction.symbol()),
+            Command::ResetPan => "✋=".to_owned(),
+            Command::ToggleFullscreen => "⛶".to_owned(),
+
+            Command::Plugin { command, .. } => command.clone(),
+
+            Command::Sequence(commands) => commands
+                .iter()
+                .map(|command| command.short_description())
+                .join(" "),
 
             Command::None => String::new(),
         }
@@ -79,6 +147,9 @@ pub enum PuzzleMouseCommand {
     TwistCcw,
     Recenter,
     SelectPiece,
+    /// Opens the sticker context menu. This only has an effect when bound to
+    /// the right mouse button.
+    ContextMenu,
 
     #[default]
     #[serde(other)]
@@ -102,10 +173,30 @@ pub enum PuzzleCommand {
         #[serde(default)]
         layers: LayerMaskDesc,
     },
+    /// Twists an axis and its opposite at the same time, each by the same
+    /// layers counted from its own side.
+    OppositeTwist {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        axis: Option<String>,
+        #[serde(default)]
+        direction: String,
+        /// Direction for the opposite axis; empty means the same as
+        /// `direction`.
+        #[serde(default)]
+        opposite_direction: String,
+        #[serde(default)]
+        layers: LayerMaskDesc,
+    },
     Recenter {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         axis: Option<String>,
     },
+    /// Applies the twists since the scramble again, mirrored across the
+    /// hyperplane perpendicular to the axis.
+    Mirror {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        axis: Option<String>,
+    },
 
     Filter {
         #[serde(default)]
@@ -151,6 +242,31 @@ impl PuzzleCommand {
                 ty.twist_direction_from_name(direction).unwrap_or_default(),
                 layers.to_layer_mask(ty.layer_count()),
             ),
+            PuzzleCommand::OppositeTwist {
+                axis,
+                direction,
+                opposite_direction,
+                layers,
+            } => {
+                let axis = axis
+                    .as_deref()
+                    .and_then(|axis_name| ty.twist_axis_from_name(axis_name));
+                let layers = layers.to_layer_mask(ty.layer_count());
+                let dir = |name: &str| ty.twist_direction_from_name(name).unwrap_or_default();
+                let opposite_direction = match opposite_direction.as_str() {
+                    "" => direction,
+                    s => s,
+                };
+                format!(
+                    "{}+{}",
+                    ty.twist_command_short_description(axis, dir(direction), layers),
+                    ty.twist_command_short_description(
+                        axis.and_then(|axis| ty.opposite_twist_axis(axis)),
+                        dir(opposite_direction),
+                        layers,
+                    ),
+                )
+            }
             PuzzleCommand::Recenter { axis } => {
                 match axis
                     .as_deref()
@@ -167,6 +283,10 @@ impl PuzzleCommand {
                     None => "Recenter".to_string(),
                 }
             }
+            PuzzleCommand::Mirror { axis } => match axis {
+                Some(axis_name) => format!("Mirror {axis_name}"),
+                None => "Mirror".to_string(),
+            },
 
             PuzzleCommand::Filter { mode, filter_name } => match filter_name.as_str() {
                 "Next" => "➡".to_string(),
@@ -189,21 +309,35 @@ impl PuzzleCommand {
 
     pub fn layers_mut(&mut self) -> Option<&mut LayerMaskDesc> {
         match self {
-            Self::Grip { layers, .. } | Self::Twist { layers, .. } => Some(layers),
+            Self::Grip { layers, .. }
+            | Self::Twist { layers, .. }
+            | Self::OppositeTwist { layers, .. } => Some(layers),
             _ => None,
         }
     }
     pub fn axis_mut(&mut self) -> Option<&mut Option<String>> {
         match self {
-            Self::Grip { axis, .. } | Self::Twist { axis, .. } | Self::Recenter { axis } => {
-                Some(axis)
-            }
+            Self::Grip { axis, .. }
+            | Self::Twist { axis, .. }
+            | Self::OppositeTwist { axis, .. }
+            | Self::Recenter { axis }
+            | Self::Mirror { axis } => Some(axis),
             _ => None,
         }
     }
     pub fn direction_mut(&mut self) -> Option<&mut String> {
         match self {
-            Self::Twist { direction, .. } => Some(direction),
+            Self::Twist { direction, .. } | Self::OppositeTwist { direction, .. } => {
+                Some(direction)
+            }
+            _ => None,
+        }
+    }
+    pub fn opposite_direction_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::OppositeTwist {
+                opposite_direction, ..
+            } => Some(opposite_direction),
             _ => None,
         }
     }
@@ -275,6 +409,51 @@ pub enum FilterMode {
     Toggle,
 }
 
+/// Direction on the screen, used to rotate or pan the view using the keyboard.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Display,
+    AsRefStr,
+    IntoStaticStr,
+    EnumIter,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenDirection {
+    #[default]
+    Left,
+    Right,
+    Up,
+    Down,
+}
+impl ScreenDirection {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ScreenDirection::Left => "⏴",
+            ScreenDirection::Right => "⏵",
+            ScreenDirection::Up => "⏶",
+            ScreenDirection::Down => "⏷",
+        }
+    }
+    /// Returns the direction as a unit vector with +Y pointing down, in the
+    /// same form as a mouse drag.
+    pub fn offset(self) -> [f32; 2] {
+        match self {
+            ScreenDirection::Left => [-1.0, 0.0],
+            ScreenDirection::Right => [1.0, 0.0],
+            ScreenDirection::Up => [0.0, -1.0],
+            ScreenDirection::Down => [0.0, 1.0],
+        }
+    }
+}
+
 /// Description of a layer mask that adjusts to the size of a puzzle.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct LayerMaskDesc {
@@ -325,6 +504,45 @@ impl LayerMaskDesc {
         *self == Self::default()
     }
 
+    /// Returns a description of the layers from `start` to `end`, inclusive.
+    pub(crate) fn from_range(start: i8, end: i8) -> Self {
+        Self {
+            segments: vec![LayerMaskDescSegment {
+                subtract: false,
+                start,
+                end,
+            }],
+        }
+    }
+    /// Returns a description of exactly the layers in `mask`, using one range
+    /// for each contiguous group of layers.
+    pub(crate) fn from_layer_mask(mask: LayerMask) -> Self {
+        let mut segments = vec![];
+        let mut bits = mask.0;
+        let mut start = 0;
+        while bits != 0 {
+            start += bits.trailing_zeros();
+            bits >>= bits.trailing_zeros();
+            let len = bits.trailing_ones();
+            segments.push(LayerMaskDescSegment {
+                subtract: false,
+                start: (start + 1) as i8,
+                end: (start + len) as i8,
+            });
+            start += len;
+            bits = bits.checked_shr(len).unwrap_or(0);
+        }
+        Self { segments }
+    }
+    /// Returns the first and last layer if this describes a single range of
+    /// layers.
+    pub(crate) fn as_range(&self) -> Option<(i8, i8)> {
+        match self.segments.as_slice() {
+            [segment] if !segment.subtract => Some((segment.start, segment.end)),
+            _ => None,
+        }
+    }
+
     pub(crate) fn to_layer_mask(&self, layer_count: u8) -> LayerMask {
         let mut ret = LayerMask(0);
 
@@ -409,3 +627,20 @@ impl FromStr for LayerMaskDescSegment {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_mask_desc_from_layer_mask() {
+        for bits in [0, 0b1, 0b110, 0b1011_0001, u32::MAX] {
+            let desc = LayerMaskDesc::from_layer_mask(LayerMask(bits));
+            assert_eq!(desc.to_layer_mask(32), LayerMask(bits), "{desc}");
+        }
+        assert_eq!(
+            LayerMaskDesc::from_layer_mask(LayerMask(0b1101)).to_string(),
+            "1,3..4",
+        );
+    }
+}
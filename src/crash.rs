@@ -0,0 +1,145 @@
+//! Crash dumps that save the puzzle state when Hyperspeedcube panics, so that
+//! it can be recovered on the next launch.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::logfile::LogFileFormat;
+use crate::preferences::Preferences;
+use crate::puzzle::PuzzleController;
+
+/// Suffix added to the file name of a crash dump once it has been offered for
+/// recovery.
+const RECOVERED_SUFFIX: &str = "_recovered";
+
+lazy_static! {
+    /// Latest snapshot of the app, written to a crash dump on panic.
+    static ref SNAPSHOT: Mutex<Option<CrashDump>> = Mutex::new(None);
+}
+
+/// State of the app at the time of a crash.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub(crate) struct CrashDump {
+    /// Panic message and location.
+    pub panic_message: String,
+    /// Puzzle state and undo history, in the native log format.
+    pub log: Option<String>,
+    /// Log file that the puzzle was last loaded from or saved to.
+    pub log_file: Option<PathBuf>,
+    /// Preferences, in YAML.
+    pub prefs: String,
+}
+impl CrashDump {
+    /// Captures a snapshot of the puzzle and preferences.
+    pub fn new(puzzle: &PuzzleController, prefs: &Preferences) -> Self {
+        Self {
+            panic_message: String::new(),
+            log: crate::logfile::serialize(puzzle, LogFileFormat::Hsc).ok(),
+            log_file: prefs.log_file.clone(),
+            prefs: serde_yaml::to_string(prefs).unwrap_or_default(),
+        }
+    }
+
+    /// Sets the snapshot to write if the app crashes.
+    pub fn set_snapshot(self) {
+        if let Ok(mut snapshot) = SNAPSHOT.lock() {
+            *snapshot = Some(self);
+        }
+    }
+    /// Writes the latest snapshot to the crash dump folder, returning the path
+    /// of the crash dump. This is called from the panic hook, so it must not
+    /// block if the panic happened while the snapshot was being set.
+    pub fn write_snapshot(panic_message: String) -> Result<PathBuf> {
+        let mut dump = match SNAPSHOT.try_lock() {
+            Ok(snapshot) => snapshot.clone().context("no snapshot")?,
+            Err(_) => anyhow::bail!("snapshot is locked"),
+        };
+        dump.panic_message = panic_message;
+
+        let dir = crate::preferences::crash_dump_dir_path()?;
+        std::fs::create_dir_all(&dir)?;
+        let now =
+            time::OffsetDateTime::now_local().unwrap_or_else(|_| time::OffsetDateTime::now_utc());
+        let path = dir.join(format!(
+            "crash_{:04}-{:02}-{:02}_{:02}-{:02}-{:02}.yaml",
+            now.year(),
+            now.month() as u8,
+            now.day(),
+            now.hour(),
+            now.minute(),
+            now.second(),
+        ));
+        std::fs::write(&path, serde_yaml::to_string(&dump)?)?;
+        Ok(path)
+    }
+
+    /// Loads the most recent crash dump that has not been offered for
+    /// recovery yet, along with its path.
+    pub fn load_unrecovered() -> Result<Option<(PathBuf, Self)>> {
+        let dir = crate::preferences::crash_dump_dir_path()?;
+        if !dir.exists() {
+            return Ok(None);
+        }
+        let latest = std::fs::read_dir(&dir)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map_or(false, |stem| {
+                        stem.starts_with("crash_") && !stem.ends_with(RECOVERED_SUFFIX)
+                    })
+            })
+            .max();
+        let Some(path) = latest else {
+            return Ok(None);
+        };
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading crash dump {}", path.display()))?;
+        Ok(Some((path, serde_yaml::from_str(&contents)?)))
+    }
+    /// Renames a crash dump so that it is not offered for recovery again. The
+    /// file is kept so that it can be attached to a bug report.
+    pub fn mark_recovered(path: &Path) -> Result<()> {
+        let stem = path.file_stem().context("no file name")?.to_string_lossy();
+        let new_path = path.with_file_name(format!("{stem}{RECOVERED_SUFFIX}.yaml"));
+        std::fs::rename(path, new_path)?;
+        Ok(())
+    }
+
+    /// Deserializes the saved puzzle, if there is one.
+    pub fn puzzle(&self) -> Result<Option<(PuzzleController, Vec<String>)>> {
+        self.log
+            .as_deref()
+            .map(crate::logfile::deserialize)
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preferences::DEFAULT_PREFS;
+    use crate::puzzle::*;
+
+    #[test]
+    fn test_crash_dump_round_trip() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        for twist in ty.parse_twists("R U R' U'").unwrap() {
+            puzzle.twist(twist).unwrap();
+        }
+
+        let mut dump = CrashDump::new(&puzzle, &DEFAULT_PREFS);
+        dump.panic_message = "panicked at 'test'".to_string();
+        let yaml = serde_yaml::to_string(&dump).unwrap();
+        let loaded: CrashDump = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(loaded, dump);
+
+        let (recovered, _warnings) = loaded.puzzle().unwrap().unwrap();
+        assert_eq!(recovered.latest(), puzzle.latest());
+        assert_eq!(recovered.undo_buffer().len(), 4);
+    }
+}
@@ -0,0 +1,182 @@
+//! Premoves and NISS (normal-inverse scramble switch) for fewest-moves
+//! analysis.
+//!
+//! The solution is split into moves made on the normal scramble and
+//! premoves, which are applied before the scramble. The inverse of the
+//! premoves is solved on the inverse scramble, and the premoves are appended
+//! to the end of the final solution.
+
+use crate::puzzle::*;
+
+/// Which version of the scramble is shown on the puzzle.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum NissSide {
+    /// The scramble, with premoves applied before it.
+    #[default]
+    Normal,
+    /// The inverse of the scramble, with the inverse of the normal moves
+    /// applied before it.
+    Inverse,
+}
+
+/// Fewest-moves session on the current puzzle.
+#[derive(Debug, Clone)]
+pub(crate) struct FmcSession {
+    /// Original scramble.
+    scramble: Vec<Twist>,
+    /// Moves applied before the scramble.
+    premoves: Vec<Twist>,
+    /// Moves applied after the scramble.
+    moves: Vec<Twist>,
+    /// Which version of the scramble is shown on the puzzle.
+    side: NissSide,
+    /// Scramble of the puzzle when it was last set up, used to detect when the
+    /// puzzle is scrambled or reset by something else.
+    puzzle_scramble: Vec<Twist>,
+}
+impl FmcSession {
+    /// Starts a session on a scrambled puzzle, keeping the twists made so far
+    /// as normal moves.
+    pub fn start(puzzle: &PuzzleController) -> Result<Self, &'static str> {
+        if puzzle.scramble().is_empty() {
+            return Err("Puzzle is not scrambled");
+        }
+        Ok(Self {
+            scramble: puzzle.scramble().to_vec(),
+            premoves: vec![],
+            moves: history_twists(puzzle),
+            side: NissSide::Normal,
+            puzzle_scramble: puzzle.scramble().to_vec(),
+        })
+    }
+
+    /// Returns whether the puzzle is still set up for this session.
+    pub fn is_current(&self, puzzle: &PuzzleController) -> bool {
+        puzzle.scramble() == self.puzzle_scramble
+    }
+    /// Records the twists made on the puzzle since it was last set up.
+    pub fn sync(&mut self, puzzle: &PuzzleController) {
+        let history = history_twists(puzzle);
+        match self.side {
+            NissSide::Normal => self.moves = history,
+            NissSide::Inverse => self.premoves = invert(puzzle.ty(), &history),
+        }
+    }
+
+    /// Returns which version of the scramble is shown on the puzzle.
+    pub fn side(&self) -> NissSide {
+        self.side
+    }
+    /// Returns the moves applied before the scramble.
+    pub fn premoves(&self) -> &[Twist] {
+        &self.premoves
+    }
+    /// Returns the moves applied after the scramble.
+    pub fn moves(&self) -> &[Twist] {
+        &self.moves
+    }
+    /// Returns the solution found so far: the normal moves followed by the
+    /// premoves.
+    pub fn solution(&self) -> Vec<Twist> {
+        [&self.moves[..], &self.premoves[..]].concat()
+    }
+
+    /// Shows the other version of the scramble on the puzzle.
+    pub fn switch_side(&mut self, puzzle: &mut PuzzleController) -> Result<(), &'static str> {
+        self.sync(puzzle);
+        self.side = match self.side {
+            NissSide::Normal => NissSide::Inverse,
+            NissSide::Inverse => NissSide::Normal,
+        };
+        self.set_up(puzzle)
+    }
+    /// Adds premoves before the existing ones. This is only possible on the
+    /// normal side.
+    pub fn add_premoves(
+        &mut self,
+        puzzle: &mut PuzzleController,
+        premoves: &[Twist],
+    ) -> Result<(), &'static str> {
+        if self.side != NissSide::Normal {
+            return Err("Premoves can only be added on the normal scramble");
+        }
+        self.sync(puzzle);
+        self.premoves.splice(0..0, premoves.iter().copied());
+        self.set_up(puzzle)
+    }
+
+    /// Scrambles the puzzle with the current version of the scramble and
+    /// applies the moves made on it.
+    fn set_up(&mut self, puzzle: &mut PuzzleController) -> Result<(), &'static str> {
+        let ty = puzzle.ty();
+        let (scramble, history) = match self.side {
+            NissSide::Normal => (
+                [&self.premoves[..], &self.scramble[..]].concat(),
+                self.moves.clone(),
+            ),
+            NissSide::Inverse => (
+                [invert(ty, &self.moves), invert(ty, &self.scramble)].concat(),
+                invert(ty, &self.premoves),
+            ),
+        };
+        puzzle.scramble_with(&scramble, ScrambleState::Full)?;
+        for twist in history {
+            puzzle.twist_no_collapse(twist)?;
+        }
+        puzzle.skip_twist_animations();
+        self.puzzle_scramble = puzzle.scramble().to_vec();
+        Ok(())
+    }
+}
+
+fn history_twists(puzzle: &PuzzleController) -> Vec<Twist> {
+    puzzle
+        .undo_buffer()
+        .iter()
+        .filter_map(|&entry| entry.twist())
+        .collect()
+}
+
+fn invert(ty: PuzzleTypeEnum, twists: &[Twist]) -> Vec<Twist> {
+    twists
+        .iter()
+        .rev()
+        .map(|&twist| ty.reverse_twist(twist))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_niss() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let parse = |s: &str| ty.parse_twists(s).unwrap();
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle
+            .scramble_with(&parse("R U"), ScrambleState::Full)
+            .unwrap();
+        puzzle.twist(parse("U'")[0]).unwrap();
+
+        let mut session = FmcSession::start(&puzzle).unwrap();
+        session.switch_side(&mut puzzle).unwrap();
+        assert!(session.is_current(&puzzle));
+        assert!(!puzzle.is_solved());
+
+        // R solves the inverse scramble, so R' is a premove.
+        puzzle.twist(parse("R")[0]).unwrap();
+        assert!(puzzle.is_solved());
+        session.sync(&puzzle);
+        assert_eq!(session.premoves(), parse("R'"));
+        assert_eq!(session.solution(), parse("U' R'"));
+
+        session.switch_side(&mut puzzle).unwrap();
+        assert_eq!(session.side(), NissSide::Normal);
+        assert!(puzzle.is_solved());
+        assert_eq!(session.moves(), parse("U'"));
+
+        puzzle.reset();
+        assert!(!session.is_current(&puzzle));
+    }
+}
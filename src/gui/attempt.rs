@@ -0,0 +1,81 @@
+//! Overlay showing the scramble, inspection time, and solve clock of the
+//! current timed attempt.
+
+use itertools::Itertools;
+use std::time::Duration;
+
+use crate::app::App;
+use crate::attempt::Attempt;
+use crate::commands::Command;
+use crate::puzzle::traits::*;
+
+const OVERLAY_MARGIN: f32 = 8.0;
+/// Width of the scramble text.
+const SCRAMBLE_WIDTH: f32 = 400.0;
+
+pub(super) fn build(ctx: &egui::Context, app: &mut App) {
+    let Some(attempt) = app.attempts.current.clone() else {
+        return;
+    };
+
+    egui::Area::new("attempt_overlay")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, OVERLAY_MARGIN))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| match attempt {
+                Attempt::Pending { scramble, .. } => {
+                    let notation = app.puzzle.notation_scheme();
+                    ui.strong(format!("Scramble ({} twists)", scramble.len()));
+                    ui.set_max_width(SCRAMBLE_WIDTH);
+                    ui.label(
+                        egui::RichText::new(
+                            scramble
+                                .iter()
+                                .map(|&twist| notation.twist_to_string(twist))
+                                .join(" "),
+                        )
+                        .monospace(),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("Start inspection").clicked() {
+                            app.event(Command::NewAttempt);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            app.attempts.cancel();
+                        }
+                    });
+                }
+
+                Attempt::Inspecting { start } => {
+                    ui.label("Inspecting");
+                    ui.heading(format_time(start.elapsed()));
+                    ui.small("The timer starts on the first twist");
+                    ctx.request_repaint();
+                }
+
+                Attempt::Solving { .. } => {
+                    ui.label("Solving");
+                    ui.heading(format_time(app.puzzle.solve_clock()));
+                    ctx.request_repaint();
+                }
+
+                Attempt::Done(result) => {
+                    ui.label("Solved");
+                    ui.heading(format_time(result.time));
+                    ui.label(format!("Inspection: {}", format_time(result.inspection)));
+                    ui.label(format!("{} twists", result.twist_count));
+                    ui.horizontal(|ui| {
+                        if ui.button("New attempt").clicked() {
+                            app.event(Command::NewAttempt);
+                        }
+                        if ui.button("Close").clicked() {
+                            app.attempts.cancel();
+                        }
+                    });
+                }
+            });
+        });
+}
+
+fn format_time(t: Duration) -> String {
+    format!("{:.2}s", t.as_secs_f64())
+}
@@ -7,7 +7,7 @@ use strum::IntoEnumIterator;
 
 use crate::app::App;
 use crate::commands::{
-    Command, FilterMode, PuzzleCommand, PARTIAL_SCRAMBLE_MOVE_COUNT_MAX,
+    Command, FilterMode, PuzzleCommand, ScreenDirection, PARTIAL_SCRAMBLE_MOVE_COUNT_MAX,
     PARTIAL_SCRAMBLE_MOVE_COUNT_MIN,
 };
 use crate::gui::components::{
@@ -243,6 +243,7 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
                     "Open..." => Cmd::Open,
                     "Save" => Cmd::Save,
                     "Save as..." => Cmd::SaveAs,
+                    "Export solve bundle..." => Cmd::ExportSolveBundle,
                     "Exit" => Cmd::Exit,
 
                     "Copy .hsc" => Cmd::CopyHscLog,
@@ -251,12 +252,38 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
 
                     "Undo" => Cmd::Undo,
                     "Redo" => Cmd::Redo,
+                    "Cancel queued twists" => Cmd::CancelQueuedTwists,
+                    "Realign solved puzzle" => Cmd::RealignSolved,
                     "Reset" => Cmd::Reset,
+                    "Reset to scramble" => Cmd::ResetToScramble,
 
                     "Scramble partially" => Cmd::ScrambleN(PARTIAL_SCRAMBLE_MOVE_COUNT_MIN),
                     "Scramble fully" => Cmd::ScrambleFull,
+                    "New attempt" => Cmd::NewAttempt,
                     "Toggle blindfold" => Cmd::ToggleBlindfold,
+                    "Toggle step mode" => Cmd::ToggleStepMode,
+                    "Step twist" => Cmd::StepTwist,
+                    "Import MC4D settings..." => Cmd::ImportMc4dSettings,
+                    "Snap view" => Cmd::SnapView,
+                    "Rotate view" => Cmd::RotateView(ScreenDirection::default()),
+                    "Zoom in" => Cmd::ZoomIn,
+                    "Zoom out" => Cmd::ZoomOut,
+                    "Reset zoom" => Cmd::ResetZoom,
+                    "Pan" => Cmd::Pan(ScreenDirection::default()),
+                    "Reset pan" => Cmd::ResetPan,
+                    "Toggle fullscreen" => Cmd::ToggleFullscreen,
                     "New puzzle" => Cmd::NewPuzzle(PuzzleTypeEnum::default()),
+                    "Next puzzle" => Cmd::NextPuzzle,
+                    "Previous puzzle" => Cmd::PreviousPuzzle,
+                    "Swap background puzzle" => Cmd::SwapBackgroundPuzzle,
+                    "Describe state" => Cmd::DescribeState,
+                    "Copy diagram" => Cmd::CopyTextDiagram,
+                    "Copy color diagram" => Cmd::CopyColorDiagram,
+                    "Plugin command" => Cmd::Plugin {
+                        plugin: String::new(),
+                        command: String::new(),
+                    },
+                    "Command sequence" => Cmd::Sequence(vec![]),
                 }
             );
             changed |= r.changed();
@@ -269,6 +296,17 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
                     changed |= r.changed();
                 }
 
+                Cmd::RotateView(direction) | Cmd::Pan(direction) => {
+                    let r = ui.add(FancyComboBox {
+                        combo_box: egui::ComboBox::from_id_source(unique_id!(self.idx)),
+                        selected: direction,
+                        options: ScreenDirection::iter()
+                            .map(|d| (d, Cow::Borrowed(d.into())))
+                            .collect(),
+                    });
+                    changed |= r.changed();
+                }
+
                 Cmd::NewPuzzle(puzzle_type) => {
                     if let Some(Some(ty)) =
                         ui.menu_button(puzzle_type.name(), puzzle_type_menu).inner
@@ -278,6 +316,39 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
                     }
                 }
 
+                Cmd::Plugin { plugin, command } => {
+                    for (text, hint) in [(plugin, "Plugin"), (command, "Command")] {
+                        let r = ui.add(
+                            egui::TextEdit::singleline(text)
+                                .hint_text(hint)
+                                .desired_width(100.0),
+                        );
+                        changed |= r.changed();
+                    }
+                }
+
+                Cmd::Sequence(commands) => {
+                    ui.vertical(|ui| {
+                        let id = ui.id().with("sequence");
+                        let r = ReorderableList::new(id, commands).show(ui, |ui, idx, cmd| {
+                            ui.add(CommandSelectWidget {
+                                cmd,
+
+                                keybind_set: self.keybind_set,
+                                idx,
+
+                                prefs: self.prefs,
+                            })
+                        });
+                        changed |= r.changed();
+
+                        if big_icon_button(ui, "➕", "Add a command to the sequence").clicked() {
+                            commands.push(Cmd::None);
+                            changed = true;
+                        }
+                    });
+                }
+
                 _ => (),
             }
         });
@@ -294,6 +365,7 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
         use PuzzleCommand as Cmd;
 
         let puzzle_type = self.keybind_set.puzzle_type;
+        let desc = puzzle_type.desc();
 
         let mut changed = false;
 
@@ -310,14 +382,33 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                     },
                     "Twist" => Cmd::Twist {
                         axis: self.cmd.axis_mut().cloned().unwrap_or_default(),
-                        direction: self.cmd.direction_mut().cloned().unwrap_or_else(|| {
-                            puzzle_type.twist_directions()[0].name.to_owned()
-                        }),
+                        direction: self
+                            .cmd
+                            .direction_mut()
+                            .cloned()
+                            .unwrap_or_else(|| desc.twist_directions()[0].name.to_owned()),
+                        layers: self.cmd.layers_mut().cloned().unwrap_or_default(),
+                    },
+                    "Opposite twist" => Cmd::OppositeTwist {
+                        axis: self.cmd.axis_mut().cloned().unwrap_or_default(),
+                        direction: self
+                            .cmd
+                            .direction_mut()
+                            .cloned()
+                            .unwrap_or_else(|| desc.twist_directions()[0].name.to_owned()),
+                        opposite_direction: self
+                            .cmd
+                            .opposite_direction_mut()
+                            .cloned()
+                            .unwrap_or_else(|| desc.twist_directions()[0].name.to_owned()),
                         layers: self.cmd.layers_mut().cloned().unwrap_or_default(),
                     },
                     "Recenter" => Cmd::Recenter {
                         axis: self.cmd.axis_mut().cloned().unwrap_or_default(),
                     },
+                    "Mirror" => Cmd::Mirror {
+                        axis: self.cmd.axis_mut().cloned().unwrap_or_default(),
+                    },
 
                     "Filter" => Cmd::Filter {
                         mode: self.cmd.filter_mode_mut().cloned().unwrap_or_default(),
@@ -346,6 +437,7 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                 let r = ui.add(LayerMaskEdit {
                     id: unique_id!(self.idx),
                     layers,
+                    layer_count: puzzle_type.layer_count(),
                 });
                 changed |= r.changed();
             }
@@ -353,7 +445,7 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                 let r = ui.add(FancyComboBox::new_optional(
                     unique_id!(self.idx),
                     axis,
-                    puzzle_type.twist_axes(),
+                    desc.twist_axes(),
                 ));
                 changed |= r.changed();
             }
@@ -361,10 +453,20 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                 let r = ui.add(FancyComboBox::new(
                     unique_id!(self.idx),
                     direction,
-                    puzzle_type.twist_directions(),
+                    desc.twist_directions(),
                 ));
                 changed |= r.changed();
             }
+            if let Some(opposite_direction) = self.cmd.opposite_direction_mut() {
+                let r = ui
+                    .add(FancyComboBox::new(
+                        unique_id!(self.idx),
+                        opposite_direction,
+                        desc.twist_directions(),
+                    ))
+                    .on_hover_explanation("", "Direction to twist the opposite axis.");
+                changed |= r.changed();
+            }
             if let Some(filter_mode) = self.cmd.filter_mode_mut() {
                 let r = ui.add(FancyComboBox {
                     combo_box: egui::ComboBox::from_id_source(unique_id!(self.idx)),
@@ -1,11 +1,15 @@
 use crate::commands::LayerMaskDesc;
 use crate::gui::ext::*;
+use crate::puzzle::LayerMask;
 
 const LAYER_DESCRIPTION_WIDTH: f32 = 50.0;
+const MAX_LAYER: i8 = 32;
 
 pub struct LayerMaskEdit<'a> {
     pub id: egui::Id,
     pub layers: &'a mut LayerMaskDesc,
+    /// Number of layers of the puzzle, used to validate the layer mask.
+    pub layer_count: u8,
 }
 impl<'a> egui::Widget for LayerMaskEdit<'a> {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
@@ -21,10 +25,22 @@ impl<'a> egui::Widget for LayerMaskEdit<'a> {
                     .get_temp(text_id)
                     .unwrap_or_else(|| default_string.clone());
 
-                let r = egui::TextEdit::singleline(&mut text)
-                    .desired_width(LAYER_DESCRIPTION_WIDTH)
-                    .show(ui)
-                    .response;
+                // An empty layer mask means the gripped layers, but a
+                // non-empty one that excludes every layer of the puzzle is
+                // probably a mistake.
+                let mask = self.layers.to_layer_mask(self.layer_count);
+                let is_valid = self.layers.is_default() || mask != LayerMask(0);
+                let mut text_edit =
+                    egui::TextEdit::singleline(&mut text).desired_width(LAYER_DESCRIPTION_WIDTH);
+                if !is_valid {
+                    text_edit = text_edit.text_color(egui::Color32::RED);
+                }
+                let r = text_edit.show(ui).response;
+                let r = if is_valid {
+                    r
+                } else {
+                    r.on_hover_text(format!("This puzzle only has {} layers", self.layer_count))
+                };
 
                 if r.changed() {
                     // Try to parse the new layer mask string.
@@ -53,6 +69,81 @@ impl<'a> egui::Widget for LayerMaskEdit<'a> {
                      • {1..-1,!3} = all except layer 3",
                 );
 
+                ui.menu_button("⬌", |ui| {
+                    let (mut start, mut end) = self.layers.as_range().unwrap_or((1, 1));
+                    ui.horizontal(|ui| {
+                        ui.label("Layers");
+                        let r1 = ui.add(
+                            egui::DragValue::new(&mut start).clamp_range(-MAX_LAYER..=MAX_LAYER),
+                        );
+                        ui.label("to");
+                        let r2 = ui.add(
+                            egui::DragValue::new(&mut end).clamp_range(-MAX_LAYER..=MAX_LAYER),
+                        );
+                        if r1.changed() || r2.changed() {
+                            *self.layers = LayerMaskDesc::from_range(start, end);
+                            text = format!("{{{}}}", self.layers);
+                            changed = true;
+                        }
+                    });
+
+                    ui.separator();
+
+                    let mut mask = self.layers.to_layer_mask(self.layer_count);
+                    let mut mask_changed = false;
+                    ui.horizontal_wrapped(|ui| {
+                        for i in 0..self.layer_count {
+                            let mut is_selected = mask[i];
+                            if ui.checkbox(&mut is_selected, (i + 1).to_string()).changed() {
+                                mask.0 ^= 1 << i;
+                                mask_changed = true;
+                            }
+                        }
+                    });
+                    if mask_changed {
+                        *self.layers = LayerMaskDesc::from_layer_mask(mask);
+                        text = format!("{{{}}}", self.layers);
+                        changed = true;
+                    }
+                    if self.layers.is_default() {
+                        ui.label("No layers selected; uses the gripped layers");
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Whole puzzle").clicked() {
+                            *self.layers = LayerMaskDesc::from_range(1, -1);
+                            text = format!("{{{}}}", self.layers);
+                            changed = true;
+                            ui.close_menu();
+                        }
+                        let r = ui.add_enabled(self.layer_count >= 3, egui::Button::new("Slice"));
+                        let r = r.on_hover_text("All layers except the outer ones");
+                        if r.clicked() {
+                            *self.layers = LayerMaskDesc::from_range(2, -2);
+                            text = format!("{{{}}}", self.layers);
+                            changed = true;
+                            ui.close_menu();
+                        }
+                        let r =
+                            ui.add_enabled(self.layer_count >= 3, egui::Button::new("Opposite"));
+                        let r = r.on_hover_text(
+                            "Outer layers on both sides, \
+                             such as R and L' at once",
+                        );
+                        if r.clicked() {
+                            *self.layers = "1,-1".parse().unwrap_or_default();
+                            text = format!("{{{}}}", self.layers);
+                            changed = true;
+                            ui.close_menu();
+                        }
+                    });
+                })
+                .response
+                .on_hover_text(
+                    "Select a range of layers. \
+                     Negative numbers count from the other side of the puzzle.",
+                );
+
                 ui.data().insert_temp(text_id, text);
             })
             .response;
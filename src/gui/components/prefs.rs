@@ -1,11 +1,16 @@
 use egui::NumExt;
+use strum::IntoEnumIterator;
 
 use crate::app::App;
 use crate::gui::components::{with_reset_button, PresetsUi, WidgetWithReset};
 use crate::gui::ext::*;
 use crate::gui::util::Access;
-use crate::preferences::{OpacityPreferences, DEFAULT_PREFS};
-use crate::puzzle::{traits::*, Face, ProjectionType};
+use crate::preferences::{
+    CursorStyle, FocusLossAnimation, FullscreenMode, GfxPreset, KeyRepeatMode, OpacityPreferences,
+    OverlayCorner, Perspective4D, Projection4D, SpacingOverride, StickerLabelScheme,
+    TwistQueuePreset, DEFAULT_PREFS, MAX_SCALE, MIN_SCALE,
+};
+use crate::puzzle::{Face, ProjectionType};
 use crate::serde_impl::hex_color;
 
 pub struct PrefsUi<'a, T> {
@@ -139,7 +144,7 @@ pub fn build_colors_section(ui: &mut egui::Ui, app: &mut App) {
     };
 
     prefs_ui.ui.strong("Faces");
-    for (i, &face) in puzzle_type.faces().iter().enumerate() {
+    for (i, &face) in puzzle_type.desc().faces().iter().enumerate() {
         prefs_ui.color(face.name, access!([(puzzle_type, Face(i as _))]));
     }
 
@@ -166,6 +171,30 @@ pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
         changed: &mut changed,
     };
 
+    let current_preset = GfxPreset::from_prefs(prefs_ui.current);
+    prefs_ui.ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(unique_id!())
+            .selected_text(current_preset.map_or("Custom".to_string(), |p| p.to_string()))
+            .show_ui(ui, |ui| {
+                for preset in GfxPreset::iter() {
+                    let r = ui.selectable_label(current_preset == Some(preset), preset.to_string());
+                    if r.clicked() {
+                        preset.apply(prefs_ui.current);
+                        *prefs_ui.changed = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_explanation(
+                "",
+                "Sets the FPS limit and MSAA together. \
+                 A preset is picked automatically based \
+                 on the graphics card the first time \
+                 Hyperspeedcube is run.",
+            );
+        ui.label("Preset");
+    });
+
     let speed = prefs_ui.current.fps_limit as f64 / 1000.0; // logarithmic speed
     prefs_ui
         .num("FPS limit", access!(.fps_limit), |dv| {
@@ -188,6 +217,107 @@ pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
             );
     });
 
+    prefs_ui
+        .checkbox("Fog", access!(.fog))
+        .on_hover_explanation(
+            "",
+            "Fades parts of the puzzle farther from \
+             the camera into a fog, which makes the \
+             back of large puzzles recede.",
+        );
+    if prefs_ui.current.fog {
+        prefs_ui.color("Fog color", access!(.fog_color));
+        prefs_ui.num("Fog density", access!(.fog_density), |dv| {
+            dv.fixed_decimals(2).clamp_range(0.0..=5.0_f32).speed(0.01)
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        prefs_ui
+            .checkbox("Custom shader", access!(.custom_shader))
+            .on_hover_explanation(
+                "",
+                "Draws the puzzle using the shader in \
+                 shaders/basic.wgsl next to the preferences \
+                 file, which is created from the built-in \
+                 shader if it doesn't exist. The shader is \
+                 reloaded whenever the file changes.",
+            );
+        if prefs_ui.current.custom_shader {
+            if let Some(error) = &app.custom_shader_error {
+                prefs_ui.ui.label(
+                    egui::RichText::new(error)
+                        .color(egui::Color32::RED)
+                        .monospace(),
+                );
+            }
+        }
+    }
+
+    prefs.needs_save |= changed;
+    if changed {
+        app.request_redraw_puzzle();
+    }
+}
+pub fn build_window_section(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.window,
+        defaults: &DEFAULT_PREFS.window,
+        changed: &mut changed,
+    };
+
+    let current_fullscreen_mode = prefs_ui.current.fullscreen_mode;
+    prefs_ui.ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(unique_id!())
+            .selected_text(current_fullscreen_mode.to_string())
+            .show_ui(ui, |ui| {
+                for mode in FullscreenMode::iter() {
+                    let r = ui.selectable_value(
+                        &mut prefs_ui.current.fullscreen_mode,
+                        mode,
+                        mode.to_string(),
+                    );
+                    *prefs_ui.changed |= r.changed();
+                }
+            })
+            .response
+            .on_hover_explanation(
+                "",
+                "Exclusive fullscreen uses the highest \
+                 resolution and refresh rate of the monitor, \
+                 but may be slower to switch in and out of.",
+            );
+        ui.label("Fullscreen");
+    });
+
+    prefs_ui
+        .num("GUI scale", access!(.gui_scale), |dv| {
+            dv.fixed_decimals(2).clamp_range(0.5..=3.0_f32).speed(0.01)
+        })
+        .on_hover_explanation(
+            "",
+            "Scale of the GUI relative to the scale \
+             factor of the monitor that the window is on.",
+        );
+
+    let is_placement_disabled = cfg!(target_arch = "wasm32");
+    prefs_ui.ui.add_enabled_ui(!is_placement_disabled, |ui| {
+        PrefsUi { ui, ..prefs_ui }
+            .checkbox("Remember window placement", access!(.remember_placement))
+            .on_hover_explanation(
+                "",
+                "When enabled, the window opens with the same \
+                 size, position, monitor, and fullscreen state \
+                 as when it was last closed.",
+            )
+            .on_disabled_hover_text("Window placement is not saved on web.");
+    });
+
     prefs.needs_save |= changed;
     if changed {
         app.request_redraw_puzzle();
@@ -216,6 +346,92 @@ pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
              is only shown when the puzzle has been fully \
              scrambled.",
         );
+    #[cfg(not(target_arch = "wasm32"))]
+    prefs_ui
+        .checkbox("Restore session on startup", access!(.restore_session))
+        .on_hover_explanation(
+            "",
+            "When enabled, the puzzle (including unsaved \
+             changes) and open windows are saved on exit \
+             and restored the next time Hyperspeedcube is \
+             launched.",
+        );
+    prefs_ui
+        .checkbox(
+            "Keep previous puzzle in background",
+            access!(.keep_background_puzzle),
+        )
+        .on_hover_explanation(
+            "",
+            "When enabled, switching to another puzzle keeps \
+             the previous one in the background instead of \
+             discarding it. Use the \"Swap background \
+             puzzle\" command to switch back to it.",
+        );
+    prefs_ui
+        .checkbox(
+            "Remember each puzzle's state",
+            access!(.remember_puzzle_states),
+        )
+        .on_hover_explanation(
+            "",
+            "When enabled, switching to another puzzle type \
+             restores the state, view, and filters that it \
+             was left with, instead of starting fresh.",
+        );
+
+    prefs_ui.ui.separator();
+
+    prefs_ui
+        .checkbox("Pause clock when idle", access!(.auto_pause))
+        .on_hover_explanation(
+            "",
+            "When enabled, the solve clock pauses when \
+             there is no keyboard or mouse input for a \
+             while, and resumes on the next input. Timed \
+             attempts, versus matches, and ranked solves \
+             are never paused.",
+        );
+    if prefs_ui.current.auto_pause {
+        prefs_ui.num("Idle delay", access!(.auto_pause_delay), |dv| {
+            dv.fixed_decimals(0)
+                .clamp_range(1.0..=600.0_f32)
+                .speed(0.5)
+                .suffix("s")
+        });
+    }
+    prefs_ui
+        .checkbox("Pause clock when unfocused", access!(.pause_on_focus_loss))
+        .on_hover_explanation(
+            "",
+            "When enabled, the solve clock pauses when \
+             the window loses focus, and resumes on the \
+             next input.",
+        );
+    let current_focus_loss_animation = prefs_ui.current.focus_loss_animation;
+    prefs_ui.ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(unique_id!())
+            .selected_text(current_focus_loss_animation.to_string())
+            .show_ui(ui, |ui| {
+                for mode in FocusLossAnimation::iter() {
+                    let r = ui.selectable_value(
+                        &mut prefs_ui.current.focus_loss_animation,
+                        mode,
+                        mode.to_string(),
+                    );
+                    *prefs_ui.changed |= r.changed();
+                }
+            })
+            .response
+            .on_hover_explanation(
+                "",
+                "What to do with queued twist animations \
+                 when the window loses focus. Paused \
+                 animations resume when the window is \
+                 focused again.",
+            );
+        ui.label("Twists when unfocused");
+    });
 
     prefs_ui.ui.separator();
 
@@ -247,6 +463,62 @@ pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
 
     prefs_ui.ui.separator();
 
+    prefs_ui.num("View rotation speed", access!(.view_rotation_speed), |dv| {
+        dv.fixed_decimals(0)
+            .clamp_range(1.0..=720.0_f32)
+            .speed(1.0)
+            .suffix("°/s")
+    });
+    prefs_ui
+        .checkbox("Smooth view rotation", access!(.smooth_view_rotation))
+        .on_hover_explanation(
+            "",
+            "When enabled, rotating the view using the \
+             keyboard speeds up and slows down gradually.",
+        );
+
+    prefs_ui.ui.separator();
+
+    let current_key_repeat = prefs_ui.current.key_repeat;
+    prefs_ui.ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(unique_id!())
+            .selected_text(current_key_repeat.to_string())
+            .show_ui(ui, |ui| {
+                for mode in KeyRepeatMode::iter() {
+                    let r = ui.selectable_value(
+                        &mut prefs_ui.current.key_repeat,
+                        mode,
+                        mode.to_string(),
+                    );
+                    *prefs_ui.changed |= r.changed();
+                }
+            })
+            .response
+            .on_hover_explanation(
+                "",
+                "What to do when a twist key is held down. \
+                 The OS key repeat delay and rate vary \
+                 between platforms.",
+            );
+        ui.label("Held twist keys");
+    });
+    if prefs_ui.current.key_repeat == KeyRepeatMode::Delayed {
+        prefs_ui.num("Repeat delay", access!(.key_repeat_delay), |dv| {
+            dv.fixed_decimals(2)
+                .clamp_range(0.05..=2.0_f32)
+                .speed(0.01)
+                .suffix("s")
+        });
+        prefs_ui.num("Repeat rate", access!(.key_repeat_rate), |dv| {
+            dv.fixed_decimals(1)
+                .clamp_range(1.0..=60.0_f32)
+                .speed(0.1)
+                .suffix("/s")
+        });
+    }
+
+    prefs_ui.ui.separator();
+
     prefs_ui.collapsing("Animations", |mut prefs_ui| {
         prefs_ui
             .checkbox("Dynamic twist speed", access!(.dynamic_twist_speed))
@@ -257,10 +529,72 @@ pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
                  moves are complete, the twist speed resets.",
             );
 
+        let current_preset = TwistQueuePreset::from_prefs(prefs_ui.current);
+        prefs_ui.ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source(unique_id!())
+                .selected_text(current_preset.map_or("Custom".to_string(), |p| p.to_string()))
+                .show_ui(ui, |ui| {
+                    for preset in TwistQueuePreset::iter() {
+                        let r =
+                            ui.selectable_label(current_preset == Some(preset), preset.to_string());
+                        if r.clicked() {
+                            preset.apply(prefs_ui.current);
+                            *prefs_ui.changed = true;
+                        }
+                    }
+                })
+                .response
+                .on_hover_explanation(
+                    "",
+                    "How quickly the puzzle catches up when \
+                     many moves are queued up.",
+                );
+            ui.label("Queue catch-up");
+        });
+        prefs_ui
+            .num("Speed-up factor", access!(.twist_speedup_factor), |dv| {
+                dv.fixed_decimals(2).clamp_range(0.0..=5.0_f32).speed(0.01)
+            })
+            .on_hover_explanation(
+                "",
+                "How much faster each queued move is \
+                 animated when dynamic twist speed is \
+                 enabled.",
+            );
+        prefs_ui
+            .num("Min twist duration", access!(.min_twist_duration), |dv| {
+                dv.fixed_decimals(2)
+                    .clamp_range(0.0..=1.0_f32)
+                    .speed(0.01)
+                    .suffix("s")
+            })
+            .on_hover_explanation(
+                "",
+                "Twists that would be animated faster than \
+                 this are completed instantly.",
+            );
+
         let speed = prefs_ui.current.twist_duration.at_least(0.1) / 100.0; // logarithmic speed
         prefs_ui.num("Twist duration", access!(.twist_duration), |dv| {
             dv.fixed_decimals(2).clamp_range(0.0..=5.0_f32).speed(speed)
         });
+        prefs_ui
+            .percent("Per extra layer", access!(.extra_layer_twist_duration))
+            .on_hover_explanation(
+                "",
+                "How much longer twists take for each layer \
+                 moved beyond the first, relative to the \
+                 twist duration.",
+            );
+        prefs_ui
+            .num("Rotation scale", access!(.rotation_duration_scale), |dv| {
+                dv.fixed_decimals(2).clamp_range(0.1..=5.0_f32).speed(0.01)
+            })
+            .on_hover_explanation(
+                "",
+                "Multiplier for the duration of whole-puzzle \
+                 rotations, relative to the twist duration.",
+            );
 
         let speed = prefs_ui.current.other_anim_duration.at_least(0.1) / 100.0; // logarithmic speed
         prefs_ui
@@ -274,6 +608,47 @@ pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
             );
     });
 
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        prefs_ui.ui.separator();
+
+        let mut prefs_ui = PrefsUi {
+            ui: prefs_ui.ui,
+            current: &mut prefs.solves,
+            defaults: &DEFAULT_PREFS.solves,
+            changed: prefs_ui.changed,
+        };
+        prefs_ui
+            .checkbox("Save finished solves", access!(.auto_save))
+            .on_hover_explanation(
+                "",
+                "When enabled, the log file of every solve \
+                 is saved to the solves folder as soon as \
+                 the puzzle is solved, and added to the \
+                 index of solves in that folder.",
+            );
+        let dir_str = match &prefs_ui.current.dir {
+            Some(dir) => dir.display().to_string(),
+            None => "Default".to_string(),
+        };
+        prefs_ui.ui.horizontal(|ui| {
+            let r = crate::gui::components::reset_button(
+                ui,
+                &mut prefs_ui.current.dir,
+                None,
+                "default",
+            );
+            *prefs_ui.changed |= r.clicked();
+            if ui.button("Choose...").clicked() {
+                if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                    prefs_ui.current.dir = Some(dir);
+                    *prefs_ui.changed = true;
+                }
+            }
+            ui.label(format!("Solves folder: {dir_str}"));
+        });
+    }
+
     prefs.needs_save |= changed;
 }
 pub fn build_outlines_section(ui: &mut egui::Ui, app: &mut App) {
@@ -314,6 +689,200 @@ pub fn build_outlines_section(ui: &mut egui::Ui, app: &mut App) {
         app.request_redraw_puzzle();
     }
 }
+pub fn build_overlays_section(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.info,
+        defaults: &DEFAULT_PREFS.info,
+        changed: &mut changed,
+    };
+
+    prefs_ui
+        .checkbox("Show view gizmo", access!(.view_gizmo))
+        .on_hover_explanation(
+            "",
+            "Shows the orientation of the puzzle in \
+             the corner of the puzzle view. Click an \
+             axis to view the puzzle along it.",
+        );
+    prefs_ui
+        .checkbox("Show solved percentage", access!(.solved_percentage))
+        .on_hover_explanation(
+            "",
+            "Shows the fraction of pieces that are in \
+             their solved position and orientation in \
+             the status bar.",
+        );
+    prefs_ui
+        .checkbox("Show piece tooltip", access!(.sticker_tooltip))
+        .on_hover_explanation(
+            "",
+            "Shows the name and location of the piece \
+             under the cursor, and the twists that \
+             clicking it would perform.",
+        );
+
+    prefs_ui.ui.separator();
+
+    prefs_ui.ui.strong("Face labels");
+    prefs_ui
+        .checkbox("Show face labels", access!(.face_labels.show))
+        .on_hover_explanation(
+            "",
+            "Labels each face near its center with \
+             the symbol used in twist notation.",
+        );
+    prefs_ui.num("Font size", access!(.face_labels.font_size), |dv| {
+        dv.fixed_decimals(0).clamp_range(8.0..=48.0_f32).speed(0.1)
+    });
+    prefs_ui.percent("Opacity", access!(.face_labels.opacity));
+    prefs_ui
+        .checkbox("Fade with depth", access!(.face_labels.fade_with_depth))
+        .on_hover_explanation(
+            "",
+            "Makes labels of faces farther from the \
+             camera more transparent.",
+        );
+
+    prefs_ui.ui.separator();
+
+    prefs_ui.ui.strong("Sticker labels");
+    prefs_ui
+        .checkbox("Show sticker labels", access!(.sticker_labels.show))
+        .on_hover_explanation(
+            "",
+            "Labels each sticker with the symbol of \
+             its face or with a custom label, such as \
+             a blindfolded letter scheme. Labels are \
+             hidden in blindfold mode.",
+        );
+    let current_scheme = prefs_ui.current.sticker_labels.scheme;
+    prefs_ui.ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(unique_id!())
+            .selected_text(current_scheme.to_string())
+            .show_ui(ui, |ui| {
+                for scheme in StickerLabelScheme::iter() {
+                    let r = ui.selectable_value(
+                        &mut prefs_ui.current.sticker_labels.scheme,
+                        scheme,
+                        scheme.to_string(),
+                    );
+                    *prefs_ui.changed |= r.changed();
+                }
+            });
+        ui.label("Scheme");
+    });
+    if current_scheme == StickerLabelScheme::Custom {
+        let r = prefs_ui
+            .ui
+            .text_edit_multiline(&mut prefs_ui.current.sticker_labels.custom)
+            .on_hover_explanation(
+                "",
+                "Labels separated by spaces, in the order \
+                 of the puzzle's stickers. Stickers \
+                 without a label use the symbol of their \
+                 face.",
+            );
+        *prefs_ui.changed |= r.changed();
+    }
+    prefs_ui.percent("Size", access!(.sticker_labels.size));
+    prefs_ui.percent("Opacity", access!(.sticker_labels.opacity));
+
+    prefs_ui.ui.separator();
+
+    prefs_ui.ui.strong("Input display");
+    prefs_ui
+        .checkbox("Show input display", access!(.input_display.show))
+        .on_hover_explanation(
+            "",
+            "Shows recently pressed keys and the \
+             commands they triggered, for recording \
+             tutorials and streaming.",
+        );
+    prefs_ui.num("Keys", access!(.input_display.max_keys), |dv| {
+        dv.clamp_range(1..=30).speed(0.05)
+    });
+    prefs_ui.num("Duration", access!(.input_display.duration), |dv| {
+        dv.fixed_decimals(1)
+            .clamp_range(0.5..=30.0_f32)
+            .speed(0.05)
+            .suffix("s")
+    });
+    prefs_ui.num("Font size", access!(.input_display.font_size), |dv| {
+        dv.fixed_decimals(0).clamp_range(8.0..=48.0_f32).speed(0.1)
+    });
+    let current_corner = prefs_ui.current.input_display.corner;
+    prefs_ui.ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(unique_id!())
+            .selected_text(current_corner.to_string())
+            .show_ui(ui, |ui| {
+                for corner in OverlayCorner::iter() {
+                    let r = ui.selectable_value(
+                        &mut prefs_ui.current.input_display.corner,
+                        corner,
+                        corner.to_string(),
+                    );
+                    *prefs_ui.changed |= r.changed();
+                }
+            });
+        ui.label("Position");
+    });
+
+    prefs.needs_save |= changed;
+}
+pub fn build_cursor_section(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.cursor,
+        defaults: &DEFAULT_PREFS.cursor,
+        changed: &mut changed,
+    };
+
+    let current_style = prefs_ui.current.style;
+    prefs_ui.ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(unique_id!())
+            .selected_text(current_style.to_string())
+            .show_ui(ui, |ui| {
+                for style in CursorStyle::iter() {
+                    let r =
+                        ui.selectable_value(&mut prefs_ui.current.style, style, style.to_string());
+                    *prefs_ui.changed |= r.changed();
+                }
+            });
+        ui.label("Style");
+    });
+    if current_style.is_drawn() {
+        prefs_ui.num("Size", access!(.size), |dv| {
+            dv.fixed_decimals(0).clamp_range(4.0..=64.0_f32).speed(0.1)
+        });
+        prefs_ui.color("Color", access!(.color));
+    }
+
+    prefs_ui
+        .checkbox("Hide during twists", access!(.hide_during_twists))
+        .on_hover_explanation(
+            "",
+            "Hides the cursor over the puzzle while \
+             a twist is animating.",
+        );
+    prefs_ui
+        .checkbox("Snap to clicked sticker", access!(.snap_to_sticker))
+        .on_hover_explanation(
+            "",
+            "Moves the cursor to the center of a \
+             sticker when it is clicked, so that \
+             repeated clicks land on the same sticker \
+             even when the view changes.",
+        );
+
+    prefs.needs_save |= changed;
+}
 pub fn build_opacity_section(ui: &mut egui::Ui, app: &mut App) {
     let prefs = &mut app.prefs;
 
@@ -331,6 +900,16 @@ pub fn build_opacity_section(ui: &mut egui::Ui, app: &mut App) {
     prefs_ui.percent("Selected", access!(.selected));
     build_unhide_grip_checkbox(&mut prefs_ui);
 
+    prefs_ui
+        .checkbox("Show ghost", access!(.show_ghost))
+        .on_hover_explanation(
+            "",
+            "When enabled, a faint ghost of the solved \
+             state (or of the target state, if one is set) \
+             is drawn behind the puzzle.",
+        );
+    prefs_ui.percent("Ghost", access!(.ghost));
+
     prefs.needs_save |= changed;
     if changed {
         app.request_redraw_puzzle();
@@ -340,7 +919,7 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
     let puzzle_type = app.puzzle.ty();
     let proj_ty = puzzle_type.projection_type();
     let prefs = &mut app.prefs;
-    let presets = prefs.view_presets(&app.puzzle);
+    let presets = prefs.view_presets(puzzle_type);
 
     let mut changed = false;
 
@@ -405,18 +984,121 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
         prefs_ui.angle("Pitch", access!(.pitch), |dv| dv.clamp_range(-90.0..=90.0));
         prefs_ui.angle("Yaw", access!(.yaw), |dv| dv.clamp_range(-180.0..=180.0));
         prefs_ui.angle("Roll", access!(.roll), |dv| dv.clamp_range(-180.0..=180.0));
+
+        if proj_ty == ProjectionType::_4D {
+            prefs_ui.angle("XW", access!(.angle_xw), |dv| {
+                dv.clamp_range(-180.0..=180.0)
+            });
+            prefs_ui.angle("YW", access!(.angle_yw), |dv| {
+                dv.clamp_range(-180.0..=180.0)
+            });
+            prefs_ui.angle("ZW", access!(.angle_zw), |dv| {
+                dv.clamp_range(-180.0..=180.0)
+            });
+        }
     });
 
     prefs_ui.collapsing("Projection", |mut prefs_ui| {
         let speed = prefs_ui.current.scale / 100.0; // logarithmic speed
         prefs_ui.num("Scale", access!(.scale), |dv| {
-            dv.fixed_decimals(2).clamp_range(0.1..=5.0_f32).speed(speed)
+            dv.fixed_decimals(2)
+                .clamp_range(MIN_SCALE..=MAX_SCALE)
+                .speed(speed)
         });
 
         if proj_ty == ProjectionType::_4D {
+            let current_projection = Projection4D::from_view_prefs(prefs_ui.current);
+            egui::ComboBox::from_label("4D projection")
+                .selected_text(current_projection.map_or("Custom".to_owned(), |p| p.to_string()))
+                .show_ui(prefs_ui.ui, |ui| {
+                    for projection in Projection4D::iter() {
+                        let is_selected = current_projection == Some(projection);
+                        if ui
+                            .selectable_label(is_selected, projection.to_string())
+                            .clicked()
+                        {
+                            let old = prefs_ui.current.clone();
+                            projection.apply(prefs_ui.current);
+                            app.puzzle.animate_from_view_settings(old);
+                            *prefs_ui.changed = true;
+                        }
+                    }
+                })
+                .response
+                .on_hover_explanation(
+                    "",
+                    "Sets the 4D view angle and FOV to show the \
+                     puzzle with a cell, face, edge, or vertex \
+                     facing the 4D camera.",
+                );
+
             prefs_ui.angle("4D FOV", access!(.fov_4d), |dv| {
                 dv.clamp_range(1.0..=120.0).speed(0.5)
             });
+
+            let current_perspective = Perspective4D::from_view_prefs(prefs_ui.current);
+            egui::ComboBox::from_label("4D perspective")
+                .selected_text(current_perspective.map_or("Custom".to_owned(), |p| p.to_string()))
+                .show_ui(prefs_ui.ui, |ui| {
+                    for perspective in Perspective4D::iter() {
+                        let is_selected = current_perspective == Some(perspective);
+                        if ui
+                            .selectable_label(is_selected, perspective.to_string())
+                            .clicked()
+                        {
+                            let old = prefs_ui.current.clone();
+                            perspective.apply(prefs_ui.current);
+                            app.puzzle.animate_from_view_settings(old);
+                            *prefs_ui.changed = true;
+                        }
+                    }
+                })
+                .response
+                .on_hover_explanation(
+                    "",
+                    "Sets the relative sizes of the cells nearest \
+                     and farthest from the 4D camera.",
+                );
+
+            prefs_ui
+                .num("Near cell scale", access!(.near_scale_4d), |dv| {
+                    dv.fixed_decimals(2).clamp_range(0.1..=3.0_f32).speed(0.01)
+                })
+                .on_hover_explanation(
+                    "",
+                    "Scale of the part of the puzzle nearest the 4D \
+                     camera, which is usually the outer cell.",
+                );
+            prefs_ui
+                .num("Far cell scale", access!(.far_scale_4d), |dv| {
+                    dv.fixed_decimals(2).clamp_range(0.1..=3.0_f32).speed(0.01)
+                })
+                .on_hover_explanation(
+                    "",
+                    "Scale of the part of the puzzle farthest from \
+                     the 4D camera, which is usually the inner cell.",
+                );
+            prefs_ui
+                .num("W offset", access!(.w_offset_4d), |dv| {
+                    dv.fixed_decimals(2)
+                        .clamp_range(-0.9..=10.0_f32)
+                        .speed(0.01)
+                })
+                .on_hover_explanation(
+                    "",
+                    "Distance to move the 4D camera back, relative \
+                     to the size of the puzzle. Negative values move \
+                     the camera into the puzzle, unfolding the outer \
+                     cell further.",
+                );
+            prefs_ui
+                .percent("W fade", access!(.w_fade_4d))
+                .on_hover_explanation(
+                    "",
+                    "Makes stickers farther from the 4D camera more \
+                     transparent, which makes the 4D structure of \
+                     the puzzle easier to see.",
+                );
         }
 
         let label = if prefs_ui.current.fov_3d == 120.0 {
@@ -432,12 +1114,42 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
     });
 
     prefs_ui.collapsing("Geometry", |mut prefs_ui| {
+        prefs_ui
+            .checkbox("Interior view", access!(.interior_view))
+            .on_hover_explanation(
+                "",
+                "Places the camera at the center of the \
+                 puzzle, looking outward. Use the 3D FOV \
+                 to control how much is visible. On 4D \
+                 puzzles, this shows the inner cell from \
+                 the inside.",
+            );
         if proj_ty == ProjectionType::_3D {
             prefs_ui.checkbox("Show frontfaces", access!(.show_frontfaces));
             prefs_ui.checkbox("Show backfaces", access!(.show_backfaces));
         }
         if proj_ty == ProjectionType::_4D {
+            prefs_ui
+                .checkbox("Cull 4D backfaces", access!(.cull_4d))
+                .on_hover_explanation(
+                    "",
+                    "Hides stickers that are turned inside-out \
+                     by the 4D projection, which are facing \
+                     away from the 4D camera.",
+                );
             prefs_ui.checkbox("Clip 4D", access!(.clip_4d));
+            if prefs_ui.current.clip_4d {
+                prefs_ui
+                    .num("4D clipping threshold", access!(.clip_threshold_4d), |dv| {
+                        dv.fixed_decimals(2).clamp_range(0.0..=1.0_f32).speed(0.005)
+                    })
+                    .on_hover_explanation(
+                        "",
+                        "Geometry closer to the 4D camera than this is \
+                         clipped. Lower values show more of the geometry \
+                         near the 4D camera.",
+                    );
+            }
         }
 
         prefs_ui.num("Face spacing", access!(.face_spacing), |dv| {
@@ -460,6 +1172,45 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
         prefs_ui.percent("Ambient", access!(.light_ambient));
     });
 
+    ui.collapsing("Per-face spacing", |ui| {
+        let view_prefs = prefs.view(puzzle_type).clone();
+        let overrides = &mut prefs.spacing_overrides[puzzle_type];
+        egui::Grid::new(unique_id!()).show(ui, |ui| {
+            ui.label("");
+            ui.label("Face");
+            ui.label("Sticker");
+            ui.end_row();
+
+            for face in puzzle_type.desc().faces() {
+                let mut enabled = overrides.contains_key(face.symbol);
+                if ui.checkbox(&mut enabled, face.name).changed() {
+                    if enabled {
+                        let new_override = SpacingOverride {
+                            face_spacing: view_prefs.face_spacing,
+                            sticker_spacing: view_prefs.sticker_spacing,
+                        };
+                        overrides.insert(face.symbol.to_owned(), new_override);
+                    } else {
+                        overrides.remove(face.symbol);
+                    }
+                    changed = true;
+                }
+                if let Some(o) = overrides.get_mut(face.symbol) {
+                    for value in [&mut o.face_spacing, &mut o.sticker_spacing] {
+                        let r = ui.add(
+                            egui::DragValue::new(value)
+                                .fixed_decimals(2)
+                                .clamp_range(0.0..=0.9_f32)
+                                .speed(0.005),
+                        );
+                        changed |= r.changed();
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    });
+
     prefs.needs_save |= changed;
     if changed {
         app.request_redraw_puzzle();
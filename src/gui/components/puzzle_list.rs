@@ -1,4 +1,4 @@
-use crate::puzzle::{rubiks_3d, rubiks_4d, PuzzleType, PuzzleTypeEnum};
+use crate::puzzle::{rubiks_3d, rubiks_4d, PuzzleTypeEnum};
 
 pub fn puzzle_type_menu(ui: &mut egui::Ui) -> Option<PuzzleTypeEnum> {
     let mut ret = None;
@@ -2,6 +2,21 @@ use egui::NumExt;
 
 use crate::gui::components::{big_icon_button, BIG_ICON_BUTTON_SIZE};
 
+/// Returns whether the widget with keyboard focus is the reorder handle of a
+/// [`ReorderableList`], in which case the up and down arrow keys move its row.
+pub fn is_reorder_handle_focused(ctx: &egui::Context) -> bool {
+    let focus = ctx.memory().focus();
+    focus.is_some() && ctx.data().get_temp::<egui::Id>(focused_handle_id()) == focus
+}
+fn focused_handle_id() -> egui::Id {
+    unique_id!()
+}
+
+/// List whose rows can be reordered by dragging their handles.
+///
+/// When a handle has keyboard focus, the up and down arrow keys move its row,
+/// Delete removes it, and Enter moves focus into the row to edit it. Escape
+/// from inside a row returns focus to its handle.
 pub struct ReorderableList<'a, T> {
     id: egui::Id,
     list: &'a mut Vec<T>,
@@ -16,6 +31,8 @@ impl<'a, T> ReorderableList<'a, T> {
         mut row_ui: impl FnMut(&mut egui::Ui, usize, &mut T) -> egui::Response,
     ) -> egui::Response {
         let drag_id = self.id.with("drag");
+        let focused_row_id = self.id.with("focused_row");
+        let last_focused_row = ui.data().get_temp::<usize>(focused_row_id);
         let is_anything_being_dragged = ui.memory().is_anything_being_dragged();
         let mut reorder_from: Option<usize> = ui
             .data()
@@ -26,6 +43,7 @@ impl<'a, T> ReorderableList<'a, T> {
         let mut to_delete: Option<usize> = None;
 
         let mut drag_handle = Vec::with_capacity(self.list.len());
+        let mut row_resps = Vec::with_capacity(self.list.len());
 
         let mut changed = false;
         let mut resp = ui
@@ -40,7 +58,9 @@ impl<'a, T> ReorderableList<'a, T> {
                                 to_delete = Some(i);
                             }
 
-                            changed |= row_ui(ui, i, elem).changed();
+                            let row_resp = row_ui(ui, i, elem);
+                            changed |= row_resp.changed();
+                            row_resps.push(row_resp);
                         })
                     });
                 }
@@ -52,6 +72,9 @@ impl<'a, T> ReorderableList<'a, T> {
             ui.output().cursor_icon = egui::CursorIcon::ResizeVertical;
         }
         if let Some(from) = drag_handle.iter().position(|r| r.has_focus()) {
+            let handle_id = drag_handle[from].id;
+            ui.data().insert_temp(focused_handle_id(), handle_id);
+
             // Reorder using keyboard.
             let up = ui.input().num_presses(egui::Key::ArrowUp);
             let down = ui.input().num_presses(egui::Key::ArrowDown);
@@ -62,6 +85,21 @@ impl<'a, T> ReorderableList<'a, T> {
                 drag_handle[to].request_focus();
                 reorder_from = Some(from);
                 reorder_to = Some(to);
+            } else if ui.input().key_pressed(egui::Key::Delete) {
+                // The next row takes this row's place, including its handle's
+                // ID, so focus only needs to move if this is the last row.
+                if from + 1 == self.list.len() && from > 0 {
+                    drag_handle[from - 1].request_focus();
+                }
+                to_delete = Some(from);
+            } else if ui.input().key_pressed(egui::Key::Enter) {
+                row_resps[from].request_focus();
+            }
+        } else if ui.input().key_pressed(egui::Key::Escape) {
+            // egui has already removed focus from the row, so give it to the
+            // row's handle instead.
+            if let Some(handle) = last_focused_row.and_then(|i| drag_handle.get(i)) {
+                handle.request_focus();
             }
         } else if ui.memory().is_anything_being_dragged() {
             // Reorder using mouse.
@@ -100,6 +138,11 @@ impl<'a, T> ReorderableList<'a, T> {
             changed = true;
         }
 
+        match row_resps.iter().position(|r| r.has_focus()) {
+            Some(i) => ui.data().insert_temp::<usize>(focused_row_id, i),
+            None => ui.data().remove::<usize>(focused_row_id),
+        }
+
         match reorder_from {
             Some(from) => ui.data().insert_temp::<usize>(drag_id, from),
             None => ui.data().remove::<usize>(drag_id),
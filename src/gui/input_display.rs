@@ -0,0 +1,95 @@
+//! Overlay showing recently pressed keys and the commands they triggered, for
+//! recording tutorials and streaming.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use instant::Instant;
+
+use crate::app::App;
+
+const OVERLAY_MARGIN: f32 = 8.0;
+
+/// Recently pressed keys, oldest first.
+#[derive(Debug, Default)]
+pub(crate) struct RecentInputs(VecDeque<RecentInput>);
+
+#[derive(Debug)]
+struct RecentInput {
+    key: String,
+    /// Short descriptions of the commands triggered by the key.
+    commands: Vec<String>,
+    time: Instant,
+}
+
+impl RecentInputs {
+    /// Records a pressed key, discarding the oldest keys so that at most
+    /// `max_keys` are kept.
+    pub fn push_key(&mut self, key: String, max_keys: usize) {
+        self.0.push_back(RecentInput {
+            key,
+            commands: vec![],
+            time: Instant::now(),
+        });
+        while self.0.len() > max_keys {
+            self.0.pop_front();
+        }
+    }
+    /// Records a command triggered by the most recently pressed key.
+    pub fn push_command(&mut self, description: String) {
+        if description.is_empty() {
+            return;
+        }
+        if let Some(input) = self.0.back_mut() {
+            input.commands.push(description);
+        }
+    }
+}
+
+pub(super) fn build(ctx: &egui::Context, app: &mut App) {
+    let prefs = app.prefs.info.input_display;
+    if !prefs.show {
+        return;
+    }
+
+    let duration = Duration::from_secs_f32(prefs.duration.max(0.0));
+    let now = Instant::now();
+    let inputs = &mut app.recent_inputs.0;
+    inputs.retain(|input| now.duration_since(input.time) < duration);
+
+    // Repaint when the oldest key expires.
+    let Some(oldest) = inputs.front() else {
+        return;
+    };
+    ctx.request_repaint_after(duration.saturating_sub(now.duration_since(oldest.time)));
+
+    let align = prefs.corner.align();
+    let offset = egui::vec2(
+        -align.x().to_sign() * OVERLAY_MARGIN,
+        -align.y().to_sign() * OVERLAY_MARGIN,
+    );
+    egui::Area::new("input_display")
+        .anchor(align, offset)
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.with_layout(egui::Layout::top_down(align.x()), |ui| {
+                for input in inputs.iter() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(&input.key)
+                                    .strong()
+                                    .size(prefs.font_size),
+                            );
+                            if !input.commands.is_empty() {
+                                ui.label(
+                                    egui::RichText::new(input.commands.join(" "))
+                                        .size(prefs.font_size * 0.75),
+                                );
+                            }
+                        });
+                    });
+                }
+            });
+        });
+}
@@ -5,11 +5,13 @@ use winit::event::{ElementState, ModifiersState, VirtualKeyCode, WindowEvent};
 use super::components::KeybindSetAccessor;
 use super::ext::*;
 use crate::app::App;
-use crate::preferences::{Key, KeyCombo};
+use crate::preferences::{Key, KeyCombo, KeyComboMode};
 
 const KEYBIND_POPUP_SIZE: egui::Vec2 = egui::vec2(300.0, 200.0);
 
 const SCANCODE_EXPLANATION: &str = "Scancodes are based on physical key position, while virtual keycodes depend on the keyboard layout";
+const IGNORE_OTHER_MODS_EXPLANATION: &str = "Whether the key combo also works while other modifiers are held, such as when Shift is used to grip a layer. Key combos that match the held modifiers exactly take priority";
+const KEY_ORDER_EXPLANATION: &str = "Whether the keys of a multi-key combo may be pressed in any order or must be pressed in the order shown, or whether the keys must be tapped on their own (such as a lone modifier key)";
 
 #[derive(Default, Clone)]
 pub(super) struct State {
@@ -25,6 +27,9 @@ pub(super) struct State {
 
     use_vk: bool,
     use_vk_id: Option<egui::Id>,
+
+    mode: KeyComboMode,
+    ignore_other_mods: bool,
 }
 impl State {
     fn update_keybind(&mut self) {
@@ -52,7 +57,10 @@ impl State {
                 .collect();
         }
 
-        self.key_combo = Some(KeyCombo::new(keys, self.mods));
+        self.key_combo = Some(
+            KeyCombo::new(keys, self.mods, self.mode)
+                .with_ignore_other_mods(self.ignore_other_mods),
+        );
     }
     fn set_key(&mut self, sc: Option<KeyMappingCode>, vk: Option<VirtualKeyCode>) {
         self.ordered_pressed_sc.retain(|&key| !key.is_modifier());
@@ -120,6 +128,11 @@ pub(super) fn open<S: KeybindSetAccessor>(
     let use_vk_id = unique_id!().with(S::USE_VK_BY_DEFAULT);
     let use_vk = data.get_temp(use_vk_id).unwrap_or(S::USE_VK_BY_DEFAULT);
 
+    let mode = key_combo.as_ref().map(KeyCombo::mode).unwrap_or_default();
+    let ignore_other_mods = key_combo
+        .as_ref()
+        .map_or(false, KeyCombo::ignore_other_mods);
+
     *popup_state_mut(&mut data) = State {
         callback: Some(Arc::new(move |app, new_key_combo| {
             keybind_set.get_mut(&mut app.prefs)[idx].key = new_key_combo;
@@ -134,6 +147,9 @@ pub(super) fn open<S: KeybindSetAccessor>(
 
         use_vk,
         use_vk_id: Some(use_vk_id),
+
+        mode,
+        ignore_other_mods,
     };
 }
 
@@ -216,6 +232,60 @@ pub(super) fn build(ctx: &egui::Context, app: &mut App) -> Option<egui::Response
                                     }
                                 }
 
+                                let mut mode = popup_state(ctx).mode;
+                                let mut changed = false;
+                                ui.horizontal(|ui| {
+                                    ui.label("Key order:");
+                                    let r = ui.selectable_value(
+                                        &mut mode,
+                                        KeyComboMode::Chord,
+                                        "Any order",
+                                    );
+                                    changed |= r.changed();
+                                    let r = ui.selectable_value(
+                                        &mut mode,
+                                        KeyComboMode::Sequence,
+                                        "In order",
+                                    );
+                                    changed |= r.changed();
+                                    let r =
+                                        ui.selectable_value(&mut mode, KeyComboMode::Tap, "Tap");
+                                    changed |= r.changed();
+                                })
+                                .response
+                                .on_hover_explanation("", KEY_ORDER_EXPLANATION);
+                                if changed {
+                                    let mut data = ctx.data();
+                                    let popup = popup_state_mut(&mut data);
+                                    popup.mode = mode;
+                                    // Keep the keys that were already entered.
+                                    if let Some(key_combo) = &popup.key_combo {
+                                        popup.key_combo = Some(
+                                            KeyCombo::new(
+                                                key_combo.keys().clone(),
+                                                key_combo.clone().mods(),
+                                                mode,
+                                            )
+                                            .with_ignore_other_mods(popup.ignore_other_mods),
+                                        );
+                                    }
+                                }
+
+                                let mut ignore_other_mods = popup_state(ctx).ignore_other_mods;
+                                let r = ui
+                                    .checkbox(&mut ignore_other_mods, "Ignore other modifiers")
+                                    .on_hover_explanation("", IGNORE_OTHER_MODS_EXPLANATION);
+                                if r.changed() {
+                                    let mut data = ctx.data();
+                                    let popup = popup_state_mut(&mut data);
+                                    popup.ignore_other_mods = ignore_other_mods;
+                                    if let Some(key_combo) = popup.key_combo.take() {
+                                        popup.key_combo = Some(
+                                            key_combo.with_ignore_other_mods(ignore_other_mods),
+                                        );
+                                    }
+                                }
+
                                 ui.horizontal_wrapped(|ui| {
                                     ui.spacing_mut().item_spacing.y = ui.spacing().item_spacing.x;
 
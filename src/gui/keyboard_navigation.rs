@@ -0,0 +1,43 @@
+//! Keyboard navigation between widgets.
+//!
+//! egui already moves focus with Tab and Shift+Tab, clicks the focused widget
+//! with Enter or Space, and removes focus with Escape, which returns keyboard
+//! input to the puzzle. This module adds arrow key navigation on top of that.
+//!
+//! In reorderable lists such as presets and keybinds, the up and down arrow
+//! keys instead move the row whose handle has focus. Delete removes that row,
+//! Enter edits it, and Escape from inside the row returns to its handle.
+
+/// Moves focus with the up and down arrow keys, as if they were Shift+Tab and
+/// Tab, while a widget other than a text field has focus. This makes lists
+/// such as combo boxes and presets navigable with the arrow keys.
+///
+/// The left and right arrow keys are left alone so that they can still adjust
+/// a focused slider. `text_edit_focused` is whether a text field had focus on
+/// the last frame, in which case the arrow keys move the text cursor instead.
+/// The up and down arrow keys are also left alone while a reorder handle has
+/// focus so that they can move its row.
+pub fn translate_arrow_keys(
+    ctx: &egui::Context,
+    input: &mut egui::RawInput,
+    text_edit_focused: bool,
+) {
+    if text_edit_focused
+        || ctx.memory().focus().is_none()
+        || crate::gui::components::is_reorder_handle_focused(ctx)
+    {
+        return;
+    }
+    for event in &mut input.events {
+        if let egui::Event::Key { key, modifiers, .. } = event {
+            match key {
+                egui::Key::ArrowDown => *key = egui::Key::Tab,
+                egui::Key::ArrowUp => {
+                    *key = egui::Key::Tab;
+                    modifiers.shift = true;
+                }
+                _ => (),
+            }
+        }
+    }
+}
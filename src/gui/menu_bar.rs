@@ -15,6 +15,12 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
             {
                 command_button(ui, app, "Save", Command::Save);
                 command_button(ui, app, "Save as...", Command::SaveAs);
+                command_button(
+                    ui,
+                    app,
+                    "Export solve bundle...",
+                    Command::ExportSolveBundle,
+                );
                 ui.separator();
             }
             command_button_with_explanation(
@@ -48,8 +54,15 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
             ui.add_enabled_ui(app.puzzle.has_redo(), |ui| {
                 command_button(ui, app, "Redo twist", Command::Redo);
             });
+            ui.add_enabled_ui(app.puzzle.queued_twist_count() > 0, |ui| {
+                command_button(ui, app, "Cancel queued twists", Command::CancelQueuedTwists);
+            });
+            ui.add_enabled_ui(app.puzzle.is_solved_up_to_rotation(), |ui| {
+                command_button(ui, app, "Realign solved puzzle", Command::RealignSolved);
+            });
             ui.separator();
             command_button(ui, app, "Reset puzzle", Command::Reset);
+            command_button(ui, app, "Reset to scramble", Command::ResetToScramble);
         });
 
         ui.menu_button("Scramble", |ui| {
@@ -58,18 +71,53 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
             }
             ui.separator();
             command_button(ui, app, "Full", Command::ScrambleFull);
+            command_button(ui, app, "New attempt", Command::NewAttempt);
+            ui.separator();
+            windows::SCRAMBLE_PREVIEW.menu_button_toggle(ui);
+            windows::ENTER_SCRAMBLE.menu_button_toggle(ui);
+            windows::SCRAMBLE_OPTIONS.menu_button_toggle(ui);
+            ui.separator();
+            windows::PATTERNS.menu_button_toggle(ui);
         });
 
         ui.menu_button("Puzzle", |ui| {
             if let Some(ty) = puzzle_type_menu(ui) {
                 app.event(Command::NewPuzzle(ty));
             }
+            ui.separator();
+            command_button(ui, app, "Next puzzle", Command::NextPuzzle);
+            command_button(ui, app, "Previous puzzle", Command::PreviousPuzzle);
+            ui.add_enabled_ui(app.background_puzzle.is_some(), |ui| {
+                command_button(
+                    ui,
+                    app,
+                    "Swap background puzzle",
+                    Command::SwapBackgroundPuzzle,
+                );
+            });
+            ui.separator();
+            command_button(ui, app, "Copy state description", Command::DescribeState);
+            command_button(ui, app, "Copy diagram", Command::CopyTextDiagram);
+            command_button(ui, app, "Copy color diagram", Command::CopyColorDiagram);
+            windows::PUZZLE_INFO.menu_button_toggle(ui);
         });
 
         ui.menu_button("Settings", |ui| {
             windows::APPEARANCE_SETTINGS.menu_button_toggle(ui);
             windows::INTERACTION_SETTINGS.menu_button_toggle(ui);
             windows::VIEW_SETTINGS.menu_button_toggle(ui);
+            windows::LAYOUTS.menu_button_toggle(ui);
+            #[cfg(not(target_arch = "wasm32"))]
+            command_button(
+                ui,
+                app,
+                "Import MC4D settings...",
+                Command::ImportMc4dSettings,
+            );
+            command_button(ui, app, "Snap view to axes", Command::SnapView);
+            command_button(ui, app, "Reset zoom", Command::ResetZoom);
+            command_button(ui, app, "Reset pan", Command::ResetPan);
+            command_button(ui, app, "Toggle fullscreen", Command::ToggleFullscreen);
             ui.separator();
             windows::GLOBAL_KEYBINDS.menu_button_toggle(ui);
             windows::PUZZLE_KEYBINDS.menu_button_toggle(ui);
@@ -79,15 +127,37 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
         ui.menu_button("Tools", |ui| {
             windows::PIECE_FILTERS.menu_button_toggle(ui);
             windows::PUZZLE_CONTROLS.menu_button_toggle(ui);
+            windows::TWIST_BUTTONS.menu_button_toggle(ui);
             windows::KEYBIND_SETS.menu_button_toggle(ui);
             windows::MODIFIER_KEYS.menu_button_toggle(ui);
+            windows::KEYFRAMES.menu_button_toggle(ui);
+            windows::SOLUTION_TRANSFORMS.menu_button_toggle(ui);
+            windows::FEWEST_MOVES.menu_button_toggle(ui);
+            windows::STATE_EDITOR.menu_button_toggle(ui);
+            windows::SOLVE_STATS.menu_button_toggle(ui);
+            windows::MOVE_HEATMAP.menu_button_toggle(ui);
+            windows::VERSUS.menu_button_toggle(ui);
+            windows::ACHIEVEMENTS.menu_button_toggle(ui);
+            windows::LIFETIME_STATS.menu_button_toggle(ui);
+            windows::PIECE_TRACKING.menu_button_toggle(ui);
+            windows::SEQUENCE_ANALYSIS.menu_button_toggle(ui);
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.separator();
+                windows::PLUGINS.menu_button_toggle(ui);
+                windows::LEADERBOARD.menu_button_toggle(ui);
+                windows::LOG_ANALYSIS.menu_button_toggle(ui);
+            }
         });
 
         ui.menu_button("Help", |ui| {
             windows::KEYBINDS_REFERENCE.menu_button_toggle(ui);
+            windows::KEYBOARD_TESTER.menu_button_toggle(ui);
             ui.separator();
             windows::WELCOME.menu_button_toggle(ui);
             windows::ABOUT.menu_button_toggle(ui);
+            #[cfg(not(target_arch = "wasm32"))]
+            windows::UPDATES.menu_button_toggle(ui);
             #[cfg(debug_assertions)]
             windows::DEBUG.menu_button_toggle(ui);
         });
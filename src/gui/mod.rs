@@ -6,19 +6,32 @@ macro_rules! unique_id {
 
 #[macro_use]
 mod util;
+mod attempt;
 mod components;
 mod ext;
+mod input_display;
 mod key_combo_popup;
+mod keyboard_navigation;
 mod menu_bar;
 mod puzzle_view;
 mod side_bar;
 mod status_bar;
+mod toasts;
+mod versus;
+mod view_gizmo;
 pub(super) mod windows;
 
 use crate::app::App;
+pub(super) use input_display::RecentInputs;
 pub(super) use key_combo_popup::{key_combo_popup_captures_event, key_combo_popup_handle_event};
+pub(super) use keyboard_navigation::translate_arrow_keys;
 
-pub fn build(ctx: &egui::Context, app: &mut App, puzzle_texture_id: egui::TextureId) {
+pub fn build(
+    ctx: &egui::Context,
+    app: &mut App,
+    puzzle_texture_id: egui::TextureId,
+    versus_texture_id: egui::TextureId,
+) {
     egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| menu_bar::build(ui, app));
 
     egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| status_bar::build(ui, app));
@@ -37,8 +50,22 @@ pub fn build(ctx: &egui::Context, app: &mut App, puzzle_texture_id: egui::Textur
                     window.show(ui.ctx(), app);
                 }
             }
-            puzzle_view::build(ui, app, puzzle_texture_id);
+            if app.versus.is_some() {
+                versus::build(ui, app, puzzle_texture_id, versus_texture_id);
+            } else {
+                puzzle_view::build(ui, app, puzzle_texture_id);
+            }
         });
 
+    // Show the changelog when a new version is found on startup.
+    #[cfg(not(target_arch = "wasm32"))]
+    if std::mem::take(&mut app.updates.notification) {
+        windows::UPDATES.set_open(ctx, true);
+    }
+
+    attempt::build(ctx, app);
+    toasts::build(ctx, app);
+    input_display::build(ctx, app);
+
     key_combo_popup::build(ctx, app);
 }
@@ -1,31 +1,28 @@
-use winit::event::ModifiersState;
-
 use crate::app::{App, AppEvent};
+use crate::commands::PuzzleMouseCommand;
+use crate::preferences::CursorStyle;
+use crate::puzzle::*;
 
-// experimental
-const ENABLE_CONTEXT_MENU: bool = false;
+/// Exponential rate at which scrolling zooms the puzzle, per point scrolled.
+const SCROLL_ZOOM_SENSITIVITY: f32 = 0.002;
+/// Minimum font size for sticker labels, in points. Smaller labels are not
+/// drawn.
+const MIN_STICKER_LABEL_FONT_SIZE: f32 = 6.0;
 
-pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureId) {
-    let dpi = ui.ctx().pixels_per_point();
+/// Sticker that the context menu was opened on, along with its twists and the
+/// layers selected in the menu.
+#[derive(Debug, Copy, Clone)]
+struct ContextMenuState {
+    sticker: Sticker,
+    twists: ClickTwists,
+    layers: LayerMask,
+}
 
-    // Round rectangle to pixel boundary for crisp
-    // image.
-    let mut pixels_rect = ui.available_rect_before_wrap();
-    pixels_rect.set_left((dpi * pixels_rect.left()).ceil());
-    pixels_rect.set_bottom((dpi * pixels_rect.bottom()).floor());
-    pixels_rect.set_right((dpi * pixels_rect.right()).floor());
-    pixels_rect.set_top((dpi * pixels_rect.top()).ceil());
+pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureId) {
+    let (texture_size, egui_rect) = available_puzzle_rect(ui);
 
     // Update texture size.
-    app.puzzle_texture_size = (pixels_rect.width() as u32, pixels_rect.height() as u32);
-
-    // Convert back from pixel coordinates to egui
-    // coordinates.
-    let mut egui_rect = pixels_rect;
-    *egui_rect.left_mut() /= dpi;
-    *egui_rect.bottom_mut() /= dpi;
-    *egui_rect.right_mut() /= dpi;
-    *egui_rect.top_mut() /= dpi;
+    app.puzzle_texture_size = texture_size;
 
     let mut r = ui.put(
         egui_rect,
@@ -39,19 +36,46 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
         cgmath::point2(p.x * 2.0 - 1.0, 1.0 - p.y * 2.0)
     });
 
+    if app.prefs.info.sticker_labels.show && !app.prefs.colors.blindfold {
+        draw_sticker_labels(ui, app, egui_rect);
+    }
+    if app.prefs.info.face_labels.show {
+        draw_face_labels(ui, app, egui_rect);
+    }
+    if app.prefs.info.view_gizmo {
+        super::view_gizmo::build(ui.ctx(), app, egui_rect);
+    }
+
     let popup_state_id = egui::Id::new("puzzle_context_menu_state");
-    let mut popup_was_open = ui.data().get_temp(popup_state_id).unwrap_or(false);
-    if popup_was_open || app.pressed_modifiers() == ModifiersState::SHIFT {
-        ui.data().insert_temp(popup_state_id, false);
-        if ENABLE_CONTEXT_MENU {
+    let mut popup_was_open = ui
+        .data()
+        .get_temp::<Option<ContextMenuState>>(popup_state_id)
+        .flatten()
+        .is_some();
+    let context_menu_bound = app.mousebind_command(egui::PointerButton::Secondary)
+        == Some(&PuzzleMouseCommand::ContextMenu);
+    if popup_was_open || context_menu_bound {
+        let mut state = if popup_was_open {
+            ui.data().get_temp(popup_state_id).flatten()
+        } else {
+            // Remember the sticker under the cursor, since the hovered sticker
+            // changes as the cursor moves over the menu.
+            app.puzzle
+                .hovered_sticker()
+                .zip(app.puzzle.hovered_twists())
+                .map(|(sticker, twists)| ContextMenuState {
+                    sticker,
+                    twists,
+                    layers: app.gripped_layers(twists.cw.map(|t| t.layers).unwrap_or_default()),
+                })
+        };
+        ui.data()
+            .insert_temp::<Option<ContextMenuState>>(popup_state_id, None);
+        if let Some(state) = &mut state {
             r = r.context_menu(|ui| {
-                if !popup_was_open && app.puzzle.hovered_sticker().is_some() {
-                    app.event(AppEvent::Click(egui::PointerButton::Secondary));
-                }
-                ui.data().insert_temp(popup_state_id, true);
-                popup_was_open |= true;
-
-                build_puzzle_context_menu(ui, app);
+                popup_was_open = true;
+                build_puzzle_context_menu(ui, app, state);
+                ui.data().insert_temp(popup_state_id, Some(*state));
             });
         }
     }
@@ -66,23 +90,50 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
         egui::PointerButton::Middle,
     ] {
         if r.clicked_by(button) {
-            app.event(AppEvent::Click(button))
+            app.event(AppEvent::Click(button));
+            if app.prefs.cursor.snap_to_sticker {
+                app.cursor_warp = hovered_sticker_center(app, egui_rect);
+            }
         }
     }
 
-    // Submit drag events.
-    if r.dragged() {
+    build_cursor(ui, app, &r);
+
+    // Submit zoom events.
+    if r.hovered() {
+        let scroll = ui.input().scroll_delta.y;
+        if scroll != 0.0 {
+            app.event(AppEvent::Zoom((scroll * SCROLL_ZOOM_SENSITIVITY).exp()));
+        }
+    }
+
+    // Submit drag events. Dragging with the middle mouse button pans the
+    // puzzle instead of rotating it.
+    if r.dragged_by(egui::PointerButton::Middle) {
+        // Convert to alignment units, which span the viewport from -1.0 to
+        // +1.0 with +Y pointing up.
+        let delta = r.drag_delta() / egui_rect.size() * 2.0;
+        app.event(AppEvent::Pan(egui::vec2(delta.x, -delta.y)));
+    } else if r.dragged() {
         app.event(AppEvent::Drag(r.drag_delta() / egui_rect.size().min_elem()))
     }
     if r.drag_released() {
         app.event(AppEvent::DragReleased);
     }
 
+    if app.prefs.info.sticker_tooltip && !r.dragged() {
+        if let Some(sticker) = app.puzzle.hovered_sticker() {
+            egui::popup::show_tooltip_at_pointer(
+                ui.ctx(),
+                egui::Id::new("sticker_tooltip"),
+                |ui| build_sticker_tooltip(ui, app, sticker),
+            );
+        }
+    }
+
     // Show debug info for each sticker.
     #[cfg(debug_assertions)]
     if let Some(sticker) = app.puzzle.hovered_sticker() {
-        use crate::puzzle::traits::*;
-
         let mut s = String::new();
         app.puzzle.displayed().sticker_debug_info(&mut s, sticker);
         if !s.is_empty() {
@@ -95,64 +146,295 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
     }
 }
 
-fn build_puzzle_context_menu(_ui: &mut egui::Ui, _app: &mut App) {
-    // let ty = app.puzzle.ty();
-
-    // let selection = app.puzzle.selection().clone();
-    // let colors: HashSet<_> = selection.iter().map(|&s| ty.info(s).color).collect();
-    // let piece_types: HashSet<_> = selection
-    //     .iter()
-    //     .map(|&s| ty.info(ty.info(s).piece).piece_type)
-    //     .collect();
-
-    // if ui.button("Show all pieces").clicked() {
-    //     app.puzzle.show_stickers(|_| true);
-    // }
-    // ui.separator();
-    // if ui.button("Show only this color").clicked() {
-    //     app.puzzle.hide_stickers(|s| {
-    //         !colors.iter().all(|&c| {
-    //             ty.info(ty.info(s).piece)
-    //                 .stickers
-    //                 .iter()
-    //                 .any(|&s| ty.info(s).color == c)
-    //         })
-    //     });
-    //     ui.close_menu();
-    // }
-    // if ui.button("Show only this piece type").clicked() {
-    //     app.puzzle
-    //         .hide_stickers(|s| !piece_types.contains(&ty.info(ty.info(s).piece).piece_type));
-    //     ui.close_menu();
-    // }
-    // if ui.button("Show only this piece").clicked() {
-    //     app.puzzle.hide_stickers(|_| true);
-    //     for &sticker in &selection {
-    //         app.puzzle
-    //             .show_stickers(|s| ty.info(s).piece == ty.info(sticker).piece);
-    //     }
-    //     ui.close_menu();
-    // }
-    // ui.separator();
-    // if ui.button("Hide this color").clicked() {
-    //     app.puzzle.hide_stickers(|s| {
-    //         ty.info(ty.info(s).piece)
-    //             .stickers
-    //             .iter()
-    //             .any(|&s| colors.contains(&ty.info(s).color))
-    //     });
-    //     ui.close_menu();
-    // }
-    // if ui.button("Hide this piece type").clicked() {
-    //     app.puzzle
-    //         .hide_stickers(|s| piece_types.contains(&ty.info(ty.info(s).piece).piece_type));
-    //     ui.close_menu();
-    // }
-    // if ui.button("Hide this piece").clicked() {
-    //     for &sticker in &selection {
-    //         app.puzzle
-    //             .hide_stickers(|s| ty.info(s).piece == ty.info(sticker).piece);
-    //     }
-    //     ui.close_menu();
-    // }
+/// Shows the name and location of the piece with a sticker, and the twists
+/// that clicking the sticker would perform.
+fn build_sticker_tooltip(ui: &mut egui::Ui, app: &App, sticker: Sticker) {
+    let ty = app.puzzle.ty();
+    let piece = app.puzzle.info(sticker).piece;
+
+    ui.strong(app.puzzle.piece_name(piece));
+    let location = app.puzzle.piece_location_name(piece);
+    if app.puzzle.latest().is_piece_solved(piece) {
+        ui.label(format!("At {location} (solved)"));
+    } else {
+        ui.label(format!("At {location}"));
+    }
+
+    let Some(twists) = app.puzzle.hovered_twists() else {
+        return;
+    };
+    for (button_name, button) in [
+        ("Left click", egui::PointerButton::Primary),
+        ("Right click", egui::PointerButton::Secondary),
+        ("Middle click", egui::PointerButton::Middle),
+    ] {
+        let twist = match app.mousebind_command(button) {
+            Some(PuzzleMouseCommand::TwistCw) => twists.cw,
+            Some(PuzzleMouseCommand::TwistCcw) => twists.ccw,
+            Some(PuzzleMouseCommand::Recenter) => twists.recenter,
+            _ => None,
+        };
+        if let Some(mut twist) = twist {
+            twist.layers = app.gripped_layers(twist.layers);
+            ui.label(format!("{button_name}: {}", ty.twists_to_string(&[twist])));
+        }
+    }
+}
+
+/// Sets the cursor icon over the puzzle and draws the cursor if it is not
+/// drawn by the OS.
+fn build_cursor(ui: &egui::Ui, app: &App, r: &egui::Response) {
+    let Some(hover_pos) = r.hover_pos() else {
+        return;
+    };
+    let prefs = &app.prefs.cursor;
+
+    let is_twisting = app.puzzle.current_twist().is_some();
+    let hidden = prefs.hide_during_twists && is_twisting;
+    ui.output().cursor_icon = match prefs.style {
+        _ if hidden || prefs.style.is_drawn() => egui::CursorIcon::None,
+        CursorStyle::Crosshair => egui::CursorIcon::Crosshair,
+        _ => egui::CursorIcon::Default,
+    };
+    if hidden {
+        return;
+    }
+
+    // Draw an outline so that the cursor is visible on any sticker color.
+    let painter = ui.painter_at(r.rect);
+    let outline = egui::Stroke::new(3.0, egui::Color32::BLACK);
+    let stroke = egui::Stroke::new(1.0, prefs.color);
+    let radius = prefs.size / 2.0;
+    match prefs.style {
+        CursorStyle::Default | CursorStyle::Crosshair => (),
+        CursorStyle::Dot => {
+            painter.circle(hover_pos, radius / 2.0, prefs.color, outline);
+        }
+        CursorStyle::Cross => {
+            let gap = radius / 3.0;
+            for dir in [egui::vec2(1.0, 0.0), egui::vec2(0.0, 1.0)] {
+                for sign in [-1.0, 1.0] {
+                    let line = [
+                        hover_pos + dir * sign * gap,
+                        hover_pos + dir * sign * radius,
+                    ];
+                    painter.line_segment(line, outline);
+                    painter.line_segment(line, stroke);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the center of the hovered sticker in egui coordinates.
+fn hovered_sticker_center(app: &mut App, rect: egui::Rect) -> Option<egui::Pos2> {
+    let p = app.puzzle.hovered_sticker_center()?;
+    let view_prefs = app.puzzle.view_prefs(&app.prefs).into_owned();
+
+    // Use the same scale as the puzzle texture.
+    let min_dimen = f32::min(rect.width(), rect.height());
+    let scale = egui::vec2(min_dimen / rect.width(), min_dimen / rect.height()) * view_prefs.scale;
+
+    // Transform from wgpu to egui coordinates.
+    let x = p.x * scale.x + view_prefs.align_h;
+    let y = p.y * scale.y + view_prefs.align_v;
+    Some(rect.min + egui::vec2((x + 1.0) / 2.0, (1.0 - y) / 2.0) * rect.size())
+}
+
+/// Returns the size in pixels of the texture to fill the available space, along
+/// with the rectangle to draw it in.
+pub(super) fn available_puzzle_rect(ui: &egui::Ui) -> ((u32, u32), egui::Rect) {
+    let dpi = ui.ctx().pixels_per_point();
+
+    // Round rectangle to pixel boundary for crisp
+    // image.
+    let mut pixels_rect = ui.available_rect_before_wrap();
+    pixels_rect.set_left((dpi * pixels_rect.left()).ceil());
+    pixels_rect.set_bottom((dpi * pixels_rect.bottom()).floor());
+    pixels_rect.set_right((dpi * pixels_rect.right()).floor());
+    pixels_rect.set_top((dpi * pixels_rect.top()).ceil());
+
+    let texture_size = (pixels_rect.width() as u32, pixels_rect.height() as u32);
+
+    // Convert back from pixel coordinates to egui
+    // coordinates.
+    let mut egui_rect = pixels_rect;
+    *egui_rect.left_mut() /= dpi;
+    *egui_rect.bottom_mut() /= dpi;
+    *egui_rect.right_mut() /= dpi;
+    *egui_rect.top_mut() /= dpi;
+
+    (texture_size, egui_rect)
+}
+
+fn draw_face_labels(ui: &egui::Ui, app: &mut App, rect: egui::Rect) {
+    let prefs = app.prefs.info.face_labels;
+    let view_prefs = app.puzzle.view_prefs(&app.prefs).into_owned();
+    let desc = app.puzzle.ty().desc();
+    let labels = app.puzzle.face_label_positions();
+
+    // Use the same scale as the puzzle texture.
+    let min_dimen = f32::min(rect.width(), rect.height());
+    let scale = egui::vec2(min_dimen / rect.width(), min_dimen / rect.height()) * view_prefs.scale;
+
+    let (min_z, max_z) = labels
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), (_, p)| {
+            (lo.min(p.z), hi.max(p.z))
+        });
+
+    let painter = ui.painter_at(rect);
+    let font_id = egui::FontId::proportional(prefs.font_size);
+    for (face, p) in labels {
+        let mut opacity = prefs.opacity;
+        if prefs.fade_with_depth && max_z > min_z {
+            // Larger Z is closer to the camera. Labels in the back are drawn
+            // at a quarter of the opacity of labels in the front.
+            opacity *= 0.25 + 0.75 * (p.z - min_z) / (max_z - min_z);
+        }
+
+        // Transform from wgpu to egui coordinates.
+        let x = p.x * scale.x + view_prefs.align_h;
+        let y = p.y * scale.y + view_prefs.align_v;
+        let pos = rect.min + egui::vec2((x + 1.0) / 2.0, (1.0 - y) / 2.0) * rect.size();
+
+        let galley = painter.layout_no_wrap(
+            desc.info(face).symbol.to_string(),
+            font_id.clone(),
+            egui::Color32::WHITE.linear_multiply(opacity),
+        );
+        let text_rect =
+            egui::Align2::CENTER_CENTER.anchor_rect(egui::Rect::from_min_size(pos, galley.size()));
+        painter.rect_filled(
+            text_rect.expand(2.0),
+            2.0,
+            egui::Color32::BLACK.linear_multiply(opacity * 0.5),
+        );
+        painter.galley(text_rect.min, galley);
+    }
+}
+
+fn draw_sticker_labels(ui: &egui::Ui, app: &mut App, rect: egui::Rect) {
+    let view_prefs = app.puzzle.view_prefs(&app.prefs).into_owned();
+    let prefs = &app.prefs.info.sticker_labels;
+    let face_colors = app.prefs.colors.face_colors_list(app.puzzle.ty());
+
+    // Use the same scale as the puzzle texture.
+    let min_dimen = f32::min(rect.width(), rect.height());
+    let scale = egui::vec2(min_dimen / rect.width(), min_dimen / rect.height()) * view_prefs.scale;
+    // Number of points per unit of distance in wgpu coordinates.
+    let points_per_unit = min_dimen * view_prefs.scale / 2.0;
+
+    let painter = ui.painter_at(rect);
+    for (sticker, p, size) in app.puzzle.sticker_label_positions() {
+        let font_size = size * points_per_unit * prefs.size;
+        if font_size < MIN_STICKER_LABEL_FONT_SIZE {
+            continue;
+        }
+
+        let piece = app.puzzle.info(sticker).piece;
+        let opacity = prefs.opacity
+            * app
+                .puzzle
+                .visual_piece_state(piece)
+                .opacity(&app.prefs.opacity);
+        let face = app.puzzle.sticker_color(sticker);
+        let label = prefs.label(sticker.0 as usize, app.puzzle.info(face).symbol);
+
+        // Use black or white, whichever contrasts more with the sticker.
+        let sticker_color = egui::Rgba::from(face_colors[face.0 as usize]);
+        let text_color = if sticker_color.intensity() > 0.5 {
+            egui::Color32::BLACK
+        } else {
+            egui::Color32::WHITE
+        };
+
+        // Transform from wgpu to egui coordinates.
+        let x = p.x * scale.x + view_prefs.align_h;
+        let y = p.y * scale.y + view_prefs.align_v;
+        let pos = rect.min + egui::vec2((x + 1.0) / 2.0, (1.0 - y) / 2.0) * rect.size();
+
+        painter.text(
+            pos,
+            egui::Align2::CENTER_CENTER,
+            label,
+            egui::FontId::proportional(font_size),
+            text_color.linear_multiply(opacity),
+        );
+    }
+}
+
+fn build_puzzle_context_menu(ui: &mut egui::Ui, app: &mut App, state: &mut ContextMenuState) {
+    let desc = app.puzzle.ty().desc();
+    let piece = desc.info(state.sticker).piece;
+    let piece_type = desc.info(piece).piece_type;
+
+    ui.horizontal(|ui| {
+        ui.label("Layers:");
+        for i in 0..desc.layer_count() {
+            let mut is_sel = state.layers[i];
+            if ui
+                .selectable_value(&mut is_sel, true, format!("{}", i + 1))
+                .changed()
+            {
+                state.layers.0 ^= 1 << i;
+            }
+        }
+    });
+
+    let twist_with_layers = |twist: Option<Twist>| {
+        twist
+            .filter(|_| state.layers != LayerMask(0))
+            .map(|t| Twist {
+                layers: state.layers,
+                ..t
+            })
+    };
+    for (label, twist) in [
+        ("Twist clockwise", twist_with_layers(state.twists.cw)),
+        (
+            "Twist counterclockwise",
+            twist_with_layers(state.twists.ccw),
+        ),
+        ("Recenter on this face", state.twists.recenter),
+    ] {
+        if ui
+            .add_enabled(twist.is_some(), egui::Button::new(label))
+            .clicked()
+        {
+            if let Some(twist) = twist {
+                app.event(twist);
+            }
+            ui.close_menu();
+        }
+    }
+
+    ui.separator();
+
+    if ui.button("Hide this piece").clicked() {
+        app.puzzle.visible_pieces_mut().set(piece.0 as usize, false);
+        app.request_redraw_puzzle();
+        ui.close_menu();
+    }
+    if ui.button("Hide this piece type").clicked() {
+        for (i, info) in desc.pieces().iter().enumerate() {
+            if info.piece_type == piece_type {
+                app.puzzle.visible_pieces_mut().set(i, false);
+            }
+        }
+        app.request_redraw_puzzle();
+        ui.close_menu();
+    }
+
+    ui.separator();
+
+    if ui.button("Track this piece").clicked() {
+        let mut tracked = app.puzzle.tracked_pieces().to_vec();
+        if !tracked.contains(&piece) {
+            tracked.push(piece);
+        }
+        app.puzzle.set_tracked_pieces(tracked);
+        app.request_redraw_puzzle();
+        ui.close_menu();
+    }
 }
@@ -7,6 +7,7 @@ use crate::app::App;
 use crate::commands::Command;
 use crate::preferences::Key;
 use crate::puzzle::TwistMetric;
+use crate::worker::TaskProgress;
 
 pub fn build(ui: &mut egui::Ui, app: &mut App) {
     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -14,9 +15,37 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
         bld_toggle(ui, app);
         ui.separator();
 
+        step_toggle(ui, app);
+        ui.separator();
+
         twist_count(ui, app);
         ui.separator();
 
+        if app.prefs.info.solved_percentage {
+            solved_percentage(ui, app);
+            ui.separator();
+        }
+
+        if app.puzzle.is_solved_up_to_rotation() {
+            solved_up_to_rotation(ui, app);
+            ui.separator();
+        }
+
+        if !app.prefs.move_goals[app.puzzle.ty()].is_empty() {
+            move_goal(ui, app);
+            ui.separator();
+        }
+
+        if app.puzzle.queued_twist_count() > 0 {
+            queued_twists(ui, app);
+            ui.separator();
+        }
+
+        if app.solve_clock_paused {
+            paused_indicator(ui);
+            ui.separator();
+        }
+
         // Left-aligned segments
         ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
             if app.prefs.info.modifier_toggles {
@@ -24,6 +53,11 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
                 ui.separator();
             }
 
+            if let Some(progress) = app.worker.progress() {
+                task_progress(ui, &progress);
+                ui.separator();
+            }
+
             ui.label(app.status_msg());
         });
     });
@@ -79,6 +113,116 @@ fn bld_toggle(ui: &mut egui::Ui, app: &mut App) {
     }
 }
 
+fn step_toggle(ui: &mut egui::Ui, app: &mut App) {
+    let r = ui
+        .selectable_label(app.puzzle.is_step_mode(), "STEP")
+        .on_hover_explanation(
+            "Step mode",
+            "Twists only animate when stepped through \
+             manually. Press space to advance the current \
+             twist by 10%.",
+        );
+    if r.clicked() {
+        app.event(Command::ToggleStepMode);
+    }
+}
+
+fn queued_twists(ui: &mut egui::Ui, app: &mut App) {
+    let r = ui
+        .add(
+            egui::Label::new(format!("⏳ {}", app.puzzle.queued_twist_count()))
+                .sense(egui::Sense::click()),
+        )
+        .on_hover_explanation("Queued twists", "Click to cancel queued twists");
+    if r.clicked() {
+        app.event(Command::CancelQueuedTwists);
+    }
+}
+
+fn paused_indicator(ui: &mut egui::Ui) {
+    ui.strong("⏸ Paused").on_hover_explanation(
+        "Solve clock paused",
+        "The solve clock was paused because of inactivity. \
+         It resumes on the next input.",
+    );
+}
+
+fn task_progress(ui: &mut egui::Ui, progress: &TaskProgress) {
+    ui.add(egui::Spinner::new());
+    match progress.total {
+        Some(total) if total > 0 => {
+            let fraction = progress.done as f32 / total as f32;
+            ui.add(
+                egui::ProgressBar::new(fraction)
+                    .desired_width(150.0)
+                    .text(progress.description.as_str()),
+            );
+        }
+        _ => {
+            ui.label(progress.description.as_str());
+        }
+    }
+}
+
+fn solved_percentage(ui: &mut egui::Ui, app: &App) {
+    ui.label(format!(
+        "{:.0}% solved",
+        app.puzzle.solved_fraction() * 100.0
+    ))
+    .on_hover_explanation(
+        "Solved pieces",
+        "Fraction of pieces that are in their solved \
+             position and orientation",
+    );
+}
+
+fn solved_up_to_rotation(ui: &mut egui::Ui, app: &mut App) {
+    let r = ui
+        .add(egui::Label::new("🔄 Solved up to rotation").sense(egui::Sense::click()))
+        .on_hover_explanation(
+            "Solved up to rotation",
+            "Every face is one color, but the puzzle is not \
+             in its original orientation. Click to rotate it back.",
+        );
+    if r.clicked() {
+        app.event(Command::RealignSolved);
+    }
+}
+
+fn move_goal(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let metric = app.prefs.info.metric;
+    let budget = app.prefs.move_goals[puzzle_type].remaining(
+        &app.puzzle,
+        &app.prefs.phases[puzzle_type],
+        metric,
+    );
+
+    let remaining_text = |remaining: isize| {
+        if remaining < 0 {
+            egui::RichText::new(format!("{} over", -remaining)).color(egui::Color32::RED)
+        } else {
+            egui::RichText::new(format!("{remaining} left"))
+        }
+    };
+
+    // Right-to-left layout, so the phase budget is added first.
+    if let Some((phase, remaining)) = budget.phase {
+        ui.label(remaining_text(remaining)).on_hover_explanation(
+            "Phase move budget",
+            &format!("Moves left in {phase} ({metric})"),
+        );
+        ui.label(format!("{phase}:"));
+    }
+    if let Some(remaining) = budget.total {
+        ui.label(remaining_text(remaining)).on_hover_explanation(
+            "Move budget",
+            &format!("Moves left in the solve ({metric})"),
+        );
+    }
+    ui.label("🎯");
+}
+
 fn twist_count(ui: &mut egui::Ui, app: &mut App) {
     let mut changed = false;
 
@@ -0,0 +1,40 @@
+//! Notifications shown in the corner of the screen when an achievement is
+//! unlocked.
+
+use std::time::Duration;
+
+use instant::Instant;
+
+use crate::app::App;
+
+/// How long each toast is shown.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const TOAST_MARGIN: f32 = 8.0;
+
+pub(super) fn build(ctx: &egui::Context, app: &mut App) {
+    let now = Instant::now();
+    app.achievement_toasts
+        .retain(|(_, unlocked)| now.duration_since(*unlocked) < TOAST_DURATION);
+
+    // Repaint when the oldest toast expires.
+    let Some((_, oldest)) = app.achievement_toasts.first() else {
+        return;
+    };
+    ctx.request_repaint_after(TOAST_DURATION.saturating_sub(now.duration_since(*oldest)));
+
+    egui::Area::new("achievement_toasts")
+        .anchor(
+            egui::Align2::RIGHT_TOP,
+            egui::vec2(-TOAST_MARGIN, TOAST_MARGIN),
+        )
+        .interactable(false)
+        .show(ctx, |ui| {
+            for (achievement, _) in &app.achievement_toasts {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.strong("🏆 Achievement unlocked");
+                    ui.label(achievement.to_string());
+                    ui.small(achievement.description());
+                });
+            }
+        });
+}
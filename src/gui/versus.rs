@@ -0,0 +1,68 @@
+//! Split-screen view of both puzzles in versus mode.
+
+use crate::app::App;
+
+use super::puzzle_view;
+
+const LABEL_MARGIN: f32 = 8.0;
+const LABEL_FONT_SIZE: f32 = 20.0;
+
+pub(super) fn build(
+    ui: &mut egui::Ui,
+    app: &mut App,
+    puzzle_texture_id: egui::TextureId,
+    versus_texture_id: egui::TextureId,
+) {
+    ui.columns(2, |columns| {
+        let rect = columns[0].available_rect_before_wrap();
+        puzzle_view::build(&mut columns[0], app, puzzle_texture_id);
+        draw_player_label(&columns[0], app, 0, rect);
+
+        let (texture_size, rect) = puzzle_view::available_puzzle_rect(&columns[1]);
+        if let Some(versus) = &mut app.versus {
+            versus.texture_size = texture_size;
+        }
+        columns[1].put(rect, egui::Image::new(versus_texture_id, rect.size()));
+        draw_player_label(&columns[1], app, 1, rect);
+    });
+
+    // Keep the race timer running.
+    if let Some(versus) = &app.versus {
+        if versus.start.is_some() && !versus.is_finished() {
+            ui.ctx().request_repaint();
+        }
+    }
+}
+
+fn draw_player_label(ui: &egui::Ui, app: &App, player: usize, rect: egui::Rect) {
+    let Some(versus) = &app.versus else {
+        return;
+    };
+
+    let mut text = format!("Player {}", player + 1);
+    match (versus.finish_times[player], versus.elapsed()) {
+        (Some(t), _) => {
+            text += &format!(" — {:.2}s", t.as_secs_f64());
+            if versus.winner() == Some(player) {
+                text += " 🏆";
+            }
+        }
+        (None, Some(t)) => text += &format!(" — {:.2}s", t.as_secs_f64()),
+        (None, None) => (),
+    }
+    if versus.finish_times[player].is_none() {
+        let puzzle = match player {
+            0 => &app.puzzle,
+            _ => &versus.puzzle,
+        };
+        text += &format!(" — {:.0}% solved", puzzle.solved_fraction() * 100.0);
+    }
+
+    ui.painter().text(
+        rect.left_top() + egui::Vec2::splat(LABEL_MARGIN),
+        egui::Align2::LEFT_TOP,
+        text,
+        egui::FontId::proportional(LABEL_FONT_SIZE),
+        ui.visuals().strong_text_color(),
+    );
+}
@@ -0,0 +1,199 @@
+//! Widget in the corner of the puzzle view showing the orientation of the
+//! puzzle, which can be clicked to view the puzzle along an axis.
+
+use cgmath::{InnerSpace, Vector3};
+
+use crate::app::App;
+use crate::puzzle::*;
+
+const GIZMO_SIZE: f32 = 72.0;
+const GIZMO_MARGIN: f32 = 8.0;
+const AXIS_END_RADIUS: f32 = 7.0;
+
+/// Axis shown by the gizmo, along with the pitch and yaw that make it point
+/// toward the camera.
+struct GizmoAxis {
+    label: &'static str,
+    vector: Vector3<f32>,
+    color: egui::Color32,
+    pitch: f32,
+    yaw: f32,
+}
+
+const X_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 60, 60);
+const Y_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 200, 60);
+const Z_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 120, 240);
+
+const AXES: [GizmoAxis; 6] = [
+    GizmoAxis {
+        label: "X",
+        vector: Vector3::new(1.0, 0.0, 0.0),
+        color: X_COLOR,
+        pitch: 0.0,
+        yaw: -90.0,
+    },
+    GizmoAxis {
+        label: "-X",
+        vector: Vector3::new(-1.0, 0.0, 0.0),
+        color: X_COLOR,
+        pitch: 0.0,
+        yaw: 90.0,
+    },
+    GizmoAxis {
+        label: "Y",
+        vector: Vector3::new(0.0, 1.0, 0.0),
+        color: Y_COLOR,
+        pitch: 90.0,
+        yaw: 0.0,
+    },
+    GizmoAxis {
+        label: "-Y",
+        vector: Vector3::new(0.0, -1.0, 0.0),
+        color: Y_COLOR,
+        pitch: -90.0,
+        yaw: 0.0,
+    },
+    GizmoAxis {
+        label: "Z",
+        vector: Vector3::new(0.0, 0.0, 1.0),
+        color: Z_COLOR,
+        pitch: 0.0,
+        yaw: 0.0,
+    },
+    GizmoAxis {
+        label: "-Z",
+        vector: Vector3::new(0.0, 0.0, -1.0),
+        color: Z_COLOR,
+        pitch: 0.0,
+        yaw: 180.0,
+    },
+];
+
+pub(super) fn build(ctx: &egui::Context, app: &mut App, puzzle_rect: egui::Rect) {
+    let Some(view_transform) = app.puzzle.view_transform() else {
+        return;
+    };
+
+    let show_4d_indicator = app.puzzle.ty().projection_type() == ProjectionType::_4D;
+    let mut height = GIZMO_SIZE;
+    if show_4d_indicator {
+        height += AXIS_END_RADIUS * 2.0 + ctx.style().spacing.item_spacing.y;
+    }
+    let pos = puzzle_rect.left_bottom() + egui::vec2(GIZMO_MARGIN, -GIZMO_MARGIN - height);
+
+    // Use an area so that clicks on the gizmo don't reach the puzzle.
+    egui::Area::new("view_gizmo")
+        .fixed_pos(pos)
+        .show(ctx, |ui| {
+            let (rect, _) =
+                ui.allocate_exact_size(egui::Vec2::splat(GIZMO_SIZE), egui::Sense::hover());
+            let center = rect.center();
+            let length = GIZMO_SIZE / 2.0 - AXIS_END_RADIUS;
+
+            // Draw axes from back to front.
+            let mut ends = AXES
+                .iter()
+                .map(|axis| ((view_transform * axis.vector).normalize(), axis))
+                .collect::<Vec<_>>();
+            ends.sort_by(|(a, _), (b, _)| a.z.total_cmp(&b.z));
+
+            for (v, axis) in ends {
+                // Flip the Y axis, which points up in puzzle space but down on
+                // the screen.
+                let end = center + egui::vec2(v.x, -v.y) * length;
+                let is_positive = !axis.label.starts_with('-');
+
+                let r = ui
+                    .interact(
+                        egui::Rect::from_center_size(end, egui::Vec2::splat(AXIS_END_RADIUS * 2.0)),
+                        ui.id().with(axis.label),
+                        egui::Sense::click(),
+                    )
+                    .on_hover_text(format!("View along {}", axis.label));
+                let color = if r.hovered() {
+                    axis.color.linear_multiply(1.5)
+                } else {
+                    axis.color
+                };
+
+                let painter = ui.painter();
+                if is_positive {
+                    painter.line_segment([center, end], egui::Stroke::new(2.0, color));
+                    painter.circle_filled(end, AXIS_END_RADIUS, color);
+                    painter.text(
+                        end,
+                        egui::Align2::CENTER_CENTER,
+                        axis.label,
+                        egui::FontId::proportional(AXIS_END_RADIUS * 1.6),
+                        egui::Color32::BLACK,
+                    );
+                } else {
+                    painter.circle(
+                        end,
+                        AXIS_END_RADIUS * 0.7,
+                        color.linear_multiply(0.3),
+                        egui::Stroke::new(1.0, color),
+                    );
+                }
+
+                if r.clicked() {
+                    snap_view(app, axis.pitch, axis.yaw);
+                }
+            }
+
+            if show_4d_indicator {
+                build_4d_indicator(ui, app);
+            }
+        });
+}
+
+/// Animates the view to the given pitch and yaw, with no roll.
+fn snap_view(app: &mut App, pitch: f32, yaw: f32) {
+    let view_prefs = app.prefs.view_mut(app.puzzle.ty());
+    let old = view_prefs.clone();
+    view_prefs.pitch = pitch;
+    view_prefs.yaw = yaw;
+    view_prefs.roll = 0.0;
+    app.puzzle.animate_from_view_settings(old);
+    app.prefs.needs_save = true;
+}
+
+/// Shows which cell of a 4D puzzle is currently in the center of the view,
+/// which changes when the whole puzzle is rotated in 4D.
+fn build_4d_indicator(ui: &mut egui::Ui, app: &App) {
+    let ty = app.puzzle.ty();
+    let Some(face) = centered_face(app.puzzle.displayed()) else {
+        return;
+    };
+    let face_color = app.prefs.colors.face_colors_list(ty)[face.0 as usize];
+
+    ui.horizontal(|ui| {
+        let (rect, r) = ui.allocate_exact_size(
+            egui::Vec2::splat(AXIS_END_RADIUS * 2.0),
+            egui::Sense::hover(),
+        );
+        ui.painter()
+            .circle_filled(rect.center(), AXIS_END_RADIUS, face_color);
+        ui.label("W");
+        r.on_hover_text(format!(
+            "{} cell is in the center",
+            app.puzzle.info(face).name
+        ));
+    });
+}
+
+/// Returns the face whose color covers most of the center cell, or `None` if
+/// the puzzle has no center cell.
+fn centered_face(state: &Puzzle) -> Option<Face> {
+    // The center cell of a 4D puzzle is the "in" face.
+    let inner = state.faces().iter().position(|f| f.symbol == "I")?;
+
+    let mut counts = vec![0; state.faces().len()];
+    for (sticker, &home) in state.permutation().as_slice().iter().enumerate() {
+        if state.info(home).color.0 as usize == inner {
+            counts[state.stickers()[sticker].color.0 as usize] += 1;
+        }
+    }
+    let (i, _) = counts.iter().enumerate().max_by_key(|&(_, &n)| n)?;
+    Some(Face(i as _))
+}
@@ -0,0 +1,35 @@
+use strum::IntoEnumIterator;
+
+use super::Window;
+use crate::achievements::Achievement;
+use crate::app::App;
+
+pub(crate) const ACHIEVEMENTS: Window = Window {
+    name: "Achievements",
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        for achievement in Achievement::iter() {
+            match app.achievements.unlocked.get(&achievement) {
+                Some(date) => {
+                    ui.label("✔").on_hover_text(format!("Unlocked {date}"));
+                    ui.strong(achievement.to_string());
+                }
+                None => {
+                    ui.label("🔒").on_hover_text("Locked");
+                    ui.label(achievement.to_string());
+                }
+            }
+            ui.label(achievement.description());
+            ui.end_row();
+        }
+    });
+
+    ui.separator();
+    let unlocked = app.achievements.unlocked.len();
+    let total = Achievement::iter().count();
+    ui.label(format!("{unlocked}/{total} unlocked"));
+}
@@ -0,0 +1,62 @@
+use super::{Location, Window, ENTER_SCRAMBLE_WINDOW_WIDTH};
+use crate::app::{App, AppEvent};
+use crate::puzzle::*;
+
+pub(crate) const ENTER_SCRAMBLE: Window = Window {
+    name: "Enter scramble",
+    location: Location::Centered,
+    fixed_width: Some(ENTER_SCRAMBLE_WINDOW_WIDTH),
+    build,
+    cleanup,
+    ..Window::DEFAULT
+};
+
+fn text_id() -> egui::Id {
+    unique_id!()
+}
+
+fn cleanup(ctx: &egui::Context, _app: &mut App) {
+    ctx.data().remove::<String>(text_id());
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+
+    let mut text: String = ui.data().get_temp(text_id()).unwrap_or_default();
+
+    ui.label(format!(
+        "Paste a scramble for the {} in standard notation.",
+        puzzle_type.name(),
+    ));
+    ui.add(
+        egui::TextEdit::multiline(&mut text)
+            .code_editor()
+            .desired_width(f32::INFINITY),
+    );
+
+    let parsed = puzzle_type.parse_twists(&text);
+
+    let mut applied = false;
+    ui.horizontal(|ui| match &parsed {
+        Ok(twists) => {
+            let r = ui.add_enabled(!twists.is_empty(), egui::Button::new("Apply"));
+            if r.clicked() {
+                app.event(AppEvent::Scramble(twists.clone(), ScrambleState::Full));
+                applied = true;
+            }
+            ui.label(match twists.len() {
+                1 => "1 move".to_string(),
+                n => format!("{n} moves"),
+            });
+        }
+        Err(e) => {
+            ui.add_enabled(false, egui::Button::new("Apply"));
+            ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+        }
+    });
+
+    if applied {
+        text.clear();
+    }
+    ui.data().insert_temp(text_id(), text);
+}
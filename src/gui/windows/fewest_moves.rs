@@ -0,0 +1,112 @@
+use super::Window;
+use crate::app::App;
+use crate::fmc::{FmcSession, NissSide};
+use crate::puzzle::*;
+
+pub(crate) const FEWEST_MOVES: Window = Window {
+    name: "Fewest moves",
+    build,
+    ..Window::DEFAULT
+};
+
+const SEQUENCE_TEXT_WIDTH: f32 = 300.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+
+    let Some(session) = &mut app.fmc else {
+        ui.label(
+            "Find a short solution using premoves and by \
+             switching between the normal and inverse scramble.",
+        );
+        let r = ui.add_enabled(
+            !app.puzzle.scramble().is_empty(),
+            egui::Button::new("Start"),
+        );
+        if r.clicked() {
+            match FmcSession::start(&app.puzzle) {
+                Ok(session) => app.fmc = Some(session),
+                Err(e) => app.set_status_err(e),
+            }
+        }
+        return;
+    };
+
+    if !session.is_current(&app.puzzle) {
+        app.fmc = None;
+        app.set_status_err("Fewest moves session ended because the puzzle changed");
+        return;
+    }
+    session.sync(&app.puzzle);
+
+    let mut result = Ok(());
+
+    ui.horizontal(|ui| {
+        ui.label("Scramble:");
+        for (side, name) in [(NissSide::Normal, "Normal"), (NissSide::Inverse, "Inverse")] {
+            if ui.selectable_label(session.side() == side, name).clicked() && session.side() != side
+            {
+                result = session.switch_side(&mut app.puzzle);
+            }
+        }
+    });
+
+    ui.separator();
+
+    let metric = app.prefs.info.metric;
+    let solution = session.solution();
+    for (label, twists) in [
+        ("Moves", session.moves()),
+        ("Premoves", session.premoves()),
+        ("Solution", &solution[..]),
+    ] {
+        ui.strong(format!(
+            "{label} ({} {metric})",
+            metric.count_twists(&app.puzzle, twists.iter().copied()),
+        ));
+        let text = puzzle_type.twists_to_string(twists);
+        ui.add(
+            egui::TextEdit::multiline(&mut text.as_str())
+                .code_editor()
+                .desired_rows(1)
+                .desired_width(SEQUENCE_TEXT_WIDTH),
+        );
+    }
+
+    if ui.button("🗐 Copy solution").clicked() {
+        ui.output().copied_text = puzzle_type.twists_to_string(&solution);
+    }
+
+    ui.separator();
+
+    ui.add_enabled_ui(session.side() == NissSide::Normal, |ui| {
+        let text_id = unique_id!();
+        let mut text: String = ui.data().get_temp(text_id).unwrap_or_default();
+        ui.horizontal(|ui| {
+            ui.label("Add premoves:");
+            ui.add(egui::TextEdit::singleline(&mut text).desired_width(120.0));
+            let parsed = puzzle_type.parse_twists(&text);
+            let r = ui.add_enabled(
+                matches!(&parsed, Ok(twists) if !twists.is_empty()),
+                egui::Button::new("Apply"),
+            );
+            if r.clicked() {
+                if let Ok(premoves) = parsed {
+                    result = session.add_premoves(&mut app.puzzle, &premoves);
+                    text.clear();
+                }
+            }
+        });
+        ui.data().insert_temp(text_id, text);
+    })
+    .response
+    .on_disabled_hover_text("Switch to the normal scramble to add premoves");
+
+    if ui.button("Stop").clicked() {
+        app.fmc = None;
+    }
+
+    if let Err(e) = result {
+        app.set_status_err(e);
+    }
+}
@@ -122,6 +122,7 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
             Some(key),
             vk,
             &pressed_keys,
+            false,
         )
         .into_iter()
         .filter(|bind| {
@@ -138,7 +139,13 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
         })
         .collect();
     let matching_global_keybinds: Vec<&Keybind<Command>> = app
-        .resolve_keypress(&app.prefs.global_keybinds, Some(key), vk, &pressed_keys)
+        .resolve_keypress(
+            &app.prefs.global_keybinds,
+            Some(key),
+            vk,
+            &pressed_keys,
+            false,
+        )
         .into_iter()
         .filter(|bind| {
             bind.command != Command::None
@@ -161,14 +168,15 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
             match &mut c {
                 // Don't show keybinds that depend on a grip when we don't have an
                 // axis gripped.
-                PuzzleCommand::Twist { axis, .. } | PuzzleCommand::Recenter { axis } => {
-                    match app.gripped_twist_axis(axis.as_deref()) {
-                        Ok(gripped_axis) => {
-                            *axis = Some(puzzle_type.info(gripped_axis).name.to_string())
-                        }
-                        Err(_) => return None,
+                PuzzleCommand::Twist { axis, .. }
+                | PuzzleCommand::OppositeTwist { axis, .. }
+                | PuzzleCommand::Recenter { axis }
+                | PuzzleCommand::Mirror { axis } => match app.gripped_twist_axis(axis.as_deref()) {
+                    Ok(gripped_axis) => {
+                        *axis = Some(app.puzzle.info(gripped_axis).name.to_string())
                     }
-                }
+                    Err(_) => return None,
+                },
                 _ => (),
             }
             Some(c.short_description(puzzle_type))
@@ -246,11 +254,41 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                         }
                     }
                 }
+                PuzzleCommand::OppositeTwist {
+                    axis,
+                    direction,
+                    opposite_direction,
+                    layers,
+                } => {
+                    ui.label("Twist");
+                    ui.strong(axis.as_deref().unwrap_or("gripped"));
+                    ui.label("in");
+                    ui.strong(direction);
+                    ui.label("direction and its opposite in");
+                    ui.strong(match opposite_direction.as_str() {
+                        "" => direction,
+                        s => s,
+                    });
+                    ui.label("direction");
+                    if !layers.is_default() {
+                        let layers = layers.to_layer_mask(puzzle_type.layer_count());
+                        ui.label("(");
+                        subtract_space(ui);
+                        ui.strong(layers.long_description());
+                        subtract_space(ui);
+                        ui.label(")");
+                    }
+                }
                 PuzzleCommand::Recenter { axis } => {
                     ui.label("Recenter");
                     ui.strong(axis.as_deref().unwrap_or("gripped"));
                     ui.label("axis");
                 }
+                PuzzleCommand::Mirror { axis } => {
+                    ui.label("Apply solution mirrored across");
+                    ui.strong(axis.as_deref().unwrap_or("gripped"));
+                    ui.label("axis");
+                }
 
                 PuzzleCommand::Filter { mode, filter_name } => {
                     ui.label(mode.as_ref());
@@ -278,6 +316,7 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                 Command::Open => ui.label("Open"),
                 Command::Save => ui.label("Save"),
                 Command::SaveAs => ui.label("Save As"),
+                Command::ExportSolveBundle => ui.label("Export solve bundle"),
                 Command::Exit => ui.label("Exit"),
 
                 Command::CopyHscLog => ui.label("Copy puzzle log (.hsc)"),
@@ -286,21 +325,66 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
 
                 Command::Undo => ui.label("Undo"),
                 Command::Redo => ui.label("Redo"),
+                Command::CancelQueuedTwists => ui.label("Cancel queued twists"),
+                Command::RealignSolved => ui.label("Realign solved puzzle"),
                 Command::Reset => ui.label("Reset"),
+                Command::ResetToScramble => ui.label("Reset to scramble"),
 
                 Command::ScrambleN(n) => {
                     ui.label("Scramble");
                     ui.strong(n.to_string())
                 }
                 Command::ScrambleFull => ui.label("Scramble fully"),
+                Command::NewAttempt => ui.label("New attempt"),
 
                 Command::NewPuzzle(ty) => {
                     ui.label("Load new");
                     ui.strong(ty.name());
                     ui.label("puzzle")
                 }
+                Command::NextPuzzle => ui.label("Next puzzle"),
+                Command::PreviousPuzzle => ui.label("Previous puzzle"),
+                Command::SwapBackgroundPuzzle => ui.label("Swap background puzzle"),
+                Command::DescribeState => ui.label("Copy puzzle state description"),
+                Command::CopyTextDiagram => ui.label("Copy puzzle diagram"),
+                Command::CopyColorDiagram => ui.label("Copy puzzle color diagram"),
 
                 Command::ToggleBlindfold => ui.label("Toggle blindfold"),
+                Command::ToggleStepMode => ui.label("Toggle step mode"),
+                Command::StepTwist => ui.label("Step twist"),
+
+                Command::ImportMc4dSettings => ui.label("Import MC4D settings"),
+                Command::SnapView => ui.label("Snap view"),
+                Command::RotateView(direction) => {
+                    ui.label("Rotate view");
+                    ui.strong(direction.to_string())
+                }
+                Command::ZoomIn => ui.label("Zoom in"),
+                Command::ZoomOut => ui.label("Zoom out"),
+                Command::ResetZoom => ui.label("Reset zoom"),
+                Command::Pan(direction) => {
+                    ui.label("Pan");
+                    ui.strong(direction.to_string())
+                }
+                Command::ResetPan => ui.label("Reset pan"),
+                Command::ToggleFullscreen => ui.label("Toggle fullscreen"),
+
+                Command::Plugin { plugin, command } => {
+                    ui.label("Run");
+                    ui.strong(command);
+                    ui.label("from");
+                    ui.strong(plugin)
+                }
+
+                Command::Sequence(commands) => {
+                    ui.label("Run");
+                    ui.strong(commands.len().to_string());
+                    ui.label(if commands.len() == 1 {
+                        "command"
+                    } else {
+                        "commands"
+                    })
+                }
 
                 Command::None => unreachable!(),
             });
@@ -346,15 +430,15 @@ macro_rules! keyboard_row {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-enum KeyboardElement {
+pub(super) enum KeyboardElement {
     Key(KeyMappingCode),
     Gap(f32),
 }
 
 #[derive(Debug, Copy, Clone)]
-struct KeyboardArea {
-    rect: egui::Rect,
-    rows: &'static [&'static [KeyboardElement]],
+pub(super) struct KeyboardArea {
+    pub(super) rect: egui::Rect,
+    pub(super) rows: &'static [&'static [KeyboardElement]],
 }
 
 const FUNCTION_KEYS: KeyboardArea = KeyboardArea {
@@ -364,7 +448,7 @@ const FUNCTION_KEYS: KeyboardArea = KeyboardArea {
     },
     rows: &[keyboard_row![Escape 1.0 F1 F2 F3 F4 0.5 F5 F6 F7 F8 0.5 F9 F10 F11 F12]],
 };
-const MAIN_KEYS: KeyboardArea = KeyboardArea {
+pub(super) const MAIN_KEYS: KeyboardArea = KeyboardArea {
     rect: egui::Rect {
         min: egui::pos2(0.0, 1.5),
         max: egui::pos2(15.0, 6.5),
@@ -418,7 +502,7 @@ const NUMPAD_KEYS_NO_NAV: KeyboardArea = KeyboardArea {
     ..NUMPAD_KEYS
 };
 
-fn get_key_size(key: KeyMappingCode) -> egui::Vec2 {
+pub(super) fn get_key_size(key: KeyMappingCode) -> egui::Vec2 {
     use KeyMappingCode::*;
 
     let w = match key {
@@ -0,0 +1,241 @@
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use winit::event::ModifiersState;
+
+use super::Window;
+use crate::app::App;
+use crate::commands::PuzzleCommand;
+use crate::preferences::{Key, KeyCombo};
+
+pub(crate) const KEYBOARD_TESTER: Window = Window {
+    name: "Keyboard tester",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+const WORKS_COLOR: egui::Color32 = egui::Color32::GREEN;
+const BLOCKED_COLOR: egui::Color32 = egui::Color32::RED;
+
+#[derive(Debug, Default, Clone)]
+struct KeyboardTester {
+    /// Most physical keys held at once since the window was opened.
+    rollover: usize,
+    current_test: Option<ComboTest>,
+    /// Keys that could not be registered for each tested combination, keyed
+    /// by the combination's name. An empty list means that the combination
+    /// works.
+    results: BTreeMap<String, Vec<Key>>,
+}
+
+/// Test of whether a combination of keys can be held at once.
+#[derive(Debug, Clone)]
+struct ComboTest {
+    name: String,
+    keys: Vec<Key>,
+    mods: ModifiersState,
+    /// Most keys from the combination that were held at once so far.
+    best: Vec<Key>,
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let id = unique_id!();
+    let mut tester: KeyboardTester = ui.data().get_temp(id).unwrap_or_default();
+
+    let pressed_keys = app.pressed_keys().clone();
+    let pressed_mods = app.pressed_modifiers();
+    let physical_key_count = pressed_keys
+        .iter()
+        .filter(|key| matches!(key, Key::Sc(_)) && !key.is_modifier())
+        .count()
+        + [
+            ModifiersState::SHIFT,
+            ModifiersState::CTRL,
+            ModifiersState::ALT,
+            ModifiersState::LOGO,
+        ]
+        .into_iter()
+        .filter(|&m| pressed_mods.contains(m))
+        .count();
+    tester.rollover = tester.rollover.max(physical_key_count);
+
+    ui.label(
+        "Many keyboards cannot register certain combinations of keys \
+         held at the same time. Use this window to check that your \
+         keybinds work on your keyboard.",
+    );
+
+    ui.separator();
+
+    ui.strong("Pressed keys");
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        ui.label("Scancodes");
+        ui.label(
+            pressed_keys
+                .iter()
+                .filter_map(|key| match key {
+                    Key::Sc(sc) => Some(format!("{} ({sc:?})", key_names::key_name(*sc))),
+                    Key::Vk(_) => None,
+                })
+                .join(", "),
+        );
+        ui.end_row();
+
+        ui.label("Keycodes");
+        ui.label(
+            pressed_keys
+                .iter()
+                .filter_map(|key| match key {
+                    Key::Sc(_) => None,
+                    Key::Vk(vk) => Some(format!("{vk:?}")),
+                })
+                .join(", "),
+        );
+        ui.end_row();
+
+        ui.label("Modifiers");
+        ui.label(key_names::mods_prefix_string(
+            pressed_mods.shift(),
+            pressed_mods.ctrl(),
+            pressed_mods.alt(),
+            pressed_mods.logo(),
+        ));
+        ui.end_row();
+    });
+    ui.label(format!("Most keys held at once: {}", tester.rollover));
+
+    ui.separator();
+
+    ui.strong("Keybind combinations");
+    if let Some(test) = &mut tester.current_test {
+        let all_held = test.keys.iter().all(|key| pressed_keys.contains(key))
+            && pressed_mods.contains(test.mods);
+        let held: Vec<Key> = (test.keys.iter())
+            .filter(|key| pressed_keys.contains(key))
+            .copied()
+            .collect();
+        if held.len() > test.best.len() {
+            test.best = held.clone();
+        }
+
+        ui.label(format!("Hold {} all at once", test.name));
+        ui.horizontal_wrapped(|ui| {
+            for key in &test.keys {
+                let color = if held.contains(key) {
+                    WORKS_COLOR
+                } else {
+                    BLOCKED_COLOR
+                };
+                ui.colored_label(color, key_name(*key));
+            }
+        });
+
+        // The test is done once every key has been held at once, or once the
+        // user has tried and released all of the keys.
+        let result = if all_held {
+            Some(vec![])
+        } else if held.is_empty() && !test.best.is_empty() {
+            Some(
+                (test.keys.iter())
+                    .filter(|key| !test.best.contains(key))
+                    .copied()
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let cancel = ui.button("Cancel").clicked();
+        if let Some(missing) = result {
+            tester.results.insert(test.name.clone(), missing);
+            tester.current_test = None;
+        } else if cancel {
+            tester.current_test = None;
+        }
+    } else {
+        ui.label("Click a combination and then hold its keys to test it.");
+    }
+
+    let combos = combos_to_test(app);
+    if combos.is_empty() {
+        ui.label("The active keybind set has no multi-key combinations.");
+    }
+    for (name, keys, mods) in combos {
+        ui.horizontal(|ui| {
+            match tester.results.get(&name) {
+                None => ui.label("❓"),
+                Some(missing) if missing.is_empty() => ui.colored_label(WORKS_COLOR, "✔"),
+                Some(missing) => ui.colored_label(BLOCKED_COLOR, "⚠").on_hover_text(format!(
+                    "Your keyboard did not register {}",
+                    missing.iter().map(|&key| key_name(key)).join(", "),
+                )),
+            };
+            if ui.button(name.as_str()).clicked() {
+                tester.current_test = Some(ComboTest {
+                    name,
+                    keys,
+                    mods,
+                    best: vec![],
+                });
+            }
+        });
+    }
+
+    ui.separator();
+
+    if ui.button("Clear results").clicked() {
+        tester = KeyboardTester::default();
+    }
+
+    ui.data().insert_temp(id, tester);
+}
+
+/// Returns the combinations of keys in the active puzzle keybind set that
+/// require holding multiple keys at once: multi-key keybinds, and each grip
+/// key combined with each twist key that relies on the grip.
+fn combos_to_test(app: &App) -> Vec<(String, Vec<Key>, ModifiersState)> {
+    let keybinds = app.prefs.puzzle_keybinds[app.puzzle.ty()]
+        .get_active_keybinds()
+        .collect_vec();
+
+    let multi_key_combos = keybinds
+        .iter()
+        .filter(|bind| bind.key.keys().len() > 1)
+        .map(|bind| {
+            let name = bind.key.to_string();
+            (name, bind.key.keys().clone(), bind.key.clone().mods())
+        });
+
+    let grips = keybinds
+        .iter()
+        .filter(|bind| matches!(bind.command, PuzzleCommand::Grip { .. }));
+    let gripped_twists = keybinds.iter().filter(|bind| {
+        matches!(
+            bind.command,
+            PuzzleCommand::Twist { axis: None, .. }
+                | PuzzleCommand::OppositeTwist { axis: None, .. }
+                | PuzzleCommand::Recenter { axis: None }
+                | PuzzleCommand::Mirror { axis: None },
+        )
+    });
+    let grip_combos = grips
+        .cartesian_product(gripped_twists.collect_vec())
+        .filter(|(grip, twist)| grip.key.keys() != twist.key.keys())
+        .map(|(grip, twist)| {
+            let name = format!("{} + {}", grip.key, twist.key);
+            let keys = (grip.key.keys().iter().chain(twist.key.keys()))
+                .copied()
+                .unique()
+                .collect();
+            let mods = grip.key.clone().mods() | twist.key.clone().mods();
+            (name, keys, mods)
+        });
+
+    multi_key_combos
+        .chain(grip_combos)
+        .unique_by(|(name, _, _)| name.clone())
+        .collect()
+}
+
+fn key_name(key: Key) -> String {
+    KeyCombo::new(vec![key], ModifiersState::empty(), Default::default()).to_string()
+}
@@ -0,0 +1,81 @@
+use super::Window;
+use crate::app::App;
+use crate::gui::components::small_icon_button;
+use crate::keyframes::Keyframe;
+
+pub(crate) const KEYFRAMES: Window = Window {
+    name: "Keyframes",
+    vscroll: true,
+    build,
+    cleanup,
+    ..Window::DEFAULT
+};
+
+fn cleanup(_ctx: &egui::Context, app: &mut App) {
+    app.keyframes.stop();
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.horizontal(|ui| {
+        if ui.button("Add keyframe").clicked() {
+            app.keyframes.frames.push(Keyframe {
+                view: app.prefs.view(app.puzzle.ty()).clone(),
+                history_len: app.puzzle.undo_buffer().len(),
+            });
+        }
+
+        if app.keyframes.is_playing() {
+            if ui.button("⏹ Stop").clicked() {
+                app.keyframes.stop();
+            }
+        } else {
+            let r = ui.add_enabled(
+                !app.keyframes.frames.is_empty(),
+                egui::Button::new("▶ Play"),
+            );
+            if r.clicked() {
+                app.keyframes.play();
+            }
+        }
+    });
+
+    ui.separator();
+
+    if app.keyframes.frames.is_empty() {
+        ui.label("Add keyframes to record the current view and twist history.");
+        ui.label(
+            "Video export is not available yet, so use a \
+             screen recorder to capture playback.",
+        );
+        return;
+    }
+
+    let mut to_remove = None;
+    let mut to_overwrite = None;
+    for (i, keyframe) in app.keyframes.frames.iter().enumerate() {
+        ui.horizontal(|ui| {
+            if small_icon_button(ui, "🗑", "Delete keyframe").clicked() {
+                to_remove = Some(i);
+            }
+            if small_icon_button(ui, "⟳", "Overwrite with current view").clicked() {
+                to_overwrite = Some(i);
+            }
+            let is_current = app.keyframes.current() == Some(i);
+            ui.selectable_label(is_current, format!("#{}", i + 1));
+            ui.label(match keyframe.history_len {
+                1 => "1 twist".to_string(),
+                n => format!("{n} twists"),
+            });
+        });
+    }
+
+    if let Some(i) = to_overwrite {
+        app.keyframes.frames[i] = Keyframe {
+            view: app.prefs.view(app.puzzle.ty()).clone(),
+            history_len: app.puzzle.undo_buffer().len(),
+        };
+    }
+    if let Some(i) = to_remove {
+        app.keyframes.remove(i);
+    }
+}
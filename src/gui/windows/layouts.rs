@@ -0,0 +1,108 @@
+use super::Window;
+use crate::app::App;
+use crate::gui::components::{PresetsUi, PresetsUiStrings};
+use crate::preferences::{Layout, ViewPreferences, WindowLayout, WithPresets};
+
+pub(crate) const LAYOUTS: Window = Window {
+    name: "Layouts",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let mut changed = false;
+
+    let current_layout = current_layout(ui.ctx(), app);
+    let mut layout_to_apply = None;
+
+    let mut presets_ui = PresetsUi {
+        id: unique_id!(),
+        presets: &mut app.prefs.layouts,
+        changed: &mut changed,
+        strings: PresetsUiStrings {
+            edit: "Edit layouts",
+            save: "Save layout",
+            name: "Layout name",
+        },
+        enable_yaml: true,
+    };
+    presets_ui.show_header(ui, || current_layout.clone());
+    ui.separator();
+    presets_ui.show_list(ui, |ui, _idx, preset| {
+        let r = ui.button("Load");
+        if r.clicked() {
+            layout_to_apply = Some(preset.value.clone());
+        }
+        if preset.value == current_layout {
+            ui.strong(&preset.preset_name);
+        } else {
+            ui.label(&preset.preset_name);
+        }
+        r
+    });
+
+    app.prefs.needs_save |= changed;
+
+    if let Some(layout) = layout_to_apply {
+        apply_layout(ui.ctx(), app, &layout);
+    }
+}
+
+/// Captures the current arrangement of windows and active view presets.
+fn current_layout(ctx: &egui::Context, app: &App) -> Layout {
+    let active_preset_name = |presets: &WithPresets<ViewPreferences>| {
+        Some(presets.active_preset.as_ref()?.preset_name.clone())
+    };
+
+    Layout {
+        windows: super::ALL
+            .iter()
+            .filter(|w| w.is_open(ctx) && w.name != LAYOUTS.name)
+            .map(|w| {
+                let pos = w.pos(ctx).map(|p| [p.x, p.y]);
+                (w.name.to_owned(), WindowLayout { pos })
+            })
+            .collect(),
+        view_3d_preset: active_preset_name(&app.prefs.view_3d),
+        view_4d_preset: active_preset_name(&app.prefs.view_4d),
+    }
+}
+
+/// Opens and moves windows and loads view presets according to a layout.
+fn apply_layout(ctx: &egui::Context, app: &mut App, layout: &Layout) {
+    for window in super::ALL {
+        // Keep this window open so that the user can switch layouts again.
+        if window.name == LAYOUTS.name {
+            continue;
+        }
+        match layout.windows.get(window.name) {
+            Some(window_layout) => {
+                window.set_open(ctx, true);
+                if let Some([x, y]) = window_layout.pos {
+                    window.set_pos(ctx, egui::pos2(x, y));
+                }
+            }
+            None => window.set_open(ctx, false),
+        }
+    }
+
+    let ty = app.puzzle.ty();
+    let old_view = app.prefs.view(ty).clone();
+    for (presets, preset_name, dimensions) in [
+        (&mut app.prefs.view_3d, &layout.view_3d_preset, "3D"),
+        (&mut app.prefs.view_4d, &layout.view_4d_preset, "4D"),
+    ] {
+        let Some(name) = preset_name else {
+            continue;
+        };
+        let Some(preset) = presets.presets.iter().find(|p| p.preset_name == *name) else {
+            log::warn!("No {dimensions} view preset named {name:?}");
+            continue;
+        };
+        presets.current = preset.value.clone();
+        presets.active_preset = Some(preset.clone());
+    }
+    app.puzzle.animate_from_view_settings(old_view);
+    app.prefs.needs_save = true;
+}
@@ -0,0 +1,102 @@
+use super::Window;
+use crate::app::App;
+use crate::puzzle::TwistMetric;
+
+pub(crate) const LEADERBOARD: Window = Window {
+    name: "Leaderboard",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs.leaderboard;
+    let mut changed = false;
+
+    changed |= ui
+        .checkbox(
+            &mut prefs.enabled,
+            "Submit ranked solves to the leaderboard",
+        )
+        .changed();
+    ui.label(
+        "When enabled, each ranked solve is sent to the leaderboard server \
+         along with your player name: the scramble seed, the time, the move \
+         counts, and the log file of the solve. Nothing else is sent, and \
+         nothing is sent when this is disabled.",
+    );
+    ui.horizontal(|ui| {
+        ui.label("Server");
+        changed |= ui.text_edit_singleline(&mut prefs.server).changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label("Player name");
+        changed |= ui.text_edit_singleline(&mut prefs.player_name).changed();
+    });
+    app.prefs.needs_save |= changed;
+
+    ui.separator();
+
+    let enabled = app.prefs.leaderboard.enabled && !app.prefs.leaderboard.server.is_empty();
+    let r = ui
+        .add_enabled(enabled, egui::Button::new("🔀 Start ranked solve"))
+        .on_hover_text("Scramble fully from a random seed that the server can check")
+        .on_disabled_hover_text("Enable the leaderboard and choose a server first");
+    if r.clicked() {
+        app.start_ranked_solve();
+    }
+    if app.leaderboard.is_ranked(&app.puzzle) {
+        ui.label("Ranked solve in progress");
+    }
+
+    let queued = app.leaderboard.queue.len();
+    if queued > 0 {
+        ui.horizontal(|ui| {
+            ui.label(format!("{queued} solve(s) waiting to be submitted"));
+            if ui
+                .add_enabled(enabled, egui::Button::new("Retry"))
+                .clicked()
+            {
+                app.leaderboard.submit_queued(&app.prefs.leaderboard);
+            }
+            if ui.button("Discard").clicked() {
+                app.leaderboard.clear_queue();
+            }
+        });
+    }
+    if let Some(e) = &app.leaderboard.error {
+        ui.colored_label(egui::Color32::RED, e);
+    }
+
+    ui.separator();
+
+    let puzzle_name = app.puzzle.ty().name().to_string();
+    ui.horizontal(|ui| {
+        ui.strong(format!("{puzzle_name} rankings"));
+        if ui
+            .add_enabled(enabled, egui::Button::new("⟲ Refresh"))
+            .clicked()
+        {
+            app.leaderboard
+                .fetch_rankings(&app.prefs.leaderboard, puzzle_name.clone());
+        }
+    });
+    match &app.leaderboard.rankings {
+        Some((name, rankings)) if *name == puzzle_name => {
+            egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+                for (i, ranking) in rankings.iter().enumerate() {
+                    ui.label(format!("{}.", i + 1));
+                    ui.label(&ranking.player);
+                    ui.label(format!("{:.2}s", ranking.time_ms as f64 / 1000.0));
+                    if let Some(n) = ranking.twist_counts.get(&TwistMetric::default()) {
+                        ui.label(format!("{n} {}", TwistMetric::default()));
+                    }
+                    ui.end_row();
+                }
+            });
+        }
+        _ => {
+            ui.label("No rankings loaded");
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::Window;
+use crate::app::App;
+use crate::lifetime_stats::PuzzleStats;
+
+pub(crate) const LIFETIME_STATS: Window = Window {
+    name: "Lifetime stats",
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let stats = &app.lifetime_stats;
+
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        ui.strong("Puzzle");
+        ui.strong("Twists");
+        ui.strong("Solves");
+        ui.strong("Time")
+            .on_hover_text("Time spent twisting, not counting long pauses");
+        ui.end_row();
+
+        for (name, puzzle_stats) in &stats.puzzles {
+            ui.label(name);
+            stats_row(ui, puzzle_stats);
+        }
+
+        ui.strong("Total");
+        stats_row(ui, &stats.total());
+    });
+
+    ui.separator();
+
+    let session = stats.session_total();
+    ui.label(format!(
+        "This session: {} twists, {} solves, {}",
+        session.twists,
+        session.solves,
+        format_time(session.time_secs),
+    ));
+}
+
+fn stats_row(ui: &mut egui::Ui, stats: &PuzzleStats) {
+    ui.label(stats.twists.to_string());
+    ui.label(stats.solves.to_string());
+    ui.label(format_time(stats.time_secs));
+    ui.end_row();
+}
+
+/// Formats a number of seconds as hours and minutes.
+fn format_time(secs: f64) -> String {
+    let minutes = (secs / 60.0) as u64;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
@@ -0,0 +1,129 @@
+use super::Window;
+use crate::app::App;
+use crate::log_analysis;
+
+pub(crate) const LOG_ANALYSIS: Window = Window {
+    name: "Log analysis",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+/// Number of first moves to show for each puzzle type.
+const FIRST_MOVE_COUNT: usize = 5;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.label(
+        "Load every log file in a folder and show statistics \
+         over the solves in them.",
+    );
+    ui.horizontal(|ui| {
+        if ui.button("📂 Analyze folder...").clicked() {
+            if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                match log_analysis::analyze_folder(&dir, app.prefs.info.metric) {
+                    Ok(report) => app.log_report = Some(report),
+                    Err(e) => app.set_status_err(e),
+                }
+            }
+        }
+
+        let Some(report) = &app.log_report else {
+            return;
+        };
+        if ui.button("💾 Save report...").clicked() {
+            let dialog = rfd::FileDialog::new()
+                .add_filter("JSON Files", &["json"])
+                .add_filter("CSV Files", &["csv"])
+                .set_file_name("log_report.json");
+            if let Some(path) = dialog.save_file() {
+                match report.save_file(&path) {
+                    Ok(()) => app.set_status_ok(format!("Saved report to {}", path.display())),
+                    Err(e) => app.set_status_err(e),
+                }
+            }
+        }
+    });
+
+    let Some(report) = &app.log_report else {
+        return;
+    };
+    let metric = report.metric;
+
+    ui.separator();
+
+    let r = ui.label(format!("{} log files", report.files));
+    if !report.errors.is_empty() {
+        r.on_hover_text(report.errors.join("\n"));
+        ui.colored_label(
+            egui::Color32::RED,
+            format!("{} could not be loaded", report.errors.len()),
+        );
+    }
+
+    for (puzzle_name, r) in &report.puzzles {
+        egui::CollapsingHeader::new(puzzle_name)
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new(unique_id!(puzzle_name))
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Solves");
+                        ui.label(r.solves.to_string());
+                        ui.end_row();
+
+                        ui.label("Incomplete");
+                        ui.label(r.incomplete.to_string());
+                        ui.end_row();
+
+                        if let Some(d) = r.twist_counts.get(&metric) {
+                            ui.label(format!("Moves ({metric})"));
+                            ui.label(format!("{} / {:.1} / {}", d.min, d.mean, d.max))
+                                .on_hover_text("Minimum / mean / maximum");
+                            ui.end_row();
+                        }
+
+                        if let Some(t) = r.mean_time_ms {
+                            ui.label("Mean time");
+                            ui.label(format!("{:.2}s", t / 1000.0));
+                            ui.end_row();
+                        }
+
+                        ui.label("First moves");
+                        ui.label(
+                            r.most_common_first_moves()
+                                .into_iter()
+                                .take(FIRST_MOVE_COUNT)
+                                .map(|(twist, count)| format!("{twist} ({count})"))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        );
+                        ui.end_row();
+                    });
+
+                if r.phases.is_empty() {
+                    return;
+                }
+                ui.add_space(ui.spacing().item_spacing.y);
+                egui::Grid::new(unique_id!(puzzle_name))
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Phase");
+                        ui.strong("Solves");
+                        ui.strong(format!("Moves ({metric})"));
+                        ui.strong("Time");
+                        ui.end_row();
+
+                        for phase in &r.phases {
+                            ui.label(phase.name.as_str());
+                            ui.label(phase.solves.to_string());
+                            ui.label(format!("{:.1}", phase.mean_twists));
+                            match phase.mean_time_ms {
+                                Some(t) => ui.label(format!("{:.2}s", t / 1000.0)),
+                                None => ui.label("-"),
+                            };
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
@@ -1,46 +1,128 @@
 mod about;
+mod achievements;
+mod enter_scramble;
+mod fewest_moves;
 mod keybind_sets;
 mod keybinds_reference;
 mod keybinds_table;
+mod keyboard_tester;
+mod keyframes;
+mod layouts;
+#[cfg(not(target_arch = "wasm32"))]
+mod leaderboard;
+mod lifetime_stats;
+#[cfg(not(target_arch = "wasm32"))]
+mod log_analysis;
 mod modifier_keys;
 mod mousebinds_table;
+mod move_heatmap;
+mod patterns;
 mod piece_filters;
+mod piece_tracking;
+#[cfg(not(target_arch = "wasm32"))]
+mod plugins;
 mod puzzle_controls;
+mod puzzle_info;
+mod scramble_options;
+mod scramble_preview;
+mod sequence_analysis;
 mod settings;
+mod solution_transforms;
+mod solve_stats;
+mod state_editor;
+mod twist_buttons;
+#[cfg(not(target_arch = "wasm32"))]
+mod updates;
+mod versus;
 mod welcome;
 
 use crate::app::App;
 pub(crate) use about::*;
+pub(crate) use achievements::*;
+pub(crate) use enter_scramble::*;
+pub(crate) use fewest_moves::*;
 pub(crate) use keybind_sets::*;
 pub(crate) use keybinds_reference::*;
 pub(crate) use keybinds_table::*;
+pub(crate) use keyboard_tester::*;
+pub(crate) use keyframes::*;
+pub(crate) use layouts::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use leaderboard::*;
+pub(crate) use lifetime_stats::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use log_analysis::*;
 pub(crate) use modifier_keys::*;
 pub(crate) use mousebinds_table::*;
+pub(crate) use move_heatmap::*;
+pub(crate) use patterns::*;
 pub(crate) use piece_filters::*;
+pub(crate) use piece_tracking::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use plugins::*;
 pub(crate) use puzzle_controls::*;
+pub(crate) use puzzle_info::*;
+pub(crate) use scramble_options::*;
+pub(crate) use scramble_preview::*;
+pub(crate) use sequence_analysis::*;
 pub(crate) use settings::*;
+pub(crate) use solution_transforms::*;
+pub(crate) use solve_stats::*;
+pub(crate) use state_editor::*;
+pub(crate) use twist_buttons::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use updates::*;
+pub(crate) use versus::*;
 pub(crate) use welcome::*;
 
 pub const FLOATING_WINDOW_OPACITY: f32 = 0.98;
 pub const PREFS_WINDOW_WIDTH: f32 = 240.0;
 pub const ABOUT_WINDOW_WIDTH: f32 = 360.0;
 pub const WELCOME_WINDOW_WIDTH: f32 = 540.0;
+pub const ENTER_SCRAMBLE_WINDOW_WIDTH: f32 = 360.0;
 
 pub const ALL: &[Window] = &[
     // Misc.
     WELCOME,
     ABOUT,
+    #[cfg(not(target_arch = "wasm32"))]
+    UPDATES,
+    ENTER_SCRAMBLE,
+    SCRAMBLE_OPTIONS,
     #[cfg(debug_assertions)]
     DEBUG,
     // Tools
     KEYBINDS_REFERENCE,
+    KEYBOARD_TESTER,
     PUZZLE_CONTROLS,
+    TWIST_BUTTONS,
     PIECE_FILTERS,
     MODIFIER_KEYS,
+    KEYFRAMES,
+    SCRAMBLE_PREVIEW,
+    SOLUTION_TRANSFORMS,
+    FEWEST_MOVES,
+    STATE_EDITOR,
+    SOLVE_STATS,
+    MOVE_HEATMAP,
+    VERSUS,
+    ACHIEVEMENTS,
+    LIFETIME_STATS,
+    PIECE_TRACKING,
+    SEQUENCE_ANALYSIS,
+    PATTERNS,
+    PUZZLE_INFO,
+    #[cfg(not(target_arch = "wasm32"))]
+    PLUGINS,
+    #[cfg(not(target_arch = "wasm32"))]
+    LEADERBOARD,
+    #[cfg(not(target_arch = "wasm32"))]
+    LOG_ANALYSIS,
     // Settings
     APPEARANCE_SETTINGS,
     INTERACTION_SETTINGS,
     VIEW_SETTINGS,
+    LAYOUTS,
     // Keybinds
     KEYBIND_SETS,
     GLOBAL_KEYBINDS,
@@ -100,6 +182,16 @@ impl Window {
         ctx.data().insert_persisted(self.id(), is_open);
     }
 
+    /// Returns the position of the top left corner of the window, if it is
+    /// floating and has been shown.
+    pub fn pos(self, ctx: &egui::Context) -> Option<egui::Pos2> {
+        ctx.data().get_temp(self.id().with("pos"))
+    }
+    /// Moves the window the next time it is shown, if it is floating.
+    pub fn set_pos(self, ctx: &egui::Context, pos: egui::Pos2) {
+        ctx.data().insert_temp(self.id().with("requested_pos"), pos);
+    }
+
     pub fn show(self, ctx: &egui::Context, app: &mut App) {
         let opacity = if self.id() == KEYBINDS_REFERENCE.id() {
             app.prefs.info.keybinds_reference.opacity
@@ -118,19 +210,29 @@ impl Window {
                         .collapsible(false)
                         .resizable(false);
                 } else {
+                    let requested_pos_id = self.id().with("requested_pos");
+                    let requested_pos = ctx.data().get_temp::<egui::Pos2>(requested_pos_id);
+                    if let Some(pos) = requested_pos {
+                        ctx.data().remove::<egui::Pos2>(requested_pos_id);
+                        w = w.current_pos(pos);
+                    }
                     w = w
                         .collapsible(true)
                         .scroll2([false, self.vscroll])
                         .resizable(self.fixed_width.is_none() || self.vscroll)
                         .frame(egui::Frame::popup(&ctx.style()).multiply_with_opacity(opacity));
                 }
-                w.show(ctx, |ui| {
+                let r = w.show(ctx, |ui| {
                     if let Some(w) = self.fixed_width {
                         ui.set_min_width(w);
                         ui.set_max_width(w);
                     }
                     (self.build)(ui, app);
                 });
+                if let Some(r) = r {
+                    ctx.data()
+                        .insert_temp(self.id().with("pos"), r.response.rect.min);
+                }
             }
             Location::LeftSide => {
                 super::side_bar::build(ctx, self.name, &mut is_open, |ui| (self.build)(ui, app));
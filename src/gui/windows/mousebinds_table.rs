@@ -93,6 +93,7 @@ impl egui::Widget for MousebindsTable<'_> {
                                 ),
                                 (PuzzleMouseCommand::Recenter, "Recenter".into()),
                                 (PuzzleMouseCommand::SelectPiece, "Select piece".into()),
+                                (PuzzleMouseCommand::ContextMenu, "Context menu".into()),
                             ],
                         });
 
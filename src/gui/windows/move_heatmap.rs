@@ -0,0 +1,200 @@
+use key_names::KeyMappingCode;
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fmt::Write;
+
+use super::keybinds_reference::{get_key_size, KeyboardElement, MAIN_KEYS};
+use super::Window;
+use crate::app::App;
+use crate::puzzle::traits::*;
+
+pub(crate) const MOVE_HEATMAP: Window = Window {
+    name: "Move heatmap",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+/// Size of each twist axis cell, in points.
+const AXIS_CELL_SIZE: f32 = 40.0;
+/// Size of a 1-unit key in the keyboard heatmap, in points.
+const KEY_SIZE: f32 = 28.0;
+const KEY_PADDING: f32 = 2.0;
+
+/// Color for cells that were never used.
+const COLD_COLOR: egui::Color32 = egui::Color32::from_rgb(40, 48, 80);
+/// Color for the most used cell.
+const HOT_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 70, 30);
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let axis_counts = axis_counts(app);
+
+    ui.strong("Twist axes");
+    ui.label("Number of twists on each axis in the current solve.");
+    let max = axis_counts.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    ui.horizontal_wrapped(|ui| {
+        for (name, n) in &axis_counts {
+            let (rect, r) =
+                ui.allocate_exact_size(egui::Vec2::splat(AXIS_CELL_SIZE), egui::Sense::hover());
+            draw_cell(ui, rect, name, heat_color(*n, max));
+            r.on_hover_text(format!("{name}: {n} twists"));
+        }
+    });
+
+    ui.separator();
+
+    ui.strong("Keyboard");
+    ui.label("Number of times each key was pressed since launch.");
+    let key_counts = &app.key_press_counts;
+    let max = key_counts.values().copied().max().unwrap_or(0);
+    let size = MAIN_KEYS.rect.size() * KEY_SIZE;
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    for (key, key_rect) in key_rects() {
+        let n = key_counts.get(&key).copied().unwrap_or(0);
+        let key_rect = key_rect.translate(rect.min.to_vec2());
+        draw_cell(ui, key_rect, &key_names::key_name(key), heat_color(n, max));
+        if ui.rect_contains_pointer(key_rect) {
+            egui::show_tooltip_text(ui.ctx(), unique_id!(), format!("{n} presses"));
+        }
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("Clear keyboard counts").clicked() {
+            app.key_press_counts.clear();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Export image...").clicked() {
+            let svg = heatmap_svg(&axis_counts, &app.key_press_counts);
+            app.try_export_heatmap(&svg);
+        }
+    });
+}
+
+/// Returns the number of twists on each twist axis in the undo history.
+fn axis_counts(app: &App) -> Vec<(String, usize)> {
+    let puzzle = &app.puzzle;
+    let mut counts = vec![0; puzzle.twist_axes().len()];
+    for twist in puzzle
+        .undo_buffer()
+        .iter()
+        .filter_map(|entry| entry.twist())
+    {
+        counts[twist.axis.0 as usize] += 1;
+    }
+    puzzle
+        .twist_axes()
+        .iter()
+        .map(|axis| axis.name.to_owned())
+        .zip(counts)
+        .collect()
+}
+
+/// Returns the rectangle of each key in the main area of the keyboard,
+/// relative to the top left corner of the keyboard.
+fn key_rects() -> Vec<(KeyMappingCode, egui::Rect)> {
+    let mut ret = vec![];
+    let mut cursor = egui::Vec2::ZERO;
+    for &row in MAIN_KEYS.rows {
+        for &element in row {
+            match element {
+                KeyboardElement::Key(key) => {
+                    let key_size = get_key_size(key) * KEY_SIZE;
+                    let rect = egui::Rect::from_min_size(cursor.to_pos2(), key_size);
+                    ret.push((key, rect.shrink(KEY_PADDING)));
+                    cursor.x += key_size.x;
+                }
+                KeyboardElement::Gap(dx) => cursor.x += dx * KEY_SIZE,
+            }
+        }
+        cursor.x = 0.0;
+        cursor.y += KEY_SIZE;
+    }
+    ret
+}
+
+fn draw_cell(ui: &egui::Ui, rect: egui::Rect, label: &str, color: egui::Color32) {
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, color);
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        label,
+        egui::FontId::proportional(12.0),
+        egui::Color32::WHITE,
+    );
+}
+
+/// Returns the color of a cell used `n` times, where the most used cell is
+/// used `max` times.
+fn heat_color(n: usize, max: usize) -> egui::Color32 {
+    if max == 0 {
+        return COLD_COLOR;
+    }
+    let t = n as f32 / max as f32;
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    egui::Color32::from_rgb(
+        lerp(COLD_COLOR.r(), HOT_COLOR.r()),
+        lerp(COLD_COLOR.g(), HOT_COLOR.g()),
+        lerp(COLD_COLOR.b(), HOT_COLOR.b()),
+    )
+}
+
+/// Renders both heatmaps as an SVG image.
+#[cfg(not(target_arch = "wasm32"))]
+fn heatmap_svg(
+    axis_counts: &[(String, usize)],
+    key_counts: &HashMap<KeyMappingCode, usize>,
+) -> String {
+    let keyboard_size = MAIN_KEYS.rect.size() * KEY_SIZE;
+    let width = keyboard_size
+        .x
+        .max(AXIS_CELL_SIZE * axis_counts.len() as f32);
+    let height = AXIS_CELL_SIZE + KEY_SIZE + keyboard_size.y;
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" font-family="sans-serif" font-size="12">"#,
+    );
+    let mut cell = |rect: egui::Rect, label: &str, color: egui::Color32| {
+        let _ = write!(
+            svg,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" rx="2" fill="#{:02x}{:02x}{:02x}"/><text x="{}" y="{}" fill="white" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height(),
+            color.r(),
+            color.g(),
+            color.b(),
+            rect.center().x,
+            rect.center().y,
+            escape_xml(label),
+        );
+    };
+
+    let max = axis_counts.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    for (i, (name, n)) in axis_counts.iter().enumerate() {
+        let min = egui::pos2(i as f32 * AXIS_CELL_SIZE, 0.0);
+        let rect = egui::Rect::from_min_size(min, egui::Vec2::splat(AXIS_CELL_SIZE));
+        cell(rect.shrink(KEY_PADDING), name, heat_color(*n, max));
+    }
+
+    let max = key_counts.values().copied().max().unwrap_or(0);
+    let keyboard_offset = egui::vec2(0.0, AXIS_CELL_SIZE + KEY_SIZE);
+    for (key, rect) in key_rects() {
+        let n = key_counts.get(&key).copied().unwrap_or(0);
+        let rect = rect.translate(keyboard_offset);
+        cell(rect, &key_names::key_name(key), heat_color(n, max));
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,138 @@
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::puzzle::*;
+
+pub(crate) const PATTERNS: Window = Window {
+    name: "Patterns",
+    build,
+    ..Window::DEFAULT
+};
+
+const THUMBNAIL_SIZE: f32 = 64.0;
+const MAX_HEIGHT: f32 = 400.0;
+
+/// Polygons of a thumbnail, with coordinates from 0.0 to 1.0, in the order
+/// that they should be drawn.
+type Thumbnail = Vec<(Vec<egui::Pos2>, Face)>;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let patterns = patterns(puzzle_type);
+
+    if patterns.is_empty() {
+        ui.label(format!("No patterns available for {}.", puzzle_type.name()));
+        return;
+    }
+
+    // Generating thumbnails requires projecting every sticker of every
+    // pattern, so only do it once per puzzle type.
+    let thumbnails_id = unique_id!(puzzle_type);
+    let cached_thumbnails: Option<Vec<Thumbnail>> = ui.data().get_temp(thumbnails_id);
+    let thumbnails = cached_thumbnails.or_else(|| {
+        let thumbnails = patterns
+            .iter()
+            .map(|pattern| {
+                let mut state = Puzzle::new(puzzle_type);
+                for &twist in &pattern.twists {
+                    state.twist(twist).ok()?;
+                }
+                let geometry = app.puzzle.preview_geometry(&state)?;
+                Some(make_thumbnail(&state, &geometry))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        ui.data().insert_temp(thumbnails_id, thumbnails.clone());
+        Some(thumbnails)
+    });
+
+    let face_colors = app.prefs.colors.face_colors_list(puzzle_type);
+
+    egui::ScrollArea::vertical()
+        .max_height(MAX_HEIGHT)
+        .show(ui, |ui| {
+            for (i, pattern) in patterns.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let (rect, _) = ui.allocate_exact_size(
+                        egui::vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE),
+                        egui::Sense::hover(),
+                    );
+                    if let Some(thumbnail) = thumbnails.as_ref().map(|t| &t[i]) {
+                        draw_thumbnail(ui, rect, thumbnail, &face_colors);
+                    }
+
+                    ui.vertical(|ui| {
+                        ui.strong(pattern.name.as_str());
+                        ui.label(match pattern.twists.len() {
+                            1 => "1 move".to_string(),
+                            n => format!("{n} moves"),
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.button("Apply").clicked() {
+                                app.event(AppEvent::ApplyPattern(pattern.clone()));
+                            }
+                            if ui.button("🗐 Copy").clicked() {
+                                ui.output().copied_text =
+                                    puzzle_type.twists_to_string(&pattern.twists);
+                            }
+                        });
+                    });
+                });
+            }
+        });
+}
+
+fn make_thumbnail(state: &Puzzle, geometry: &[ProjectedStickerGeometry]) -> Thumbnail {
+    let mut min = egui::pos2(f32::INFINITY, f32::INFINITY);
+    let mut max = egui::pos2(f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for geom in geometry {
+        min.x = min.x.min(geom.min_bound.x);
+        min.y = min.y.min(geom.min_bound.y);
+        max.x = max.x.max(geom.max_bound.x);
+        max.y = max.y.max(geom.max_bound.y);
+    }
+    // Preserve the aspect ratio and center the puzzle.
+    let scale = (max.x - min.x).max(max.y - min.y);
+    let center = min + (max - min) / 2.0;
+
+    geometry
+        .iter()
+        .flat_map(|geom| {
+            let color = state.info(geom.sticker).color;
+            geom.front_polygons.iter().map(move |polygon| {
+                let verts = polygon
+                    .verts
+                    .iter()
+                    .map(|v| {
+                        // Flip the Y axis, which points up in puzzle space
+                        // but down on the screen.
+                        egui::pos2(
+                            (v.x - center.x) / scale + 0.5,
+                            (center.y - v.y) / scale + 0.5,
+                        )
+                    })
+                    .collect();
+                (verts, color)
+            })
+        })
+        .collect()
+}
+
+fn draw_thumbnail(
+    ui: &egui::Ui,
+    rect: egui::Rect,
+    thumbnail: &Thumbnail,
+    face_colors: &[egui::Color32],
+) {
+    let stroke = egui::Stroke::new(0.5, egui::Color32::BLACK);
+    let painter = ui.painter_at(rect);
+    for (verts, face) in thumbnail {
+        let points = verts
+            .iter()
+            .map(|p| rect.min + egui::vec2(p.x * rect.width(), p.y * rect.height()))
+            .collect();
+        painter.add(egui::Shape::convex_polygon(
+            points,
+            face_colors[face.0 as usize],
+            stroke,
+        ));
+    }
+}
@@ -4,7 +4,9 @@ use super::Window;
 use crate::app::App;
 use crate::gui::components::{prefs, small_icon_button, PrefsUi, PresetsUi};
 use crate::preferences::{PieceFilter, DEFAULT_PREFS};
-use crate::puzzle::{traits::*, Face, PieceInfo, PieceType};
+use crate::puzzle::{
+    traits::*, Face, PieceInfo, PieceType, ProjectionType, PuzzleTypeEnum, TwistAxis,
+};
 
 pub(crate) const PIECE_FILTERS: Window = Window {
     name: "Piece filters",
@@ -44,6 +46,7 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
     app.puzzle.set_visible_pieces_preview(None, None);
 
     let puzzle_type = app.puzzle.ty();
+    let desc = puzzle_type.desc();
 
     ui.set_min_width(MIN_WIDTH);
 
@@ -67,17 +70,15 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
 
     ui.separator();
 
-    PieceFilterWidget::new_uppercased("everything", piece_subset(puzzle_type, |_| true))
+    PieceFilterWidget::new_uppercased("everything", piece_subset(&*desc, |_| true))
         .no_all_except()
         .show(ui, app);
 
     ui.collapsing("Types", |ui| {
-        for (i, piece_type) in puzzle_type.piece_types().iter().enumerate() {
+        for (i, piece_type) in desc.piece_types().iter().enumerate() {
             PieceFilterWidget::new_uppercased(
                 &format!("{}s", piece_type.name),
-                piece_subset(puzzle_type, move |piece| {
-                    piece.piece_type == PieceType(i as _)
-                }),
+                piece_subset(&*desc, move |piece| piece.piece_type == PieceType(i as _)),
             )
             .show(ui, app);
         }
@@ -93,10 +94,10 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
             ui.data().get_temp(colors_selection_id).unwrap_or_default();
         selected_colors.resize(app.puzzle.faces().len(), false);
 
-        for i in 0..puzzle_type.faces().len() {
+        for i in 0..desc.faces().len() {
             PieceFilterWidget::new_uppercased(
                 "pieces with this color",
-                piece_subset_from_sticker_colors!(puzzle_type, |colors| {
+                piece_subset_from_sticker_colors!(&*desc, |colors| {
                     colors.any(|c| c == Face(i as _))
                 }),
             )
@@ -113,7 +114,7 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
         ui.add_enabled_ui(selected_colors.contains(&true), |ui| {
             PieceFilterWidget::new_uppercased(
                 "pieces with all these colors",
-                piece_subset_from_sticker_colors!(puzzle_type, |colors| {
+                piece_subset_from_sticker_colors!(&*desc, |colors| {
                     selected_colors.iter().enumerate().all(|(i, selected)| {
                         !selected || colors.clone().any(|color| color == Face(i as _))
                     })
@@ -123,7 +124,7 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
 
             PieceFilterWidget::new_uppercased(
                 "pieces with any of these colors",
-                piece_subset_from_sticker_colors!(puzzle_type, |colors| {
+                piece_subset_from_sticker_colors!(&*desc, |colors| {
                     colors.any(|c| selected_colors[c.0 as usize])
                 }),
             )
@@ -131,7 +132,7 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
 
             PieceFilterWidget::new_uppercased(
                 "pieces with only these colors",
-                piece_subset_from_sticker_colors!(puzzle_type, |colors| {
+                piece_subset_from_sticker_colors!(&*desc, |colors| {
                     colors.all(|c| selected_colors[c.0 as usize])
                 }),
             )
@@ -141,6 +142,10 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
         ui.data().insert_temp(colors_selection_id, selected_colors);
     });
 
+    if puzzle_type.projection_type() == ProjectionType::_4D {
+        ui.collapsing("Cells", |ui| build_cells_section(ui, app));
+    }
+
     ui.collapsing("Presets", |ui| {
         ui.set_enabled(!app.prefs.colors.blindfold);
 
@@ -188,6 +193,8 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
 
         app.prefs.needs_save |= changed;
     });
+
+    ui.collapsing("Combine", |ui| build_combine_section(ui, app));
 }
 
 #[must_use]
@@ -325,3 +332,258 @@ where
         .response
     }
 }
+
+/// Shows toggles for hiding whole cells, regardless of which pieces are
+/// hidden. Cells are positions, so a hidden cell stays hidden as the puzzle is
+/// twisted.
+fn build_cells_section(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let desc = puzzle_type.desc();
+    let face_colors = app.prefs.colors.face_colors_list(puzzle_type);
+
+    let mut changed = false;
+    for (i, face) in desc.faces().iter().enumerate() {
+        let cell = Face(i as _);
+        // Each cell of a 4D puzzle has a twist axis with the same index.
+        let opposite = puzzle_type
+            .opposite_twist_axis(TwistAxis(i as _))
+            .map(|axis| Face(axis.0));
+
+        ui.horizontal(|ui| {
+            let mut shown = !app.puzzle.hidden_cells()[i];
+            egui::color_picker::show_color(ui, face_colors[i], ui.spacing().interact_size);
+            if ui.checkbox(&mut shown, face.name).changed() {
+                app.puzzle.set_cell_hidden(cell, !shown);
+                changed = true;
+            }
+            if let Some(opposite) = opposite {
+                let r = ui.small_button("Only this pair").on_hover_text(format!(
+                    "Show only {} and {}",
+                    face.name,
+                    desc.info(opposite).name,
+                ));
+                if r.clicked() {
+                    app.puzzle.show_only_cells(&[cell, opposite]);
+                    changed = true;
+                }
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("Show all").clicked() {
+            app.puzzle.show_all_cells();
+            changed = true;
+        }
+        if let Some(i) = desc.faces().iter().position(|f| f.symbol == "I") {
+            if ui.button("Hide inner cell").clicked() {
+                app.puzzle.set_cell_hidden(Face(i as _), true);
+                changed = true;
+            }
+        }
+    });
+
+    if changed {
+        app.request_redraw_puzzle();
+    }
+}
+
+/// Set of pieces that can be combined with others in the "Combine" section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FilterTerm {
+    PieceType(PieceType),
+    Color(Face),
+    Preset(String),
+}
+impl FilterTerm {
+    fn label(&self, app: &App) -> String {
+        match self {
+            FilterTerm::PieceType(piece_type) => {
+                let mut s = format!("{}s", app.puzzle.info(*piece_type).name);
+                s[0..1].make_ascii_uppercase();
+                s
+            }
+            FilterTerm::Color(face) => app.puzzle.info(*face).name.to_string(),
+            FilterTerm::Preset(name) => name.clone(),
+        }
+    }
+
+    fn piece_set(&self, app: &App) -> BitVec {
+        let puzzle_type = app.puzzle.ty();
+        match self {
+            FilterTerm::PieceType(piece_type) => {
+                piece_subset(&app.puzzle, |piece| piece.piece_type == *piece_type)
+            }
+            FilterTerm::Color(face) => {
+                piece_subset_from_sticker_colors!(&app.puzzle, |colors| colors.any(|c| c == *face))
+            }
+            // A preset that has since been deleted matches nothing.
+            FilterTerm::Preset(name) => {
+                let mut set = app.prefs.piece_filters[puzzle_type]
+                    .iter()
+                    .find(|preset| preset.preset_name == *name)
+                    .map(|preset| preset.value.visible_pieces.clone())
+                    .unwrap_or_default();
+                set.resize(app.puzzle.pieces().len(), false);
+                set
+            }
+        }
+    }
+}
+
+/// Term of a combined filter, shown as a chip that can be clicked to negate
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FilterChip {
+    term: FilterTerm,
+    negated: bool,
+}
+
+/// Filter that matches the pieces matching every chip in any one of its
+/// groups.
+#[derive(Debug, Default, Clone)]
+struct CombinedFilter {
+    puzzle_type: Option<PuzzleTypeEnum>,
+    groups: Vec<Vec<FilterChip>>,
+}
+impl CombinedFilter {
+    fn new(puzzle_type: PuzzleTypeEnum) -> Self {
+        Self {
+            puzzle_type: Some(puzzle_type),
+            groups: vec![vec![]],
+        }
+    }
+
+    /// Returns the pieces matched by the filter, or `None` if it is empty.
+    fn piece_set(&self, app: &App) -> Option<BitVec> {
+        let piece_count = app.puzzle.pieces().len();
+        let mut ret: Option<BitVec> = None;
+        for group in self.groups.iter().filter(|group| !group.is_empty()) {
+            let mut group_set = BitVec::repeat(true, piece_count);
+            for chip in group {
+                let mut set = chip.term.piece_set(app);
+                if chip.negated {
+                    set = !set;
+                }
+                group_set = group_set & set.as_bitslice();
+            }
+            ret = Some(match ret {
+                Some(set) => set | group_set.as_bitslice(),
+                None => group_set,
+            });
+        }
+        ret
+    }
+}
+
+/// Shows a builder for filters that combine piece types, colors, and presets
+/// with AND, OR, and NOT.
+fn build_combine_section(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_enabled(!app.prefs.colors.blindfold);
+
+    let puzzle_type = app.puzzle.ty();
+    let desc = puzzle_type.desc();
+
+    let filter_id = unique_id!();
+    let mut filter: CombinedFilter = ui.data().get_temp(filter_id).unwrap_or_default();
+    if filter.puzzle_type != Some(puzzle_type) {
+        filter = CombinedFilter::new(puzzle_type);
+    }
+
+    let group_count = filter.groups.len();
+    let mut remove_group = None;
+    for (i, group) in filter.groups.iter_mut().enumerate() {
+        if i > 0 {
+            ui.strong("OR");
+        }
+        ui.horizontal_wrapped(|ui| {
+            let mut remove_chip = None;
+            for (j, chip) in group.iter_mut().enumerate() {
+                if j > 0 {
+                    ui.label("AND");
+                }
+                let label = chip.term.label(app);
+                let text = match chip.negated {
+                    true => format!("NOT {label}"),
+                    false => label,
+                };
+                let r = ui.button(text).on_hover_text("Click to toggle NOT");
+                if r.hovered() {
+                    let preview = chip.term.piece_set(app);
+                    app.puzzle.set_visible_pieces_preview(Some(&preview), None);
+                }
+                if r.clicked() {
+                    chip.negated = !chip.negated;
+                }
+                if small_icon_button(ui, "ｘ", "Remove").clicked() {
+                    remove_chip = Some(j);
+                }
+            }
+            if let Some(j) = remove_chip {
+                group.remove(j);
+            }
+
+            ui.menu_button("➕", |ui| {
+                let mut add_button = |ui: &mut egui::Ui, term: FilterTerm| {
+                    if ui.button(term.label(app)).clicked() {
+                        group.push(FilterChip {
+                            term,
+                            negated: false,
+                        });
+                        ui.close_menu();
+                    }
+                };
+
+                ui.strong("Types");
+                for j in 0..desc.piece_types().len() {
+                    add_button(ui, FilterTerm::PieceType(PieceType(j as _)));
+                }
+                ui.separator();
+                ui.strong("Colors");
+                for j in 0..desc.faces().len() {
+                    add_button(ui, FilterTerm::Color(Face(j as _)));
+                }
+                let presets = &app.prefs.piece_filters[puzzle_type];
+                if !presets.is_empty() {
+                    ui.separator();
+                    ui.strong("Presets");
+                    for preset in presets {
+                        add_button(ui, FilterTerm::Preset(preset.preset_name.clone()));
+                    }
+                }
+            })
+            .response
+            .on_hover_text("Add a term");
+
+            if group_count > 1 && small_icon_button(ui, "🗑", "Remove group").clicked() {
+                remove_group = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove_group {
+        filter.groups.remove(i);
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("➕ OR").clicked() {
+            filter.groups.push(vec![]);
+        }
+        if ui.button("Clear").clicked() {
+            filter = CombinedFilter::new(puzzle_type);
+        }
+    });
+
+    let piece_set = filter.piece_set(app);
+    ui.data().insert_temp(filter_id, filter);
+
+    ui.separator();
+
+    match piece_set {
+        Some(piece_set) => {
+            PieceFilterWidget::new_uppercased("matching pieces", piece_set).show(ui, app);
+        }
+        None => {
+            ui.label("Add terms to build a filter.");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use super::Window;
+use crate::app::App;
+use crate::gui::components::PrefsUi;
+use crate::preferences::DEFAULT_PREFS;
+use crate::puzzle::*;
+
+pub(crate) const PIECE_TRACKING: Window = Window {
+    name: "Piece tracking",
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.label("Select pieces and track them to show where they move.");
+
+    let mut selected_pieces = app
+        .puzzle
+        .selection()
+        .iter()
+        .map(|&sticker| app.puzzle.info(sticker).piece)
+        .collect::<Vec<_>>();
+    selected_pieces.sort_by_key(|piece| piece.0);
+    selected_pieces.dedup();
+
+    ui.horizontal(|ui| {
+        let r = ui.add_enabled(
+            !selected_pieces.is_empty(),
+            egui::Button::new("Track selected pieces"),
+        );
+        if r.clicked() {
+            app.puzzle.set_tracked_pieces(selected_pieces);
+            app.request_redraw_puzzle();
+        }
+    });
+
+    let tracked_count = app.puzzle.tracked_pieces().len();
+    ui.label(match tracked_count {
+        0 => "No pieces tracked.".to_string(),
+        1 => "Tracking 1 piece.".to_string(),
+        n => format!("Tracking {n} pieces."),
+    });
+    ui.add_enabled_ui(tracked_count > 0, |ui| {
+        ui.horizontal(|ui| {
+            if ui.button("Clear trails").clicked() {
+                app.puzzle.clear_trails();
+                app.request_redraw_puzzle();
+            }
+            if ui.button("Stop tracking").clicked() {
+                app.puzzle.set_tracked_pieces(vec![]);
+                app.request_redraw_puzzle();
+            }
+        });
+    });
+
+    ui.separator();
+
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.tracking,
+        defaults: &DEFAULT_PREFS.tracking,
+        changed: &mut changed,
+    };
+
+    prefs_ui
+        .num("Trail length", access!(.trail_length), |dv| {
+            dv.clamp_range(1..=MAX_TRAIL_LENGTH)
+        })
+        .on_hover_text("Number of twists shown in each trail");
+    prefs_ui.num("Trail size", access!(.trail_size), |dv| {
+        dv.fixed_decimals(1).clamp_range(0.5..=5.0_f32).speed(0.01)
+    });
+    prefs_ui.color("Trail color", access!(.trail_color));
+
+    prefs.needs_save |= changed;
+    if changed {
+        app.request_redraw_puzzle();
+    }
+}
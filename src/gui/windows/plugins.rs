@@ -0,0 +1,125 @@
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::commands::Command;
+use crate::plugins::Plugins;
+use crate::puzzle::ScrambleState;
+
+pub(crate) const PLUGINS: Window = Window {
+    name: "Plugins",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    match Plugins::dir() {
+        Ok(dir) => {
+            ui.label("Plugins are loaded from:");
+            ui.code(dir.display().to_string());
+        }
+        Err(e) => {
+            ui.colored_label(egui::Color32::RED, format!("No plugins folder: {e}"));
+        }
+    }
+    if ui.button("Reload plugins").clicked() {
+        app.plugins.reload(&app.prefs.plugins.disabled);
+    }
+    ui.separator();
+
+    if app.plugins.loaded.is_empty()
+        && app.plugins.failed.is_empty()
+        && app.plugins.disabled.is_empty()
+    {
+        ui.label("No plugins");
+        return;
+    }
+
+    // Name of a plugin to enable or disable.
+    let mut toggled = None;
+
+    for i in 0..app.plugins.loaded.len() {
+        let plugin = &app.plugins.loaded[i];
+        let name = plugin.name.clone();
+        let commands = plugin.commands.clone();
+        let stats_exporters = plugin.stats_exporters.clone();
+        let scramble_generators = plugin.scramble_generators.clone();
+        let error = plugin.error.clone();
+
+        if ui.checkbox(&mut true, &name).changed() {
+            toggled = Some(name.clone());
+        }
+        ui.indent(&name, |ui| {
+            for command in commands {
+                let r = ui
+                    .button(format!("▶ {command}"))
+                    .on_hover_text("Run this command. It can also be bound to a key.");
+                if r.clicked() {
+                    app.event(Command::Plugin {
+                        plugin: name.clone(),
+                        command,
+                    });
+                }
+            }
+            for exporter in stats_exporters {
+                let r = ui
+                    .button(format!("🗐 {exporter}"))
+                    .on_hover_text("Copy stats about the puzzle to the clipboard");
+                if r.clicked() {
+                    if let Ok(text) = app.plugins.export_stats(&name, &exporter, &app.puzzle) {
+                        ui.output().copied_text = text;
+                    }
+                }
+            }
+            for generator in scramble_generators {
+                let r = ui
+                    .button(format!("🔀 {generator}"))
+                    .on_hover_text("Reset the puzzle and scramble it");
+                if r.clicked() {
+                    let result =
+                        app.plugins
+                            .generate_scramble(&name, &generator, rand_seed(), &app.puzzle);
+                    if let Ok(twists) = result {
+                        app.event(AppEvent::Scramble(twists, ScrambleState::Partial));
+                    }
+                }
+            }
+            if let Some(e) = error {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        });
+    }
+
+    for (name, e) in &app.plugins.failed {
+        if ui.checkbox(&mut true, name).changed() {
+            toggled = Some(name.clone());
+        }
+        ui.indent(name, |ui| ui.colored_label(egui::Color32::RED, e));
+    }
+
+    for name in &app.plugins.disabled {
+        if ui.checkbox(&mut false, name).changed() {
+            toggled = Some(name.clone());
+        }
+    }
+
+    if let Some(name) = toggled {
+        let disabled = &mut app.prefs.plugins.disabled;
+        match disabled.iter().position(|n| *n == name) {
+            Some(i) => {
+                disabled.remove(i);
+            }
+            None => disabled.push(name),
+        }
+        app.prefs.needs_save = true;
+        app.plugins.reload(&app.prefs.plugins.disabled);
+    }
+}
+
+/// Returns a random seed for a scramble generator.
+fn rand_seed() -> i64 {
+    let mut bytes = [0; 8];
+    if let Err(e) = getrandom::getrandom(&mut bytes) {
+        log::warn!("Error generating random seed: {}", e);
+    }
+    i64::from_le_bytes(bytes)
+}
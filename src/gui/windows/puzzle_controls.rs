@@ -18,6 +18,7 @@ fn cleanup(_ctx: &egui::Context, app: &mut App) {
 
 fn build(ui: &mut egui::Ui, app: &mut App) {
     let puzzle_type = app.puzzle.ty();
+    let desc = puzzle_type.desc();
 
     let grip = app.grip();
 
@@ -29,7 +30,7 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
     ui.strong("Twist axis");
     ui.with_layout(h_layout, |ui| {
         reset_button(ui, &mut app.toggle_grip.axes, Grip::default().axes, "");
-        for (i, twist_axis) in puzzle_type.twist_axes().iter().enumerate() {
+        for (i, twist_axis) in desc.twist_axes().iter().enumerate() {
             let mut is_sel = grip.axes.contains(&TwistAxis(i as _));
             let r = ui.selectable_value(&mut is_sel, true, twist_axis.name);
             if r.changed() {
@@ -42,16 +43,20 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
     ui.separator();
 
     ui.strong("Layers");
-    ui.with_layout(h_layout, |ui| {
-        reset_button(ui, &mut app.toggle_grip.layers, Grip::default().layers, "");
-        for i in 0..puzzle_type.layer_count() {
-            let mut is_sel = grip.layers.unwrap_or_default()[i];
-            let r = ui.selectable_value(&mut is_sel, true, format!("{}", i + 1));
-            if r.changed() {
-                app.toggle_grip.toggle_layer(i, false);
+    if puzzle_type.layer_count() == 1 {
+        ui.label("Every twist rotates the whole puzzle.");
+    } else {
+        ui.with_layout(h_layout, |ui| {
+            reset_button(ui, &mut app.toggle_grip.layers, Grip::default().layers, "");
+            for i in 0..puzzle_type.layer_count() {
+                let mut is_sel = grip.layers.unwrap_or_default()[i];
+                let r = ui.selectable_value(&mut is_sel, true, format!("{}", i + 1));
+                if r.changed() {
+                    app.toggle_grip.toggle_layer(i, false);
+                }
             }
-        }
-    });
+        });
+    }
 
     ui.separator();
 
@@ -61,7 +66,7 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
     ui.strong("Twist");
     ui.add_enabled_ui(can_twist, |ui| {
         ui.with_layout(h_layout, |ui| {
-            for (i, twist_direction) in puzzle_type.twist_directions().iter().enumerate() {
+            for (i, twist_direction) in desc.twist_directions().iter().enumerate() {
                 if ui.button(twist_direction.name).clicked() {
                     if let Ok(axis) = twist_axis {
                         // should always be `Ok`
@@ -0,0 +1,93 @@
+use super::Window;
+use crate::app::App;
+use crate::puzzle::{gods_number, log10_state_count, traits::*, PieceType, ScrambleState};
+
+pub(crate) const PUZZLE_INFO: Window = Window {
+    name: "Puzzle info",
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let ty = app.puzzle.ty();
+
+    ui.strong(ty.name());
+    ui.separator();
+
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        ui.strong("Piece type");
+        ui.strong("Pieces");
+        ui.strong("Stickers");
+        ui.end_row();
+
+        for (i, piece_type) in app.puzzle.piece_types().iter().enumerate() {
+            let pieces = app
+                .puzzle
+                .pieces()
+                .iter()
+                .filter(|piece| piece.piece_type == PieceType(i as _));
+            let (piece_count, sticker_count) =
+                pieces.fold((0, 0), |(p, s), piece| (p + 1, s + piece.stickers.len()));
+            ui.label(&piece_type.name);
+            ui.label(piece_count.to_string());
+            ui.label(sticker_count.to_string());
+            ui.end_row();
+        }
+
+        ui.strong("Total");
+        ui.strong(app.puzzle.pieces().len().to_string());
+        ui.strong(app.puzzle.stickers().len().to_string());
+        ui.end_row();
+    });
+
+    ui.separator();
+
+    egui::Grid::new(unique_id!()).show(ui, |ui| {
+        ui.label("States");
+        match log10_state_count(ty) {
+            Some(log10) => ui.label(format_state_count(log10)),
+            None => ui.label("Unknown"),
+        }
+        .on_hover_text("Number of reachable states, not counting rotations");
+        ui.end_row();
+
+        ui.label("God's number");
+        let gods_number = gods_number(ty);
+        if gods_number.is_empty() {
+            ui.label("Unknown");
+        } else {
+            let s = gods_number
+                .iter()
+                .map(|(metric, n)| format!("{n} {metric}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            ui.label(s)
+                .on_hover_text("Twists needed to solve the hardest state");
+        }
+        ui.end_row();
+
+        ui.label("Scramble");
+        let scramble_len = app.puzzle.scramble().len();
+        ui.label(match app.puzzle.scramble_state() {
+            ScrambleState::None => "None".to_string(),
+            ScrambleState::Partial => format!("{scramble_len} twists (partial)"),
+            ScrambleState::Full | ScrambleState::Solved => {
+                let full_len = ty.scramble_moves_count();
+                format!("{scramble_len} twists (full scramble is {full_len})")
+            }
+        });
+        ui.end_row();
+    });
+}
+
+/// Formats a number of states given its base-10 logarithm, using scientific
+/// notation for large numbers.
+fn format_state_count(log10: f64) -> String {
+    if log10 < 9.0 {
+        format!("{}", 10_f64.powf(log10).round())
+    } else {
+        let exponent = log10.floor();
+        let mantissa = 10_f64.powf(log10 - exponent);
+        format!("{mantissa:.2} × 10^{exponent}")
+    }
+}
@@ -0,0 +1,65 @@
+use super::Window;
+use crate::app::App;
+use crate::gui::components::PrefsUi;
+use crate::preferences::{ScrambleOptions, DEFAULT_PREFS};
+use crate::puzzle::traits::*;
+
+pub(crate) const SCRAMBLE_OPTIONS: Window = Window {
+    name: "Scramble options",
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let desc = puzzle_type.desc();
+
+    let prefs = &mut app.prefs;
+    let options = &mut prefs.scramble[puzzle_type];
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: options,
+        defaults: &DEFAULT_PREFS.scramble[puzzle_type],
+        changed: &mut changed,
+    };
+
+    // Puzzles with two layers or fewer are always scrambled using only their
+    // outer layers.
+    if puzzle_type.layer_count() > 2 {
+        prefs_ui.checkbox("Outer layers only", access!(.outer_layers_only));
+    }
+    prefs_ui.checkbox("Corners only", access!(.corners_only));
+
+    ui.separator();
+
+    ui.label("Allowed twist axes:");
+    ui.horizontal_wrapped(|ui| {
+        for axis in desc.essential_twist_axes() {
+            let name = desc.info(axis).name;
+            let mut allowed = !options.excluded_axes.iter().any(|a| a == name);
+            if ui.checkbox(&mut allowed, name).changed() {
+                if allowed {
+                    options.excluded_axes.retain(|a| a != name);
+                } else {
+                    options.excluded_axes.push(name.to_string());
+                }
+                changed = true;
+            }
+        }
+    });
+
+    ui.separator();
+
+    let r = ui.add_enabled(options.is_restricted(), egui::Button::new("Reset"));
+    if r.clicked() {
+        *options = ScrambleOptions::default();
+        changed = true;
+    }
+    if options.is_restricted() {
+        ui.label("Scrambles using these options never count as full scrambles.");
+    }
+
+    prefs.needs_save |= changed;
+}
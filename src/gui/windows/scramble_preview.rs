@@ -0,0 +1,89 @@
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::puzzle::*;
+
+pub(crate) const SCRAMBLE_PREVIEW: Window = Window {
+    name: "Scramble preview",
+    build,
+    ..Window::DEFAULT
+};
+
+const SCRAMBLE_TEXT_WIDTH: f32 = 300.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+
+    // Keep separate state per puzzle type so that switching puzzles doesn't
+    // leave behind a scramble in the wrong notation.
+    let len_id = unique_id!(puzzle_type);
+    let text_id = unique_id!(puzzle_type);
+
+    let mut len: usize = ui
+        .data()
+        .get_temp(len_id)
+        .unwrap_or_else(|| puzzle_type.scramble_moves_count());
+    let mut text: Option<String> = ui.data().get_temp(text_id);
+
+    ui.horizontal(|ui| {
+        ui.add(egui::DragValue::new(&mut len).clamp_range(1..=MAX_SCRAMBLE_LEN));
+        ui.label("moves");
+        if ui.button("Full").clicked() {
+            len = puzzle_type.scramble_moves_count();
+        }
+        if ui.button("🔀 Regenerate").clicked() {
+            text = None;
+        }
+    });
+
+    let options = &app.prefs.scramble[puzzle_type];
+    let mut text = match text {
+        Some(text) => text,
+        None => match app.puzzle.generate_scramble(len, options) {
+            Ok(twists) => puzzle_type.twists_to_string(&twists),
+            Err(e) => {
+                ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+                ui.data().insert_temp(len_id, len);
+                return;
+            }
+        },
+    };
+
+    ui.add(
+        egui::TextEdit::multiline(&mut text)
+            .code_editor()
+            .desired_width(SCRAMBLE_TEXT_WIDTH),
+    );
+
+    let parsed = puzzle_type.parse_twists(&text);
+
+    ui.horizontal(|ui| {
+        if ui.button("🗐 Copy").clicked() {
+            ui.output().copied_text = text.clone();
+        }
+        match &parsed {
+            Ok(twists) => {
+                if ui.button("Apply").clicked() {
+                    let is_full = twists.len() >= puzzle_type.scramble_moves_count()
+                        && !options.is_restricted();
+                    let new_scramble_state = if is_full {
+                        ScrambleState::Full
+                    } else {
+                        ScrambleState::Partial
+                    };
+                    app.event(AppEvent::Scramble(twists.clone(), new_scramble_state));
+                }
+                ui.label(match twists.len() {
+                    1 => "1 move".to_string(),
+                    n => format!("{n} moves"),
+                });
+            }
+            Err(e) => {
+                ui.add_enabled(false, egui::Button::new("Apply"));
+                ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+            }
+        }
+    });
+
+    ui.data().insert_temp(len_id, len);
+    ui.data().insert_temp(text_id, text);
+}
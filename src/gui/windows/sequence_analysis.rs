@@ -0,0 +1,76 @@
+use super::Window;
+use crate::app::App;
+use crate::puzzle::*;
+
+pub(crate) const SEQUENCE_ANALYSIS: Window = Window {
+    name: "Sequence analysis",
+    build,
+    ..Window::DEFAULT
+};
+
+const SEQUENCE_TEXT_WIDTH: f32 = 300.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+
+    let text_id = unique_id!(puzzle_type);
+    let analysis_id = unique_id!(puzzle_type);
+
+    let mut text: String = ui.data().get_temp(text_id).unwrap_or_default();
+
+    ui.label("Enter a twist sequence to see its effect on a solved puzzle.");
+
+    ui.add(
+        egui::TextEdit::multiline(&mut text)
+            .code_editor()
+            .desired_width(SEQUENCE_TEXT_WIDTH),
+    );
+
+    ui.horizontal(|ui| {
+        if ui.button("📋 Use current solution").clicked() {
+            let twists = app
+                .puzzle
+                .undo_buffer()
+                .iter()
+                .copied()
+                .filter_map(HistoryEntry::twist)
+                .collect::<Vec<_>>();
+            text = puzzle_type.twists_to_string(&twists);
+        }
+        if ui.button("Use scramble").clicked() {
+            text = puzzle_type.twists_to_string(app.puzzle.scramble());
+        }
+    });
+
+    ui.separator();
+
+    // Analysis requires applying the whole sequence to a fresh puzzle, so
+    // only redo it when the text changes.
+    let cached: Option<(String, Result<SequenceAnalysis, String>)> =
+        ui.data().get_temp(analysis_id);
+    let analysis = match cached {
+        Some((cached_text, analysis)) if cached_text == text => analysis,
+        _ => {
+            let analysis = puzzle_type.parse_twists(&text).and_then(|twists| {
+                SequenceAnalysis::new(puzzle_type, &twists).map_err(|e| e.to_string())
+            });
+            ui.data()
+                .insert_temp(analysis_id, (text.clone(), analysis.clone()));
+            analysis
+        }
+    };
+
+    match analysis {
+        Ok(analysis) => {
+            ui.strong(analysis.to_string());
+            for line in analysis.summary(puzzle_type) {
+                ui.label(format!("• {line}"));
+            }
+        }
+        Err(e) => {
+            ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+        }
+    }
+
+    ui.data().insert_temp(text_id, text);
+}
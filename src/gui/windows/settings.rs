@@ -15,9 +15,18 @@ pub(crate) const APPEARANCE_SETTINGS: Window = Window {
         ui.collapsing("Opacity", |ui| {
             prefs::build_opacity_section(ui, app);
         });
+        ui.collapsing("Overlays", |ui| {
+            prefs::build_overlays_section(ui, app);
+        });
+        ui.collapsing("Cursor", |ui| {
+            prefs::build_cursor_section(ui, app);
+        });
         ui.collapsing("Performance", |ui| {
             prefs::build_graphics_section(ui, app);
         });
+        ui.collapsing("Window", |ui| {
+            prefs::build_window_section(ui, app);
+        });
     },
     ..Window::DEFAULT
 };
@@ -0,0 +1,86 @@
+use super::Window;
+use crate::app::App;
+use crate::puzzle::*;
+
+pub(crate) const SOLUTION_TRANSFORMS: Window = Window {
+    name: "Solution transforms",
+    build,
+    ..Window::DEFAULT
+};
+
+const TRANSFORM_TEXT_WIDTH: f32 = 300.0;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum Transform {
+    #[default]
+    Inverse,
+    Mirror(TwistAxis),
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let desc = puzzle_type.desc();
+
+    let transform_id = unique_id!(puzzle_type);
+    let mut transform: Transform = ui.data().get_temp(transform_id).unwrap_or_default();
+
+    ui.horizontal_wrapped(|ui| {
+        ui.selectable_value(&mut transform, Transform::Inverse, "Inverse");
+        // Only list each mirror plane once.
+        for (i, axis_info) in desc.twist_axes().iter().enumerate() {
+            let axis = TwistAxis(i as _);
+            match desc.opposite_twist_axis(axis) {
+                Some(opposite) if opposite.0 > axis.0 => {
+                    let label = format!("Mirror {} ↔ {}", axis_info.name, desc.info(opposite).name);
+                    ui.selectable_value(&mut transform, Transform::Mirror(axis), label);
+                }
+                _ => (),
+            }
+        }
+    });
+
+    ui.data().insert_temp(transform_id, transform);
+
+    let solution = app
+        .puzzle
+        .undo_buffer()
+        .iter()
+        .filter_map(|&entry| entry.twist())
+        .collect::<Vec<_>>();
+    let transformed = match transform {
+        Transform::Inverse => solution
+            .iter()
+            .rev()
+            .map(|&twist| desc.reverse_twist(twist))
+            .collect::<Vec<_>>(),
+        Transform::Mirror(axis) => solution
+            .iter()
+            .map(|&twist| desc.mirror_twist(twist, axis))
+            .collect(),
+    };
+    let text = desc.twists_to_string(&transformed);
+
+    ui.separator();
+
+    if transformed.is_empty() {
+        ui.label("No twists to transform.");
+        return;
+    }
+
+    ui.add(
+        egui::TextEdit::multiline(&mut text.as_str())
+            .code_editor()
+            .desired_width(TRANSFORM_TEXT_WIDTH),
+    );
+
+    ui.horizontal(|ui| {
+        if ui.button("🗐 Copy").clicked() {
+            ui.output().copied_text = text.clone();
+        }
+        if ui.button("Apply").clicked() {
+            for twist in transformed {
+                app.event(twist);
+            }
+        }
+    });
+}
@@ -0,0 +1,265 @@
+use egui::plot::{HLine, Line, Plot, PlotPoints, VLine};
+use instant::Duration;
+
+use super::Window;
+use crate::app::App;
+
+pub(crate) const SOLVE_STATS: Window = Window {
+    name: "Solve stats",
+    build,
+    ..Window::DEFAULT
+};
+
+const PLOT_WIDTH: f32 = 360.0;
+const PLOT_HEIGHT: f32 = 160.0;
+
+/// Width of the sliding window used to compute TPS at each point.
+const TPS_WINDOW: Duration = Duration::from_secs(3);
+/// Minimum time between consecutive twists to count as a pause.
+const PAUSE_THRESHOLD: Duration = Duration::from_secs(2);
+/// Maximum number of pauses to list below the graph.
+const MAX_LISTED_PAUSES: usize = 10;
+/// Move budget suggested when enabling a move goal.
+const DEFAULT_MOVE_BUDGET: usize = 100;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    build_timing(ui, app);
+    ui.separator();
+    build_splits(ui, app);
+    ui.separator();
+    ui.collapsing("Phases", |ui| build_phases(ui, app));
+    ui.collapsing("Move goal", |ui| build_move_goal(ui, app));
+}
+
+fn build_timing(ui: &mut egui::Ui, app: &mut App) {
+    // Pair each known time with the number of twists made up to that point.
+    let times: Vec<(usize, Duration)> = app
+        .puzzle
+        .twist_times()
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| Some((i + 1, (*t)?)))
+        .collect();
+
+    let Some(&(_, last)) = times.last() else {
+        ui.label("No twist times recorded.");
+        return;
+    };
+
+    let total_secs = last.as_secs_f64();
+    if total_secs > 0.0 {
+        ui.label(format!(
+            "{} moves in {:.2}s ({:.2} TPS)",
+            times.len(),
+            total_secs,
+            times.len() as f64 / total_secs,
+        ));
+    } else {
+        ui.label(format!("{} moves", times.len()));
+    }
+
+    let tps_points: Vec<[f64; 2]> = times
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, t))| {
+            let window_start = t.saturating_sub(TPS_WINDOW);
+            let count = times[..=i]
+                .iter()
+                .rev()
+                .take_while(|&&(_, t2)| t2 > window_start)
+                .count();
+            // The window is shorter at the start of the solve.
+            let window = (t - window_start).as_secs_f64();
+            let tps = if window > 0.0 {
+                count as f64 / window
+            } else {
+                0.0
+            };
+            [t.as_secs_f64(), tps]
+        })
+        .collect();
+
+    let pauses: Vec<(usize, Duration, Duration)> = times
+        .windows(2)
+        .filter_map(|w| {
+            let (n, start) = w[0];
+            let (_, end) = w[1];
+            (end - start >= PAUSE_THRESHOLD).then_some((n, start, end - start))
+        })
+        .collect();
+
+    Plot::new(unique_id!())
+        .width(PLOT_WIDTH)
+        .height(PLOT_HEIGHT)
+        .include_x(0.0)
+        .include_y(0.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            for &(_, start, _) in &pauses {
+                plot_ui.vline(VLine::new(start.as_secs_f64()).color(egui::Color32::RED));
+            }
+            for split in app.puzzle.phase_splits() {
+                if let Some(t) = split.time {
+                    plot_ui.vline(VLine::new(t.as_secs_f64()).color(egui::Color32::GREEN));
+                }
+            }
+            if total_secs > 0.0 {
+                let average = times.len() as f64 / total_secs;
+                plot_ui.hline(HLine::new(average).color(egui::Color32::GRAY));
+            }
+            plot_ui.line(Line::new(PlotPoints::new(tps_points)).name("TPS"));
+        });
+
+    if pauses.is_empty() {
+        ui.label("No pauses.");
+    } else {
+        ui.label(format!(
+            "Pauses longer than {}s:",
+            PAUSE_THRESHOLD.as_secs(),
+        ));
+        for &(n, start, length) in pauses.iter().take(MAX_LISTED_PAUSES) {
+            ui.label(format!(
+                "• {:.1}s after move {n} (at {:.1}s)",
+                length.as_secs_f64(),
+                start.as_secs_f64(),
+            ));
+        }
+        if pauses.len() > MAX_LISTED_PAUSES {
+            ui.label(format!("… and {} more", pauses.len() - MAX_LISTED_PAUSES));
+        }
+    }
+}
+
+fn build_splits(ui: &mut egui::Ui, app: &mut App) {
+    let splits = app.puzzle.phase_splits();
+    if splits.is_empty() {
+        ui.label("No phases completed.");
+        return;
+    }
+
+    let metric = app.prefs.info.metric;
+    let twists = app.puzzle.undo_buffer();
+
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        ui.strong("Phase");
+        ui.strong(format!("Moves ({metric})"));
+        ui.strong("Time");
+        ui.strong("Split");
+        ui.end_row();
+
+        let mut last_time = Some(Duration::ZERO);
+        for split in splits {
+            let moves = metric.count_twists(
+                &app.puzzle,
+                twists[..split.twist_count]
+                    .iter()
+                    .filter_map(|&entry| entry.twist()),
+            );
+
+            ui.label(split.name.as_str());
+            ui.label(moves.to_string());
+            match split.time {
+                Some(t) => ui.label(format!("{:.2}s", t.as_secs_f64())),
+                None => ui.label("-"),
+            };
+            match last_time.zip(split.time) {
+                Some((prev, t)) => ui.label(format!("+{:.2}s", (t - prev).as_secs_f64())),
+                None => ui.label("-"),
+            };
+            ui.end_row();
+
+            last_time = split.time;
+        }
+    });
+}
+
+fn build_phases(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let presets = &app.prefs.piece_filters[puzzle_type];
+
+    if presets.is_empty() {
+        ui.label("Save a piece filter preset to use it as a phase.");
+        return;
+    }
+
+    ui.label(
+        "A phase is complete when all the pieces in \
+         its piece filter are solved.",
+    );
+
+    let phases = &app.prefs.phases[puzzle_type];
+    let mut enabled: Vec<bool> = presets
+        .iter()
+        .map(|preset| phases.contains(&preset.preset_name))
+        .collect();
+
+    let mut changed = false;
+    for (preset, is_phase) in presets.iter().zip(&mut enabled) {
+        changed |= ui.checkbox(is_phase, &preset.preset_name).changed();
+    }
+
+    if changed {
+        // Keep phases in the same order as the piece filter presets.
+        let new_phases = presets
+            .iter()
+            .zip(enabled)
+            .filter(|(_, is_phase)| *is_phase)
+            .map(|(preset, _)| preset.preset_name.clone())
+            .collect();
+        app.prefs.phases[puzzle_type] = new_phases;
+        app.prefs.needs_save = true;
+    }
+}
+
+fn build_move_goal(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let metric = app.prefs.info.metric;
+    let phases = app.prefs.phases[puzzle_type].clone();
+    let goal = &mut app.prefs.move_goals[puzzle_type];
+
+    ui.label(format!(
+        "Move budgets are counted in {metric}. The status bar shows how \
+         many moves are left."
+    ));
+
+    let mut changed = false;
+    egui::Grid::new(unique_id!()).show(ui, |ui| {
+        changed |= budget_edit(ui, "Whole solve", &mut goal.total);
+        ui.end_row();
+
+        for phase in &phases {
+            let mut budget = goal.phases.get(phase).copied();
+            if budget_edit(ui, phase, &mut budget) {
+                changed = true;
+                match budget {
+                    Some(n) => goal.phases.insert(phase.clone(), n),
+                    None => goal.phases.remove(phase),
+                };
+            }
+            ui.end_row();
+        }
+    });
+    if phases.is_empty() {
+        ui.label("Enable phases to set a budget for each phase.");
+    }
+
+    app.prefs.needs_save |= changed;
+}
+
+/// Shows a checkbox and a drag value to edit an optional move budget, and
+/// returns whether it was changed.
+fn budget_edit(ui: &mut egui::Ui, label: &str, budget: &mut Option<usize>) -> bool {
+    let mut enabled = budget.is_some();
+    let mut n = budget.unwrap_or(DEFAULT_MOVE_BUDGET);
+    let mut changed = ui.checkbox(&mut enabled, label).changed();
+    changed |= ui
+        .add_enabled(
+            enabled,
+            egui::DragValue::new(&mut n).clamp_range(1..=usize::MAX),
+        )
+        .changed();
+    *budget = enabled.then_some(n);
+    changed
+}
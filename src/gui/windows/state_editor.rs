@@ -0,0 +1,106 @@
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::puzzle::*;
+
+pub(crate) const STATE_EDITOR: Window = Window {
+    name: "State editor",
+    build,
+    cleanup,
+    ..Window::DEFAULT
+};
+
+fn cleanup(_ctx: &egui::Context, app: &mut App) {
+    if app.puzzle.is_painting() {
+        app.puzzle.stop_painting();
+        app.request_redraw_puzzle();
+    }
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let desc = puzzle_type.desc();
+
+    app.puzzle.start_painting();
+
+    ui.label("Click stickers to paint them.");
+
+    let face_colors = app.prefs.colors.face_colors_list(puzzle_type);
+    let mut brush = app.puzzle.paint_brush().unwrap_or_default();
+    ui.horizontal_wrapped(|ui| {
+        for (i, face) in desc.faces().iter().enumerate() {
+            let text = egui::RichText::new(format!("⏹ {}", face.symbol)).color(face_colors[i]);
+            ui.selectable_value(&mut brush, Face(i as _), text)
+                .on_hover_text(face.name);
+        }
+    });
+    app.puzzle.set_paint_brush(brush);
+
+    if ui.button("Reset colors").clicked() {
+        app.puzzle.reset_paint();
+        app.request_redraw_puzzle();
+    }
+
+    ui.separator();
+
+    match app.puzzle.painted_state() {
+        Some(Ok(state)) => {
+            ui.horizontal(|ui| {
+                if ui.button("Set as current state").clicked() {
+                    app.event(AppEvent::SetCustomState(state.clone()));
+                }
+                if ui.button("Set as training target").clicked() {
+                    app.puzzle.set_ghost_target(Some(state));
+                    app.prefs.opacity.show_ghost = true;
+                    app.prefs.needs_save = true;
+                    app.request_redraw_puzzle();
+                }
+            });
+        }
+        Some(Err(e)) => {
+            ui.label(egui::RichText::new(format!("Invalid state: {e}")).color(egui::Color32::RED));
+        }
+        None => (),
+    }
+
+    if app.puzzle.ghost_target().is_some() && ui.button("Clear training target").clicked() {
+        app.puzzle.set_ghost_target(None);
+        app.request_redraw_puzzle();
+    }
+
+    ui.separator();
+
+    ui.strong("Position code");
+    ui.label("Share a code to reproduce the current state exactly.");
+
+    let code_id = unique_id!(puzzle_type);
+    let mut code: String = ui.data().get_temp(code_id).unwrap_or_default();
+
+    ui.horizontal(|ui| {
+        if ui.button("🗐 Copy code").clicked() {
+            ui.output().copied_text = app.puzzle.state_code();
+        }
+        ui.add(egui::TextEdit::singleline(&mut code).hint_text("Paste code here"));
+    });
+
+    if !code.trim().is_empty() {
+        match decode_state(&code) {
+            Ok(state) if state.ty() != puzzle_type => {
+                let e = format!("Code is for {}", state.ty().name());
+                ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+            }
+            Ok(state) => {
+                if ui.button("Load code").clicked() {
+                    app.event(AppEvent::SetCustomState(state));
+                    code.clear();
+                }
+            }
+            Err(e) => {
+                ui.label(
+                    egui::RichText::new(format!("Invalid code: {e}")).color(egui::Color32::RED),
+                );
+            }
+        }
+    }
+
+    ui.data().insert_temp(code_id, code);
+}
@@ -0,0 +1,98 @@
+use super::Window;
+use crate::app::App;
+use crate::gui::components::PrefsUi;
+use crate::preferences::{TwistButtonsLayout, DEFAULT_PREFS};
+use crate::puzzle::*;
+
+pub(crate) const TWIST_BUTTONS: Window = Window {
+    name: "Twist buttons",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let desc = puzzle_type.desc();
+    let notation = desc.notation_scheme();
+
+    // Use the gripped layers, so that layers can be selected using the puzzle
+    // controls or keybinds.
+    let layers = app.grip().layers.unwrap_or_default();
+    let button_size = egui::Vec2::splat(app.prefs.twist_buttons.button_size);
+
+    let axes = (0..desc.twist_axes().len() as u8).map(TwistAxis);
+    let directions = (0..desc.twist_directions().len() as u8).map(TwistDirection);
+    let rows: Vec<Vec<Twist>> = match app.prefs.twist_buttons.layout {
+        TwistButtonsLayout::AxesInRows => axes
+            .map(|axis| {
+                directions
+                    .clone()
+                    .map(|direction| Twist {
+                        axis,
+                        direction,
+                        layers,
+                    })
+                    .collect()
+            })
+            .collect(),
+        TwistButtonsLayout::DirectionsInRows => directions
+            .map(|direction| {
+                axes.clone()
+                    .map(|axis| Twist {
+                        axis,
+                        direction,
+                        layers,
+                    })
+                    .collect()
+            })
+            .collect(),
+    };
+
+    ui.add_enabled_ui(layers != LayerMask(0), |ui| {
+        egui::Grid::new(unique_id!()).show(ui, |ui| {
+            for row in rows {
+                for twist in row {
+                    let label = notation.twist_to_string(twist);
+                    if ui
+                        .add_sized(button_size, egui::Button::new(label))
+                        .clicked()
+                    {
+                        app.event(twist);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    });
+
+    ui.separator();
+
+    let prefs = &mut app.prefs;
+
+    let mut layout = prefs.twist_buttons.layout;
+    ui.horizontal(|ui| {
+        ui.label("Layout:");
+        ui.selectable_value(&mut layout, TwistButtonsLayout::AxesInRows, "Axes in rows");
+        ui.selectable_value(
+            &mut layout,
+            TwistButtonsLayout::DirectionsInRows,
+            "Directions in rows",
+        );
+    });
+
+    let mut changed = layout != prefs.twist_buttons.layout;
+    prefs.twist_buttons.layout = layout;
+
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.twist_buttons,
+        defaults: &DEFAULT_PREFS.twist_buttons,
+        changed: &mut changed,
+    };
+    prefs_ui.num("Button size", access!(.button_size), |dv| {
+        dv.fixed_decimals(0).clamp_range(16.0..=96.0_f32).speed(0.5)
+    });
+
+    prefs.needs_save |= changed;
+}
@@ -0,0 +1,69 @@
+use super::Window;
+use crate::app::App;
+
+pub(crate) const UPDATES: Window = Window {
+    name: "Updates",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs.updates;
+    app.prefs.needs_save |= ui
+        .checkbox(&mut prefs.check_on_startup, "Check for updates on startup")
+        .on_hover_text("Ask GitHub for the latest version each time the app starts")
+        .changed();
+
+    ui.horizontal(|ui| {
+        ui.label(format!("Current version: v{}", env!("CARGO_PKG_VERSION")));
+        if ui
+            .add_enabled(!app.updates.busy, egui::Button::new("Check now"))
+            .clicked()
+        {
+            app.updates.check(false);
+        }
+    });
+    if app.updates.busy {
+        ui.spinner();
+    }
+    if let Some(e) = &app.updates.error {
+        ui.colored_label(egui::Color32::RED, e);
+    }
+
+    let Some(release) = app.updates.latest.clone() else {
+        return;
+    };
+    ui.separator();
+
+    if !release.is_newer() {
+        ui.label("You have the latest version");
+        return;
+    }
+
+    ui.strong(format!("Version v{} is available", release.version()));
+    ui.hyperlink_to("Open release page", &release.html_url);
+    if app.updates.staged {
+        ui.label("The update has been installed. Restart to use it.");
+    } else if release.platform_asset().is_some() {
+        let r = ui
+            .add_enabled(!app.updates.busy, egui::Button::new("Download and install"))
+            .on_hover_text("The new version is used the next time the app starts");
+        if r.clicked() {
+            app.updates.download_and_stage();
+        }
+    }
+    if app.prefs.updates.skipped_version != release.version()
+        && ui
+            .button("Skip this version")
+            .on_hover_text("Do not notify about this version on startup")
+            .clicked()
+    {
+        app.prefs.updates.skipped_version = release.version().to_string();
+        app.prefs.needs_save = true;
+    }
+
+    ui.separator();
+    ui.strong("Changelog");
+    ui.label(&release.body);
+}
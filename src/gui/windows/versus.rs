@@ -0,0 +1,79 @@
+use super::Window;
+use crate::app::App;
+use crate::versus::Versus;
+
+pub(crate) const VERSUS: Window = Window {
+    name: "Versus",
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let mut enabled = app.versus.is_some();
+    if ui
+        .checkbox(&mut enabled, "Split-screen versus mode")
+        .changed()
+    {
+        app.versus = enabled.then(|| Versus::new(app.puzzle.ty()));
+        app.request_redraw_puzzle();
+    }
+
+    ui.separator();
+
+    let puzzle_type = app.puzzle.ty();
+    let set_names: Vec<String> = app.prefs.puzzle_keybinds[puzzle_type]
+        .sets
+        .iter()
+        .map(|set| set.preset_name.clone())
+        .collect();
+    let mut changed = false;
+    let versus_prefs = &mut app.prefs.versus;
+    for (i, set_name) in [
+        &mut versus_prefs.player_1_keybinds,
+        &mut versus_prefs.player_2_keybinds,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source(unique_id!(i))
+                .selected_text(set_name.as_str())
+                .show_ui(ui, |ui| {
+                    for name in &set_names {
+                        changed |= ui.selectable_value(set_name, name.clone(), name).changed();
+                    }
+                });
+            ui.label(format!("Player {} keybinds", i + 1));
+        });
+    }
+    app.prefs.needs_save |= changed;
+    ui.label(
+        "Player 2 can only use twist keybinds with an explicit twist axis. \
+         Grips, views, and other commands only apply to player 1.",
+    );
+
+    ui.separator();
+
+    let Some(versus) = &app.versus else {
+        return;
+    };
+    match versus.winner() {
+        Some(winner) => {
+            ui.strong(format!("Player {} wins!", winner + 1));
+        }
+        None if versus.start.is_some() => {
+            ui.label("Race in progress");
+        }
+        None => {
+            ui.label("Start a race to scramble both puzzles the same way.");
+        }
+    }
+    for (i, t) in versus.finish_times.iter().enumerate() {
+        if let Some(t) = t {
+            ui.label(format!("Player {}: {:.2}s", i + 1, t.as_secs_f64()));
+        }
+    }
+    if ui.button("🔀 New race").clicked() {
+        app.start_versus_race();
+    }
+}
@@ -74,6 +74,15 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
                 ui.strong("Settings ➡ Puzzle keybinds");
                 ui.label("to get started.");
             });
+            ui.horizontal_wrapped(|ui| {
+                ui.label("The menus and windows work without a mouse too: press");
+                ui.strong("Tab");
+                ui.label("or the arrow keys to move between buttons,");
+                ui.strong("Enter");
+                ui.label("to press one, and");
+                ui.strong("Escape");
+                ui.label("to go back to the puzzle.");
+            });
         });
 
     ui.label("");
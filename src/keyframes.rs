@@ -0,0 +1,68 @@
+//! Camera keyframes for recording and playing back presentations.
+//!
+//! Playback overrides the view without changing the view preferences, and the
+//! view returns to normal when playback stops. There is no video export yet, so
+//! playback has to be captured with a screen recorder.
+
+use crate::preferences::ViewPreferences;
+
+/// Snapshot of the view settings and the position in the undo history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe {
+    /// View settings at this keyframe.
+    pub view: ViewPreferences,
+    /// Number of entries in the undo history at this keyframe.
+    pub history_len: usize,
+}
+
+/// Sequence of keyframes, along with playback state.
+#[derive(Debug, Default, Clone)]
+pub struct Keyframes {
+    /// Recorded keyframes, in playback order.
+    pub frames: Vec<Keyframe>,
+    /// Index of the next keyframe to animate toward, if playing.
+    playback: Option<usize>,
+}
+impl Keyframes {
+    /// Returns whether the keyframes are currently being played back.
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+    /// Returns the index of the most recent keyframe reached during playback.
+    pub fn current(&self) -> Option<usize> {
+        self.playback.and_then(|i| i.checked_sub(1))
+    }
+
+    /// Starts playback from the first keyframe.
+    pub fn play(&mut self) {
+        self.playback = (!self.frames.is_empty()).then_some(0);
+    }
+    /// Stops playback.
+    pub fn stop(&mut self) {
+        self.playback = None;
+    }
+
+    /// Returns the next keyframe to animate toward and advances playback, or
+    /// returns `None` and stops playback if there are no more keyframes.
+    pub fn advance(&mut self) -> Option<(usize, Keyframe)> {
+        let i = self.playback?;
+        match self.frames.get(i) {
+            Some(kf) => {
+                self.playback = Some(i + 1);
+                Some((i, kf.clone()))
+            }
+            None => {
+                self.playback = None;
+                None
+            }
+        }
+    }
+
+    /// Removes a keyframe, stopping playback.
+    pub fn remove(&mut self, index: usize) {
+        self.stop();
+        if index < self.frames.len() {
+            self.frames.remove(index);
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Opt-in client for an online leaderboard.
+//!
+//! Ranked solves are scrambled from a random seed so that the server can
+//! reproduce the scramble. When a ranked solve is finished, it is verified
+//! locally and queued for submission to the server in the preferences.
+//! Submissions that cannot be sent are kept in a file and retried later, even
+//! after a restart. Nothing is sent unless the leaderboard is enabled in the
+//! preferences.
+//!
+//! The server must accept these requests:
+//!
+//! - `POST {server}/submit` with a JSON [`Submission`]
+//! - `GET {server}/rankings?puzzle={name}`, which responds with a JSON list of
+//!   [`Ranking`]s, fastest first
+
+use anyhow::Result;
+use instant::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+
+use crate::logfile::{self, LogFileFormat};
+use crate::preferences::{LeaderboardPreferences, ScrambleOptions};
+use crate::puzzle::*;
+
+/// Time to wait before retrying queued submissions after a failure.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// Time to wait for the server to respond.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Solve to submit to the leaderboard server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Submission {
+    pub player: String,
+    /// Name of the puzzle type, such as "3x3x3x3".
+    pub puzzle: String,
+    /// Seed that the scramble was generated from.
+    pub scramble_seed: u64,
+    /// Duration of the solve in milliseconds.
+    pub time_ms: u64,
+    /// Number of twists in the solve, in each twist metric.
+    pub twist_counts: BTreeMap<TwistMetric, usize>,
+    /// SHA-256 hash of the log file, in hexadecimal.
+    pub log_hash: String,
+    /// Log file of the solve, so that the server can verify it.
+    pub log: String,
+}
+impl Submission {
+    /// Verifies a solve and returns a submission for it.
+    pub fn new(
+        puzzle: &PuzzleController,
+        scramble_seed: u64,
+        player: &str,
+    ) -> Result<Self, String> {
+        let log = logfile::serialize(puzzle, LogFileFormat::Hsc).map_err(|e| e.to_string())?;
+        let verification = logfile::verify(&log, Some(scramble_seed));
+        if !verification.valid {
+            return Err(verification.errors.join("; "));
+        }
+        let Some(time_ms) = verification.duration_ms else {
+            return Err("Solve has no twist times".to_string());
+        };
+        Ok(Self {
+            player: player.to_string(),
+            puzzle: puzzle.ty().name().to_string(),
+            scramble_seed,
+            time_ms,
+            twist_counts: verification.twist_counts,
+            log_hash: hex::encode(Sha256::digest(log.as_bytes())),
+            log,
+        })
+    }
+}
+
+/// Entry in the rankings for a puzzle type.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Ranking {
+    pub player: String,
+    /// Duration of the solve in milliseconds.
+    pub time_ms: u64,
+    /// Number of twists in the solve, in each twist metric.
+    #[serde(default)]
+    pub twist_counts: BTreeMap<TwistMetric, usize>,
+}
+
+/// Response from a request on another thread.
+#[derive(Debug)]
+enum Response {
+    /// Some number of queued submissions were handled by the server, and the
+    /// rest were not sent because of an error.
+    Submitted { sent: usize, error: Option<String> },
+    /// Rankings for a puzzle type were received.
+    Rankings {
+        puzzle: String,
+        result: Result<Vec<Ranking>, String>,
+    },
+}
+
+/// Ranked solve in progress, submissions waiting to be sent, and the latest
+/// rankings.
+pub(crate) struct Leaderboard {
+    /// Seed and twists of the scramble of the ranked solve in progress.
+    ranked_scramble: Option<(u64, Vec<Twist>)>,
+    /// Submissions that have not been sent yet, oldest first.
+    pub queue: Vec<Submission>,
+    /// Name of the puzzle type of the latest rankings, along with the
+    /// rankings, fastest first.
+    pub rankings: Option<(String, Vec<Ranking>)>,
+    /// Last error from the server.
+    pub error: Option<String>,
+
+    responses_tx: mpsc::Sender<Response>,
+    responses: mpsc::Receiver<Response>,
+    /// Whether queued submissions are being sent.
+    submitting: bool,
+    /// Time of the last failure to send queued submissions.
+    last_failure: Option<Instant>,
+}
+impl Default for Leaderboard {
+    fn default() -> Self {
+        let (responses_tx, responses) = mpsc::channel();
+        Self {
+            ranked_scramble: None,
+            queue: vec![],
+            rankings: None,
+            error: None,
+
+            responses_tx,
+            responses,
+            submitting: false,
+            last_failure: None,
+        }
+    }
+}
+impl Leaderboard {
+    /// Loads submissions that were queued on a previous launch.
+    pub fn load_queue(&mut self) {
+        let contents = crate::preferences::leaderboard_queue_file_path()
+            .map_err(anyhow::Error::from)
+            .and_then(|path| Ok(std::fs::read_to_string(path)?));
+        match contents {
+            Ok(contents) => match serde_yaml::from_str(&contents) {
+                Ok(queue) => self.queue = queue,
+                Err(e) => log::warn!("Error loading leaderboard queue: {}", e),
+            },
+            Err(e) => log::info!("No leaderboard queue loaded: {}", e),
+        }
+    }
+    /// Saves the submissions that have not been sent yet.
+    fn save_queue(&self) -> Result<()> {
+        // Tests must not overwrite the user's queue.
+        #[cfg(not(test))]
+        {
+            let path = crate::preferences::leaderboard_queue_file_path()?;
+            if self.queue.is_empty() {
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+            } else {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::write(path, serde_yaml::to_string(&self.queue)?)?;
+            }
+        }
+        Ok(())
+    }
+    /// Discards submissions that have not been sent yet.
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+        if let Err(e) = self.save_queue() {
+            log::warn!("Error saving leaderboard queue: {}", e);
+        }
+    }
+
+    /// Resets the puzzle and scrambles it fully from a random seed for a
+    /// ranked solve.
+    pub fn start_ranked_solve(
+        &mut self,
+        puzzle: &mut PuzzleController,
+    ) -> Result<(), &'static str> {
+        let mut bytes = [0; 8];
+        getrandom::getrandom(&mut bytes).map_err(|_| "Unable to generate a random seed")?;
+        let seed = u64::from_le_bytes(bytes);
+
+        let n = puzzle.scramble_moves_count();
+        let twists = puzzle.generate_seeded_scramble(n, &ScrambleOptions::default(), seed)?;
+        puzzle.scramble_with(&twists, ScrambleState::Full)?;
+        self.ranked_scramble = Some((seed, twists));
+        Ok(())
+    }
+    /// Returns whether the puzzle has the scramble of the ranked solve in
+    /// progress.
+    pub fn is_ranked(&self, puzzle: &PuzzleController) -> bool {
+        match &self.ranked_scramble {
+            Some((_seed, twists)) => {
+                puzzle.scramble_state() != ScrambleState::None
+                    && puzzle.scramble() == twists.as_slice()
+            }
+            None => false,
+        }
+    }
+
+    /// Queues a submission if the puzzle was just solved from a ranked
+    /// scramble, and returns whether it was queued.
+    pub fn record_solve(
+        &mut self,
+        puzzle: &PuzzleController,
+        prefs: &LeaderboardPreferences,
+    ) -> Result<bool, String> {
+        if !prefs.enabled || !self.is_ranked(puzzle) {
+            return Ok(false);
+        }
+        let Some((seed, _twists)) = self.ranked_scramble.take() else {
+            return Ok(false);
+        };
+
+        self.queue
+            .push(Submission::new(puzzle, seed, &prefs.player_name)?);
+        if let Err(e) = self.save_queue() {
+            log::warn!("Error saving leaderboard queue: {}", e);
+        }
+        self.submit_queued(prefs);
+        Ok(true)
+    }
+
+    /// Sends queued submissions to the server on another thread, unless they
+    /// are already being sent.
+    pub fn submit_queued(&mut self, prefs: &LeaderboardPreferences) {
+        if !prefs.enabled || prefs.server.is_empty() || self.submitting || self.queue.is_empty() {
+            return;
+        }
+        self.submitting = true;
+
+        let url = format!("{}/submit", prefs.server.trim_end_matches('/'));
+        let queue = self.queue.clone();
+        let responses_tx = self.responses_tx.clone();
+        std::thread::spawn(move || {
+            let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+            let mut sent = 0;
+            let mut error = None;
+            for submission in &queue {
+                match agent.post(&url).send_json(submission) {
+                    Ok(_) => sent += 1,
+                    // The server rejected the solve, so sending it again
+                    // would not help.
+                    Err(ureq::Error::Status(code @ 400..=499, _)) => {
+                        sent += 1;
+                        error = Some(format!("Server rejected solve (HTTP {code})"));
+                    }
+                    Err(e) => {
+                        error = Some(e.to_string());
+                        break;
+                    }
+                }
+            }
+            let _ = responses_tx.send(Response::Submitted { sent, error });
+        });
+    }
+
+    /// Requests the rankings for a puzzle type on another thread.
+    pub fn fetch_rankings(&mut self, prefs: &LeaderboardPreferences, puzzle: String) {
+        if !prefs.enabled || prefs.server.is_empty() {
+            return;
+        }
+
+        let url = format!("{}/rankings", prefs.server.trim_end_matches('/'));
+        let responses_tx = self.responses_tx.clone();
+        std::thread::spawn(move || {
+            let result = ureq::AgentBuilder::new()
+                .timeout(TIMEOUT)
+                .build()
+                .get(&url)
+                .query("puzzle", &puzzle)
+                .call()
+                .map_err(|e| e.to_string())
+                .and_then(|response| response.into_json().map_err(|e| e.to_string()));
+            let _ = responses_tx.send(Response::Rankings { puzzle, result });
+        });
+    }
+
+    /// Handles responses from the server, and retries queued submissions if
+    /// it has been long enough since the last failure.
+    pub fn poll(&mut self, prefs: &LeaderboardPreferences) {
+        while let Ok(response) = self.responses.try_recv() {
+            match response {
+                Response::Submitted { sent, error } => {
+                    self.submitting = false;
+                    self.queue.drain(..sent.min(self.queue.len()));
+                    if let Err(e) = self.save_queue() {
+                        log::warn!("Error saving leaderboard queue: {}", e);
+                    }
+                    if error.is_some() {
+                        self.last_failure = Some(Instant::now());
+                    }
+                    self.error = error;
+                }
+                Response::Rankings { puzzle, result } => match result {
+                    Ok(rankings) => {
+                        self.rankings = Some((puzzle, rankings));
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                },
+            }
+        }
+
+        let retry = self
+            .last_failure
+            .map_or(true, |t| t.elapsed() >= RETRY_INTERVAL);
+        if retry {
+            self.submit_queued(prefs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submission() {
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.start_ranked_solve(&mut puzzle).unwrap();
+        assert!(leaderboard.is_ranked(&puzzle));
+        let (seed, scramble) = leaderboard.ranked_scramble.clone().unwrap();
+
+        // Unsolved puzzles are not submitted.
+        assert!(Submission::new(&puzzle, seed, "").is_err());
+
+        let ty = puzzle.ty();
+        for &twist in scramble.iter().rev() {
+            puzzle.twist(ty.reverse_twist(twist)).unwrap();
+        }
+        let submission = Submission::new(&puzzle, seed, "player").unwrap();
+        assert_eq!(submission.puzzle, "3x3x3");
+        assert_eq!(submission.scramble_seed, seed);
+        assert_eq!(submission.twist_counts[&TwistMetric::Etm], scramble.len());
+        assert_eq!(submission.log_hash.len(), 64);
+
+        // Solves are only queued if the leaderboard is enabled.
+        let mut prefs = LeaderboardPreferences::default();
+        assert_eq!(leaderboard.record_solve(&puzzle, &prefs), Ok(false));
+        prefs.enabled = true;
+        assert_eq!(leaderboard.record_solve(&puzzle, &prefs), Ok(true));
+        assert_eq!(leaderboard.queue, [submission]);
+        assert!(!leaderboard.is_ranked(&puzzle));
+    }
+}
@@ -0,0 +1,195 @@
+//! Totals of twists, time, and solves across all sessions, saved between
+//! launches.
+
+use anyhow::Result;
+use instant::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::puzzle::*;
+
+/// Maximum time between twists that is counted as time spent solving. Longer
+/// pauses are assumed to be breaks.
+const MAX_ACTIVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Statistics for one puzzle type.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub(crate) struct PuzzleStats {
+    /// Number of twists made, not including scrambles.
+    pub twists: u64,
+    /// Number of scrambled puzzles that were solved.
+    pub solves: u64,
+    /// Time spent twisting, in seconds, not including long pauses.
+    pub time_secs: f64,
+}
+impl PuzzleStats {
+    fn add(&mut self, other: &PuzzleStats) {
+        self.twists += other.twists;
+        self.solves += other.solves;
+        self.time_secs += other.time_secs;
+    }
+}
+
+/// Twists since the last update, collected from puzzle events.
+#[derive(Debug, Default)]
+struct PendingTwists {
+    count: u64,
+    active_time: Duration,
+    last_twist: Option<Instant>,
+}
+
+/// Statistics for every puzzle type that has been used, both since launch and
+/// across all sessions.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub(crate) struct LifetimeStats {
+    /// Statistics across all sessions, keyed by puzzle type name.
+    pub puzzles: BTreeMap<String, PuzzleStats>,
+
+    /// Statistics since launch, keyed by puzzle type name.
+    #[serde(skip)]
+    pub session: BTreeMap<String, PuzzleStats>,
+    #[serde(skip)]
+    pending: Rc<RefCell<PendingTwists>>,
+}
+impl LifetimeStats {
+    /// Loads statistics saved on a previous launch.
+    pub fn load() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let contents = crate::preferences::lifetime_stats_file_path()
+                .map_err(anyhow::Error::from)
+                .and_then(|path| Ok(std::fs::read_to_string(path)?));
+            match contents {
+                Ok(contents) => match serde_yaml::from_str(&contents) {
+                    Ok(stats) => return stats,
+                    Err(e) => log::warn!("Error loading lifetime stats: {}", e),
+                },
+                Err(e) => log::info!("No lifetime stats loaded: {}", e),
+            }
+        }
+        Self::default()
+    }
+    /// Saves statistics to be loaded on the next launch.
+    pub fn save(&self) -> Result<()> {
+        // Tests must not overwrite the user's statistics.
+        #[cfg(not(any(target_arch = "wasm32", test)))]
+        {
+            let path = crate::preferences::lifetime_stats_file_path()?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, serde_yaml::to_string(self)?)?;
+        }
+        Ok(())
+    }
+
+    /// Counts twists made on a puzzle controller. The subscription is kept
+    /// when the puzzle is replaced.
+    pub fn subscribe(&self, puzzle: &mut PuzzleController) {
+        let pending = Rc::clone(&self.pending);
+        puzzle.on_twist(move |_| {
+            let mut pending = pending.borrow_mut();
+            let now = Instant::now();
+            if let Some(last) = pending.last_twist {
+                let interval = now.duration_since(last);
+                if interval <= MAX_ACTIVE_INTERVAL {
+                    pending.active_time += interval;
+                }
+            }
+            pending.last_twist = Some(now);
+            pending.count += 1;
+        });
+    }
+
+    /// Adds the twists made since the last update to the statistics for a
+    /// puzzle type.
+    pub fn update(&mut self, ty: PuzzleTypeEnum) {
+        let mut pending = self.pending.borrow_mut();
+        if pending.count == 0 && pending.active_time.is_zero() {
+            return;
+        }
+        let new_stats = PuzzleStats {
+            twists: std::mem::take(&mut pending.count),
+            solves: 0,
+            time_secs: std::mem::take(&mut pending.active_time).as_secs_f64(),
+        };
+        drop(pending);
+        self.add(ty, new_stats);
+    }
+    /// Records that a scrambled puzzle was solved.
+    pub fn record_solve(&mut self, ty: PuzzleTypeEnum) {
+        self.add(
+            ty,
+            PuzzleStats {
+                solves: 1,
+                ..Default::default()
+            },
+        );
+    }
+    fn add(&mut self, ty: PuzzleTypeEnum, stats: PuzzleStats) {
+        let name = ty.name();
+        self.puzzles
+            .entry(name.to_string())
+            .or_default()
+            .add(&stats);
+        self.session
+            .entry(name.to_string())
+            .or_default()
+            .add(&stats);
+    }
+
+    /// Returns the statistics across all sessions for all puzzle types.
+    pub fn total(&self) -> PuzzleStats {
+        let mut total = PuzzleStats::default();
+        for stats in self.puzzles.values() {
+            total.add(stats);
+        }
+        total
+    }
+    /// Returns the statistics since launch for all puzzle types.
+    pub fn session_total(&self) -> PuzzleStats {
+        let mut total = PuzzleStats::default();
+        for stats in self.session.values() {
+            total.add(stats);
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifetime_stats() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut stats = LifetimeStats::default();
+        let mut puzzle = PuzzleController::new(ty);
+        stats.subscribe(&mut puzzle);
+
+        // Scramble twists are not counted.
+        let twists = ty.parse_twists("R U").unwrap();
+        puzzle.scramble_with(&twists, ScrambleState::Full).unwrap();
+        for &twist in twists.iter().rev() {
+            puzzle.twist(ty.reverse_twist(twist)).unwrap();
+        }
+        assert!(puzzle.check_just_solved());
+        stats.update(ty);
+        stats.record_solve(ty);
+
+        let name = ty.name();
+        assert_eq!(stats.puzzles[name].twists, 2);
+        assert_eq!(stats.puzzles[name].solves, 1);
+        assert_eq!(stats.session[name], stats.puzzles[name]);
+
+        // Session statistics are not saved.
+        let loaded: LifetimeStats =
+            serde_yaml::from_str(&serde_yaml::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(loaded.total(), stats.total());
+        assert!(loaded.session.is_empty());
+    }
+}
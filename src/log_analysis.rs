@@ -0,0 +1,398 @@
+//! Aggregate statistics over a folder of log files.
+//!
+//! Every `.hsc` and `.log` file in the folder is loaded, and the complete
+//! solves are grouped by puzzle type. The report can be shown in the log
+//! analysis window, or written to a JSON or CSV file, either from that window
+//! or with `hyperspeedcube --analyze-logs FOLDER [OUTPUT]`.
+
+use anyhow::Result;
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+use crate::logfile;
+use crate::puzzle::*;
+
+/// Extensions of files that are loaded as log files.
+const LOG_FILE_EXTENSIONS: &[&str] = &["hsc", "log"];
+
+/// Statistics over a folder of log files.
+#[derive(Serialize, Debug, Default, Clone)]
+pub(crate) struct LogReport {
+    /// Number of log files that were found.
+    pub files: usize,
+    /// Log files that could not be loaded, along with the reason.
+    pub errors: Vec<String>,
+    /// Twist metric used for phase splits.
+    pub metric: TwistMetric,
+    /// Statistics for each puzzle type, by name.
+    pub puzzles: BTreeMap<String, PuzzleReport>,
+}
+
+/// Statistics over the solves of one puzzle type.
+#[derive(Serialize, Debug, Default, Clone)]
+pub(crate) struct PuzzleReport {
+    /// Number of complete solves from a full scramble.
+    pub solves: usize,
+    /// Number of log files that do not contain a complete solve. These are
+    /// not included in any other statistics.
+    pub incomplete: usize,
+    /// Distribution of the number of twists in each solve, in each twist
+    /// metric.
+    pub twist_counts: BTreeMap<TwistMetric, Distribution>,
+    /// Average duration of a solve in milliseconds, over the solves whose
+    /// twists were timed.
+    pub mean_time_ms: Option<f64>,
+    /// Number of solves starting with each twist, ignoring whole-puzzle
+    /// rotations.
+    pub first_moves: BTreeMap<String, usize>,
+    /// Average splits of each phase, in the order that the phases were first
+    /// seen.
+    pub phases: Vec<PhaseReport>,
+
+    #[serde(skip)]
+    time_sum_ms: f64,
+    #[serde(skip)]
+    timed_solves: usize,
+}
+impl PuzzleReport {
+    /// Returns the most common first moves, most common first.
+    pub fn most_common_first_moves(&self) -> Vec<(&str, usize)> {
+        self.first_moves
+            .iter()
+            .map(|(twist, &count)| (twist.as_str(), count))
+            .sorted_by_key(|&(_, count)| std::cmp::Reverse(count))
+            .collect()
+    }
+
+    fn add_solve(&mut self, puzzle: &PuzzleController, metric: TwistMetric) {
+        self.solves += 1;
+
+        for m in TwistMetric::iter() {
+            let count = puzzle.twist_count(m);
+            let distribution = self.twist_counts.entry(m).or_default();
+            *distribution.counts.entry(count).or_default() += 1;
+        }
+        if let Some(time) = puzzle.twist_times().iter().rev().find_map(|&t| t) {
+            self.time_sum_ms += time.as_secs_f64() * 1000.0;
+            self.timed_solves += 1;
+        }
+
+        let twists = puzzle.undo_buffer();
+        let all_layers = puzzle.all_layers();
+        if let Some(first) = twists
+            .iter()
+            .filter_map(|&entry| entry.twist())
+            .find(|twist| twist.layers != all_layers)
+        {
+            let name = puzzle.ty().twists_to_string(&[first]);
+            *self.first_moves.entry(name).or_default() += 1;
+        }
+
+        let mut last_twist_count = 0;
+        let mut last_time = Some(instant::Duration::ZERO);
+        for split in puzzle.phase_splits() {
+            let twist_count = metric.count_twists(
+                puzzle,
+                twists[..split.twist_count]
+                    .iter()
+                    .filter_map(|&entry| entry.twist()),
+            );
+            let i = match self.phases.iter().position(|p| p.name == split.name) {
+                Some(i) => i,
+                None => {
+                    self.phases.push(PhaseReport {
+                        name: split.name.clone(),
+                        ..Default::default()
+                    });
+                    self.phases.len() - 1
+                }
+            };
+            let phase = &mut self.phases[i];
+            phase.solves += 1;
+            phase.twist_sum += twist_count.saturating_sub(last_twist_count);
+            if let Some((prev, t)) = last_time.zip(split.time) {
+                phase.time_sum_ms += t.saturating_sub(prev).as_secs_f64() * 1000.0;
+                phase.timed_solves += 1;
+            }
+            last_twist_count = twist_count;
+            last_time = split.time;
+        }
+    }
+
+    fn finish(&mut self) {
+        for distribution in self.twist_counts.values_mut() {
+            distribution.finish();
+        }
+        self.mean_time_ms = mean(self.time_sum_ms, self.timed_solves);
+        for phase in &mut self.phases {
+            phase.mean_twists = phase.twist_sum as f64 / phase.solves as f64;
+            phase.mean_time_ms = mean(phase.time_sum_ms, phase.timed_solves);
+        }
+    }
+}
+
+/// Distribution of a number over several solves.
+#[derive(Serialize, Debug, Default, Clone)]
+pub(crate) struct Distribution {
+    /// Smallest value.
+    pub min: usize,
+    /// Largest value.
+    pub max: usize,
+    /// Average value.
+    pub mean: f64,
+    /// Number of solves with each value.
+    pub counts: BTreeMap<usize, usize>,
+}
+impl Distribution {
+    fn finish(&mut self) {
+        let total: usize = self.counts.values().sum();
+        self.min = self.counts.keys().next().copied().unwrap_or(0);
+        self.max = self.counts.keys().next_back().copied().unwrap_or(0);
+        let sum: usize = self.counts.iter().map(|(value, count)| value * count).sum();
+        self.mean = sum as f64 / total.max(1) as f64;
+    }
+}
+
+/// Average split of one phase, measured from the end of the previous phase.
+#[derive(Serialize, Debug, Default, Clone)]
+pub(crate) struct PhaseReport {
+    /// Name of the phase.
+    pub name: String,
+    /// Number of solves in which the phase was completed.
+    pub solves: usize,
+    /// Average number of twists in the phase.
+    pub mean_twists: f64,
+    /// Average duration of the phase in milliseconds, over the solves whose
+    /// splits were timed.
+    pub mean_time_ms: Option<f64>,
+
+    #[serde(skip)]
+    twist_sum: usize,
+    #[serde(skip)]
+    time_sum_ms: f64,
+    #[serde(skip)]
+    timed_solves: usize,
+}
+
+/// Loads every log file in `dir` and returns statistics over the solves in
+/// them. Phase splits are counted using `metric`.
+pub(crate) fn analyze_folder(dir: &Path, metric: TwistMetric) -> Result<LogReport> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    paths.retain(|path| {
+        path.is_file()
+            && path.extension().map_or(false, |ext| {
+                LOG_FILE_EXTENSIONS
+                    .iter()
+                    .any(|e| ext.eq_ignore_ascii_case(e))
+            })
+    });
+    paths.sort();
+
+    let mut report = LogReport {
+        metric,
+        ..Default::default()
+    };
+    for path in paths {
+        report.files += 1;
+        let puzzle = match logfile::load_file(&path) {
+            Ok((puzzle, _warnings)) => puzzle,
+            Err(e) => {
+                report.errors.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        };
+        report.add_log(&puzzle);
+    }
+    for puzzle_report in report.puzzles.values_mut() {
+        puzzle_report.finish();
+    }
+    Ok(report)
+}
+
+impl LogReport {
+    fn add_log(&mut self, puzzle: &PuzzleController) {
+        let puzzle_report = self
+            .puzzles
+            .entry(puzzle.ty().name().to_string())
+            .or_default();
+        let fully_scrambled = matches!(
+            puzzle.scramble_state(),
+            ScrambleState::Full | ScrambleState::Solved,
+        );
+        if fully_scrambled && puzzle.is_solved() {
+            puzzle_report.add_solve(puzzle, self.metric);
+        } else {
+            puzzle_report.incomplete += 1;
+        }
+    }
+
+    /// Writes the report to a file, as CSV if the file extension is `.csv`
+    /// and as JSON otherwise.
+    pub fn save_file(&self, path: &Path) -> Result<()> {
+        let is_csv = path
+            .extension()
+            .map_or(false, |ext| ext.eq_ignore_ascii_case("csv"));
+        let contents = if is_csv {
+            self.to_csv()
+        } else {
+            self.to_json()?
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Returns the report as a JSON object.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Returns the report as CSV, with one statistic per row.
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec![["puzzle", "statistic", "key", "value"].map(str::to_string)];
+        let mut row = |puzzle: &str, statistic: &str, key: String, value: String| {
+            rows.push([puzzle.to_string(), statistic.to_string(), key, value]);
+        };
+        for (puzzle, r) in &self.puzzles {
+            row(puzzle, "solves", String::new(), r.solves.to_string());
+            row(
+                puzzle,
+                "incomplete",
+                String::new(),
+                r.incomplete.to_string(),
+            );
+            if let Some(t) = r.mean_time_ms {
+                row(puzzle, "mean time (ms)", String::new(), format!("{t:.0}"));
+            }
+            for (metric, d) in &r.twist_counts {
+                row(
+                    puzzle,
+                    &format!("min twists ({metric})"),
+                    String::new(),
+                    d.min.to_string(),
+                );
+                row(
+                    puzzle,
+                    &format!("max twists ({metric})"),
+                    String::new(),
+                    d.max.to_string(),
+                );
+                row(
+                    puzzle,
+                    &format!("mean twists ({metric})"),
+                    String::new(),
+                    format!("{:.2}", d.mean),
+                );
+                for (value, count) in &d.counts {
+                    row(
+                        puzzle,
+                        &format!("twist count ({metric})"),
+                        value.to_string(),
+                        count.to_string(),
+                    );
+                }
+            }
+            for (twist, count) in r.most_common_first_moves() {
+                row(puzzle, "first move", twist.to_string(), count.to_string());
+            }
+            let metric = self.metric;
+            for phase in &r.phases {
+                row(
+                    puzzle,
+                    "phase solves",
+                    phase.name.clone(),
+                    phase.solves.to_string(),
+                );
+                row(
+                    puzzle,
+                    &format!("phase mean twists ({metric})"),
+                    phase.name.clone(),
+                    format!("{:.2}", phase.mean_twists),
+                );
+                if let Some(t) = phase.mean_time_ms {
+                    row(
+                        puzzle,
+                        "phase mean time (ms)",
+                        phase.name.clone(),
+                        format!("{t:.0}"),
+                    );
+                }
+            }
+        }
+
+        let mut ret = String::new();
+        for row in rows {
+            let _ = writeln!(ret, "{}", row.iter().map(|s| csv_field(s)).join(","));
+        }
+        ret
+    }
+}
+
+fn mean(sum: f64, count: usize) -> Option<f64> {
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Quotes a CSV field if necessary.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_folder() {
+        let dir =
+            std::env::temp_dir().join(format!("hsc_log_analysis_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let parse = |s: &str| ty.parse_twists(s).unwrap();
+        let save = |name: &str, scramble: &str, solution: &str| {
+            let mut puzzle = PuzzleController::new(ty);
+            puzzle
+                .scramble_with(&parse(scramble), ScrambleState::Full)
+                .unwrap();
+            for twist in parse(solution) {
+                puzzle.twist_no_collapse(twist).unwrap();
+            }
+            logfile::save_file(&dir.join(name), &mut puzzle).unwrap();
+        };
+        save("a.hsc", "R U", "U' R'");
+        save("b.hsc", "R U", "y U' y' R'");
+        save("c.hsc", "R U F", "F' U' R'");
+        save("d.hsc", "R U", "U'");
+        std::fs::write(dir.join("e.hsc"), "not a log file").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let report = analyze_folder(&dir, TwistMetric::Stm).unwrap();
+        assert_eq!(report.files, 5);
+        assert_eq!(report.errors.len(), 1);
+
+        let r = &report.puzzles[ty.name()];
+        assert_eq!(r.solves, 3);
+        assert_eq!(r.incomplete, 1);
+        let stm = &r.twist_counts[&TwistMetric::Stm];
+        assert_eq!((stm.min, stm.max), (2, 3));
+        assert_eq!(stm.counts[&2], 2);
+        assert_eq!(r.most_common_first_moves(), vec![("U'", 2), ("F'", 1)]);
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("puzzle,statistic,key,value\n"));
+        assert!(csv.contains(&format!("{},first move,U',2\n", ty.name())));
+        assert!(report.to_json().unwrap().contains("\"first_moves\""));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
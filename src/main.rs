@@ -10,10 +10,6 @@
     missing_docs, // TODO: remove
 )]
 
-#[macro_use]
-extern crate ambassador;
-#[macro_use]
-extern crate enum_dispatch;
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
@@ -30,21 +26,48 @@ use winit::platform::web::WindowBuilderExtWebSys;
 
 #[macro_use]
 mod debug;
+mod achievements;
 mod app;
+mod attempt;
+#[cfg(not(target_arch = "wasm32"))]
+mod bundle;
 mod commands;
+#[cfg(not(target_arch = "wasm32"))]
+mod crash;
+mod fmc;
 mod gui;
 #[cfg(not(target_arch = "wasm32"))]
 mod icon;
-mod logfile;
+mod keyframes;
+#[cfg(not(target_arch = "wasm32"))]
+mod leaderboard;
+mod lifetime_stats;
+#[cfg(not(target_arch = "wasm32"))]
+mod log_analysis;
+#[cfg(not(target_arch = "wasm32"))]
+mod plugins;
 mod preferences;
-pub mod puzzle;
 mod render;
+#[cfg(test)]
+mod replay;
 mod serde_impl;
-mod util;
+#[cfg(not(target_arch = "wasm32"))]
+mod serve;
+#[cfg(not(target_arch = "wasm32"))]
+mod session;
+#[cfg(not(target_arch = "wasm32"))]
+mod solve_archive;
+#[cfg(not(target_arch = "wasm32"))]
+mod taskbar;
+#[cfg(not(target_arch = "wasm32"))]
+mod updates;
+mod versus;
 #[cfg(target_arch = "wasm32")]
 mod web_workarounds;
+mod worker;
 
 use app::App;
+use hyperspeedcube_core::{logfile, puzzle, util};
 
 const TITLE: &str = "Hyperspeedcube";
 
@@ -62,6 +85,30 @@ fn main() {
         )
         .init();
 
+    // Run headlessly to verify solves or analyze log files, without opening a
+    // window.
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("--serve") => {
+            let address = args
+                .next()
+                .unwrap_or_else(|| serve::DEFAULT_ADDRESS.to_string());
+            if let Err(e) = serve::run(&address) {
+                eprintln!("Error running server: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("--analyze-logs") => {
+            if let Err(e) = analyze_logs(args.next(), args.next()) {
+                eprintln!("Error analyzing log files: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        _ => (),
+    }
+
     let human_panic_metadata = human_panic::Metadata {
         name: TITLE.into(),
         version: env!("CARGO_PKG_VERSION").into(),
@@ -75,16 +122,30 @@ fn main() {
         human_panic::print_msg(file_path.as_ref(), &human_panic_metadata)
             .expect("human-panic: printing error message to console failed");
 
+        // Save the puzzle so that it can be recovered on the next launch.
+        let crash_dump_path = crash::CrashDump::write_snapshot(info.to_string());
+        if let Err(e) = &crash_dump_path {
+            eprintln!("Error saving crash dump: {e}");
+        }
+
+        let mut description = match file_path {
+            Some(fp) => format!(
+                "A crash report has been saved to \"{}\"\n\n\
+                 Please submit this to the developer",
+                fp.display(),
+            ),
+            None => format!("Error saving crash report"),
+        };
+        if crash_dump_path.is_ok() {
+            description += &format!(
+                "\n\nYour puzzle has been saved and can be recovered \
+                 the next time you start {TITLE}",
+            );
+        }
+
         rfd::MessageDialog::new()
             .set_title(&format!("{TITLE} crashed"))
-            .set_description(&match file_path {
-                Some(fp) => format!(
-                    "A crash report has been saved to \"{}\"\n\n\
-                     Please submit this to the developer",
-                    fp.display(),
-                ),
-                None => format!("Error saving crash report"),
-            })
+            .set_description(&description)
             .set_level(rfd::MessageLevel::Error)
             .show();
 
@@ -94,6 +155,24 @@ fn main() {
     pollster::block_on(run());
 }
 
+/// Writes statistics over a folder of log files to `output`, or prints them as
+/// JSON if no output file is given.
+#[cfg(not(target_arch = "wasm32"))]
+fn analyze_logs(dir: Option<String>, output: Option<String>) -> anyhow::Result<()> {
+    let dir = dir.ok_or_else(|| anyhow::anyhow!("usage: --analyze-logs FOLDER [OUTPUT]"))?;
+    let report = log_analysis::analyze_folder(dir.as_ref(), puzzle::TwistMetric::default())?;
+    for e in &report.errors {
+        eprintln!("Skipping {e}");
+    }
+    match output {
+        Some(path) => report.save_file(path.as_ref()),
+        None => {
+            println!("{}", report.to_json()?);
+            Ok(())
+        }
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 fn main() {
     // Initialize logging.
@@ -109,12 +188,23 @@ fn main() {
 }
 
 async fn run() {
-    // Initialize window.
     let event_loop = EventLoopBuilder::with_user_event().build();
+    let egui_ctx = egui::Context::default();
+
+    let initial_file = std::env::args().nth(1).map(std::path::PathBuf::from);
+
+    // Initialize app state. This happens before creating the window so that
+    // the window can be placed according to the preferences.
+    let mut app = App::new(&event_loop, &egui_ctx, initial_file);
+
+    // Initialize window.
     #[cfg(not(target_arch = "wasm32"))]
-    let window_builder = winit::window::WindowBuilder::new()
-        .with_title(crate::TITLE)
-        .with_window_icon(icon::load_application_icon());
+    let window_builder = app.prefs.window.apply_to_builder(
+        winit::window::WindowBuilder::new()
+            .with_title(crate::TITLE)
+            .with_window_icon(icon::load_application_icon()),
+        &event_loop,
+    );
     #[cfg(target_arch = "wasm32")]
     let window_builder =
         winit::window::WindowBuilder::new().with_canvas(Some(find_canvas_element()));
@@ -126,12 +216,18 @@ async fn run() {
 
     // Initialize graphics state.
     let mut gfx = render::GraphicsState::new(&window).await;
+    if app.prefs.first_run {
+        app.detect_gfx_preset(&gfx);
+    }
     let mut last_fps = 0;
     let mut frames_this_second = 0;
     let mut last_second = Instant::now();
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut window_title = String::new();
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut taskbar_progress = taskbar::TaskbarProgress::default();
 
     // Initialize egui.
-    let egui_ctx = egui::Context::default();
     let mut egui_winit_state = egui_winit::State::new(&event_loop);
     match dark_light::detect() {
         dark_light::Mode::Light => switch_to_light_mode(&egui_ctx),
@@ -144,11 +240,11 @@ async fn run() {
         &gfx.dummy_texture_view(),
         wgpu::FilterMode::Linear,
     );
-
-    let initial_file = std::env::args().nth(1).map(std::path::PathBuf::from);
-
-    // Initialize app state.
-    let mut app = App::new(&event_loop, initial_file);
+    let versus_texture_id = egui_renderer.register_native_texture(
+        &gfx.device,
+        &gfx.dummy_texture_view(),
+        wgpu::FilterMode::Linear,
+    );
 
     if app.prefs.show_welcome_at_startup {
         gui::windows::WELCOME.set_open(&egui_ctx, true);
@@ -160,6 +256,9 @@ async fn run() {
     #[cfg(not(target_arch = "wasm32"))]
     let mut request_paste = false;
 
+    // Whether a text field had keyboard focus on the last frame.
+    let mut text_edit_focused = false;
+
     // Begin main loop.
     let mut next_frame_time = Instant::now();
     event_loop.run(move |ev, _ev_loop, control_flow| {
@@ -248,14 +347,26 @@ async fn run() {
                         scale_factor,
                         new_inner_size,
                     } => {
+                        // The window moved to a monitor with a different
+                        // scale factor, so redraw the puzzle at the new
+                        // resolution.
                         gfx.set_scale_factor(*scale_factor as f32);
                         gfx.resize(**new_inner_size);
+                        app.request_redraw_puzzle();
                     }
                     WindowEvent::ThemeChanged(theme) => match theme {
                         winit::window::Theme::Light => switch_to_light_mode(&egui_ctx),
                         winit::window::Theme::Dark => switch_to_dark_mode(&egui_ctx),
                     },
                     _ => {
+                        if matches!(
+                            &event,
+                            WindowEvent::KeyboardInput { .. }
+                                | WindowEvent::MouseInput { .. }
+                                | WindowEvent::MouseWheel { .. }
+                        ) {
+                            app.handle_user_input();
+                        }
                         if !event_has_been_captured {
                             app.handle_window_event(&event);
                         }
@@ -288,6 +399,9 @@ async fn run() {
                     #[cfg(not(target_arch = "wasm32"))]
                     clipboard.set(copy_string);
                 }
+                if r.toggle_fullscreen {
+                    window.set_fullscreen(app.prefs.window.toggled_fullscreen(&window));
+                }
             }
 
             Event::MainEventsCleared => {
@@ -301,11 +415,12 @@ async fn run() {
 
                 if next_frame_time <= now {
                     // Update scale factor.
-                    egui_winit_state.set_pixels_per_point(gfx.scale_factor);
+                    egui_winit_state
+                        .set_pixels_per_point(gfx.scale_factor * app.prefs.window.gui_scale);
 
                     // Start egui frame.
-                    #[allow(unused_mut)]
                     let mut egui_input = egui_winit_state.take_egui_input(&window);
+                    gui::translate_arrow_keys(&egui_ctx, &mut egui_input, text_edit_focused);
 
                     // Handle paste on web, which winit *should* do for us.
                     #[cfg(target_arch = "wasm32")]
@@ -328,7 +443,7 @@ async fn run() {
 
                     let egui_output = egui_ctx.run(egui_input, |ctx| {
                         // Build all the UI.
-                        gui::build(ctx, &mut app, puzzle_texture_id);
+                        gui::build(ctx, &mut app, puzzle_texture_id, versus_texture_id);
                     });
 
                     // Handle cut & copy on web, which winit *should* do for us.
@@ -338,12 +453,37 @@ async fn run() {
                             .set_clipboard_text(&egui_output.platform_output.copied_text);
                     }
 
+                    // Text fields report where their cursor is.
+                    text_edit_focused = egui_output.platform_output.text_cursor_pos.is_some();
+
                     egui_winit_state.handle_platform_output(
                         &window,
                         &egui_ctx,
                         egui_output.platform_output,
                     );
 
+                    // Move the cursor if the GUI asked for it, such as to snap
+                    // it to a clicked sticker.
+                    if let Some(pos) = app.cursor_warp.take() {
+                        let pos = pos * egui_ctx.pixels_per_point();
+                        let pos = winit::dpi::PhysicalPosition::new(pos.x, pos.y);
+                        if let Err(e) = window.set_cursor_position(pos) {
+                            log::warn!("Error moving cursor: {}", e);
+                        }
+                    }
+
+                    // Show the puzzle and solve clock in the window title,
+                    // and solve progress on the taskbar or dock icon.
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        let title = app.window_title();
+                        if title != window_title {
+                            window.set_title(&title);
+                            window_title = title;
+                        }
+                        taskbar_progress.update(&window, app.solve_progress());
+                    }
+
                     if app.prefs.needs_save {
                         app.prefs.save();
                     }
@@ -353,6 +493,17 @@ async fn run() {
                         app.save_in_local_storage();
                     }
 
+                    // Draw the second player's puzzle if necessary.
+                    if let Some(versus_texture) = app.draw_versus_puzzle(&mut gfx) {
+                        egui_renderer.update_egui_texture_from_wgpu_texture(
+                            &gfx.device,
+                            &versus_texture,
+                            wgpu::FilterMode::Linear,
+                            versus_texture_id,
+                        );
+                        egui_ctx.request_repaint();
+                    }
+
                     // Draw puzzle if necessary.
                     if let Some(puzzle_texture) = app.draw_puzzle(&mut gfx) {
                         log::trace!("Repainting puzzle");
@@ -368,6 +519,8 @@ async fn run() {
                         // Request a repaint.
                         egui_ctx.request_repaint();
                     }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    app.export_pending_bundle(&gfx);
 
                     let frame_duration = app.prefs.gfx.frame_duration();
                     next_frame_time += frame_duration;
@@ -474,6 +627,23 @@ async fn run() {
                     // TODO: display framerate somewhere
                     printlnd!("FPS: {}", last_fps);
                 }
+
+                // Sleep until the next frame. Input events still wake up the
+                // event loop and are handled as soon as they arrive, so twist
+                // times aren't rounded to frame boundaries.
+                control_flow.set_wait_until(next_frame_time);
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            Event::LoopDestroyed => {
+                if app.prefs.window.remember_placement {
+                    app.prefs.window.save_placement(&window);
+                    app.prefs.save();
+                }
+                app.save_session(&egui_ctx);
+                if let Err(e) = app.lifetime_stats.save() {
+                    log::warn!("Error saving lifetime stats: {}", e);
+                }
             }
 
             // Ignore other events.
@@ -0,0 +1,736 @@
+//! Plugins: WebAssembly modules that add custom commands, stats exporters, and
+//! scramble generators.
+//!
+//! Plugins are loaded from `.wasm` files in the plugins folder, which is next
+//! to the preferences file. A plugin can only call the functions below, so it
+//! cannot access files, the network, or the rest of the app. Changes to the
+//! puzzle are checked and queued while the plugin runs, then applied the same
+//! way as keybinds once it returns. Each call into a plugin has a limited
+//! amount of fuel, so a plugin stuck in a loop fails instead of freezing the
+//! app.
+//!
+//! # Interface
+//!
+//! A plugin exports its `memory` and these functions:
+//!
+//! - `hsc_init()` is called once after loading. Only during this call, the
+//!   plugin may register names with `register_command()`,
+//!   `register_stats_exporter()`, and `register_scramble_generator()`, each
+//!   of which returns an ID for the name.
+//! - `hsc_command(id: i32)` runs a custom command. Custom commands can be bound
+//!   to keys like built-in commands.
+//! - `hsc_export_stats(id: i32)` calls `output()` with text about the puzzle,
+//!   which is copied to the clipboard.
+//! - `hsc_generate_scramble(id: i32, seed: i64)` calls `output()` with a
+//!   scramble in the notation of the puzzle.
+//! - `hsc_event(kind: i32)` is optional, and is called with one of the
+//!   `EVENT_*` constants for each event of the puzzle controller.
+//!
+//! All strings are UTF-8 and passed as a pointer and length into the plugin's
+//! memory. The `hsc` import module provides:
+//!
+//! - Queries that copy a string into a buffer if it fits and return its length,
+//!   so that the plugin can retry with a bigger buffer: `puzzle_type()`,
+//!   `state_code()`, `history()`, and `event_twist()`. `event_twist()` gives
+//!   the twist of the event being handled by `hsc_event()`.
+//! - `is_solved() -> i32` and `twist_count(metric) -> i32`.
+//! - `twist(notation) -> i32` and `run_command(yaml) -> i32`, which queue
+//!   twists or a built-in command in the same format as the preferences file.
+//!   Commands that access files, the clipboard, or the window are not allowed.
+//!   They return 0 on success or -1 on error.
+//! - `output(text)`, `set_status(text)`, and `log(text)`.
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use strum::IntoEnumIterator;
+use wasmi::{Config, Engine, Extern, Func, Instance, Linker, Module, Store, Value};
+
+use crate::commands::Command;
+use crate::puzzle::*;
+
+/// File extension of plugins.
+const PLUGIN_EXTENSION: &str = "wasm";
+/// Fuel given to each call into a plugin, which is roughly the number of
+/// WebAssembly instructions that it may execute.
+const FUEL_PER_CALL: u64 = 10_000_000;
+/// Maximum length of a string read from a plugin's memory.
+const MAX_STRING_LEN: usize = 1 << 20;
+
+/// Event kind passed to `hsc_event()` when a new twist is made.
+pub(crate) const EVENT_TWIST: i32 = 0;
+/// Event kind passed to `hsc_event()` when the puzzle is scrambled.
+pub(crate) const EVENT_SCRAMBLE: i32 = 1;
+/// Event kind passed to `hsc_event()` when the puzzle is solved after being
+/// scrambled.
+pub(crate) const EVENT_SOLVED: i32 = 2;
+/// Event kind passed to `hsc_event()` when a twist is undone.
+pub(crate) const EVENT_UNDO: i32 = 3;
+/// Event kind passed to `hsc_event()` when a twist is redone.
+pub(crate) const EVENT_REDO: i32 = 4;
+
+/// Change to the app requested by a plugin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PluginAction {
+    Twists(Vec<Twist>),
+    Command(Command),
+    Status(String),
+}
+
+/// Loaded plugins, along with the puzzle events that have not been sent to
+/// them yet.
+pub(crate) struct Plugins {
+    engine: Engine,
+    /// Plugins that loaded successfully.
+    pub loaded: Vec<Plugin>,
+    /// Names of plugins that failed to load, along with the error.
+    pub failed: Vec<(String, String)>,
+    /// Names of plugins that are disabled in the preferences.
+    pub disabled: Vec<String>,
+    events: Rc<RefCell<Vec<PuzzleEvent>>>,
+}
+impl Plugins {
+    /// Constructs an empty set of plugins that receives events from a puzzle
+    /// controller. The subscription is kept when the puzzle is replaced.
+    pub fn new(puzzle: &mut PuzzleController) -> Self {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+
+        let events = Rc::new(RefCell::new(vec![]));
+        puzzle.subscribe({
+            let events = Rc::clone(&events);
+            move |event| events.borrow_mut().push(*event)
+        });
+
+        Self {
+            engine: Engine::new(&config),
+            loaded: vec![],
+            failed: vec![],
+            disabled: vec![],
+            events,
+        }
+    }
+
+    /// Returns the folder that plugins are loaded from.
+    pub fn dir() -> Result<PathBuf, String> {
+        crate::preferences::plugins_dir_path().map_err(|e| e.to_string())
+    }
+
+    /// Unloads all plugins and loads the ones in the plugins folder that are
+    /// not disabled.
+    pub fn reload(&mut self, disabled: &[String]) {
+        self.loaded.clear();
+        self.failed.clear();
+        self.disabled.clear();
+
+        let dir = match Self::dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                log::warn!("Error finding plugins folder: {}", e);
+                return;
+            }
+        };
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return; // There are no plugins.
+        };
+        let mut paths = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |ext| ext == PLUGIN_EXTENSION)
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        for path in paths {
+            let name = plugin_name(&path);
+            if disabled.contains(&name) {
+                self.disabled.push(name);
+                continue;
+            }
+            let result = std::fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| Plugin::load(&self.engine, name.clone(), &bytes));
+            match result {
+                Ok(plugin) => {
+                    log::info!("Loaded plugin {}", name);
+                    self.loaded.push(plugin);
+                }
+                Err(e) => {
+                    log::warn!("Error loading plugin {}: {}", name, e);
+                    self.failed.push((name, e));
+                }
+            }
+        }
+    }
+    /// Loads a plugin from WebAssembly bytes.
+    pub fn load_bytes(&mut self, name: String, bytes: &[u8]) -> Result<(), String> {
+        let plugin = Plugin::load(&self.engine, name, bytes)?;
+        self.loaded.push(plugin);
+        Ok(())
+    }
+
+    fn get_mut(&mut self, plugin: &str) -> Result<&mut Plugin, String> {
+        self.loaded
+            .iter_mut()
+            .find(|p| p.name == plugin)
+            .ok_or_else(|| format!("Plugin {plugin:?} is not loaded"))
+    }
+
+    /// Runs a custom command from a plugin and returns the changes that it
+    /// requested.
+    pub fn run_command(
+        &mut self,
+        plugin: &str,
+        command: &str,
+        puzzle: &PuzzleController,
+    ) -> Result<Vec<PluginAction>, String> {
+        let plugin = self.get_mut(plugin)?;
+        let id = plugin.id_of(&plugin.commands, command)?;
+        plugin.call("hsc_command", &[Value::I32(id)], puzzle)?;
+        Ok(plugin.take_actions())
+    }
+    /// Runs a stats exporter from a plugin and returns its output.
+    pub fn export_stats(
+        &mut self,
+        plugin: &str,
+        exporter: &str,
+        puzzle: &PuzzleController,
+    ) -> Result<String, String> {
+        let plugin = self.get_mut(plugin)?;
+        let id = plugin.id_of(&plugin.stats_exporters, exporter)?;
+        plugin.call("hsc_export_stats", &[Value::I32(id)], puzzle)?;
+        plugin.take_output()
+    }
+    /// Runs a scramble generator from a plugin and returns the scramble.
+    pub fn generate_scramble(
+        &mut self,
+        plugin: &str,
+        generator: &str,
+        seed: i64,
+        puzzle: &PuzzleController,
+    ) -> Result<Vec<Twist>, String> {
+        let plugin = self.get_mut(plugin)?;
+        let id = plugin.id_of(&plugin.scramble_generators, generator)?;
+        plugin.call(
+            "hsc_generate_scramble",
+            &[Value::I32(id), Value::I64(seed)],
+            puzzle,
+        )?;
+        let scramble = plugin.take_output()?;
+        puzzle.ty().parse_twists(&scramble).map_err(|e| {
+            let e = format!("Invalid scramble from plugin: {e}");
+            plugin.error = Some(e.clone());
+            e
+        })
+    }
+
+    /// Sends the puzzle events since the last call to every plugin that
+    /// handles them, and returns the changes that they requested.
+    pub fn dispatch_events(&mut self, puzzle: &PuzzleController) -> Vec<PluginAction> {
+        let events = std::mem::take(&mut *self.events.borrow_mut());
+        let notation = puzzle.notation_scheme();
+        let mut actions = vec![];
+        for plugin in self.loaded.iter_mut().filter(|p| p.handles_events) {
+            for event in &events {
+                let (kind, twist) = match *event {
+                    PuzzleEvent::Twist(twist) => (EVENT_TWIST, Some(twist)),
+                    PuzzleEvent::Scramble(_) => (EVENT_SCRAMBLE, None),
+                    PuzzleEvent::Solved => (EVENT_SOLVED, None),
+                    PuzzleEvent::Undo(entry) => (EVENT_UNDO, entry.twist()),
+                    PuzzleEvent::Redo(entry) => (EVENT_REDO, entry.twist()),
+                };
+                plugin.store.state_mut().event_twist =
+                    twist.map_or_else(String::new, |t| notation.twist_to_string(t));
+                if let Err(e) = plugin.call("hsc_event", &[Value::I32(kind)], puzzle) {
+                    log::warn!("Error in plugin {}: {}", plugin.name, e);
+                    break;
+                }
+                actions.extend(plugin.take_actions());
+            }
+        }
+        actions
+    }
+}
+
+/// Plugin that has been loaded and initialized.
+pub(crate) struct Plugin {
+    /// File name of the plugin, without the extension.
+    pub name: String,
+    /// Names of custom commands.
+    pub commands: Vec<String>,
+    /// Names of stats exporters.
+    pub stats_exporters: Vec<String>,
+    /// Names of scramble generators.
+    pub scramble_generators: Vec<String>,
+    /// Most recent error from the plugin.
+    pub error: Option<String>,
+
+    /// Whether the plugin exports `hsc_event()`.
+    handles_events: bool,
+    /// Total fuel given to the plugin so far.
+    fuel_added: u64,
+    store: Store<HostState>,
+    instance: Instance,
+}
+impl Plugin {
+    fn load(engine: &Engine, name: String, bytes: &[u8]) -> Result<Self, String> {
+        let module = Module::new(engine, bytes).map_err(|e| e.to_string())?;
+        let mut store = Store::new(engine, HostState::new(name.clone()));
+        let mut linker = Linker::new();
+        define_host_functions(&mut linker, &mut store)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| e.to_string())?;
+        let handles_events = instance
+            .get_export(&store, "hsc_event")
+            .and_then(Extern::into_func)
+            .is_some();
+
+        let mut plugin = Self {
+            name,
+            commands: vec![],
+            stats_exporters: vec![],
+            scramble_generators: vec![],
+            error: None,
+
+            handles_events,
+            fuel_added: 0,
+            store,
+            instance,
+        };
+
+        plugin.store.state_mut().initializing = true;
+        let result = plugin.call("hsc_init", &[], &PuzzleController::default());
+        let state = plugin.store.state_mut();
+        state.initializing = false;
+        result?;
+        plugin.commands = std::mem::take(&mut state.commands);
+        plugin.stats_exporters = std::mem::take(&mut state.stats_exporters);
+        plugin.scramble_generators = std::mem::take(&mut state.scramble_generators);
+        Ok(plugin)
+    }
+
+    fn id_of(&self, names: &[String], name: &str) -> Result<i32, String> {
+        names
+            .iter()
+            .position(|n| n == name)
+            .map(|i| i as i32)
+            .ok_or_else(|| format!("Plugin {:?} has no {:?}", self.name, name))
+    }
+
+    /// Calls a function exported by the plugin.
+    fn call(
+        &mut self,
+        function: &str,
+        args: &[Value],
+        puzzle: &PuzzleController,
+    ) -> Result<(), String> {
+        let result = self.try_call(function, args, puzzle);
+        if let Err(e) = &result {
+            self.error = Some(e.clone());
+        }
+        result
+    }
+    fn try_call(
+        &mut self,
+        function: &str,
+        args: &[Value],
+        puzzle: &PuzzleController,
+    ) -> Result<(), String> {
+        let func = self
+            .instance
+            .get_export(&self.store, function)
+            .and_then(Extern::into_func)
+            .ok_or_else(|| format!("Plugin does not export {function}()"))?;
+
+        let state = self.store.state_mut();
+        state.puzzle = PuzzleSnapshot::new(puzzle);
+        state.actions.clear();
+        state.output = None;
+
+        // Top up the fuel so that every call starts with the same amount.
+        let consumed = self.store.fuel_consumed().unwrap_or(0);
+        let remaining = self.fuel_added.saturating_sub(consumed);
+        let fuel = FUEL_PER_CALL.saturating_sub(remaining);
+        self.store.add_fuel(fuel).map_err(|e| e.to_string())?;
+        self.fuel_added += fuel;
+
+        func.call(&mut self.store, args, &mut [])
+            .map_err(|e| format!("Error in {function}(): {e}"))
+    }
+
+    fn take_actions(&mut self) -> Vec<PluginAction> {
+        std::mem::take(&mut self.store.state_mut().actions)
+    }
+    fn take_output(&mut self) -> Result<String, String> {
+        self.store.state_mut().output.take().ok_or_else(|| {
+            let e = "Plugin did not output anything".to_string();
+            self.error = Some(e.clone());
+            e
+        })
+    }
+}
+
+/// Information about the puzzle that plugins can query.
+#[derive(Debug, Default, Clone)]
+struct PuzzleSnapshot {
+    ty: PuzzleTypeEnum,
+    state_code: String,
+    /// Twists made since the scramble, in the notation of the puzzle.
+    history: String,
+    is_solved: bool,
+    twist_counts: Vec<(TwistMetric, usize)>,
+}
+impl PuzzleSnapshot {
+    fn new(puzzle: &PuzzleController) -> Self {
+        let ty = puzzle.ty();
+        let twists = puzzle
+            .undo_buffer()
+            .iter()
+            .filter_map(|entry| entry.twist())
+            .collect::<Vec<_>>();
+        Self {
+            ty,
+            state_code: puzzle.state_code(),
+            history: ty.twists_to_string(&twists),
+            is_solved: puzzle.is_solved(),
+            twist_counts: TwistMetric::iter()
+                .map(|metric| (metric, puzzle.twist_count(metric)))
+                .collect(),
+        }
+    }
+}
+
+/// State of a plugin that is accessible to the functions it imports.
+struct HostState {
+    plugin_name: String,
+    /// Whether `hsc_init()` is running, which is the only time that names can
+    /// be registered.
+    initializing: bool,
+    commands: Vec<String>,
+    stats_exporters: Vec<String>,
+    scramble_generators: Vec<String>,
+
+    puzzle: PuzzleSnapshot,
+    /// Twist of the event being handled, in the notation of the puzzle.
+    event_twist: String,
+
+    actions: Vec<PluginAction>,
+    output: Option<String>,
+}
+impl HostState {
+    fn new(plugin_name: String) -> Self {
+        Self {
+            plugin_name,
+            initializing: false,
+            commands: vec![],
+            stats_exporters: vec![],
+            scramble_generators: vec![],
+
+            puzzle: PuzzleSnapshot::default(),
+            event_twist: String::new(),
+
+            actions: vec![],
+            output: None,
+        }
+    }
+}
+
+fn define_host_functions(
+    linker: &mut Linker<HostState>,
+    store: &mut Store<HostState>,
+) -> Result<(), String> {
+    let mut define = |name: &str, func: Func| {
+        linker
+            .define("hsc", name, func)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+
+    define(
+        "register_command",
+        Func::wrap(&mut *store, host::register_command),
+    )?;
+    define(
+        "register_stats_exporter",
+        Func::wrap(&mut *store, host::register_stats_exporter),
+    )?;
+    define(
+        "register_scramble_generator",
+        Func::wrap(&mut *store, host::register_scramble_generator),
+    )?;
+
+    define("puzzle_type", Func::wrap(&mut *store, host::puzzle_type))?;
+    define("state_code", Func::wrap(&mut *store, host::state_code))?;
+    define("history", Func::wrap(&mut *store, host::history))?;
+    define("event_twist", Func::wrap(&mut *store, host::event_twist))?;
+    define("is_solved", Func::wrap(&mut *store, host::is_solved))?;
+    define("twist_count", Func::wrap(&mut *store, host::twist_count))?;
+
+    define("twist", Func::wrap(&mut *store, host::twist))?;
+    define("run_command", Func::wrap(&mut *store, host::run_command))?;
+    define("output", Func::wrap(&mut *store, host::output))?;
+    define("set_status", Func::wrap(&mut *store, host::set_status))?;
+    define("log", Func::wrap(&mut *store, host::log))?;
+
+    Ok(())
+}
+
+/// Functions imported by plugins from the `hsc` module.
+mod host {
+    use super::*;
+
+    type Caller<'a> = wasmi::Caller<'a, HostState>;
+
+    pub(super) fn register_command(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        register(&mut caller, ptr, len, |state| &mut state.commands)
+    }
+    pub(super) fn register_stats_exporter(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        register(&mut caller, ptr, len, |state| &mut state.stats_exporters)
+    }
+    pub(super) fn register_scramble_generator(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        register(&mut caller, ptr, len, |state| {
+            &mut state.scramble_generators
+        })
+    }
+    fn register(
+        caller: &mut Caller<'_>,
+        ptr: i32,
+        len: i32,
+        list: fn(&mut HostState) -> &mut Vec<String>,
+    ) -> i32 {
+        let Some(name) = read_str(caller, ptr, len) else {
+            return -1;
+        };
+        let state = caller.host_data_mut();
+        if !state.initializing {
+            return -1;
+        }
+        let list = list(state);
+        list.push(name);
+        list.len() as i32 - 1
+    }
+
+    pub(super) fn puzzle_type(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        let s = caller.host_data().puzzle.ty.name().to_string();
+        write_str(&mut caller, &s, ptr, len)
+    }
+    pub(super) fn state_code(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        let s = caller.host_data().puzzle.state_code.clone();
+        write_str(&mut caller, &s, ptr, len)
+    }
+    pub(super) fn history(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        let s = caller.host_data().puzzle.history.clone();
+        write_str(&mut caller, &s, ptr, len)
+    }
+    pub(super) fn event_twist(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        let s = caller.host_data().event_twist.clone();
+        write_str(&mut caller, &s, ptr, len)
+    }
+    pub(super) fn is_solved(caller: Caller<'_>) -> i32 {
+        caller.host_data().puzzle.is_solved as i32
+    }
+    pub(super) fn twist_count(caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        let Some(metric) = read_str(&caller, ptr, len) else {
+            return -1;
+        };
+        caller
+            .host_data()
+            .puzzle
+            .twist_counts
+            .iter()
+            .find(|(m, _)| m.to_string().eq_ignore_ascii_case(&metric))
+            .map_or(-1, |&(_, count)| count as i32)
+    }
+
+    pub(super) fn twist(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        let Some(notation) = read_str(&caller, ptr, len) else {
+            return -1;
+        };
+        let state = caller.host_data_mut();
+        match state.puzzle.ty.parse_twists(&notation) {
+            Ok(twists) => {
+                state.actions.push(PluginAction::Twists(twists));
+                0
+            }
+            Err(e) => {
+                log::warn!("Invalid twists from plugin {}: {}", state.plugin_name, e);
+                -1
+            }
+        }
+    }
+    pub(super) fn run_command(mut caller: Caller<'_>, ptr: i32, len: i32) -> i32 {
+        let Some(yaml) = read_str(&caller, ptr, len) else {
+            return -1;
+        };
+        let state = caller.host_data_mut();
+        match serde_yaml::from_str::<Command>(&yaml) {
+            Ok(command) if is_allowed_command(&command) => {
+                state.actions.push(PluginAction::Command(command));
+                0
+            }
+            _ => {
+                log::warn!(
+                    "Invalid command from plugin {}: {}",
+                    state.plugin_name,
+                    yaml
+                );
+                -1
+            }
+        }
+    }
+    pub(super) fn output(mut caller: Caller<'_>, ptr: i32, len: i32) {
+        if let Some(text) = read_str(&caller, ptr, len) {
+            caller.host_data_mut().output = Some(text);
+        }
+    }
+    pub(super) fn set_status(mut caller: Caller<'_>, ptr: i32, len: i32) {
+        if let Some(text) = read_str(&caller, ptr, len) {
+            let state = caller.host_data_mut();
+            state.actions.push(PluginAction::Status(text));
+        }
+    }
+    pub(super) fn log(caller: Caller<'_>, ptr: i32, len: i32) {
+        if let Some(text) = read_str(&caller, ptr, len) {
+            log::info!("[{}] {}", caller.host_data().plugin_name, text);
+        }
+    }
+
+    /// Reads a string from the plugin's memory.
+    fn read_str(caller: &Caller<'_>, ptr: i32, len: i32) -> Option<String> {
+        let ptr = usize::try_from(ptr).ok()?;
+        let len = usize::try_from(len).ok()?;
+        if len > MAX_STRING_LEN {
+            return None;
+        }
+        let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+        let mut buffer = vec![0; len];
+        memory.read(caller, ptr, &mut buffer).ok()?;
+        String::from_utf8(buffer).ok()
+    }
+    /// Copies a string into a buffer in the plugin's memory if it fits, and
+    /// returns the length of the string, or -1 if the buffer is invalid.
+    fn write_str(caller: &mut Caller<'_>, s: &str, ptr: i32, len: i32) -> i32 {
+        let (Ok(ptr), Ok(len)) = (usize::try_from(ptr), usize::try_from(len)) else {
+            return -1;
+        };
+        let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory) else {
+            return -1;
+        };
+        if s.len() <= len && memory.write(&mut *caller, ptr, s.as_bytes()).is_err() {
+            return -1;
+        }
+        s.len() as i32
+    }
+}
+
+/// Returns whether plugins may run a built-in command. Only commands that
+/// twist the puzzle, undo twists, or move the view are allowed, so that a
+/// plugin can never throw away a solve or access files, the clipboard, or the
+/// window.
+fn is_allowed_command(command: &Command) -> bool {
+    match command {
+        Command::Undo
+        | Command::Redo
+        | Command::CancelQueuedTwists
+        | Command::RealignSolved
+        | Command::SnapView
+        | Command::RotateView(_)
+        | Command::ZoomIn
+        | Command::ZoomOut
+        | Command::ResetZoom
+        | Command::Pan(_)
+        | Command::ResetPan => true,
+        Command::Sequence(commands) => commands.iter().all(is_allowed_command),
+        _ => false,
+    }
+}
+
+/// Returns the name of a plugin from its path.
+fn plugin_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEXY_MOVE_PLUGIN: &str = r#"
+        (module
+          (import "hsc" "register_command" (func $register_command (param i32 i32) (result i32)))
+          (import "hsc" "twist" (func $twist (param i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "Sexy move")
+          (data (i32.const 16) "R U R' U'")
+          (func (export "hsc_init")
+            (drop (call $register_command (i32.const 0) (i32.const 9))))
+          (func (export "hsc_command") (param i32)
+            (drop (call $twist (i32.const 16) (i32.const 9)))))
+    "#;
+
+    const INFINITE_LOOP_PLUGIN: &str = r#"
+        (module
+          (import "hsc" "register_command" (func $register_command (param i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "Loop")
+          (func (export "hsc_init")
+            (drop (call $register_command (i32.const 0) (i32.const 4))))
+          (func (export "hsc_command") (param i32)
+            (loop $l (br $l))))
+    "#;
+
+    fn load(plugins: &mut Plugins, name: &str, wat: &str) {
+        let bytes = wat::parse_str(wat).unwrap();
+        plugins.load_bytes(name.to_string(), &bytes).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_command() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        let mut plugins = Plugins::new(&mut puzzle);
+        load(&mut plugins, "sexy", SEXY_MOVE_PLUGIN);
+        assert_eq!(plugins.loaded[0].commands, ["Sexy move"]);
+
+        let actions = plugins.run_command("sexy", "Sexy move", &puzzle).unwrap();
+        let twists = ty.parse_twists("R U R' U'").unwrap();
+        assert_eq!(actions, [PluginAction::Twists(twists)]);
+
+        assert!(plugins.run_command("sexy", "Other", &puzzle).is_err());
+        assert!(plugins.run_command("other", "Sexy move", &puzzle).is_err());
+    }
+
+    #[test]
+    fn test_plugin_out_of_fuel() {
+        let mut puzzle = PuzzleController::default();
+        let mut plugins = Plugins::new(&mut puzzle);
+        load(&mut plugins, "loop", INFINITE_LOOP_PLUGIN);
+        assert!(plugins.run_command("loop", "Loop", &puzzle).is_err());
+        assert!(plugins.loaded[0].error.is_some());
+    }
+
+    #[test]
+    fn test_plugin_commands_are_sandboxed() {
+        assert!(is_allowed_command(&Command::Undo));
+        assert!(!is_allowed_command(&Command::Exit));
+        // Commands that throw away the current solve are not allowed.
+        for command in [
+            Command::Reset,
+            Command::ResetToScramble,
+            Command::ScrambleFull,
+            Command::NewAttempt,
+            Command::NextPuzzle,
+            Command::SwapBackgroundPuzzle,
+        ] {
+            assert!(!is_allowed_command(&command));
+        }
+        assert!(!is_allowed_command(&Command::Sequence(vec![
+            Command::Undo,
+            Command::Save,
+        ])));
+    }
+}
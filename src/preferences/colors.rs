@@ -23,7 +23,7 @@ impl Index<(PuzzleTypeEnum, Face)> for ColorPreferences {
     fn index(&self, (puzzle_type, face): (PuzzleTypeEnum, Face)) -> &Self::Output {
         self.faces
             .get(puzzle_type)
-            .and_then(|face_colors| face_colors.get(puzzle_type.info(face).symbol))
+            .and_then(|face_colors| face_colors.get(puzzle_type.desc().info(face).symbol))
             .map(|color| &color.0)
             .unwrap_or(&self.blind_face)
     }
@@ -34,7 +34,7 @@ impl IndexMut<(PuzzleTypeEnum, Face)> for ColorPreferences {
             .faces
             .entry(puzzle_type)
             .or_default()
-            .entry(puzzle_type.info(face).symbol.to_owned())
+            .entry(puzzle_type.desc().info(face).symbol.to_owned())
             .or_insert(FaceColor(self.blind_face))
             .0
     }
@@ -48,7 +48,8 @@ pub struct FaceColor(#[serde(with = "hex_color")] pub egui::Color32);
 impl ColorPreferences {
     pub fn face_colors_list(&self, ty: PuzzleTypeEnum) -> Vec<egui::Color32> {
         let faces = &self.faces[ty];
-        ty.faces()
+        ty.desc()
+            .faces()
             .iter()
             .map(|face| match faces.get(face.symbol) {
                 Some(c) => c.0,
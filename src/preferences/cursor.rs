@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use crate::serde_impl::hex_color;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct CursorPreferences {
+    /// Cursor shown over the puzzle.
+    pub style: CursorStyle,
+    /// Size of a drawn cursor, in points.
+    pub size: f32,
+    /// Color of a drawn cursor.
+    #[serde(with = "hex_color")]
+    pub color: egui::Color32,
+
+    /// Whether to hide the cursor over the puzzle while a twist is animating.
+    pub hide_during_twists: bool,
+    /// Whether to move the cursor to the center of a sticker when it is
+    /// clicked, so that the next click on the same sticker lands precisely.
+    pub snap_to_sticker: bool,
+}
+
+/// Cursor shown over the puzzle.
+#[derive(Serialize, Deserialize, Debug, Display, EnumIter, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorStyle {
+    /// Normal OS cursor.
+    #[default]
+    #[strum(serialize = "Default")]
+    Default,
+    /// OS crosshair cursor.
+    #[strum(serialize = "Crosshair")]
+    Crosshair,
+    /// Dot drawn at the cursor position.
+    #[strum(serialize = "Dot")]
+    Dot,
+    /// Cross drawn at the cursor position, with a gap in the middle so that
+    /// the sticker under the cursor stays visible.
+    #[strum(serialize = "Cross")]
+    Cross,
+}
+impl CursorStyle {
+    /// Returns whether the cursor is drawn by the app instead of by the OS.
+    pub fn is_drawn(self) -> bool {
+        matches!(self, CursorStyle::Dot | CursorStyle::Cross)
+    }
+}
@@ -1,17 +1,38 @@
 use instant::Duration;
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::serde_impl::hex_color;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct GfxPreferences {
     pub fps_limit: usize,
     pub msaa: bool,
+
+    /// Whether to fade parts of the puzzle farther from the camera into a fog.
+    pub fog: bool,
+    #[serde(with = "hex_color")]
+    pub fog_color: egui::Color32,
+    /// Density of the fog. Higher values make the back of the puzzle closer to
+    /// the fog color.
+    pub fog_density: f32,
+
+    /// Whether to draw the puzzle using the shader in the shaders folder
+    /// instead of the built-in shader.
+    pub custom_shader: bool,
 }
 impl Default for GfxPreferences {
     fn default() -> Self {
         Self {
             fps_limit: 60,
             msaa: true,
+
+            fog: false,
+            fog_color: egui::Color32::from_rgb(0x44, 0x44, 0x44),
+            fog_density: 1.0,
+
+            custom_shader: false,
         }
     }
 }
@@ -29,4 +50,68 @@ impl GfxPreferences {
             1
         }
     }
+
+    /// Returns the fog density, or zero if fog is disabled.
+    pub fn effective_fog_density(&self) -> f32 {
+        if self.fog {
+            self.fog_density
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Puzzles with more stickers than this get a lower graphics preset when one is
+/// picked automatically.
+const LARGE_PUZZLE_STICKER_COUNT: usize = 2000;
+
+/// Named graphics settings, from fastest to best-looking.
+#[derive(Debug, Display, EnumIter, Copy, Clone, PartialEq, Eq)]
+pub enum GfxPreset {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+impl GfxPreset {
+    /// Returns the FPS limit and whether MSAA is enabled in the preset.
+    fn values(self) -> (usize, bool) {
+        match self {
+            GfxPreset::Low => (30, false),
+            GfxPreset::Medium => (60, false),
+            GfxPreset::High => (60, true),
+            GfxPreset::Ultra => (120, true),
+        }
+    }
+    /// Applies the preset to graphics preferences.
+    pub fn apply(self, prefs: &mut GfxPreferences) {
+        (prefs.fps_limit, prefs.msaa) = self.values();
+    }
+    /// Returns the preset that matches graphics preferences, if any.
+    pub fn from_prefs(prefs: &GfxPreferences) -> Option<Self> {
+        Self::iter().find(|preset| preset.values() == (prefs.fps_limit, prefs.msaa))
+    }
+
+    /// Returns a sensible preset for a kind of graphics adapter and a puzzle
+    /// with `sticker_count` stickers.
+    pub fn detect(device_type: wgpu::DeviceType, sticker_count: usize) -> Self {
+        let preset = match device_type {
+            wgpu::DeviceType::DiscreteGpu => GfxPreset::Ultra,
+            wgpu::DeviceType::IntegratedGpu => GfxPreset::High,
+            wgpu::DeviceType::VirtualGpu | wgpu::DeviceType::Other => GfxPreset::Medium,
+            wgpu::DeviceType::Cpu => GfxPreset::Low,
+        };
+        if sticker_count > LARGE_PUZZLE_STICKER_COUNT {
+            preset.lower()
+        } else {
+            preset
+        }
+    }
+    fn lower(self) -> Self {
+        match self {
+            GfxPreset::Low | GfxPreset::Medium => GfxPreset::Low,
+            GfxPreset::High => GfxPreset::Medium,
+            GfxPreset::Ultra => GfxPreset::High,
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::puzzle::{PuzzleController, TwistMetric};
+
+/// Target move counts for solves of a puzzle, in the selected twist metric.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct MoveGoal {
+    /// Move budget for the whole solve.
+    pub total: Option<usize>,
+    /// Move budget for each phase, keyed by phase name.
+    pub phases: BTreeMap<String, usize>,
+}
+impl MoveGoal {
+    pub fn is_empty(&self) -> bool {
+        self.total.is_none() && self.phases.is_empty()
+    }
+
+    /// Returns the number of moves remaining in the budget for the whole solve
+    /// and for the current phase. Negative values mean that the budget has
+    /// been exceeded.
+    pub fn remaining(
+        &self,
+        puzzle: &PuzzleController,
+        phases: &[String],
+        metric: TwistMetric,
+    ) -> MoveBudget {
+        let undo_buffer = puzzle.undo_buffer();
+        let count_moves = |start: usize| {
+            let twists = undo_buffer[start.min(undo_buffer.len())..]
+                .iter()
+                .filter_map(|entry| entry.twist());
+            metric.count_twists(puzzle, twists) as isize
+        };
+
+        let total = self.total.map(|budget| budget as isize - count_moves(0));
+
+        let splits = puzzle.phase_splits();
+        let current_phase = phases
+            .iter()
+            .find(|&name| !splits.iter().any(|split| split.name == *name));
+        let phase = current_phase.and_then(|name| {
+            let budget = *self.phases.get(name)?;
+            let phase_start = splits.last().map_or(0, |split| split.twist_count);
+            Some((name.clone(), budget as isize - count_moves(phase_start)))
+        });
+
+        MoveBudget { total, phase }
+    }
+}
+
+/// Moves remaining in a [`MoveGoal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveBudget {
+    pub total: Option<isize>,
+    /// Name of the current phase and the moves remaining in it.
+    pub phase: Option<(String, isize)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::PuzzleTypeEnum;
+
+    #[test]
+    fn test_move_budget() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        for twist in ty.parse_twists("R U R'").unwrap() {
+            puzzle.twist(twist).unwrap();
+        }
+
+        let goal = MoveGoal {
+            total: Some(2),
+            phases: BTreeMap::from([("Cross".to_owned(), 5)]),
+        };
+        let phases = ["Cross".to_owned(), "F2L".to_owned()];
+        assert_eq!(
+            goal.remaining(&puzzle, &phases, TwistMetric::Stm),
+            MoveBudget {
+                total: Some(-1),
+                phase: Some(("Cross".to_owned(), 2)),
+            },
+        );
+    }
+}
@@ -10,8 +10,16 @@ pub struct InfoPreferences {
     pub qtm: bool,
 
     pub keybinds_reference: KeybindsReferencePreferences,
+    pub face_labels: FaceLabelsPreferences,
+    pub sticker_labels: StickerLabelsPreferences,
+    pub input_display: InputDisplayPreferences,
+    pub view_gizmo: bool,
 
     pub modifier_toggles: bool,
+    /// Whether to show the fraction of solved pieces in the status bar.
+    pub solved_percentage: bool,
+    /// Whether to show information about the piece under the cursor.
+    pub sticker_tooltip: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
@@ -25,3 +33,94 @@ pub struct KeybindsReferencePreferences {
 
     pub max_font_size: f32,
 }
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
+#[serde(default)]
+pub struct FaceLabelsPreferences {
+    pub show: bool,
+
+    pub font_size: f32,
+    pub opacity: f32,
+    /// Whether to fade labels of faces that are farther from the camera.
+    pub fade_with_depth: bool,
+}
+
+/// Labels drawn on each sticker.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct StickerLabelsPreferences {
+    pub show: bool,
+
+    pub scheme: StickerLabelScheme,
+    /// Whitespace-separated labels for each sticker, in the order of the
+    /// puzzle's stickers, used by the custom label scheme.
+    pub custom: String,
+    /// Font size relative to the size of the sticker.
+    pub size: f32,
+    pub opacity: f32,
+}
+impl StickerLabelsPreferences {
+    /// Returns the label for a sticker with the given index and face symbol.
+    pub fn label<'a>(&'a self, sticker_index: usize, face_symbol: &'a str) -> &'a str {
+        match self.scheme {
+            StickerLabelScheme::Face => face_symbol,
+            StickerLabelScheme::Custom => self
+                .custom
+                .split_whitespace()
+                .nth(sticker_index)
+                .unwrap_or(face_symbol),
+        }
+    }
+}
+
+/// Scheme used to label stickers.
+#[derive(Serialize, Deserialize, Debug, Display, EnumIter, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StickerLabelScheme {
+    /// Symbol of the face that the sticker belongs to.
+    #[default]
+    #[strum(serialize = "Face letter")]
+    Face,
+    /// Labels given by the user, such as a blindfolded letter scheme.
+    #[strum(serialize = "Custom")]
+    Custom,
+}
+
+/// Overlay showing recently pressed keys and the commands they triggered.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
+#[serde(default)]
+pub struct InputDisplayPreferences {
+    pub show: bool,
+
+    /// Maximum number of keypresses shown at once.
+    pub max_keys: usize,
+    /// How long each keypress is shown, in seconds.
+    pub duration: f32,
+    pub font_size: f32,
+    pub corner: OverlayCorner,
+}
+
+/// Corner of the window that an overlay is shown in.
+#[derive(Serialize, Deserialize, Debug, Display, EnumIter, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayCorner {
+    #[strum(serialize = "Top left")]
+    TopLeft,
+    #[strum(serialize = "Top right")]
+    TopRight,
+    #[default]
+    #[strum(serialize = "Bottom left")]
+    BottomLeft,
+    #[strum(serialize = "Bottom right")]
+    BottomRight,
+}
+impl OverlayCorner {
+    pub fn align(self) -> egui::Align2 {
+        match self {
+            OverlayCorner::TopLeft => egui::Align2::LEFT_TOP,
+            OverlayCorner::TopRight => egui::Align2::RIGHT_TOP,
+            OverlayCorner::BottomLeft => egui::Align2::LEFT_BOTTOM,
+            OverlayCorner::BottomRight => egui::Align2::RIGHT_BOTTOM,
+        }
+    }
+}
@@ -30,6 +30,8 @@ fn deser_valid_key_combo<'de, D: Deserializer<'de>>(deserializer: D) -> Result<K
 #[serde(default)]
 pub struct KeyCombo {
     pub keys: Vec<Key>,
+    #[serde(skip_serializing_if = "KeyComboMode::is_chord")]
+    mode: KeyComboMode,
 
     #[serde(skip_serializing_if = "is_false")]
     ctrl: bool,
@@ -39,6 +41,10 @@ pub struct KeyCombo {
     alt: bool,
     #[serde(skip_serializing_if = "is_false")]
     logo: bool,
+    /// Whether the combo matches even if modifiers other than the ones above
+    /// are held.
+    #[serde(skip_serializing_if = "is_false")]
+    ignore_other_mods: bool,
 }
 impl fmt::Display for KeyCombo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -49,7 +55,7 @@ impl fmt::Display for KeyCombo {
 
         for key in self.keys() {
             if display_text.len() > 0 {
-                display_text.push_str(" + ");
+                display_text.push_str(self.mode.separator());
             }
 
             match key {
@@ -74,20 +80,36 @@ impl fmt::Display for KeyCombo {
             }
         }
 
-        write!(f, "{}", display_text)
+        write!(f, "{}", display_text)?;
+        if self.mode == KeyComboMode::Tap {
+            write!(f, " (tap)")?;
+        }
+        if self.ignore_other_mods {
+            write!(f, " (any modifiers)")?;
+        }
+        Ok(())
     }
 }
 impl KeyCombo {
-    pub fn new(keys: Vec<Key>, mods: ModifiersState) -> Self {
+    pub fn new(keys: Vec<Key>, mods: ModifiersState, mode: KeyComboMode) -> Self {
         Self {
             keys,
+            mode,
             ctrl: mods.ctrl(),
             shift: mods.shift(),
             alt: mods.alt(),
             logo: mods.logo(),
+            ignore_other_mods: false,
         }
         .validate()
     }
+    /// Returns the same key combo, set to match even if other modifiers are
+    /// held.
+    #[must_use]
+    pub fn with_ignore_other_mods(mut self, ignore_other_mods: bool) -> Self {
+        self.ignore_other_mods = ignore_other_mods;
+        self
+    }
     #[must_use]
     pub fn validate(self) -> Self {
         let (mut ctrl, mut shift, mut alt, mut logo) = (false, false, false, false);
@@ -101,6 +123,7 @@ impl KeyCombo {
 
         Self {
             keys: self.keys.clone(),
+            mode: self.mode,
 
             // If a `key` in keys is equivalent to a modifier key, exclude it from the
             // modifier booleans.
@@ -108,11 +131,15 @@ impl KeyCombo {
             shift: *self.shift() && !shift,
             alt: *self.alt() && !alt,
             logo: *self.logo() && !logo,
+            ignore_other_mods: self.ignore_other_mods,
         }
     }
     pub fn keys(&self) -> &Vec<Key> {
         &self.keys
     }
+    pub fn mode(&self) -> KeyComboMode {
+        self.mode
+    }
     pub fn ctrl(&self) -> &bool {
         &self.ctrl
     }
@@ -125,6 +152,26 @@ impl KeyCombo {
     pub fn logo(&self) -> &bool {
         &self.logo
     }
+    pub fn ignore_other_mods(&self) -> bool {
+        self.ignore_other_mods
+    }
+
+    /// Returns whether the keys in the combo are all held down, given the
+    /// currently held keys in the order they were pressed.
+    pub fn keys_match(&self, pressed_keys: &[Key]) -> bool {
+        if self.keys.is_empty() || !self.keys.iter().all(|key| pressed_keys.contains(key)) {
+            return false;
+        }
+        match self.mode {
+            KeyComboMode::Chord | KeyComboMode::Tap => true,
+            // Other held keys are ignored here; the caller decides whether
+            // extra keys are allowed.
+            KeyComboMode::Sequence => pressed_keys
+                .iter()
+                .filter(|key| self.keys.contains(key))
+                .eq(&self.keys),
+        }
+    }
 
     pub fn mods(self) -> ModifiersState {
         let mut ret = ModifiersState::empty();
@@ -144,6 +191,31 @@ impl KeyCombo {
     }
 }
 
+/// How the keys of a multi-key combo must be pressed.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyComboMode {
+    /// All keys must be held at the same time, in any order.
+    #[default]
+    Chord,
+    /// All keys must be held at the same time, pressed in the order given.
+    Sequence,
+    /// A single key must be pressed and quickly released without pressing
+    /// anything else. This is mainly useful for binding lone modifier keys.
+    Tap,
+}
+impl KeyComboMode {
+    fn is_chord(&self) -> bool {
+        *self == Self::Chord
+    }
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Chord | Self::Tap => " + ",
+            Self::Sequence => " → ",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum Key {
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Arrangement of the GUI that can be saved and restored by name.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct Layout {
+    /// Windows that are open, keyed by window name.
+    pub windows: BTreeMap<String, WindowLayout>,
+    /// Name of the active 3D view preset.
+    pub view_3d_preset: Option<String>,
+    /// Name of the active 4D view preset.
+    pub view_4d_preset: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct WindowLayout {
+    /// Position of the top left corner of the window, if it is floating.
+    pub pos: Option<[f32; 2]>,
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct LeaderboardPreferences {
+    /// Whether to submit ranked solves to the leaderboard server. Nothing is
+    /// sent unless this is enabled.
+    pub enabled: bool,
+    /// URL of the leaderboard server, such as `https://example.com/api`.
+    pub server: String,
+    /// Name to show in the rankings.
+    pub player_name: String,
+}
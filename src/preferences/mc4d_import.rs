@@ -0,0 +1,132 @@
+//! Importer for MagicCube4D settings files.
+//!
+//! MC4D stores its settings as a Java properties file. Only the face colors,
+//! background color, and twist speed have equivalents in Hyperspeedcube. MC4D
+//! does not store keybinds in its settings file, so there are none to import.
+
+use itertools::Itertools;
+
+use super::{Preferences, DEFAULT_PREFS};
+use crate::puzzle::{Face, PuzzleTypeEnum};
+
+/// Symbols of the 3^4 faces in the order that MC4D numbers them.
+const MC4D_FACE_ORDER: [&str; 8] = ["I", "B", "D", "L", "R", "U", "F", "O"];
+
+const BACKGROUND_COLOR_KEY: &str = "sky.color";
+const TWIST_SPEED_KEY: &str = "twistfactor";
+
+/// Applies the settings from an MC4D settings file to `prefs`, returning
+/// warnings about settings that could not be imported.
+pub fn import_mc4d_settings(prefs: &mut Preferences, contents: &str) -> Vec<String> {
+    let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+
+    let mut warnings = vec![];
+    let mut ignored = vec![];
+
+    for (key, value) in parse_properties(contents) {
+        let face_index = key
+            .strip_prefix("face")
+            .and_then(|rest| rest.strip_suffix(".color"))
+            .and_then(|i| i.parse::<usize>().ok());
+
+        if let Some(i) = face_index {
+            let Some(symbol) = MC4D_FACE_ORDER.get(i) else {
+                warnings.push(format!("No face with index {i}"));
+                continue;
+            };
+            let Some(face) = ty.desc().faces().iter().position(|f| f.symbol == *symbol) else {
+                continue;
+            };
+            match parse_color(value) {
+                Some(color) => prefs.colors[(ty, Face(face as _))] = color,
+                None => warnings.push(format!("Invalid color for {key}: {value:?}")),
+            }
+        } else if key == BACKGROUND_COLOR_KEY {
+            match parse_color(value) {
+                Some(color) => prefs.colors.background = color,
+                None => warnings.push(format!("Invalid color for {key}: {value:?}")),
+            }
+        } else if key == TWIST_SPEED_KEY {
+            // MC4D scales the number of frames in each twist by this factor.
+            match value.parse::<f32>() {
+                Ok(factor) if factor > 0.0 => {
+                    prefs.interaction.twist_duration =
+                        DEFAULT_PREFS.interaction.twist_duration * factor;
+                }
+                _ => warnings.push(format!("Invalid twist speed: {value:?}")),
+            }
+        } else {
+            ignored.push(key);
+        }
+    }
+
+    if !ignored.is_empty() {
+        warnings.push(format!(
+            "Settings with no equivalent were ignored: {}",
+            ignored.iter().join(", "),
+        ));
+    }
+
+    prefs.needs_save = true;
+    warnings
+}
+
+/// Parses the key-value pairs in a Java properties file.
+fn parse_properties(contents: &str) -> impl Iterator<Item = (&str, &str)> {
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once(|c| c == '=' || c == ':')?;
+            Some((key.trim(), value.trim()))
+        })
+}
+
+/// Parses a color written as comma-separated RGB components from 0 to 255.
+fn parse_color(s: &str) -> Option<egui::Color32> {
+    let (r, g, b) = s
+        .split(',')
+        .map(|c| c.trim().parse::<u8>().ok())
+        .collect_tuple()?;
+    Some(egui::Color32::from_rgb(r?, g?, b?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_mc4d_settings() {
+        let mut prefs = DEFAULT_PREFS.clone();
+        let warnings = import_mc4d_settings(
+            &mut prefs,
+            "# MagicCube4D settings
+            face0.color=255,0,0
+            face7.color = 0, 0, 255
+            sky.color=10,20,30
+            twistfactor=2
+            eyew=5.2
+            face5.color=green",
+        );
+
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+        let face = |symbol| {
+            let i = ty
+                .desc()
+                .faces()
+                .iter()
+                .position(|f| f.symbol == symbol)
+                .unwrap();
+            Face(i as _)
+        };
+        assert_eq!(prefs.colors[(ty, face("I"))], egui::Color32::RED);
+        assert_eq!(prefs.colors[(ty, face("O"))], egui::Color32::BLUE);
+        assert_eq!(prefs.colors.background, egui::Color32::from_rgb(10, 20, 30),);
+        assert_eq!(
+            prefs.interaction.twist_duration,
+            DEFAULT_PREFS.interaction.twist_duration * 2.0,
+        );
+        assert_eq!(warnings.len(), 2);
+    }
+}
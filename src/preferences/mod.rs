@@ -11,35 +11,60 @@ use std::ops::{Index, IndexMut};
 use std::path::PathBuf;
 
 mod colors;
+mod cursor;
 mod gfx;
+mod goals;
 mod info;
-mod interaction;
 mod keybinds;
+mod layout;
+mod leaderboard;
+mod mc4d_import;
 mod migration;
 mod mousebinds;
-mod opacity;
 mod outlines;
 #[cfg(not(target_arch = "wasm32"))]
 mod persist_local;
 #[cfg(target_arch = "wasm32")]
 mod persist_web;
-mod view;
+mod plugins;
+mod solves;
+mod tracking;
+mod twist_buttons;
+mod updates;
+mod versus;
+mod window;
 
 use crate::commands::{Command, PuzzleCommand, PuzzleMouseCommand};
-use crate::puzzle::{traits::*, ProjectionType, PuzzleTypeEnum};
+use crate::puzzle::{ProjectionType, PuzzleTypeEnum};
 pub use colors::*;
+pub use cursor::*;
 pub use gfx::*;
+pub use goals::*;
+pub use hyperspeedcube_core::preferences::*;
 pub use info::*;
-pub use interaction::*;
 pub use keybinds::*;
+pub use layout::*;
+pub use leaderboard::*;
+pub use mc4d_import::*;
 pub use mousebinds::*;
-pub use opacity::*;
 pub use outlines::*;
 #[cfg(not(target_arch = "wasm32"))]
 use persist_local as persist;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use persist_local::{
+    achievements_file_path, crash_dump_dir_path, custom_shader_file_path,
+    leaderboard_queue_file_path, lifetime_stats_file_path, plugins_dir_path, session_file_path,
+    solves_dir_path,
+};
 #[cfg(target_arch = "wasm32")]
 use persist_web as persist;
-pub use view::*;
+pub use plugins::*;
+pub use solves::*;
+pub use tracking::*;
+pub use twist_buttons::*;
+pub use updates::*;
+pub use versus::*;
+pub use window::*;
 
 const PREFS_FILE_FORMAT: config::FileFormat = config::FileFormat::Yaml;
 const DEFAULT_PREFS_STR: &str = include_str!("default.yaml");
@@ -54,6 +79,10 @@ lazy_static! {
 pub struct Preferences {
     #[serde(skip)]
     pub needs_save: bool,
+    /// Whether there were no saved preferences to load, which means that this
+    /// is the first time the program has been run.
+    #[serde(skip)]
+    pub first_run: bool,
 
     /// Preferences file format version.
     #[serde(skip_deserializing)]
@@ -67,57 +96,86 @@ pub struct Preferences {
     pub info: InfoPreferences,
 
     pub gfx: GfxPreferences,
+    pub window: WindowPreferences,
     pub interaction: InteractionPreferences,
+    pub cursor: CursorPreferences,
     pub opacity: OpacityPreferences,
     pub outlines: OutlinePreferences,
+    pub tracking: TrackingPreferences,
+    pub twist_buttons: TwistButtonsPreferences,
 
     pub view_3d: WithPresets<ViewPreferences>,
     pub view_4d: WithPresets<ViewPreferences>,
+    /// View settings to show instead of the current ones for puzzles with the
+    /// given projection type, such as while playing back keyframes. These are
+    /// never saved.
+    #[serde(skip)]
+    pub view_override: Option<(ProjectionType, ViewPreferences)>,
+    /// Spacing overrides for individual faces, keyed by face symbol.
+    pub spacing_overrides: PerPuzzle<BTreeMap<String, SpacingOverride>>,
 
     pub colors: ColorPreferences,
 
     pub piece_filters: PerPuzzle<Vec<Preset<PieceFilter>>>,
+    /// Names of the piece filter presets used as phases of a solve, in order.
+    /// A phase is complete when all the pieces in its filter are solved.
+    pub phases: PerPuzzle<Vec<String>>,
+    /// Target move counts for each puzzle.
+    pub move_goals: PerPuzzle<MoveGoal>,
+
+    pub scramble: PerPuzzleFamily<ScrambleOptions>,
 
     pub global_keybinds: Vec<Keybind<Command>>,
     pub puzzle_keybinds: PerPuzzleFamily<PuzzleKeybindSets>,
     pub mousebinds: Vec<Mousebind<PuzzleMouseCommand>>,
+
+    pub versus: VersusPreferences,
+
+    pub plugins: PluginPreferences,
+
+    pub leaderboard: LeaderboardPreferences,
+
+    pub solves: SolvesPreferences,
+
+    pub updates: UpdatePreferences,
+
+    /// Named arrangements of windows and view presets.
+    pub layouts: Vec<Preset<Layout>>,
 }
 impl Preferences {
-    pub fn load(backup: Option<&Self>) -> Self {
+    /// Loads the user's preferences on top of the defaults. If the user's
+    /// preferences cannot be parsed, returns an error naming the line or field
+    /// that is invalid.
+    pub fn try_load() -> Result<Self, config::ConfigError> {
         let mut config = config::Config::builder();
 
         // Load default preferences.
-        let default_config_source = config::File::from_str(DEFAULT_PREFS_STR, PREFS_FILE_FORMAT);
-        config = config.add_source(default_config_source.clone());
+        config = config.add_source(config::File::from_str(DEFAULT_PREFS_STR, PREFS_FILE_FORMAT));
 
         // Load user preferences.
+        let first_run = !persist::user_prefs_exist();
         match persist::user_config_source() {
             Ok(config_source) => config = config.add_source(config_source),
             Err(e) => log::warn!("Error loading user preferences: {}", e),
         }
 
-        config
+        let mut prefs = config.build().and_then(migration::try_deserialize)?;
+        prefs.first_run = first_run;
+        Ok(prefs)
+    }
+    /// Loads only the default preferences.
+    pub fn load_defaults() -> Self {
+        config::Config::builder()
+            .add_source(config::File::from_str(DEFAULT_PREFS_STR, PREFS_FILE_FORMAT))
             .build()
-            .and_then(migration::try_deserialize)
-            .unwrap_or_else(|e| {
-                log::warn!("Error loading preferences: {}", e);
-
-                persist::backup_prefs_file();
-
-                // Try backup
-                backup
-                    .cloned()
-                    // Try just default config
-                    .or_else(|| {
-                        config::Config::builder()
-                            .add_source(default_config_source)
-                            .build()
-                            .ok()?
-                            .try_deserialize()
-                            .ok()
-                    })
-                    .unwrap_or_default()
-            })
+            .ok()
+            .and_then(|config| config.try_deserialize().ok())
+            .unwrap_or_default()
+    }
+    /// Moves the user's preferences file aside so that loading the defaults
+    /// does not overwrite it.
+    pub fn backup_user_prefs() {
+        persist::backup_prefs_file();
     }
 
     pub fn save(&mut self) {
@@ -126,6 +184,8 @@ impl Preferences {
 
             // Clear empty entries.
             self.piece_filters.map.retain(|_k, v| !v.is_empty());
+            self.phases.map.retain(|_k, v| !v.is_empty());
+            self.move_goals.map.retain(|_k, v| !v.is_empty());
 
             // Set version number.
             self.version = migration::LATEST_VERSION;
@@ -139,23 +199,42 @@ impl Preferences {
         }
     }
 
-    pub fn view(&self, ty: impl PuzzleType) -> &ViewPreferences {
+    pub fn view(&self, ty: PuzzleTypeEnum) -> &ViewPreferences {
+        if let Some((projection_type, view)) = &self.view_override {
+            if *projection_type == ty.projection_type() {
+                return view;
+            }
+        }
         match ty.projection_type() {
             ProjectionType::_3D => &self.view_3d.current,
             ProjectionType::_4D => &self.view_4d.current,
         }
     }
-    pub fn view_mut(&mut self, ty: impl PuzzleType) -> &mut ViewPreferences {
+    pub fn view_mut(&mut self, ty: PuzzleTypeEnum) -> &mut ViewPreferences {
         &mut self.view_presets(ty).current
     }
 
-    pub fn view_presets(&mut self, ty: impl PuzzleType) -> &mut WithPresets<ViewPreferences> {
+    pub fn view_presets(&mut self, ty: PuzzleTypeEnum) -> &mut WithPresets<ViewPreferences> {
         match ty.projection_type() {
             ProjectionType::_3D => &mut self.view_3d,
             ProjectionType::_4D => &mut self.view_4d,
         }
     }
 }
+impl PuzzlePreferences for Preferences {
+    fn view(&self, ty: PuzzleTypeEnum) -> &ViewPreferences {
+        Preferences::view(self, ty)
+    }
+    fn spacing_overrides(&self, ty: PuzzleTypeEnum) -> &BTreeMap<String, SpacingOverride> {
+        &self.spacing_overrides[ty]
+    }
+    fn interaction(&self) -> &InteractionPreferences {
+        &self.interaction
+    }
+    fn opacity(&self) -> &OpacityPreferences {
+        &self.opacity
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct PuzzleKeybindSets {
@@ -183,12 +262,17 @@ impl PuzzleKeybindSets {
         }
     }
     pub fn get_active(&self) -> Vec<&Preset<KeybindSet<PuzzleCommand>>> {
-        let mut included_names = vec![&self.active];
+        self.get_with_includes(&self.active)
+    }
+    /// Returns the keybind set with the given name, along with all the sets
+    /// that it includes.
+    pub fn get_with_includes(&self, set_name: &str) -> Vec<&Preset<KeybindSet<PuzzleCommand>>> {
+        let mut included_names = vec![set_name];
         let mut unprocessed_idx = 0;
         while unprocessed_idx < included_names.len() {
             if let Some(set) = self.get(included_names[unprocessed_idx]) {
                 for name in &set.value.includes {
-                    if !included_names.contains(&name) {
+                    if !included_names.contains(&name.as_str()) {
                         included_names.push(name);
                     }
                 }
@@ -199,11 +283,19 @@ impl PuzzleKeybindSets {
         // Standardize order.
         self.sets
             .iter()
-            .filter(|set| included_names.contains(&&set.preset_name))
+            .filter(|set| included_names.contains(&set.preset_name.as_str()))
             .collect()
     }
     pub fn get_active_keybinds(&self) -> impl '_ + Iterator<Item = &'_ Keybind<PuzzleCommand>> {
-        self.get_active()
+        self.get_keybinds(&self.active)
+    }
+    /// Returns the keybinds in the keybind set with the given name, including
+    /// those in the sets that it includes.
+    pub fn get_keybinds<'a>(
+        &'a self,
+        set_name: &str,
+    ) -> impl 'a + Iterator<Item = &'a Keybind<PuzzleCommand>> {
+        self.get_with_includes(set_name)
             .into_iter()
             .flat_map(|set| &set.value.keybinds)
     }
@@ -305,3 +397,32 @@ pub struct PieceFilter {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hidden_opacity: Option<f32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_from_str(user_prefs: &str) -> Result<Preferences, config::ConfigError> {
+        config::Config::builder()
+            .add_source(config::File::from_str(DEFAULT_PREFS_STR, PREFS_FILE_FORMAT))
+            .add_source(config::File::from_str(user_prefs, PREFS_FILE_FORMAT))
+            .build()
+            .and_then(migration::try_deserialize)
+    }
+
+    #[test]
+    fn test_invalid_prefs_errors() {
+        load_from_str("version: 1").unwrap();
+
+        // Invalid values name the field.
+        let e = load_from_str("version: 1\ninteraction:\n  drag_sensitivity: fast\n");
+        let e = e.unwrap_err().to_string();
+        assert!(e.contains("drag_sensitivity"), "{e}");
+
+        // Syntax errors name the line.
+        let e = load_from_str("version: 1\ninteraction: [\n")
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains("line"), "{e}");
+    }
+}
@@ -1,4 +1,6 @@
+use crate::puzzle::VisualPieceState;
 use crate::serde_impl::hex_color;
+use crate::util;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -20,3 +22,39 @@ pub struct OutlinePreferences {
     #[serde(with = "hex_color")]
     pub selected_piece_color: egui::Color32,
 }
+impl OutlinePreferences {
+    /// Returns the outline color of a sticker on a piece.
+    pub fn color(&self, state: VisualPieceState, is_sticker_selected: bool) -> egui::Rgba {
+        let hidden_or_ungripped = f32::max(state.hidden, state.ungripped);
+
+        let mut ret = egui::Rgba::from(self.default_color);
+        // In order from lowest to highest priority:
+        ret = util::mix(
+            ret,
+            egui::Rgba::from(self.hidden_color),
+            hidden_or_ungripped,
+        );
+        ret = util::mix(ret, egui::Rgba::from(self.hovered_color), state.hovered);
+        ret = util::mix(
+            ret,
+            egui::Rgba::from(if is_sticker_selected {
+                self.selected_sticker_color
+            } else {
+                self.selected_piece_color
+            }),
+            state.selected,
+        );
+        ret
+    }
+    /// Returns the outline size of a sticker on a piece.
+    pub fn size(&self, state: VisualPieceState) -> f32 {
+        let hidden_or_ungripped = f32::max(state.hidden, state.ungripped);
+
+        let mut ret = self.default_size;
+        // In order from lowest to highest priority:
+        ret = util::mix(ret, self.hidden_size, hidden_or_ungripped);
+        ret = util::mix(ret, self.selected_size, state.selected);
+        ret = util::mix(ret, self.hovered_size, state.hovered);
+        ret
+    }
+}
@@ -5,6 +5,15 @@ use std::path::PathBuf;
 
 const PREFS_FILE_NAME: &str = "hyperspeedcube";
 const PREFS_FILE_EXTENSION: &str = "yaml";
+const SESSION_FILE_NAME: &str = "hyperspeedcube_session";
+const ACHIEVEMENTS_FILE_NAME: &str = "hyperspeedcube_achievements";
+const LEADERBOARD_QUEUE_FILE_NAME: &str = "hyperspeedcube_leaderboard_queue";
+const LIFETIME_STATS_FILE_NAME: &str = "hyperspeedcube_stats";
+const CRASH_DUMP_DIR_NAME: &str = "crash_dumps";
+const PLUGINS_DIR_NAME: &str = "plugins";
+const SOLVES_DIR_NAME: &str = "solves";
+const SHADERS_DIR_NAME: &str = "shaders";
+const CUSTOM_SHADER_FILE_NAME: &str = "basic.wgsl";
 
 // File paths
 lazy_static! {
@@ -63,7 +72,86 @@ impl Error for PrefsError {}
 pub fn user_config_source() -> Result<impl config::Source, PrefsError> {
     PREFS_FILE_PATH
         .clone()
-        .map(|path| config::File::from(path.as_ref()))
+        .map(|path| config::File::from(path.as_ref()).required(false))
+}
+
+/// Returns whether the user's preferences have been saved before.
+pub fn user_prefs_exist() -> bool {
+    PREFS_FILE_PATH.as_ref().map_or(false, |path| path.exists())
+}
+
+/// Returns the path of the file that stores the session, which is in the same
+/// directory as the preferences file.
+pub fn session_file_path() -> Result<PathBuf, PrefsError> {
+    let mut p = PREFS_FILE_PATH.clone()?;
+    p.set_file_name(format!("{}.{}", SESSION_FILE_NAME, PREFS_FILE_EXTENSION));
+    Ok(p)
+}
+
+/// Returns the path of the file that stores unlocked achievements, which is in
+/// the same directory as the preferences file.
+pub fn achievements_file_path() -> Result<PathBuf, PrefsError> {
+    let mut p = PREFS_FILE_PATH.clone()?;
+    p.set_file_name(format!(
+        "{}.{}",
+        ACHIEVEMENTS_FILE_NAME, PREFS_FILE_EXTENSION
+    ));
+    Ok(p)
+}
+
+/// Returns the path of the file that stores leaderboard submissions that have
+/// not been sent yet, which is in the same directory as the preferences file.
+pub fn leaderboard_queue_file_path() -> Result<PathBuf, PrefsError> {
+    let mut p = PREFS_FILE_PATH.clone()?;
+    p.set_file_name(format!(
+        "{}.{}",
+        LEADERBOARD_QUEUE_FILE_NAME, PREFS_FILE_EXTENSION
+    ));
+    Ok(p)
+}
+
+/// Returns the path of the file that stores lifetime statistics, which is in
+/// the same directory as the preferences file.
+pub fn lifetime_stats_file_path() -> Result<PathBuf, PrefsError> {
+    let mut p = PREFS_FILE_PATH.clone()?;
+    p.set_file_name(format!(
+        "{}.{}",
+        LIFETIME_STATS_FILE_NAME, PREFS_FILE_EXTENSION
+    ));
+    Ok(p)
+}
+
+/// Returns the path of the folder that stores crash dumps, which is in the
+/// same directory as the preferences file.
+pub fn crash_dump_dir_path() -> Result<PathBuf, PrefsError> {
+    let mut p = PREFS_FILE_PATH.clone()?;
+    p.set_file_name(CRASH_DUMP_DIR_NAME);
+    Ok(p)
+}
+
+/// Returns the path of the folder that plugins are loaded from, which is in
+/// the same directory as the preferences file.
+pub fn plugins_dir_path() -> Result<PathBuf, PrefsError> {
+    let mut p = PREFS_FILE_PATH.clone()?;
+    p.set_file_name(PLUGINS_DIR_NAME);
+    Ok(p)
+}
+
+/// Returns the path of the file that the custom shader is loaded from, which
+/// is in a folder in the same directory as the preferences file.
+pub fn custom_shader_file_path() -> Result<PathBuf, PrefsError> {
+    let mut p = PREFS_FILE_PATH.clone()?;
+    p.set_file_name(SHADERS_DIR_NAME);
+    p.push(CUSTOM_SHADER_FILE_NAME);
+    Ok(p)
+}
+
+/// Returns the default path of the folder that finished solves are saved to,
+/// which is in the same directory as the preferences file.
+pub fn solves_dir_path() -> Result<PathBuf, PrefsError> {
+    let mut p = PREFS_FILE_PATH.clone()?;
+    p.set_file_name(SOLVES_DIR_NAME);
+    Ok(p)
 }
 
 pub fn save(prefs_data: &impl Serialize) -> anyhow::Result<()> {
@@ -24,6 +24,13 @@ pub fn user_config_source() -> Result<impl config::Source, PrefsError> {
     ))
 }
 
+/// Returns whether the user's preferences have been saved before.
+pub fn user_prefs_exist() -> bool {
+    local_storage().map_or(false, |storage| {
+        storage.get_item(PREFS_KEY).ok().flatten().is_some()
+    })
+}
+
 pub fn save(prefs_data: &impl Serialize) -> anyhow::Result<()> {
     let prefs_string = serde_yaml::to_string(prefs_data).map_err(|e| anyhow!(e))?;
     local_storage()?
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct PluginPreferences {
+    /// Names of plugins in the plugins folder that are not loaded.
+    pub disabled: Vec<String>,
+}
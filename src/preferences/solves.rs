@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SolvesPreferences {
+    /// Whether to save the log file of every solve when it is finished.
+    pub auto_save: bool,
+    /// Folder to save solves to, or `None` to use the `solves` folder next to
+    /// the preferences file.
+    pub dir: Option<PathBuf>,
+}
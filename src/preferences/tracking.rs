@@ -0,0 +1,12 @@
+use crate::serde_impl::hex_color;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct TrackingPreferences {
+    /// Number of twists shown in the trail of each tracked piece.
+    pub trail_length: usize,
+    pub trail_size: f32,
+    #[serde(with = "hex_color")]
+    pub trail_color: egui::Color32,
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct TwistButtonsPreferences {
+    pub layout: TwistButtonsLayout,
+    /// Width and height of each button, in points.
+    pub button_size: f32,
+}
+
+/// Arrangement of the on-screen twist buttons.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TwistButtonsLayout {
+    /// One row per twist axis, with one button per twist direction.
+    #[default]
+    AxesInRows,
+    /// One row per twist direction, with one button per twist axis.
+    DirectionsInRows,
+}
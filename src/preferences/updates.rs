@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct UpdatePreferences {
+    /// Whether to check GitHub for a new version on startup.
+    pub check_on_startup: bool,
+    /// Version that the user chose not to be notified about.
+    pub skipped_version: String,
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct VersusPreferences {
+    /// Name of the puzzle keybind set used by the first player.
+    pub player_1_keybinds: String,
+    /// Name of the puzzle keybind set used by the second player.
+    pub player_2_keybinds: String,
+}
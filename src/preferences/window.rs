@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use winit::dpi::{LogicalSize, PhysicalPosition};
+use winit::event_loop::EventLoopWindowTarget;
+use winit::monitor::{MonitorHandle, VideoMode};
+use winit::window::{Fullscreen, Window, WindowBuilder};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WindowPreferences {
+    /// Whether to save the placement of the window on exit and restore it on
+    /// the next launch.
+    pub remember_placement: bool,
+    /// Size of the window contents at startup, in logical pixels.
+    pub size: Option<[f64; 2]>,
+    /// Position of the window at startup, in physical pixels relative to the
+    /// top left corner of the monitor.
+    pub position: Option<[i32; 2]>,
+    /// Name of the monitor to open the window on.
+    pub monitor: Option<String>,
+    /// Whether to open the window in fullscreen.
+    pub fullscreen: bool,
+    /// Kind of fullscreen to use.
+    pub fullscreen_mode: FullscreenMode,
+    /// Scale of the GUI, relative to the scale factor of the monitor.
+    pub gui_scale: f32,
+}
+impl Default for WindowPreferences {
+    fn default() -> Self {
+        Self {
+            remember_placement: true,
+            size: None,
+            position: None,
+            monitor: None,
+            fullscreen: false,
+            fullscreen_mode: FullscreenMode::default(),
+            gui_scale: 1.0,
+        }
+    }
+}
+impl WindowPreferences {
+    /// Applies the startup size, position, monitor, and fullscreen state to a
+    /// window that has not been built yet.
+    pub fn apply_to_builder<T>(
+        &self,
+        mut builder: WindowBuilder,
+        event_loop: &EventLoopWindowTarget<T>,
+    ) -> WindowBuilder {
+        let monitor = self
+            .monitor
+            .as_ref()
+            .and_then(|name| {
+                event_loop
+                    .available_monitors()
+                    .find(|m| m.name().as_ref() == Some(name))
+            })
+            .or_else(|| event_loop.primary_monitor());
+
+        if let Some([width, height]) = self.size {
+            builder = builder.with_inner_size(LogicalSize::new(width, height));
+        }
+        if let (Some([x, y]), Some(m)) = (self.position, &monitor) {
+            let origin = m.position();
+            builder = builder.with_position(PhysicalPosition::new(origin.x + x, origin.y + y));
+        }
+        if self.fullscreen {
+            builder = builder.with_fullscreen(Some(self.fullscreen_mode.fullscreen(monitor)));
+        }
+        builder
+    }
+
+    /// Records the current size, position, monitor, and fullscreen state of
+    /// the window, to be restored on the next launch.
+    pub fn save_placement(&mut self, window: &Window) {
+        let monitor = window.current_monitor();
+        self.monitor = monitor.as_ref().and_then(|m| m.name());
+        self.fullscreen = window.fullscreen().is_some();
+
+        // Keep the windowed placement while in fullscreen, so that leaving
+        // fullscreen after the next launch restores it.
+        if !self.fullscreen {
+            let size: LogicalSize<f64> = window.inner_size().to_logical(window.scale_factor());
+            self.size = Some([size.width, size.height]);
+            if let (Ok(pos), Some(m)) = (window.outer_position(), &monitor) {
+                let origin = m.position();
+                self.position = Some([pos.x - origin.x, pos.y - origin.y]);
+            }
+        }
+    }
+
+    /// Returns the fullscreen state to switch to when toggling fullscreen.
+    pub fn toggled_fullscreen(&self, window: &Window) -> Option<Fullscreen> {
+        match window.fullscreen() {
+            Some(_) => None,
+            None => Some(self.fullscreen_mode.fullscreen(window.current_monitor())),
+        }
+    }
+}
+
+/// Kind of fullscreen.
+#[derive(Serialize, Deserialize, Debug, Display, EnumIter, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FullscreenMode {
+    /// Borderless window covering the whole monitor.
+    #[default]
+    #[strum(serialize = "Borderless window")]
+    Borderless,
+    /// Exclusive fullscreen at the highest resolution and refresh rate of the
+    /// monitor.
+    #[strum(serialize = "Exclusive fullscreen")]
+    Exclusive,
+}
+impl FullscreenMode {
+    /// Returns the fullscreen state for this mode on a monitor. Exclusive
+    /// fullscreen falls back to a borderless window if the monitor's video
+    /// modes are unknown.
+    pub fn fullscreen(self, monitor: Option<MonitorHandle>) -> Fullscreen {
+        match self {
+            Self::Borderless => Fullscreen::Borderless(monitor),
+            Self::Exclusive => match monitor.as_ref().and_then(best_video_mode) {
+                Some(mode) => Fullscreen::Exclusive(mode),
+                None => Fullscreen::Borderless(monitor),
+            },
+        }
+    }
+}
+
+fn best_video_mode(monitor: &MonitorHandle) -> Option<VideoMode> {
+    monitor.video_modes().max_by_key(|mode| {
+        let size = mode.size();
+        (
+            size.width * size.height,
+            mode.refresh_rate_millihertz(),
+            mode.bit_depth(),
+        )
+    })
+}
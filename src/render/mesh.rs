@@ -26,6 +26,21 @@ pub(super) fn make_puzzle_mesh(
     // incrementation for each sticker to get the next-largest `f32` value.
     let mut z = 0.5_f32;
 
+    // Fog does need the real depth values, normalized so that the front of the
+    // puzzle is at 0 and the back is at 1.
+    let (min_z, max_z) = sticker_geometries
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), geom| {
+            (lo.min(geom.min_bound.z), hi.max(geom.max_bound.z))
+        });
+    let fog_depth = |z: f32| {
+        if max_z > min_z {
+            (max_z - z) / (max_z - min_z)
+        } else {
+            0.0
+        }
+    };
+
     let face_colors = &prefs.colors.face_colors_list(puzzle.ty());
 
     for geom in sticker_geometries {
@@ -34,24 +49,26 @@ pub(super) fn make_puzzle_mesh(
         let visual_state = puzzle.visual_piece_state(sticker_info.piece);
 
         // Determine sticker alpha.
-        let alpha = visual_state.opacity(prefs);
+        let alpha = visual_state.opacity(&prefs.opacity);
 
         // Determine sticker fill color.
         let sticker_color = egui::Rgba::from(if prefs.colors.blindfold {
             prefs.colors.blind_face
         } else {
-            face_colors[puzzle.info(geom.sticker).color.0 as usize]
+            face_colors[puzzle.sticker_color(geom.sticker).0 as usize]
         })
         .multiply(alpha);
 
         // Determine outline appearance.
-        let outline_color = visual_state
-            .outline_color(prefs, puzzle.selection().contains(&geom.sticker))
+        let outline_color = prefs
+            .outlines
+            .color(visual_state, puzzle.selection().contains(&geom.sticker))
             .multiply(alpha);
-        let outline_size = visual_state.outline_size(prefs);
+        let outline_size = prefs.outlines.size(visual_state);
 
         // Generate outline vertices.
         if outline_size > 0.0 {
+            let depth = fog_depth((geom.min_bound.z + geom.max_bound.z) / 2.0);
             let mut outlines = vec![];
             for polygon in &*geom.front_polygons {
                 for (a, b) in polygon
@@ -75,6 +92,7 @@ pub(super) fn make_puzzle_mesh(
                 |Point2 { x, y }| RgbaVertex {
                     pos: [x, y, z],
                     color: outline_color.to_array(),
+                    depth,
                 },
             );
         }
@@ -82,15 +100,26 @@ pub(super) fn make_puzzle_mesh(
         // Generate face vertices.
         for polygon in &*geom.front_polygons {
             let base = verts.len() as u32;
-            verts.extend(polygon.verts.iter().map(|v| RgbaVertex {
-                pos: [v.x, v.y, z],
-                color: [
-                    sticker_color.r() * polygon.illumination,
-                    sticker_color.g() * polygon.illumination,
-                    sticker_color.b() * polygon.illumination,
-                    sticker_color.a(),
-                ],
-            }));
+            verts.extend(
+                polygon
+                    .verts
+                    .iter()
+                    .zip(&polygon.alphas)
+                    .map(|(v, &alpha)| {
+                        // Colors are premultiplied, so scale every channel.
+                        let color = sticker_color.multiply(alpha);
+                        RgbaVertex {
+                            pos: [v.x, v.y, z],
+                            color: [
+                                color.r() * polygon.illumination,
+                                color.g() * polygon.illumination,
+                                color.b() * polygon.illumination,
+                                color.a(),
+                            ],
+                            depth: fog_depth(v.z),
+                        }
+                    }),
+            );
             let n = polygon.verts.len() as u32;
             indices.extend((2..n).flat_map(|i| [base, base + i - 1, base + i]));
         }
@@ -103,6 +132,106 @@ pub(super) fn make_puzzle_mesh(
     (verts, indices)
 }
 
+/// Generates a mesh for the ghost overlay, which is drawn faintly behind the
+/// puzzle. Outlines are omitted.
+pub(super) fn make_ghost_mesh(
+    puzzle: &PuzzleController,
+    prefs: &Preferences,
+    sticker_geometries: &[ProjectedStickerGeometry],
+) -> (Vec<RgbaVertex>, Vec<u32>) {
+    let mut verts = vec![];
+    let mut indices = vec![];
+
+    // Start well below the puzzle mesh so that the ghost is only visible where
+    // the puzzle does not cover it.
+    let mut z = 0.25_f32;
+
+    let face_colors = &prefs.colors.face_colors_list(puzzle.ty());
+    let alpha = prefs.opacity.ghost;
+
+    for geom in sticker_geometries {
+        let sticker_color = egui::Rgba::from(if prefs.colors.blindfold {
+            prefs.colors.blind_face
+        } else {
+            face_colors[puzzle.info(geom.sticker).color.0 as usize]
+        })
+        .multiply(alpha);
+
+        for polygon in &*geom.front_polygons {
+            let base = verts.len() as u32;
+            verts.extend(
+                polygon
+                    .verts
+                    .iter()
+                    .zip(&polygon.alphas)
+                    .map(|(v, &alpha)| {
+                        // Colors are premultiplied, so scale every channel.
+                        let color = sticker_color.multiply(alpha);
+                        RgbaVertex {
+                            pos: [v.x, v.y, z],
+                            color: [
+                                color.r() * polygon.illumination,
+                                color.g() * polygon.illumination,
+                                color.b() * polygon.illumination,
+                                color.a(),
+                            ],
+                            depth: 0.0,
+                        }
+                    }),
+            );
+            let n = polygon.verts.len() as u32;
+            indices.extend((2..n).flat_map(|i| [base, base + i - 1, base + i]));
+        }
+
+        z = f32::from_bits(z.to_bits() + 1);
+    }
+
+    (verts, indices)
+}
+
+/// Adds trails for tracked pieces to a mesh, drawn on top of everything else.
+/// Each trail ends in an arrowhead pointing toward the piece's current
+/// location.
+pub(super) fn add_trail_mesh(
+    verts: &mut Vec<RgbaVertex>,
+    indices: &mut Vec<u32>,
+    prefs: &Preferences,
+    trails: &[Vec<Point2<f32>>],
+) {
+    const Z: f32 = 0.9;
+    const ARROWHEAD_SCALE: f32 = 4.0;
+
+    let color = egui::Rgba::from(prefs.tracking.trail_color).to_array();
+    let make_vert = |Point2 { x, y }: Point2<f32>| RgbaVertex {
+        pos: [x, y, Z],
+        color,
+        depth: 0.0,
+    };
+
+    for trail in trails {
+        // Skip segments with zero length, since they have no direction.
+        let lines = trail
+            .iter()
+            .tuple_windows()
+            .filter(|(a, b)| a != b)
+            .map(|(&a, &b)| [a, b])
+            .collect_vec();
+        let Some(&[a, b]) = lines.last() else {
+            continue;
+        };
+
+        generate_outline_geometry(verts, indices, &lines, prefs.tracking.trail_size, make_vert);
+
+        // Generate arrowhead.
+        let size = prefs.tracking.trail_size * OUTLINE_SCALE * ARROWHEAD_SCALE;
+        let forward = (b - a).normalize_to(size);
+        let side = cgmath::vec2(-forward.y, forward.x);
+        let base = verts.len() as u32;
+        verts.extend([b + forward, b - forward + side, b - forward - side].map(make_vert));
+        indices.extend([base, base + 1, base + 2]);
+    }
+}
+
 fn generate_outline_geometry(
     verts_out: &mut Vec<RgbaVertex>,
     indices_out: &mut Vec<u32>,
@@ -188,3 +317,43 @@ fn generate_outline_geometry(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preferences::DEFAULT_PREFS;
+
+    fn z_range(verts: &[RgbaVertex]) -> (f32, f32) {
+        verts
+            .iter()
+            .map(|v| v.pos[2])
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), z| {
+                (lo.min(z), hi.max(z))
+            })
+    }
+
+    #[test]
+    fn test_ghost_behind_puzzle() {
+        let mut prefs = DEFAULT_PREFS.clone();
+        prefs.opacity.show_ghost = true;
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let geometry = puzzle.geometry(&prefs);
+        let ghost_geometry = puzzle.ghost_geometry(&prefs).unwrap();
+
+        let (puzzle_verts, _) = make_puzzle_mesh(&mut puzzle, &prefs, &geometry);
+        let (ghost_verts, _) = make_ghost_mesh(&puzzle, &prefs, &ghost_geometry);
+        let mut trail_verts = vec![];
+        let trail = vec![point2(0.0, 0.0), point2(0.5, 0.5)];
+        add_trail_mesh(&mut trail_verts, &mut vec![], &prefs, &[trail]);
+
+        // Nearer vertices have larger Z values.
+        let (puzzle_min, puzzle_max) = z_range(&puzzle_verts);
+        let (_, ghost_max) = z_range(&ghost_verts);
+        let (trail_min, _) = z_range(&trail_verts);
+        assert!(
+            ghost_max < puzzle_min,
+            "ghost is drawn in front of the puzzle"
+        );
+        assert!(puzzle_max < trail_min, "trails are drawn behind the puzzle");
+    }
+}
@@ -10,7 +10,10 @@ mod state;
 mod structs;
 
 use crate::app::App;
-use crate::puzzle::ProjectedStickerGeometry;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::bundle::Screenshot;
+use crate::preferences::Preferences;
+use crate::puzzle::{ProjectedStickerGeometry, PuzzleController};
 use cache::{CachedDynamicBuffer, CachedUniformBuffer};
 pub(crate) use state::GraphicsState;
 use structs::*;
@@ -30,9 +33,13 @@ pub(crate) struct PuzzleRenderCache {
     last_render_time: Instant,
     last_params: Option<PuzzleRenderParams>,
     last_puzzle_geometry: Option<Arc<Vec<ProjectedStickerGeometry>>>,
+    last_ghost_geometry: Option<Arc<Vec<ProjectedStickerGeometry>>>,
+    last_piece_trails: Vec<Vec<cgmath::Point2<f32>>>,
 
     vertex_buffer: CachedDynamicBuffer,
     index_buffer: CachedDynamicBuffer,
+    overlay_vertex_buffer: CachedDynamicBuffer,
+    overlay_index_buffer: CachedDynamicBuffer,
     uniform_buffer: CachedUniformBuffer<BasicUniform>,
 
     multisample_texture: Option<(wgpu::Texture, wgpu::TextureView)>,
@@ -40,6 +47,9 @@ pub(crate) struct PuzzleRenderCache {
     depth_texture: Option<(wgpu::Texture, wgpu::TextureView)>,
 
     basic_pipeline: Option<wgpu::RenderPipeline>,
+    /// Generation of the custom shader that `basic_pipeline` was created with.
+    #[cfg(not(target_arch = "wasm32"))]
+    shader_generation: u64,
 }
 impl Default for PuzzleRenderCache {
     fn default() -> Self {
@@ -47,6 +57,8 @@ impl Default for PuzzleRenderCache {
             last_render_time: Instant::now(),
             last_params: None,
             last_puzzle_geometry: None,
+            last_ghost_geometry: None,
+            last_piece_trails: vec![],
 
             vertex_buffer: CachedDynamicBuffer::new::<RgbaVertex>(
                 Some("puzzle_vertex_buffer"),
@@ -56,6 +68,14 @@ impl Default for PuzzleRenderCache {
                 Some("puzzle_index_buffer"),
                 wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::INDEX,
             ),
+            overlay_vertex_buffer: CachedDynamicBuffer::new::<RgbaVertex>(
+                Some("overlay_vertex_buffer"),
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+            ),
+            overlay_index_buffer: CachedDynamicBuffer::new::<u32>(
+                Some("overlay_index_buffer"),
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::INDEX,
+            ),
             uniform_buffer: CachedUniformBuffer::new(Some("puzzle_uniform_buffer"), 0),
 
             multisample_texture: None,
@@ -63,6 +83,8 @@ impl Default for PuzzleRenderCache {
             depth_texture: None,
 
             basic_pipeline: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            shader_generation: 0,
         }
     }
 }
@@ -100,9 +122,60 @@ impl PuzzleRenderCache {
 pub(crate) fn draw_puzzle(
     app: &mut App,
     gfx: &mut GraphicsState,
+    force_redraw: bool,
+) -> Option<wgpu::TextureView> {
+    // Disable MSAA on web.
+    #[cfg(target_arch = "wasm32")]
+    {
+        app.prefs.gfx.msaa = false;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let force_redraw = update_custom_shader(app, gfx) || force_redraw;
+
+    draw_puzzle_view(
+        &mut app.puzzle,
+        &mut app.render_cache,
+        &app.prefs,
+        app.puzzle_texture_size,
+        app.cursor_pos,
+        gfx,
+        force_redraw,
+    )
+}
+
+/// Reloads the custom shader if it changed and reports whether it loaded
+/// successfully. Returns whether the shader changed.
+#[cfg(not(target_arch = "wasm32"))]
+fn update_custom_shader(app: &mut App, gfx: &mut GraphicsState) -> bool {
+    let enabled = app.prefs.gfx.custom_shader;
+    let shader = &mut gfx.shaders.custom_basic;
+    let changed = shader.update(&gfx.device, enabled);
+    if changed && enabled {
+        match shader.error() {
+            None => app.set_status_ok("Loaded custom shader"),
+            Some(e) => app.set_status_err(format!("Error in custom shader: {e}")),
+        }
+    }
+    // Errors from creating the pipeline are only found while drawing, so this
+    // is updated every frame.
+    if app.custom_shader_error.as_deref() != shader.error() {
+        app.custom_shader_error = shader.error().map(str::to_owned);
+    }
+    changed
+}
+
+/// Draws a puzzle to the "out" texture of `cache`, and returns a view of the
+/// texture if it was redrawn.
+pub(crate) fn draw_puzzle_view(
+    puzzle: &mut PuzzleController,
+    cache: &mut PuzzleRenderCache,
+    prefs: &Preferences,
+    (width, height): (u32, u32),
+    cursor_pos: Option<cgmath::Point2<f32>>,
+    gfx: &mut GraphicsState,
     mut force_redraw: bool,
 ) -> Option<wgpu::TextureView> {
-    let (width, height) = app.puzzle_texture_size;
     let size = cgmath::vec2(width as f32, height as f32);
 
     // Avoid divide-by-zero errors.
@@ -110,16 +183,7 @@ pub(crate) fn draw_puzzle(
         return None;
     }
 
-    // Disable MSAA on web.
-    #[cfg(target_arch = "wasm32")]
-    {
-        app.prefs.gfx.msaa = false;
-    }
-
-    let puzzle = &mut app.puzzle;
-    let prefs = &app.prefs;
     let view_prefs = puzzle.view_prefs(prefs);
-    let cache = &mut app.render_cache;
 
     let now = Instant::now();
     let delta = now - cache.last_render_time;
@@ -157,9 +221,25 @@ pub(crate) fn draw_puzzle(
     }
     cache.last_puzzle_geometry = Some(Arc::clone(&puzzle_geometry));
 
+    // Same for the ghost overlay.
+    let ghost_geometry = puzzle.ghost_geometry(prefs);
+    match (&ghost_geometry, &cache.last_ghost_geometry) {
+        (Some(new_geom), Some(old_geom)) if Arc::ptr_eq(new_geom, old_geom) => (),
+        (None, None) => (),
+        _ => force_redraw = true,
+    }
+    cache.last_ghost_geometry = ghost_geometry.clone();
+
+    // Same for the trails of tracked pieces.
+    let piece_trails = puzzle.piece_trails(prefs.tracking.trail_length);
+    if piece_trails != cache.last_piece_trails {
+        force_redraw = true;
+    }
+    cache.last_piece_trails = piece_trails.clone();
+
     // Determine which sticker(s) are at the mouse cursor, in order from front
     // to back.
-    if let Some(cursor_pos) = app.cursor_pos {
+    if let Some(cursor_pos) = cursor_pos {
         let transformed_cursor_pos = cgmath::point2(
             (cursor_pos.x - view_prefs.align_h) / scale.x,
             (cursor_pos.y - view_prefs.align_v) / scale.y,
@@ -183,6 +263,16 @@ pub(crate) fn draw_puzzle(
 
     // Generate the mesh.
     let (mut verts, mut indices) = mesh::make_puzzle_mesh(puzzle, prefs, &puzzle_geometry);
+    let (mut overlay_verts, mut overlay_indices) = match &ghost_geometry {
+        Some(geom) => mesh::make_ghost_mesh(puzzle, prefs, geom),
+        None => (vec![], vec![]),
+    };
+    mesh::add_trail_mesh(
+        &mut overlay_verts,
+        &mut overlay_indices,
+        prefs,
+        &piece_trails,
+    );
 
     // Create "out" texture that will ultimately be returned.
     let (out_texture, out_texture_view) = cache.out_texture.get_or_insert_with(|| {
@@ -193,7 +283,10 @@ pub(crate) fn draw_puzzle(
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: gfx.config.format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            // `COPY_SRC` is needed for screenshots.
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC,
         })
     });
 
@@ -262,6 +355,57 @@ pub(crate) fn draw_puzzle(
         }
     };
 
+    // Populate uniform.
+    let uniform = BasicUniform {
+        scale: scale.into(),
+        align: [view_prefs.align_h, view_prefs.align_v],
+        fog_color: egui::Rgba::from(prefs.gfx.fog_color).to_array(),
+        fog_density: prefs.gfx.effective_fog_density(),
+        _padding: [0.0; 3],
+    };
+    cache.uniform_buffer.write(gfx, &uniform);
+
+    // Recreate the pipeline if the custom shader changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let shader_generation = gfx.shaders.custom_basic.generation();
+        if cache.shader_generation != shader_generation {
+            cache.shader_generation = shader_generation;
+            cache.basic_pipeline = None;
+        }
+    }
+
+    // Create pipeline.
+    let basic_pipeline = cache.basic_pipeline.get_or_insert_with(|| {
+        let bind_group_layout = cache.uniform_buffer.bind_group_layout(gfx);
+        let sample_count = prefs.gfx.sample_count();
+
+        // Fall back to the built-in shader if the custom shader is invalid.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(module) = gfx.shaders.custom_basic.module() {
+            gfx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let pipeline = create_basic_pipeline(gfx, bind_group_layout, module, sample_count);
+            match pollster::block_on(gfx.device.pop_error_scope()) {
+                None => return pipeline,
+                Some(e) => gfx.shaders.custom_basic.reject(e.to_string()),
+            }
+        }
+
+        let module = gfx.shaders.basic.get(gfx);
+        create_basic_pipeline(gfx, bind_group_layout, module, sample_count)
+    });
+
+    // The ghost overlay and piece trails are drawn in a second pass on top of
+    // the first.
+    let overlay_pass_color_attachment = wgpu::RenderPassColorAttachment {
+        view: render_pass_color_attachment.view,
+        resolve_target: render_pass_color_attachment.resolve_target,
+        ops: wgpu::Operations {
+            load: wgpu::LoadOp::Load,
+            store: true,
+        },
+    };
+
     // Begin the render pass.
     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
         label: Some("puzzle_stickers_render_pass"),
@@ -278,55 +422,7 @@ pub(crate) fn draw_puzzle(
 
     // Draw stickers, if there's anything to draw.
     if !indices.is_empty() {
-        // Set pipeline.
-        render_pass.set_pipeline(cache.basic_pipeline.get_or_insert_with(|| {
-            gfx.device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("basic_pipeline"),
-                    layout: Some(&gfx.device.create_pipeline_layout(
-                        &wgpu::PipelineLayoutDescriptor {
-                            label: Some("basic_pipeline_layout"),
-                            bind_group_layouts: &[cache.uniform_buffer.bind_group_layout(gfx)],
-                            push_constant_ranges: &[],
-                        },
-                    )),
-                    vertex: wgpu::VertexState {
-                        module: gfx.shaders.basic.get(gfx),
-                        entry_point: "vs_main",
-                        buffers: &[RgbaVertex::LAYOUT],
-                    },
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: None,
-                        unclipped_depth: false,
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        conservative: false,
-                    },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: wgpu::TextureFormat::Depth32Float,
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::Greater,
-                        stencil: wgpu::StencilState::default(),
-                        bias: wgpu::DepthBiasState::default(),
-                    }),
-                    multisample: wgpu::MultisampleState {
-                        count: prefs.gfx.sample_count(),
-                        ..Default::default()
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        module: gfx.shaders.basic.get(gfx),
-                        entry_point: "fs_main",
-                        targets: &[Some(wgpu::ColorTargetState {
-                            format: gfx.config.format,
-                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                            write_mask: wgpu::ColorWrites::ALL,
-                        })],
-                    }),
-                    multiview: None,
-                })
-        }));
+        render_pass.set_pipeline(basic_pipeline);
 
         // Populate vertex buffer.
         let vertex_buffer = cache.vertex_buffer.write_all(gfx, &mut verts);
@@ -336,12 +432,7 @@ pub(crate) fn draw_puzzle(
         let index_buffer = cache.index_buffer.write_all(gfx, &mut indices);
         render_pass.set_index_buffer(index_buffer, wgpu::IndexFormat::Uint32);
 
-        // Populate and bind uniform.
-        let uniform = BasicUniform {
-            scale: scale.into(),
-            align: [view_prefs.align_h, view_prefs.align_v],
-        };
-        cache.uniform_buffer.write(gfx, &uniform);
+        // Bind uniform.
         render_pass.set_bind_group(0, cache.uniform_buffer.bind_group(gfx), &[]);
 
         // Draw stickers.
@@ -350,11 +441,118 @@ pub(crate) fn draw_puzzle(
 
     drop(render_pass);
 
+    // Draw the overlay, if there's anything to draw.
+    if !overlay_indices.is_empty() {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("puzzle_overlay_render_pass"),
+            color_attachments: &[Some(overlay_pass_color_attachment)],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_texture_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+
+        render_pass.set_pipeline(basic_pipeline);
+
+        let vertex_buffer = cache
+            .overlay_vertex_buffer
+            .write_all(gfx, &mut overlay_verts);
+        render_pass.set_vertex_buffer(0, vertex_buffer);
+
+        let index_buffer = cache
+            .overlay_index_buffer
+            .write_all(gfx, &mut overlay_indices);
+        render_pass.set_index_buffer(index_buffer, wgpu::IndexFormat::Uint32);
+
+        render_pass.set_bind_group(0, cache.uniform_buffer.bind_group(gfx), &[]);
+
+        render_pass.draw_indexed(0..overlay_indices.len() as u32, 0, 0..1);
+    }
+
     gfx.queue.submit(std::iter::once(encoder.finish()));
 
     Some(out_texture.create_view(&wgpu::TextureViewDescriptor::default()))
 }
 
+/// Reads back the most recently drawn puzzle image.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn capture_puzzle(app: &App, gfx: &GraphicsState) -> Option<Screenshot> {
+    let (out_texture, _) = app.render_cache.out_texture.as_ref()?;
+    let (width, height) = app.puzzle_texture_size;
+
+    // Rows in the buffer must be padded to a multiple of
+    // `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    let unpadded_bytes_per_row = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+    let buffer = gfx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("puzzle_screenshot_buffer"),
+        size: padded_bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = gfx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("puzzle_screenshot_command_encoder"),
+        });
+    encoder.copy_texture_to_buffer(
+        out_texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        extent3d(width, height),
+    );
+    gfx.queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    gfx.device.poll(wgpu::Maintain::Wait);
+    if let Err(e) = rx.recv().ok()? {
+        log::error!("Error reading puzzle texture: {}", e);
+        return None;
+    }
+
+    let is_bgra = matches!(
+        gfx.config.format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb,
+    );
+    let mut rgba = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    for row in slice
+        .get_mapped_range()
+        .chunks(padded_bytes_per_row as usize)
+    {
+        for pixel in row[..unpadded_bytes_per_row as usize].chunks(4) {
+            if is_bgra {
+                rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], 255]);
+            } else {
+                rgba.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]);
+            }
+        }
+    }
+    buffer.unmap();
+
+    Some(Screenshot {
+        width,
+        height,
+        rgba,
+    })
+}
+
 fn extent3d(width: u32, height: u32) -> wgpu::Extent3d {
     wgpu::Extent3d {
         width,
@@ -362,3 +560,58 @@ fn extent3d(width: u32, height: u32) -> wgpu::Extent3d {
         depth_or_array_layers: 1,
     }
 }
+
+fn create_basic_pipeline(
+    gfx: &GraphicsState,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    module: &wgpu::ShaderModule,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    gfx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("basic_pipeline"),
+            layout: Some(
+                &gfx.device
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some("basic_pipeline_layout"),
+                        bind_group_layouts: &[bind_group_layout],
+                        push_constant_ranges: &[],
+                    }),
+            ),
+            vertex: wgpu::VertexState {
+                module,
+                entry_point: "vs_main",
+                buffers: &[RgbaVertex::LAYOUT],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Greater,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            fragment: Some(wgpu::FragmentState {
+                module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: gfx.config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        })
+}
@@ -1,16 +1,20 @@
 struct RgbaVertex {
     @location(0) pos: vec3<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) depth: f32,
 }
 
 struct VertexOutput {
     @builtin(position) pos: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) depth: f32,
 }
 
 struct BasicUniform {
     scale: vec2<f32>,
     align: vec2<f32>,
+    fog_color: vec4<f32>,
+    fog_density: f32,
 }
 
 @group(0) @binding(0)
@@ -24,10 +28,15 @@ fn vs_main(
     var out: VertexOutput;
     out.pos = vec4<f32>(in.pos.xy * u.scale + u.align, in.pos.z, 1.0);
     out.color = in.color;
+    out.depth = in.depth;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    // Exponential fog. Colors are premultiplied, so the fog color is scaled by
+    // the alpha of the fragment.
+    let fog = 1.0 - exp(-u.fog_density * in.depth);
+    let rgb = mix(in.color.rgb, u.fog_color.rgb * in.color.a, fog);
+    return vec4<f32>(rgb, in.color.a);
 }
@@ -0,0 +1,132 @@
+//! Shader loaded from a file in the shaders folder, which is reloaded whenever
+//! the file changes.
+
+use instant::{Duration, Instant};
+use std::time::SystemTime;
+
+/// Minimum time between checks for changes to the shader file.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(in crate::render) struct CustomShader {
+    /// Source code of the built-in shader, which is written to the shader file
+    /// as a starting point if it does not exist.
+    default_source: &'static str,
+    /// Shader module compiled from the file, if it compiled successfully.
+    module: Option<wgpu::ShaderModule>,
+    /// Modification time of the file when it was last loaded.
+    modified: Option<SystemTime>,
+    /// Time of the last check for changes to the file.
+    last_check: Option<Instant>,
+    /// Error from the last time the shader was loaded or used.
+    error: Option<String>,
+    /// Number of times the shader has changed, so that pipelines using it can
+    /// be recreated.
+    generation: u64,
+}
+impl CustomShader {
+    pub(super) fn new(default_source: &'static str) -> Self {
+        Self {
+            default_source,
+            module: None,
+            modified: None,
+            last_check: None,
+            error: None,
+            generation: 0,
+        }
+    }
+
+    /// Returns the shader module, if the shader is enabled and compiled
+    /// successfully.
+    pub(in crate::render) fn module(&self) -> Option<&wgpu::ShaderModule> {
+        self.module.as_ref()
+    }
+    /// Returns the error from the last time the shader was loaded or used.
+    pub(in crate::render) fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+    /// Returns a number that changes whenever the shader changes.
+    pub(in crate::render) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Records an error from using the shader, and stops using it until the
+    /// file changes.
+    pub(in crate::render) fn reject(&mut self, error: String) {
+        self.module = None;
+        self.error = Some(error);
+        self.generation += 1;
+    }
+
+    /// Loads the shader file if it changed since it was last loaded, or
+    /// unloads the shader if `enabled` is `false`. Returns whether the shader
+    /// changed.
+    pub(in crate::render) fn update(&mut self, device: &wgpu::Device, enabled: bool) -> bool {
+        if !enabled {
+            let was_enabled = self.modified.is_some() || self.error.is_some();
+            if was_enabled {
+                self.module = None;
+                self.modified = None;
+                self.last_check = None;
+                self.error = None;
+                self.generation += 1;
+            }
+            return was_enabled;
+        }
+
+        if self
+            .last_check
+            .map_or(false, |t| t.elapsed() < CHECK_INTERVAL)
+        {
+            return false;
+        }
+        self.last_check = Some(Instant::now());
+
+        let path = match crate::preferences::custom_shader_file_path() {
+            Ok(path) => path,
+            Err(e) => return self.set_error(format!("Unable to find shader file: {e}")),
+        };
+        // Start from the built-in shader.
+        if !path.exists() {
+            let result = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, self.default_source));
+            if let Err(e) = result {
+                return self.set_error(format!("Unable to create {}: {e}", path.display()));
+            }
+        }
+
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if modified.is_some() && modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) => return self.set_error(format!("Unable to read {}: {e}", path.display())),
+        };
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("custom_basic_shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(e) => self.reject(e.to_string()),
+            None => {
+                self.module = Some(module);
+                self.error = None;
+                self.generation += 1;
+            }
+        }
+        true
+    }
+
+    fn set_error(&mut self, error: String) -> bool {
+        if self.error.as_ref() == Some(&error) {
+            return false;
+        }
+        self.reject(error);
+        true
+    }
+}
@@ -2,13 +2,24 @@ use once_cell::unsync::OnceCell;
 
 use super::GraphicsState;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod custom;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) use custom::CustomShader;
+
 pub(super) struct Shaders {
     pub(super) basic: CachedShaderModule,
+    /// Replacement for `basic` loaded from the shaders folder.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(super) custom_basic: CustomShader,
 }
 impl Shaders {
     pub(super) fn new() -> Self {
         Self {
             basic: CachedShaderModule::new(|| wgpu::include_wgsl!("basic.wgsl")),
+            #[cfg(not(target_arch = "wasm32"))]
+            custom_basic: CustomShader::new(include_str!("basic.wgsl")),
         }
     }
 }
@@ -7,6 +7,9 @@ pub(crate) struct GraphicsState {
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
     pub(crate) config: wgpu::SurfaceConfiguration,
+    /// Information about the graphics adapter, such as whether it is a
+    /// discrete GPU.
+    pub(crate) adapter_info: wgpu::AdapterInfo,
 
     pub(super) shaders: Shaders,
 
@@ -77,6 +80,7 @@ impl GraphicsState {
             device,
             queue,
             config,
+            adapter_info: adapter.get_info(),
 
             shaders,
 
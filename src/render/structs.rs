@@ -5,6 +5,8 @@
 pub(super) struct RgbaVertex {
     pub pos: [f32; 3],
     pub color: [f32; 4],
+    /// Depth used for fog, from 0.0 (front of the puzzle) to 1.0 (back).
+    pub depth: f32,
 }
 impl RgbaVertex {
     pub const LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
@@ -13,6 +15,7 @@ impl RgbaVertex {
         attributes: &wgpu::vertex_attr_array![
             0 => Float32x3,
             1 => Float32x4,
+            2 => Float32,
         ],
     };
 }
@@ -22,4 +25,8 @@ impl RgbaVertex {
 pub(super) struct BasicUniform {
     pub scale: [f32; 2],
     pub align: [f32; 2],
+    pub fog_color: [f32; 4],
+    pub fog_density: f32,
+    /// Padding to match the alignment of the uniform on the GPU.
+    pub _padding: [f32; 3],
 }
@@ -0,0 +1,218 @@
+//! Replay tests that feed recorded key events through the app's input handling
+//! without a window, and check the resulting puzzle state and undo history.
+//!
+//! Each script in `tests/replays` is a YAML file like this:
+//!
+//! ```yaml
+//! puzzle: { Rubiks3D: { layer_count: 3 } }
+//! scramble: R
+//! events:
+//!   - { time: 0, press: { sc: KeyK } }
+//!   - { time: 50, release: { sc: KeyK } }
+//! expect:
+//!   twists: R'
+//!   solved: true
+//! ```
+//!
+//! Times are in milliseconds since the start of the replay. The app sees the
+//! time of each event instead of the real time, so key taps and key repeats
+//! behave the same way on every run.
+
+use instant::{Duration, Instant};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::Path;
+use winit::event::{ElementState, ModifiersState, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+use crate::app::{App, AppEvent};
+use crate::preferences::{Key, Preferences, DEFAULT_PREFS};
+use crate::puzzle::*;
+
+/// Directory containing replay scripts, relative to the crate root.
+const REPLAYS_DIR: &str = "tests/replays";
+/// Maximum time to wait for the worker thread after the last event.
+const WORKER_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ReplayScript {
+    /// Puzzle type to replay on.
+    #[serde(default)]
+    puzzle: PuzzleTypeEnum,
+    /// Preferences to change from the defaults, in the same format as the
+    /// preferences file.
+    #[serde(default)]
+    prefs: serde_yaml::Value,
+    /// Twists to scramble the puzzle with before the first event.
+    #[serde(default)]
+    scramble: String,
+    /// Key events, in order of time.
+    events: Vec<ReplayEvent>,
+    /// Expected result of the replay.
+    #[serde(default)]
+    expect: ReplayExpectation,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReplayEvent {
+    /// Time of the event, in milliseconds since the start of the replay.
+    time: u64,
+    #[serde(flatten)]
+    action: KeyAction,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+enum KeyAction {
+    Press(Key),
+    Release(Key),
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ReplayExpectation {
+    /// Twists in the undo history, in the notation of the puzzle.
+    twists: Option<String>,
+    /// Whether the puzzle is solved.
+    solved: Option<bool>,
+}
+
+#[test]
+fn test_replays() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(REPLAYS_DIR);
+    let mut count = 0;
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(true, |ext| ext != "yaml") {
+            continue;
+        }
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let script: ReplayScript = serde_yaml::from_str(&contents)
+            .unwrap_or_else(|e| panic!("error parsing replay {name}: {e}"));
+        run_replay(&name, script);
+        count += 1;
+    }
+    assert!(count > 0, "no replays in {}", dir.display());
+}
+
+fn run_replay(name: &str, script: ReplayScript) {
+    let ty = script.puzzle;
+    let (mut app, queue) = App::new_headless(replay_prefs(name, script.prefs));
+    app.puzzle.replace(PuzzleController::new(ty));
+
+    let scramble = ty
+        .parse_twists(&script.scramble)
+        .unwrap_or_else(|e| panic!("{name}: error parsing scramble: {e}"));
+    if !scramble.is_empty() {
+        app.puzzle
+            .scramble_with(&scramble, ScrambleState::Partial)
+            .unwrap();
+    }
+
+    let start = Instant::now();
+    let mut held_keys: Vec<Key> = vec![];
+    for event in script.events {
+        // Run a frame first so that key repeats happen before the event.
+        app.replay_time = Some(start + Duration::from_millis(event.time));
+        app.frame();
+
+        let (key, state) = match event.action {
+            KeyAction::Press(key) => (key, ElementState::Pressed),
+            KeyAction::Release(key) => (key, ElementState::Released),
+        };
+        match state {
+            ElementState::Pressed => held_keys.push(key),
+            ElementState::Released => held_keys.retain(|&k| k != key),
+        }
+        if key.is_modifier() {
+            let mods = held_keys
+                .iter()
+                .fold(ModifiersState::empty(), |mods, k| mods | k.modifier_bit());
+            app.handle_window_event(&WindowEvent::ModifiersChanged(mods));
+        }
+        let (sc, vk) = match key {
+            Key::Sc(sc) => (Some(sc), None),
+            Key::Vk(vk) => (None, Some(vk)),
+        };
+        app.handle_key_input(sc, vk, state);
+        handle_queued_events(&mut app, &queue);
+    }
+
+    // Wait for puzzle loading and scrambling to finish.
+    let deadline = Instant::now() + WORKER_TIMEOUT;
+    loop {
+        app.frame();
+        handle_queued_events(&mut app, &queue);
+        if app.worker.progress().is_none() {
+            break;
+        }
+        assert!(Instant::now() < deadline, "{name}: timed out");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let expect = script.expect;
+    if let Some(twists) = &expect.twists {
+        let expected = ty
+            .parse_twists(twists)
+            .unwrap_or_else(|e| panic!("{name}: error parsing expected twists: {e}"));
+        let actual = app
+            .puzzle
+            .undo_buffer()
+            .iter()
+            .filter_map(|entry| entry.twist())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ty.twists_to_string(&actual),
+            ty.twists_to_string(&expected),
+            "{name}: wrong twists",
+        );
+
+        let mut expected_state = Puzzle::new(ty);
+        for &twist in scramble.iter().chain(&expected) {
+            expected_state.twist(twist).unwrap();
+        }
+        assert_eq!(app.puzzle.latest(), &expected_state, "{name}: wrong state");
+    }
+    if let Some(solved) = expect.solved {
+        assert_eq!(app.puzzle.is_solved(), solved, "{name}: wrong solved state");
+    }
+}
+
+/// Returns the default preferences with the changes from a replay script.
+fn replay_prefs(name: &str, changes: serde_yaml::Value) -> Preferences {
+    let mut value = serde_yaml::to_value(&*DEFAULT_PREFS).unwrap();
+    merge_yaml(&mut value, changes);
+    serde_yaml::from_value(value)
+        .unwrap_or_else(|e| panic!("{name}: error parsing preferences: {e}"))
+}
+fn merge_yaml(base: &mut serde_yaml::Value, changes: serde_yaml::Value) {
+    match (base, changes) {
+        (_, serde_yaml::Value::Null) => (),
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(changes)) => {
+            for (k, v) in changes {
+                match base.get_mut(&k) {
+                    Some(base_value) => merge_yaml(base_value, v),
+                    None => {
+                        base.insert(k, v);
+                    }
+                }
+            }
+        }
+        (base, changes) => *base = changes,
+    }
+}
+
+fn handle_queued_events(app: &mut App, queue: &RefCell<VecDeque<AppEvent>>) {
+    let mut control_flow = ControlFlow::Poll;
+    loop {
+        // Handling an event may queue more events, so the queue must not be
+        // borrowed while handling one.
+        let Some(event) = queue.borrow_mut().pop_front() else {
+            break;
+        };
+        let _ = app.handle_app_event(event, &mut control_flow);
+    }
+}
@@ -3,9 +3,10 @@
 use key_names::KeyMappingCode;
 use serde::{Deserialize, Serialize};
 
-pub(crate) mod hex_bitvec;
 pub(crate) mod hex_color;
 
+pub(crate) use hyperspeedcube_core::serde_impl::hex_bitvec;
+
 /// Taken from docs here:
 /// https://docs.rs/keycode/0.3.0/keycode/enum.KeyMappingCode.html
 ///
@@ -0,0 +1,138 @@
+//! Headless HTTP server that verifies solves, for leaderboards that need to
+//! check submitted log files automatically.
+//!
+//! Start it with `hyperspeedcube --serve [ADDRESS]`, then send the contents of
+//! a log file (`.hsc` or MC4D `.log`) in the body of a `POST /verify` request.
+//! To check that the scramble is the one generated from a seed, add the seed
+//! to the URL as `/verify?seed=<SEED>`; without a seed, any scramble of the
+//! right length is accepted and reported as unverified. The response is a
+//! JSON object with these fields:
+//!
+//! - `valid`: whether the log file contains a complete solve from a
+//!   full-length scramble that matches the seed, if one is given
+//! - `errors`: reasons that the solve is not valid
+//! - `puzzle`: name of the puzzle type, such as `"3x3x3x3"`
+//! - `scramble_length`: number of twists in the scramble
+//! - `scramble_verified`: whether the scramble was checked against the seed
+//! - `twist_counts`: number of twists in the solve in each twist metric
+//! - `duration_ms`: duration of the solve, if the log file records twist times
+
+use anyhow::{anyhow, Result};
+use std::io::Read;
+use std::sync::Arc;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::logfile;
+
+/// Address to listen on if none is given.
+pub(crate) const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+/// Maximum size of a log file, in bytes.
+const MAX_BODY_LEN: u64 = 16 * 1024 * 1024;
+
+/// Listens for verification requests until the process is killed.
+pub(crate) fn run(address: &str) -> Result<()> {
+    let server = Arc::new(Server::http(address).map_err(|e| anyhow!(e))?);
+    println!("Listening for solves to verify on http://{address}/verify");
+
+    // Verifying a long solve can take a while, so handle requests on several
+    // threads.
+    let thread_count = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let threads = (0..thread_count)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle_request(request);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        if thread.join().is_err() {
+            log::error!("Server thread panicked");
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(mut request: Request) {
+    let mut body = String::new();
+    let body_result = request
+        .as_reader()
+        .take(MAX_BODY_LEN + 1)
+        .read_to_string(&mut body)
+        .map(|_| body);
+
+    let (status, json) = route(request.method(), request.url(), body_result);
+    log::info!("{} {} -> {status}", request.method(), request.url());
+
+    let content_type =
+        Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("invalid header");
+    let response = Response::from_string(json)
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        log::warn!("Error sending response: {e}");
+    }
+}
+
+/// Returns the status code and JSON body of the response to a request.
+fn route(method: &Method, url: &str, body: std::io::Result<String>) -> (u16, String) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    match (method, path) {
+        (Method::Post, "/verify") => match body {
+            Ok(body) if body.len() as u64 > MAX_BODY_LEN => {
+                (413, error_json("Log file is too large"))
+            }
+            Ok(body) => match scramble_seed(query) {
+                Ok(seed) => match serde_json::to_string(&logfile::verify(&body, seed)) {
+                    Ok(json) => (200, json),
+                    Err(e) => (500, error_json(&e.to_string())),
+                },
+                Err(e) => (400, error_json(&e)),
+            },
+            Err(e) => (400, error_json(&format!("Error reading log file: {e}"))),
+        },
+        (_, "/verify") => (405, error_json("Use POST to verify a log file")),
+        _ => (404, error_json("Not found")),
+    }
+}
+
+/// Returns the scramble seed from the query string of a URL, if there is one.
+fn scramble_seed(query: &str) -> Result<Option<u64>, String> {
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix("seed="))
+        .map(|seed| seed.parse().map_err(|_| format!("Invalid seed: {seed:?}")))
+        .transpose()
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        let (status, json) = route(&Method::Post, "/verify", Ok("not a log file".to_string()));
+        assert_eq!(status, 200);
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(response["valid"], false);
+
+        let (status, json) = route(&Method::Post, "/verify?seed=42", Ok(String::new()));
+        assert_eq!(status, 200);
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(response["scramble_verified"], false);
+        assert_eq!(
+            route(&Method::Post, "/verify?seed=x", Ok(String::new())).0,
+            400
+        );
+
+        assert_eq!(route(&Method::Get, "/verify", Ok(String::new())).0, 405);
+        assert_eq!(route(&Method::Post, "/", Ok(String::new())).0, 404);
+    }
+}
@@ -0,0 +1,94 @@
+//! Session saved on exit and restored on the next launch.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::logfile::LogFileFormat;
+use crate::puzzle::PuzzleController;
+
+/// Snapshot of everything needed to pick up where the user left off, other
+/// than preferences (which include the view angles and are saved separately).
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub(crate) struct Session {
+    /// Puzzle state and undo history, in the native log format.
+    pub log: Option<String>,
+    /// Log file that the puzzle was last loaded from or saved to.
+    pub log_file: Option<PathBuf>,
+    /// Whether the puzzle had unsaved changes.
+    pub unsaved: bool,
+    /// States of other puzzle types that were in progress, in the native log
+    /// format.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub other_logs: Vec<String>,
+    /// Names of the windows that were open.
+    pub open_windows: Vec<String>,
+}
+impl Session {
+    /// Captures a session from the current puzzle.
+    pub fn new<'a>(
+        puzzle: &PuzzleController,
+        other_puzzles: impl IntoIterator<Item = &'a PuzzleController>,
+        log_file: Option<PathBuf>,
+        open_windows: Vec<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            log: Some(crate::logfile::serialize(puzzle, LogFileFormat::Hsc)?),
+            log_file,
+            unsaved: puzzle.is_unsaved(),
+            other_logs: other_puzzles
+                .into_iter()
+                .map(|p| crate::logfile::serialize(p, LogFileFormat::Hsc))
+                .collect::<Result<_, _>>()?,
+            open_windows,
+        })
+    }
+
+    /// Loads the session saved on the last exit.
+    pub fn load() -> Result<Self> {
+        let path = crate::preferences::session_file_path()?;
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading session file {}", path.display()))?;
+        Ok(serde_yaml::from_str(&contents)?)
+    }
+    /// Saves the session to be restored on the next launch.
+    pub fn save(&self) -> Result<()> {
+        let path = crate::preferences::session_file_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+    /// Deletes the saved session, if there is one.
+    pub fn delete() -> Result<()> {
+        let path = crate::preferences::session_file_path()?;
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Deserializes the saved puzzle, if there is one.
+    pub fn puzzle(&self) -> Result<Option<(PuzzleController, Vec<String>)>> {
+        self.log
+            .as_deref()
+            .map(crate::logfile::deserialize)
+            .transpose()
+    }
+    /// Deserializes the saved states of other puzzle types, skipping any that
+    /// cannot be loaded.
+    pub fn other_puzzles(&self) -> Vec<PuzzleController> {
+        self.other_logs
+            .iter()
+            .filter_map(|log| match crate::logfile::deserialize(log) {
+                Ok((puzzle, _warnings)) => Some(puzzle),
+                Err(e) => {
+                    log::error!("Error restoring puzzle state: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
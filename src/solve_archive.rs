@@ -0,0 +1,136 @@
+//! Automatic saving of finished solves.
+//!
+//! When a scrambled puzzle is solved, its log file is saved to the solves
+//! folder with a timestamped name, and the solve is added to an index file in
+//! the same folder so that it can be found again without opening every log
+//! file.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+
+use crate::logfile::{self, LogFileFormat};
+use crate::puzzle::*;
+
+/// Name of the index file in the solves folder.
+const INDEX_FILE_NAME: &str = "solves.yaml";
+
+/// Entry in the index of saved solves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SolveRecord {
+    /// Name of the log file, relative to the solves folder.
+    pub file: String,
+    /// Name of the puzzle type, such as "3x3x3x3".
+    pub puzzle: String,
+    /// Local date and time when the solve was finished, such as
+    /// "2023-01-31 12:34:56".
+    pub date: String,
+    /// Duration of the solve in milliseconds, if the twists were timed.
+    #[serde(default)]
+    pub time_ms: Option<u64>,
+    /// Number of twists in the solve, in each twist metric.
+    #[serde(default)]
+    pub twist_counts: BTreeMap<TwistMetric, usize>,
+}
+
+/// Saves the log file of a just-solved puzzle to `dir` and adds it to the
+/// index of saved solves. Returns the path of the log file.
+pub(crate) fn save_solve(puzzle: &PuzzleController, dir: &Path) -> Result<PathBuf> {
+    let log = logfile::serialize(puzzle, LogFileFormat::Hsc)?;
+
+    let now = time::OffsetDateTime::now_local().unwrap_or_else(|_| time::OffsetDateTime::now_utc());
+    let date = format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        now.year(),
+        now.month() as u8,
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second(),
+    );
+    let file_stem = format!(
+        "{}_{}",
+        puzzle.ty().name(),
+        date.replace(' ', "_").replace(':', "-"),
+    );
+
+    std::fs::create_dir_all(dir)?;
+    // Don't overwrite another solve finished in the same second.
+    let mut file = format!("{file_stem}.hsc");
+    let mut n = 1;
+    while dir.join(&file).exists() {
+        n += 1;
+        file = format!("{file_stem}_{n}.hsc");
+    }
+    let path = dir.join(&file);
+    std::fs::write(&path, log)?;
+
+    let record = SolveRecord {
+        file,
+        puzzle: puzzle.ty().name().to_string(),
+        date,
+        time_ms: puzzle
+            .twist_times()
+            .iter()
+            .rev()
+            .find_map(|&t| t)
+            .map(|t| t.as_millis() as u64),
+        twist_counts: TwistMetric::iter()
+            .map(|metric| (metric, puzzle.twist_count(metric)))
+            .collect(),
+    };
+    add_to_index(dir, record)?;
+
+    Ok(path)
+}
+
+/// Loads the index of saved solves in `dir`, oldest first.
+pub(crate) fn load_index(dir: &Path) -> Result<Vec<SolveRecord>> {
+    let path = dir.join(INDEX_FILE_NAME);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    Ok(serde_yaml::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn add_to_index(dir: &Path, record: SolveRecord) -> Result<()> {
+    let mut records = load_index(dir)?;
+    records.push(record);
+    std::fs::write(dir.join(INDEX_FILE_NAME), serde_yaml::to_string(&records)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_solve() {
+        let dir = std::env::temp_dir().join(format!("hsc_solves_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let ty = puzzle.ty();
+        let scramble = ty.parse_twists("R U").unwrap();
+        puzzle
+            .scramble_with(&scramble, ScrambleState::Full)
+            .unwrap();
+        for twist in ty.parse_twists("U' R'").unwrap() {
+            puzzle.twist(twist).unwrap();
+        }
+
+        let first = save_solve(&puzzle, &dir).unwrap();
+        let second = save_solve(&puzzle, &dir).unwrap();
+        assert_ne!(first, second);
+        assert!(first.exists() && second.exists());
+
+        let index = load_index(&dir).unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[0].puzzle, puzzle.ty().name());
+        assert_eq!(index[0].twist_counts[&TwistMetric::Etm], 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
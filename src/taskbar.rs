@@ -0,0 +1,107 @@
+//! Solve progress on the taskbar icon (Windows) or dock icon (macOS).
+
+use winit::window::Window;
+
+/// Shows solve progress on the application icon, updating it only when the
+/// displayed percentage changes.
+#[derive(Default)]
+pub(crate) struct TaskbarProgress {
+    /// Percentage currently shown, or `None` if no progress is shown.
+    shown: Option<u32>,
+    #[cfg(windows)]
+    taskbar: Option<windows::Win32::UI::Shell::ITaskbarList3>,
+}
+impl TaskbarProgress {
+    /// Shows `progress` (from 0.0 to 1.0) on the application icon, or hides it
+    /// if `progress` is `None`.
+    pub(crate) fn update(&mut self, window: &Window, progress: Option<f32>) {
+        let percent = progress.map(|p| (p.clamp(0.0, 1.0) * 100.0).round() as u32);
+        if percent == self.shown {
+            return;
+        }
+        self.shown = percent;
+        self.show(window, percent);
+    }
+
+    #[cfg(windows)]
+    fn show(&mut self, window: &Window, percent: Option<u32>) {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::System::Com::{
+            CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+        };
+        use windows::Win32::UI::Shell::{ITaskbarList3, TaskbarList, TBPF_NOPROGRESS, TBPF_NORMAL};
+        use winit::platform::windows::WindowExtWindows;
+
+        if self.taskbar.is_none() {
+            // SAFETY: COM may already be initialized on this thread, in which
+            // case this does nothing.
+            let taskbar = unsafe {
+                let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+                let taskbar: windows::core::Result<ITaskbarList3> =
+                    CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER);
+                taskbar.and_then(|taskbar| taskbar.HrInit().map(|()| taskbar))
+            };
+            match taskbar {
+                Ok(taskbar) => self.taskbar = Some(taskbar),
+                Err(e) => {
+                    log::warn!("Error initializing taskbar progress: {}", e);
+                    return;
+                }
+            }
+        }
+        let Some(taskbar) = &self.taskbar else {
+            return;
+        };
+
+        let hwnd = HWND(window.hwnd() as _);
+        // SAFETY: `hwnd` is the handle of a live window.
+        let result = unsafe {
+            match percent {
+                Some(percent) => taskbar
+                    .SetProgressState(hwnd, TBPF_NORMAL)
+                    .and_then(|()| taskbar.SetProgressValue(hwnd, percent as u64, 100)),
+                None => taskbar.SetProgressState(hwnd, TBPF_NOPROGRESS),
+            }
+        };
+        if let Err(e) = result {
+            log::warn!("Error setting taskbar progress: {}", e);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn show(&mut self, _window: &Window, percent: Option<u32>) {
+        use objc::runtime::Object;
+        use objc::{class, msg_send, sel, sel_impl};
+
+        /// `NSUTF8StringEncoding`
+        const UTF8_ENCODING: usize = 4;
+
+        // SAFETY: These are Objective-C messages to the shared application and
+        // its dock tile, which exist for the lifetime of the program.
+        unsafe {
+            let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let dock_tile: *mut Object = msg_send![app, dockTile];
+            match percent {
+                Some(percent) => {
+                    let label = format!("{percent}%");
+                    let ns_string: *mut Object = msg_send![class!(NSString), alloc];
+                    let ns_string: *mut Object = msg_send![
+                        ns_string,
+                        initWithBytes: label.as_ptr()
+                        length: label.len()
+                        encoding: UTF8_ENCODING
+                    ];
+                    let _: () = msg_send![dock_tile, setBadgeLabel: ns_string];
+                    let _: () = msg_send![ns_string, release];
+                }
+                None => {
+                    let nil = std::ptr::null_mut::<Object>();
+                    let _: () = msg_send![dock_tile, setBadgeLabel: nil];
+                }
+            }
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    fn show(&mut self, _window: &Window, _percent: Option<u32>) {}
+}
@@ -0,0 +1,374 @@
+//! Checks for new versions on GitHub, and on supported platforms downloads
+//! and stages them.
+//!
+//! A staged update replaces the executable, which takes effect the next time
+//! the app is launched. The old executable is renamed and removed on the next
+//! launch.
+//!
+//! Only release assets with an expected name and a published SHA-256 checksum
+//! are downloaded, and the download is checked against the checksum before
+//! anything is replaced.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Read};
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+/// URL of the GitHub API endpoint for the latest release.
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/HactarCE/Hyperspeedcube/releases/latest";
+/// Maximum size of a downloaded update, in bytes.
+const MAX_DOWNLOAD_LEN: u64 = 256 * 1024 * 1024;
+/// Maximum size of a downloaded checksum file, in bytes.
+const MAX_CHECKSUM_LEN: u64 = 1024;
+
+/// Suffixes of the release assets that can be staged on this platform. Asset
+/// names are `hyperspeedcube_<tag>_<suffix>`, and other assets are ignored.
+const PLATFORM_ASSET_SUFFIXES: &[&str] = if cfg!(windows) {
+    &["win64.zip", "win64.exe"]
+} else if cfg!(target_os = "linux") {
+    &["linux.zip", "linux"]
+} else {
+    // Other platforms use app bundles, which cannot be replaced by copying a
+    // single executable.
+    &[]
+};
+
+/// Release on GitHub, as returned by the releases API.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Release {
+    /// Git tag of the release, such as `v1.0.5`.
+    pub tag_name: String,
+    /// Changelog of the release, in Markdown.
+    #[serde(default)]
+    pub body: String,
+    /// URL of the release page.
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+impl Release {
+    /// Returns the version number of the release, without the leading `v`.
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+    /// Returns whether the release is newer than this version of the app.
+    pub fn is_newer(&self) -> bool {
+        is_newer_version(self.version(), env!("CARGO_PKG_VERSION"))
+    }
+    /// Returns the download for this platform, if the update can be staged
+    /// automatically on this platform. The download must have a published
+    /// checksum.
+    pub fn platform_asset(&self) -> Option<&ReleaseAsset> {
+        let allowed_names: Vec<String> = PLATFORM_ASSET_SUFFIXES
+            .iter()
+            .map(|suffix| format!("hyperspeedcube_{}_{suffix}", self.tag_name).to_lowercase())
+            .collect();
+        self.assets.iter().find(|asset| {
+            allowed_names.contains(&asset.name.to_lowercase())
+                && self.checksum_asset(asset).is_some()
+        })
+    }
+    /// Returns the file containing the SHA-256 checksum of `asset`, which is
+    /// named like the asset with `.sha256` appended.
+    pub fn checksum_asset(&self, asset: &ReleaseAsset) -> Option<&ReleaseAsset> {
+        let checksum_name = format!("{}.sha256", asset.name);
+        self.assets.iter().find(|a| a.name == checksum_name)
+    }
+}
+
+/// File attached to a release.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// Response from a request on another thread.
+#[derive(Debug)]
+enum Response {
+    Checked(Result<Release, String>),
+    Staged(Result<(), String>),
+}
+
+/// State of the update check and download.
+pub(crate) struct Updates {
+    /// Latest release, if it has been checked.
+    pub latest: Option<Release>,
+    /// Last error from checking or downloading.
+    pub error: Option<String>,
+    /// Whether a request is in progress.
+    pub busy: bool,
+    /// Whether an update has been staged and will be used after a restart.
+    pub staged: bool,
+    /// Whether to tell the user about a new version once it has been checked.
+    notify: bool,
+    /// Whether the user should be told about a new version.
+    pub notification: bool,
+
+    responses_tx: mpsc::Sender<Response>,
+    responses: mpsc::Receiver<Response>,
+}
+impl Default for Updates {
+    fn default() -> Self {
+        let (responses_tx, responses) = mpsc::channel();
+        Self {
+            latest: None,
+            error: None,
+            busy: false,
+            staged: false,
+            notify: false,
+            notification: false,
+
+            responses_tx,
+            responses,
+        }
+    }
+}
+impl Updates {
+    /// Queries GitHub for the latest release on another thread. If `notify`
+    /// is `true`, the user is told about it if it is newer.
+    pub fn check(&mut self, notify: bool) {
+        if self.busy {
+            return;
+        }
+        self.busy = true;
+        self.notify = notify;
+
+        let responses_tx = self.responses_tx.clone();
+        std::thread::spawn(move || {
+            let result = ureq::get(LATEST_RELEASE_URL)
+                .set("Accept", "application/vnd.github+json")
+                .call()
+                .map_err(|e| e.to_string())
+                .and_then(|response| response.into_json().map_err(|e| e.to_string()));
+            let _ = responses_tx.send(Response::Checked(result));
+        });
+    }
+
+    /// Downloads the latest release for this platform on another thread and
+    /// stages it to be used after a restart.
+    pub fn download_and_stage(&mut self) {
+        let Some(release) = &self.latest else {
+            return;
+        };
+        let Some(asset) = release.platform_asset() else {
+            self.error = Some("No automatic update for this platform".to_string());
+            return;
+        };
+        let Some(checksum_asset) = release.checksum_asset(asset) else {
+            self.error = Some("No checksum for this update".to_string());
+            return;
+        };
+        if self.busy {
+            return;
+        }
+        self.busy = true;
+
+        let url = asset.browser_download_url.clone();
+        let checksum_url = checksum_asset.browser_download_url.clone();
+        let responses_tx = self.responses_tx.clone();
+        std::thread::spawn(move || {
+            let result = download_update(&url, &checksum_url)
+                .and_then(|bytes| stage_executable(&bytes))
+                .map_err(|e| format!("{e:#}"));
+            let _ = responses_tx.send(Response::Staged(result));
+        });
+    }
+
+    /// Handles responses from other threads.
+    pub fn poll(&mut self, skipped_version: &str) {
+        while let Ok(response) = self.responses.try_recv() {
+            self.busy = false;
+            match response {
+                Response::Checked(Ok(release)) => {
+                    self.notification =
+                        self.notify && release.is_newer() && release.version() != skipped_version;
+                    self.latest = Some(release);
+                    self.error = None;
+                }
+                Response::Staged(Ok(())) => {
+                    self.staged = true;
+                    self.error = None;
+                }
+                Response::Checked(Err(e)) | Response::Staged(Err(e)) => self.error = Some(e),
+            }
+        }
+    }
+}
+
+/// Returns whether version `a` is newer than version `b`. Versions are
+/// compared by their numeric components, so `1.10.0` is newer than `1.9.0`.
+fn is_newer_version(a: &str, b: &str) -> bool {
+    let components = |v: &str| -> Vec<u32> {
+        v.split(|c: char| !c.is_ascii_digit())
+            .map_while(|s| s.parse().ok())
+            .collect()
+    };
+    components(a) > components(b)
+}
+
+/// Downloads an update and checks it against its published checksum, then
+/// returns the contents of the executable.
+fn download_update(url: &str, checksum_url: &str) -> Result<Vec<u8>> {
+    let expected_checksum = parse_checksum(&download(checksum_url, MAX_CHECKSUM_LEN)?)?;
+    let bytes = download(url, MAX_DOWNLOAD_LEN)?;
+    if Sha256::digest(&bytes)[..] != expected_checksum {
+        bail!("downloaded update does not match its checksum");
+    }
+    if url.ends_with(".zip") {
+        extract_executable(&bytes)
+    } else {
+        Ok(bytes)
+    }
+}
+
+/// Downloads a file, returning an error if it is larger than `max_len` bytes.
+fn download(url: &str, max_len: u64) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    ureq::get(url)
+        .call()?
+        .into_reader()
+        .take(max_len + 1)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > max_len {
+        bail!("download is larger than {max_len} bytes");
+    }
+    Ok(bytes)
+}
+
+/// Parses a checksum file in the format written by `sha256sum`: a hex digest,
+/// optionally followed by the file name.
+fn parse_checksum(checksum_file: &[u8]) -> Result<[u8; 32]> {
+    let hex_digest = std::str::from_utf8(checksum_file)
+        .context("invalid checksum file")?
+        .split_whitespace()
+        .next()
+        .unwrap_or_default();
+    let mut ret = [0; 32];
+    hex::decode_to_slice(hex_digest, &mut ret).context("invalid checksum")?;
+    Ok(ret)
+}
+
+/// Returns the contents of the executable in a zip archive.
+fn extract_executable(zip_bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(zip_bytes))?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file
+            .name()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let is_executable = name.starts_with("hyperspeedcube")
+            && if cfg!(windows) {
+                name.ends_with(".exe")
+            } else {
+                !name.contains('.')
+            };
+        if file.is_file() && is_executable {
+            let mut bytes = vec![];
+            file.read_to_end(&mut bytes)?;
+            return Ok(bytes);
+        }
+    }
+    Err(anyhow!("no executable in the downloaded archive"))
+}
+
+/// Returns the path that the old executable is moved to when an update is
+/// staged.
+fn old_executable_path() -> Result<PathBuf> {
+    Ok(std::env::current_exe()?.with_extension("old"))
+}
+
+/// Replaces the executable with a new one, keeping the old one until the next
+/// launch because it may still be running.
+fn stage_executable(new_exe: &[u8]) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let new_path = exe.with_extension("new");
+    std::fs::write(&new_path, new_exe).context("writing new executable")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&new_path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    let old_path = old_executable_path()?;
+    std::fs::rename(&exe, &old_path).context("moving old executable")?;
+    if let Err(e) = std::fs::rename(&new_path, &exe) {
+        // Put the old executable back.
+        let _ = std::fs::rename(&old_path, &exe);
+        return Err(e).context("moving new executable");
+    }
+    Ok(())
+}
+
+/// Removes the executable left over from a staged update, if there is one.
+pub(crate) fn remove_old_executable() {
+    if let Ok(path) = old_executable_path() {
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!("Error removing old executable {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer_version() {
+        assert!(is_newer_version("1.0.6", "1.0.5"));
+        assert!(is_newer_version("1.10.0", "1.9.0"));
+        assert!(is_newer_version("2.0", "1.9.9"));
+        assert!(!is_newer_version("1.0.5", "1.0.5"));
+        assert!(!is_newer_version("1.0.4", "1.0.5"));
+        assert!(!is_newer_version("nightly", "1.0.5"));
+    }
+
+    #[test]
+    fn test_parse_checksum() {
+        let digest = Sha256::digest(b"hyperspeedcube");
+        let line = format!("{}  hyperspeedcube_v1.0.6_linux.zip\n", hex::encode(digest));
+        assert_eq!(parse_checksum(line.as_bytes()).unwrap()[..], digest[..]);
+        assert!(parse_checksum(b"").is_err());
+        assert!(parse_checksum(b"not a checksum").is_err());
+    }
+
+    #[test]
+    #[cfg(any(windows, target_os = "linux"))]
+    fn test_platform_asset() {
+        let asset = |name: &str| ReleaseAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{name}"),
+        };
+        let expected_name = format!("hyperspeedcube_v1.0.6_{}", PLATFORM_ASSET_SUFFIXES[0]);
+        let mut release = Release {
+            tag_name: "v1.0.6".to_string(),
+            body: String::new(),
+            html_url: String::new(),
+            assets: vec![
+                asset(&format!("evil_{expected_name}")),
+                asset(&format!("evil_{expected_name}.sha256")),
+                asset(&expected_name),
+            ],
+        };
+        // Assets without a checksum are never downloaded.
+        assert_eq!(release.platform_asset(), None);
+
+        release
+            .assets
+            .push(asset(&format!("{expected_name}.sha256")));
+        let platform_asset = release.platform_asset().unwrap();
+        assert_eq!(platform_asset.name, expected_name);
+        assert_eq!(
+            release.checksum_asset(platform_asset).unwrap().name,
+            format!("{expected_name}.sha256"),
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Local versus mode, in which two players race on separate puzzles with the
+//! same scramble.
+
+use instant::{Duration, Instant};
+
+use crate::preferences::ScrambleOptions;
+use crate::puzzle::*;
+use crate::render::PuzzleRenderCache;
+
+/// State of the second player's puzzle and the race between the players. The
+/// first player uses the main puzzle.
+pub(crate) struct Versus {
+    /// Puzzle of the second player.
+    pub puzzle: PuzzleController,
+    pub render_cache: PuzzleRenderCache,
+    pub texture_size: (u32, u32),
+
+    /// Time at which the current race started.
+    pub start: Option<Instant>,
+    /// Time at which each player solved their puzzle in the current race.
+    pub finish_times: [Option<Duration>; 2],
+}
+impl Versus {
+    pub fn new(ty: PuzzleTypeEnum) -> Self {
+        Self {
+            puzzle: PuzzleController::new(ty),
+            render_cache: PuzzleRenderCache::default(),
+            texture_size: (0, 0),
+
+            start: None,
+            finish_times: [None; 2],
+        }
+    }
+
+    /// Scrambles both puzzles with the same scramble and starts the race.
+    pub fn start_race(
+        &mut self,
+        player_1: &mut PuzzleController,
+        options: &ScrambleOptions,
+    ) -> Result<(), &'static str> {
+        let scramble = player_1.generate_scramble(player_1.scramble_moves_count(), options)?;
+        let scramble_state = if options.is_restricted() {
+            ScrambleState::Partial
+        } else {
+            ScrambleState::Full
+        };
+        player_1.scramble_with(&scramble, scramble_state)?;
+        self.puzzle.scramble_with(&scramble, scramble_state)?;
+        self.start = Some(Instant::now());
+        self.finish_times = [None; 2];
+        Ok(())
+    }
+
+    /// Returns the time since the race started.
+    pub fn elapsed(&self) -> Option<Duration> {
+        Some(self.start?.elapsed())
+    }
+    /// Returns the index of the player who won the current race.
+    pub fn winner(&self) -> Option<usize> {
+        match self.finish_times {
+            [Some(a), Some(b)] => Some(if b < a { 1 } else { 0 }),
+            [Some(_), None] => Some(0),
+            [None, Some(_)] => Some(1),
+            [None, None] => None,
+        }
+    }
+    /// Returns whether both players have finished the current race.
+    pub fn is_finished(&self) -> bool {
+        self.finish_times.iter().all(Option::is_some)
+    }
+
+    /// Records finish times for players who just solved their puzzle, and
+    /// keeps the second player's puzzle the same type as the first player's.
+    pub fn update(&mut self, player_1: &PuzzleController) {
+        if self.puzzle.ty() != player_1.ty() {
+            *self = Self::new(player_1.ty());
+            return;
+        }
+
+        let Some(elapsed) = self.elapsed() else {
+            return;
+        };
+        for (puzzle, finish_time) in [player_1, &self.puzzle]
+            .into_iter()
+            .zip(&mut self.finish_times)
+        {
+            if finish_time.is_none()
+                && puzzle.scramble_state() != ScrambleState::None
+                && puzzle.is_solved()
+            {
+                *finish_time = Some(elapsed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versus_race() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut player_1 = PuzzleController::new(ty);
+        let mut versus = Versus::new(ty);
+
+        versus
+            .start_race(&mut player_1, &ScrambleOptions::default())
+            .unwrap();
+        assert_eq!(player_1.scramble(), versus.puzzle.scramble());
+        versus.update(&player_1);
+        assert_eq!(versus.winner(), None);
+
+        // The second player solves first.
+        let solution: Vec<Twist> = (versus.puzzle.scramble().iter().rev())
+            .map(|&t| ty.reverse_twist(t))
+            .collect();
+        for &twist in &solution {
+            versus.puzzle.twist(twist).unwrap();
+        }
+        versus.update(&player_1);
+        assert_eq!(versus.winner(), Some(1));
+        assert!(!versus.is_finished());
+
+        for &twist in &solution {
+            player_1.twist(twist).unwrap();
+        }
+        versus.update(&player_1);
+        assert_eq!(versus.winner(), Some(1));
+        assert!(versus.is_finished());
+    }
+}
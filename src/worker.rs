@@ -0,0 +1,179 @@
+//! Worker thread for slow puzzle operations, such as building the description
+//! of a large puzzle or generating a scramble, so that they do not stall the
+//! UI.
+//!
+//! On web there are no threads, so tasks run as soon as they are submitted.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+
+use crate::preferences::ScrambleOptions;
+use crate::puzzle::{PrecomputedScramble, PuzzleTypeEnum};
+
+/// Task to run on the worker thread.
+#[derive(Debug, Clone)]
+pub(crate) enum Task {
+    /// Builds the description of a puzzle type before switching to it.
+    LoadPuzzle(PuzzleTypeEnum),
+    /// Generates a random scramble of some number of twists, or a full
+    /// scramble if the number is `None`, and applies it to a solved puzzle.
+    Scramble {
+        ty: PuzzleTypeEnum,
+        n: Option<usize>,
+        options: ScrambleOptions,
+    },
+}
+impl Task {
+    fn description(&self) -> String {
+        match self {
+            Task::LoadPuzzle(ty) => format!("Loading {ty}"),
+            Task::Scramble { ty, .. } => format!("Scrambling {ty}"),
+        }
+    }
+    /// Returns the number of steps in the task, if known.
+    fn total(&self) -> Option<usize> {
+        match self {
+            Task::LoadPuzzle(_) => None,
+            Task::Scramble { ty, n, .. } => Some(n.unwrap_or_else(|| ty.scramble_moves_count())),
+        }
+    }
+
+    fn run(self, progress: &AtomicUsize) -> TaskOutput {
+        match self {
+            Task::LoadPuzzle(ty) => {
+                ty.load_description();
+                TaskOutput::PuzzleLoaded(ty)
+            }
+            Task::Scramble { ty, n, options } => {
+                ty.load_description();
+                let scramble = PrecomputedScramble::generate(ty, n, &options, |i| {
+                    progress.store(i, Ordering::Relaxed);
+                });
+                TaskOutput::Scrambled { ty, n, scramble }
+            }
+        }
+    }
+}
+
+/// Result of a task.
+#[derive(Debug)]
+pub(crate) enum TaskOutput {
+    PuzzleLoaded(PuzzleTypeEnum),
+    Scrambled {
+        ty: PuzzleTypeEnum,
+        n: Option<usize>,
+        scramble: Result<PrecomputedScramble, &'static str>,
+    },
+}
+
+/// Progress of the task that is running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TaskProgress {
+    /// Description of the task.
+    pub description: String,
+    /// Number of steps completed so far.
+    pub done: usize,
+    /// Total number of steps, if known.
+    pub total: Option<usize>,
+}
+
+/// Handle to the worker thread.
+pub(crate) struct Worker {
+    /// Sender for tasks, or `None` if there is no worker thread.
+    tasks: Option<mpsc::Sender<Task>>,
+    outputs_tx: mpsc::Sender<TaskOutput>,
+    outputs: mpsc::Receiver<TaskOutput>,
+
+    /// Descriptions and step counts of the tasks that have been submitted and
+    /// not yet received, oldest first.
+    pending: VecDeque<(String, Option<usize>)>,
+    /// Number of steps completed in the task that is running.
+    progress: Arc<AtomicUsize>,
+}
+impl Default for Worker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Worker {
+    /// Starts the worker thread.
+    pub fn new() -> Self {
+        let (outputs_tx, outputs) = mpsc::channel();
+        let progress = Arc::new(AtomicUsize::new(0));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let tasks = {
+            let (tasks, tasks_rx) = mpsc::channel::<Task>();
+            let outputs_tx = outputs_tx.clone();
+            let progress = Arc::clone(&progress);
+            let spawn_result = std::thread::Builder::new()
+                .name("puzzle worker".to_string())
+                .spawn(move || {
+                    for task in tasks_rx {
+                        progress.store(0, Ordering::Relaxed);
+                        if outputs_tx.send(task.run(&progress)).is_err() {
+                            break; // The app has exited.
+                        }
+                    }
+                });
+            match spawn_result {
+                Ok(_) => Some(tasks),
+                Err(e) => {
+                    log::error!("Error starting worker thread: {}", e);
+                    None
+                }
+            }
+        };
+        #[cfg(target_arch = "wasm32")]
+        let tasks = None;
+
+        Self {
+            tasks,
+            outputs_tx,
+            outputs,
+
+            pending: VecDeque::new(),
+            progress,
+        }
+    }
+
+    /// Queues a task to run after all the tasks submitted before it.
+    pub fn submit(&mut self, task: Task) {
+        self.pending.push_back((task.description(), task.total()));
+
+        let task = match &self.tasks {
+            Some(tasks) => match tasks.send(task) {
+                Ok(()) => return,
+                Err(mpsc::SendError(task)) => {
+                    log::error!("Worker thread has stopped");
+                    self.tasks = None;
+                    task
+                }
+            },
+            None => task,
+        };
+        // There is no worker thread, so run the task now.
+        self.progress.store(0, Ordering::Relaxed);
+        let output = task.run(&self.progress);
+        let _ = self.outputs_tx.send(output);
+    }
+
+    /// Returns the output of the oldest finished task that has not been
+    /// received yet.
+    pub fn poll(&mut self) -> Option<TaskOutput> {
+        let output = self.outputs.try_recv().ok()?;
+        self.pending.pop_front();
+        Some(output)
+    }
+
+    /// Returns the progress of the task that is running, if there is one.
+    pub fn progress(&self) -> Option<TaskProgress> {
+        let (description, total) = self.pending.front()?;
+        Some(TaskProgress {
+            description: description.clone(),
+            done: self.progress.load(Ordering::Relaxed),
+            total: *total,
+        })
+    }
+}